@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use massa_fuzz::block_deserializer;
+use massa_serialization::{DeserializeError, Deserializer};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = block_deserializer().deserialize::<DeserializeError>(data);
+});
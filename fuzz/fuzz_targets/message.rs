@@ -0,0 +1,13 @@
+#![no_main]
+
+use std::sync::OnceLock;
+
+use libfuzzer_sys::fuzz_target;
+use massa_protocol_exports::ProtocolConfig;
+use massa_protocol_worker::fuzzing::deserialize_message;
+
+static CONFIG: OnceLock<ProtocolConfig> = OnceLock::new();
+
+fuzz_target!(|data: &[u8]| {
+    deserialize_message(CONFIG.get_or_init(ProtocolConfig::default), data);
+});
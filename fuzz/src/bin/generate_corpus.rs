@@ -0,0 +1,24 @@
+//! Write valid messages and blocks to `corpus/<target>/` so that fuzzing starts from valid inputs.
+//!
+//! Run from the `fuzz` directory with `cargo run --bin generate_corpus`.
+
+use std::{fs, path::Path};
+
+use massa_fuzz::block_corpus;
+use massa_protocol_exports::ProtocolConfig;
+use massa_protocol_worker::fuzzing::message_corpus;
+
+fn write_corpus(target: &str, inputs: Vec<Vec<u8>>) -> std::io::Result<()> {
+    let dir = Path::new("corpus").join(target);
+    fs::create_dir_all(&dir)?;
+    for (index, input) in inputs.iter().enumerate() {
+        fs::write(dir.join(format!("valid_{}", index)), input)?;
+    }
+    println!("{} inputs written to {}", inputs.len(), dir.display());
+    Ok(())
+}
+
+fn main() -> std::io::Result<()> {
+    write_corpus("message", message_corpus(&ProtocolConfig::default()))?;
+    write_corpus("block", block_corpus())
+}
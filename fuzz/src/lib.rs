@@ -0,0 +1,107 @@
+//! Helpers shared by the fuzz targets and the corpus generator
+
+use massa_hash::Hash;
+use massa_models::{
+    address::Address,
+    amount::Amount,
+    block::{Block, BlockDeserializer, BlockDeserializerArgs, BlockSerializer},
+    block_header::{BlockHeader, BlockHeaderSerializer},
+    block_id::BlockId,
+    config::{
+        ENDORSEMENT_COUNT, MAX_DENUNCIATIONS_PER_BLOCK_HEADER, MAX_OPERATIONS_PER_BLOCK,
+        THREAD_COUNT,
+    },
+    endorsement::{Endorsement, EndorsementSerializer},
+    operation::{Operation, OperationSerializer, OperationType},
+    secure_share::{SecureShareContent, SecureShareDeserializer, SecureShareSerializer},
+    slot::Slot,
+};
+use massa_serialization::Serializer;
+use massa_signature::KeyPair;
+
+/// Deserializer of the blocks, with the limits of the node
+pub fn block_deserializer() -> SecureShareDeserializer<Block, BlockDeserializer> {
+    SecureShareDeserializer::new(BlockDeserializer::new(BlockDeserializerArgs {
+        thread_count: THREAD_COUNT,
+        max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
+        endorsement_count: ENDORSEMENT_COUNT,
+        max_denunciations_per_block_header: MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
+        last_start_period: Some(0),
+    }))
+}
+
+/// Valid serialized blocks: a genesis block, an empty block and a block with endorsements and operations
+pub fn block_corpus() -> Vec<Vec<u8>> {
+    let keypair = KeyPair::generate(0).unwrap();
+    let parents: Vec<BlockId> = (0..THREAD_COUNT)
+        .map(|i| BlockId(Hash::compute_from(&[i])))
+        .collect();
+    let slot = Slot::new(1, 0);
+    let endorsements = (0..2)
+        .map(|index| {
+            Endorsement::new_verifiable(
+                Endorsement {
+                    slot,
+                    index,
+                    endorsed_block: parents[slot.thread as usize],
+                },
+                EndorsementSerializer::new(),
+                &keypair,
+            )
+            .unwrap()
+        })
+        .collect::<Vec<_>>();
+    let operation_ids = (0..3)
+        .map(|i| {
+            Operation::new_verifiable(
+                Operation {
+                    fee: Amount::from_raw(i),
+                    expire_period: 10,
+                    op: OperationType::Transaction {
+                        recipient_address: Address::from_public_key(&keypair.get_public_key()),
+                        amount: Amount::from_raw(1),
+                    },
+                },
+                OperationSerializer::new(),
+                &keypair,
+            )
+            .unwrap()
+            .id
+        })
+        .collect::<Vec<_>>();
+
+    [
+        (Slot::new(0, 0), Vec::new(), Vec::new(), Vec::new()),
+        (slot, parents.clone(), Vec::new(), Vec::new()),
+        (slot, parents, endorsements, operation_ids),
+    ]
+    .into_iter()
+    .map(|(slot, parents, endorsements, operations)| {
+        let header = BlockHeader::new_verifiable(
+            BlockHeader {
+                current_version: 0,
+                announced_version: None,
+                slot,
+                parents,
+                operation_merkle_root: Hash::compute_from(&[]),
+                endorsements,
+                denunciations: Vec::new(),
+            },
+            BlockHeaderSerializer::new(),
+            &keypair,
+        )
+        .unwrap();
+        let block = Block::new_verifiable(
+            Block { header, operations },
+            BlockSerializer::new(),
+            &keypair,
+        )
+        .unwrap();
+        let mut buffer = Vec::new();
+        SecureShareSerializer::new()
+            .serialize(&block, &mut buffer)
+            .unwrap();
+        buffer
+    })
+    .collect()
+}
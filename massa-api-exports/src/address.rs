@@ -5,12 +5,13 @@ use massa_models::endorsement::EndorsementId;
 use massa_models::operation::OperationId;
 use massa_models::slot::{IndexedSlot, Slot};
 use massa_models::{address::Address, amount::Amount, block_id::BlockId};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::slot::SlotAmount;
 
 /// All you ever dream to know about an address
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct AddressInfo {
     /// the address
     pub address: Address,
@@ -1,22 +1,31 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use massa_models::{address::Address, block::Block, block_id::BlockId, slot::Slot};
+use massa_models::{
+    address::Address,
+    block::{Block, InvalidBlockReason},
+    block_id::BlockId,
+    slot::Slot,
+};
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::display_if_true;
 
 /// refactor to delete
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct BlockInfo {
     /// block id
     pub id: BlockId,
     /// optional block info content
     pub content: Option<BlockInfoContent>,
+    /// reason why the block was discarded as invalid, if the graph still knows it as such
+    #[serde(default)]
+    pub invalid_reason: Option<InvalidBlockReason>,
 }
 
 /// Block content
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct BlockInfoContent {
     /// true if final
     pub is_final: bool,
@@ -42,7 +51,12 @@ impl std::fmt::Display for BlockInfo {
                 display_if_true(content.is_in_blockclique, " (blockclique)"),
                 display_if_true(content.is_discarded, " (discarded)"),
             )?;
+            if let Some(reason) = &self.invalid_reason {
+                writeln!(f, "Invalid: {}", reason)?;
+            }
             writeln!(f, "Block: {}", content.block)?;
+        } else if let Some(reason) = &self.invalid_reason {
+            writeln!(f, "Block {} invalid: {}", self.id, reason)?;
         } else {
             writeln!(f, "Block {} not found", self.id)?;
         }
@@ -51,7 +65,7 @@ impl std::fmt::Display for BlockInfo {
 }
 
 /// A block resume (without the block itself)
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct BlockSummary {
     /// id
     pub id: BlockId,
@@ -67,6 +81,9 @@ pub struct BlockSummary {
     pub creator: Address,
     /// the block parents
     pub parents: Vec<BlockId>,
+    /// reason why the block was discarded as invalid, if it was
+    #[serde(default)]
+    pub invalid_reason: Option<InvalidBlockReason>,
 }
 
 impl std::fmt::Display for BlockSummary {
@@ -79,6 +96,9 @@ impl std::fmt::Display for BlockSummary {
             display_if_true(self.is_stale, "stale"),
             display_if_true(self.is_in_blockclique, "in blockclique"),
         )?;
+        if let Some(reason) = &self.invalid_reason {
+            writeln!(f, "Invalid: {}", reason)?;
+        }
         writeln!(f, "Slot: {}", self.slot)?;
         writeln!(f, "Creator: {}", self.creator)?;
         writeln!(f, "Parents' IDs:")?;
@@ -88,3 +108,106 @@ impl std::fmt::Display for BlockSummary {
         Ok(())
     }
 }
+
+/// Position in a paginated export of the block graph.
+/// Blocks are ordered by slot, then by id, and the cursor points to the last block already returned.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize, JsonSchema,
+)]
+pub struct BlockGraphCursor {
+    /// slot of the last returned block
+    pub slot: Slot,
+    /// id of the last returned block
+    pub id: BlockId,
+}
+
+impl From<&BlockSummary> for BlockGraphCursor {
+    fn from(summary: &BlockSummary) -> Self {
+        BlockGraphCursor {
+            slot: summary.slot,
+            id: summary.id,
+        }
+    }
+}
+
+/// A page of the block graph
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct BlockGraphPage {
+    /// block summaries of the page, ordered by slot then id
+    pub blocks: Vec<BlockSummary>,
+    /// cursor to provide to get the next page, `None` if this is the last page
+    pub next_cursor: Option<BlockGraphCursor>,
+}
+
+impl BlockGraphPage {
+    /// Build a page of at most `limit` blocks located strictly after `cursor`
+    pub fn new(
+        mut blocks: Vec<BlockSummary>,
+        cursor: Option<BlockGraphCursor>,
+        limit: usize,
+    ) -> Self {
+        blocks.sort_unstable_by_key(BlockGraphCursor::from);
+        let start = match cursor {
+            Some(cursor) => blocks.partition_point(|b| BlockGraphCursor::from(b) <= cursor),
+            None => 0,
+        };
+        let mut blocks: Vec<BlockSummary> = blocks.into_iter().skip(start).collect();
+        let next_cursor = if blocks.len() > limit {
+            blocks.truncate(limit);
+            blocks.last().map(BlockGraphCursor::from)
+        } else {
+            None
+        };
+        BlockGraphPage {
+            blocks,
+            next_cursor,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_hash::Hash;
+    use massa_signature::KeyPair;
+
+    fn summary(period: u64, thread: u8, seed: &[u8]) -> BlockSummary {
+        BlockSummary {
+            id: BlockId(Hash::compute_from(seed)),
+            is_final: false,
+            is_stale: false,
+            is_in_blockclique: true,
+            slot: Slot::new(period, thread),
+            creator: Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key()),
+            parents: Vec::new(),
+            invalid_reason: None,
+        }
+    }
+
+    #[test]
+    fn test_block_graph_page_cursor() {
+        let blocks = vec![
+            summary(2, 0, b"c"),
+            summary(1, 1, b"b"),
+            summary(1, 0, b"a"),
+            summary(3, 0, b"d"),
+            summary(3, 1, b"e"),
+        ];
+
+        let first = BlockGraphPage::new(blocks.clone(), None, 2);
+        assert_eq!(first.blocks.len(), 2);
+        assert_eq!(first.blocks[0].slot, Slot::new(1, 0));
+        assert_eq!(first.blocks[1].slot, Slot::new(1, 1));
+        let cursor = first.next_cursor.expect("missing cursor");
+
+        let second = BlockGraphPage::new(blocks.clone(), Some(cursor), 2);
+        assert_eq!(second.blocks.len(), 2);
+        assert_eq!(second.blocks[0].slot, Slot::new(2, 0));
+        assert_eq!(second.blocks[1].slot, Slot::new(3, 0));
+
+        let last = BlockGraphPage::new(blocks, second.next_cursor, 2);
+        assert_eq!(last.blocks.len(), 1);
+        assert_eq!(last.blocks[0].slot, Slot::new(3, 1));
+        assert!(last.next_cursor.is_none());
+    }
+}
@@ -33,12 +33,22 @@ pub struct APIConfig {
     pub max_response_body_size: u32,
     /// maximum number of incoming connections allowed.
     pub max_connections: u32,
+    /// maximum number of blocks returned by a single page of the block graph.
+    pub max_block_graph_page_size: usize,
     /// maximum number of subscriptions per connection.
     pub max_subscriptions_per_connection: u32,
     /// max length for logging for requests and responses. Logs bigger than this limit will be truncated.
     pub max_log_length: u32,
     /// host filtering.
     pub allow_hosts: Vec<String>,
+    /// origins allowed by CORS. Empty means any origin.
+    pub cors_allowed_origins: Vec<String>,
+    /// maximum number of requests per second from a single client. 0 means unlimited.
+    pub max_requests_per_second: u64,
+    /// whether clients are identified by the last address of the `X-Forwarded-For` header, otherwise by the address of the peer.
+    pub trust_forwarded_for: bool,
+    /// maximum number of requests processed concurrently. 0 means unlimited.
+    pub max_concurrent_requests: usize,
     /// batch request limit. 0 means disabled.
     pub batch_request_limit: u32,
     /// the interval at which `Ping` frames are submitted.
@@ -67,8 +77,14 @@ pub struct APIConfig {
     pub t0: MassaTime,
     /// periods per cycle
     pub periods_per_cycle: u64,
+    /// number of periods during which an operation is valid
+    pub operation_validity_periods: u64,
     /// keypair file
     pub keypair: KeyPair,
     /// last_start_period value, used to know if we are during a restart or not
     pub last_start_period: u64,
+    /// memory budget of the node caches in bytes, if any
+    pub memory_budget_bytes: Option<u64>,
+    /// path of the event journal of the node
+    pub journal_path: PathBuf,
 }
@@ -1,10 +1,11 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use massa_models::address::Address;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Datastore entry query input structure
-#[derive(Debug, Deserialize, Clone, Serialize)]
+#[derive(Debug, Deserialize, Clone, Serialize, JsonSchema)]
 pub struct DatastoreEntryInput {
     /// associated address of the entry
     pub address: Address,
@@ -13,7 +14,7 @@ pub struct DatastoreEntryInput {
 }
 
 /// Datastore entry query output structure
-#[derive(Debug, Deserialize, Clone, Serialize)]
+#[derive(Debug, Deserialize, Clone, Serialize, JsonSchema)]
 pub struct DatastoreEntryOutput {
     /// final datastore entry value
     pub final_value: Option<Vec<u8>>,
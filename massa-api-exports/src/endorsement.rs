@@ -4,12 +4,13 @@ use massa_models::{
     block_id::BlockId,
     endorsement::{EndorsementId, SecureShareEndorsement},
 };
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::display_if_true;
 
 /// All you wanna know about an endorsement
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
 pub struct EndorsementInfo {
     /// id
     pub id: EndorsementId,
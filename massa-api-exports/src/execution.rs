@@ -2,11 +2,12 @@
 
 use massa_final_state::StateChanges;
 use massa_models::{address::Address, output_event::SCOutputEvent, slot::Slot};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::{collections::VecDeque, fmt::Display};
 
 /// The result of the read-only execution.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
 pub enum ReadOnlyResult {
     /// An error occurred during execution.
     Error(String),
@@ -15,7 +16,7 @@ pub enum ReadOnlyResult {
 }
 
 /// The response to a request for a read-only execution.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
 pub struct ExecuteReadOnlyResponse {
     /// The slot at which the read-only execution occurred.
     pub executed_at: Slot,
@@ -53,7 +54,7 @@ impl Display for ExecuteReadOnlyResponse {
 }
 
 /// read only bytecode execution request
-#[derive(Debug, Deserialize, Clone, Serialize)]
+#[derive(Debug, Deserialize, Clone, Serialize, JsonSchema)]
 pub struct ReadOnlyBytecodeExecution {
     /// max available gas
     pub max_gas: u64,
@@ -69,7 +70,7 @@ pub struct ReadOnlyBytecodeExecution {
 }
 
 /// read SC call request
-#[derive(Debug, Deserialize, Clone, Serialize)]
+#[derive(Debug, Deserialize, Clone, Serialize, JsonSchema)]
 pub struct ReadOnlyCall {
     /// max available gas
     pub max_gas: u64,
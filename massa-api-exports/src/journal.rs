@@ -0,0 +1,81 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use massa_time::MassaTime;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// significant event of the node (reorg, desync, ban, invalid block, bootstrap attempt),
+/// written as a JSON line in the event journal
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct JournalEvent {
+    /// time of the event
+    pub time: MassaTime,
+    /// kind of event, for example `reorg` or `peer_banned`
+    pub kind: String,
+    /// details of the event, depending on its kind
+    pub details: serde_json::Value,
+}
+
+impl std::fmt::Display for JournalEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} {}: {}",
+            self.time.format_instant(),
+            self.kind,
+            self.details
+        )
+    }
+}
+
+/// filter of the events of the journal
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+pub struct JournalFilter {
+    /// optional start time, included
+    pub start: Option<MassaTime>,
+    /// optional end time, excluded
+    pub end: Option<MassaTime>,
+    /// optional kind of event
+    pub kind: Option<String>,
+}
+
+impl JournalFilter {
+    /// true if `event` passes the filter
+    pub fn matches(&self, event: &JournalEvent) -> bool {
+        self.start.map_or(true, |start| event.time >= start)
+            && self.end.map_or(true, |end| event.time < end)
+            && self.kind.as_ref().map_or(true, |kind| &event.kind == kind)
+    }
+}
+
+/// File in which the journal at `path` is moved when it gets too large
+pub fn previous_journal_path(path: &Path) -> PathBuf {
+    let mut previous = path.as_os_str().to_owned();
+    previous.push(".1");
+    PathBuf::from(previous)
+}
+
+/// Read the events of the journal at `path` that pass `filter`, oldest first.
+/// Lines that can't be parsed, such as a line cut by a crash, are skipped.
+pub fn read_journal(path: &Path, filter: &JournalFilter) -> std::io::Result<Vec<JournalEvent>> {
+    let mut events = Vec::new();
+    for path in [previous_journal_path(path), path.to_path_buf()] {
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err),
+        };
+        for line in BufReader::new(file).lines() {
+            if let Ok(event) = serde_json::from_str::<JournalEvent>(&line?) {
+                if filter.matches(&event) {
+                    events.push(event);
+                }
+            }
+        }
+    }
+    Ok(events)
+}
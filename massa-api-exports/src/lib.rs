@@ -7,8 +7,11 @@
 #![feature(int_roundings)]
 #![feature(iter_intersperse)]
 
+use crate::block::BlockGraphCursor;
 use crate::page::PageRequest;
+use massa_models::slot::Slot;
 use massa_time::MassaTime;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// address related structures
@@ -25,6 +28,8 @@ pub mod endorsement;
 pub mod error;
 /// execution
 pub mod execution;
+/// event journal of the node
+pub mod journal;
 /// ledger structures
 pub mod ledger;
 /// node related structure
@@ -35,6 +40,8 @@ pub mod operation;
 pub mod page;
 /// rolls
 pub mod rolls;
+/// JSON schema of the API methods
+pub mod schema;
 /// slots
 pub mod slot;
 
@@ -68,7 +75,7 @@ fn display_option_bool(
 }
 
 /// Just a wrapper with a optional beginning and end
-#[derive(Debug, Deserialize, Clone, Copy, Serialize)]
+#[derive(Debug, Deserialize, Clone, Copy, Serialize, JsonSchema)]
 pub struct TimeInterval {
     /// optional start slot
     pub start: Option<MassaTime>,
@@ -76,6 +83,19 @@ pub struct TimeInterval {
     pub end: Option<MassaTime>,
 }
 
+/// Request for a page of the block graph between two slots
+#[derive(Debug, Deserialize, Clone, Copy, Serialize, JsonSchema)]
+pub struct GraphIntervalRequest {
+    /// optional start slot (included)
+    pub start_slot: Option<Slot>,
+    /// optional end slot (excluded)
+    pub end_slot: Option<Slot>,
+    /// optional cursor returned by the previous page
+    pub cursor: Option<BlockGraphCursor>,
+    /// optional maximum number of blocks to return, capped by the node configuration
+    pub limit: Option<usize>,
+}
+
 /// SCRUD operations
 #[derive(strum::Display)]
 #[strum(serialize_all = "snake_case")]
@@ -103,7 +123,7 @@ pub enum ListType {
 }
 
 /// Wrap request params into struct for ApiV2 method
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, JsonSchema)]
 pub struct ApiRequest {
     /// pagination
     pub page_request: Option<PageRequest>,
@@ -4,12 +4,13 @@ use massa_models::node::NodeId;
 use massa_models::stats::{ConsensusStats, ExecutionStats, NetworkStats};
 use massa_models::{config::CompactConfig, slot::Slot, version::Version};
 use massa_time::MassaTime;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
-use std::net::IpAddr;
+use std::net::{IpAddr, SocketAddr};
 
 /// node status
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct NodeStatus {
     /// our node id
     pub node_id: NodeId,
@@ -89,3 +90,66 @@ impl std::fmt::Display for NodeStatus {
         Ok(())
     }
 }
+
+/// peer known by a node, as exported to seed the peer database of another node
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct KnownPeer {
+    /// node id of the peer
+    pub node_id: NodeId,
+    /// addresses the peer listens on
+    pub listeners: Vec<SocketAddr>,
+    /// timestamp of the last announcement received from the peer, in milliseconds
+    pub last_announce: u64,
+    /// true if the node could connect to the peer
+    pub trusted: bool,
+}
+
+/// summary of the node state for its operator
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct NodeStatusSummary {
+    /// our node id
+    pub node_id: NodeId,
+    /// node version
+    pub version: Version,
+    /// time since the node started
+    pub uptime: MassaTime,
+    /// number of connected peers
+    pub connected_peer_count: usize,
+    /// latest slot, none if now is before genesis timestamp
+    pub current_slot: Option<Slot>,
+    /// latest final slot executed by the node
+    pub final_slot: Slot,
+    /// number of slots between the latest final slot and the current slot
+    pub sync_lag: u64,
+    /// number of operations in the pool
+    pub pool_operation_count: usize,
+    /// number of endorsements in the pool
+    pub pool_endorsement_count: usize,
+    /// number of addresses the node stakes with
+    pub staking_key_count: usize,
+}
+
+impl std::fmt::Display for NodeStatusSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Node's ID: {}", self.node_id)?;
+        writeln!(f, "Version: {}", self.version)?;
+        match self.uptime.format_duration() {
+            Ok(uptime) => writeln!(f, "Uptime: {}", uptime)?,
+            Err(_) => writeln!(f, "Uptime: {} ms", self.uptime)?,
+        }
+        writeln!(f, "Connected peers: {}", self.connected_peer_count)?;
+        if let Some(current_slot) = self.current_slot {
+            writeln!(f, "Current slot: {}", current_slot)?;
+        }
+        writeln!(f, "Final slot: {}", self.final_slot)?;
+        writeln!(f, "Sync lag: {} slots", self.sync_lag)?;
+        writeln!(f, "Pool operations count: {}", self.pool_operation_count)?;
+        writeln!(
+            f,
+            "Pool endorsements count: {}",
+            self.pool_endorsement_count
+        )?;
+        writeln!(f, "Staking keys count: {}", self.staking_key_count)?;
+        Ok(())
+    }
+}
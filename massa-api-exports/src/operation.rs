@@ -1,17 +1,20 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use massa_models::{
+    address::Address,
+    amount::Amount,
     block_id::BlockId,
     operation::{OperationId, SecureShareOperation},
 };
 
 use massa_signature::{PublicKey, Signature};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::{display_if_true, display_option_bool};
 
 /// operation input
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
 pub struct OperationInput {
     /// The public key of the creator of the TX
     pub creator_public_key: PublicKey,
@@ -21,8 +24,30 @@ pub struct OperationInput {
     pub serialized_content: Vec<u8>,
 }
 
+/// transaction built and signed by the node with a key of its wallet
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
+pub struct TransactionInput {
+    /// sender address, its key must be in the node wallet
+    pub from: Address,
+    /// recipient address
+    pub to: Address,
+    /// amount of coins to transfer
+    pub amount: Amount,
+    /// fee paid to the block producer
+    pub fee: Amount,
+}
+
+/// Request for the pending operations of an address
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, JsonSchema)]
+pub struct PoolOperationsRequest {
+    /// address sending the operations, or receiving their coins or smart contract calls
+    pub address: Address,
+    /// optional maximum number of operations to return, capped by the node configuration
+    pub limit: Option<usize>,
+}
+
 /// Operation and contextual info about it
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct OperationInfo {
     /// id
     pub id: OperationId,
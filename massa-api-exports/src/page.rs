@@ -1,6 +1,9 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use paginate::Pages;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize, Serializer};
 
 /// Represents a Vec that can be split across Pages
@@ -43,8 +46,18 @@ impl<T: Serialize> Serialize for PagedVec<T> {
     }
 }
 
+impl<T: JsonSchema> JsonSchema for PagedVec<T> {
+    fn schema_name() -> String {
+        Vec::<T>::schema_name()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        Vec::<T>::json_schema(gen)
+    }
+}
+
 /// Represents the request inputs for a PagedVec
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, JsonSchema)]
 pub struct PageRequest {
     /// The limit of elements in a page
     pub limit: usize,
@@ -53,7 +66,7 @@ pub struct PageRequest {
 }
 
 /// Represents the request inputs for a PagedVecV2
-#[derive(Clone, Deserialize, Serialize)]
+#[derive(Clone, Deserialize, Serialize, JsonSchema)]
 pub struct PagedVecV2<T> {
     content: Vec<T>,
     total_count: usize,
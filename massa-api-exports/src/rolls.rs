@@ -1,9 +1,10 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Roll counts
-#[derive(Debug, Deserialize, Serialize, Clone, Copy)]
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, JsonSchema)]
 pub struct RollsInfo {
     /// count taken into account for the current cycle
     pub active_rolls: u64,
@@ -0,0 +1,175 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use crate::{
+    address::AddressInfo,
+    block::{BlockGraphPage, BlockInfo, BlockSummary},
+    datastore::{DatastoreEntryInput, DatastoreEntryOutput},
+    endorsement::EndorsementInfo,
+    execution::{ExecuteReadOnlyResponse, ReadOnlyBytecodeExecution, ReadOnlyCall},
+    node::NodeStatus,
+    operation::{OperationInfo, OperationInput, PoolOperationsRequest},
+    page::{PageRequest, PagedVec},
+    GraphIntervalRequest, TimeInterval,
+};
+use massa_models::{
+    address::Address, block::Block, block_id::BlockId, clique::Clique, endorsement::EndorsementId,
+    execution::EventFilter, operation::OperationId, output_event::SCOutputEvent, slot::Slot,
+};
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::schema::Schema;
+use schemars::{JsonSchema, Map};
+use serde::Serialize;
+
+/// Schema of an API method: the schema of each positional parameter and of the result
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct MethodSchema {
+    /// method name
+    pub name: String,
+    /// schemas of the parameters, in order
+    pub params: Vec<Schema>,
+    /// schema of the result
+    pub result: Schema,
+}
+
+/// Machine-readable description of the API, generated from the Rust types
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ApiSchema {
+    /// JSON schema draft used for the definitions
+    #[serde(rename = "$schema")]
+    pub meta_schema: Option<String>,
+    /// methods of the API
+    pub methods: Vec<MethodSchema>,
+    /// definitions of the models referenced by the methods
+    pub definitions: Map<String, Schema>,
+}
+
+/// Builds the schema of a method from the types of its parameters and result
+macro_rules! method {
+    ($gen:ident, $name:literal, [$($param:ty),*], $result:ty) => {
+        MethodSchema {
+            name: $name.to_string(),
+            params: vec![$($gen.subschema_for::<$param>()),*],
+            result: $gen.subschema_for::<$result>(),
+        }
+    };
+}
+
+/// Generates the schema of the public API methods
+pub fn public_api_schema() -> ApiSchema {
+    let settings = SchemaSettings::draft07();
+    let meta_schema = settings.meta_schema.clone();
+    let mut gen = settings.into_generator();
+
+    let methods = vec![
+        method!(
+            gen,
+            "execute_read_only_bytecode",
+            [Vec<ReadOnlyBytecodeExecution>],
+            Vec<ExecuteReadOnlyResponse>
+        ),
+        method!(
+            gen,
+            "execute_read_only_call",
+            [Vec<ReadOnlyCall>],
+            Vec<ExecuteReadOnlyResponse>
+        ),
+        method!(gen, "get_status", [], NodeStatus),
+        method!(gen, "get_cliques", [], Vec<Clique>),
+        method!(
+            gen,
+            "get_stakers",
+            [Option<PageRequest>],
+            PagedVec<(Address, u64)>
+        ),
+        method!(
+            gen,
+            "get_operations",
+            [Vec<OperationId>],
+            Vec<OperationInfo>
+        ),
+        method!(
+            gen,
+            "get_pool_operations",
+            [PoolOperationsRequest],
+            Vec<OperationInfo>
+        ),
+        method!(
+            gen,
+            "get_endorsements",
+            [Vec<EndorsementId>],
+            Vec<EndorsementInfo>
+        ),
+        method!(gen, "get_blocks", [Vec<BlockId>], Vec<BlockInfo>),
+        method!(gen, "get_blockclique_block_by_slot", [Slot], Option<Block>),
+        method!(gen, "get_graph_interval", [TimeInterval], Vec<BlockSummary>),
+        method!(
+            gen,
+            "get_graph_interval_paged",
+            [GraphIntervalRequest],
+            BlockGraphPage
+        ),
+        method!(
+            gen,
+            "get_datastore_entries",
+            [Vec<DatastoreEntryInput>],
+            Vec<DatastoreEntryOutput>
+        ),
+        method!(gen, "get_addresses", [Vec<Address>], Vec<AddressInfo>),
+        method!(
+            gen,
+            "send_operations",
+            [Vec<OperationInput>],
+            Vec<OperationId>
+        ),
+        method!(
+            gen,
+            "get_filtered_sc_output_event",
+            [EventFilter],
+            Vec<SCOutputEvent>
+        ),
+        method!(gen, "get_api_schema", [], ApiSchema),
+    ];
+
+    ApiSchema {
+        meta_schema,
+        methods,
+        definitions: gen.take_definitions(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_public_api_schema() {
+        let schema = public_api_schema();
+
+        let names: HashSet<&str> = schema.methods.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names.len(), schema.methods.len(), "duplicate method name");
+        for definition in [
+            "AddressInfo",
+            "BlockSummary",
+            "NodeStatus",
+            "Slot",
+            "Address",
+            "Block",
+            "BlockHeader",
+            "OperationType",
+            "Denunciation",
+            "StateChanges",
+            "LedgerEntry",
+            "ApiSchema",
+        ] {
+            assert!(
+                schema.definitions.contains_key(definition),
+                "missing definition {}",
+                definition
+            );
+        }
+
+        let json = serde_json::to_value(&schema).unwrap();
+        assert!(json["methods"].as_array().unwrap().len() > 10);
+    }
+}
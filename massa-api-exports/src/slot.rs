@@ -2,10 +2,11 @@
 
 use massa_models::{amount::Amount, slot::Slot};
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// slot / amount pair
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct SlotAmount {
     /// slot
     pub slot: Slot,
@@ -14,9 +14,10 @@ use massa_api_exports::error::ApiError;
 use massa_api_exports::page::{PageRequest, PagedVec, PagedVecV2};
 use massa_api_exports::ApiRequest;
 use massa_consensus_exports::{ConsensusChannels, ConsensusController};
-use massa_execution_exports::ExecutionController;
+use massa_execution_exports::{ExecutionChannels, ExecutionController, SlotExecutionOutput};
 use massa_models::address::Address;
 use massa_models::block_id::BlockId;
+use massa_models::execution::EventFilter;
 use massa_models::slot::Slot;
 use massa_models::timeslots::get_latest_block_slot_at_timestamp;
 use massa_models::version::Version;
@@ -31,6 +32,7 @@ impl API<ApiV2> {
         consensus_controller: Box<dyn ConsensusController>,
         consensus_channels: ConsensusChannels,
         execution_controller: Box<dyn ExecutionController>,
+        execution_channels: ExecutionChannels,
         pool_channels: PoolChannels,
         api_settings: APIConfig,
         version: Version,
@@ -39,6 +41,7 @@ impl API<ApiV2> {
             consensus_controller,
             consensus_channels,
             execution_controller,
+            execution_channels,
             pool_channels,
             api_settings,
             version,
@@ -53,7 +56,8 @@ impl ApiServer for API<ApiV2> {
         url: &SocketAddr,
         api_config: &APIConfig,
     ) -> Result<StopHandle, JsonRpseeError> {
-        crate::serve(self.into_rpc(), url, api_config).await
+        let consensus_controller = self.0.consensus_controller.clone();
+        crate::serve(self.into_rpc(), url, api_config, consensus_controller).await
     }
 }
 
@@ -151,6 +155,56 @@ impl MassaApiServer for API<ApiV2> {
     ) -> SubscriptionResult {
         broadcast_via_ws(self.0.pool_channels.operation_sender.clone(), pending).await
     }
+
+    async fn subscribe_new_events(
+        &self,
+        pending: PendingSubscriptionSink,
+        filter: EventFilter,
+    ) -> SubscriptionResult {
+        let sink = pending.accept().await?;
+        let closed = sink.closed();
+        let stream = BroadcastStream::new(
+            self.0
+                .execution_channels
+                .slot_execution_output_sender
+                .subscribe(),
+        );
+        futures::pin_mut!(closed, stream);
+
+        loop {
+            match future::select(closed, stream.next()).await {
+                // subscription closed.
+                Either::Left((_, _)) => break Ok(()),
+
+                // received the output of a slot: send its events matching the filter.
+                Either::Right((Some(Ok(output)), c)) => {
+                    let events = match output {
+                        SlotExecutionOutput::ExecutedSlot(output) => output.events,
+                        SlotExecutionOutput::FinalizedSlot(output) => {
+                            // the output is broadcast before its events are marked final
+                            let mut events = output.events;
+                            events.finalize();
+                            events
+                        }
+                    };
+                    for event in events.get_filtered_sc_output_events(&filter) {
+                        let notif = SubscriptionMessage::from_json(&event)?;
+                        if sink.send(notif).await.is_err() {
+                            return Ok(());
+                        }
+                    }
+
+                    closed = c;
+                }
+
+                // Send back back the error.
+                Either::Right((Some(Err(e)), _)) => break Err(e.into()),
+
+                // Stream is closed.
+                Either::Right((None, _)) => break Ok(()),
+            }
+        }
+    }
 }
 
 // Brodcast the stream(sender) content via a WebSocket
@@ -4,32 +4,38 @@
 #![warn(missing_docs)]
 #![warn(unused_crate_dependencies)]
 use api_trait::MassaApiServer;
-use hyper::Method;
+use hyper::{
+    header::{HeaderName, HeaderValue},
+    Method,
+};
 use jsonrpsee::core::{Error as JsonRpseeError, RpcResult};
 use jsonrpsee::proc_macros::rpc;
 use jsonrpsee::server::{AllowHosts, BatchRequestConfig, ServerBuilder, ServerHandle};
 use jsonrpsee::RpcModule;
 use massa_api_exports::{
     address::AddressInfo,
-    block::{BlockInfo, BlockSummary},
+    block::{BlockGraphPage, BlockInfo, BlockSummary},
     config::APIConfig,
     datastore::{DatastoreEntryInput, DatastoreEntryOutput},
     endorsement::EndorsementInfo,
     error::ApiError::WrongAPI,
     execution::{ExecuteReadOnlyResponse, ReadOnlyBytecodeExecution, ReadOnlyCall},
-    node::NodeStatus,
-    operation::{OperationInfo, OperationInput},
+    journal::{JournalEvent, JournalFilter},
+    node::{KnownPeer, NodeStatus, NodeStatusSummary},
+    operation::{OperationInfo, OperationInput, PoolOperationsRequest, TransactionInput},
     page::{PageRequest, PagedVec},
-    TimeInterval,
+    schema::ApiSchema,
+    GraphIntervalRequest, TimeInterval,
 };
 use massa_consensus_exports::{ConsensusChannels, ConsensusController};
-use massa_execution_exports::ExecutionController;
+use massa_execution_exports::{ExecutionChannels, ExecutionController};
 use massa_models::clique::Clique;
 use massa_models::composite::PubkeySig;
 use massa_models::node::NodeId;
 use massa_models::operation::OperationId;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashSet;
+use massa_models::stats::{MemoryStats, PeerStats};
 use massa_models::{
     address::Address, block::Block, block_id::BlockId, endorsement::EndorsementId,
     execution::EventFilter, slot::Slot, version::Version,
@@ -38,19 +44,26 @@ use massa_pool_exports::{PoolChannels, PoolController};
 use massa_pos_exports::SelectorController;
 use massa_protocol_exports::{ProtocolConfig, ProtocolController};
 use massa_storage::Storage;
+use massa_time::MassaTime;
 use massa_versioning::keypair_factory::KeyPairFactory;
 use massa_wallet::Wallet;
 use parking_lot::RwLock;
+use rate_limit::{PeerAddrLogger, RateLimitLayer, RateLimiter};
 use serde_json::Value;
+use stale::{StaleFlagLayer, POTENTIALLY_STALE_HEADER};
 use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::AtomicBool;
 use std::sync::{Arc, Condvar, Mutex};
-use tower_http::cors::{Any, CorsLayer};
+use tower::limit::GlobalConcurrencyLimitLayer;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
 use tracing::{info, warn};
 
 mod api;
 mod api_trait;
 mod private;
 mod public;
+mod rate_limit;
+mod stale;
 
 /// Public API component
 pub struct Public {
@@ -78,19 +91,37 @@ pub struct Public {
     pub keypair_factory: KeyPairFactory,
 }
 
+/// Sets the log levels per module of the node from a comma-separated list of `module=level`
+/// directives. Returns an error message if the directives are invalid.
+pub type LogFilterSetter = Arc<dyn Fn(&str) -> Result<(), String> + Send + Sync>;
+
 /// Private API content
 pub struct Private {
+    /// link to the consensus component
+    pub consensus_controller: Box<dyn ConsensusController>,
     /// link to the protocol component
     pub protocol_controller: Box<dyn ProtocolController>,
     /// link to the execution component
     pub execution_controller: Box<dyn ExecutionController>,
+    /// link to the pool component
+    pub pool_controller: Box<dyn PoolController>,
+    /// Massa storage
+    pub storage: Storage,
     /// API settings
     pub api_settings: APIConfig,
     /// Mechanism by which to gracefully shut down.
     /// To be a clone of the same pair provided to the ctrlc handler.
     pub stop_cv: Arc<(Mutex<bool>, Condvar)>,
+    /// Set to request a reload of the configuration to the node
+    pub reload_requested: Arc<AtomicBool>,
+    /// Sets the log levels per module of the node
+    pub log_filter: LogFilterSetter,
     /// User wallet
     pub node_wallet: Arc<RwLock<Wallet>>,
+    /// node version
+    pub version: Version,
+    /// time at which the node started
+    pub start_time: MassaTime,
 }
 
 /// API v2 content
@@ -101,6 +132,8 @@ pub struct ApiV2 {
     pub consensus_channels: ConsensusChannels,
     /// link to the execution component
     pub execution_controller: Box<dyn ExecutionController>,
+    /// link(channels) to the execution component
+    pub execution_channels: ExecutionChannels,
     /// link(channels) to the pool component
     pub pool_channels: PoolChannels,
     /// API settings
@@ -138,6 +171,7 @@ async fn serve<T>(
     api: RpcModule<T>,
     url: &SocketAddr,
     api_config: &APIConfig,
+    consensus_controller: Box<dyn ConsensusController>,
 ) -> Result<StopHandle, JsonRpseeError> {
     let allowed_hosts = if api_config.allow_hosts.is_empty() {
         AllowHosts::Any
@@ -161,6 +195,7 @@ async fn serve<T>(
             BatchRequestConfig::Disabled
         })
         .ping_interval(api_config.ping_interval.to_duration())
+        .set_logger(PeerAddrLogger)
         .custom_tokio_runtime(tokio::runtime::Handle::current());
 
     if api_config.enable_http && !api_config.enable_ws {
@@ -171,20 +206,48 @@ async fn serve<T>(
         panic!("wrong server configuration, you can't disable both http and ws");
     }
 
+    let allowed_origins = if api_config.cors_allowed_origins.is_empty() {
+        AllowOrigin::from(Any)
+    } else {
+        let origins = api_config
+            .cors_allowed_origins
+            .iter()
+            .map(|origin| {
+                HeaderValue::from_str(origin).unwrap_or_else(|_| {
+                    panic!(
+                        "wrong server configuration, invalid CORS origin: {}",
+                        origin
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+        AllowOrigin::list(origins)
+    };
+
     let cors = CorsLayer::new()
         // Allow `POST` and `OPTIONS` when accessing the resource
         .allow_methods([Method::POST, Method::OPTIONS])
-        // Allow requests from any origin
-        .allow_origin(Any)
-        .allow_headers([hyper::header::CONTENT_TYPE]);
-
-    let middleware = tower::ServiceBuilder::new().layer(cors);
-
-    let server = server_builder
-        .set_middleware(middleware)
-        .build(url)
-        .await
-        .expect("failed to build server");
+        // Allow requests from the configured origins, any origin if none is configured
+        .allow_origin(allowed_origins)
+        .allow_headers([hyper::header::CONTENT_TYPE])
+        .expose_headers([HeaderName::from_static(POTENTIALLY_STALE_HEADER)]);
+
+    let rate_limit = (api_config.max_requests_per_second > 0).then(|| {
+        RateLimitLayer::new(RateLimiter::new(
+            api_config.max_requests_per_second,
+            api_config.trust_forwarded_for,
+        ))
+    });
+    let concurrency_limit = (api_config.max_concurrent_requests > 0)
+        .then(|| GlobalConcurrencyLimitLayer::new(api_config.max_concurrent_requests));
+
+    let middleware = tower::ServiceBuilder::new()
+        .layer(cors)
+        .layer(StaleFlagLayer::new(consensus_controller))
+        .option_layer(rate_limit)
+        .option_layer(concurrency_limit);
+
+    let server = server_builder.set_middleware(middleware).build(url).await?;
 
     let server_handler = server.start(api).expect("server start failed");
     let stop_handler = StopHandle { server_handler };
@@ -217,14 +280,25 @@ pub trait MassaRpc {
     #[method(name = "stop_node")]
     fn stop_node(&self) -> RpcResult<()>;
 
+    /// Reload the part of the configuration that can change while the node is running:
+    /// log level, initial peers and addresses of the API servers.
+    #[method(name = "node_reload_config")]
+    fn node_reload_config(&self) -> RpcResult<()>;
+
+    /// Set the log levels per module, from a comma-separated list of `module=level` directives.
+    /// An empty list removes the levels per module.
+    #[method(name = "node_set_log_filter")]
+    fn node_set_log_filter(&self, arg: String) -> RpcResult<()>;
+
     /// Sign message with node's key.
     /// Returns the public key that signed the message and the signature.
     #[method(name = "node_sign_message")]
     async fn node_sign_message(&self, arg: Vec<u8>) -> RpcResult<PubkeySig>;
 
     /// Add a vector of new secret(private) keys for the node to use to stake.
+    /// The keys are stored in the encrypted node wallet and used by the factory without restart.
     /// No confirmation to expect.
-    #[method(name = "add_staking_secret_keys")]
+    #[method(name = "add_staking_secret_keys", aliases = ["register_staking_keys"])]
     async fn add_staking_secret_keys(&self, arg: Vec<String>) -> RpcResult<()>;
 
     /// Execute bytecode in read-only mode.
@@ -243,11 +317,11 @@ pub trait MassaRpc {
 
     /// Remove a vector of addresses used to stake.
     /// No confirmation to expect.
-    #[method(name = "remove_staking_addresses")]
+    #[method(name = "remove_staking_addresses", aliases = ["remove_staking_keys"])]
     async fn remove_staking_addresses(&self, arg: Vec<Address>) -> RpcResult<()>;
 
     /// Return hash set of staking addresses.
-    #[method(name = "get_staking_addresses")]
+    #[method(name = "get_staking_addresses", aliases = ["list_staking_keys"])]
     async fn get_staking_addresses(&self) -> RpcResult<PreHashSet<Address>>;
 
     /// Bans given IP address(es).
@@ -315,6 +389,35 @@ pub trait MassaRpc {
     #[method(name = "node_unban_by_id")]
     async fn node_unban_by_id(&self, arg: Vec<NodeId>) -> RpcResult<()>;
 
+    /// Estimated memory used by the bounded caches of the node (blocks waiting for their slot or dependencies,
+    /// discarded blocks, protocol deduplication caches, pool), with their limits and the memory budget.
+    #[method(name = "get_memory_stats")]
+    async fn get_memory_stats(&self) -> RpcResult<MemoryStats>;
+
+    /// Summary of the node for its operator: version, node id, uptime, connected peers count,
+    /// current slot and latest final slot, pool size and staking keys count.
+    #[method(name = "get_node_status")]
+    async fn get_node_status(&self) -> RpcResult<NodeStatusSummary>;
+
+    /// Bandwidth and message counters of each connected peer: bytes exchanged, messages received by type,
+    /// invalid messages received, messages sent and time of the last exchanges.
+    #[method(name = "get_peers_detail")]
+    async fn get_peers_detail(&self) -> RpcResult<Vec<PeerStats>>;
+
+    /// Peers of the peer database of the node that are not banned, to seed the peer database of another node.
+    #[method(name = "node_get_known_peers")]
+    async fn node_get_known_peers(&self) -> RpcResult<Vec<KnownPeer>>;
+
+    /// Test the given peers, exported from another node, to add them to the peer database.
+    /// No confirmation to expect.
+    #[method(name = "node_add_known_peers")]
+    async fn node_add_known_peers(&self, arg: Vec<KnownPeer>) -> RpcResult<()>;
+
+    /// Significant events recorded in the event journal of the node (reorgs, desyncs, bans, invalid blocks,
+    /// bootstrap attempts), oldest first.
+    #[method(name = "get_journal_events")]
+    async fn get_journal_events(&self, arg: JournalFilter) -> RpcResult<Vec<JournalEvent>>;
+
     /// Summary of the current state: time, last final blocks (hash, thread, slot, timestamp), clique count, connected nodes count.
     #[method(name = "get_status")]
     async fn get_status(&self) -> RpcResult<NodeStatus>;
@@ -334,6 +437,14 @@ pub trait MassaRpc {
     #[method(name = "get_operations")]
     async fn get_operations(&self, arg: Vec<OperationId>) -> RpcResult<Vec<OperationInfo>>;
 
+    /// Returns the operations waiting in the pool that are sent by an address, or that transfer coins
+    /// or call a smart contract at this address, from the most to the least likely to be included.
+    #[method(name = "get_pool_operations")]
+    async fn get_pool_operations(
+        &self,
+        arg: PoolOperationsRequest,
+    ) -> RpcResult<Vec<OperationInfo>>;
+
     /// Returns endorsement(s) information associated to a given list of endorsement(s) ID(s)
     #[method(name = "get_endorsements")]
     async fn get_endorsements(&self, arg: Vec<EndorsementId>) -> RpcResult<Vec<EndorsementInfo>>;
@@ -352,6 +463,15 @@ pub trait MassaRpc {
     #[method(name = "get_graph_interval")]
     async fn get_graph_interval(&self, arg: TimeInterval) -> RpcResult<Vec<BlockSummary>>;
 
+    /// Get a page of the block graph within the specified slot interval.
+    /// Optional parameters: from `<start_slot>` (included) and to `<end_slot>` (excluded),
+    /// `<cursor>` returned by the previous page and `<limit>` capped by the node configuration.
+    #[method(name = "get_graph_interval_paged")]
+    async fn get_graph_interval_paged(
+        &self,
+        arg: GraphIntervalRequest,
+    ) -> RpcResult<BlockGraphPage>;
+
     /// Get multiple datastore entries.
     #[method(name = "get_datastore_entries")]
     async fn get_datastore_entries(
@@ -367,6 +487,11 @@ pub trait MassaRpc {
     #[method(name = "send_operations")]
     async fn send_operations(&self, arg: Vec<OperationInput>) -> RpcResult<Vec<OperationId>>;
 
+    /// Builds a transaction signed with a key of the node wallet, adds it to pool and propagates it.
+    /// Returns the id of the operation.
+    #[method(name = "send_transaction")]
+    async fn send_transaction(&self, arg: TransactionInput) -> RpcResult<OperationId>;
+
     /// Get events optionally filtered by:
     /// * start slot
     /// * end slot
@@ -380,6 +505,10 @@ pub trait MassaRpc {
     /// Get OpenRPC specification.
     #[method(name = "rpc.discover")]
     async fn get_openrpc_spec(&self) -> RpcResult<Value>;
+
+    /// Get the JSON schema of the public API methods and models, generated from the node types.
+    #[method(name = "get_api_schema")]
+    async fn get_api_schema(&self) -> RpcResult<ApiSchema>;
 }
 
 fn wrong_api<T>() -> RpcResult<T> {
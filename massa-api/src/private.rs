@@ -1,36 +1,55 @@
 //! Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use crate::{MassaRpcServer, Private, RpcServer, StopHandle, Value, API};
+use crate::{LogFilterSetter, MassaRpcServer, Private, RpcServer, StopHandle, Value, API};
 
 use async_trait::async_trait;
 use jsonrpsee::core::{Error as JsonRpseeError, RpcResult};
 use massa_api_exports::{
     address::AddressInfo,
-    block::{BlockInfo, BlockSummary},
+    block::{BlockGraphPage, BlockInfo, BlockSummary},
     config::APIConfig,
     datastore::{DatastoreEntryInput, DatastoreEntryOutput},
     endorsement::EndorsementInfo,
     error::ApiError,
     execution::{ExecuteReadOnlyResponse, ReadOnlyBytecodeExecution, ReadOnlyCall},
-    node::NodeStatus,
-    operation::{OperationInfo, OperationInput},
+    journal::{read_journal, JournalEvent, JournalFilter},
+    node::{KnownPeer, NodeStatus, NodeStatusSummary},
+    operation::{OperationInfo, OperationInput, PoolOperationsRequest, TransactionInput},
     page::{PageRequest, PagedVec},
-    ListType, ScrudOperation, TimeInterval,
+    schema::ApiSchema,
+    GraphIntervalRequest, ListType, ScrudOperation, TimeInterval,
 };
+use massa_consensus_exports::ConsensusController;
 use massa_execution_exports::ExecutionController;
 use massa_hash::Hash;
 use massa_models::{
-    address::Address, block::Block, block_id::BlockId, clique::Clique, composite::PubkeySig,
-    endorsement::EndorsementId, execution::EventFilter, node::NodeId, operation::OperationId,
-    output_event::SCOutputEvent, prehash::PreHashSet, slot::Slot,
+    address::Address,
+    block::Block,
+    block_id::BlockId,
+    clique::Clique,
+    composite::PubkeySig,
+    endorsement::EndorsementId,
+    execution::EventFilter,
+    node::NodeId,
+    operation::{Operation, OperationId, OperationType},
+    output_event::SCOutputEvent,
+    prehash::PreHashSet,
+    slot::Slot,
+    stats::{MemoryStats, PeerStats},
+    timeslots::get_latest_block_slot_at_timestamp,
+    version::Version,
 };
-use massa_protocol_exports::{PeerId, ProtocolController};
+use massa_pool_exports::PoolController;
+use massa_protocol_exports::{PeerId, ProtocolController, TransportType};
 use massa_signature::KeyPair;
+use massa_storage::Storage;
+use massa_time::MassaTime;
 use massa_wallet::Wallet;
 use parking_lot::RwLock;
 use std::net::{IpAddr, SocketAddr};
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::{collections::BTreeSet, sync::Mutex};
 use std::{
@@ -40,19 +59,34 @@ use std::{
 
 impl API<Private> {
     /// generate a new private API
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
+        consensus_controller: Box<dyn ConsensusController>,
         protocol_controller: Box<dyn ProtocolController>,
         execution_controller: Box<dyn ExecutionController>,
+        pool_controller: Box<dyn PoolController>,
+        storage: Storage,
         api_settings: APIConfig,
         stop_cv: Arc<(Mutex<bool>, Condvar)>,
+        reload_requested: Arc<AtomicBool>,
+        log_filter: LogFilterSetter,
         node_wallet: Arc<RwLock<Wallet>>,
+        version: Version,
+        start_time: MassaTime,
     ) -> Self {
         API(Private {
+            consensus_controller,
             protocol_controller,
             execution_controller,
+            pool_controller,
+            storage,
             api_settings,
             stop_cv,
+            reload_requested,
+            log_filter,
             node_wallet,
+            version,
+            start_time,
         })
     }
 }
@@ -64,7 +98,8 @@ impl RpcServer for API<Private> {
         url: &SocketAddr,
         settings: &APIConfig,
     ) -> Result<StopHandle, JsonRpseeError> {
-        crate::serve(self.into_rpc(), url, settings).await
+        let consensus_controller = self.0.consensus_controller.clone();
+        crate::serve(self.into_rpc(), url, settings, consensus_controller).await
     }
 }
 
@@ -77,6 +112,15 @@ impl MassaRpcServer for API<Private> {
         Ok(())
     }
 
+    fn node_reload_config(&self) -> RpcResult<()> {
+        self.0.reload_requested.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn node_set_log_filter(&self, directives: String) -> RpcResult<()> {
+        (self.0.log_filter)(&directives).map_err(|err| ApiError::BadRequest(err).into())
+    }
+
     async fn node_sign_message(&self, message: Vec<u8>) -> RpcResult<PubkeySig> {
         let signature = match self
             .0
@@ -187,6 +231,110 @@ impl MassaRpcServer for API<Private> {
         );
     }
 
+    async fn get_memory_stats(&self) -> RpcResult<MemoryStats> {
+        let consensus = self.0.consensus_controller.get_memory_stats();
+        let protocol = self
+            .0
+            .protocol_controller
+            .get_memory_stats()
+            .map_err(ApiError::ProtocolError)?;
+        let pool = self.0.pool_controller.get_memory_stats();
+        Ok(MemoryStats::new(
+            self.0.api_settings.memory_budget_bytes,
+            consensus,
+            protocol,
+            pool,
+        ))
+    }
+
+    async fn get_node_status(&self) -> RpcResult<NodeStatusSummary> {
+        let api_settings = &self.0.api_settings;
+        let now = MassaTime::now().map_err(ApiError::TimeError)?;
+        let current_slot = get_latest_block_slot_at_timestamp(
+            api_settings.thread_count,
+            api_settings.t0,
+            api_settings.genesis_timestamp,
+            now,
+        )
+        .map_err(ApiError::ModelsError)?;
+        let final_slot = self.0.execution_controller.get_stats().final_cursor;
+        let sync_lag = match current_slot {
+            Some(current_slot) if current_slot > final_slot => current_slot
+                .slots_since(&final_slot, api_settings.thread_count)
+                .map_err(ApiError::ModelsError)?,
+            _ => 0,
+        };
+        let (_, peers) = self
+            .0
+            .protocol_controller
+            .get_stats()
+            .map_err(ApiError::ProtocolError)?;
+        Ok(NodeStatusSummary {
+            node_id: NodeId::new(api_settings.keypair.get_public_key()),
+            version: self.0.version,
+            uptime: now.saturating_sub(self.0.start_time),
+            connected_peer_count: peers.len(),
+            current_slot,
+            final_slot,
+            sync_lag,
+            pool_operation_count: self.0.pool_controller.get_operation_count(),
+            pool_endorsement_count: self.0.pool_controller.get_endorsement_count(),
+            staking_key_count: self.0.node_wallet.read().get_full_wallet().len(),
+        })
+    }
+
+    async fn get_peers_detail(&self) -> RpcResult<Vec<PeerStats>> {
+        Ok(self
+            .0
+            .protocol_controller
+            .get_peers_detail()
+            .map_err(ApiError::ProtocolError)?)
+    }
+
+    async fn node_get_known_peers(&self) -> RpcResult<Vec<KnownPeer>> {
+        let peers = self
+            .0
+            .protocol_controller
+            .get_known_peers()
+            .map_err(ApiError::ProtocolError)?;
+        Ok(peers
+            .into_iter()
+            .map(|(peer_id, peer)| KnownPeer {
+                node_id: NodeId::new(peer_id.get_public_key()),
+                listeners: peer.listeners.into_keys().collect(),
+                last_announce: peer.last_announce,
+                trusted: peer.trusted,
+            })
+            .collect())
+    }
+
+    async fn node_add_known_peers(&self, peers: Vec<KnownPeer>) -> RpcResult<()> {
+        //TODO: Change when unify node id and peer id
+        let peers = peers
+            .into_iter()
+            .map(|peer| {
+                (
+                    PeerId::from_public_key(peer.node_id.get_public_key()),
+                    peer.listeners
+                        .into_iter()
+                        .map(|addr| (addr, TransportType::Tcp))
+                        .collect(),
+                )
+            })
+            .collect();
+        self.0
+            .protocol_controller
+            .add_known_peers(peers)
+            .map_err(|e| ApiError::ProtocolError(e).into())
+    }
+
+    async fn get_journal_events(&self, filter: JournalFilter) -> RpcResult<Vec<JournalEvent>> {
+        read_journal(&self.0.api_settings.journal_path, &filter).map_err(|err| {
+            ApiError::InternalServerError(format!("could not read the event journal: {}", err))
+                .into()
+        })
+    }
+
     async fn get_status(&self) -> RpcResult<NodeStatus> {
         crate::wrong_api::<NodeStatus>()
     }
@@ -203,6 +351,10 @@ impl MassaRpcServer for API<Private> {
         crate::wrong_api::<Vec<OperationInfo>>()
     }
 
+    async fn get_pool_operations(&self, _: PoolOperationsRequest) -> RpcResult<Vec<OperationInfo>> {
+        crate::wrong_api::<Vec<OperationInfo>>()
+    }
+
     async fn get_endorsements(&self, _: Vec<EndorsementId>) -> RpcResult<Vec<EndorsementInfo>> {
         crate::wrong_api::<Vec<EndorsementInfo>>()
     }
@@ -219,6 +371,10 @@ impl MassaRpcServer for API<Private> {
         crate::wrong_api::<Vec<BlockSummary>>()
     }
 
+    async fn get_graph_interval_paged(&self, _: GraphIntervalRequest) -> RpcResult<BlockGraphPage> {
+        crate::wrong_api::<BlockGraphPage>()
+    }
+
     async fn get_datastore_entries(
         &self,
         _: Vec<DatastoreEntryInput>,
@@ -234,6 +390,56 @@ impl MassaRpcServer for API<Private> {
         crate::wrong_api::<Vec<OperationId>>()
     }
 
+    async fn send_transaction(&self, transaction: TransactionInput) -> RpcResult<OperationId> {
+        let api_cfg = &self.0.api_settings;
+        let now = MassaTime::now().map_err(ApiError::TimeError)?;
+        let slot = get_latest_block_slot_at_timestamp(
+            api_cfg.thread_count,
+            api_cfg.t0,
+            api_cfg.genesis_timestamp,
+            now,
+        )
+        .map_err(ApiError::ModelsError)?
+        .unwrap_or_else(|| Slot::new(0, 0));
+        // the operation can only be included after the current slot in the thread of the sender
+        let mut expire_period = slot.period + api_cfg.operation_validity_periods;
+        if slot.thread >= transaction.from.get_thread(api_cfg.thread_count) {
+            expire_period += 1;
+        }
+
+        let operation = self
+            .0
+            .node_wallet
+            .read()
+            .create_operation(
+                Operation {
+                    fee: transaction.fee,
+                    expire_period,
+                    op: OperationType::Transaction {
+                        recipient_address: transaction.to,
+                        amount: transaction.amount,
+                    },
+                },
+                transaction.from,
+            )
+            .map_err(ApiError::WalletError)?;
+        let operation_id = operation.id;
+
+        let mut to_send = self.0.storage.clone_without_refs();
+        to_send.store_operations(vec![operation]);
+        let mut pool_controller = self.0.pool_controller.clone();
+        pool_controller.add_operations(to_send.clone());
+
+        let protocol_controller = self.0.protocol_controller.clone();
+        tokio::task::spawn_blocking(move || protocol_controller.propagate_operations(to_send))
+            .await
+            .map_err(|err| ApiError::InternalServerError(err.to_string()))?
+            .map_err(|err| {
+                ApiError::InternalServerError(format!("Failed to propagate operations: {}", err))
+            })?;
+        Ok(operation_id)
+    }
+
     async fn get_filtered_sc_output_event(&self, _: EventFilter) -> RpcResult<Vec<SCOutputEvent>> {
         crate::wrong_api::<Vec<SCOutputEvent>>()
     }
@@ -338,6 +544,10 @@ impl MassaRpcServer for API<Private> {
     async fn get_openrpc_spec(&self) -> RpcResult<Value> {
         crate::wrong_api::<Value>()
     }
+
+    async fn get_api_schema(&self) -> RpcResult<ApiSchema> {
+        crate::wrong_api::<ApiSchema>()
+    }
 }
 
 /// Run Search, Create, Read, Update, Delete operation on bootstrap list of IP(s)
@@ -7,18 +7,21 @@ use itertools::{izip, Itertools};
 use jsonrpsee::core::{Error as JsonRpseeError, RpcResult};
 use massa_api_exports::{
     address::AddressInfo,
-    block::{BlockInfo, BlockInfoContent, BlockSummary},
+    block::{BlockGraphPage, BlockInfo, BlockInfoContent, BlockSummary},
     config::APIConfig,
     datastore::{DatastoreEntryInput, DatastoreEntryOutput},
     endorsement::EndorsementInfo,
     error::ApiError,
     execution::{ExecuteReadOnlyResponse, ReadOnlyBytecodeExecution, ReadOnlyCall, ReadOnlyResult},
-    node::NodeStatus,
-    operation::{OperationInfo, OperationInput},
+    journal::{JournalEvent, JournalFilter},
+    node::{KnownPeer, NodeStatus, NodeStatusSummary},
+    operation::{OperationInfo, OperationInput, PoolOperationsRequest, TransactionInput},
     page::{PageRequest, PagedVec},
+    schema::{public_api_schema, ApiSchema},
     slot::SlotAmount,
-    TimeInterval,
+    GraphIntervalRequest, TimeInterval,
 };
+use massa_consensus_exports::block_graph_export::BlockGraphExport;
 use massa_consensus_exports::block_status::DiscardReason;
 use massa_consensus_exports::ConsensusController;
 use massa_execution_exports::{
@@ -44,6 +47,7 @@ use massa_models::{
     prehash::{PreHashMap, PreHashSet},
     secure_share::SecureShareDeserializer,
     slot::Slot,
+    stats::{MemoryStats, PeerStats},
     timeslots,
     timeslots::{get_latest_block_slot_at_timestamp, time_range_to_slot_range},
     version::Version,
@@ -99,7 +103,8 @@ impl RpcServer for API<Public> {
         url: &SocketAddr,
         api_config: &APIConfig,
     ) -> Result<StopHandle, JsonRpseeError> {
-        crate::serve(self.into_rpc(), url, api_config).await
+        let consensus_controller = self.0.consensus_controller.clone();
+        crate::serve(self.into_rpc(), url, api_config, consensus_controller).await
     }
 }
 
@@ -110,6 +115,14 @@ impl MassaRpcServer for API<Public> {
         crate::wrong_api::<()>()
     }
 
+    fn node_reload_config(&self) -> RpcResult<()> {
+        crate::wrong_api::<()>()
+    }
+
+    fn node_set_log_filter(&self, _: String) -> RpcResult<()> {
+        crate::wrong_api::<()>()
+    }
+
     async fn node_sign_message(&self, _: Vec<u8>) -> RpcResult<PubkeySig> {
         crate::wrong_api::<PubkeySig>()
     }
@@ -326,6 +339,30 @@ impl MassaRpcServer for API<Public> {
         crate::wrong_api::<()>()
     }
 
+    async fn get_memory_stats(&self) -> RpcResult<MemoryStats> {
+        crate::wrong_api::<MemoryStats>()
+    }
+
+    async fn get_node_status(&self) -> RpcResult<NodeStatusSummary> {
+        crate::wrong_api::<NodeStatusSummary>()
+    }
+
+    async fn get_peers_detail(&self) -> RpcResult<Vec<PeerStats>> {
+        crate::wrong_api::<Vec<PeerStats>>()
+    }
+
+    async fn node_get_known_peers(&self) -> RpcResult<Vec<KnownPeer>> {
+        crate::wrong_api::<Vec<KnownPeer>>()
+    }
+
+    async fn node_add_known_peers(&self, _: Vec<KnownPeer>) -> RpcResult<()> {
+        crate::wrong_api::<()>()
+    }
+
+    async fn get_journal_events(&self, _: JournalFilter) -> RpcResult<Vec<JournalEvent>> {
+        crate::wrong_api::<Vec<JournalEvent>>()
+    }
+
     async fn get_status(&self) -> RpcResult<NodeStatus> {
         let execution_controller = self.0.execution_controller.clone();
         let consensus_controller = self.0.consensus_controller.clone();
@@ -562,6 +599,25 @@ impl MassaRpcServer for API<Public> {
         Ok(res)
     }
 
+    async fn get_pool_operations(
+        &self,
+        request: PoolOperationsRequest,
+    ) -> RpcResult<Vec<OperationInfo>> {
+        let max_arguments = self.0.api_settings.max_arguments as usize;
+        let limit = request
+            .limit
+            .map_or(max_arguments, |limit| limit.min(max_arguments));
+        if limit == 0 {
+            return Err(ApiError::BadRequest("limit must be positive".to_string()).into());
+        }
+
+        let ops = self
+            .0
+            .pool_command_sender
+            .get_address_operations(&request.address, limit);
+        self.get_operations(ops).await
+    }
+
     async fn get_endorsements(&self, eds: Vec<EndorsementId>) -> RpcResult<Vec<EndorsementInfo>> {
         // get the endorsements and the list of blocks that contain them from storage
         let storage_info: Vec<(SecureShareEndorsement, PreHashSet<BlockId>)> = {
@@ -642,17 +698,24 @@ impl MassaRpcServer for API<Public> {
     }
 
     /// gets a block(s). Returns nothing if not found
-    /// only active blocks are returned
+    /// only active blocks are returned, with the invalid blocks still known by the graph
     async fn get_blocks(&self, ids: Vec<BlockId>) -> RpcResult<Vec<BlockInfo>> {
         let consensus_controller = self.0.consensus_controller.clone();
         let storage = self.0.storage.clone_without_refs();
+        let invalid_reasons = consensus_controller.get_invalid_block_reasons(&ids);
         let blocks = ids
             .into_iter()
-            .filter_map(|id| {
+            .zip(invalid_reasons)
+            .filter_map(|(id, invalid_reason)| {
                 let content = if let Some(wrapped_block) = storage.read_blocks().get(&id) {
                     wrapped_block.content.clone()
                 } else {
-                    return None;
+                    // the content of an invalid block is not kept
+                    return invalid_reason.map(|reason| BlockInfo {
+                        id,
+                        content: None,
+                        invalid_reason: Some(reason),
+                    });
                 };
 
                 if let Some(graph_status) = consensus_controller
@@ -675,6 +738,7 @@ impl MassaRpcServer for API<Public> {
                             is_discarded,
                             block: content,
                         }),
+                        invalid_reason,
                     });
                 }
 
@@ -727,38 +791,35 @@ impl MassaRpcServer for API<Public> {
             Ok(graph) => graph,
             Err(e) => return Err(ApiError::ConsensusError(e).into()),
         };
+        summarize_block_graph(graph)
+    }
 
-        let mut res = Vec::with_capacity(graph.active_blocks.len());
-        let blockclique = graph
-            .max_cliques
-            .iter()
-            .find(|clique| clique.is_blockclique)
-            .ok_or_else(|| ApiError::InconsistencyError("missing blockclique".to_string()))?;
-        for (id, exported_block) in graph.active_blocks.into_iter() {
-            res.push(BlockSummary {
-                id,
-                is_final: exported_block.is_final,
-                is_stale: false,
-                is_in_blockclique: blockclique.block_ids.contains(&id),
-                slot: exported_block.header.content.slot,
-                creator: exported_block.header.content_creator_address,
-                parents: exported_block.header.content.parents,
-            });
-        }
-        for (id, (reason, (slot, creator, parents))) in graph.discarded_blocks.into_iter() {
-            if reason == DiscardReason::Stale {
-                res.push(BlockSummary {
-                    id,
-                    is_final: false,
-                    is_stale: true,
-                    is_in_blockclique: false,
-                    slot,
-                    creator,
-                    parents,
-                });
-            }
+    /// gets a page of the block graph from consensus, with slot filtering
+    /// the page size is capped by `max_block_graph_page_size`
+    async fn get_graph_interval_paged(
+        &self,
+        request: GraphIntervalRequest,
+    ) -> RpcResult<BlockGraphPage> {
+        let max_page_size = self.0.api_settings.max_block_graph_page_size;
+        let limit = request
+            .limit
+            .map_or(max_page_size, |limit| limit.min(max_page_size));
+        if limit == 0 {
+            return Err(ApiError::BadRequest("page limit must be positive".to_string()).into());
         }
-        Ok(res)
+
+        // one more block than the page size tells if there is a next page
+        let graph = match self.0.consensus_controller.get_block_graph_page(
+            request.start_slot,
+            request.end_slot,
+            request.cursor.map(|cursor| (cursor.slot, cursor.id)),
+            limit.saturating_add(1),
+        ) {
+            Ok(graph) => graph,
+            Err(e) => return Err(ApiError::ConsensusError(e).into()),
+        };
+        let blocks = summarize_block_graph(graph)?;
+        Ok(BlockGraphPage::new(blocks, request.cursor, limit))
     }
 
     async fn get_datastore_entries(
@@ -976,6 +1037,10 @@ impl MassaRpcServer for API<Public> {
         Ok(ids)
     }
 
+    async fn send_transaction(&self, _: TransactionInput) -> RpcResult<OperationId> {
+        crate::wrong_api::<OperationId>()
+    }
+
     /// Get events optionally filtered by:
     /// * start slot
     /// * end slot
@@ -1057,4 +1122,124 @@ impl MassaRpcServer for API<Public> {
 
         openrpc
     }
+
+    async fn get_api_schema(&self) -> RpcResult<ApiSchema> {
+        Ok(public_api_schema())
+    }
+}
+
+/// Summarize the active, stale and invalid blocks of the graph between `start_slot` (included) and `end_slot` (excluded)
+fn summarize_block_graph(graph: BlockGraphExport) -> RpcResult<Vec<BlockSummary>> {
+    let mut res = Vec::with_capacity(graph.active_blocks.len());
+    let blockclique = graph
+        .max_cliques
+        .iter()
+        .find(|clique| clique.is_blockclique)
+        .ok_or_else(|| ApiError::InconsistencyError("missing blockclique".to_string()))?;
+    for (id, exported_block) in graph.active_blocks.into_iter() {
+        res.push(BlockSummary {
+            id,
+            is_final: exported_block.is_final,
+            is_stale: false,
+            is_in_blockclique: blockclique.block_ids.contains(&id),
+            slot: exported_block.header.content.slot,
+            creator: exported_block.header.content_creator_address,
+            parents: exported_block.header.content.parents,
+            invalid_reason: None,
+        });
+    }
+    for (id, (reason, (slot, creator, parents))) in graph.discarded_blocks.into_iter() {
+        let (is_stale, invalid_reason) = match reason {
+            DiscardReason::Stale => (true, None),
+            DiscardReason::Invalid(reason) => (false, Some(reason)),
+            DiscardReason::Final => continue,
+        };
+        res.push(BlockSummary {
+            id,
+            is_final: false,
+            is_stale,
+            is_in_blockclique: false,
+            slot,
+            creator,
+            parents,
+            invalid_reason,
+        });
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use massa_api_exports::schema::public_api_schema;
+    use std::collections::{BTreeSet, HashMap};
+
+    /// Names of the RPC methods declared in `MassaRpc`, by name of the Rust function
+    fn rpc_method_names() -> HashMap<String, String> {
+        let mut names = HashMap::new();
+        let mut rpc_name = None;
+        for line in include_str!("lib.rs").lines().map(str::trim) {
+            if let Some(rest) = line.strip_prefix("#[method(name = \"") {
+                rpc_name = rest.split('"').next().map(str::to_string);
+            } else if let Some(rest) = line
+                .strip_prefix("async fn ")
+                .or_else(|| line.strip_prefix("fn "))
+            {
+                if let Some(rpc_name) = rpc_name.take() {
+                    let fn_name = rest.split(['(', '<']).next().unwrap();
+                    names.insert(fn_name.to_string(), rpc_name);
+                }
+            }
+        }
+        names
+    }
+
+    /// Names of the RPC methods served by the public API: the ones whose implementation
+    /// in this file does not answer `wrong_api`
+    fn served_method_names() -> BTreeSet<String> {
+        let rpc_names = rpc_method_names();
+        let source = include_str!("public.rs");
+        let start = source
+            .find("impl MassaRpcServer for API<Public> {")
+            .expect("missing MassaRpcServer implementation");
+        let mut methods: Vec<(String, bool)> = Vec::new();
+        for line in source[start..].lines().skip(1) {
+            if line == "}" {
+                break;
+            }
+            if let Some(rest) = line
+                .strip_prefix("    async fn ")
+                .or_else(|| line.strip_prefix("    fn "))
+            {
+                let fn_name = rest.split(['(', '<']).next().unwrap().to_string();
+                methods.push((fn_name, true));
+            } else if line.contains("wrong_api") {
+                if let Some((_, served)) = methods.last_mut() {
+                    *served = false;
+                }
+            }
+        }
+        methods
+            .into_iter()
+            .filter(|(_, served)| *served)
+            .map(|(fn_name, _)| {
+                rpc_names
+                    .get(&fn_name)
+                    .unwrap_or_else(|| panic!("{} is not a method of MassaRpc", fn_name))
+                    .clone()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_api_schema_lists_the_public_methods() {
+        let mut served = served_method_names();
+        // described by the OpenRPC specification it returns
+        assert!(served.remove("rpc.discover"));
+        let described: BTreeSet<String> = public_api_schema()
+            .methods
+            .into_iter()
+            .map(|method| method.name)
+            .collect();
+        assert_eq!(served, described);
+    }
 }
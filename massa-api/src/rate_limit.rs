@@ -0,0 +1,367 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+//! Per-client request rate limiting of the API servers
+//!
+//! The server only gives the address of the peer to its logger, once the middlewares were
+//! called: `PeerAddrLogger` stores it in the request, and the rate limit of the request is
+//! applied when the server reads its body, or when it responds to a request whose body is not read.
+
+use futures::future::BoxFuture;
+use futures::{FutureExt, Stream};
+use hyper::body::Bytes;
+use hyper::{Body, Request, Response, StatusCode};
+use jsonrpsee::server::logger::{HttpRequest, Logger, MethodKind, Params, TransportProtocol};
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Instant;
+use tower::{Layer, Service};
+
+/// Maximum number of tracked clients before buckets are dropped
+const MAX_TRACKED_CLIENTS: usize = 65536;
+
+/// Identity of a client for the rate limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ClientKey {
+    /// client address, as reported by the trusted reverse proxy or by the server
+    Ip(IpAddr),
+    /// connection to the API server, when the client address is not known
+    Connection(u64),
+}
+
+/// Identify the client: by the address reported by the trusted reverse proxy, otherwise by the
+/// address of the peer, and by the connection if the server did not report it
+fn client_key(forwarded_ip: Option<IpAddr>, peer_ip: Option<IpAddr>, connection: u64) -> ClientKey {
+    forwarded_ip
+        .or(peer_ip)
+        .map_or(ClientKey::Connection(connection), ClientKey::Ip)
+}
+
+/// Address of the peer of a request, set by `PeerAddrLogger`
+#[derive(Clone, Default)]
+struct PeerAddr(Arc<Mutex<Option<IpAddr>>>);
+
+/// Logger of the API servers storing the address of the peer in each request
+#[derive(Clone)]
+pub(crate) struct PeerAddrLogger;
+
+impl Logger for PeerAddrLogger {
+    type Instant = ();
+
+    fn on_connect(&self, remote_addr: SocketAddr, request: &HttpRequest, _t: TransportProtocol) {
+        if let Some(peer) = request.extensions().get::<PeerAddr>() {
+            *peer.0.lock() = Some(remote_addr.ip());
+        }
+    }
+
+    fn on_request(&self, _t: TransportProtocol) -> Self::Instant {}
+
+    fn on_call(&self, _method: &str, _params: Params, _kind: MethodKind, _t: TransportProtocol) {}
+
+    fn on_result(&self, _method: &str, _success: bool, _started_at: (), _t: TransportProtocol) {}
+
+    fn on_response(&self, _result: &str, _started_at: (), _t: TransportProtocol) {}
+
+    fn on_disconnect(&self, _remote_addr: SocketAddr, _t: TransportProtocol) {}
+}
+
+/// Token bucket of a client
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// Shared state of the rate limiter: one token bucket per client
+pub(crate) struct RateLimiter {
+    /// number of requests allowed per second per client, also used as burst size
+    requests_per_second: u64,
+    /// whether to identify clients through the `X-Forwarded-For` header
+    trust_forwarded_for: bool,
+    /// identifier of the next connection
+    next_connection: AtomicU64,
+    /// buckets of the clients
+    buckets: Mutex<HashMap<ClientKey, Bucket>>,
+}
+
+impl RateLimiter {
+    /// Create a new rate limiter allowing `requests_per_second` for each client
+    pub(crate) fn new(requests_per_second: u64, trust_forwarded_for: bool) -> Self {
+        RateLimiter {
+            requests_per_second,
+            trust_forwarded_for,
+            next_connection: AtomicU64::new(0),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Address of the client reported by the trusted reverse proxy, if any.
+    /// It is the last address of `X-Forwarded-For`: it was appended by the proxy,
+    /// the previous ones come from the client and can be forged.
+    fn forwarded_ip(&self, request: &Request<Body>) -> Option<IpAddr> {
+        if !self.trust_forwarded_for {
+            return None;
+        }
+        request
+            .headers()
+            .get_all("x-forwarded-for")
+            .iter()
+            .last()
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.rsplit(',').next())
+            .and_then(|ip| ip.trim().parse().ok())
+    }
+
+    /// Consume a token of the client, returns false if the client exceeded its rate
+    fn try_acquire(&self, key: ClientKey, now: Instant) -> bool {
+        let capacity = self.requests_per_second as f64;
+        let mut buckets = self.buckets.lock();
+        if buckets.len() >= MAX_TRACKED_CLIENTS && !buckets.contains_key(&key) {
+            evict_buckets(&mut buckets, capacity, now);
+        }
+        let bucket = buckets.entry(key).or_insert(Bucket {
+            tokens: capacity,
+            last_refill: now,
+        });
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * capacity).min(capacity);
+        bucket.last_refill = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Make room in the buckets: forget the idle clients, whose bucket is full again,
+/// then the least recently seen quarter of the clients if there are still too many
+fn evict_buckets(buckets: &mut HashMap<ClientKey, Bucket>, capacity: f64, now: Instant) {
+    buckets.retain(|_, bucket| {
+        bucket.tokens + now.duration_since(bucket.last_refill).as_secs_f64() * capacity < capacity
+    });
+    if buckets.len() < MAX_TRACKED_CLIENTS {
+        return;
+    }
+    let mut last_refills: Vec<Instant> = buckets.values().map(|b| b.last_refill).collect();
+    let (_, threshold, _) = last_refills.select_nth_unstable(MAX_TRACKED_CLIENTS / 4);
+    let threshold = *threshold;
+    buckets.retain(|_, bucket| bucket.last_refill > threshold);
+}
+
+/// Rate limit of a request, decided once the server reported the address of its peer
+struct Gate {
+    limiter: Arc<RateLimiter>,
+    forwarded_ip: Option<IpAddr>,
+    peer: PeerAddr,
+    connection: u64,
+    allowed: Mutex<Option<bool>>,
+}
+
+impl Gate {
+    /// Whether the request is allowed, consuming a token of the client the first time
+    fn allowed(&self) -> bool {
+        *self.allowed.lock().get_or_insert_with(|| {
+            let key = client_key(self.forwarded_ip, *self.peer.0.lock(), self.connection);
+            self.limiter.try_acquire(key, Instant::now())
+        })
+    }
+}
+
+/// Body of a request, failing instead of being read if the client exceeded its rate
+struct GatedBody {
+    gate: Arc<Gate>,
+    body: Body,
+}
+
+impl Stream for GatedBody {
+    type Item = Result<Bytes, Box<dyn std::error::Error + Send + Sync>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if !self.gate.allowed() {
+            return Poll::Ready(Some(Err("rate limit exceeded".into())));
+        }
+        Pin::new(&mut self.body).poll_next(cx).map_err(Into::into)
+    }
+}
+
+/// Response to the requests of a client exceeding its rate
+fn too_many_requests() -> Response<Body> {
+    let mut response = Response::new(Body::from("rate limit exceeded"));
+    *response.status_mut() = StatusCode::TOO_MANY_REQUESTS;
+    response
+}
+
+/// Layer rejecting the requests of clients exceeding their rate with `429 Too Many Requests`
+#[derive(Clone)]
+pub(crate) struct RateLimitLayer {
+    limiter: Arc<RateLimiter>,
+}
+
+impl RateLimitLayer {
+    /// Create a new layer, the limiter being shared by all the connections
+    pub(crate) fn new(limiter: RateLimiter) -> Self {
+        RateLimitLayer {
+            limiter: Arc::new(limiter),
+        }
+    }
+}
+
+/// The server builds the middleware for each connection it accepts
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimit<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimit {
+            inner,
+            limiter: self.limiter.clone(),
+            connection: self.limiter.next_connection.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+}
+
+/// Service produced by `RateLimitLayer`
+#[derive(Clone)]
+pub(crate) struct RateLimit<S> {
+    inner: S,
+    limiter: Arc<RateLimiter>,
+    /// connection served by this service
+    connection: u64,
+}
+
+impl<S> Service<Request<Body>> for RateLimit<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>>,
+    S::Future: Send + 'static,
+    S::Error: Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<Body>) -> Self::Future {
+        let peer = PeerAddr::default();
+        request.extensions_mut().insert(peer.clone());
+        let gate = Arc::new(Gate {
+            limiter: self.limiter.clone(),
+            forwarded_ip: self.limiter.forwarded_ip(&request),
+            peer,
+            connection: self.connection,
+            allowed: Mutex::new(None),
+        });
+        let (parts, body) = request.into_parts();
+        let body = Body::wrap_stream(GatedBody {
+            gate: gate.clone(),
+            body,
+        });
+        self.inner
+            .call(Request::from_parts(parts, body))
+            .map(move |result| {
+                result.map(|response| {
+                    if gate.allowed() {
+                        response
+                    } else {
+                        too_many_requests()
+                    }
+                })
+            })
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn request(forwarded_for: Option<&str>) -> Request<Body> {
+        let mut builder = Request::builder();
+        if let Some(forwarded_for) = forwarded_for {
+            builder = builder.header("x-forwarded-for", forwarded_for);
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    fn gate(limiter: &Arc<RateLimiter>, peer_ip: Option<&str>, connection: u64) -> Gate {
+        Gate {
+            limiter: limiter.clone(),
+            forwarded_ip: None,
+            peer: PeerAddr(Arc::new(Mutex::new(peer_ip.map(|ip| ip.parse().unwrap())))),
+            connection,
+            allowed: Mutex::new(None),
+        }
+    }
+
+    #[test]
+    fn test_client_key() {
+        let peer_ip: IpAddr = "3.3.3.3".parse().unwrap();
+        let trusting = RateLimiter::new(10, true);
+        // the entries before the one appended by the proxy are forged by the client
+        let forwarded_ip = trusting.forwarded_ip(&request(Some("1.1.1.1, 2.2.2.2")));
+        assert_eq!(forwarded_ip, Some("2.2.2.2".parse().unwrap()));
+        assert_eq!(
+            client_key(forwarded_ip, Some(peer_ip), 7),
+            ClientKey::Ip("2.2.2.2".parse().unwrap())
+        );
+        assert_eq!(trusting.forwarded_ip(&request(None)), None);
+
+        let not_trusting = RateLimiter::new(10, false);
+        assert_eq!(not_trusting.forwarded_ip(&request(Some("2.2.2.2"))), None);
+        assert_eq!(client_key(None, Some(peer_ip), 7), ClientKey::Ip(peer_ip));
+        // the server did not report the peer
+        assert_eq!(client_key(None, None, 7), ClientKey::Connection(7));
+    }
+
+    #[test]
+    fn test_gate() {
+        let limiter = Arc::new(RateLimiter::new(1, false));
+        let first = gate(&limiter, Some("3.3.3.3"), 0);
+        assert!(first.allowed());
+        // the decision is taken once per request
+        assert!(first.allowed());
+        // reconnecting does not give a new budget to the client
+        assert!(!gate(&limiter, Some("3.3.3.3"), 1).allowed());
+        assert!(gate(&limiter, Some("4.4.4.4"), 2).allowed());
+        assert!(gate(&limiter, None, 3).allowed());
+    }
+
+    #[test]
+    fn test_token_bucket() {
+        let limiter = RateLimiter::new(2, false);
+        let start = Instant::now();
+        let (client, other) = (ClientKey::Connection(0), ClientKey::Connection(1));
+        assert!(limiter.try_acquire(client, start));
+        assert!(limiter.try_acquire(client, start));
+        assert!(!limiter.try_acquire(client, start));
+        // each client has its own bucket
+        assert!(limiter.try_acquire(other, start));
+        // the bucket refills at the configured rate
+        assert!(limiter.try_acquire(client, start + Duration::from_millis(500)));
+        assert!(!limiter.try_acquire(client, start + Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn test_evict_buckets() {
+        let limiter = RateLimiter::new(1, false);
+        let start = Instant::now();
+        // busy clients: their bucket is empty so they are not idle
+        for connection in 0..MAX_TRACKED_CLIENTS as u64 {
+            let now = start + Duration::from_micros(connection);
+            assert!(limiter.try_acquire(ClientKey::Connection(connection), now));
+        }
+        let now = start + Duration::from_micros(MAX_TRACKED_CLIENTS as u64);
+        assert!(limiter.try_acquire(ClientKey::Connection(u64::MAX), now));
+        let buckets = limiter.buckets.lock();
+        assert!(buckets.len() <= MAX_TRACKED_CLIENTS * 3 / 4 + 1);
+        // the least recently seen clients are forgotten first
+        assert!(!buckets.contains_key(&ClientKey::Connection(0)));
+        assert!(buckets.contains_key(&ClientKey::Connection(MAX_TRACKED_CLIENTS as u64 - 1)));
+    }
+}
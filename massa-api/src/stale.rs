@@ -0,0 +1,81 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+//! Flag of the API responses given while consensus suspects a network partition
+
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use hyper::header::HeaderValue;
+use hyper::{Body, Request, Response};
+use massa_consensus_exports::ConsensusController;
+use tower::{Layer, Service};
+
+/// Header added to the responses while a network partition is suspected:
+/// the node is probably on a minority fork and the data it returns may be stale
+pub(crate) const POTENTIALLY_STALE_HEADER: &str = "massa-potentially-stale";
+
+/// Layer adding the `Massa-Potentially-Stale` header to the responses while a partition is suspected
+#[derive(Clone)]
+pub(crate) struct StaleFlagLayer {
+    consensus_controller: Arc<dyn ConsensusController>,
+}
+
+impl StaleFlagLayer {
+    /// Create a new layer reading the partition state from consensus
+    pub(crate) fn new(consensus_controller: Box<dyn ConsensusController>) -> Self {
+        StaleFlagLayer {
+            consensus_controller: Arc::from(consensus_controller),
+        }
+    }
+}
+
+impl<S> Layer<S> for StaleFlagLayer {
+    type Service = StaleFlag<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        StaleFlag {
+            inner,
+            consensus_controller: self.consensus_controller.clone(),
+        }
+    }
+}
+
+/// Service produced by `StaleFlagLayer`
+#[derive(Clone)]
+pub(crate) struct StaleFlag<S> {
+    inner: S,
+    consensus_controller: Arc<dyn ConsensusController>,
+}
+
+impl<S> Service<Request<Body>> for StaleFlag<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>>,
+    S::Future: Send + 'static,
+    S::Error: Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        let consensus_controller = self.consensus_controller.clone();
+        self.inner
+            .call(request)
+            .map(move |result| {
+                result.map(|mut response| {
+                    if consensus_controller.is_partition_suspected() {
+                        response
+                            .headers_mut()
+                            .insert(POTENTIALLY_STALE_HEADER, HeaderValue::from_static("true"));
+                    }
+                    response
+                })
+            })
+            .boxed()
+    }
+}
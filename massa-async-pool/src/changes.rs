@@ -28,10 +28,11 @@ use nom::{
     sequence::tuple,
     IResult, Parser,
 };
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Consolidated changes to the asynchronous message pool
-#[derive(Default, Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
 pub struct AsyncPoolChanges(
     pub BTreeMap<AsyncMessageId, SetUpdateOrDelete<AsyncMessage, AsyncMessageUpdate>>,
 );
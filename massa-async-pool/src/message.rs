@@ -23,6 +23,7 @@ use nom::multi::length_data;
 use nom::sequence::tuple;
 use nom::{IResult, Parser};
 use num::rational::Ratio;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::ops::Bound::{Excluded, Included};
 
@@ -172,7 +173,7 @@ impl Deserializer<AsyncMessageId> for AsyncMessageIdDeserializer {
 }
 
 /// Structure defining a trigger for an asynchronous message
-#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct AsyncMessageTrigger {
     /// Filter on the address
     pub address: Address,
@@ -260,7 +261,7 @@ impl Deserializer<AsyncMessageTrigger> for AsyncMessageTriggerDeserializer {
 }
 
 /// Structure defining an asynchronous smart contract message
-#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AsyncMessage {
     /// Slot at which the message was emitted
     pub emission_slot: Slot,
@@ -706,7 +707,7 @@ impl From<AsyncMessage> for AsyncMessageInfo {
 }
 
 /// represents an update to one or more fields of a `AsyncMessage`
-#[derive(Default, Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
 pub struct AsyncMessageUpdate {
     /// Slot at which the message was emitted
     pub emission_slot: SetOrKeep<Slot>,
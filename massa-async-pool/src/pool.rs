@@ -10,8 +10,8 @@ use crate::{
     AsyncMessageSerializer,
 };
 use massa_db_exports::{
-    DBBatch, MassaDirection, MassaIteratorMode, ShareableMassaDBController, ASYNC_POOL_PREFIX,
-    MESSAGE_ID_DESER_ERROR, MESSAGE_ID_SER_ERROR, MESSAGE_SER_ERROR, STATE_CF,
+    DBBatch, MassaDBError, MassaDirection, MassaIteratorMode, ShareableMassaDBController,
+    ASYNC_POOL_PREFIX, MESSAGE_ID_DESER_ERROR, MESSAGE_ID_SER_ERROR, MESSAGE_SER_ERROR, STATE_CF,
 };
 use massa_ledger_exports::{Applicable, SetOrKeep, SetUpdateOrDelete};
 use massa_models::address::Address;
@@ -261,11 +261,12 @@ impl AsyncPool {
     /// Resets the pool to its initial state
     ///
     /// USED ONLY FOR BOOTSTRAP
-    pub fn reset(&mut self) {
+    pub fn reset(&mut self) -> Result<(), MassaDBError> {
         self.db
             .write()
-            .delete_prefix(ASYNC_POOL_PREFIX, STATE_CF, None);
+            .delete_prefix(ASYNC_POOL_PREFIX, STATE_CF, None)?;
         self.recompute_message_info_cache();
+        Ok(())
     }
 
     /// Applies pre-compiled `AsyncPoolChanges` to the pool without checking for overflows.
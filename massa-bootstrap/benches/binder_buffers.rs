@@ -0,0 +1,158 @@
+//! Compares, in isolation, the two buffer strategies of the bootstrap binders:
+//! allocating the message and frame buffers for every message, as before, or reusing
+//! the buffers of the binder. It replays the data path of a server to client transfer over
+//! loopback (serialization copy, hash chaining copy, framing, socket write, read and string
+//! decoding) without the hash and signature computations, which are the same in both cases.
+//!
+//! Run with `cargo bench -p massa_bootstrap --bench binder_buffers`.
+//!
+//! Reference figures (3 runs, each one the median of 5 transfers; single core VM, rustc 1.72 nightly).
+//! Most of the time is spent in the copies and the loopback socket, so the hash and signature
+//! computations of the real binders make the relative gain smaller:
+//!
+//! | message size | fresh buffers  | reused buffers | change         |
+//! |--------------|----------------|----------------|----------------|
+//! | 1 kB         | 1.60 - 1.64 µs | 1.31 - 1.35 µs | -16 % to -19 % |
+//! | 100 kB       | 40.0 - 43.6 µs | 36.6 - 41.3 µs | -5 % to -8 %   |
+//! | 1 MB         | 815 - 879 µs   | 595 - 627 µs   | -27 % to -29 % |
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Instant;
+
+const SIGNATURE_SIZE: usize = 64;
+const HASH_SIZE: usize = 32;
+const RUNS: usize = 5;
+
+/// Buffers kept by a binder from one message to the next
+#[derive(Default)]
+struct Buffers {
+    msg: Vec<u8>,
+    frame: Vec<u8>,
+}
+
+fn send(stream: &mut TcpStream, payload: &[u8], prev_hash: &[u8], buffers: Option<&mut Buffers>) {
+    match buffers {
+        Some(buffers) => {
+            buffers.msg.clear();
+            buffers
+                .msg
+                .extend_from_slice(&(payload.len() as u32).to_be_bytes());
+            buffers.msg.extend_from_slice(payload);
+            buffers.frame.clear();
+            buffers.frame.extend_from_slice(prev_hash);
+            buffers.frame.extend(buffers.msg.iter());
+            std::hint::black_box(&buffers.frame);
+            buffers.frame.clear();
+            buffers.frame.extend_from_slice(&[0u8; SIGNATURE_SIZE]);
+            buffers
+                .frame
+                .extend_from_slice(&(buffers.msg.len() as u32).to_be_bytes());
+            buffers.frame.extend(buffers.msg.iter());
+            stream.write_all(&buffers.frame).unwrap();
+        }
+        None => {
+            let mut msg = Vec::new();
+            msg.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+            msg.extend_from_slice(payload);
+            let mut signed_data: Vec<u8> = Vec::with_capacity(HASH_SIZE + msg.len());
+            signed_data.extend(prev_hash);
+            signed_data.extend(&msg);
+            std::hint::black_box(&signed_data);
+            let stream_data = [
+                &[0u8; SIGNATURE_SIZE][..],
+                &(msg.len() as u32).to_be_bytes(),
+                &msg,
+            ]
+            .concat();
+            stream.write_all(&stream_data).unwrap();
+        }
+    }
+}
+
+/// Returns the length of the decoded string
+fn next(stream: &mut TcpStream, prev_hash: &[u8], buffers: Option<&mut Buffers>) -> usize {
+    let mut prefix = [0u8; SIGNATURE_SIZE + 4];
+    stream.read_exact(&mut prefix).unwrap();
+    let mut len = [0u8; 4];
+    len.copy_from_slice(&prefix[SIGNATURE_SIZE..]);
+    let len = u32::from_be_bytes(len) as usize;
+    match buffers {
+        Some(buffers) => {
+            buffers.msg.resize(len, 0);
+            stream.read_exact(&mut buffers.msg).unwrap();
+            buffers.frame.clear();
+            buffers.frame.extend_from_slice(prev_hash);
+            buffers.frame.extend(buffers.msg.iter());
+            std::hint::black_box(&buffers.frame);
+            String::from_utf8(buffers.msg[4..].to_vec()).unwrap().len()
+        }
+        None => {
+            let mut msg = vec![0u8; len];
+            stream.read_exact(&mut msg).unwrap();
+            let mut signed_data: Vec<u8> = Vec::with_capacity(HASH_SIZE + len);
+            signed_data.extend(prev_hash);
+            signed_data.extend(&msg);
+            std::hint::black_box(&signed_data);
+            String::from_utf8(msg[4..].to_vec()).unwrap().len()
+        }
+    }
+}
+
+/// Average time in microseconds to transfer `count` messages of `size` bytes
+fn transfer(size: usize, count: usize, reuse: bool) -> f64 {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (mut server, _) = listener.accept().unwrap();
+    let payload = "A".repeat(size).into_bytes();
+    let prev_hash = [1u8; HASH_SIZE];
+
+    let start = Instant::now();
+    let server_thread = std::thread::spawn(move || {
+        let mut buffers = Buffers::default();
+        for _ in 0..count {
+            // the message is built for each send, as with the bootstrap server messages
+            let payload = payload.clone();
+            send(
+                &mut server,
+                &payload,
+                &prev_hash,
+                reuse.then_some(&mut buffers),
+            );
+        }
+    });
+    let mut buffers = Buffers::default();
+    for _ in 0..count {
+        let len = next(&mut client, &prev_hash, reuse.then_some(&mut buffers));
+        assert_eq!(len, size);
+    }
+    server_thread.join().unwrap();
+    start.elapsed().as_secs_f64() * 1e6 / count as f64
+}
+
+fn median(mut values: Vec<f64>) -> f64 {
+    values.sort_by(|a, b| a.total_cmp(b));
+    values[values.len() / 2]
+}
+
+fn main() {
+    for (size, count) in [(1_000, 200_000), (100_000, 20_000), (1_000_000, 2_000)] {
+        // warm up, then interleave the runs of both strategies
+        transfer(size, count / 10, false);
+        transfer(size, count / 10, true);
+        let mut fresh = Vec::with_capacity(RUNS);
+        let mut reused = Vec::with_capacity(RUNS);
+        for _ in 0..RUNS {
+            fresh.push(transfer(size, count, false));
+            reused.push(transfer(size, count, true));
+        }
+        let (fresh, reused) = (median(fresh), median(reused));
+        println!(
+            "{:>9} bytes: fresh buffers {:>9.2} us/msg, reused buffers {:>9.2} us/msg ({:+.1}%)",
+            size,
+            fresh,
+            reused,
+            (reused / fresh - 1.0) * 100.0
+        );
+    }
+}
@@ -0,0 +1,133 @@
+//! Measures the transfer of bootstrap messages of various sizes from a server binder to a
+//! client binder over a loopback connection, including framing, signature and hash chaining.
+//!
+//! Run with `cargo bench -p massa_bootstrap --features benchmarking --bench binders`.
+
+#[cfg(feature = "benchmarking")]
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+#[cfg(feature = "benchmarking")]
+fn criterion_benchmark(c: &mut Criterion) {
+    use massa_bootstrap::{
+        BootstrapClientBinder, BootstrapClientConfig, BootstrapServerBinder,
+        BootstrapServerMessage, BootstrapSrvBindCfg,
+    };
+    use massa_models::config::{
+        BOOTSTRAP_RANDOMNESS_SIZE_BYTES, CONSENSUS_BOOTSTRAP_PART_SIZE, ENDORSEMENT_COUNT,
+        MAX_ADVERTISE_LENGTH, MAX_ASYNC_MESSAGE_DATA, MAX_ASYNC_POOL_LENGTH,
+        MAX_BOOTSTRAPPED_NEW_ELEMENTS, MAX_BOOTSTRAP_ASYNC_POOL_CHANGES, MAX_BOOTSTRAP_BLOCKS,
+        MAX_DATASTORE_ENTRY_COUNT, MAX_DATASTORE_KEY_LENGTH, MAX_DATASTORE_VALUE_LENGTH,
+        MAX_DEFERRED_CREDITS_LENGTH, MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
+        MAX_DENUNCIATION_CHANGES_LENGTH, MAX_EXECUTED_OPS_CHANGES_LENGTH, MAX_EXECUTED_OPS_LENGTH,
+        MAX_LEDGER_CHANGES_COUNT, MAX_LISTENERS_PER_PEER, MAX_OPERATIONS_PER_BLOCK,
+        MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH, MIP_STORE_STATS_BLOCK_CONSIDERED,
+        THREAD_COUNT,
+    };
+    use massa_signature::KeyPair;
+    use massa_time::MassaTime;
+    use std::net::{TcpListener, TcpStream};
+    use std::time::Instant;
+
+    const MESSAGE_SIZES: [usize; 3] = [1_000, 100_000, 1_000_000];
+
+    let server_keypair = KeyPair::generate(0).unwrap();
+    let listener = TcpListener::bind("localhost:0").unwrap();
+    let client_stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (server_stream, _) = listener.accept().unwrap();
+
+    let mut server = BootstrapServerBinder::new(
+        server_stream,
+        server_keypair.clone(),
+        BootstrapSrvBindCfg {
+            max_bytes_read_write: u64::MAX,
+            thread_count: THREAD_COUNT,
+            max_datastore_key_length: MAX_DATASTORE_KEY_LENGTH,
+            randomness_size_bytes: BOOTSTRAP_RANDOMNESS_SIZE_BYTES,
+            consensus_bootstrap_part_size: CONSENSUS_BOOTSTRAP_PART_SIZE,
+            write_error_timeout: MassaTime::from_millis(1000),
+        },
+        None,
+    );
+    let mut client = BootstrapClientBinder::new(
+        client_stream,
+        server_keypair.get_public_key(),
+        BootstrapClientConfig {
+            max_bytes_read_write: u64::MAX,
+            max_listeners_per_peer: MAX_LISTENERS_PER_PEER as u32,
+            endorsement_count: ENDORSEMENT_COUNT,
+            max_advertise_length: MAX_ADVERTISE_LENGTH,
+            max_bootstrap_blocks_length: MAX_BOOTSTRAP_BLOCKS,
+            max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
+            thread_count: THREAD_COUNT,
+            randomness_size_bytes: BOOTSTRAP_RANDOMNESS_SIZE_BYTES,
+            max_bootstrap_error_length: MESSAGE_SIZES[MESSAGE_SIZES.len() - 1] as u64,
+            max_new_elements: MAX_BOOTSTRAPPED_NEW_ELEMENTS,
+            max_datastore_entry_count: MAX_DATASTORE_ENTRY_COUNT,
+            max_datastore_key_length: MAX_DATASTORE_KEY_LENGTH,
+            max_datastore_value_length: MAX_DATASTORE_VALUE_LENGTH,
+            max_async_pool_changes: MAX_BOOTSTRAP_ASYNC_POOL_CHANGES,
+            max_async_pool_length: MAX_ASYNC_POOL_LENGTH,
+            max_async_message_data: MAX_ASYNC_MESSAGE_DATA,
+            max_ledger_changes_count: MAX_LEDGER_CHANGES_COUNT,
+            max_changes_slot_count: 1000,
+            max_rolls_length: MAX_ROLLS_COUNT_LENGTH,
+            max_production_stats_length: MAX_PRODUCTION_STATS_LENGTH,
+            max_credits_length: MAX_DEFERRED_CREDITS_LENGTH,
+            max_executed_ops_length: MAX_EXECUTED_OPS_LENGTH,
+            max_ops_changes_length: MAX_EXECUTED_OPS_CHANGES_LENGTH,
+            mip_store_stats_block_considered: MIP_STORE_STATS_BLOCK_CONSIDERED,
+            max_denunciations_per_block_header: MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
+            max_denunciation_changes_length: MAX_DENUNCIATION_CHANGES_LENGTH,
+        },
+        None,
+    );
+
+    // the server sends the requested number of messages of the requested size from its own thread,
+    // so that large messages do not fill the socket buffers while nobody reads them
+    let (request_sender, request_receiver) = std::sync::mpsc::channel::<(usize, u64)>();
+    let server_thread = std::thread::spawn(move || {
+        while let Ok((size, count)) = request_receiver.recv() {
+            let error = "A".repeat(size);
+            for _ in 0..count {
+                server
+                    .send_timeout(
+                        BootstrapServerMessage::BootstrapError {
+                            error: error.clone(),
+                        },
+                        None,
+                    )
+                    .unwrap();
+            }
+        }
+    });
+
+    let mut group = c.benchmark_group("bootstrap_binders");
+    for size in MESSAGE_SIZES {
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_function(BenchmarkId::from_parameter(size), |b| {
+            b.iter_custom(|count| {
+                let start = Instant::now();
+                request_sender.send((size, count)).unwrap();
+                for _ in 0..count {
+                    client.next_timeout(None).unwrap();
+                }
+                start.elapsed()
+            })
+        });
+    }
+    group.finish();
+
+    drop(request_sender);
+    server_thread.join().unwrap();
+}
+
+#[cfg(feature = "benchmarking")]
+criterion_group!(benches, criterion_benchmark);
+
+#[cfg(feature = "benchmarking")]
+criterion_main!(benches);
+
+#[cfg(not(feature = "benchmarking"))]
+fn main() {
+    println!("Please use the `--features benchmarking` flag to run this benchmark.");
+}
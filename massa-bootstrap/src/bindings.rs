@@ -5,8 +5,8 @@ use std::{
     time::{Duration, Instant},
 };
 
-pub(crate) use client::*;
-pub(crate) use server::*;
+pub use client::*;
+pub use server::*;
 
 trait BindingReadExact: io::Read {
     /// similar to std::io::Read::read_exact, but with a timeout that is function-global instead of per-individual-read
@@ -104,3 +104,26 @@ trait BindingWriteExact: io::Write {
     /// Internal helper
     fn set_write_timeout(&mut self, duration: Option<Duration>) -> Result<(), std::io::Error>;
 }
+
+/// Buffers above this capacity are released after a message instead of being kept for the next one
+const MAX_KEPT_BUFFER_CAPACITY: usize = 16 * 1024 * 1024;
+
+/// Buffers of a binder, reused from one message to the next instead of allocating new ones for each message
+#[derive(Default)]
+struct BinderBuffers {
+    /// serialized message, sent or received
+    msg: Vec<u8>,
+    /// data hashed to chain the messages, then bytes written to the stream
+    frame: Vec<u8>,
+}
+
+impl BinderBuffers {
+    /// Release the buffers grown by an unusually large message
+    fn shrink(&mut self) {
+        for buf in [&mut self.msg, &mut self.frame] {
+            if buf.capacity() > MAX_KEPT_BUFFER_CAPACITY {
+                *buf = Vec::new();
+            }
+        }
+    }
+}
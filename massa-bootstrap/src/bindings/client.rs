@@ -1,6 +1,6 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use crate::bindings::{BindingReadExact, BindingWriteExact};
+use crate::bindings::{BinderBuffers, BindingReadExact, BindingWriteExact};
 use crate::error::BootstrapError;
 use crate::messages::{
     BootstrapClientMessage, BootstrapClientMessageSerializer, BootstrapServerMessage,
@@ -26,6 +26,7 @@ pub struct BootstrapClientBinder {
     prev_message: Option<Hash>,
     version_serializer: VersionSerializer,
     cfg: BootstrapClientConfig,
+    buffers: BinderBuffers,
 }
 
 const KNOWN_PREFIX_LEN: usize = SIGNATURE_DESER_SIZE + MAX_BOOTSTRAP_MESSAGE_SIZE_BYTES;
@@ -57,6 +58,7 @@ impl BootstrapClientBinder {
             prev_message: None,
             version_serializer: VersionSerializer::new(),
             cfg,
+            buffers: BinderBuffers::default(),
         }
     }
 
@@ -86,6 +88,18 @@ impl BootstrapClientBinder {
     pub fn next_timeout(
         &mut self,
         duration: Option<Duration>,
+    ) -> Result<BootstrapServerMessage, BootstrapError> {
+        let mut buffers = std::mem::take(&mut self.buffers);
+        let result = self.next_with_buffers(duration, &mut buffers);
+        buffers.shrink();
+        self.buffers = buffers;
+        result
+    }
+
+    fn next_with_buffers(
+        &mut self,
+        duration: Option<Duration>,
+        buffers: &mut BinderBuffers,
     ) -> Result<BootstrapServerMessage, BootstrapError> {
         let deadline = duration.map(|d| Instant::now() + d);
 
@@ -106,16 +120,20 @@ impl BootstrapClientBinder {
         let message = {
             if let Some(prev_msg) = prev_msg {
                 // Consume the rest of the message from the stream
-                let mut stream_bytes = vec![0u8; msg_len as usize];
+                let msg_bytes = &mut buffers.msg;
+                msg_bytes.clear();
+                msg_bytes.resize(msg_len as usize, 0);
 
                 // TODO: handle a partial read
-                self.read_exact_timeout(&mut stream_bytes[..], deadline)
+                self.read_exact_timeout(msg_bytes, deadline)
                     .map_err(|(e, _consumed)| e)?;
-                let msg_bytes = &mut stream_bytes[..];
 
                 // prepend the received message with the previous messages hash, and derive the new hash.
                 // TODO: some sort of recovery if this fails?
-                let rehash_seed = &[prev_msg.to_bytes().as_slice(), msg_bytes].concat();
+                let rehash_seed = &mut buffers.frame;
+                rehash_seed.clear();
+                rehash_seed.extend(prev_msg.to_bytes());
+                rehash_seed.extend(msg_bytes.iter());
                 let msg_hash = Hash::compute_from(rehash_seed);
                 self.remote_pubkey.verify_signature(&msg_hash, &sig)?;
 
@@ -126,12 +144,13 @@ impl BootstrapClientBinder {
                 msg
             } else {
                 // Consume the rest of the message from the stream
-                let mut stream_bytes = vec![0u8; msg_len as usize];
+                let sig_msg_bytes = &mut buffers.msg;
+                sig_msg_bytes.clear();
+                sig_msg_bytes.resize(msg_len as usize, 0);
 
                 // TODO: handle a partial read
-                self.read_exact_timeout(&mut stream_bytes[..], deadline)
+                self.read_exact_timeout(sig_msg_bytes, deadline)
                     .map_err(|(e, _)| e)?;
-                let sig_msg_bytes = &mut stream_bytes[..];
 
                 // Compute the hash and verify
                 let msg_hash = Hash::compute_from(sig_msg_bytes);
@@ -153,32 +172,47 @@ impl BootstrapClientBinder {
         &mut self,
         msg: &BootstrapClientMessage,
         duration: Option<Duration>,
+    ) -> Result<(), BootstrapError> {
+        let mut buffers = std::mem::take(&mut self.buffers);
+        let result = self.send_with_buffers(msg, duration, &mut buffers);
+        buffers.shrink();
+        self.buffers = buffers;
+        result
+    }
+
+    fn send_with_buffers(
+        &mut self,
+        msg: &BootstrapClientMessage,
+        duration: Option<Duration>,
+        buffers: &mut BinderBuffers,
     ) -> Result<(), BootstrapError> {
         let deadline = duration.map(|d| Instant::now() + d);
-        let mut msg_bytes = Vec::new();
+        let msg_bytes = &mut buffers.msg;
+        msg_bytes.clear();
         let message_serializer = BootstrapClientMessageSerializer::new();
-        message_serializer.serialize(msg, &mut msg_bytes)?;
+        message_serializer.serialize(msg, msg_bytes)?;
         let msg_len: u32 = msg_bytes.len().try_into().map_err(|e| {
             BootstrapError::GeneralError(format!("bootstrap message too large to encode: {}", e))
         })?;
 
-        let mut write_buf = Vec::new();
+        let write_buf = &mut buffers.frame;
         if let Some(prev_message) = self.prev_message {
             // there was a previous message
             let prev_message = prev_message.to_bytes();
 
             // update current previous message to be hash(prev_msg_hash + msg)
-            let mut hash_data =
-                Vec::with_capacity(prev_message.len().saturating_add(msg_bytes.len()));
-            hash_data.extend(prev_message);
-            hash_data.extend(&msg_bytes);
-            self.prev_message = Some(Hash::compute_from(&hash_data));
+            write_buf.clear();
+            write_buf.extend(prev_message);
+            write_buf.extend(msg_bytes.iter());
+            self.prev_message = Some(Hash::compute_from(write_buf));
 
             // Provide the signature saved as the previous message
+            write_buf.clear();
             write_buf.extend(prev_message);
         } else {
             // No previous message, so we set the hash-chain genesis to the hash of the first msg
-            self.prev_message = Some(Hash::compute_from(&msg_bytes));
+            self.prev_message = Some(Hash::compute_from(msg_bytes));
+            write_buf.clear();
         }
 
         // Provide the message length
@@ -186,10 +220,10 @@ impl BootstrapClientBinder {
         write_buf.extend(&msg_len_bytes);
 
         // Provide the message
-        write_buf.extend(&msg_bytes);
+        write_buf.extend(msg_bytes.iter());
 
         // And send it off
-        self.write_all_timeout(&write_buf, deadline)
+        self.write_all_timeout(write_buf, deadline)
             .map_err(|(e, _)| e)?;
         Ok(())
     }
@@ -1,6 +1,6 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use crate::bindings::BindingReadExact;
+use crate::bindings::{BinderBuffers, BindingReadExact};
 use crate::error::BootstrapError;
 use crate::messages::{
     BootstrapClientMessage, BootstrapClientMessageDeserializer, BootstrapServerMessage,
@@ -24,7 +24,6 @@ use std::{
     thread,
     time::Duration,
 };
-// use stream_limiter::{Limiter, LimiterOptions};
 use tracing::error;
 
 use super::BindingWriteExact;
@@ -36,6 +35,52 @@ struct ClientMessageLeader {
     msg_len: u32,
 }
 
+/// Limits the bytes sent to a client, with a budget of at most one second of bytes
+struct RateLimiter {
+    /// bytes per second
+    limit: u64,
+    /// bytes that can be sent right away
+    available: u64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    fn new(limit: u64) -> Self {
+        RateLimiter {
+            limit,
+            available: limit,
+            last_refill: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let earned =
+            (now.duration_since(self.last_refill).as_secs_f64() * self.limit as f64) as u64;
+        if earned > 0 {
+            self.available = self.available.saturating_add(earned).min(self.limit);
+            self.last_refill = now;
+        }
+    }
+
+    /// Number of bytes out of `len` that can be sent,
+    /// waiting for the budget to allow a part of them if it is empty
+    fn acquire(&mut self, len: usize) -> usize {
+        self.refill();
+        if self.available == 0 {
+            let wanted = (len as u64).min(self.limit).max(1);
+            thread::sleep(Duration::from_secs_f64(wanted as f64 / self.limit as f64));
+            self.refill();
+        }
+        (len as u64).min(self.available) as usize
+    }
+
+    /// Spend the budget of the bytes sent
+    fn consume(&mut self, sent: usize) {
+        self.available = self.available.saturating_sub(sent as u64);
+    }
+}
+
 /// Bootstrap server binder
 pub struct BootstrapServerBinder {
     max_consensus_block_ids: u64,
@@ -43,11 +88,14 @@ pub struct BootstrapServerBinder {
     max_datastore_key_length: u8,
     randomness_size_bytes: usize,
     local_keypair: KeyPair,
-    duplex: TcpStream, //Limiter<TcpStream>,
+    duplex: TcpStream,
+    /// limit of the bytes per second sent to the client
+    limiter: Option<RateLimiter>,
     prev_message: Option<Hash>,
     version_serializer: VersionSerializer,
     version_deserializer: VersionDeserializer,
     write_error_timeout: MassaTime,
+    buffers: BinderBuffers,
 }
 
 impl BootstrapServerBinder {
@@ -56,13 +104,13 @@ impl BootstrapServerBinder {
     /// # Argument
     /// * `duplex`: duplex stream.
     /// * `local_keypair`: local node user keypair
-    /// * `limit`: limit max bytes per second (up and down)
+    /// * `rw_limit`: limit max bytes per second sent to the client
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         duplex: TcpStream,
         local_keypair: KeyPair,
         cfg: BootstrapSrvBindCfg,
-        _rw_limit: Option<u64>,
+        rw_limit: Option<u64>,
     ) -> Self {
         let BootstrapSrvBindCfg {
             max_bytes_read_write: _limit,
@@ -73,14 +121,13 @@ impl BootstrapServerBinder {
             write_error_timeout,
         } = cfg;
 
-        // let limit_opts = rw_limit.map(|limit| -> LimiterOptions {
-        //     LimiterOptions::new(limit, Duration::from_millis(1000), limit)
-        // });
-        // let duplex = Limiter::new(duplex, limit_opts.clone(), limit_opts);
         BootstrapServerBinder {
             max_consensus_block_ids: consensus_bootstrap_part_size,
             local_keypair,
             duplex,
+            limiter: rw_limit
+                .filter(|limit| *limit > 0 && *limit < u64::MAX)
+                .map(RateLimiter::new),
             prev_message: None,
             thread_count,
             max_datastore_key_length,
@@ -88,6 +135,7 @@ impl BootstrapServerBinder {
             version_serializer: VersionSerializer::new(),
             version_deserializer: VersionDeserializer::new(),
             write_error_timeout,
+            buffers: BinderBuffers::default(),
         }
     }
     /// Performs a handshake. Should be called after connection
@@ -122,6 +170,7 @@ impl BootstrapServerBinder {
         Ok(())
     }
 
+    /// Writes the next message, with a timeout error naming the message
     pub fn send_msg(
         &mut self,
         timeout: Duration,
@@ -175,6 +224,7 @@ impl BootstrapServerBinder {
             // it's an error at the OS level.
             .unwrap();
     }
+    /// Writes an error message, with the timeout of error messages
     pub fn send_error_timeout(&mut self, error: String) -> Result<(), BootstrapError> {
         self.send_timeout(
             BootstrapServerMessage::BootstrapError { error },
@@ -193,27 +243,41 @@ impl BootstrapServerBinder {
         &mut self,
         msg: BootstrapServerMessage,
         duration: Option<Duration>,
+    ) -> Result<(), BootstrapError> {
+        let mut buffers = std::mem::take(&mut self.buffers);
+        let result = self.send_with_buffers(msg, duration, &mut buffers);
+        buffers.shrink();
+        self.buffers = buffers;
+        result
+    }
+
+    fn send_with_buffers(
+        &mut self,
+        msg: BootstrapServerMessage,
+        duration: Option<Duration>,
+        buffers: &mut BinderBuffers,
     ) -> Result<(), BootstrapError> {
         let deadline = duration.map(|d| Instant::now() + d);
         // serialize the message to bytes
-        let mut msg_bytes = Vec::new();
-        BootstrapServerMessageSerializer::new().serialize(&msg, &mut msg_bytes)?;
+        let msg_bytes = &mut buffers.msg;
+        msg_bytes.clear();
+        BootstrapServerMessageSerializer::new().serialize(&msg, msg_bytes)?;
         let msg_len: u32 = msg_bytes.len().try_into().map_err(|e| {
             BootstrapError::GeneralError(format!("bootstrap message too large to encode: {}", e))
         })?;
 
         // compute signature, and extract the bytes
+        let stream_data = &mut buffers.frame;
         let sig = {
             if let Some(prev_message) = self.prev_message {
                 // there was a previous message: sign(prev_msg_hash + msg)
-                let mut signed_data =
-                    Vec::with_capacity(HASH_SIZE_BYTES.saturating_add(msg_len as usize));
-                signed_data.extend(prev_message.to_bytes());
-                signed_data.extend(&msg_bytes);
-                self.local_keypair.sign(&Hash::compute_from(&signed_data))?
+                stream_data.clear();
+                stream_data.extend(prev_message.to_bytes());
+                stream_data.extend(msg_bytes.iter());
+                self.local_keypair.sign(&Hash::compute_from(stream_data))?
             } else {
                 // there was no previous message: sign(msg)
-                self.local_keypair.sign(&Hash::compute_from(&msg_bytes))?
+                self.local_keypair.sign(&Hash::compute_from(msg_bytes))?
             }
         };
 
@@ -221,10 +285,13 @@ impl BootstrapServerBinder {
         let msg_len_bytes = msg_len.to_be_bytes_min(MAX_BOOTSTRAP_MESSAGE_SIZE)?;
 
         // organize the bytes into a sendable array
-        let stream_data = [sig.to_bytes().as_slice(), &msg_len_bytes, &msg_bytes].concat();
+        stream_data.clear();
+        stream_data.extend(sig.to_bytes());
+        stream_data.extend(&msg_len_bytes);
+        stream_data.extend(msg_bytes.iter());
 
         // send the data
-        self.write_all_timeout(&stream_data, deadline)
+        self.write_all_timeout(stream_data, deadline)
             .map_err(|(e, _)| e)?;
 
         // update prev sig
@@ -238,10 +305,22 @@ impl BootstrapServerBinder {
     pub fn next_timeout(
         &mut self,
         duration: Option<Duration>,
+    ) -> Result<BootstrapClientMessage, BootstrapError> {
+        let mut buffers = std::mem::take(&mut self.buffers);
+        let result = self.next_with_buffers(duration, &mut buffers);
+        buffers.shrink();
+        self.buffers = buffers;
+        result
+    }
+
+    fn next_with_buffers(
+        &mut self,
+        duration: Option<Duration>,
+        buffers: &mut BinderBuffers,
     ) -> Result<BootstrapClientMessage, BootstrapError> {
         let deadline = duration.map(|d| Instant::now() + d);
 
-        let mut known_len_buf = vec![0; KNOWN_PREFIX_LEN];
+        let mut known_len_buf = [0u8; KNOWN_PREFIX_LEN];
         // TODO: handle a partial read
         self.read_exact_timeout(&mut known_len_buf, deadline)
             .map_err(|(err, _consumed)| err)?;
@@ -252,8 +331,10 @@ impl BootstrapServerBinder {
         } = self.decode_message_leader(&known_len_buf)?;
 
         // read the rest of the message
-        let mut msg_bytes = vec![0u8; msg_len as usize];
-        self.read_exact_timeout(&mut msg_bytes, deadline)
+        let msg_bytes = &mut buffers.msg;
+        msg_bytes.clear();
+        msg_bytes.resize(msg_len as usize, 0);
+        self.read_exact_timeout(msg_bytes, deadline)
             .map_err(|(err, _consumed)| err)?;
 
         // check previous hash
@@ -266,14 +347,14 @@ impl BootstrapServerBinder {
         // update previous hash
         if let Some(prev_hash) = received_prev_hash {
             // there was a previous message: hash(prev_hash + message)
-            let mut hashed_bytes =
-                Vec::with_capacity(HASH_SIZE_BYTES.saturating_add(msg_bytes.len()));
+            let hashed_bytes = &mut buffers.frame;
+            hashed_bytes.clear();
             hashed_bytes.extend(prev_hash.to_bytes());
-            hashed_bytes.extend(&msg_bytes);
-            self.prev_message = Some(Hash::compute_from(&hashed_bytes));
+            hashed_bytes.extend(msg_bytes.iter());
+            self.prev_message = Some(Hash::compute_from(hashed_bytes));
         } else {
             // no previous message: hash message only
-            self.prev_message = Some(Hash::compute_from(&msg_bytes));
+            self.prev_message = Some(Hash::compute_from(msg_bytes));
         }
 
         // deserialize message
@@ -282,7 +363,7 @@ impl BootstrapServerBinder {
             self.max_datastore_key_length,
             self.max_consensus_block_ids,
         )
-        .deserialize::<DeserializeError>(&msg_bytes)
+        .deserialize::<DeserializeError>(msg_bytes)
         .map_err(|err| BootstrapError::GeneralError(format!("{}", err)))?;
 
         Ok(msg)
@@ -332,7 +413,13 @@ impl crate::bindings::BindingReadExact for BootstrapServerBinder {
 
 impl io::Write for BootstrapServerBinder {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        self.duplex.write(buf)
+        let Some(limiter) = self.limiter.as_mut() else {
+            return self.duplex.write(buf);
+        };
+        let allowed = limiter.acquire(buf.len());
+        let sent = self.duplex.write(&buf[..allowed])?;
+        limiter.consume(sent);
+        Ok(sent)
     }
 
     fn flush(&mut self) -> io::Result<()> {
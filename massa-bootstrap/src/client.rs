@@ -1,7 +1,8 @@
 use humantime::format_duration;
 use massa_db_exports::DBBatch;
 use massa_final_state::{FinalState, FinalStateError};
-use massa_logging::massa_trace;
+use massa_hash::{HashXof, HASH_XOF_SIZE_BYTES};
+use massa_logging::{massa_journal, massa_trace};
 use massa_models::{node::NodeId, slot::Slot, streaming_step::StreamingStep, version::Version};
 use massa_signature::PublicKey;
 use massa_time::MassaTime;
@@ -13,17 +14,18 @@ use rand::{
 };
 use std::collections::BTreeMap;
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     io,
     net::{SocketAddr, TcpStream},
     sync::{Arc, Condvar, Mutex},
-    time::Duration,
+    time::{Duration, Instant},
 };
 use tracing::{debug, info, warn};
 
 use crate::{
     bindings::BootstrapClientBinder,
     error::BootstrapError,
+    manifest::BootstrapManifest,
     messages::{BootstrapClientMessage, BootstrapServerMessage},
     settings::IpType,
     BootstrapConfig, GlobalBootstrapState,
@@ -64,10 +66,12 @@ impl BSConnector for DefaultConnector {
 /// This function will send the starting point to receive a stream of the ledger and will receive and process each part until receive a `BootstrapServerMessage::FinalStateFinished` message from the server.
 /// `next_bootstrap_message` passed as parameter must be `BootstrapClientMessage::AskFinalStatePart` enum variant.
 /// `next_bootstrap_message` will be updated after receiving each part so that in case of connection lost we can restart from the last message we processed.
+/// Each part is checked against its hash before being written, and recorded in `manifest` so that a restarted node can resume from it.
 fn stream_final_state_and_consensus(
     cfg: &BootstrapConfig,
     client: &mut BootstrapClientBinder,
     next_bootstrap_message: &mut BootstrapClientMessage,
+    manifest: &mut Option<BootstrapManifest>,
     global_bootstrap_state: &mut GlobalBootstrapState,
 ) -> Result<(), BootstrapError> {
     if let BootstrapClientMessage::AskBootstrapPart { .. } = &next_bootstrap_message {
@@ -132,6 +136,22 @@ fn stream_final_state_and_consensus(
                             .collect(),
                     );
 
+                    // Record the chunk written to disk, in case the node restarts
+                    let manifest = manifest.get_or_insert_with(|| BootstrapManifest {
+                        last_slot: slot,
+                        last_state_step: StreamingStep::Started,
+                        last_versioning_step: StreamingStep::Started,
+                        chunk_count: 0,
+                    });
+                    manifest.record_chunk(
+                        slot,
+                        last_state_step.clone(),
+                        last_versioning_step.clone(),
+                    );
+                    if let Err(err) = manifest.save(&cfg.manifest_path) {
+                        warn!("could not save the bootstrap manifest: {}", err);
+                    }
+
                     // Set new message in case of disconnection
                     *next_bootstrap_message = BootstrapClientMessage::AskBootstrapPart {
                         last_slot: Some(slot),
@@ -166,15 +186,11 @@ fn stream_final_state_and_consensus(
                 }
                 BootstrapServerMessage::SlotTooOld => {
                     info!("Slot is too old retry bootstrap from scratch");
-                    *next_bootstrap_message = BootstrapClientMessage::AskBootstrapPart {
-                        last_slot: None,
-                        last_state_step: StreamingStep::Started,
-                        last_versioning_step: StreamingStep::Started,
-                        last_consensus_step: StreamingStep::Started,
-                        send_last_start_period: true,
-                    };
+                    *next_bootstrap_message = initial_bootstrap_message();
                     let mut write_final_state = global_bootstrap_state.final_state.write();
-                    write_final_state.reset();
+                    write_final_state.reset()?;
+                    *manifest = None;
+                    BootstrapManifest::remove(&cfg.manifest_path);
                     return Err(BootstrapError::GeneralError(String::from("Slot too old")));
                 }
                 // At this point, we have successfully received the next message from the server, and it's an error-message String
@@ -196,17 +212,13 @@ fn stream_final_state_and_consensus(
     }
 }
 
-/// Gets the state from a bootstrap server (internal private function)
-/// needs to be CANCELLABLE
-fn bootstrap_from_server(
+/// Checks the error sent by the server at connection, then performs the handshake
+/// and checks the version and the clock of the server
+fn handshake_with_server(
     cfg: &BootstrapConfig,
     client: &mut BootstrapClientBinder,
-    next_bootstrap_message: &mut BootstrapClientMessage,
-    global_bootstrap_state: &mut GlobalBootstrapState,
     our_version: Version,
 ) -> Result<(), BootstrapError> {
-    massa_trace!("bootstrap.lib.bootstrap_from_server", {});
-
     // read error (if sent by the server)
     // client.next() is not cancel-safe but we drop the whole client object if cancelled => it's OK
     match client.next_timeout(Some(cfg.read_error_timeout.to_duration())) {
@@ -284,6 +296,22 @@ fn bootstrap_from_server(
         );
         return Err(BootstrapError::ClockError(message));
     }
+    Ok(())
+}
+
+/// Gets the state from a bootstrap server (internal private function)
+/// needs to be CANCELLABLE
+fn bootstrap_from_server(
+    cfg: &BootstrapConfig,
+    client: &mut BootstrapClientBinder,
+    next_bootstrap_message: &mut BootstrapClientMessage,
+    manifest: &mut Option<BootstrapManifest>,
+    global_bootstrap_state: &mut GlobalBootstrapState,
+    our_version: Version,
+) -> Result<(), BootstrapError> {
+    massa_trace!("bootstrap.lib.bootstrap_from_server", {});
+
+    handshake_with_server(cfg, client, our_version)?;
 
     let write_timeout: std::time::Duration = cfg.write_timeout.into();
     // Loop to ask data to the server depending on the last message we sent
@@ -294,6 +322,7 @@ fn bootstrap_from_server(
                     cfg,
                     client,
                     next_bootstrap_message,
+                    manifest,
                     global_bootstrap_state,
                 )?;
             }
@@ -321,12 +350,168 @@ fn bootstrap_from_server(
             BootstrapClientMessage::BootstrapError { error: _ } => {
                 panic!("The next message to send shouldn't be BootstrapError");
             }
+            BootstrapClientMessage::AskStateHash { .. } => {
+                panic!("The next message to send shouldn't be AskStateHash");
+            }
         };
     }
     info!("Successful bootstrap");
     Ok(())
 }
 
+/// Asks a server for its final state hash right after the changes of `change_id`
+fn ask_state_hash(
+    cfg: &BootstrapConfig,
+    client: &mut BootstrapClientBinder,
+    change_id: Slot,
+    our_version: Version,
+) -> Result<Option<HashXof<HASH_XOF_SIZE_BYTES>>, BootstrapError> {
+    handshake_with_server(cfg, client, our_version)?;
+
+    let write_timeout: Duration = cfg.write_timeout.into();
+    let state_hash = match send_client_message(
+        &BootstrapClientMessage::AskStateHash { change_id },
+        client,
+        write_timeout,
+        cfg.read_timeout.into(),
+        "ask state hash timed out",
+    )? {
+        BootstrapServerMessage::StateHash {
+            change_id: received_change_id,
+            state_hash,
+        } if received_change_id == change_id => state_hash,
+        BootstrapServerMessage::BootstrapError { error } => {
+            return Err(BootstrapError::ReceivedError(error))
+        }
+        other => return Err(BootstrapError::UnexpectedServerMessage(other)),
+    };
+    client.send_timeout(
+        &BootstrapClientMessage::BootstrapSuccess,
+        Some(write_timeout),
+    )?;
+    Ok(state_hash)
+}
+
+/// Asks the `servers` one after the other for their state hash at `change_id` with `ask`,
+/// until `quorum` of them answer `state_hash`.
+///
+/// Returns the number of servers that answered `state_hash`: an error, a missing hash
+/// or a different hash does not count.
+pub(crate) fn count_agreeing_servers(
+    servers: &[(SocketAddr, NodeId)],
+    quorum: usize,
+    change_id: Slot,
+    state_hash: HashXof<HASH_XOF_SIZE_BYTES>,
+    mut ask: impl FnMut(
+        &SocketAddr,
+        &NodeId,
+    ) -> Result<Option<HashXof<HASH_XOF_SIZE_BYTES>>, BootstrapError>,
+) -> usize {
+    let mut agreeing = 0;
+    for (addr, node_id) in servers {
+        if agreeing >= quorum {
+            break;
+        }
+        match ask(addr, node_id) {
+            Ok(Some(server_hash)) if server_hash == state_hash => agreeing += 1,
+            Ok(Some(server_hash)) => {
+                warn!(
+                    "Bootstrap server {} has the state hash {} at slot {}, but the bootstrapped state has {}",
+                    addr, server_hash, change_id, state_hash
+                );
+                massa_journal!("state_hash_mismatch", {
+                    "server": addr.to_string(),
+                    "slot": change_id.to_string()
+                });
+            }
+            Ok(None) => debug!(
+                "Bootstrap server {} has no state hash at slot {}",
+                addr, change_id
+            ),
+            Err(e) => warn!(
+                "Could not get the state hash of bootstrap server {}: {}",
+                addr, e
+            ),
+        }
+    }
+    agreeing
+}
+
+/// Checks that at least `state_hash_quorum` bootstrap servers, other than the one we bootstrapped from,
+/// have the same state hash as the one computed on the bootstrapped state, at the slot it is attached to.
+///
+/// The servers only keep the hashes of their last slots, so they are asked right after the bootstrap.
+/// A server refuses a new session from our IP during `per_ip_min_interval` after the previous one:
+/// the servers contacted more recently than that are not asked.
+fn cross_verify_state(
+    cfg: &BootstrapConfig,
+    connector: &mut impl BSConnector,
+    bootstrap_list: &[(SocketAddr, NodeId)],
+    bootstrap_node_id: &NodeId,
+    final_state: &Arc<RwLock<FinalState>>,
+    our_version: Version,
+    last_sessions: &mut HashMap<SocketAddr, Instant>,
+) -> Result<(), BootstrapError> {
+    let (change_id, state_hash) = {
+        let final_state = final_state.read();
+        let db = final_state.db.read();
+        (db.get_change_id()?, db.get_xof_db_hash())
+    };
+
+    let min_interval = cfg.per_ip_min_interval.to_duration();
+    let now = Instant::now();
+    let servers: Vec<(SocketAddr, NodeId)> = bootstrap_list
+        .iter()
+        .filter(|(addr, node_id)| {
+            if node_id == bootstrap_node_id {
+                return false;
+            }
+            match last_sessions.get(addr) {
+                Some(last) if now.saturating_duration_since(*last) < min_interval => {
+                    debug!(
+                        "Not asking bootstrap server {} for its state hash: it was contacted less than {} ago",
+                        addr,
+                        format_duration(min_interval)
+                    );
+                    false
+                }
+                _ => true,
+            }
+        })
+        .cloned()
+        .collect();
+
+    let agreeing = count_agreeing_servers(
+        &servers,
+        cfg.state_hash_quorum,
+        change_id,
+        state_hash,
+        |addr, node_id| {
+            last_sessions.insert(*addr, Instant::now());
+            connect_to_server(
+                connector,
+                cfg,
+                addr,
+                &node_id.get_public_key(),
+                Some(cfg.max_bytes_read_write),
+            )
+            .and_then(|mut client| ask_state_hash(cfg, &mut client, change_id, our_version))
+        },
+    );
+
+    if agreeing < cfg.state_hash_quorum {
+        return Err(BootstrapError::GeneralError(format!(
+            "only {} other bootstrap servers out of the {} required confirm the state hash at slot {}",
+            agreeing, cfg.state_hash_quorum, change_id
+        )));
+    }
+    info!(
+        "{} other bootstrap servers confirm the state hash at slot {}",
+        agreeing, change_id
+    );
+    Ok(())
+}
+
 fn send_client_message(
     message_to_send: &BootstrapClientMessage,
     client: &mut BootstrapClientBinder,
@@ -389,6 +574,17 @@ fn filter_bootstrap_list(
     filtered_bootstrap_list
 }
 
+/// Message asking for the first part of the state
+fn initial_bootstrap_message() -> BootstrapClientMessage {
+    BootstrapClientMessage::AskBootstrapPart {
+        last_slot: None,
+        last_state_step: StreamingStep::Started,
+        last_versioning_step: StreamingStep::Started,
+        last_consensus_step: StreamingStep::Started,
+        send_last_start_period: true,
+    }
+}
+
 /// Uses the cond-var pattern to handle sig-int cancellation.
 /// Make sure that the passed in `interrupted` shares its Arc
 /// with a sig-int handler setup.
@@ -450,7 +646,8 @@ pub fn get_state(
             final_state_guard
                 .db
                 .write()
-                .write_batch(batch, db_versioning_batch, Some(slot));
+                .write_batch(batch, db_versioning_batch, Some(slot))
+                .map_err(|e| BootstrapError::GeneralError(e.to_string()))?;
         }
         return Ok(GlobalBootstrapState::new(final_state));
     }
@@ -459,17 +656,31 @@ pub fn get_state(
     // we filter the bootstrap list to keep only the ip addresses we are compatible with
     let filtered_bootstrap_list = get_bootstrap_list_iter(bootstrap_config)?;
 
-    let mut next_bootstrap_message: BootstrapClientMessage =
-        BootstrapClientMessage::AskBootstrapPart {
-            last_slot: None,
-            last_state_step: StreamingStep::Started,
-            last_versioning_step: StreamingStep::Started,
-            last_consensus_step: StreamingStep::Started,
-            send_last_start_period: true,
-        };
+    // resume an interrupted bootstrap if the ledger is still at the last chunk written
+    let mut manifest = BootstrapManifest::load(&bootstrap_config.manifest_path);
+    if let Some(loaded) = &manifest {
+        let db_slot = final_state.read().db.read().get_change_id().ok();
+        if db_slot != Some(loaded.last_slot) {
+            warn!("the ledger does not match the manifest of the interrupted bootstrap, bootstrapping from scratch");
+            final_state.write().reset()?;
+            manifest = None;
+        }
+    }
+    let mut next_bootstrap_message = match &manifest {
+        Some(manifest) => {
+            info!(
+                "Resuming the interrupted bootstrap after {} chunks",
+                manifest.chunk_count
+            );
+            manifest.resume_message()
+        }
+        None => initial_bootstrap_message(),
+    };
     let mut global_bootstrap_state = GlobalBootstrapState::new(final_state);
 
     let limit = bootstrap_config.max_bytes_read_write;
+    // last session opened with each server, as they refuse the sessions too close to each other
+    let mut last_sessions: HashMap<SocketAddr, Instant> = HashMap::new();
     loop {
         // check for interuption
         if *interupted.0.lock().expect("double-lock on interupt-mutex") {
@@ -484,6 +695,7 @@ pub fn get_state(
                 }
             }
             info!("Start bootstrapping from {}", addr);
+            last_sessions.insert(*addr, Instant::now());
             let conn = connect_to_server(
                 &mut connector,
                 bootstrap_config,
@@ -497,9 +709,16 @@ pub fn get_state(
                         bootstrap_config,
                         &mut client,
                         &mut next_bootstrap_message,
+                        &mut manifest,
                         &mut global_bootstrap_state,
                         version,
                     );
+                    if let Err(e) = &bs {
+                        massa_journal!("bootstrap_attempt", {
+                            "server": addr.to_string(),
+                            "error": e.to_string()
+                        });
+                    }
                     // cancellable
                     match bs {
                         Err(BootstrapError::ReceivedError(error)) => {
@@ -515,11 +734,50 @@ pub fn get_state(
                                 Some(bootstrap_config.write_error_timeout.into()),
                             );
                         }
-                        Ok(()) => return Ok(global_bootstrap_state),
+                        Ok(()) => {
+                            let verified = if bootstrap_config.state_hash_quorum > 0 {
+                                cross_verify_state(
+                                    bootstrap_config,
+                                    &mut connector,
+                                    &filtered_bootstrap_list,
+                                    node_id,
+                                    &global_bootstrap_state.final_state,
+                                    version,
+                                    &mut last_sessions,
+                                )
+                            } else {
+                                Ok(())
+                            };
+                            match verified {
+                                Ok(()) => {
+                                    massa_journal!("bootstrap_attempt", { "server": addr.to_string() });
+                                    BootstrapManifest::remove(&bootstrap_config.manifest_path);
+                                    return Ok(global_bootstrap_state);
+                                }
+                                Err(e) => {
+                                    warn!("The state bootstrapped from {} is not confirmed by the other servers: {}", addr, e);
+                                    massa_journal!("bootstrap_attempt", {
+                                        "server": addr.to_string(),
+                                        "error": e.to_string()
+                                    });
+                                    // bootstrap again from scratch from another server
+                                    global_bootstrap_state.final_state.write().reset()?;
+                                    global_bootstrap_state.graph = None;
+                                    global_bootstrap_state.peers = None;
+                                    manifest = None;
+                                    BootstrapManifest::remove(&bootstrap_config.manifest_path);
+                                    next_bootstrap_message = initial_bootstrap_message();
+                                }
+                            }
+                        }
                     }
                 }
                 Err(e) => {
                     warn!("Error while connecting to bootstrap server: {}", e);
+                    massa_journal!("bootstrap_attempt", {
+                        "server": addr.to_string(),
+                        "error": e.to_string()
+                    });
                 }
             };
 
@@ -26,6 +26,7 @@ mod client;
 mod error;
 pub use error::BootstrapError;
 mod listener;
+mod manifest;
 mod messages;
 mod server;
 mod settings;
@@ -41,6 +42,14 @@ pub use server::{start_bootstrap_server, BootstrapManager};
 pub use settings::IpType;
 pub use settings::{BootstrapConfig, BootstrapServerMessageDeserializerArgs};
 
+#[cfg(feature = "benchmarking")]
+pub use bindings::{BootstrapClientBinder, BootstrapServerBinder};
+#[cfg(feature = "benchmarking")]
+pub use settings::{BootstrapClientConfig, BootstrapSrvBindCfg};
+
+#[cfg(feature = "benchmarking")]
+use criterion as _;
+
 #[cfg(test)]
 pub(crate) mod tests;
 
@@ -0,0 +1,95 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+//! Manifest of the state chunks received during a bootstrap.
+//!
+//! The messages of the server are signed and chained by the binder, so a chunk that reaches the
+//! client is the one the server sent. After writing each chunk to disk, the client records the
+//! cursors to ask for the next one in a manifest, so that an interrupted bootstrap resumes from
+//! the last chunk written: with another server after a connection loss, or after a restart of
+//! the node, which keeps the partially bootstrapped ledger while a manifest exists.
+//! The state received this way is then checked as a whole against the other servers.
+//!
+//! The manifest only holds these cursors, so it stays small however many chunks were received.
+
+use std::{fs, path::Path};
+
+use massa_models::{slot::Slot, streaming_step::StreamingStep};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::messages::BootstrapClientMessage;
+
+/// Progress of an ongoing bootstrap: the cursors to ask for the chunk following the last one written
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BootstrapManifest {
+    /// Slot the received state is attached to
+    pub last_slot: Slot,
+    /// Last received state key
+    pub last_state_step: StreamingStep<Vec<u8>>,
+    /// Last received versioning key
+    pub last_versioning_step: StreamingStep<Vec<u8>>,
+    /// Number of chunks written to disk
+    pub chunk_count: u64,
+}
+
+impl BootstrapManifest {
+    /// Load the manifest of an interrupted bootstrap, if there is one
+    pub fn load(path: &Path) -> Option<Self> {
+        let content = fs::read_to_string(path).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(manifest) => Some(manifest),
+            Err(err) => {
+                warn!(
+                    "ignoring the invalid bootstrap manifest {}: {}",
+                    path.display(),
+                    err
+                );
+                None
+            }
+        }
+    }
+
+    /// Write the manifest to disk. The previous manifest is only replaced once the new one is fully written.
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, serde_json::to_vec(self)?)?;
+        fs::rename(tmp_path, path)
+    }
+
+    /// Delete the manifest once the bootstrap is over
+    pub fn remove(path: &Path) {
+        if let Err(err) = fs::remove_file(path) {
+            if err.kind() != std::io::ErrorKind::NotFound {
+                warn!(
+                    "could not remove the bootstrap manifest {}: {}",
+                    path.display(),
+                    err
+                );
+            }
+        }
+    }
+
+    /// Record a chunk written to disk, and the cursors to ask for the next one
+    pub fn record_chunk(
+        &mut self,
+        slot: Slot,
+        last_state_step: StreamingStep<Vec<u8>>,
+        last_versioning_step: StreamingStep<Vec<u8>>,
+    ) {
+        self.chunk_count = self.chunk_count.saturating_add(1);
+        self.last_slot = slot;
+        self.last_state_step = last_state_step;
+        self.last_versioning_step = last_versioning_step;
+    }
+
+    /// Message asking for the chunk following the last one written.
+    /// The consensus graph is kept in memory only: it is streamed again from the start.
+    pub fn resume_message(&self) -> BootstrapClientMessage {
+        BootstrapClientMessage::AskBootstrapPart {
+            last_slot: Some(self.last_slot),
+            last_state_step: self.last_state_step.clone(),
+            last_versioning_step: self.last_versioning_step.clone(),
+            last_consensus_step: StreamingStep::Started,
+            send_last_start_period: true,
+        }
+    }
+}
@@ -5,6 +5,7 @@ use massa_consensus_exports::bootstrapable_graph::{
     BootstrapableGraph, BootstrapableGraphDeserializer, BootstrapableGraphSerializer,
 };
 use massa_db_exports::StreamBatch;
+use massa_hash::{HashXof, HashXofDeserializer, HashXofSerializer, HASH_XOF_SIZE_BYTES};
 use massa_models::block_id::{BlockId, BlockIdDeserializer, BlockIdSerializer};
 use massa_models::prehash::PreHashSet;
 use massa_models::serialization::{
@@ -78,6 +79,14 @@ pub enum BootstrapServerMessage {
         /// Error message
         error: String,
     },
+    /// Final state hash at a given slot, to cross-verify a state bootstrapped from another server
+    StateHash {
+        /// Slot asked by the client
+        change_id: Slot,
+        /// Final state hash right after the slot, `None` if the server has not reached the slot
+        /// or no longer remembers its hash
+        state_hash: Option<HashXof<HASH_XOF_SIZE_BYTES>>,
+    },
 }
 
 impl ToString for BootstrapServerMessage {
@@ -91,6 +100,7 @@ impl ToString for BootstrapServerMessage {
             BootstrapServerMessage::BootstrapError { error } => {
                 format!("BootstrapError {{ error: {} }}", error)
             }
+            BootstrapServerMessage::StateHash { .. } => "StateHash".to_string(),
         }
     }
 }
@@ -104,6 +114,7 @@ enum MessageServerTypeId {
     FinalStateFinished = 3u32,
     SlotTooOld = 4u32,
     BootstrapError = 5u32,
+    StateHash = 6u32,
 }
 
 /// Serializer for `BootstrapServerMessage`
@@ -118,6 +129,7 @@ pub struct BootstrapServerMessageSerializer {
     vec_u8_serializer: VecU8Serializer,
     opt_vec_u8_serializer: OptionSerializer<Vec<u8>, VecU8Serializer>,
     slot_serializer: SlotSerializer,
+    opt_hash_xof_serializer: OptionSerializer<HashXof<HASH_XOF_SIZE_BYTES>, HashXofSerializer>,
     opt_last_start_period_serializer: OptionSerializer<u64, U64VarIntSerializer>,
     opt_last_slot_before_downtime_serializer:
         OptionSerializer<Option<Slot>, OptionSerializer<Slot, SlotSerializer>>,
@@ -143,6 +155,7 @@ impl BootstrapServerMessageSerializer {
             vec_u8_serializer: VecU8Serializer::new(),
             opt_vec_u8_serializer: OptionSerializer::new(VecU8Serializer::new()),
             slot_serializer: SlotSerializer::new(),
+            opt_hash_xof_serializer: OptionSerializer::new(HashXofSerializer::new()),
             opt_last_start_period_serializer: OptionSerializer::new(U64VarIntSerializer::new()),
             opt_last_slot_before_downtime_serializer: OptionSerializer::new(OptionSerializer::new(
                 SlotSerializer::new(),
@@ -268,6 +281,15 @@ impl Serializer<BootstrapServerMessage> for BootstrapServerMessageSerializer {
                 )?;
                 buffer.extend(error.as_bytes())
             }
+            BootstrapServerMessage::StateHash {
+                change_id,
+                state_hash,
+            } => {
+                self.u32_serializer
+                    .serialize(&u32::from(MessageServerTypeId::StateHash), buffer)?;
+                self.slot_serializer.serialize(change_id, buffer)?;
+                self.opt_hash_xof_serializer.serialize(state_hash, buffer)?;
+            }
         }
         Ok(())
     }
@@ -287,6 +309,8 @@ pub struct BootstrapServerMessageDeserializer {
     block_id_set_deserializer: PreHashSetDeserializer<BlockId, BlockIdDeserializer>,
     length_bootstrap_error: U64VarIntDeserializer,
     slot_deserializer: SlotDeserializer,
+    opt_hash_xof_deserializer:
+        OptionDeserializer<HashXof<HASH_XOF_SIZE_BYTES>, HashXofDeserializer>,
     opt_last_start_period_deserializer: OptionDeserializer<u64, U64VarIntDeserializer>,
     opt_last_slot_before_downtime_deserializer:
         OptionDeserializer<Option<Slot>, OptionDeserializer<Slot, SlotDeserializer>>,
@@ -340,6 +364,7 @@ impl BootstrapServerMessageDeserializer {
                 (Included(0), Included(u64::MAX)),
                 (Included(0), Excluded(args.thread_count)),
             ),
+            opt_hash_xof_deserializer: OptionDeserializer::new(HashXofDeserializer::new()),
             opt_last_start_period_deserializer: OptionDeserializer::new(
                 U64VarIntDeserializer::new(Included(u64::MIN), Included(u64::MAX)),
             ),
@@ -575,6 +600,21 @@ impl Deserializer<BootstrapServerMessage> for BootstrapServerMessageDeserializer
                     error: String::from_utf8_lossy(error).into_owned(),
                 })
                 .parse(input),
+                MessageServerTypeId::StateHash => tuple((
+                    context("Failed change_id deserialization", |input| {
+                        self.slot_deserializer.deserialize(input)
+                    }),
+                    context("Failed state_hash deserialization", |input| {
+                        self.opt_hash_xof_deserializer.deserialize(input)
+                    }),
+                ))
+                .map(
+                    |(change_id, state_hash)| BootstrapServerMessage::StateHash {
+                        change_id,
+                        state_hash,
+                    },
+                )
+                .parse(input),
             }
         })
         .parse(buffer)
@@ -607,6 +647,11 @@ pub enum BootstrapClientMessage {
     },
     /// Bootstrap succeed
     BootstrapSuccess,
+    /// Ask for the final state hash at a given slot, to cross-verify a bootstrapped state
+    AskStateHash {
+        /// Slot the bootstrapped state is attached to
+        change_id: Slot,
+    },
 }
 
 #[derive(IntoPrimitive, Debug, Eq, PartialEq, TryFromPrimitive)]
@@ -616,6 +661,7 @@ enum MessageClientTypeId {
     AskFinalStatePart = 1u32,
     BootstrapError = 2u32,
     BootstrapSuccess = 3u32,
+    AskStateHash = 4u32,
 }
 
 /// Serializer for `BootstrapClientMessage`
@@ -711,6 +757,11 @@ impl Serializer<BootstrapClientMessage> for BootstrapClientMessageSerializer {
                 self.u32_serializer
                     .serialize(&u32::from(MessageClientTypeId::BootstrapSuccess), buffer)?;
             }
+            BootstrapClientMessage::AskStateHash { change_id } => {
+                self.u32_serializer
+                    .serialize(&u32::from(MessageClientTypeId::AskStateHash), buffer)?;
+                self.slot_serializer.serialize(change_id, buffer)?;
+            }
         }
         Ok(())
     }
@@ -864,6 +915,13 @@ impl Deserializer<BootstrapClientMessage> for BootstrapClientMessageDeserializer
                 MessageClientTypeId::BootstrapSuccess => {
                     Ok((input, BootstrapClientMessage::BootstrapSuccess))
                 }
+                MessageClientTypeId::AskStateHash => {
+                    context("Failed change_id deserialization", |input| {
+                        self.slot_deserializer.deserialize(input)
+                    })
+                    .map(|change_id| BootstrapClientMessage::AskStateHash { change_id })
+                    .parse(input)
+                }
             }
         })
         .parse(buffer)
@@ -29,7 +29,7 @@ mod white_black_list;
 use crossbeam::channel::tick;
 use humantime::format_duration;
 use massa_consensus_exports::{bootstrapable_graph::BootstrapableGraph, ConsensusController};
-use massa_db_exports::CHANGE_ID_DESER_ERROR;
+use massa_db_exports::{MassaDBError, CHANGE_ID_DESER_ERROR};
 use massa_final_state::FinalState;
 use massa_logging::massa_trace;
 use massa_models::{
@@ -472,8 +472,6 @@ pub fn stream_bootstrap_information(
         let last_start_period;
         let last_slot_before_downtime;
 
-        let slot_too_old = false;
-
         // Scope of the final state read
         {
             let final_state_read = final_state.read();
@@ -489,13 +487,24 @@ pub fn stream_bootstrap_information(
                 None
             };
 
-            state_part = final_state_read
+            let state_part_result = final_state_read
                 .db
                 .read()
-                .get_batch_to_stream(&last_state_step, last_slot)
-                .map_err(|e| {
-                    BootstrapError::GeneralError(format!("Error get_batch_to_stream: {}", e))
-                })?;
+                .get_batch_to_stream(&last_state_step, last_slot);
+            state_part = match state_part_result {
+                Ok(state_part) => state_part,
+                // the changes since the cursor of the client are no longer in our history
+                Err(MassaDBError::ChangeIdTooOld(_)) => {
+                    drop(final_state_read);
+                    return server.send_msg(write_timeout, BootstrapServerMessage::SlotTooOld);
+                }
+                Err(e) => {
+                    return Err(BootstrapError::GeneralError(format!(
+                        "Error get_batch_to_stream: {}",
+                        e
+                    )))
+                }
+            };
 
             let new_state_step = match (&last_state_step, state_part.is_empty()) {
                 // We already finished streaming the state
@@ -596,10 +605,6 @@ pub fn stream_bootstrap_information(
             send_last_start_period = false;
         }
 
-        if slot_too_old {
-            return server.send_msg(write_timeout, BootstrapServerMessage::SlotTooOld);
-        }
-
         // Setup final state global cursor
         let final_state_global_step =
             if last_state_step.finished() && last_versioning_step.finished() {
@@ -761,6 +766,19 @@ fn manage_bootstrap(
                         bootstrap_config.write_timeout.to_duration(),
                     )?;
                 }
+                BootstrapClientMessage::AskStateHash { change_id } => {
+                    let Some(write_timeout) = step_timeout_duration(&deadline, &bootstrap_config.write_timeout.to_duration()) else {
+                        return Err(BootstrapError::Interupted("insufficient time left to respond to the request for the state hash".to_string()));
+                    };
+                    let state_hash = final_state.read().db.read().get_xof_db_hash_at(change_id);
+                    server.send_msg(
+                        write_timeout,
+                        BootstrapServerMessage::StateHash {
+                            change_id,
+                            state_hash,
+                        },
+                    )?;
+                }
                 BootstrapClientMessage::BootstrapSuccess => break Ok(()),
                 BootstrapClientMessage::BootstrapError { error } => {
                     break Err(BootstrapError::ReceivedError(error));
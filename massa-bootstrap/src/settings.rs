@@ -55,6 +55,12 @@ pub struct BootstrapConfig {
     pub cache_duration: MassaTime,
     /// Keep ledger or not if not bootstrap
     pub keep_ledger: bool,
+    /// Path to the manifest of the state chunks received, kept while a bootstrap is ongoing
+    /// so that an interrupted bootstrap resumes from the last chunk written
+    pub manifest_path: PathBuf,
+    /// Number of bootstrap servers, other than the one we bootstrapped from, that must have the
+    /// same state hash as the bootstrapped state before it is accepted. 0 trusts the bootstrap server alone.
+    pub state_hash_quorum: usize,
     /// Max simultaneous bootstraps
     pub max_simultaneous_bootstraps: u32,
     /// Minimum interval between two bootstrap attempts from a given IP
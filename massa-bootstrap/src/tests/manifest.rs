@@ -0,0 +1,51 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use crate::manifest::BootstrapManifest;
+use crate::messages::BootstrapClientMessage;
+use massa_models::{slot::Slot, streaming_step::StreamingStep};
+
+#[test]
+fn test_manifest_resume() {
+    let path = tempfile::TempDir::new()
+        .unwrap()
+        .into_path()
+        .join("bootstrap_manifest.json");
+    assert_eq!(BootstrapManifest::load(&path), None);
+
+    let slot = Slot::new(3, 1);
+    let mut manifest = BootstrapManifest {
+        last_slot: slot,
+        last_state_step: StreamingStep::Started,
+        last_versioning_step: StreamingStep::Started,
+        chunk_count: 0,
+    };
+    manifest.record_chunk(
+        Slot::new(4, 0),
+        StreamingStep::Ongoing(vec![1]),
+        StreamingStep::Finished(None),
+    );
+    manifest.save(&path).unwrap();
+
+    let loaded = BootstrapManifest::load(&path).unwrap();
+    assert_eq!(loaded, manifest);
+    assert_eq!(loaded.chunk_count, 1);
+    match loaded.resume_message() {
+        BootstrapClientMessage::AskBootstrapPart {
+            last_slot,
+            last_state_step,
+            last_versioning_step,
+            last_consensus_step,
+            send_last_start_period,
+        } => {
+            assert_eq!(last_slot, Some(Slot::new(4, 0)));
+            assert_eq!(last_state_step, StreamingStep::Ongoing(vec![1]));
+            assert_eq!(last_versioning_step, StreamingStep::Finished(None));
+            assert_eq!(last_consensus_step, StreamingStep::Started);
+            assert!(send_last_start_period);
+        }
+        other => panic!("unexpected resume message: {:?}", other),
+    }
+
+    BootstrapManifest::remove(&path);
+    assert_eq!(BootstrapManifest::load(&path), None);
+}
@@ -1,5 +1,7 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 mod binders;
+mod manifest;
 mod scenarios;
+mod state_hash;
 pub(crate) mod tools;
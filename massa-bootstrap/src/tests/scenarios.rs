@@ -337,13 +337,15 @@ fn test_bootstrap_server() {
         final_write
             .db
             .write()
-            .write_batch(batch, Default::default(), Some(next));
+            .write_batch(batch, Default::default(), Some(next))
+            .unwrap();
 
         let final_state_hash = final_write.db.read().get_xof_db_hash();
         let cycle = next.get_cycle(final_state_local_config.periods_per_cycle.clone());
         final_write
             .pos_state
-            .feed_cycle_state_hash(cycle, final_state_hash);
+            .feed_cycle_state_hash(cycle, final_state_hash)
+            .unwrap();
 
         current_slot = next;
     }
@@ -480,13 +482,15 @@ fn test_bootstrap_server() {
                 final_write
                     .db
                     .write()
-                    .write_batch(batch, Default::default(), Some(next));
+                    .write_batch(batch, Default::default(), Some(next))
+                    .unwrap();
 
                 let final_state_hash = final_write.db.read().get_xof_db_hash();
                 let cycle = next.get_cycle(final_state_local_config.periods_per_cycle.clone());
                 final_write
                     .pos_state
-                    .feed_cycle_state_hash(cycle, final_state_hash);
+                    .feed_cycle_state_hash(cycle, final_state_hash)
+                    .unwrap();
 
                 let mut list_changes_write = list_changes_clone.write();
                 list_changes_write.push((next, changes));
@@ -0,0 +1,118 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use crate::bindings::BootstrapClientBinder;
+use crate::client::count_agreeing_servers;
+use crate::error::BootstrapError;
+use crate::messages::{
+    BootstrapClientMessage, BootstrapClientMessageDeserializer, BootstrapClientMessageSerializer,
+    BootstrapServerMessage, BootstrapServerMessageDeserializer, BootstrapServerMessageSerializer,
+};
+use massa_hash::{HashXof, HASH_XOF_SIZE_BYTES};
+use massa_models::config::{MAX_CONSENSUS_BLOCKS_IDS, MAX_DATASTORE_KEY_LENGTH, THREAD_COUNT};
+use massa_models::{node::NodeId, slot::Slot};
+use massa_serialization::{DeserializeError, Deserializer, Serializer};
+use massa_signature::KeyPair;
+use std::net::SocketAddr;
+
+fn servers(count: u16) -> Vec<(SocketAddr, NodeId)> {
+    (0..count)
+        .map(|index| {
+            (
+                SocketAddr::from(([127, 0, 0, 1], 31245 + index)),
+                NodeId::new(KeyPair::generate(0).unwrap().get_public_key()),
+            )
+        })
+        .collect()
+}
+
+fn hash(data: &[u8]) -> HashXof<HASH_XOF_SIZE_BYTES> {
+    HashXof::compute_from(data)
+}
+
+#[test]
+fn test_state_hash_messages_ser_de() {
+    let change_id = Slot::new(12, 3);
+
+    let message = BootstrapClientMessage::AskStateHash { change_id };
+    let mut buffer = Vec::new();
+    BootstrapClientMessageSerializer::new()
+        .serialize(&message, &mut buffer)
+        .unwrap();
+    let (rest, deserialized) = BootstrapClientMessageDeserializer::new(
+        THREAD_COUNT,
+        MAX_DATASTORE_KEY_LENGTH,
+        MAX_CONSENSUS_BLOCKS_IDS,
+    )
+    .deserialize::<DeserializeError>(&buffer)
+    .unwrap();
+    assert!(rest.is_empty());
+    match deserialized {
+        BootstrapClientMessage::AskStateHash {
+            change_id: received,
+        } => assert_eq!(received, change_id),
+        other => panic!("unexpected message: {:?}", other),
+    }
+
+    let deserializer = BootstrapServerMessageDeserializer::new(
+        (&BootstrapClientBinder::test_default_config()).into(),
+    );
+    for state_hash in [Some(hash(b"state")), None] {
+        let message = BootstrapServerMessage::StateHash {
+            change_id,
+            state_hash,
+        };
+        let mut buffer = Vec::new();
+        BootstrapServerMessageSerializer::new()
+            .serialize(&message, &mut buffer)
+            .unwrap();
+        let (rest, deserialized) = deserializer
+            .deserialize::<DeserializeError>(&buffer)
+            .unwrap();
+        assert!(rest.is_empty());
+        match deserialized {
+            BootstrapServerMessage::StateHash {
+                change_id: received_change_id,
+                state_hash: received_hash,
+            } => {
+                assert_eq!(received_change_id, change_id);
+                assert_eq!(received_hash, state_hash);
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn test_state_hash_quorum() {
+    let servers = servers(5);
+    let change_id = Slot::new(12, 3);
+    let state_hash = hash(b"state");
+    let answer = |addr: &SocketAddr| match servers.iter().position(|(a, _)| a == addr) {
+        Some(0) | Some(4) => Ok(Some(state_hash)),
+        Some(1) => Ok(Some(hash(b"other state"))),
+        Some(2) => Ok(None),
+        _ => Err(BootstrapError::GeneralError("unreachable".to_string())),
+    };
+
+    // only the servers answering the same hash are counted
+    let mut asked = 0;
+    let agreeing = count_agreeing_servers(&servers, 3, change_id, state_hash, |addr, _| {
+        asked += 1;
+        answer(addr)
+    });
+    assert_eq!(agreeing, 2);
+    assert_eq!(asked, 5);
+
+    // the servers are no longer asked once the quorum is reached
+    let mut asked = 0;
+    let agreeing = count_agreeing_servers(&servers, 1, change_id, state_hash, |addr, _| {
+        asked += 1;
+        answer(addr)
+    });
+    assert_eq!(agreeing, 1);
+    assert_eq!(asked, 1);
+
+    // no server, no agreement
+    let agreeing = count_agreeing_servers(&[], 1, change_id, state_hash, |addr, _| answer(addr));
+    assert_eq!(agreeing, 0);
+}
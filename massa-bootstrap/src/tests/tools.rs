@@ -178,14 +178,20 @@ fn get_random_pos_state(r_limit: u64, mut pos: PoSFinalState) -> PoSFinalState {
 
     pos.create_initial_cycle(&mut batch);
 
-    pos.db.write().write_batch(batch, Default::default(), None);
+    pos.db
+        .write()
+        .write_batch(batch, Default::default(), None)
+        .unwrap();
 
     let mut batch = DBBatch::new();
 
     pos.apply_changes_to_batch(changes, Slot::new(0, 0), false, &mut batch)
         .expect("Critical: Error while applying changes to pos_state");
 
-    pos.db.write().write_batch(batch, Default::default(), None);
+    pos.db
+        .write()
+        .write_batch(batch, Default::default(), None)
+        .unwrap();
 
     pos
 }
@@ -229,7 +235,9 @@ pub fn get_random_executed_ops(
     let mut executed_ops = ExecutedOps::new(config.clone(), db.clone());
     let mut batch = DBBatch::new();
     executed_ops.apply_changes_to_batch(get_random_executed_ops_changes(10), slot, &mut batch);
-    db.write().write_batch(batch, Default::default(), None);
+    db.write()
+        .write_batch(batch, Default::default(), None)
+        .unwrap();
     executed_ops
 }
 
@@ -263,7 +271,8 @@ pub fn get_random_executed_de(
     executed_de
         .db
         .write()
-        .write_batch(batch, Default::default(), None);
+        .write_batch(batch, Default::default(), None)
+        .unwrap();
 
     executed_de
 }
@@ -317,7 +326,8 @@ pub fn get_random_final_state_bootstrap(
     async_pool
         .db
         .write()
-        .write_batch(batch, versioning_batch, None);
+        .write_batch(batch, versioning_batch, None)
+        .unwrap();
 
     let executed_ops = get_random_executed_ops(
         r_limit,
@@ -383,6 +393,11 @@ pub fn get_bootstrap_config(bootstrap_public_key: NodeId) -> BootstrapConfig {
             bootstrap_public_key,
         )],
         keep_ledger: false,
+        manifest_path: tempfile::TempDir::new()
+            .unwrap()
+            .into_path()
+            .join("bootstrap_manifest.json"),
+        state_hash_quorum: 0,
         bootstrap_whitelist_path: PathBuf::from(
             "../massa-node/base_config/bootstrap_whitelist.json",
         ),
@@ -0,0 +1,99 @@
+//! Heartbeats of the worker threads.
+//!
+//! A worker creates a `Heartbeat` when it starts and beats at each iteration of its loop.
+//! The heartbeats are registered by name in a global registry, as the channel metrics are
+//! registered in prometheus, so that a watchdog can find the workers that stopped beating
+//! with `heartbeat_ages` without being linked to each of them.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Longest time a worker waits without beating.
+/// The workers that wait for messages or a deadline wake up at least this often to beat, so the
+/// timeout of a watchdog must be larger.
+pub const MAX_BEAT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Heartbeats of the running workers, by name
+static HEARTBEATS: Mutex<BTreeMap<String, Arc<AtomicU64>>> = Mutex::new(BTreeMap::new());
+
+/// Reference instant of the beats
+static EPOCH: OnceLock<Instant> = OnceLock::new();
+
+fn millis_since_epoch() -> u64 {
+    EPOCH.get_or_init(Instant::now).elapsed().as_millis() as u64
+}
+
+/// Heartbeat of a worker thread, unregistered when dropped
+pub struct Heartbeat {
+    name: String,
+    last_beat: Arc<AtomicU64>,
+}
+
+impl Heartbeat {
+    /// Register the heartbeat of the worker `name`, replacing the previous one with the same name
+    pub fn new(name: &str) -> Self {
+        let last_beat = Arc::new(AtomicU64::new(millis_since_epoch()));
+        HEARTBEATS
+            .lock()
+            .expect("heartbeats registry poisoned")
+            .insert(name.to_string(), last_beat.clone());
+        Heartbeat {
+            name: name.to_string(),
+            last_beat,
+        }
+    }
+
+    /// Signal that the worker is alive
+    pub fn beat(&self) {
+        self.last_beat
+            .store(millis_since_epoch(), Ordering::Relaxed);
+    }
+}
+
+impl Drop for Heartbeat {
+    fn drop(&mut self) {
+        let mut heartbeats = HEARTBEATS.lock().expect("heartbeats registry poisoned");
+        // a worker started again with the same name may have replaced this heartbeat
+        if heartbeats
+            .get(&self.name)
+            .map_or(false, |last_beat| Arc::ptr_eq(last_beat, &self.last_beat))
+        {
+            heartbeats.remove(&self.name);
+        }
+    }
+}
+
+/// Time since the last beat of each running worker, by name
+pub fn heartbeat_ages() -> BTreeMap<String, Duration> {
+    let now = millis_since_epoch();
+    HEARTBEATS
+        .lock()
+        .expect("heartbeats registry poisoned")
+        .iter()
+        .map(|(name, last_beat)| {
+            let age = now.saturating_sub(last_beat.load(Ordering::Relaxed));
+            (name.clone(), Duration::from_millis(age))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heartbeat_registry() {
+        let heartbeat = Heartbeat::new("test_worker");
+        heartbeat.beat();
+        assert!(heartbeat_ages().contains_key("test_worker"));
+
+        // a worker started again replaces the previous heartbeat, which is not removed when dropped
+        let restarted = Heartbeat::new("test_worker");
+        drop(heartbeat);
+        assert!(heartbeat_ages().contains_key("test_worker"));
+        drop(restarted);
+        assert!(!heartbeat_ages().contains_key("test_worker"));
+    }
+}
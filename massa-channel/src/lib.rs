@@ -20,6 +20,7 @@ use receiver::MassaReceiver;
 use sender::MassaSender;
 use tracing::debug;
 
+pub mod heartbeat;
 pub mod receiver;
 pub mod sender;
 
@@ -87,9 +87,10 @@ impl<T> MassaReceiver<T> {
                 self.update_metrics();
                 Ok(msg)
             }
-            Err(e) => {
+            Err(RecvTimeoutError::Timeout) => Err(RecvTimeoutError::Timeout),
+            Err(RecvTimeoutError::Disconnected) => {
                 self.unregister_metrics();
-                Err(e)
+                Err(RecvTimeoutError::Disconnected)
             }
         }
     }
@@ -100,9 +101,10 @@ impl<T> MassaReceiver<T> {
                 self.update_metrics();
                 Ok(msg)
             }
-            Err(e) => {
+            Err(RecvTimeoutError::Timeout) => Err(RecvTimeoutError::Timeout),
+            Err(RecvTimeoutError::Disconnected) => {
                 self.unregister_metrics();
-                Err(e)
+                Err(RecvTimeoutError::Disconnected)
             }
         }
     }
@@ -6,8 +6,15 @@
 
 use pbkdf2::Params;
 
-/// Cipher version
-pub const VERSION: u32 = 0;
+/// Cipher version, the cipher key is derived from the password with `Argon2id`
+pub const VERSION: u32 = 1;
+
+/// Legacy cipher version, the cipher key is derived from the password with `PBKDF2`.
+/// Only used for decryption.
+pub const PBKDF2_VERSION: u32 = 0;
+
+/// Size of the cipher key derived from the password.
+pub const KEY_SIZE: usize = 32;
 
 /// AES-GCM-SIV nonce size.
 ///
@@ -15,9 +22,18 @@ pub const VERSION: u32 = 0;
 /// Nonces need not be random: a counter can be used so long as the values are never repeated under the same key.
 pub const NONCE_SIZE: usize = 12;
 
-/// `PBKDF2` salt size.
+/// Key derivation salt size.
 pub const SALT_SIZE: usize = 12;
 
+/// `Argon2id` memory cost in KiB.
+pub const ARGON2_MEMORY_COST: u32 = 19 * 1024;
+
+/// `Argon2id` number of iterations.
+pub const ARGON2_TIME_COST: u32 = 2;
+
+/// `Argon2id` degree of parallelism.
+pub const ARGON2_PARALLELISM: u32 = 1;
+
 /// `PBKDF2` hash parameters.
 pub const HASH_PARAMS: Params = Params {
     rounds: 10_000,
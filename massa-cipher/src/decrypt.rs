@@ -6,13 +6,11 @@
 
 use aes_gcm::aead::Aead;
 use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
-use pbkdf2::{
-    password_hash::{PasswordHasher, SaltString},
-    Pbkdf2,
-};
+use pbkdf2::password_hash::SaltString;
 
-use crate::constants::{HASH_PARAMS, NONCE_SIZE, SALT_SIZE};
+use crate::constants::{NONCE_SIZE, SALT_SIZE};
 use crate::error::CipherError;
+use crate::key::derive_key;
 use massa_serialization::{DeserializeError, Deserializer, U32VarIntDeserializer};
 
 use std::ops::Bound::Included;
@@ -39,12 +37,8 @@ pub fn decrypt(password: &str, data: &[u8]) -> Result<(u32, Vec<u8>), CipherErro
     let salt = SaltString::new(std::str::from_utf8(salt_data)?)
         .map_err(|e| CipherError::DecryptionError(e.to_string()))?;
 
-    // compute PBKDF2 password hash
-    let password_hash = Pbkdf2
-        .hash_password_customized(password.as_bytes(), None, None, HASH_PARAMS, &salt)
-        .map_err(|e| CipherError::DecryptionError(e.to_string()))?
-        .hash
-        .expect("content is missing after a successful hash");
+    // derive the cipher key from the password, with the derivation function of the file version
+    let key = derive_key(version, password, salt.as_salt())?;
 
     // parse AES-GCM nonce
     let nonce_end_index = SALT_SIZE + NONCE_SIZE;
@@ -55,7 +49,7 @@ pub fn decrypt(password: &str, data: &[u8]) -> Result<(u32, Vec<u8>), CipherErro
     })?);
 
     // decrypt the data
-    let cipher = Aes256Gcm::new_from_slice(password_hash.as_bytes()).expect("invalid size key");
+    let cipher = Aes256Gcm::new_from_slice(&key).expect("invalid size key");
     let decrypted_bytes = cipher
         .decrypt(
             nonce,
@@ -7,18 +7,29 @@
 use aes_gcm::aead::Aead;
 use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
 use pbkdf2::password_hash::Salt;
-use pbkdf2::{password_hash::PasswordHasher, Pbkdf2};
 use rand::{distributions::Alphanumeric, thread_rng, Rng, RngCore};
 
-use crate::constants::{HASH_PARAMS, NONCE_SIZE, SALT_SIZE, VERSION};
+use crate::constants::{NONCE_SIZE, SALT_SIZE, VERSION};
 use crate::error::CipherError;
+use crate::key::derive_key;
 use massa_serialization::{Serializer, U32VarIntSerializer};
 
 /// Encryption function using AES-GCM cipher.
 ///
 /// Read `lib.rs` module documentation for more information.
 pub fn encrypt(password: &str, data: &[u8]) -> Result<Vec<u8>, CipherError> {
-    // generate the PBKDF2 salt
+    encrypt_with_version(VERSION, password, data)
+}
+
+/// Encrypt with the key derivation of a previous cipher `version`,
+/// to produce the files that older nodes used to write.
+#[cfg(feature = "testing")]
+pub fn encrypt_legacy(version: u32, password: &str, data: &[u8]) -> Result<Vec<u8>, CipherError> {
+    encrypt_with_version(version, password, data)
+}
+
+fn encrypt_with_version(version: u32, password: &str, data: &[u8]) -> Result<Vec<u8>, CipherError> {
+    // generate the salt
     let raw_salt: String = thread_rng()
         .sample_iter(&Alphanumeric)
         .take(SALT_SIZE)
@@ -26,12 +37,8 @@ pub fn encrypt(password: &str, data: &[u8]) -> Result<Vec<u8>, CipherError> {
         .collect();
     let salt = Salt::new(&raw_salt).expect("salt creation failed");
 
-    // compute PBKDF2 password hash
-    let password_hash = Pbkdf2
-        .hash_password_customized(password.as_bytes(), None, None, HASH_PARAMS, salt)
-        .map_err(|e| CipherError::EncryptionError(e.to_string()))?
-        .hash
-        .expect("content is missing after a successful hash");
+    // derive the cipher key from the password
+    let key = derive_key(version, password, salt)?;
 
     // generate the AES-GCM nonce
     let mut nonce_bytes = [0u8; NONCE_SIZE];
@@ -39,7 +46,7 @@ pub fn encrypt(password: &str, data: &[u8]) -> Result<Vec<u8>, CipherError> {
     let nonce = Nonce::from_slice(&nonce_bytes);
 
     // encrypt the data
-    let cipher = Aes256Gcm::new_from_slice(password_hash.as_bytes()).expect("invalid key length");
+    let cipher = Aes256Gcm::new_from_slice(&key).expect("invalid key length");
     let encrypted_bytes = cipher
         .encrypt(nonce, data.as_ref())
         .map_err(|e| CipherError::EncryptionError(e.to_string()))?;
@@ -47,7 +54,7 @@ pub fn encrypt(password: &str, data: &[u8]) -> Result<Vec<u8>, CipherError> {
     // build the encryption result
     let mut content = Vec::new();
     U32VarIntSerializer::new()
-        .serialize(&VERSION, &mut content)
+        .serialize(&version, &mut content)
         .map_err(|err| CipherError::EncryptionError(err.to_string()))?;
     content.extend(salt.as_bytes());
     content.extend(nonce_bytes);
@@ -0,0 +1,55 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! massa-cipher key derivation module.
+//!
+//! Read `lib.rs` module documentation for more information.
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use pbkdf2::{
+    password_hash::{PasswordHasher, Salt},
+    Pbkdf2,
+};
+
+use crate::constants::{
+    ARGON2_MEMORY_COST, ARGON2_PARALLELISM, ARGON2_TIME_COST, HASH_PARAMS, KEY_SIZE,
+    PBKDF2_VERSION, VERSION,
+};
+use crate::error::CipherError;
+
+/// Derive the AES-GCM key from the password, with the key derivation function of the given cipher version.
+pub(crate) fn derive_key(
+    version: u32,
+    password: &str,
+    salt: Salt,
+) -> Result<[u8; KEY_SIZE], CipherError> {
+    let mut key = [0u8; KEY_SIZE];
+    match version {
+        VERSION => {
+            let params = Params::new(
+                ARGON2_MEMORY_COST,
+                ARGON2_TIME_COST,
+                ARGON2_PARALLELISM,
+                Some(KEY_SIZE),
+            )
+            .map_err(|e| CipherError::KeyDerivationError(e.to_string()))?;
+            Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+                .hash_password_into(password.as_bytes(), salt.as_str().as_bytes(), &mut key)
+                .map_err(|e| CipherError::KeyDerivationError(e.to_string()))?;
+        }
+        PBKDF2_VERSION => {
+            let password_hash = Pbkdf2
+                .hash_password_customized(password.as_bytes(), None, None, HASH_PARAMS, salt)
+                .map_err(|e| CipherError::KeyDerivationError(e.to_string()))?
+                .hash
+                .expect("content is missing after a successful hash");
+            key.copy_from_slice(password_hash.as_bytes());
+        }
+        _ => {
+            return Err(CipherError::KeyDerivationError(format!(
+                "unsupported cipher version: {}",
+                version
+            )))
+        }
+    }
+    Ok(key)
+}
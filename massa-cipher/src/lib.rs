@@ -7,8 +7,10 @@
 //! AES-GCM is a state-of-the-art high-performance Authenticated Encryption with Associated Data (AEAD)
 //! that provides confidentiality and authenticity.
 //!
-//! To hash the password before using it as a cipher key, we use the `PBKDF2` key derivation function
-//! as specified in [RFC 2898](https://datatracker.ietf.org/doc/html/rfc2898).
+//! To hash the password before using it as a cipher key, we use the `Argon2id` key derivation function
+//! as specified in [RFC 9106](https://datatracker.ietf.org/doc/html/rfc9106).
+//! Files encrypted with the previous cipher version, whose key is derived with `PBKDF2`
+//! as specified in [RFC 2898](https://datatracker.ietf.org/doc/html/rfc2898), can still be decrypted.
 //!
 //! The AES-GCM crate we use has received one security audit by NCC Group, with no significant findings.
 
@@ -16,7 +18,11 @@ mod constants;
 mod decrypt;
 mod encrypt;
 mod error;
+mod key;
 
+pub use constants::{PBKDF2_VERSION, VERSION};
 pub use decrypt::decrypt;
 pub use encrypt::encrypt;
+#[cfg(feature = "testing")]
+pub use encrypt::encrypt_legacy;
 pub use error::CipherError;
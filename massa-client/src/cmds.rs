@@ -1,14 +1,17 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use crate::display::Output;
-use crate::{client_warning, rpc_error};
+use crate::export::{export_blocks, ExportFormat};
+use crate::{ask_password, client_warning, rpc_error};
 use anyhow::{anyhow, bail, Result};
 use console::style;
 use massa_api_exports::{
     address::{AddressInfo, CompactAddressInfo},
     datastore::DatastoreEntryInput,
     execution::{ReadOnlyBytecodeExecution, ReadOnlyCall},
-    operation::OperationInput,
+    journal::JournalFilter,
+    node::KnownPeer,
+    operation::{OperationInput, PoolOperationsRequest},
 };
 use massa_models::node::NodeId;
 use massa_models::prehash::PreHashMap;
@@ -93,6 +96,20 @@ pub enum Command {
     )]
     node_stop,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(pwd_not_needed = "true"),
+        message = "reloads the log level, the initial peers and the API addresses from the configuration"
+    )]
+    node_reload_config,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "[Module1=Level1,Module2=Level2,...]", pwd_not_needed = "true"),
+        message = "sets the log levels of the given modules (eg. massa_protocol_worker=debug), without arguments removes them"
+    )]
+    node_set_log_filter,
+
     #[strum(
         ascii_case_insensitive,
         props(pwd_not_needed = "true"),
@@ -100,6 +117,51 @@ pub enum Command {
     )]
     node_get_staking_addresses,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(pwd_not_needed = "true"),
+        message = "show the estimated memory used by the caches of the node and their limits"
+    )]
+    node_get_memory_stats,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(pwd_not_needed = "true"),
+        message = "show the version, uptime and sync progress of the node, its pool size and staking keys count"
+    )]
+    node_get_status,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(pwd_not_needed = "true"),
+        message = "show the bytes and messages exchanged with each connected peer"
+    )]
+    node_get_peers_detail,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "FilePath", pwd_not_needed = "true"),
+        message = "export the peers known by the node to a JSON file, to import them on another node"
+    )]
+    node_export_peers,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "FilePath", pwd_not_needed = "true"),
+        message = "import the peers of a file exported by node_export_peers: the node tests them and adds the reachable ones to its known peers"
+    )]
+    node_import_peers,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(
+            args = "start=timestamp_millis end=timestamp_millis kind=EventKind",
+            pwd_not_needed = "true"
+        ),
+        message = "show the events of the journal of the node (reorg, desync, invalid_block, peer_banned, bootstrap_attempt, worker_stuck, isolated, reconnected, partition_suspected, partition_resolved, storage_error, state_hash_mismatch, event_sink_gap, indexer_lagged, indexer_gap) with various filters"
+    )]
+    node_get_journal_events,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "Address1 Address2 ..."),
@@ -184,6 +246,23 @@ pub enum Command {
     )]
     get_operations,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Address [Limit]", pwd_not_needed = "true"),
+        message = "show the operations waiting in the pool that are sent by an address, or that transfer coins or call a smart contract at this address"
+    )]
+    get_pool_operations,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(
+            args = "start=slot_period,slot_thread end=slot_period,slot_thread format=json|csv path=FilePath",
+            pwd_not_needed = "true"
+        ),
+        message = "export the blocks kept by the node between two slots, and their operations, to a file as newline-delimited JSON or CSV"
+    )]
+    export_blocks,
+
     #[strum(
         ascii_case_insensitive,
         props(
@@ -228,6 +307,19 @@ pub enum Command {
     )]
     wallet_add_secret_keys,
 
+    #[strum(
+        ascii_case_insensitive,
+        message = "generate a 24-word mnemonic phrase and add its first derived key into the wallet"
+    )]
+    wallet_generate_mnemonic,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "KeyCount Word1 Word2 ... Word24"),
+        message = "restore the first KeyCount keys derived from a mnemonic phrase into the wallet"
+    )]
+    wallet_restore_from_mnemonic,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "Address1 Address2 ..."),
@@ -235,6 +327,13 @@ pub enum Command {
     )]
     wallet_remove_addresses,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "[KeystorePath]", pwd_not_needed = "true"),
+        message = "generate a secret key and show its public key and address, without adding it to the wallet. The key is saved in the encrypted keystore file KeystorePath if given"
+    )]
+    generate_key,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "Address string"),
@@ -528,6 +627,34 @@ impl Command {
                 Ok(Box::new(()))
             }
 
+            Command::node_reload_config => {
+                match client.private.node_reload_config().await {
+                    Ok(()) => {
+                        if !json {
+                            println!("Request of reloading the configuration successfully sent")
+                        }
+                    }
+                    Err(e) => rpc_error!(e),
+                };
+                Ok(Box::new(()))
+            }
+
+            Command::node_set_log_filter => {
+                match client
+                    .private
+                    .node_set_log_filter(parameters.join(","))
+                    .await
+                {
+                    Ok(()) => {
+                        if !json {
+                            println!("Log filter successfully set")
+                        }
+                    }
+                    Err(e) => rpc_error!(e),
+                };
+                Ok(Box::new(()))
+            }
+
             Command::node_get_staking_addresses => {
                 match client.private.get_staking_addresses().await {
                     Ok(staking_addresses) => Ok(Box::new(staking_addresses)),
@@ -535,6 +662,76 @@ impl Command {
                 }
             }
 
+            Command::node_get_memory_stats => match client.private.get_memory_stats().await {
+                Ok(memory_stats) => Ok(Box::new(memory_stats)),
+                Err(e) => rpc_error!(e),
+            },
+
+            Command::node_get_status => match client.private.get_node_status().await {
+                Ok(node_status) => Ok(Box::new(node_status)),
+                Err(e) => rpc_error!(e),
+            },
+
+            Command::node_get_peers_detail => match client.private.get_peers_detail().await {
+                Ok(peers_detail) => Ok(Box::new(peers_detail)),
+                Err(e) => rpc_error!(e),
+            },
+
+            Command::node_export_peers => {
+                if parameters.len() != 1 {
+                    bail!("wrong number of parameters");
+                }
+                let path = parameters[0].parse::<PathBuf>()?;
+                let peers = match client.private.node_get_known_peers().await {
+                    Ok(peers) => peers,
+                    Err(e) => rpc_error!(e),
+                };
+                std::fs::write(&path, serde_json::to_string_pretty(&peers)?)?;
+                Ok(Box::new(format!(
+                    "{} peers exported to {}",
+                    peers.len(),
+                    path.display()
+                )))
+            }
+
+            Command::node_import_peers => {
+                if parameters.len() != 1 {
+                    bail!("wrong number of parameters");
+                }
+                let path = parameters[0].parse::<PathBuf>()?;
+                let peers: Vec<KnownPeer> = serde_json::from_slice(&std::fs::read(&path)?)?;
+                let count = peers.len();
+                match client.private.node_add_known_peers(peers).await {
+                    Ok(()) => Ok(Box::new(format!(
+                        "{} peers sent to the node to be tested",
+                        count
+                    ))),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
+            Command::node_get_journal_events => {
+                let p_list: [&str; 3] = ["start", "end", "kind"];
+                let mut p: HashMap<&str, &str> = HashMap::new();
+                for v in parameters {
+                    let s: Vec<&str> = v.split('=').collect();
+                    if s.len() == 2 && p_list.contains(&s[0]) {
+                        p.insert(s[0], s[1]);
+                    } else {
+                        bail!("invalid parameter: {}, type \"help node_get_journal_events\" to get the list of valid parameters", v);
+                    }
+                }
+                let filter = JournalFilter {
+                    start: parse_key_value(&p, p_list[0])?,
+                    end: parse_key_value(&p, p_list[1])?,
+                    kind: parse_key_value(&p, p_list[2])?,
+                };
+                match client.private.get_journal_events(filter).await {
+                    Ok(events) => Ok(Box::new(events)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
             Command::node_testnet_rewards_program_ownership_proof => {
                 let wallet = wallet_opt.as_mut().unwrap();
 
@@ -624,6 +821,56 @@ impl Command {
                 }
             }
 
+            Command::get_pool_operations => {
+                if parameters.is_empty() || parameters.len() > 2 {
+                    bail!("wrong number of parameters");
+                }
+                let address = parameters[0].parse::<Address>()?;
+                let limit = parameters
+                    .get(1)
+                    .map(|limit| limit.parse::<usize>())
+                    .transpose()?;
+                match client
+                    .public
+                    .get_pool_operations(PoolOperationsRequest { address, limit })
+                    .await
+                {
+                    Ok(operations_info) => Ok(Box::new(operations_info)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
+            Command::export_blocks => {
+                let p_list: [&str; 4] = ["start", "end", "format", "path"];
+                let mut p: HashMap<&str, &str> = HashMap::new();
+                for v in parameters {
+                    let s: Vec<&str> = v.split('=').collect();
+                    if s.len() == 2 && p_list.contains(&s[0]) {
+                        p.insert(s[0], s[1]);
+                    } else {
+                        bail!("invalid parameter: {}, type \"help export_blocks\" to get the list of valid parameters", v);
+                    }
+                }
+                let format =
+                    parse_key_value::<ExportFormat>(&p, p_list[2])?.unwrap_or(ExportFormat::Json);
+                let Some(path) = parse_key_value::<PathBuf>(&p, p_list[3])? else {
+                    bail!("missing parameter: path");
+                };
+                let exported = export_blocks(
+                    client,
+                    parse_key_value(&p, p_list[0])?,
+                    parse_key_value(&p, p_list[1])?,
+                    format,
+                    &path,
+                )
+                .await?;
+                Ok(Box::new(format!(
+                    "{} blocks exported to {}",
+                    exported,
+                    path.display()
+                )))
+            }
+
             Command::get_filtered_sc_output_event => {
                 let p_list: [&str; 7] = [
                     "start",
@@ -803,6 +1050,40 @@ impl Command {
                 }
             }
 
+            Command::generate_key => {
+                if parameters.len() > 1 {
+                    bail!("wrong number of parameters");
+                }
+                // Note: keypair version is hardcoded here, see `wallet_generate_secret_key`
+                let keypair_version: u64 = 0;
+                let key = KeyPair::generate(keypair_version).expect("Unable to generate key pair");
+                let public_key = key.get_public_key();
+                let address = Address::from_public_key(&public_key);
+                if let Some(path) = parameters.first() {
+                    let path = PathBuf::from(path);
+                    let password = ask_password(&path);
+                    Wallet::new(path.clone(), password)?.add_keypairs(vec![key.clone()])?;
+                    if !json {
+                        println!("Saved the key in {}", path.display());
+                    }
+                }
+                if json {
+                    Ok(Box::new(serde_json::json!({
+                        "secret_key": key.to_string(),
+                        "public_key": public_key.to_string(),
+                        "address": address.to_string(),
+                    })))
+                } else {
+                    println!("Secret key: {}", key);
+                    println!("Public key: {}", public_key);
+                    println!("Address: {}", address);
+                    client_warning!(
+                        "keep the secret key secret: anyone knowing it controls the address"
+                    );
+                    Ok(Box::new(()))
+                }
+            }
+
             Command::wallet_add_secret_keys => {
                 if parameters.is_empty() {
                     bail!("wrong number of parameters");
@@ -822,6 +1103,49 @@ impl Command {
                 Ok(Box::new(()))
             }
 
+            Command::wallet_generate_mnemonic => {
+                let wallet = wallet_opt.as_mut().unwrap();
+
+                // Note: keypair version is hardcoded here, see `wallet_generate_secret_key`
+                let keypair_version: u64 = 0;
+                let phrase = massa_wallet::generate_mnemonic()?;
+                let ad = wallet.add_keypairs_from_mnemonic(&phrase, "", 0, 1, keypair_version)?[0];
+                if json {
+                    Ok(Box::new(
+                        serde_json::json!({ "mnemonic": phrase, "address": ad }),
+                    ))
+                } else {
+                    println!("Mnemonic phrase: {}", phrase);
+                    client_warning!("write down this phrase and keep it secret: it is the only way to restore the keys derived from it");
+                    println!("Generated {} address and added it to the wallet", ad);
+                    println!(
+                        "Type `wallet_restore_from_mnemonic <key count> <phrase>` to restore the keys derived from the phrase.\n"
+                    );
+                    Ok(Box::new(()))
+                }
+            }
+
+            Command::wallet_restore_from_mnemonic => {
+                if parameters.len() != massa_wallet::MNEMONIC_WORD_COUNT + 1 {
+                    bail!("wrong number of parameters");
+                }
+                let wallet = wallet_opt.as_mut().unwrap();
+
+                let count = parameters[0].parse::<u32>()?;
+                let phrase = parameters[1..].join(" ");
+                let keypair_version: u64 = 0;
+                let addresses =
+                    wallet.add_keypairs_from_mnemonic(&phrase, "", 0, count, keypair_version)?;
+                if json {
+                    return Ok(Box::new(addresses));
+                } else {
+                    for address in addresses {
+                        println!("Derived and added address {} to the wallet.", address);
+                    }
+                }
+                Ok(Box::new(()))
+            }
+
             Command::wallet_remove_addresses => {
                 if parameters.is_empty() {
                     bail!("wrong number of parameters");
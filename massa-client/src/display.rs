@@ -4,14 +4,19 @@ use crate::cmds::ExtendedWallet;
 use console::style;
 use erased_serde::{Serialize, Serializer};
 use massa_api_exports::{
-    address::AddressInfo, block::BlockInfo, datastore::DatastoreEntryOutput,
-    endorsement::EndorsementInfo, execution::ExecuteReadOnlyResponse, node::NodeStatus,
+    address::AddressInfo,
+    block::BlockInfo,
+    datastore::DatastoreEntryOutput,
+    endorsement::EndorsementInfo,
+    execution::ExecuteReadOnlyResponse,
+    journal::JournalEvent,
+    node::{NodeStatus, NodeStatusSummary},
     operation::OperationInfo,
 };
 use massa_models::composite::PubkeySig;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashSet;
-use massa_models::stats::{ConsensusStats, ExecutionStats, NetworkStats};
+use massa_models::stats::{ConsensusStats, ExecutionStats, MemoryStats, NetworkStats, PeerStats};
 use massa_models::{address::Address, config::CompactConfig, operation::OperationId};
 use massa_signature::{KeyPair, PublicKey};
 use massa_wallet::Wallet;
@@ -208,6 +213,12 @@ impl Output for String {
     }
 }
 
+impl Output for serde_json::Value {
+    fn pretty_print(&self) {
+        println!("{:#}", self);
+    }
+}
+
 impl Output for &str {
     fn pretty_print(&self) {
         println!("{}", self)
@@ -584,6 +595,37 @@ impl Output for Vec<SCOutputEvent> {
     }
 }
 
+impl Output for MemoryStats {
+    fn pretty_print(&self) {
+        println!("{}", self);
+    }
+}
+
+impl Output for NodeStatusSummary {
+    fn pretty_print(&self) {
+        println!("{}", self);
+    }
+}
+
+impl Output for Vec<PeerStats> {
+    fn pretty_print(&self) {
+        if self.is_empty() {
+            println!("No connected peer");
+        }
+        for peer in self {
+            println!("{}", peer);
+        }
+    }
+}
+
+impl Output for Vec<JournalEvent> {
+    fn pretty_print(&self) {
+        for event in self {
+            print!("{}", event);
+        }
+    }
+}
+
 impl Output for PubkeySig {
     fn pretty_print(&self) {
         println!("{}", self);
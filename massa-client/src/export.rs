@@ -0,0 +1,194 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+//! Export of the blocks of a slot range and of their operations, for external tools.
+//!
+//! The blocks are read page by page from the block graph of the node and written to the file
+//! as they arrive, so that long ranges are not held in memory. Only the blocks still kept by the
+//! node can be exported: the stale and invalid blocks, whose content is not stored, are skipped.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use anyhow::{bail, Result};
+use massa_api_exports::{block::BlockInfoContent, operation::OperationInfo, GraphIntervalRequest};
+use massa_models::{
+    address::Address,
+    block_id::BlockId,
+    operation::{OperationId, OperationType, SecureShareOperation},
+    prehash::PreHashMap,
+    slot::Slot,
+};
+use massa_sdk::Client;
+use serde::Serialize;
+use strum_macros::EnumString;
+
+use crate::rpc_error;
+
+/// number of blocks, and of operations, asked to the node in a single request.
+/// Kept below the default `max_arguments` of the API.
+const EXPORT_BATCH_SIZE: usize = 100;
+
+/// header of the CSV export: one row per operation, or a single row without operation for empty blocks
+const CSV_HEADER: &str = "block_id,period,thread,block_creator,is_final,is_in_blockclique,operation_id,operation_creator,fee,expire_period,operation_type";
+
+/// Format of an export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString)]
+#[strum(ascii_case_insensitive)]
+pub(crate) enum ExportFormat {
+    /// one JSON object per line and per block, with its operations
+    Json,
+    /// one line per operation
+    Csv,
+}
+
+/// Line of the JSON export
+#[derive(Serialize)]
+struct ExportedBlock<'a> {
+    id: BlockId,
+    slot: Slot,
+    creator: Address,
+    parents: &'a [BlockId],
+    is_final: bool,
+    is_in_blockclique: bool,
+    operations: Vec<&'a SecureShareOperation>,
+}
+
+/// Name of the type of an operation in the CSV export
+fn operation_type_name(op: &OperationType) -> &'static str {
+    match op {
+        OperationType::Transaction { .. } => "Transaction",
+        OperationType::RollBuy { .. } => "RollBuy",
+        OperationType::RollSell { .. } => "RollSell",
+        OperationType::ExecuteSC { .. } => "ExecuteSC",
+        OperationType::CallSC { .. } => "CallSC",
+    }
+}
+
+/// Write a block and the operations found on the node in `format`
+fn write_block(
+    out: &mut impl Write,
+    format: ExportFormat,
+    id: BlockId,
+    content: &BlockInfoContent,
+    operations: &PreHashMap<OperationId, OperationInfo>,
+) -> Result<()> {
+    let header = &content.block.header;
+    let block_operations: Vec<&SecureShareOperation> = content
+        .block
+        .operations
+        .iter()
+        .filter_map(|op_id| operations.get(op_id).map(|info| &info.operation))
+        .collect();
+    match format {
+        ExportFormat::Json => {
+            let line = ExportedBlock {
+                id,
+                slot: header.content.slot,
+                creator: header.content_creator_address,
+                parents: &header.content.parents,
+                is_final: content.is_final,
+                is_in_blockclique: content.is_in_blockclique,
+                operations: block_operations,
+            };
+            serde_json::to_writer(&mut *out, &line)?;
+            writeln!(out)?;
+        }
+        ExportFormat::Csv => {
+            let block_columns = format!(
+                "{},{},{},{},{},{}",
+                id,
+                header.content.slot.period,
+                header.content.slot.thread,
+                header.content_creator_address,
+                content.is_final,
+                content.is_in_blockclique
+            );
+            if block_operations.is_empty() {
+                writeln!(out, "{},,,,,", block_columns)?;
+            }
+            for op in block_operations {
+                writeln!(
+                    out,
+                    "{},{},{},{},{},{}",
+                    block_columns,
+                    op.id,
+                    op.content_creator_address,
+                    op.content.fee,
+                    op.content.expire_period,
+                    operation_type_name(&op.content.op)
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Export the blocks from `start` (included) to `end` (excluded) and their operations to `path`.
+/// Returns the number of exported blocks.
+pub(crate) async fn export_blocks(
+    client: &Client,
+    start: Option<Slot>,
+    end: Option<Slot>,
+    format: ExportFormat,
+    path: &Path,
+) -> Result<usize> {
+    let mut out = BufWriter::new(File::create(path)?);
+    if format == ExportFormat::Csv {
+        writeln!(out, "{}", CSV_HEADER)?;
+    }
+
+    let mut exported = 0;
+    let mut cursor = None;
+    loop {
+        let page = match client
+            .public
+            .get_graph_interval_paged(GraphIntervalRequest {
+                start_slot: start,
+                end_slot: end,
+                cursor,
+                limit: Some(EXPORT_BATCH_SIZE),
+            })
+            .await
+        {
+            Ok(page) => page,
+            Err(e) => rpc_error!(e),
+        };
+        let block_ids: Vec<BlockId> = page
+            .blocks
+            .iter()
+            .filter(|summary| !summary.is_stale && summary.invalid_reason.is_none())
+            .map(|summary| summary.id)
+            .collect();
+        let blocks = match client.public.get_blocks(block_ids).await {
+            Ok(blocks) => blocks,
+            Err(e) => rpc_error!(e),
+        };
+
+        let op_ids: Vec<OperationId> = blocks
+            .iter()
+            .filter_map(|block| block.content.as_ref())
+            .flat_map(|content| content.block.operations.iter().copied())
+            .collect();
+        let mut operations = PreHashMap::default();
+        for chunk in op_ids.chunks(EXPORT_BATCH_SIZE) {
+            match client.public.get_operations(chunk.to_vec()).await {
+                Ok(infos) => operations.extend(infos.into_iter().map(|info| (info.id, info))),
+                Err(e) => rpc_error!(e),
+            }
+        }
+
+        for block in blocks {
+            if let Some(content) = &block.content {
+                write_block(&mut out, format, block.id, content, &operations)?;
+                exported += 1;
+            }
+        }
+
+        match page.next_cursor {
+            Some(next_cursor) => cursor = Some(next_cursor),
+            None => break,
+        }
+    }
+    out.flush()?;
+    Ok(exported)
+}
@@ -19,6 +19,7 @@ use structopt::StructOpt;
 
 mod cmds;
 mod display;
+mod export;
 mod repl;
 mod settings;
 
@@ -1,6 +1,11 @@
 use massa_models::{
-    active_block::ActiveBlock, address::Address, block::Block, block_header::SecuredHeader,
-    block_id::BlockId, prehash::PreHashSet, slot::Slot,
+    active_block::ActiveBlock,
+    address::Address,
+    block::{Block, InvalidBlockReason},
+    block_header::SecuredHeader,
+    block_id::BlockId,
+    prehash::PreHashSet,
+    slot::Slot,
 };
 use massa_storage::Storage;
 use serde::{Deserialize, Serialize};
@@ -29,8 +34,8 @@ impl HeaderOrBlock {
 /// Something can be discarded
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DiscardReason {
-    /// Block is invalid, either structurally, or because of some incompatibility. The String contains the reason for info or debugging.
-    Invalid(String),
+    /// Block is invalid, either structurally, or because of some incompatibility.
+    Invalid(InvalidBlockReason),
     /// Block is incompatible with a final block.
     Stale,
     /// Block has enough fitness.
@@ -3,8 +3,13 @@ use crate::{bootstrapable_graph::BootstrapableGraph, error::ConsensusError};
 use massa_models::prehash::PreHashSet;
 use massa_models::streaming_step::StreamingStep;
 use massa_models::{
-    block::BlockGraphStatus, block_header::BlockHeader, block_id::BlockId, clique::Clique,
-    secure_share::SecureShare, slot::Slot, stats::ConsensusStats,
+    block::{BlockGraphStatus, InvalidBlockReason},
+    block_header::BlockHeader,
+    block_id::BlockId,
+    clique::Clique,
+    secure_share::SecureShare,
+    slot::Slot,
+    stats::{ConsensusMemoryStats, ConsensusStats},
 };
 use massa_storage::Storage;
 
@@ -24,6 +29,24 @@ pub trait ConsensusController: Send + Sync {
         end_slot: Option<Slot>,
     ) -> Result<BlockGraphExport, ConsensusError>;
 
+    /// Get a page of the graph, ordered by slot then block id
+    ///
+    /// # Arguments
+    /// * `start_slot`: the slot to start the export from, if None, the export starts from the genesis
+    /// * `end_slot`: the slot to end the export at (excluded), if None, the export ends at the current slot
+    /// * `after`: slot and id of the last block of the previous page, if None, the page starts at `start_slot`
+    /// * `limit`: maximum number of blocks in the page
+    ///
+    /// # Returns
+    /// The export of the blocks of the page, with the blockclique only
+    fn get_block_graph_page(
+        &self,
+        start_slot: Option<Slot>,
+        end_slot: Option<Slot>,
+        after: Option<(Slot, BlockId)>,
+        limit: usize,
+    ) -> Result<BlockGraphExport, ConsensusError>;
+
     /// Get statuses of a list of blocks
     ///
     /// # Arguments
@@ -33,6 +56,16 @@ pub trait ConsensusController: Send + Sync {
     /// The statuses of the blocks sorted by the order of the input list
     fn get_block_statuses(&self, ids: &[BlockId]) -> Vec<BlockGraphStatus>;
 
+    /// Get why a list of blocks were discarded as invalid
+    ///
+    /// # Arguments
+    /// * `ids`: the list of block ids to get the invalidity reason of
+    ///
+    /// # Returns
+    /// The reasons sorted by the order of the input list, `None` for the blocks that are not
+    /// known by the graph as invalid
+    fn get_invalid_block_reasons(&self, ids: &[BlockId]) -> Vec<Option<InvalidBlockReason>>;
+
     /// Get all the cliques of the graph
     ///
     /// # Returns
@@ -70,6 +103,21 @@ pub trait ConsensusController: Send + Sync {
     /// The stats of the consensus
     fn get_stats(&self) -> Result<ConsensusStats, ConsensusError>;
 
+    /// Get the memory used by the block caches of the consensus
+    ///
+    /// # Returns
+    /// The number of blocks waiting for their slot or their dependencies and of discarded blocks,
+    /// with their limits and estimated sizes
+    fn get_memory_stats(&self) -> ConsensusMemoryStats;
+
+    /// Whether a network partition is suspected
+    ///
+    /// # Returns
+    /// True if no block became final recently while most of the new blocks were created by this
+    /// node: it is then probably on a minority fork. The suspicion ends after some time, so that
+    /// the production resumes
+    fn is_partition_suspected(&self) -> bool;
+
     /// Get the best parents for the next block to be produced
     ///
     /// # Returns
@@ -115,7 +163,13 @@ pub trait ConsensusController: Send + Sync {
     /// # Arguments
     /// * `block_id`: the id of the block to mark as invalid
     /// * `header`: the header of the block to mark as invalid
-    fn mark_invalid_block(&self, block_id: BlockId, header: SecureShare<BlockHeader, BlockId>);
+    /// * `reason`: why the block is invalid
+    fn mark_invalid_block(
+        &self,
+        block_id: BlockId,
+        header: SecureShare<BlockHeader, BlockId>,
+        reason: InvalidBlockReason,
+    );
 
     /// Returns a boxed clone of self.
     /// Useful to allow cloning `Box<dyn ConsensusController>`.
@@ -3,6 +3,7 @@ use displaydoc::Display;
 use massa_execution_exports::ExecutionError;
 use massa_models::error::ModelsError;
 use massa_protocol_exports::ProtocolError;
+pub use massa_protocol_exports::ProtocolErrorKind;
 use massa_time::TimeError;
 use std::array::TryFromSliceError;
 use thiserror::Error;
@@ -45,6 +46,33 @@ pub enum ConsensusError {
     InvalidTransition(String),
 }
 
+impl ConsensusError {
+    /// Cause of the error, classified as the protocol errors.
+    /// The blocks received from peers are checked before reaching consensus, and the invalid
+    /// ones are reported to protocol as attacks: no consensus error is a peer fault of its own.
+    pub fn kind(&self) -> ProtocolErrorKind {
+        match self {
+            ConsensusError::ProtocolError(err) => err.kind(),
+            // the missing data may still be received or computed
+            ConsensusError::MissingBlock(_)
+            | ConsensusError::MissingOperation(_)
+            | ConsensusError::PosCycleUnavailable(_)
+            | ConsensusError::IOError(_) => ProtocolErrorKind::Transient,
+            ConsensusError::ExecutionError(_)
+            | ConsensusError::ModelsError(_)
+            | ConsensusError::GenesisCreationError(_)
+            | ConsensusError::ContainerInconsistency(_)
+            | ConsensusError::FitnessOverflow
+            | ConsensusError::InvalidLedgerChange(_)
+            | ConsensusError::SerdeError(_)
+            | ConsensusError::LedgerError(_)
+            | ConsensusError::MassaTimeError(_)
+            | ConsensusError::TransactionError(_)
+            | ConsensusError::InvalidTransition(_) => ProtocolErrorKind::Internal,
+        }
+    }
+}
+
 /// Internal error
 #[non_exhaustive]
 #[derive(Display, Error, Debug)]
@@ -70,3 +98,34 @@ pub enum LedgerError {
     /// serde error
     SerdeError(#[from] serde_json::Error),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_kinds() {
+        for (err, kind) in [
+            (
+                ConsensusError::MissingBlock("parent".to_string()),
+                ProtocolErrorKind::Transient,
+            ),
+            (
+                ConsensusError::ContainerInconsistency("graph".to_string()),
+                ProtocolErrorKind::Internal,
+            ),
+            (ConsensusError::FitnessOverflow, ProtocolErrorKind::Internal),
+            // the protocol errors keep their kind
+            (
+                ConsensusError::ProtocolError(ProtocolError::WrongSignature),
+                ProtocolErrorKind::PeerFault,
+            ),
+            (
+                ConsensusError::ProtocolError(ProtocolError::SendError("full".to_string())),
+                ProtocolErrorKind::Transient,
+            ),
+        ] {
+            assert_eq!(err.kind(), kind, "{}", err);
+        }
+    }
+}
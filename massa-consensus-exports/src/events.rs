@@ -1,3 +1,5 @@
+use massa_models::{address::Address, block::InvalidBlockReason, block_id::BlockId, slot::Slot};
+
 /// Events that are emitted by consensus.
 #[derive(Debug, Clone)]
 pub enum ConsensusEvent {
@@ -5,4 +7,16 @@ pub enum ConsensusEvent {
     NeedSync,
     /// Network is ended should be send after `end_timestamp`
     Stop,
+    /// A block was discarded as invalid.
+    /// Dropped if the channel is full, so that a flood of invalid blocks does not delay the other events.
+    InvalidBlock {
+        /// id of the block
+        block_id: BlockId,
+        /// slot of the block
+        slot: Slot,
+        /// creator of the block
+        creator: Address,
+        /// why the block is invalid
+        reason: InvalidBlockReason,
+    },
 }
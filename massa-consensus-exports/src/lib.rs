@@ -14,7 +14,7 @@ pub mod export_active_block;
 
 pub use channels::ConsensusChannels;
 pub use controller_trait::{ConsensusController, ConsensusManager};
-pub use settings::ConsensusConfig;
+pub use settings::{ChannelOverflowPolicy, ConsensusConfig};
 
 /// Test utils
 #[cfg(feature = "testing")]
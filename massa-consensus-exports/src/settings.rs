@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use massa_signature::KeyPair;
 use massa_time::MassaTime;
 use serde::{Deserialize, Serialize};
@@ -38,8 +40,15 @@ pub struct ConsensusConfig {
     pub end_timestamp: Option<MassaTime>,
     /// stats time span
     pub stats_timespan: MassaTime,
-    /// channel size
+    /// a network partition is suspected when no block became final for this time while most of the
+    /// new blocks were created by this node, for at most this time again. Zero disables the detection.
+    pub partition_detection_timespan: MassaTime,
+    /// size of the channel of the commands sent to the consensus worker
     pub channel_size: usize,
+    /// what to do with a command (new block, header...) when the command channel is full
+    pub channel_overflow_policy: ChannelOverflowPolicy,
+    /// maximum time a sender waits for room in the command channel with `ChannelOverflowPolicy::Block`
+    pub max_send_wait: MassaTime,
     /// size of a consensus bootstrap streaming part
     pub bootstrap_part_size: u64,
     /// whether broadcast is enabled
@@ -52,4 +61,18 @@ pub struct ConsensusConfig {
     pub broadcast_filled_blocks_channel_capacity: usize,
     /// last start period
     pub last_start_period: u64,
+    /// file where the blocks that are not final yet are saved when consensus stops,
+    /// to be registered again at the next start, if any
+    pub blocks_file_path: Option<PathBuf>,
+}
+
+/// What to do with a command sent to the consensus worker when its channel is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelOverflowPolicy {
+    /// drop the command right away
+    Drop,
+    /// block the sender until there is room in the channel, and drop the command after `max_send_wait`.
+    /// The network reader feeding the sender is slowed down instead of buffering incoming blocks.
+    Block,
 }
@@ -7,7 +7,7 @@ use massa_models::config::{
 };
 use massa_time::MassaTime;
 
-use crate::ConsensusConfig;
+use crate::{ChannelOverflowPolicy, ConsensusConfig};
 
 impl Default for ConsensusConfig {
     fn default() -> Self {
@@ -29,13 +29,17 @@ impl Default for ConsensusConfig {
             endorsement_count: ENDORSEMENT_COUNT,
             end_timestamp: None,
             stats_timespan: MassaTime::from_millis(60000),
+            partition_detection_timespan: MassaTime::from_millis(0),
             channel_size: CHANNEL_SIZE,
+            channel_overflow_policy: ChannelOverflowPolicy::Block,
+            max_send_wait: MassaTime::from_millis(500),
             bootstrap_part_size: CONSENSUS_BOOTSTRAP_PART_SIZE,
             broadcast_enabled: true,
             broadcast_blocks_headers_channel_capacity: 128,
             broadcast_blocks_channel_capacity: 128,
             broadcast_filled_blocks_channel_capacity: 128,
             last_start_period: 0,
+            blocks_file_path: None,
         }
     }
 }
@@ -6,8 +6,14 @@ use std::sync::{
 };
 
 use massa_models::{
-    block::BlockGraphStatus, block_header::BlockHeader, block_id::BlockId, clique::Clique,
-    prehash::PreHashSet, secure_share::SecureShare, slot::Slot, stats::ConsensusStats,
+    block::{BlockGraphStatus, InvalidBlockReason},
+    block_header::BlockHeader,
+    block_id::BlockId,
+    clique::Clique,
+    prehash::PreHashSet,
+    secure_share::SecureShare,
+    slot::Slot,
+    stats::{ConsensusMemoryStats, ConsensusStats},
     streaming_step::StreamingStep,
 };
 use massa_storage::Storage;
@@ -31,11 +37,22 @@ pub enum MockConsensusControllerMessage {
         block_ids: Vec<BlockId>,
         response_tx: mpsc::Sender<Vec<BlockGraphStatus>>,
     },
+    GetInvalidBlockReasons {
+        block_ids: Vec<BlockId>,
+        response_tx: mpsc::Sender<Vec<Option<InvalidBlockReason>>>,
+    },
     GetBlockGraphStatuses {
         start_slot: Option<Slot>,
         end_slot: Option<Slot>,
         response_tx: mpsc::Sender<Result<BlockGraphExport, ConsensusError>>,
     },
+    GetBlockGraphPage {
+        start_slot: Option<Slot>,
+        end_slot: Option<Slot>,
+        after: Option<(Slot, BlockId)>,
+        limit: usize,
+        response_tx: mpsc::Sender<Result<BlockGraphExport, ConsensusError>>,
+    },
     GetCliques {
         response_tx: mpsc::Sender<Vec<Clique>>,
     },
@@ -56,6 +73,12 @@ pub enum MockConsensusControllerMessage {
     GetStats {
         response_tx: mpsc::Sender<Result<ConsensusStats, ConsensusError>>,
     },
+    GetMemoryStats {
+        response_tx: mpsc::Sender<ConsensusMemoryStats>,
+    },
+    IsPartitionSuspected {
+        response_tx: mpsc::Sender<bool>,
+    },
     GetBestParents {
         response_tx: mpsc::Sender<Vec<(BlockId, u64)>>,
     },
@@ -70,6 +93,7 @@ pub enum MockConsensusControllerMessage {
     MarkInvalidBlock {
         block_id: BlockId,
         header: SecureShare<BlockHeader, BlockId>,
+        reason: InvalidBlockReason,
     },
     RegisterBlock {
         block_id: BlockId,
@@ -103,8 +127,18 @@ mockall::mock! {
             end_slot: Option<Slot>,
         ) -> Result<BlockGraphExport, ConsensusError>;
 
+        fn get_block_graph_page(
+            &self,
+            start_slot: Option<Slot>,
+            end_slot: Option<Slot>,
+            after: Option<(Slot, BlockId)>,
+            limit: usize,
+        ) -> Result<BlockGraphExport, ConsensusError>;
+
         fn get_block_statuses(&self, ids: &[BlockId]) -> Vec<BlockGraphStatus>;
 
+        fn get_invalid_block_reasons(&self, ids: &[BlockId]) -> Vec<Option<InvalidBlockReason>>;
+
         fn get_cliques(&self) -> Vec<Clique>;
 
         fn get_bootstrap_part(
@@ -122,6 +156,10 @@ mockall::mock! {
 
         fn get_stats(&self) -> Result<ConsensusStats, ConsensusError>;
 
+        fn get_memory_stats(&self) -> ConsensusMemoryStats;
+
+        fn is_partition_suspected(&self) -> bool;
+
         fn get_best_parents(&self) -> Vec<(BlockId, u64)>;
 
         fn get_blockclique_block_at_slot(&self, slot: Slot) -> Option<BlockId>;
@@ -132,7 +170,7 @@ mockall::mock! {
 
         fn register_block_header(&self, block_id: BlockId, header: SecureShare<BlockHeader, BlockId>);
 
-        fn mark_invalid_block(&self, block_id: BlockId, header: SecureShare<BlockHeader, BlockId>);
+        fn mark_invalid_block(&self, block_id: BlockId, header: SecureShare<BlockHeader, BlockId>, reason: InvalidBlockReason);
 
         fn clone_box(&self) -> Box<dyn ConsensusController>;
     }
@@ -187,6 +225,28 @@ impl ConsensusController for ConsensusControllerImpl {
         response_rx.recv().unwrap()
     }
 
+    fn get_block_graph_page(
+        &self,
+        start_slot: Option<Slot>,
+        end_slot: Option<Slot>,
+        after: Option<(Slot, BlockId)>,
+        limit: usize,
+    ) -> Result<BlockGraphExport, ConsensusError> {
+        let (response_tx, response_rx) = mpsc::channel();
+        self.0
+            .lock()
+            .unwrap()
+            .send(MockConsensusControllerMessage::GetBlockGraphPage {
+                start_slot,
+                end_slot,
+                after,
+                limit,
+                response_tx,
+            })
+            .unwrap();
+        response_rx.recv().unwrap()
+    }
+
     fn get_block_statuses(&self, ids: &[BlockId]) -> Vec<BlockGraphStatus> {
         let (response_tx, response_rx) = mpsc::channel();
         self.0
@@ -200,6 +260,19 @@ impl ConsensusController for ConsensusControllerImpl {
         response_rx.recv().unwrap()
     }
 
+    fn get_invalid_block_reasons(&self, ids: &[BlockId]) -> Vec<Option<InvalidBlockReason>> {
+        let (response_tx, response_rx) = mpsc::channel();
+        self.0
+            .lock()
+            .unwrap()
+            .send(MockConsensusControllerMessage::GetInvalidBlockReasons {
+                block_ids: ids.to_vec(),
+                response_tx,
+            })
+            .unwrap();
+        response_rx.recv().unwrap()
+    }
+
     fn get_cliques(&self) -> Vec<Clique> {
         let (response_tx, response_rx) = mpsc::channel();
         self.0
@@ -245,6 +318,26 @@ impl ConsensusController for ConsensusControllerImpl {
         response_rx.recv().unwrap()
     }
 
+    fn get_memory_stats(&self) -> ConsensusMemoryStats {
+        let (response_tx, response_rx) = mpsc::channel();
+        self.0
+            .lock()
+            .unwrap()
+            .send(MockConsensusControllerMessage::GetMemoryStats { response_tx })
+            .unwrap();
+        response_rx.recv().unwrap()
+    }
+
+    fn is_partition_suspected(&self) -> bool {
+        let (response_tx, response_rx) = mpsc::channel();
+        self.0
+            .lock()
+            .unwrap()
+            .send(MockConsensusControllerMessage::IsPartitionSuspected { response_tx })
+            .unwrap();
+        response_rx.recv().unwrap()
+    }
+
     fn get_best_parents(&self) -> Vec<(BlockId, u64)> {
         let (response_tx, response_rx) = mpsc::channel();
         self.0
@@ -280,11 +373,20 @@ impl ConsensusController for ConsensusControllerImpl {
         response_rx.recv().unwrap()
     }
 
-    fn mark_invalid_block(&self, block_id: BlockId, header: SecureShare<BlockHeader, BlockId>) {
+    fn mark_invalid_block(
+        &self,
+        block_id: BlockId,
+        header: SecureShare<BlockHeader, BlockId>,
+        reason: InvalidBlockReason,
+    ) {
         self.0
             .lock()
             .unwrap()
-            .send(MockConsensusControllerMessage::MarkInvalidBlock { block_id, header })
+            .send(MockConsensusControllerMessage::MarkInvalidBlock {
+                block_id,
+                header,
+                reason,
+            })
             .unwrap();
     }
 
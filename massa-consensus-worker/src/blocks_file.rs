@@ -0,0 +1,128 @@
+//! File in which consensus saves the blocks of the graph that are not final yet when it stops.
+//!
+//! Each block is saved with its operations. At the next start, the blocks are registered again
+//! as if they were received from the network, so that a restart of the node does not lose the
+//! blocks produced or received just before it. They go through the usual checks: the ones that
+//! are too old are discarded, and the missing parents are asked to the network.
+
+use std::{fs, io, path::Path};
+
+use massa_models::{
+    block::{BlockDeserializer, BlockDeserializerArgs, SecureShareBlock},
+    config::{
+        MAX_DATASTORE_VALUE_LENGTH, MAX_FUNCTION_NAME_LENGTH, MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+        MAX_OPERATION_DATASTORE_KEY_LENGTH, MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+        MAX_PARAMETERS_SIZE,
+    },
+    operation::{OperationDeserializer, SecureShareOperation},
+    secure_share::{SecureShareDeserializer, SecureShareSerializer},
+};
+use massa_serialization::{
+    DeserializeError, Deserializer, SerializeError, Serializer, U32VarIntDeserializer,
+    U32VarIntSerializer,
+};
+use std::ops::Bound::Included;
+use tracing::warn;
+
+/// A saved block and its operations
+pub(crate) type SavedBlock = (SecureShareBlock, Vec<SecureShareOperation>);
+
+/// Serialize `blocks` one after the other, each one followed by the count of its operations
+/// and the operations, in the format of the file
+pub(crate) fn serialize_blocks(blocks: &[SavedBlock]) -> Result<Vec<u8>, SerializeError> {
+    let serializer = SecureShareSerializer::new();
+    let count_serializer = U32VarIntSerializer::new();
+    let mut buffer = Vec::new();
+    for (block, operations) in blocks {
+        serializer.serialize(block, &mut buffer)?;
+        let count = u32::try_from(operations.len()).map_err(|_| {
+            SerializeError::GeneralError("too many operations in a block".to_string())
+        })?;
+        count_serializer.serialize(&count, &mut buffer)?;
+        for op in operations {
+            serializer.serialize(op, &mut buffer)?;
+        }
+    }
+    Ok(buffer)
+}
+
+/// Write the serialized blocks to the file at `path`.
+/// The previous file is only replaced once the new one is fully written.
+pub(crate) fn write_blocks(path: &Path, buffer: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, buffer)?;
+    fs::rename(tmp_path, path)
+}
+
+/// Read the blocks saved at `path`, if any.
+/// Stops at the first invalid block, and drops the blocks whose signature, or the signature of
+/// one of their operations, is invalid.
+pub(crate) fn load_blocks(path: &Path, block_args: BlockDeserializerArgs) -> Vec<SavedBlock> {
+    let buffer = match fs::read(path) {
+        Ok(buffer) => buffer,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Vec::new(),
+        Err(err) => {
+            warn!(
+                "could not read the saved blocks {}: {}",
+                path.display(),
+                err
+            );
+            return Vec::new();
+        }
+    };
+    let max_operations = block_args.max_operations_per_block;
+    let block_deserializer = SecureShareDeserializer::new(BlockDeserializer::new(block_args));
+    let count_deserializer = U32VarIntDeserializer::new(Included(0), Included(max_operations));
+    let op_deserializer = SecureShareDeserializer::new(OperationDeserializer::new(
+        MAX_DATASTORE_VALUE_LENGTH,
+        MAX_FUNCTION_NAME_LENGTH,
+        MAX_PARAMETERS_SIZE,
+        MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+        MAX_OPERATION_DATASTORE_KEY_LENGTH,
+        MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+    ));
+    let deserialize_block = |rest: &[u8]| -> Result<(usize, SavedBlock), String> {
+        let (after_block, block) = block_deserializer
+            .deserialize::<DeserializeError>(rest)
+            .map_err(|err| err.to_string())?;
+        let (mut after_ops, count) = count_deserializer
+            .deserialize::<DeserializeError>(after_block)
+            .map_err(|err| err.to_string())?;
+        let mut operations = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let (new_rest, op) = op_deserializer
+                .deserialize::<DeserializeError>(after_ops)
+                .map_err(|err| err.to_string())?;
+            operations.push(op);
+            after_ops = new_rest;
+        }
+        Ok((rest.len() - after_ops.len(), (block, operations)))
+    };
+
+    let mut blocks = Vec::new();
+    let mut rest = buffer.as_slice();
+    while !rest.is_empty() {
+        match deserialize_block(rest) {
+            Ok((read, (block, operations))) => {
+                if block.verify_signature().is_ok()
+                    && operations.iter().all(|op| op.verify_signature().is_ok())
+                {
+                    blocks.push((block, operations));
+                }
+                rest = &rest[read..];
+            }
+            Err(err) => {
+                warn!(
+                    "ignoring the end of the saved blocks {}: {}",
+                    path.display(),
+                    err
+                );
+                break;
+            }
+        }
+    }
+    blocks
+}
@@ -1,5 +1,6 @@
 use massa_models::{
-    block_header::BlockHeader, block_id::BlockId, secure_share::SecureShare, slot::Slot,
+    block::InvalidBlockReason, block_header::BlockHeader, block_id::BlockId,
+    secure_share::SecureShare, slot::Slot,
 };
 use massa_storage::Storage;
 
@@ -8,5 +9,9 @@ use massa_storage::Storage;
 pub enum ConsensusCommand {
     RegisterBlock(BlockId, Slot, Storage, bool),
     RegisterBlockHeader(BlockId, SecureShare<BlockHeader, BlockId>),
-    MarkInvalidBlock(BlockId, SecureShare<BlockHeader, BlockId>),
+    MarkInvalidBlock(
+        BlockId,
+        SecureShare<BlockHeader, BlockId>,
+        InvalidBlockReason,
+    ),
 }
@@ -2,11 +2,13 @@ use massa_channel::sender::MassaSender;
 use massa_consensus_exports::{
     block_graph_export::BlockGraphExport, block_status::BlockStatus,
     bootstrapable_graph::BootstrapableGraph, error::ConsensusError,
-    export_active_block::ExportActiveBlock, ConsensusChannels, ConsensusController,
+    export_active_block::ExportActiveBlock, ChannelOverflowPolicy, ConsensusChannels,
+    ConsensusController,
 };
+use massa_metrics::MassaMetrics;
 use massa_models::denunciation::DenunciationPrecursor;
 use massa_models::{
-    block::{BlockGraphStatus, FilledBlock},
+    block::{BlockGraphStatus, FilledBlock, InvalidBlockReason},
     block_header::BlockHeader,
     block_id::BlockId,
     clique::Clique,
@@ -14,21 +16,26 @@ use massa_models::{
     prehash::PreHashSet,
     secure_share::SecureShare,
     slot::Slot,
-    stats::ConsensusStats,
+    stats::{ConsensusMemoryStats, ConsensusStats},
     streaming_step::StreamingStep,
 };
 use massa_storage::Storage;
 use parking_lot::RwLock;
-use std::sync::Arc;
-use tracing::log::{debug, trace, warn};
+use std::{sync::Arc, time::Duration};
+use tracing::{debug, trace, warn};
 
-use crate::{commands::ConsensusCommand, state::ConsensusState};
+use crate::{
+    commands::ConsensusCommand,
+    state::{snapshot::SharedGraphSnapshot, ConsensusState},
+};
 
 /// The retrieval of data is made using a shared state and modifications are asked by sending message to a channel.
 /// This is done mostly to be able to:
 ///
 /// - send commands through the channel without waiting for them to be processed from the point of view of the sending thread, and channels are very much optimal for that (much faster than locks)
 /// - still be able to read the current state of the graph as processed so far (for this we need a shared state)
+/// - answer the frequent queries (block statuses and invalidity reasons, cliques, best parents, blockclique blocks) from a snapshot of the graph,
+///   without waiting for the worker to release the shared state (see `state::snapshot` for the concurrency model)
 ///
 /// Note that sending commands and reading the state is done from different, mutually-asynchronous tasks and they can have data that are not sync yet.
 #[derive(Clone)]
@@ -36,24 +43,56 @@ pub struct ConsensusControllerImpl {
     command_sender: MassaSender<ConsensusCommand>,
     channels: ConsensusChannels,
     shared_state: Arc<RwLock<ConsensusState>>,
+    snapshot: SharedGraphSnapshot,
     bootstrap_part_size: u64,
     broadcast_enabled: bool,
+    channel_overflow_policy: ChannelOverflowPolicy,
+    max_send_wait: Duration,
+    massa_metrics: MassaMetrics,
 }
 
 impl ConsensusControllerImpl {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         command_sender: MassaSender<ConsensusCommand>,
         channels: ConsensusChannels,
         shared_state: Arc<RwLock<ConsensusState>>,
+        snapshot: SharedGraphSnapshot,
         bootstrap_part_size: u64,
         broadcast_enabled: bool,
+        channel_overflow_policy: ChannelOverflowPolicy,
+        max_send_wait: Duration,
+        massa_metrics: MassaMetrics,
     ) -> Self {
         Self {
             command_sender,
             channels,
             shared_state,
+            snapshot,
             bootstrap_part_size,
             broadcast_enabled,
+            channel_overflow_policy,
+            max_send_wait,
+            massa_metrics,
+        }
+    }
+
+    /// Send a command to the worker. When the channel is full, the command is either dropped right away
+    /// or the calling thread waits for room in the channel, depending on `channel_overflow_policy`.
+    fn send_command(&self, command: ConsensusCommand, action: &str) {
+        let result = match self.channel_overflow_policy {
+            ChannelOverflowPolicy::Drop => self
+                .command_sender
+                .try_send(command)
+                .map_err(|err| err.to_string()),
+            ChannelOverflowPolicy::Block => self
+                .command_sender
+                .send_timeout(command, self.max_send_wait)
+                .map_err(|err| err.to_string()),
+        };
+        if let Err(err) = result {
+            self.massa_metrics.inc_consensus_dropped_commands();
+            warn!("error trying to {}: {}", action, err);
         }
     }
 }
@@ -77,6 +116,28 @@ impl ConsensusController for ConsensusControllerImpl {
             .extract_block_graph_part(start_slot, end_slot)
     }
 
+    /// Get a page of the block graph, without going through the whole graph.
+    ///
+    /// # Arguments:
+    /// * `start_slot`: the start slot
+    /// * `end_slot`: the end slot
+    /// * `after`: slot and id of the last block of the previous page
+    /// * `limit`: maximum number of blocks in the page
+    ///
+    /// # Returns:
+    /// An export of the blocks of the page
+    fn get_block_graph_page(
+        &self,
+        start_slot: Option<Slot>,
+        end_slot: Option<Slot>,
+        after: Option<(Slot, BlockId)>,
+        limit: usize,
+    ) -> Result<BlockGraphExport, ConsensusError> {
+        self.shared_state
+            .read()
+            .extract_block_graph_page(start_slot, end_slot, after, limit)
+    }
+
     /// Get statuses of blocks present in the graph
     ///
     /// # Arguments:
@@ -85,9 +146,21 @@ impl ConsensusController for ConsensusControllerImpl {
     /// # Returns:
     /// A vector of statuses sorted by the order of the block ids
     fn get_block_statuses(&self, ids: &[BlockId]) -> Vec<BlockGraphStatus> {
-        let read_shared_state = self.shared_state.read();
+        let snapshot = self.snapshot.read().clone();
+        ids.iter().map(|id| snapshot.get_block_status(id)).collect()
+    }
+
+    /// Get why blocks present in the graph were discarded as invalid
+    ///
+    /// # Arguments:
+    /// * `block_ids`: the block ids to get the invalidity reason of
+    ///
+    /// # Returns:
+    /// A vector of reasons sorted by the order of the block ids, `None` for the blocks not known as invalid
+    fn get_invalid_block_reasons(&self, ids: &[BlockId]) -> Vec<Option<InvalidBlockReason>> {
+        let snapshot = self.snapshot.read().clone();
         ids.iter()
-            .map(|id| read_shared_state.get_block_status(id))
+            .map(|id| snapshot.get_invalid_block_reason(id).cloned())
             .collect()
     }
 
@@ -96,7 +169,7 @@ impl ConsensusController for ConsensusControllerImpl {
     /// # Returns:
     /// A vector of cliques
     fn get_cliques(&self) -> Vec<Clique> {
-        self.shared_state.read().max_cliques.clone()
+        self.snapshot.read().get_cliques().to_vec()
     }
 
     /// Get a part of the graph to send to a node so that he can setup his graph.
@@ -194,12 +267,22 @@ impl ConsensusController for ConsensusControllerImpl {
         self.shared_state.read().get_stats()
     }
 
+    /// Get the memory used by the block caches of the consensus
+    fn get_memory_stats(&self) -> ConsensusMemoryStats {
+        self.shared_state.read().get_memory_stats()
+    }
+
+    /// Whether a network partition is suspected
+    fn is_partition_suspected(&self) -> bool {
+        self.shared_state.read().partition_state.is_suspected()
+    }
+
     /// Get the current best parents for a block creation
     ///
     /// # Returns:
     /// A block id and a period for each thread of the graph
     fn get_best_parents(&self) -> Vec<(BlockId, u64)> {
-        self.shared_state.read().best_parents.clone()
+        self.snapshot.read().get_best_parents().to_vec()
     }
 
     /// Get the block, that is in the blockclique, at a given slot.
@@ -210,9 +293,7 @@ impl ConsensusController for ConsensusControllerImpl {
     /// # Returns:
     /// The block id of the block at the given slot if exists
     fn get_blockclique_block_at_slot(&self, slot: Slot) -> Option<BlockId> {
-        self.shared_state
-            .read()
-            .get_blockclique_block_at_slot(&slot)
+        self.snapshot.read().get_blockclique_block_at_slot(&slot)
     }
 
     /// Get the latest block, that is in the blockclique, in the thread of the given slot and before this `slot`.
@@ -223,7 +304,7 @@ impl ConsensusController for ConsensusControllerImpl {
     /// # Returns:
     /// The block id of the latest block in the thread of the given slot and before this slot
     fn get_latest_blockclique_block_at_slot(&self, slot: Slot) -> BlockId {
-        self.shared_state
+        self.snapshot
             .read()
             .get_latest_blockclique_block_at_slot(&slot)
     }
@@ -279,17 +360,10 @@ impl ConsensusController for ConsensusControllerImpl {
                 .add_denunciation_precursor(de_p);
         }
 
-        if let Err(err) = self
-            .command_sender
-            .try_send(ConsensusCommand::RegisterBlock(
-                block_id,
-                slot,
-                block_storage,
-                created,
-            ))
-        {
-            warn!("error trying to register a block: {}", err);
-        }
+        self.send_command(
+            ConsensusCommand::RegisterBlock(block_id, slot, block_storage, created),
+            "register a block",
+        );
     }
 
     fn register_block_header(&self, block_id: BlockId, header: SecureShare<BlockHeader, BlockId>) {
@@ -308,21 +382,22 @@ impl ConsensusController for ConsensusControllerImpl {
             .pool_controller
             .add_denunciation_precursor(de_p);
 
-        if let Err(err) = self
-            .command_sender
-            .try_send(ConsensusCommand::RegisterBlockHeader(block_id, header))
-        {
-            warn!("error trying to register a block header: {}", err);
-        }
+        self.send_command(
+            ConsensusCommand::RegisterBlockHeader(block_id, header),
+            "register a block header",
+        );
     }
 
-    fn mark_invalid_block(&self, block_id: BlockId, header: SecureShare<BlockHeader, BlockId>) {
-        if let Err(err) = self
-            .command_sender
-            .try_send(ConsensusCommand::MarkInvalidBlock(block_id, header))
-        {
-            warn!("error trying to mark block as invalid: {}", err);
-        }
+    fn mark_invalid_block(
+        &self,
+        block_id: BlockId,
+        header: SecureShare<BlockHeader, BlockId>,
+        reason: InvalidBlockReason,
+    ) {
+        self.send_command(
+            ConsensusCommand::MarkInvalidBlock(block_id, header, reason),
+            "mark block as invalid",
+        );
     }
 
     fn clone_box(&self) -> Box<dyn ConsensusController> {
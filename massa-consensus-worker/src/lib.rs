@@ -6,6 +6,8 @@
 //! This thread has a `run` function that triggers the consensus algorithm each slot. It can be interrupted by commands
 //! that are managed on the fly. The consensus worker share a state with a controller. This controller can be called by the others modules.
 //! It avoid sending message to the thread just for getting informations on the consensus.
+//! After each change of the state, the worker also publishes a snapshot of the graph, used by the controller to answer
+//! the frequent queries without waiting for the worker (see `state::snapshot` for the concurrency model).
 //!
 //! Communications with execution is blocking. Communications with protocol blocks on sending information to protocol but not blocking
 //! when protocol sends informations to this module.
@@ -29,6 +31,7 @@
 #![feature(let_chains)]
 #![feature(async_closure)]
 
+mod blocks_file;
 mod commands;
 mod controller;
 mod manager;
@@ -1,6 +1,8 @@
 use core::panic;
+use std::{collections::BTreeSet, ops::Bound};
 
-use massa_consensus_exports::block_status::{BlockStatus, BlockStatusId};
+use massa_consensus_exports::block_status::{BlockStatus, BlockStatusId, DiscardReason};
+use massa_hash::HASH_SIZE_BYTES;
 use massa_models::{
     block_id::BlockId,
     prehash::{PreHashMap, PreHashSet},
@@ -23,6 +25,24 @@ pub struct BlocksState {
     discarded_index: PreHashSet<BlockId>,
     /// ids of active blocks
     active_index: PreHashSet<BlockId>,
+    /// active, stale and invalid blocks, ordered by slot then id
+    graph_slot_index: BTreeSet<(Slot, BlockId)>,
+    /// incremented on each change, or possible change through a mutable access, of the blocks
+    generation: u64,
+}
+
+/// Slot under which a block is listed in the graph, if it is active, stale or invalid.
+/// The final blocks pruned from the graph are not listed: they are not exported.
+fn graph_slot(block_status: &BlockStatus) -> Option<Slot> {
+    match block_status {
+        BlockStatus::Active { a_block, .. } => Some(a_block.slot),
+        BlockStatus::Discarded {
+            reason: DiscardReason::Final,
+            ..
+        } => None,
+        BlockStatus::Discarded { slot, .. } => Some(*slot),
+        _ => None,
+    }
 }
 
 impl BlocksState {
@@ -36,6 +56,8 @@ impl BlocksState {
             waiting_for_dependencies_index: PreHashSet::default(),
             discarded_index: PreHashSet::default(),
             active_index: PreHashSet::default(),
+            graph_slot_index: BTreeSet::new(),
+            generation: 0,
         }
     }
 
@@ -46,6 +68,7 @@ impl BlocksState {
 
     /// Get a mutable reference on a `BlockStatus` from a `BlockId`
     pub fn get_mut(&mut self, block_id: &BlockId) -> Option<&mut BlockStatus> {
+        self.generation += 1;
         self.block_statuses.get_mut(block_id)
     }
 
@@ -54,6 +77,11 @@ impl BlocksState {
         self.sequence_counter
     }
 
+    /// Get the generation, which changes each time the blocks may have changed
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
     /// Get a reference on the list of all blocks stored with the status `Incoming`
     pub fn incoming_blocks(&self) -> &PreHashSet<BlockId> {
         &self.incoming_index
@@ -79,6 +107,25 @@ impl BlocksState {
         &self.active_index
     }
 
+    /// Get the ids of the active, stale and invalid blocks with a slot in `[slot_start, slot_end)`,
+    /// located strictly after `after` in the (slot, id) order, in that order
+    pub fn graph_blocks_in_range(
+        &self,
+        slot_start: Option<Slot>,
+        slot_end: Option<Slot>,
+        after: Option<(Slot, BlockId)>,
+    ) -> impl Iterator<Item = &(Slot, BlockId)> + '_ {
+        let start = slot_start.map(|slot| (slot, BlockId::from_bytes(&[0; HASH_SIZE_BYTES])));
+        let lower = match (after, start) {
+            (Some(after), Some(start)) if after < start => Bound::Included(start),
+            (Some(after), _) => Bound::Excluded(after),
+            (None, Some(start)) => Bound::Included(start),
+            (None, None) => Bound::Unbounded,
+        };
+        let range = self.graph_slot_index.range((lower, Bound::Unbounded));
+        range.take_while(move |(slot, _)| slot_end.map_or(true, |end| *slot < end))
+    }
+
     // Internal function to update the indexes
     fn update_indexes(
         &mut self,
@@ -171,6 +218,7 @@ impl BlocksState {
 
     /// Get a mutable iterator over all the blocks stored in the `BlocksState`
     pub fn iter_mut(&mut self) -> impl Iterator<Item = (&BlockId, &mut BlockStatus)> + '_ {
+        self.generation += 1;
         self.block_statuses.iter_mut()
     }
 
@@ -192,10 +240,14 @@ impl BlocksState {
         block_id: &BlockId,
         callback: F,
     ) {
+        self.generation += 1;
         match self.block_statuses.remove(block_id) {
             Some(block) => {
                 let old_state_id = BlockStatusId::from(&block);
                 self.update_indexes(block_id, Some(&old_state_id), None);
+                if let Some(slot) = graph_slot(&block) {
+                    self.graph_slot_index.remove(&(slot, *block_id));
+                }
                 let Some(mut new_state) = callback(Some(block), &mut self.block_statuses) else { return; };
                 let new_state_id = BlockStatusId::from(&new_state);
                 match (&old_state_id, &new_state_id) {
@@ -312,6 +364,9 @@ impl BlocksState {
                     }
                 }
                 self.update_indexes(block_id, None, Some(&new_state_id));
+                if let Some(slot) = self.block_statuses.get(block_id).and_then(graph_slot) {
+                    self.graph_slot_index.insert((slot, *block_id));
+                }
             }
             None => {
                 let new_state = callback(None, &mut self.block_statuses);
@@ -323,6 +378,9 @@ impl BlocksState {
                             state, block_id
                         );
                     }
+                    if let Some(slot) = graph_slot(&new_state) {
+                        self.graph_slot_index.insert((slot, *block_id));
+                    }
                     self.block_statuses.insert(*block_id, new_state);
                     self.update_indexes(block_id, None, Some(&state));
                 }
@@ -330,3 +388,102 @@ impl BlocksState {
         };
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_hash::Hash;
+    use massa_models::{active_block::ActiveBlock, address::Address};
+    use massa_signature::KeyPair;
+    use massa_storage::Storage;
+
+    fn add_active_block(state: &mut BlocksState, seed: u64, slot: Slot) -> BlockId {
+        let block_id = BlockId::from_bytes(Hash::compute_from(&seed.to_be_bytes()).to_bytes());
+        let a_block = ActiveBlock {
+            creator_address: Address::from_public_key(
+                &KeyPair::generate(0).unwrap().get_public_key(),
+            ),
+            block_id,
+            parents: Vec::new(),
+            children: Vec::new(),
+            descendants: PreHashSet::default(),
+            is_final: false,
+            slot,
+            fitness: 1,
+        };
+        state.transition_map(&block_id, |_, _| {
+            Some(BlockStatus::Active {
+                a_block: Box::new(a_block),
+                storage: Storage::create_root(),
+            })
+        });
+        block_id
+    }
+
+    #[test]
+    fn test_graph_blocks_in_range() {
+        let mut state = BlocksState::new();
+        let mut blocks: Vec<(Slot, BlockId)> = (0..6)
+            .map(|seed| {
+                let slot = Slot::new(seed / 2 + 1, 0);
+                (slot, add_active_block(&mut state, seed, slot))
+            })
+            .collect();
+        blocks.sort_unstable();
+
+        let discard = |state: &mut BlocksState, (slot, block_id): (Slot, BlockId), reason| {
+            state.transition_map(&block_id, |_, _| {
+                Some(BlockStatus::Discarded {
+                    slot,
+                    creator: Address::from_public_key(
+                        &KeyPair::generate(0).unwrap().get_public_key(),
+                    ),
+                    parents: Vec::new(),
+                    reason,
+                    sequence_number: 0,
+                })
+            });
+        };
+
+        // discarded blocks stay listed under their slot
+        let (_, discarded) = blocks[2];
+        discard(&mut state, blocks[2], DiscardReason::Stale);
+        let all: Vec<_> = state
+            .graph_blocks_in_range(None, None, None)
+            .copied()
+            .collect();
+        assert_eq!(all, blocks);
+
+        // slot bounds, the end is excluded
+        let ranged: Vec<_> = state
+            .graph_blocks_in_range(Some(Slot::new(2, 0)), Some(Slot::new(3, 0)), None)
+            .copied()
+            .collect();
+        assert_eq!(ranged, blocks[2..4]);
+
+        // pages continue strictly after the cursor
+        let page: Vec<_> = state
+            .graph_blocks_in_range(Some(Slot::new(1, 0)), None, Some(blocks[2]))
+            .take(2)
+            .copied()
+            .collect();
+        assert_eq!(page, blocks[3..5]);
+
+        // blocks leaving the graph leave the index
+        state.transition_map(&discarded, |_, _| None);
+        assert_eq!(
+            state.graph_blocks_in_range(None, None, None).count(),
+            blocks.len() - 1
+        );
+
+        // the final blocks pruned from the graph are not listed,
+        // so that the pages of the range are full until its end
+        discard(&mut state, blocks[3], DiscardReason::Final);
+        let page: Vec<_> = state
+            .graph_blocks_in_range(Some(Slot::new(1, 0)), None, Some(blocks[1]))
+            .take(2)
+            .copied()
+            .collect();
+        assert_eq!(page, [blocks[4], blocks[5]]);
+    }
+}
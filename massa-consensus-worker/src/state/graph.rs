@@ -97,7 +97,7 @@ impl ConsensusState {
         self.blocks_state.transition_map(block_id, |block_status, block_statuses| {
         if let Some(BlockStatus::Active {
             a_block: active_block,
-            storage: _storage,
+            mut storage,
         }) = block_status
         {
             if active_block.is_final {
@@ -146,6 +146,14 @@ impl ConsensusState {
                 "hash": block_id
             });
 
+            // keep its operations to give them back to the pool, as the block may have been executed
+            let op_ids = storage.get_op_refs().clone();
+            self.reverted_operations.extend(storage.split_off(
+                &Default::default(),
+                &op_ids,
+                &Default::default(),
+            ));
+
             // mark as stale
             self.new_stale_blocks
                 .insert(*block_id, (active_block.creator_address, active_block.slot));
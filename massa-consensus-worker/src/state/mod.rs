@@ -1,5 +1,6 @@
 use std::{
     collections::{HashMap, VecDeque},
+    sync::Arc,
     vec,
 };
 
@@ -21,10 +22,11 @@ use massa_models::{
     slot::Slot,
 };
 use massa_storage::Storage;
-use massa_time::MassaTime;
+use massa_time::{Clock, MassaTime};
 use tracing::debug;
 
 use self::blocks_state::BlocksState;
+use self::stats::PartitionState;
 
 pub mod blocks_state;
 mod clique_computation;
@@ -32,10 +34,42 @@ mod graph;
 mod process;
 mod process_commands;
 mod prune;
+pub mod snapshot;
 mod stats;
 mod tick;
 mod verifications;
 
+/// The blockclique among `max_cliques`
+fn get_blockclique(max_cliques: &[Clique]) -> &Clique {
+    max_cliques
+        .iter()
+        .find(|clique| clique.is_blockclique)
+        .expect("blockclique absent")
+}
+
+/// Status in the graph of a block known by the `BlocksState`
+fn graph_status(
+    block_id: &BlockId,
+    block_status: &BlockStatus,
+    max_cliques: &[Clique],
+) -> BlockGraphStatus {
+    match block_status {
+        BlockStatus::Active { a_block, .. } => {
+            if a_block.is_final {
+                BlockGraphStatus::Final
+            } else if get_blockclique(max_cliques).block_ids.contains(block_id) {
+                BlockGraphStatus::ActiveInBlockclique
+            } else {
+                BlockGraphStatus::ActiveInAlternativeCliques
+            }
+        }
+        BlockStatus::Discarded { .. } => BlockGraphStatus::Discarded,
+        BlockStatus::Incoming(_) => BlockGraphStatus::Incoming,
+        BlockStatus::WaitingForDependencies { .. } => BlockGraphStatus::WaitingForDependencies,
+        BlockStatus::WaitingForSlot(_) => BlockGraphStatus::WaitingForSlot,
+    }
+}
+
 #[derive(Clone)]
 pub struct ConsensusState {
     /// Configuration
@@ -69,6 +103,9 @@ pub struct ConsensusState {
     pub new_final_blocks: PreHashSet<BlockId>,
     /// Newly stale block mapped to creator and slot
     pub new_stale_blocks: PreHashMap<BlockId, (Address, Slot)>,
+    /// Operations of the blocks that became stale or left the blockclique without becoming final,
+    /// to be given back to the pool
+    pub reverted_operations: Storage,
     /// time at which the node was launched (used for desynchronization detection)
     pub launch_time: MassaTime,
     /// Final block stats `(time, creator, is_from_protocol)`
@@ -81,6 +118,10 @@ pub struct ConsensusState {
     pub stats_history_timespan: MassaTime,
     /// the time span considered for desynchronization detection
     pub stats_desync_detection_timespan: MassaTime,
+    /// Registered blocks `(time, created)`, `created` being true for the blocks created by this node (used for partition detection)
+    pub registered_blocks: VecDeque<(MassaTime, bool)>,
+    /// network partition detection state
+    pub partition_state: PartitionState,
     /// blocks we want
     pub wishlist: PreHashMap<BlockId, Option<SecuredHeader>>,
     /// previous blockclique notified to Execution
@@ -90,6 +131,8 @@ pub struct ConsensusState {
     pub nonfinal_active_blocks_per_slot: HashMap<Slot, PreHashSet<BlockId>>,
     /// massa metrics
     pub(crate) massa_metrics: MassaMetrics,
+    /// clock used to read the current time
+    pub(crate) clock: Arc<dyn Clock>,
 }
 
 impl ConsensusState {
@@ -117,102 +160,10 @@ impl ConsensusState {
         self.max_cliques.len()
     }
 
-    /// get the blockclique (or final) block ID at a given slot, if any
-    pub fn get_blockclique_block_at_slot(&self, slot: &Slot) -> Option<BlockId> {
-        // List all blocks at this slot.
-        // The list should be small: make a copy of it to avoid holding the storage lock.
-        let blocks_at_slot = {
-            let storage_read = self.storage.read_blocks();
-            let returned = match storage_read.get_blocks_by_slot(slot) {
-                Some(v) => v.clone(),
-                None => return None,
-            };
-            returned
-        };
-
-        // search for the block in the blockclique
-        let search_in_blockclique = blocks_at_slot
-            .intersection(
-                &self
-                    .max_cliques
-                    .iter()
-                    .find(|c| c.is_blockclique)
-                    .expect("expected one clique to be the blockclique")
-                    .block_ids,
-            )
-            .next();
-        if let Some(found_id) = search_in_blockclique {
-            return Some(*found_id);
-        }
-
-        // block not found in the blockclique: search in the final blocks
-        blocks_at_slot
-            .into_iter()
-            .find(|b_id| match self.blocks_state.get(b_id) {
-                Some(BlockStatus::Active { a_block, .. }) => a_block.is_final,
-                _ => false,
-            })
-    }
-
-    /// get the latest blockclique (or final) block ID at a given slot, if any
-    pub fn get_latest_blockclique_block_at_slot(&self, slot: &Slot) -> BlockId {
-        let (mut best_block_id, mut best_block_period) = self
-            .latest_final_blocks_periods
-            .get(slot.thread as usize)
-            .unwrap_or_else(|| panic!("unexpected not found latest final block period"));
-
-        self.get_blockclique()
-            .iter()
-            .for_each(|id| match self.blocks_state.get(id) {
-                Some(BlockStatus::Active {
-                    a_block,
-                    storage: _,
-                }) => {
-                    if a_block.is_final {
-                        panic!(
-                            "unexpected final block on getting latest blockclique block at slot"
-                        );
-                    }
-                    if a_block.slot.thread == slot.thread
-                        && a_block.slot.period < slot.period
-                        && a_block.slot.period > best_block_period
-                    {
-                        best_block_period = a_block.slot.period;
-                        best_block_id = *id;
-                    }
-                }
-                _ => {
-                    panic!("expected to find only active block but found another status")
-                }
-            });
-        best_block_id
-    }
-
     pub fn get_block_status(&self, block_id: &BlockId) -> BlockGraphStatus {
         match self.blocks_state.get(block_id) {
             None => BlockGraphStatus::NotFound,
-            Some(BlockStatus::Active { a_block, .. }) => {
-                if a_block.is_final {
-                    BlockGraphStatus::Final
-                } else if self
-                    .max_cliques
-                    .iter()
-                    .find(|clique| clique.is_blockclique)
-                    .expect("blockclique absent")
-                    .block_ids
-                    .contains(block_id)
-                {
-                    BlockGraphStatus::ActiveInBlockclique
-                } else {
-                    BlockGraphStatus::ActiveInAlternativeCliques
-                }
-            }
-            Some(BlockStatus::Discarded { .. }) => BlockGraphStatus::Discarded,
-            Some(BlockStatus::Incoming(_)) => BlockGraphStatus::Incoming,
-            Some(BlockStatus::WaitingForDependencies { .. }) => {
-                BlockGraphStatus::WaitingForDependencies
-            }
-            Some(BlockStatus::WaitingForSlot(_)) => BlockGraphStatus::WaitingForSlot,
+            Some(block_status) => graph_status(block_id, block_status, &self.max_cliques),
         }
     }
 
@@ -445,6 +396,78 @@ impl ConsensusState {
         Ok(export)
     }
 
+    /// Get a page of the block graph: at most `limit` active, stale or invalid blocks with a slot in `[slot_start, slot_end)`,
+    /// located strictly after `after` in the (slot, id) order.
+    /// Only the blockclique is exported with the blocks, the incompatibility graph is left empty.
+    pub fn extract_block_graph_page(
+        &self,
+        slot_start: Option<Slot>,
+        slot_end: Option<Slot>,
+        after: Option<(Slot, BlockId)>,
+        limit: usize,
+    ) -> Result<BlockGraphExport, ConsensusError> {
+        let mut export = BlockGraphExport {
+            genesis_blocks: self.genesis_hashes.clone(),
+            active_blocks: PreHashMap::with_capacity(limit),
+            discarded_blocks: PreHashMap::with_capacity(limit),
+            best_parents: self.best_parents.clone(),
+            latest_final_blocks_periods: self.latest_final_blocks_periods.clone(),
+            gi_head: PreHashMap::default(),
+            max_cliques: self
+                .max_cliques
+                .iter()
+                .filter(|clique| clique.is_blockclique)
+                .cloned()
+                .collect(),
+        };
+
+        for (_, hash) in self
+            .blocks_state
+            .graph_blocks_in_range(slot_start, slot_end, after)
+            .take(limit)
+        {
+            match self.blocks_state.get(hash) {
+                Some(BlockStatus::Discarded {
+                    slot,
+                    creator,
+                    parents,
+                    reason,
+                    ..
+                }) => {
+                    export
+                        .discarded_blocks
+                        .insert(*hash, (reason.clone(), (*slot, *creator, parents.clone())));
+                }
+                Some(BlockStatus::Active { a_block, storage }) => {
+                    let stored_block =
+                        storage.read_blocks().get(hash).cloned().ok_or_else(|| {
+                            ConsensusError::MissingBlock(format!(
+                                "missing block in BlockGraphExport::extract_page: {}",
+                                hash
+                            ))
+                        })?;
+                    export.active_blocks.insert(
+                        *hash,
+                        ExportCompiledBlock {
+                            header: stored_block.content.header,
+                            children: a_block
+                                .children
+                                .iter()
+                                .map(|thread| {
+                                    thread.keys().copied().collect::<PreHashSet<BlockId>>()
+                                })
+                                .collect(),
+                            is_final: a_block.is_final,
+                        },
+                    );
+                }
+                _ => continue,
+            }
+        }
+
+        Ok(export)
+    }
+
     /// Gets all stored final blocks, not only the still-useful ones
     /// This is used when initializing Execution from Consensus.
     /// Since the Execution bootstrap snapshot is older than the Consensus snapshot,
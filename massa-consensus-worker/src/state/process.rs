@@ -6,8 +6,9 @@ use std::{
 use massa_consensus_exports::{
     block_status::{BlockStatus, DiscardReason, HeaderOrBlock},
     error::ConsensusError,
+    events::ConsensusEvent,
 };
-use massa_logging::massa_trace;
+use massa_logging::{massa_journal, massa_trace};
 use massa_models::{
     active_block::ActiveBlock,
     address::Address,
@@ -20,8 +21,7 @@ use massa_models::{
 };
 use massa_signature::PublicKey;
 use massa_storage::Storage;
-use massa_time::MassaTime;
-use tracing::log::{debug, info};
+use tracing::{debug, info, info_span};
 
 use crate::state::{
     clique_computation::compute_max_cliques,
@@ -63,7 +63,8 @@ impl ConsensusState {
         current_slot: Option<Slot>,
     ) -> Result<(), ConsensusError> {
         // order processing by (slot, hash)
-        while let Some((_slot, hash)) = to_ack.pop_first() {
+        while let Some((slot, hash)) = to_ack.pop_first() {
+            let _span = info_span!("process_block", block_id = %hash, slot = %slot).entered();
             // When a slot and a block ID is processed through the `process` function, it is possible that it causes others blocks
             // to need processing as well. In this case the `process` function will return them and they will be added to
             // the `to_ack` vector to be processed in the future.
@@ -167,9 +168,11 @@ impl ConsensusState {
                             current_slot,
                         );
                         match &res {
-                            HeaderCheckOutcome::Discard(reason) => {
-                                self.maybe_note_attack_attempt(reason, &block_id)
-                            }
+                            HeaderCheckOutcome::Discard(reason) => self.maybe_note_attack_attempt(
+                                reason,
+                                &block_id,
+                                &stored_block.content.header,
+                            ),
                             _ => {
                                 if self.detect_multistake(&stored_block.content.header) {
                                     return Ok(BTreeSet::new());
@@ -574,7 +577,7 @@ impl ConsensusState {
                 self.config.genesis_timestamp,
                 add_block_slot,
             )?;
-            let now = MassaTime::now()?;
+            let now = self.clock.now()?;
             let diff = now.saturating_sub(add_slot_timestamp);
             self.massa_metrics.inc_block_graph_counter();
             self.massa_metrics.inc_block_graph_ms(diff.to_millis());
@@ -584,7 +587,12 @@ impl ConsensusState {
     }
 
     /// Note an attack attempt if the discard reason indicates one.
-    pub fn maybe_note_attack_attempt(&mut self, reason: &DiscardReason, hash: &BlockId) {
+    pub fn maybe_note_attack_attempt(
+        &mut self,
+        reason: &DiscardReason,
+        hash: &BlockId,
+        header: &SecuredHeader,
+    ) {
         massa_trace!("consensus.block_graph.maybe_note_attack_attempt", {"hash": hash, "reason": reason});
         // If invalid, note the attack attempt.
         if let DiscardReason::Invalid(reason) = reason {
@@ -592,6 +600,22 @@ impl ConsensusState {
                 "consensus.block_graph.maybe_note_attack_attempt DiscardReason::Invalid:{}",
                 reason
             );
+            massa_journal!("invalid_block", {
+                "block_id": hash.to_string(),
+                "slot": header.content.slot.to_string(),
+                "creator": header.content_creator_address.to_string(),
+                "reason": reason
+            });
+            // dropped if the channel is full, the journal keeps the event anyway
+            let _ = self
+                .channels
+                .controller_event_tx
+                .try_send(ConsensusEvent::InvalidBlock {
+                    block_id: *hash,
+                    slot: header.content.slot,
+                    creator: header.content_creator_address,
+                    reason: reason.clone(),
+                });
             self.attack_attempts.push(*hash);
         }
     }
@@ -650,6 +674,29 @@ impl ConsensusState {
             // If `prev_blockclique` is not empty here, it means that it contained elements that are not in the new blockclique anymore.
             // In that case, we mark the blockclique as having changed.
             blockclique_changed = true;
+
+            // blocks leaving the blockclique without becoming final were reverted:
+            // the operations of the ones still active are given back to the pool
+            // (the operations of the stale ones were kept when they became stale)
+            let final_block_ids: PreHashSet<BlockId> = finalized_blocks.values().copied().collect();
+            let mut reverted_blocks = Vec::new();
+            for (block_id, slot) in self
+                .prev_blockclique
+                .iter()
+                .filter(|(block_id, _)| !final_block_ids.contains(*block_id))
+            {
+                reverted_blocks.push(format!("{} at {}", block_id, slot));
+                if let Some(BlockStatus::Active { storage, .. }) = self.blocks_state.get(block_id) {
+                    self.reverted_operations
+                        .claim_operation_refs(storage.get_op_refs());
+                }
+            }
+            if !reverted_blocks.is_empty() {
+                massa_journal!("reorg", {
+                    "reverted_blocks": reverted_blocks,
+                    "blockclique_size": new_blockclique.len()
+                });
+            }
         }
         // Overwrite previous blockclique.
         // Should still be done even if unchanged because elements were removed from it above.
@@ -682,12 +729,13 @@ impl ConsensusState {
     /// 3. get new final blocks
     /// 4. get blockclique
     /// 5. notify Execution
-    /// 6. Process new final blocks
-    /// 7. Notify pool of new final ops
-    /// 8. Notify PoS of final blocks
-    /// 9. notify protocol of block wish list
-    /// 10. note new latest final periods (prune graph if changed)
-    /// 11. add stale blocks to stats
+    /// 6. give the operations of the blocks that became stale or left the blockclique back to Pool
+    /// 7. Process new final blocks
+    /// 8. Notify pool of new final ops
+    /// 9. Notify PoS of final blocks
+    /// 10. notify protocol of block wish list
+    /// 11. note new latest final periods (prune graph if changed)
+    /// 12. add stale blocks to stats
     pub fn block_db_changed(&mut self) -> Result<(), ConsensusError> {
         let final_block_slots = {
             massa_trace!("consensus.consensus_worker.block_db_changed", {});
@@ -713,7 +761,7 @@ impl ConsensusState {
             }
 
             // manage finalized blocks
-            let timestamp = MassaTime::now()?;
+            let timestamp = self.clock.now()?;
             let finalized_blocks = mem::take(&mut self.new_final_blocks);
             let mut final_block_slots = HashMap::with_capacity(finalized_blocks.len());
             let mut final_block_stats = VecDeque::with_capacity(finalized_blocks.len());
@@ -742,7 +790,7 @@ impl ConsensusState {
 
             // add stale blocks to stats
             let new_stale_block_ids_creators_slots = mem::take(&mut self.new_stale_blocks);
-            let timestamp = MassaTime::now()?;
+            let timestamp = self.clock.now()?;
             for (_b_id, (_b_creator, _b_slot)) in new_stale_block_ids_creators_slots.into_iter() {
                 self.stale_block_stats.push_back(timestamp);
             }
@@ -752,6 +800,18 @@ impl ConsensusState {
         // notify execution
         self.notify_execution(final_block_slots);
 
+        // give the operations of the blocks that became stale or left the blockclique back to the pool:
+        // the pool drops the ones that are still executed in other blocks on its next refresh
+        if !self.reverted_operations.get_op_refs().is_empty() {
+            let reverted_operations = mem::replace(
+                &mut self.reverted_operations,
+                self.storage.clone_without_refs(),
+            );
+            self.channels
+                .pool_controller
+                .add_operations(reverted_operations);
+        }
+
         // notify protocol of block wishlist
         let new_wishlist = self.get_block_wishlist()?;
         let new_blocks: PreHashMap<BlockId, Option<SecuredHeader>> = new_wishlist
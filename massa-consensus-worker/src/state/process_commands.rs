@@ -5,9 +5,10 @@ use massa_consensus_exports::{
     error::ConsensusError,
 };
 use massa_logging::massa_trace;
-use massa_models::{block_header::SecuredHeader, block_id::BlockId, slot::Slot};
+use massa_models::{
+    block::InvalidBlockReason, block_header::SecuredHeader, block_id::BlockId, slot::Slot,
+};
 use massa_storage::Storage;
-use massa_time::MassaTime;
 use tracing::debug;
 
 use super::ConsensusState;
@@ -78,10 +79,11 @@ impl ConsensusState {
         }
 
         // Block is coming from protocol mark it for desync calculation
+        let now = self.clock.now()?;
         if !created {
-            let now = MassaTime::now()?;
             self.protocol_blocks.push_back((now, block_id));
         }
+        self.registered_blocks.push_back((now, created));
 
         debug!("received block {} for slot {}", block_id, slot);
 
@@ -143,9 +145,15 @@ impl ConsensusState {
     /// # Arguments:
     /// * `block_id`: Block id of the block to mark as invalid
     /// * `header`: Header of the block to mark as invalid
-    pub fn mark_invalid_block(&mut self, block_id: &BlockId, header: SecuredHeader) {
-        let reason = DiscardReason::Invalid("invalid".to_string());
-        self.maybe_note_attack_attempt(&reason, block_id);
+    /// * `reason`: Why the content of the block was rejected by protocol
+    pub fn mark_invalid_block(
+        &mut self,
+        block_id: &BlockId,
+        header: SecuredHeader,
+        reason: InvalidBlockReason,
+    ) {
+        let reason = DiscardReason::Invalid(reason);
+        self.maybe_note_attack_attempt(&reason, block_id, &header);
         massa_trace!("consensus.block_graph.process.invalid_block", {"block_id": block_id, "reason": reason});
         let sequence_number = self.blocks_state.sequence_counter();
         self.blocks_state.transition_map(block_id, |_, _| {
@@ -196,7 +196,8 @@ impl ConsensusState {
                             discarded_dep_found = true;
                             match reason {
                                 DiscardReason::Invalid(reason) => {
-                                    discard_reason = Some(DiscardReason::Invalid(format!("discarded because depend on block:{} that has discard reason:{}", block_id, reason)));
+                                    discard_reason =
+                                        Some(DiscardReason::Invalid(reason.inherited_from(*dep)));
                                     break;
                                 }
                                 DiscardReason::Stale => discard_reason = Some(DiscardReason::Stale),
@@ -239,7 +240,8 @@ impl ConsensusState {
                             dep_to_discard_found = true;
                             match reason {
                                 Some(DiscardReason::Invalid(reason)) => {
-                                    discard_reason = Some(DiscardReason::Invalid(format!("discarded because depend on block:{} that has discard reason:{}", hash, reason)));
+                                    discard_reason =
+                                        Some(DiscardReason::Invalid(reason.inherited_from(*dep)));
                                     break;
                                 }
                                 Some(DiscardReason::Stale) => {
@@ -0,0 +1,306 @@
+//! Read-only view of the block graph, used to answer the frequent queries of the other modules
+//! without waiting for the consensus worker.
+//!
+//! Concurrency model of the consensus:
+//! - the `ConsensusState` is only modified by the consensus worker thread. It is shared with the
+//!   controller behind a `RwLock`, and the worker holds the write lock for the whole processing of a
+//!   command (block or header registration) or of a slot tick, including the clique computations.
+//! - after each of these changes, the worker downgrades its write lock to a read lock, builds a
+//!   `GraphSnapshot` from the state and replaces the `Arc` stored in the `SharedGraphSnapshot`:
+//!   the queries reading the state do not wait for the build. The snapshot is only rebuilt when the graph may
+//!   have changed, which the generation of the `BlocksState` tells: the slot ticks without block
+//!   to process and the commands about blocks already known cost no rebuild. The cliques only
+//!   change along with the blocks. The lock of the `SharedGraphSnapshot` is only held to clone or
+//!   replace the `Arc`, so the queries answered from a snapshot never wait for a block
+//!   processing: block statuses (asked by protocol for every header it receives) and invalidity
+//!   reasons, cliques, best parents and the blockclique blocks used by the factories and the API.
+//! - the other queries (graph exports, bootstrap parts, stats) need data that is too large to be
+//!   copied after each change. They still take the read lock of the state and wait for the end of
+//!   the current processing.
+//!
+//! A snapshot reflects the state at the end of the last processed command or tick, as the state
+//! read under its lock does: commands are processed asynchronously from the point of view of the
+//! controller anyway.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use massa_consensus_exports::block_status::{BlockStatus, DiscardReason};
+use massa_models::{
+    block::{BlockGraphStatus, InvalidBlockReason},
+    block_id::BlockId,
+    clique::Clique,
+    prehash::{CapacityAllocator, PreHashMap},
+    slot::Slot,
+};
+use parking_lot::RwLock;
+
+use super::{blocks_state::BlocksState, graph_status, ConsensusState};
+
+/// Last snapshot of the graph, shared between the worker and the controller
+pub type SharedGraphSnapshot = Arc<RwLock<Arc<GraphSnapshot>>>;
+
+/// Read-only view of the block graph at the end of the last change of the consensus state
+#[derive(Debug, Default)]
+pub struct GraphSnapshot {
+    /// generation of the `BlocksState` the snapshot was built from
+    generation: u64,
+    /// status of each block known by the graph
+    block_statuses: PreHashMap<BlockId, BlockGraphStatus>,
+    /// why the discarded blocks still known by the graph were invalid, if they were
+    invalid_reasons: PreHashMap<BlockId, InvalidBlockReason>,
+    /// all the cliques
+    max_cliques: Vec<Clique>,
+    /// best parents, one (block id, period) per thread
+    best_parents: Vec<(BlockId, u64)>,
+    /// latest final block of each thread, one (block id, period) per thread
+    latest_final_blocks_periods: Vec<(BlockId, u64)>,
+    /// active blocks of the blockclique that are not final yet
+    blockclique_blocks: BTreeMap<Slot, BlockId>,
+    /// final blocks still kept in memory
+    final_blocks: BTreeMap<Slot, BlockId>,
+}
+
+impl GraphSnapshot {
+    /// Build a snapshot of the current consensus state
+    pub fn new(state: &ConsensusState) -> Self {
+        GraphSnapshot::from_graph(
+            &state.blocks_state,
+            &state.max_cliques,
+            &state.best_parents,
+            &state.latest_final_blocks_periods,
+        )
+    }
+
+    /// Whether the snapshot still reflects `state`, so that it does not need to be rebuilt
+    pub fn is_up_to_date(&self, state: &ConsensusState) -> bool {
+        self.is_up_to_date_with(
+            &state.blocks_state,
+            &state.best_parents,
+            &state.latest_final_blocks_periods,
+        )
+    }
+
+    fn is_up_to_date_with(
+        &self,
+        blocks_state: &BlocksState,
+        best_parents: &[(BlockId, u64)],
+        latest_final_blocks_periods: &[(BlockId, u64)],
+    ) -> bool {
+        self.generation == blocks_state.generation()
+            && self.best_parents == best_parents
+            && self.latest_final_blocks_periods == latest_final_blocks_periods
+    }
+
+    fn from_graph(
+        blocks_state: &BlocksState,
+        max_cliques: &[Clique],
+        best_parents: &[(BlockId, u64)],
+        latest_final_blocks_periods: &[(BlockId, u64)],
+    ) -> Self {
+        let mut block_statuses = PreHashMap::with_capacity(blocks_state.len());
+        let mut invalid_reasons = PreHashMap::default();
+        let mut blockclique_blocks = BTreeMap::new();
+        let mut final_blocks = BTreeMap::new();
+        for (block_id, block_status) in blocks_state.iter() {
+            let status = graph_status(block_id, block_status, max_cliques);
+            match block_status {
+                BlockStatus::Active { a_block, .. } => match status {
+                    BlockGraphStatus::Final => {
+                        final_blocks.insert(a_block.slot, *block_id);
+                    }
+                    BlockGraphStatus::ActiveInBlockclique => {
+                        blockclique_blocks.insert(a_block.slot, *block_id);
+                    }
+                    _ => {}
+                },
+                BlockStatus::Discarded {
+                    reason: DiscardReason::Invalid(reason),
+                    ..
+                } => {
+                    invalid_reasons.insert(*block_id, reason.clone());
+                }
+                _ => {}
+            }
+            block_statuses.insert(*block_id, status);
+        }
+        GraphSnapshot {
+            generation: blocks_state.generation(),
+            block_statuses,
+            invalid_reasons,
+            max_cliques: max_cliques.to_vec(),
+            best_parents: best_parents.to_vec(),
+            latest_final_blocks_periods: latest_final_blocks_periods.to_vec(),
+            blockclique_blocks,
+            final_blocks,
+        }
+    }
+
+    /// Status of a block in the graph
+    pub fn get_block_status(&self, block_id: &BlockId) -> BlockGraphStatus {
+        self.block_statuses
+            .get(block_id)
+            .copied()
+            .unwrap_or(BlockGraphStatus::NotFound)
+    }
+
+    /// Why a block was discarded as invalid, if the graph still knows it as such
+    pub fn get_invalid_block_reason(&self, block_id: &BlockId) -> Option<&InvalidBlockReason> {
+        self.invalid_reasons.get(block_id)
+    }
+
+    /// All the cliques
+    pub fn get_cliques(&self) -> &[Clique] {
+        &self.max_cliques
+    }
+
+    /// Best parents, one (block id, period) per thread
+    pub fn get_best_parents(&self) -> &[(BlockId, u64)] {
+        &self.best_parents
+    }
+
+    /// Blockclique (or final) block ID at a given slot, if any
+    pub fn get_blockclique_block_at_slot(&self, slot: &Slot) -> Option<BlockId> {
+        self.blockclique_blocks
+            .get(slot)
+            .or_else(|| self.final_blocks.get(slot))
+            .copied()
+    }
+
+    /// Latest blockclique (or final) block ID in the thread of `slot` and before `slot`
+    pub fn get_latest_blockclique_block_at_slot(&self, slot: &Slot) -> BlockId {
+        let (mut best_block_id, mut best_block_period) = self
+            .latest_final_blocks_periods
+            .get(slot.thread as usize)
+            .unwrap_or_else(|| panic!("unexpected not found latest final block period"));
+        for (block_slot, block_id) in self.blockclique_blocks.range(..slot) {
+            if block_slot.thread == slot.thread && block_slot.period > best_block_period {
+                best_block_period = block_slot.period;
+                best_block_id = *block_id;
+            }
+        }
+        best_block_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_hash::Hash;
+    use massa_models::{active_block::ActiveBlock, address::Address, prehash::PreHashSet};
+    use massa_signature::KeyPair;
+    use massa_storage::Storage;
+
+    fn add_active_block(state: &mut BlocksState, seed: u64, slot: Slot, is_final: bool) -> BlockId {
+        let block_id = BlockId::from_bytes(Hash::compute_from(&seed.to_be_bytes()).to_bytes());
+        let a_block = ActiveBlock {
+            creator_address: Address::from_public_key(
+                &KeyPair::generate(0).unwrap().get_public_key(),
+            ),
+            block_id,
+            parents: Vec::new(),
+            children: Vec::new(),
+            descendants: PreHashSet::default(),
+            is_final,
+            slot,
+            fitness: 1,
+        };
+        state.transition_map(&block_id, |_, _| {
+            Some(BlockStatus::Active {
+                a_block: Box::new(a_block),
+                storage: Storage::create_root(),
+            })
+        });
+        block_id
+    }
+
+    #[test]
+    fn test_snapshot_statuses() {
+        let mut blocks_state = BlocksState::new();
+        let final_block = add_active_block(&mut blocks_state, 0, Slot::new(1, 0), true);
+        let blockclique_block = add_active_block(&mut blocks_state, 1, Slot::new(2, 0), false);
+        let alternative_block = add_active_block(&mut blocks_state, 2, Slot::new(2, 1), false);
+        let max_cliques = vec![
+            Clique {
+                block_ids: [blockclique_block].into_iter().collect(),
+                fitness: 1,
+                is_blockclique: true,
+            },
+            Clique {
+                block_ids: [alternative_block].into_iter().collect(),
+                fitness: 1,
+                is_blockclique: false,
+            },
+        ];
+        let latest_final_blocks_periods = vec![(final_block, 1), (final_block, 0)];
+        let snapshot = GraphSnapshot::from_graph(
+            &blocks_state,
+            &max_cliques,
+            &latest_final_blocks_periods,
+            &latest_final_blocks_periods,
+        );
+
+        assert_eq!(
+            snapshot.get_block_status(&final_block),
+            BlockGraphStatus::Final
+        );
+        assert_eq!(
+            snapshot.get_block_status(&blockclique_block),
+            BlockGraphStatus::ActiveInBlockclique
+        );
+        assert_eq!(
+            snapshot.get_block_status(&alternative_block),
+            BlockGraphStatus::ActiveInAlternativeCliques
+        );
+        assert_eq!(
+            snapshot.get_blockclique_block_at_slot(&Slot::new(1, 0)),
+            Some(final_block)
+        );
+        assert_eq!(
+            snapshot.get_blockclique_block_at_slot(&Slot::new(2, 1)),
+            None
+        );
+        assert_eq!(
+            snapshot.get_latest_blockclique_block_at_slot(&Slot::new(3, 0)),
+            blockclique_block
+        );
+    }
+
+    #[test]
+    fn test_snapshot_rebuilt_only_on_change() {
+        let mut blocks_state = BlocksState::new();
+        let block_id = add_active_block(&mut blocks_state, 0, Slot::new(1, 0), false);
+        let max_cliques = vec![Clique {
+            block_ids: [block_id].into_iter().collect(),
+            fitness: 1,
+            is_blockclique: true,
+        }];
+        let periods = vec![(block_id, 1)];
+        let snapshot = GraphSnapshot::from_graph(&blocks_state, &max_cliques, &periods, &periods);
+        assert!(snapshot.is_up_to_date_with(&blocks_state, &periods, &periods));
+
+        // reads keep the snapshot up to date
+        assert!(blocks_state.get(&block_id).is_some());
+        assert_eq!(blocks_state.iter().count(), 1);
+        assert!(snapshot.is_up_to_date_with(&blocks_state, &periods, &periods));
+
+        // other best parents need a new snapshot
+        let new_periods = vec![(block_id, 2)];
+        assert!(!snapshot.is_up_to_date_with(&blocks_state, &new_periods, &periods));
+        assert!(!snapshot.is_up_to_date_with(&blocks_state, &periods, &new_periods));
+
+        // a mutable access to a block, as when it becomes final, needs a new snapshot
+        if let Some(BlockStatus::Active { a_block, .. }) = blocks_state.get_mut(&block_id) {
+            a_block.is_final = true;
+        }
+        assert!(!snapshot.is_up_to_date_with(&blocks_state, &periods, &periods));
+        let snapshot = GraphSnapshot::from_graph(&blocks_state, &max_cliques, &periods, &periods);
+        assert_eq!(
+            snapshot.get_block_status(&block_id),
+            BlockGraphStatus::Final
+        );
+
+        // so does a new block
+        add_active_block(&mut blocks_state, 1, Slot::new(1, 1), false);
+        assert!(!snapshot.is_up_to_date_with(&blocks_state, &periods, &periods));
+    }
+}
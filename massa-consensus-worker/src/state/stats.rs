@@ -1,19 +1,70 @@
 use super::ConsensusState;
 use massa_consensus_exports::error::ConsensusError;
-use massa_models::stats::ConsensusStats;
-use massa_time::MassaTime;
+use massa_logging::massa_journal;
+use massa_models::{
+    config::{BLOCK_MEMORY_SIZE_ESTIMATE, HEADER_MEMORY_SIZE_ESTIMATE},
+    stats::{CacheMemoryStats, ConsensusMemoryStats, ConsensusStats},
+};
 use std::cmp::max;
 
-#[cfg(not(feature = "sandbox"))]
-use tracing::log::warn;
+use massa_time::MassaTime;
+use tracing::{info, warn};
 
 #[cfg(not(feature = "sandbox"))]
 use massa_consensus_exports::events::ConsensusEvent;
 
+/// Network partition detection state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PartitionState {
+    /// blocks become final, or the node does not mostly build on its own blocks
+    #[default]
+    NotSuspected,
+    /// suspected since the given time
+    Suspected(MassaTime),
+    /// suspected for longer than the detection time span: production resumed,
+    /// so that the network can't stall if most nodes suspect a partition at the same time.
+    /// A new final block ends it.
+    Expired,
+}
+
+impl PartitionState {
+    /// Whether a network partition is currently suspected
+    pub fn is_suspected(&self) -> bool {
+        matches!(self, PartitionState::Suspected(_))
+    }
+
+    /// State at `now`, given the time of the last final block and the number of blocks
+    /// created by this node and received from the network during the last `timespan`.
+    /// A partition is suspected when no block became final during `timespan` while most of the
+    /// new blocks were created by this node: it then probably builds on a minority fork.
+    fn next(
+        self,
+        now: MassaTime,
+        timespan: MassaTime,
+        last_final_time: MassaTime,
+        created_blocks: usize,
+        received_blocks: usize,
+    ) -> PartitionState {
+        let detection_start = now.saturating_sub(timespan);
+        if last_final_time >= detection_start {
+            return PartitionState::NotSuspected;
+        }
+        match self {
+            PartitionState::NotSuspected if created_blocks > received_blocks => {
+                PartitionState::Suspected(now)
+            }
+            PartitionState::Suspected(since) if now.saturating_sub(since) >= timespan => {
+                PartitionState::Expired
+            }
+            state => state,
+        }
+    }
+}
+
 impl ConsensusState {
     /// Calculate and return stats about consensus
     pub fn get_stats(&self) -> Result<ConsensusStats, ConsensusError> {
-        let timespan_end = max(self.launch_time, MassaTime::now()?);
+        let timespan_end = max(self.launch_time, self.clock.now()?);
         let timespan_start = max(
             timespan_end.saturating_sub(self.config.stats_timespan),
             self.launch_time,
@@ -35,15 +86,44 @@ impl ConsensusState {
             clique_count,
             start_timespan: timespan_start,
             end_timespan: timespan_end,
+            partition_suspected: self.partition_state.is_suspected(),
         })
     }
 
+    /// Estimate the memory used by the active blocks of the graph, by the blocks waiting for their slot
+    /// or their dependencies and by the discarded blocks. Only the last three are bounded by the consensus config.
+    pub fn get_memory_stats(&self) -> ConsensusMemoryStats {
+        ConsensusMemoryStats {
+            active_blocks: CacheMemoryStats::new(
+                self.blocks_state.active_blocks().len(),
+                0,
+                BLOCK_MEMORY_SIZE_ESTIMATE,
+            ),
+            future_blocks: CacheMemoryStats::new(
+                self.blocks_state.waiting_for_slot_blocks().len(),
+                self.config.max_future_processing_blocks,
+                BLOCK_MEMORY_SIZE_ESTIMATE,
+            ),
+            dependency_blocks: CacheMemoryStats::new(
+                self.blocks_state.waiting_for_dependencies_blocks().len(),
+                self.config.max_dependency_blocks,
+                BLOCK_MEMORY_SIZE_ESTIMATE,
+            ),
+            discarded_blocks: CacheMemoryStats::new(
+                self.blocks_state.discarded_blocks().len(),
+                self.config.max_discarded_blocks,
+                HEADER_MEMORY_SIZE_ESTIMATE,
+            ),
+        }
+    }
+
     /// Must be called each tick to update stats. Will detect if a desynchronization happened
     pub fn stats_tick(&mut self) -> Result<(), ConsensusError> {
         #[cfg(not(feature = "sandbox"))]
         {
             self.check_desync()?;
         }
+        self.check_partition()?;
         // prune stats
         self.prune_stats()?;
         Ok(())
@@ -54,7 +134,7 @@ impl ConsensusState {
     /// if none => we are probably desync
     /// Ignore if we are before the last_start_period
     fn check_desync(&mut self) -> Result<(), ConsensusError> {
-        let now = MassaTime::now()?;
+        let now = self.clock.now()?;
         if now
             > max(
                 self.config
@@ -72,6 +152,9 @@ impl ConsensusState {
                 })
         {
             warn!("desynchronization detected because the recent final block history is empty or contains only blocks produced by this node");
+            massa_journal!("desync", {
+                "reason": "the recent final block history is empty or contains only blocks produced by this node"
+            });
             let _ = self
                 .channels
                 .controller_event_tx
@@ -81,9 +164,81 @@ impl ConsensusState {
         Ok(())
     }
 
+    /// Helper function for stats_tick. Suspects a network partition when no block became final for
+    /// `partition_detection_timespan` while most of the new blocks were created by this node: it is
+    /// then probably building on a minority fork, and it stops producing until blocks become final
+    /// again, for at most `partition_detection_timespan`.
+    fn check_partition(&mut self) -> Result<(), ConsensusError> {
+        let timespan = self.config.partition_detection_timespan;
+        if timespan == MassaTime::from_millis(0) {
+            return Ok(());
+        }
+        let now = self.clock.now()?;
+        let last_final_time = self
+            .final_block_stats
+            .back()
+            .map_or(self.launch_time, |(time, _, _)| {
+                max(*time, self.launch_time)
+            });
+        let detection_start = now.saturating_sub(timespan);
+        let (created_blocks, received_blocks) = self
+            .registered_blocks
+            .iter()
+            .filter(|(time, _)| *time >= detection_start)
+            .fold((0, 0), |(created, received), (_, is_created)| {
+                if *is_created {
+                    (created + 1, received)
+                } else {
+                    (created, received + 1)
+                }
+            });
+        let previous_state = self.partition_state;
+        self.partition_state = previous_state.next(
+            now,
+            timespan,
+            last_final_time,
+            created_blocks,
+            received_blocks,
+        );
+        match (previous_state, self.partition_state) {
+            (PartitionState::NotSuspected, PartitionState::Suspected(_)) => {
+                warn!(
+                    "network partition suspected: no block became final for {} ms while {} of the {} new blocks were created by this node",
+                    now.saturating_sub(last_final_time),
+                    created_blocks,
+                    created_blocks + received_blocks
+                );
+                massa_journal!("partition_suspected", {
+                    "last_final_block_time": last_final_time,
+                    "created_blocks": created_blocks,
+                    "received_blocks": received_blocks
+                });
+            }
+            (PartitionState::Suspected(_), PartitionState::Expired) => {
+                warn!(
+                    "network partition suspected for {} ms without new final block: resuming production",
+                    timespan
+                );
+                massa_journal!("partition_expired", {});
+            }
+            (
+                PartitionState::Suspected(_) | PartitionState::Expired,
+                PartitionState::NotSuspected,
+            ) => {
+                info!("network partition not suspected anymore");
+                massa_journal!("partition_resolved", {});
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
     /// Remove old stats from consensus storage
     fn prune_stats(&mut self) -> Result<(), ConsensusError> {
-        let start_time = MassaTime::now()?.saturating_sub(self.stats_history_timespan);
+        let start_time = self
+            .clock
+            .now()?
+            .saturating_sub(self.stats_history_timespan);
         while let Some((t, _, _)) = self.final_block_stats.front() {
             if t < &start_time {
                 self.final_block_stats.pop_front();
@@ -105,6 +260,74 @@ impl ConsensusState {
                 break;
             }
         }
+        while let Some((t, _)) = self.registered_blocks.front() {
+            if t < &start_time {
+                self.registered_blocks.pop_front();
+            } else {
+                break;
+            }
+        }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partition_state() {
+        let timespan = MassaTime::from_millis(100_000);
+        let last_final_time = MassaTime::from_millis(1_000_000);
+        let now = last_final_time.saturating_add(MassaTime::from_millis(150_000));
+        let state = PartitionState::NotSuspected;
+
+        // blocks became final recently
+        assert_eq!(
+            state.next(
+                last_final_time.saturating_add(MassaTime::from_millis(50_000)),
+                timespan,
+                last_final_time,
+                10,
+                0
+            ),
+            PartitionState::NotSuspected
+        );
+        // no final block, but the node mostly receives the blocks of others: the whole network stalls
+        assert_eq!(
+            state.next(now, timespan, last_final_time, 2, 10),
+            PartitionState::NotSuspected
+        );
+        // no final block while the node mostly builds on its own blocks
+        let state = state.next(now, timespan, last_final_time, 10, 2);
+        assert_eq!(state, PartitionState::Suspected(now));
+        assert!(state.is_suspected());
+
+        // still suspected whatever the new blocks, until the detection time span elapsed
+        let later = now.saturating_add(MassaTime::from_millis(50_000));
+        assert_eq!(
+            state.next(later, timespan, last_final_time, 0, 10),
+            PartitionState::Suspected(now)
+        );
+        let state = state.next(
+            now.saturating_add(timespan),
+            timespan,
+            last_final_time,
+            10,
+            2,
+        );
+        assert_eq!(state, PartitionState::Expired);
+        assert!(!state.is_suspected());
+
+        // production resumed: not suspected again until a block becomes final
+        let much_later = now.saturating_add(timespan.saturating_mul(3));
+        assert_eq!(
+            state.next(much_later, timespan, last_final_time, 10, 2),
+            PartitionState::Expired
+        );
+        assert_eq!(
+            state.next(much_later, timespan, much_later, 10, 2),
+            PartitionState::NotSuspected
+        );
+    }
+}
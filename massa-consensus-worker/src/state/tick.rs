@@ -3,6 +3,7 @@ use std::collections::BTreeSet;
 use massa_consensus_exports::{block_status::BlockStatus, error::ConsensusError};
 use massa_logging::massa_trace;
 use massa_models::{block_id::BlockId, slot::Slot};
+use tracing::info_span;
 
 use super::ConsensusState;
 
@@ -15,6 +16,7 @@ impl ConsensusState {
     /// # Returns:
     /// Error if the process of a block returned an error.
     pub fn slot_tick(&mut self, current_slot: Slot) -> Result<(), ConsensusError> {
+        let _span = info_span!("slot_tick", slot = %current_slot).entered();
         massa_trace!("consensus.consensus_worker.slot_tick", {
             "slot": current_slot
         });
@@ -53,7 +55,7 @@ impl ConsensusState {
             DOWNTIME_END_TIMESTAMP, DOWNTIME_END_TIMESTAMP_BOOTSTRAP, DOWNTIME_START_TIMESTAMP,
         };
 
-        let now = massa_time::MassaTime::now().expect("could not get now time");
+        let now = self.clock.now().expect("could not get now time");
 
         // last_start_period should be set to trigger after the DOWNTIME_END_TIMESTAMP
         let start_time = DOWNTIME_START_TIMESTAMP;
@@ -3,7 +3,8 @@ use super::{process::BlockInfos, ConsensusState};
 use massa_consensus_exports::block_status::{BlockStatus, DiscardReason, HeaderOrBlock};
 use massa_logging::massa_trace;
 use massa_models::{
-    block_header::SecuredHeader, block_id::BlockId, prehash::PreHashSet, slot::Slot,
+    block::InvalidBlockReason, block_header::SecuredHeader, block_id::BlockId, prehash::PreHashSet,
+    slot::Slot,
 };
 use tracing::warn;
 
@@ -131,7 +132,7 @@ impl ConsensusState {
         block_id: BlockId,
         header: SecuredHeader,
     ) -> BlockStatus {
-        self.maybe_note_attack_attempt(&reason, &block_id);
+        self.maybe_note_attack_attempt(&reason, &block_id, &header);
         massa_trace!("consensus.block_graph.process.incoming_header.discarded", {"block_id": block_id, "reason": reason});
         // count stales
         if reason == DiscardReason::Stale {
@@ -200,10 +201,13 @@ impl ConsensusState {
         };
         if creator_addr != slot_draw_address {
             // it was not the creator's turn to create a block for this slot
-            return HeaderCheckOutcome::Discard(DiscardReason::Invalid(format!(
-                "Bad creator turn for the slot:{}",
-                header.content.slot
-            )));
+            return HeaderCheckOutcome::Discard(DiscardReason::Invalid(
+                InvalidBlockReason::WrongCreator {
+                    slot: header.content.slot,
+                    expected: slot_draw_address,
+                    creator: creator_addr,
+                },
+            ));
         }
 
         // check if block is in the future: queue it
@@ -224,10 +228,9 @@ impl ConsensusState {
                 Some(BlockStatus::Discarded { reason, .. }) => {
                     // parent is discarded
                     return HeaderCheckOutcome::Discard(match reason {
-                        DiscardReason::Invalid(invalid_reason) => DiscardReason::Invalid(format!(
-                            "discarded because a parent was discarded for the following reason: {}",
-                            invalid_reason
-                        )),
+                        DiscardReason::Invalid(invalid_reason) => {
+                            DiscardReason::Invalid(invalid_reason.inherited_from(parent_hash))
+                        }
                         r => r.clone(),
                     });
                 }
@@ -237,11 +240,22 @@ impl ConsensusState {
                     // parent is active
 
                     // check that the parent is from an earlier slot in the right thread
-                    if parent.slot.thread != parent_thread || parent.slot >= header.content.slot {
-                        return HeaderCheckOutcome::Discard(DiscardReason::Invalid(format!(
-                            "Bad parent {} in thread:{} or slot:{} for {}.",
-                            parent_hash, parent_thread, parent.slot, header.content.slot
-                        )));
+                    if parent.slot.thread != parent_thread {
+                        return HeaderCheckOutcome::Discard(DiscardReason::Invalid(
+                            InvalidBlockReason::WrongParentThread {
+                                parent: parent_hash,
+                                parent_slot: parent.slot,
+                                thread: parent_thread,
+                            },
+                        ));
+                    }
+                    if parent.slot >= header.content.slot {
+                        return HeaderCheckOutcome::Discard(DiscardReason::Invalid(
+                            InvalidBlockReason::ParentsInFuture {
+                                parent: parent_hash,
+                                parent_slot: parent.slot,
+                            },
+                        ));
                     }
 
                     // inherit parent incompatibilities
@@ -249,7 +263,7 @@ impl ConsensusState {
                     if let Some(p_incomp) = self.gi_head.get(&parent_hash) {
                         if !p_incomp.is_disjoint(&parent_set) {
                             return HeaderCheckOutcome::Discard(DiscardReason::Invalid(
-                                "Parent not mutually compatible".to_string(),
+                                InvalidBlockReason::IncompatibleParents,
                             ));
                         }
                         incomp.extend(p_incomp);
@@ -292,8 +306,7 @@ impl ConsensusState {
                 if parent_period < gp_max_slots[parent_i as usize] {
                     // a parent is earlier than a block known by another parent in that thread
                     return HeaderCheckOutcome::Discard(DiscardReason::Invalid(
-                        "a parent is earlier than a block known by another parent in that thread"
-                            .to_string(),
+                        InvalidBlockReason::InconsistentParents,
                     ));
                 }
                 gp_max_slots[parent_i as usize] = parent_period;
@@ -309,14 +322,19 @@ impl ConsensusState {
                     match self.blocks_state.get(&gp_h) {
                         // this grandpa is discarded
                         Some(BlockStatus::Discarded { reason, .. }) => {
-                            return HeaderCheckOutcome::Discard(reason.clone());
+                            return HeaderCheckOutcome::Discard(match reason {
+                                DiscardReason::Invalid(invalid_reason) => {
+                                    DiscardReason::Invalid(invalid_reason.inherited_from(gp_h))
+                                }
+                                r => r.clone(),
+                            });
                         }
                         // this grandpa is active
                         Some(BlockStatus::Active { a_block: gp, .. }) => {
                             if gp.slot.period > gp_max_slots[gp_i as usize] {
                                 if gp_i < parent_i {
                                     return HeaderCheckOutcome::Discard(DiscardReason::Invalid(
-                                        "grandpa error: gp_i < parent_i".to_string(),
+                                        InvalidBlockReason::InconsistentParents,
                                     ));
                                 }
                                 gp_max_slots[gp_i as usize] = gp.slot.period;
@@ -418,7 +436,7 @@ impl ConsensusState {
         // check if the block is incompatible with a parent
         if !incomp.is_disjoint(&parents.iter().map(|(h, _p)| *h).collect()) {
             return HeaderCheckOutcome::Discard(DiscardReason::Invalid(
-                "Block incompatible with a parent".to_string(),
+                InvalidBlockReason::IncompatibleParents,
             ));
         }
 
@@ -470,10 +488,13 @@ impl ConsensusState {
             if endorsement.content_creator_address
                 != endorsement_draws[endorsement.content.index as usize]
             {
-                return EndorsementsCheckOutcome::Discard(DiscardReason::Invalid(format!(
-                    "endorser draw mismatch for header in slot: {}",
-                    header.content.slot
-                )));
+                return EndorsementsCheckOutcome::Discard(DiscardReason::Invalid(
+                    InvalidBlockReason::WrongEndorser {
+                        index: endorsement.content.index,
+                        expected: endorsement_draws[endorsement.content.index as usize],
+                        endorser: endorsement.content_creator_address,
+                    },
+                ));
             }
 
             // note that the following aspects are checked in protocol
@@ -0,0 +1,96 @@
+use massa_hash::Hash;
+use massa_models::{
+    block::BlockDeserializerArgs,
+    block_id::BlockId,
+    config::{
+        ENDORSEMENT_COUNT, MAX_DENUNCIATIONS_PER_BLOCK_HEADER, MAX_OPERATIONS_PER_BLOCK,
+        THREAD_COUNT,
+    },
+    slot::Slot,
+};
+use massa_signature::KeyPair;
+
+use super::tools::{
+    create_block, create_block_with_operations, create_operations, BlockOperations,
+};
+use crate::blocks_file::{load_blocks, serialize_blocks, write_blocks, SavedBlock};
+
+fn block_args() -> BlockDeserializerArgs {
+    BlockDeserializerArgs {
+        thread_count: THREAD_COUNT,
+        max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
+        endorsement_count: ENDORSEMENT_COUNT,
+        max_denunciations_per_block_header: MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
+        last_start_period: Some(0),
+    }
+}
+
+/// Test that the saved blocks are reloaded with their operations, that the blocks with an
+/// invalid operation are dropped and that the end of a truncated file is ignored.
+#[test]
+fn test_blocks_file() {
+    let keypair = KeyPair::generate(0).unwrap();
+    let path = std::env::temp_dir().join(format!(
+        "massa_consensus_blocks_{}.bin",
+        keypair.get_public_key()
+    ));
+    let parents: Vec<BlockId> = (0..THREAD_COUNT)
+        .map(|i| BlockId(Hash::compute_from(&[i])))
+        .collect();
+    let valid_operations = create_operations(
+        &keypair,
+        BlockOperations {
+            valid: 2,
+            invalid: 0,
+            expire_period: 10,
+        },
+    );
+    let invalid_operations = create_operations(
+        &keypair,
+        BlockOperations {
+            valid: 0,
+            invalid: 1,
+            expire_period: 10,
+        },
+    );
+    let blocks: Vec<SavedBlock> = vec![
+        (
+            create_block(Slot::new(1, 0), parents.clone(), &keypair),
+            Vec::new(),
+        ),
+        (
+            create_block_with_operations(
+                Slot::new(1, 1),
+                parents.clone(),
+                &keypair,
+                &invalid_operations,
+            ),
+            invalid_operations,
+        ),
+        (
+            create_block_with_operations(Slot::new(1, 2), parents, &keypair, &valid_operations),
+            valid_operations,
+        ),
+    ];
+
+    let buffer = serialize_blocks(&blocks).unwrap();
+    write_blocks(&path, &buffer).unwrap();
+    let loaded = load_blocks(&path, block_args());
+    assert_eq!(
+        loaded
+            .iter()
+            .map(|(block, operations)| (block.id, operations.len()))
+            .collect::<Vec<_>>(),
+        vec![(blocks[0].0.id, 0), (blocks[2].0.id, 2)]
+    );
+
+    write_blocks(&path, &buffer[..buffer.len() - 1]).unwrap();
+    let loaded = load_blocks(&path, block_args());
+    assert_eq!(
+        loaded.iter().map(|(block, _)| block.id).collect::<Vec<_>>(),
+        vec![blocks[0].0.id]
+    );
+
+    std::fs::remove_file(&path).unwrap();
+    assert!(load_blocks(&path, block_args()).is_empty());
+}
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use massa_consensus_exports::ConsensusConfig;
 use massa_models::{address::Address, block::BlockGraphStatus, slot::Slot};
 use massa_signature::KeyPair;
@@ -5,7 +7,8 @@ use massa_storage::Storage;
 use massa_time::MassaTime;
 
 use super::tools::{
-    consensus_without_pool_test, register_block_and_process_with_tc, TestController,
+    consensus_without_pool_test_with_clock, register_block_and_process_with_tc,
+    virtual_clock_at_period, TestController,
 };
 
 // Always use latest blocks as parents.
@@ -25,8 +28,9 @@ fn test_fts_latest_blocks_as_parents() {
     let storage = Storage::create_root();
     let staking_address = Address::from_public_key(&staking_key.get_public_key());
 
-    consensus_without_pool_test(
+    consensus_without_pool_test_with_clock(
         cfg.clone(),
+        Arc::new(virtual_clock_at_period(&cfg, 3)),
         move |protocol_controller,
               consensus_controller,
               consensus_event_receiver,
@@ -214,8 +218,9 @@ fn test_fts_multiple_max_cliques_1() {
     let storage = Storage::create_root();
     let staking_address = Address::from_public_key(&staking_key.get_public_key());
 
-    consensus_without_pool_test(
+    consensus_without_pool_test_with_clock(
         cfg.clone(),
+        Arc::new(virtual_clock_at_period(&cfg, 5)),
         move |protocol_controller,
               consensus_controller,
               consensus_event_receiver,
@@ -572,8 +577,9 @@ fn test_fts_multiple_max_cliques_2() {
     let storage = Storage::create_root();
     let staking_address = Address::from_public_key(&staking_key.get_public_key());
 
-    consensus_without_pool_test(
+    consensus_without_pool_test_with_clock(
         cfg.clone(),
+        Arc::new(virtual_clock_at_period(&cfg, 15)),
         move |protocol_controller,
               consensus_controller,
               consensus_event_receiver,
@@ -1,5 +1,6 @@
 mod tools;
 
+mod blocks_file_tests;
 pub mod four_threads_scenarios;
 pub mod scenarios;
 pub mod two_threads_scenarios;
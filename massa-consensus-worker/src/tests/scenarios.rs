@@ -1,14 +1,16 @@
 use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
 
 use crate::tests::tools::create_block;
 use massa_consensus_exports::ConsensusConfig;
 use massa_models::{address::Address, block_id::BlockId, slot::Slot};
 use massa_signature::KeyPair;
 use massa_storage::Storage;
-use massa_time::MassaTime;
+use massa_time::{MassaTime, VirtualClock};
 
 use super::tools::{
-    answer_ask_producer_pos, answer_ask_selection_pos, consensus_without_pool_test, register_block,
+    advance_slots, answer_ask_producer_pos, answer_ask_selection_pos,
+    consensus_without_pool_test_with_clock, register_block, virtual_clock_at_period,
 };
 
 #[test]
@@ -26,9 +28,11 @@ fn test_unsorted_block() {
     };
 
     let storage = Storage::create_root();
+    let clock = VirtualClock::new(cfg.genesis_timestamp);
 
-    consensus_without_pool_test(
+    consensus_without_pool_test_with_clock(
         cfg.clone(),
+        Arc::new(clock.clone()),
         move |protocol_controller,
               consensus_controller,
               consensus_event_receiver,
@@ -137,13 +141,12 @@ fn test_unsorted_block() {
                 storage.clone(),
             );
 
+            // reach the slot of the last blocks without waiting for the real time
+            advance_slots(&clock, &cfg, (5 + start_period) * cfg.thread_count as u64);
+
             // block t0s1 and t1s1 are propagated
             let staking_address = Address::from_public_key(&staking_key.get_public_key());
-            answer_ask_producer_pos(
-                &selector_receiver,
-                &staking_address,
-                3000 + start_period * 1000,
-            );
+            answer_ask_producer_pos(&selector_receiver, &staking_address, 1000);
             answer_ask_selection_pos(&selector_receiver, &staking_address, 1000);
 
             answer_ask_producer_pos(&selector_receiver, &staking_address, 1000);
@@ -189,8 +192,9 @@ fn test_grandpa_incompatibility() {
     let storage = Storage::create_root();
     let staking_address = Address::from_public_key(&staking_key.get_public_key());
 
-    consensus_without_pool_test(
+    consensus_without_pool_test_with_clock(
         cfg.clone(),
+        Arc::new(virtual_clock_at_period(&cfg, 35)),
         move |protocol_controller,
               consensus_controller,
               consensus_event_receiver,
@@ -1,4 +1,4 @@
-use std::{sync::Arc, time::Duration, vec};
+use std::{collections::HashMap, sync::Arc, time::Duration, vec};
 
 use crate::start_consensus_worker;
 use crossbeam_channel::Receiver;
@@ -11,14 +11,17 @@ use massa_hash::Hash;
 use massa_metrics::MassaMetrics;
 use massa_models::{
     address::Address,
-    block::{Block, BlockSerializer, SecureShareBlock},
+    amount::Amount,
+    block::{Block, BlockGraphStatus, BlockSerializer, SecureShareBlock},
     block_header::{BlockHeader, BlockHeaderSerializer},
     block_id::BlockId,
     config::{ENDORSEMENT_COUNT, THREAD_COUNT},
-    secure_share::SecureShareContent,
+    operation::{Operation, OperationId, OperationSerializer, OperationType, SecureShareOperation},
+    secure_share::{Id, SecureShareContent},
     slot::Slot,
+    timeslots::get_block_slot_timestamp,
 };
-use massa_pool_exports::test_exports::MockPoolController;
+use massa_pool_exports::{test_exports::MockPoolController, PoolController};
 use massa_pos_exports::{
     test_exports::{MockSelectorController, MockSelectorControllerMessage},
     Selection, SelectorController,
@@ -26,10 +29,42 @@ use massa_pos_exports::{
 use massa_protocol_exports::{MockProtocolController, ProtocolController};
 use massa_signature::KeyPair;
 use massa_storage::Storage;
+use massa_time::{Clock, VirtualClock};
 use parking_lot::Mutex;
 
-pub fn consensus_without_pool_test<F>(cfg: ConsensusConfig, test: F)
-where
+/// Run `test` against a consensus worker without pool that reads the time from `clock`,
+/// typically a `VirtualClock` from `virtual_clock_at_period` or moved forward by the test with `advance_slots`
+pub fn consensus_without_pool_test_with_clock<F>(
+    cfg: ConsensusConfig,
+    clock: Arc<dyn Clock>,
+    test: F,
+) where
+    F: FnOnce(
+        MockProtocolController,
+        Box<dyn ConsensusController>,
+        MassaReceiver<ConsensusEvent>,
+        Box<dyn SelectorController>,
+        Receiver<MockSelectorControllerMessage>,
+    ) -> (
+        MockProtocolController,
+        Box<dyn ConsensusController>,
+        MassaReceiver<ConsensusEvent>,
+        Box<dyn SelectorController>,
+        Receiver<MockSelectorControllerMessage>,
+    ),
+{
+    let (pool_controller, _pool_event_receiver) = MockPoolController::new_with_receiver();
+    consensus_test_with_pool_and_clock(cfg, pool_controller, clock, test)
+}
+
+/// Same as `consensus_without_pool_test_with_clock`, but consensus talks to `pool_controller`,
+/// typically a `MockPoolController` whose receiver is kept by the test
+pub fn consensus_test_with_pool_and_clock<F>(
+    cfg: ConsensusConfig,
+    pool_controller: Box<dyn PoolController>,
+    clock: Arc<dyn Clock>,
+    test: F,
+) where
     F: FnOnce(
         MockProtocolController,
         Box<dyn ConsensusController>,
@@ -62,7 +97,6 @@ where
     protocol_controller
         .expect_clone_box()
         .return_once(move || Box::new(protocol_controller_2));
-    let (pool_controller, _pool_event_receiver) = MockPoolController::new_with_receiver();
     let (selector_controller, selector_receiver) = MockSelectorController::new_with_receiver();
     // for now, execution_rx is ignored: clique updates to Execution pile up and are discarded
     let (execution_controller, execution_rx) = MockExecutionController::new_with_receiver();
@@ -102,6 +136,7 @@ where
             Duration::from_secs(1),
         )
         .0,
+        clock,
     );
 
     // Call test func.
@@ -127,6 +162,29 @@ where
     execution_sink.join().unwrap();
 }
 
+/// Move `clock` forward by `slot_count` slots of `cfg`
+pub fn advance_slots(clock: &VirtualClock, cfg: &ConsensusConfig, slot_count: u64) {
+    let duration = cfg
+        .t0
+        .checked_div_u64(cfg.thread_count as u64)
+        .and_then(|slot_duration| slot_duration.checked_mul(slot_count))
+        .expect("could not compute the duration of the slots");
+    clock.advance(duration);
+}
+
+/// Virtual clock set at the last slot of `period`: blocks of the scenario up to that period are
+/// processed as soon as they are registered instead of waiting for the real time to reach their slot
+pub fn virtual_clock_at_period(cfg: &ConsensusConfig, period: u64) -> VirtualClock {
+    let timestamp = get_block_slot_timestamp(
+        cfg.thread_count,
+        cfg.t0,
+        cfg.genesis_timestamp,
+        Slot::new(period, cfg.thread_count - 1),
+    )
+    .expect("could not compute the timestamp of the period");
+    VirtualClock::new(timestamp)
+}
+
 // returns hash and resulting discarded blocks
 pub fn create_block(slot: Slot, best_parents: Vec<BlockId>, creator: &KeyPair) -> SecureShareBlock {
     create_block_with_merkle_root(
@@ -143,6 +201,84 @@ pub fn create_block_with_merkle_root(
     slot: Slot,
     best_parents: Vec<BlockId>,
     creator: &KeyPair,
+) -> SecureShareBlock {
+    create_block_with_body(
+        operation_merkle_root,
+        slot,
+        best_parents,
+        creator,
+        Default::default(),
+    )
+}
+
+/// Operations put in the body of a block, see `create_operations`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BlockOperations {
+    /// number of operations with a valid signature
+    pub valid: usize,
+    /// number of operations whose signature does not match their content, after the valid ones
+    pub invalid: usize,
+    /// expire period of all the operations
+    pub expire_period: u64,
+}
+
+/// Transactions from `creator` to itself, each one with a different amount so that their ids differ
+pub fn create_operations(
+    creator: &KeyPair,
+    operations: BlockOperations,
+) -> Vec<SecureShareOperation> {
+    let address = Address::from_public_key(&creator.get_public_key());
+    (0..operations.valid + operations.invalid)
+        .map(|index| {
+            let mut operation = Operation::new_verifiable(
+                Operation {
+                    fee: Amount::zero(),
+                    expire_period: operations.expire_period,
+                    op: OperationType::Transaction {
+                        recipient_address: address,
+                        amount: Amount::from_raw(index as u64 + 1),
+                    },
+                },
+                OperationSerializer::new(),
+                creator,
+            )
+            .unwrap();
+            if index >= operations.valid {
+                operation.signature = creator
+                    .sign(&Hash::compute_from("invalid operation".as_bytes()))
+                    .unwrap();
+            }
+            operation
+        })
+        .collect()
+}
+
+/// Same as `create_block` but the body of the block holds `operations`, with the matching merkle root
+pub fn create_block_with_operations(
+    slot: Slot,
+    best_parents: Vec<BlockId>,
+    creator: &KeyPair,
+    operations: &[SecureShareOperation],
+) -> SecureShareBlock {
+    let mut total_hash = Vec::new();
+    for operation in operations {
+        total_hash.extend(operation.id.get_hash().into_bytes());
+    }
+    create_block_with_body(
+        Hash::compute_from(&total_hash),
+        slot,
+        best_parents,
+        creator,
+        operations.iter().map(|operation| operation.id).collect(),
+    )
+}
+
+fn create_block_with_body(
+    operation_merkle_root: Hash,
+    slot: Slot,
+    best_parents: Vec<BlockId>,
+    creator: &KeyPair,
+    operations: Vec<OperationId>,
 ) -> SecureShareBlock {
     let header = BlockHeader::new_verifiable(
         BlockHeader {
@@ -160,10 +296,7 @@ pub fn create_block_with_merkle_root(
     .unwrap();
 
     Block::new_verifiable(
-        Block {
-            header,
-            operations: Default::default(),
-        },
+        Block { header, operations },
         BlockSerializer::new(),
         creator,
     )
@@ -292,3 +425,126 @@ pub fn register_block_and_process(
     answer_ask_selection_pos(selector_receiver, staking_address, timeout_ms);
     return block;
 }
+
+/// Declarative description of a block DAG fed to consensus.
+/// Genesis blocks are named `G0`, `G1`, ... and each declared block is registered and processed
+/// with the creator of the `TestController`, or with the staker given to `block_from`.
+///
+/// ```ignore
+/// let mut dag = BlockDag::new(&tc, genesis);
+/// dag.block("A", (1, 0), &["G0", "G1"])
+///     .block_from("B", (1, 1), &["A", "G1"], &other_staker)
+///     .assert_statuses(&[("A", ActiveInBlockclique), ("B", ActiveInBlockclique)]);
+/// ```
+pub struct BlockDag<'a> {
+    test_controller: &'a TestController,
+    blocks: HashMap<String, BlockId>,
+}
+
+impl<'a> BlockDag<'a> {
+    pub fn new(test_controller: &'a TestController, genesis: Vec<BlockId>) -> Self {
+        let blocks = genesis
+            .into_iter()
+            .enumerate()
+            .map(|(thread, block_id)| (format!("G{}", thread), block_id))
+            .collect();
+        BlockDag {
+            test_controller,
+            blocks,
+        }
+    }
+
+    /// Register and process a block named `name` at `(period, thread)` with the given named parents
+    pub fn block(&mut self, name: &str, slot: (u64, u8), parents: &[&str]) -> &mut Self {
+        let creator = self.test_controller.creator.clone();
+        self.register(name, slot, parents, &creator, Vec::new())
+    }
+
+    /// Same as `block` but the block is created by `staker`,
+    /// which the selector reports as the producer of the slot
+    pub fn block_from(
+        &mut self,
+        name: &str,
+        slot: (u64, u8),
+        parents: &[&str],
+        staker: &KeyPair,
+    ) -> &mut Self {
+        self.register(name, slot, parents, staker, Vec::new())
+    }
+
+    /// Same as `block` but the body of the block is filled with `operations`, which are stored alongside it
+    pub fn block_with_operations(
+        &mut self,
+        name: &str,
+        slot: (u64, u8),
+        parents: &[&str],
+        operations: BlockOperations,
+    ) -> &mut Self {
+        let creator = self.test_controller.creator.clone();
+        let operations = create_operations(&creator, operations);
+        self.register(name, slot, parents, &creator, operations)
+    }
+
+    fn register(
+        &mut self,
+        name: &str,
+        (period, thread): (u64, u8),
+        parents: &[&str],
+        staker: &KeyPair,
+        operations: Vec<SecureShareOperation>,
+    ) -> &mut Self {
+        let parents = parents.iter().map(|parent| self.id(parent)).collect();
+        let tc = self.test_controller;
+        let block = if operations.is_empty() {
+            create_block(Slot::new(period, thread), parents, staker)
+        } else {
+            create_block_with_operations(Slot::new(period, thread), parents, staker, &operations)
+        };
+        let mut storage = tc.storage.clone();
+        storage.store_operations(operations);
+        register_block(
+            &tc.consensus_controller,
+            &tc.selector_receiver,
+            block.clone(),
+            storage,
+        );
+        let staking_address = Address::from_public_key(&staker.get_public_key());
+        answer_ask_producer_pos(&tc.selector_receiver, &staking_address, tc.timeout_ms);
+        answer_ask_selection_pos(&tc.selector_receiver, &staking_address, tc.timeout_ms);
+        self.insert(name, block.id)
+    }
+
+    fn insert(&mut self, name: &str, block_id: BlockId) -> &mut Self {
+        assert!(
+            self.blocks.insert(name.to_string(), block_id).is_none(),
+            "block {} declared twice",
+            name
+        );
+        self
+    }
+
+    /// Id of the block named `name`
+    pub fn id(&self, name: &str) -> BlockId {
+        *self
+            .blocks
+            .get(name)
+            .unwrap_or_else(|| panic!("unknown block {}", name))
+    }
+
+    /// Check the graph status of the named blocks
+    pub fn assert_statuses(&mut self, expected: &[(&str, BlockGraphStatus)]) -> &mut Self {
+        let ids: Vec<BlockId> = expected.iter().map(|(name, _)| self.id(name)).collect();
+        let statuses = self
+            .test_controller
+            .consensus_controller
+            .get_block_statuses(&ids);
+        for ((name, expected_status), status) in expected.iter().zip(statuses) {
+            assert_eq!(
+                &status, expected_status,
+                "incorrect status for block {}",
+                name
+            );
+        }
+        self
+    }
+}
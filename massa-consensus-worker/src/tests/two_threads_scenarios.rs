@@ -1,14 +1,52 @@
-use massa_consensus_exports::ConsensusConfig;
-use massa_models::{address::Address, block::BlockGraphStatus, slot::Slot};
+use std::{sync::Arc, time::Duration};
+
+use massa_channel::receiver::MassaReceiver;
+use massa_consensus_exports::{events::ConsensusEvent, ConsensusConfig};
+use massa_models::{
+    address::Address,
+    block::{BlockGraphStatus, InvalidBlockReason},
+    block_id::BlockId,
+    operation::OperationId,
+    prehash::PreHashSet,
+    slot::Slot,
+};
+use massa_pool_exports::test_exports::{
+    MockPoolController, MockPoolControllerMessage, PoolEventReceiver,
+};
 use massa_signature::KeyPair;
 use massa_storage::Storage;
 use massa_time::MassaTime;
 
 use super::tools::{
-    answer_ask_producer_pos, answer_ask_selection_pos, consensus_without_pool_test, create_block,
-    register_block, register_block_and_process_with_tc, TestController,
+    answer_ask_producer_pos, answer_ask_selection_pos, consensus_test_with_pool_and_clock,
+    consensus_without_pool_test_with_clock, create_block, create_operations, register_block,
+    register_block_and_process_with_tc, virtual_clock_at_period, BlockDag, BlockOperations,
+    TestController,
 };
 
+/// Ids of the operations given to the pool until no command is received for 500 ms
+fn operations_given_to_pool(pool_receiver: &mut PoolEventReceiver) -> PreHashSet<OperationId> {
+    let mut operation_ids = PreHashSet::default();
+    while let Some(command) = pool_receiver.wait_command(MassaTime::from_millis(500), Some) {
+        if let MockPoolControllerMessage::AddOperations { operations } = command {
+            operation_ids.extend(operations.get_op_refs().iter().copied());
+        }
+    }
+    operation_ids
+}
+
+/// Next invalid block reported by consensus, within a second
+fn next_invalid_block(
+    consensus_event_receiver: &MassaReceiver<ConsensusEvent>,
+) -> (BlockId, InvalidBlockReason) {
+    match consensus_event_receiver.recv_timeout(Duration::from_millis(1000)) {
+        Ok(ConsensusEvent::InvalidBlock {
+            block_id, reason, ..
+        }) => (block_id, reason),
+        other => panic!("unexpected consensus event: {:?}", other),
+    }
+}
+
 // Always use latest blocks as parents.
 // Blocks should be finalized as expected.
 #[test]
@@ -26,8 +64,9 @@ fn test_tts_latest_blocks_as_parents() {
     let storage = Storage::create_root();
     let staking_address = Address::from_public_key(&staking_key.get_public_key());
 
-    consensus_without_pool_test(
+    consensus_without_pool_test_with_clock(
         cfg.clone(),
+        Arc::new(virtual_clock_at_period(&cfg, 5)),
         move |protocol_controller,
               consensus_controller,
               consensus_event_receiver,
@@ -47,125 +86,43 @@ fn test_tts_latest_blocks_as_parents() {
                 timeout_ms: 1000,
             };
 
-            // Period 1.
-            let block_1_0 = register_block_and_process_with_tc(
-                Slot::new(1, 0),
-                vec![genesis[0], genesis[1]],
-                &tc,
-            );
-            let block_1_1 = register_block_and_process_with_tc(
-                Slot::new(1, 1),
-                vec![block_1_0.id, genesis[1]],
-                &tc,
-            );
-
-            // Period 2.
-            let block_2_0 = register_block_and_process_with_tc(
-                Slot::new(2, 0),
-                vec![block_1_0.id, block_1_1.id],
-                &tc,
-            );
-            let block_2_1 = register_block_and_process_with_tc(
-                Slot::new(2, 1),
-                vec![block_2_0.id, block_1_1.id],
-                &tc,
-            );
-
-            // Period 3, thread 0.
-            let block_3_0 = register_block_and_process_with_tc(
-                Slot::new(3, 0),
-                vec![block_2_0.id, block_2_1.id],
-                &tc,
-            );
-
-            // block_1_0 has not been finalized yet.
-            assert_eq!(
-                tc.consensus_controller.get_block_statuses(&[
-                    block_1_0.id,
-                    block_1_1.id,
-                    block_2_0.id,
-                    block_2_1.id
-                ]),
-                [
-                    BlockGraphStatus::ActiveInBlockclique,
-                    BlockGraphStatus::ActiveInBlockclique,
-                    BlockGraphStatus::ActiveInBlockclique,
-                    BlockGraphStatus::ActiveInBlockclique,
-                ],
-                "incorrect block statuses"
-            );
-
-            // Period 3, thread 1.
-            let block_3_1 = register_block_and_process_with_tc(
-                Slot::new(3, 1),
-                vec![block_3_0.id, block_2_1.id],
-                &tc,
-            );
-
-            // block_1_0 has been finalized while block_1_1 has not.
-            assert_eq!(
-                tc.consensus_controller.get_block_statuses(&[
-                    block_1_0.id,
-                    block_1_1.id,
-                    block_2_0.id,
-                    block_2_1.id
-                ]),
-                [
-                    BlockGraphStatus::Final,
-                    BlockGraphStatus::ActiveInBlockclique,
-                    BlockGraphStatus::ActiveInBlockclique,
-                    BlockGraphStatus::ActiveInBlockclique,
-                ],
-                "incorrect block statuses"
-            );
-
-            // Period 4, thread 0.
-            let block_4_0 = register_block_and_process_with_tc(
-                Slot::new(4, 0),
-                vec![block_3_0.id, block_3_1.id],
-                &tc,
-            );
-
-            // block_1_1 has been finalized while block_2_0 has not.
-            assert_eq!(
-                tc.consensus_controller.get_block_statuses(&[
-                    block_1_0.id,
-                    block_1_1.id,
-                    block_2_0.id,
-                    block_2_1.id
-                ]),
-                [
-                    BlockGraphStatus::Final,
-                    BlockGraphStatus::Final,
-                    BlockGraphStatus::ActiveInBlockclique,
-                    BlockGraphStatus::ActiveInBlockclique,
-                ],
-                "incorrect block statuses"
-            );
-
-            // Period 4, thread 1.
-            let _block_4_1 = register_block_and_process_with_tc(
-                Slot::new(4, 1),
-                vec![block_4_0.id, block_3_1.id],
-                &tc,
-            );
-
-            // block_2_0 has been finalized while block_2_1 has not.
-            assert_eq!(
-                tc.consensus_controller.get_block_statuses(&[
-                    block_1_0.id,
-                    block_1_1.id,
-                    block_2_0.id,
-                    block_2_1.id
-                ]),
-                [
-                    BlockGraphStatus::Final,
-                    BlockGraphStatus::Final,
-                    BlockGraphStatus::Final,
-                    BlockGraphStatus::ActiveInBlockclique,
-                ],
-                "incorrect block statuses"
-            );
+            let mut dag = BlockDag::new(&tc, genesis);
+            dag.block("1_0", (1, 0), &["G0", "G1"])
+                .block("1_1", (1, 1), &["1_0", "G1"])
+                .block("2_0", (2, 0), &["1_0", "1_1"])
+                .block("2_1", (2, 1), &["2_0", "1_1"])
+                .block("3_0", (3, 0), &["2_0", "2_1"])
+                // block_1_0 has not been finalized yet.
+                .assert_statuses(&[
+                    ("1_0", BlockGraphStatus::ActiveInBlockclique),
+                    ("1_1", BlockGraphStatus::ActiveInBlockclique),
+                    ("2_0", BlockGraphStatus::ActiveInBlockclique),
+                    ("2_1", BlockGraphStatus::ActiveInBlockclique),
+                ])
+                .block("3_1", (3, 1), &["3_0", "2_1"])
+                // block_1_0 has been finalized while block_1_1 has not.
+                .assert_statuses(&[
+                    ("1_0", BlockGraphStatus::Final),
+                    ("1_1", BlockGraphStatus::ActiveInBlockclique),
+                    ("2_0", BlockGraphStatus::ActiveInBlockclique),
+                    ("2_1", BlockGraphStatus::ActiveInBlockclique),
+                ])
+                .block("4_0", (4, 0), &["3_0", "3_1"])
+                // block_1_1 has been finalized while block_2_0 has not.
+                .assert_statuses(&[
+                    ("1_0", BlockGraphStatus::Final),
+                    ("1_1", BlockGraphStatus::Final),
+                    ("2_0", BlockGraphStatus::ActiveInBlockclique),
+                    ("2_1", BlockGraphStatus::ActiveInBlockclique),
+                ])
+                .block("4_1", (4, 1), &["4_0", "3_1"])
+                // block_2_0 has been finalized while block_2_1 has not.
+                .assert_statuses(&[
+                    ("1_0", BlockGraphStatus::Final),
+                    ("1_1", BlockGraphStatus::Final),
+                    ("2_0", BlockGraphStatus::Final),
+                    ("2_1", BlockGraphStatus::ActiveInBlockclique),
+                ]);
 
             (
                 protocol_controller,
@@ -195,8 +152,9 @@ fn test_tts_latest_period_blocks_as_parents() {
     let storage = Storage::create_root();
     let staking_address = Address::from_public_key(&staking_key.get_public_key());
 
-    consensus_without_pool_test(
+    consensus_without_pool_test_with_clock(
         cfg.clone(),
+        Arc::new(virtual_clock_at_period(&cfg, 5)),
         move |protocol_controller,
               consensus_controller,
               consensus_event_receiver,
@@ -216,106 +174,38 @@ fn test_tts_latest_period_blocks_as_parents() {
                 timeout_ms: 1000,
             };
 
-            // Period 1.
-            let block_1_0 = register_block_and_process_with_tc(
-                Slot::new(1, 0),
-                vec![genesis[0], genesis[1]],
-                &tc,
-            );
-            let block_1_1 = register_block_and_process_with_tc(
-                Slot::new(1, 1),
-                vec![genesis[0], genesis[1]],
-                &tc,
-            );
-
-            // Period 2.
-            let block_2_0 = register_block_and_process_with_tc(
-                Slot::new(2, 0),
-                vec![block_1_0.id, block_1_1.id],
-                &tc,
-            );
-            let block_2_1 = register_block_and_process_with_tc(
-                Slot::new(2, 1),
-                vec![block_1_0.id, block_1_1.id],
-                &tc,
-            );
-
-            // Period 3.
-            let block_3_0 = register_block_and_process_with_tc(
-                Slot::new(3, 0),
-                vec![block_2_0.id, block_2_1.id],
-                &tc,
-            );
-            let block_3_1 = register_block_and_process_with_tc(
-                Slot::new(3, 1),
-                vec![block_2_0.id, block_2_1.id],
-                &tc,
-            );
-
-            // block_1_0 and block_1_1 have not been finalized yet.
-            assert_eq!(
-                tc.consensus_controller.get_block_statuses(&[
-                    block_1_0.id,
-                    block_1_1.id,
-                    block_2_0.id,
-                    block_2_1.id
-                ]),
-                [
-                    BlockGraphStatus::ActiveInBlockclique,
-                    BlockGraphStatus::ActiveInBlockclique,
-                    BlockGraphStatus::ActiveInBlockclique,
-                    BlockGraphStatus::ActiveInBlockclique,
-                ],
-                "incorrect block statuses"
-            );
-
-            // Period 4, thread 0.
-            let _block_4_0 = register_block_and_process_with_tc(
-                Slot::new(4, 0),
-                vec![block_3_0.id, block_3_1.id],
-                &tc,
-            );
-
-            // block_1_0 and block_1_1 have been finalized.
-            assert_eq!(
-                tc.consensus_controller.get_block_statuses(&[
-                    block_1_0.id,
-                    block_1_1.id,
-                    block_2_0.id,
-                    block_2_1.id
-                ]),
-                [
-                    BlockGraphStatus::Final,
-                    BlockGraphStatus::Final,
-                    BlockGraphStatus::ActiveInBlockclique,
-                    BlockGraphStatus::ActiveInBlockclique,
-                ],
-                "incorrect block statuses"
-            );
-
-            // Period 4, thread 1.
-            let _block_4_1 = register_block_and_process_with_tc(
-                Slot::new(4, 1),
-                vec![block_3_0.id, block_3_1.id],
-                &tc,
-            );
-
-            // No new finalized blocks.
-            assert_eq!(
-                tc.consensus_controller.get_block_statuses(&[
-                    block_1_0.id,
-                    block_1_1.id,
-                    block_2_0.id,
-                    block_2_1.id
-                ]),
-                [
-                    BlockGraphStatus::Final,
-                    BlockGraphStatus::Final,
-                    BlockGraphStatus::ActiveInBlockclique,
-                    BlockGraphStatus::ActiveInBlockclique,
-                ],
-                "incorrect block statuses"
-            );
+            // Each thread has its own staker.
+            let other_staker = KeyPair::generate(0).unwrap();
+            let mut dag = BlockDag::new(&tc, genesis);
+            dag.block("1_0", (1, 0), &["G0", "G1"])
+                .block_from("1_1", (1, 1), &["G0", "G1"], &other_staker)
+                .block("2_0", (2, 0), &["1_0", "1_1"])
+                .block_from("2_1", (2, 1), &["1_0", "1_1"], &other_staker)
+                .block("3_0", (3, 0), &["2_0", "2_1"])
+                .block_from("3_1", (3, 1), &["2_0", "2_1"], &other_staker)
+                // block_1_0 and block_1_1 have not been finalized yet.
+                .assert_statuses(&[
+                    ("1_0", BlockGraphStatus::ActiveInBlockclique),
+                    ("1_1", BlockGraphStatus::ActiveInBlockclique),
+                    ("2_0", BlockGraphStatus::ActiveInBlockclique),
+                    ("2_1", BlockGraphStatus::ActiveInBlockclique),
+                ])
+                .block("4_0", (4, 0), &["3_0", "3_1"])
+                // block_1_0 and block_1_1 have been finalized.
+                .assert_statuses(&[
+                    ("1_0", BlockGraphStatus::Final),
+                    ("1_1", BlockGraphStatus::Final),
+                    ("2_0", BlockGraphStatus::ActiveInBlockclique),
+                    ("2_1", BlockGraphStatus::ActiveInBlockclique),
+                ])
+                .block_from("4_1", (4, 1), &["3_0", "3_1"], &other_staker)
+                // No new finalized blocks.
+                .assert_statuses(&[
+                    ("1_0", BlockGraphStatus::Final),
+                    ("1_1", BlockGraphStatus::Final),
+                    ("2_0", BlockGraphStatus::ActiveInBlockclique),
+                    ("2_1", BlockGraphStatus::ActiveInBlockclique),
+                ]);
 
             (
                 protocol_controller,
@@ -345,8 +235,9 @@ fn test_tts_mixed_blocks_as_parents() {
     let storage = Storage::create_root();
     let staking_address = Address::from_public_key(&staking_key.get_public_key());
 
-    consensus_without_pool_test(
+    consensus_without_pool_test_with_clock(
         cfg.clone(),
+        Arc::new(virtual_clock_at_period(&cfg, 5)),
         move |protocol_controller,
               consensus_controller,
               consensus_event_receiver,
@@ -366,125 +257,43 @@ fn test_tts_mixed_blocks_as_parents() {
                 timeout_ms: 1000,
             };
 
-            // Period 1.
-            let block_1_0 = register_block_and_process_with_tc(
-                Slot::new(1, 0),
-                vec![genesis[0], genesis[1]],
-                &tc,
-            );
-            let block_1_1 = register_block_and_process_with_tc(
-                Slot::new(1, 1),
-                vec![block_1_0.id, genesis[1]],
-                &tc,
-            );
-
-            // Period 2.
-            let block_2_0 = register_block_and_process_with_tc(
-                Slot::new(2, 0),
-                vec![block_1_0.id, block_1_1.id],
-                &tc,
-            );
-            let block_2_1 = register_block_and_process_with_tc(
-                Slot::new(2, 1),
-                vec![block_1_0.id, block_1_1.id],
-                &tc,
-            );
-
-            // Period 3, thread 0.
-            let block_3_0 = register_block_and_process_with_tc(
-                Slot::new(3, 0),
-                vec![block_2_0.id, block_2_1.id],
-                &tc,
-            );
-
-            // block_1_0 has not been finalized yet.
-            assert_eq!(
-                tc.consensus_controller.get_block_statuses(&[
-                    block_1_0.id,
-                    block_1_1.id,
-                    block_2_0.id,
-                    block_2_1.id
-                ]),
-                [
-                    BlockGraphStatus::ActiveInBlockclique,
-                    BlockGraphStatus::ActiveInBlockclique,
-                    BlockGraphStatus::ActiveInBlockclique,
-                    BlockGraphStatus::ActiveInBlockclique,
-                ],
-                "incorrect block statuses"
-            );
-
-            // Period 3, thread 1.
-            let block_3_1 = register_block_and_process_with_tc(
-                Slot::new(3, 1),
-                vec![block_3_0.id, block_2_1.id],
-                &tc,
-            );
-
-            // block_1_0 has been finalized while block_1_1 has not.
-            assert_eq!(
-                tc.consensus_controller.get_block_statuses(&[
-                    block_1_0.id,
-                    block_1_1.id,
-                    block_2_0.id,
-                    block_2_1.id
-                ]),
-                [
-                    BlockGraphStatus::Final,
-                    BlockGraphStatus::ActiveInBlockclique,
-                    BlockGraphStatus::ActiveInBlockclique,
-                    BlockGraphStatus::ActiveInBlockclique,
-                ],
-                "incorrect block statuses"
-            );
-
-            // Period 4, thread 0.
-            let _block_4_0 = register_block_and_process_with_tc(
-                Slot::new(4, 0),
-                vec![block_3_0.id, block_3_1.id],
-                &tc,
-            );
-
-            // block_1_1 has been finalized while block_2_0 has not.
-            assert_eq!(
-                tc.consensus_controller.get_block_statuses(&[
-                    block_1_0.id,
-                    block_1_1.id,
-                    block_2_0.id,
-                    block_2_1.id
-                ]),
-                [
-                    BlockGraphStatus::Final,
-                    BlockGraphStatus::Final,
-                    BlockGraphStatus::ActiveInBlockclique,
-                    BlockGraphStatus::ActiveInBlockclique,
-                ],
-                "incorrect block statuses"
-            );
-
-            // Period 4, thread 1.
-            let _block_4_1 = register_block_and_process_with_tc(
-                Slot::new(4, 1),
-                vec![block_3_0.id, block_3_1.id],
-                &tc,
-            );
-
-            // Neither of block_2_0 and block_2_1 have been finalized.
-            assert_eq!(
-                tc.consensus_controller.get_block_statuses(&[
-                    block_1_0.id,
-                    block_1_1.id,
-                    block_2_0.id,
-                    block_2_1.id
-                ]),
-                [
-                    BlockGraphStatus::Final,
-                    BlockGraphStatus::Final,
-                    BlockGraphStatus::ActiveInBlockclique,
-                    BlockGraphStatus::ActiveInBlockclique,
-                ],
-                "incorrect block statuses"
-            );
+            let mut dag = BlockDag::new(&tc, genesis);
+            dag.block("1_0", (1, 0), &["G0", "G1"])
+                .block("1_1", (1, 1), &["1_0", "G1"])
+                .block("2_0", (2, 0), &["1_0", "1_1"])
+                .block("2_1", (2, 1), &["1_0", "1_1"])
+                .block("3_0", (3, 0), &["2_0", "2_1"])
+                // block_1_0 has not been finalized yet.
+                .assert_statuses(&[
+                    ("1_0", BlockGraphStatus::ActiveInBlockclique),
+                    ("1_1", BlockGraphStatus::ActiveInBlockclique),
+                    ("2_0", BlockGraphStatus::ActiveInBlockclique),
+                    ("2_1", BlockGraphStatus::ActiveInBlockclique),
+                ])
+                .block("3_1", (3, 1), &["3_0", "2_1"])
+                // block_1_0 has been finalized while block_1_1 has not.
+                .assert_statuses(&[
+                    ("1_0", BlockGraphStatus::Final),
+                    ("1_1", BlockGraphStatus::ActiveInBlockclique),
+                    ("2_0", BlockGraphStatus::ActiveInBlockclique),
+                    ("2_1", BlockGraphStatus::ActiveInBlockclique),
+                ])
+                .block("4_0", (4, 0), &["3_0", "3_1"])
+                // block_1_1 has been finalized while block_2_0 has not.
+                .assert_statuses(&[
+                    ("1_0", BlockGraphStatus::Final),
+                    ("1_1", BlockGraphStatus::Final),
+                    ("2_0", BlockGraphStatus::ActiveInBlockclique),
+                    ("2_1", BlockGraphStatus::ActiveInBlockclique),
+                ])
+                .block("4_1", (4, 1), &["3_0", "3_1"])
+                // Neither of block_2_0 and block_2_1 have been finalized.
+                .assert_statuses(&[
+                    ("1_0", BlockGraphStatus::Final),
+                    ("1_1", BlockGraphStatus::Final),
+                    ("2_0", BlockGraphStatus::ActiveInBlockclique),
+                    ("2_1", BlockGraphStatus::ActiveInBlockclique),
+                ]);
 
             (
                 protocol_controller,
@@ -515,8 +324,9 @@ fn test_tts_p2_depends_on_p0_1() {
     let storage = Storage::create_root();
     let staking_address = Address::from_public_key(&staking_key.get_public_key());
 
-    consensus_without_pool_test(
+    consensus_without_pool_test_with_clock(
         cfg.clone(),
+        Arc::new(virtual_clock_at_period(&cfg, 5)),
         move |protocol_controller,
               consensus_controller,
               consensus_event_receiver,
@@ -654,8 +464,9 @@ fn test_tts_p2_depends_on_p0_2() {
     let storage = Storage::create_root();
     let staking_address = Address::from_public_key(&staking_key.get_public_key());
 
-    consensus_without_pool_test(
+    consensus_without_pool_test_with_clock(
         cfg.clone(),
+        Arc::new(virtual_clock_at_period(&cfg, 5)),
         move |protocol_controller,
               consensus_controller,
               consensus_event_receiver,
@@ -778,8 +589,9 @@ fn test_tts_p3_depends_on_p0() {
     let storage = Storage::create_root();
     let staking_address = Address::from_public_key(&staking_key.get_public_key());
 
-    consensus_without_pool_test(
+    consensus_without_pool_test_with_clock(
         cfg.clone(),
+        Arc::new(virtual_clock_at_period(&cfg, 5)),
         move |protocol_controller,
               consensus_controller,
               consensus_event_receiver,
@@ -879,8 +691,9 @@ fn test_tts_multiple_blocks_depend_on_p0_no_incomp() {
     let storage = Storage::create_root();
     let staking_address = Address::from_public_key(&staking_key.get_public_key());
 
-    consensus_without_pool_test(
+    consensus_without_pool_test_with_clock(
         cfg.clone(),
+        Arc::new(virtual_clock_at_period(&cfg, 5)),
         move |protocol_controller,
               consensus_controller,
               consensus_event_receiver,
@@ -1054,8 +867,9 @@ fn test_tts_multiple_blocks_depend_on_p0_grandpa_incomp() {
     let storage = Storage::create_root();
     let staking_address = Address::from_public_key(&staking_key.get_public_key());
 
-    consensus_without_pool_test(
+    consensus_without_pool_test_with_clock(
         cfg.clone(),
+        Arc::new(virtual_clock_at_period(&cfg, 5)),
         move |protocol_controller,
               consensus_controller,
               consensus_event_receiver,
@@ -1185,8 +999,9 @@ fn test_tts_parent_registered_later() {
     let storage = Storage::create_root();
     let staking_address = Address::from_public_key(&staking_key.get_public_key());
 
-    consensus_without_pool_test(
+    consensus_without_pool_test_with_clock(
         cfg.clone(),
+        Arc::new(virtual_clock_at_period(&cfg, 5)),
         move |protocol_controller,
               consensus_controller,
               consensus_event_receiver,
@@ -1328,8 +1143,9 @@ fn test_tts_incompatible_parents() {
     let storage = Storage::create_root();
     let staking_address = Address::from_public_key(&staking_key.get_public_key());
 
-    consensus_without_pool_test(
+    consensus_without_pool_test_with_clock(
         cfg.clone(),
+        Arc::new(virtual_clock_at_period(&cfg, 5)),
         move |protocol_controller,
               consensus_controller,
               consensus_event_receiver,
@@ -1428,3 +1244,286 @@ fn test_tts_incompatible_parents() {
         },
     );
 }
+
+// Blocks carrying operations in their body, some of them with an invalid signature.
+// Consensus does not check the operations themselves so all blocks are kept,
+// and their operations stay available in the storage.
+#[test]
+fn test_tts_blocks_with_operations() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let cfg = ConsensusConfig {
+        t0: MassaTime::from_millis(200),
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now().unwrap(),
+        force_keep_final_periods_without_ops: 128,
+        force_keep_final_periods: 10,
+        delta_f0: 4,
+        ..ConsensusConfig::default()
+    };
+    let storage = Storage::create_root();
+    let staking_address = Address::from_public_key(&staking_key.get_public_key());
+
+    consensus_without_pool_test_with_clock(
+        cfg.clone(),
+        Arc::new(virtual_clock_at_period(&cfg, 5)),
+        move |protocol_controller,
+              consensus_controller,
+              consensus_event_receiver,
+              selector_controller,
+              selector_receiver| {
+            let genesis = consensus_controller
+                .get_block_graph_status(None, None)
+                .expect("could not get block graph status")
+                .genesis_blocks;
+
+            let tc = TestController {
+                creator: staking_key,
+                consensus_controller,
+                selector_receiver,
+                storage,
+                staking_address,
+                timeout_ms: 1000,
+            };
+
+            let operations = BlockOperations {
+                valid: 10,
+                invalid: 2,
+                expire_period: 10,
+            };
+            let mut dag = BlockDag::new(&tc, genesis);
+            dag.block_with_operations("1_0", (1, 0), &["G0", "G1"], operations)
+                .block("1_1", (1, 1), &["1_0", "G1"])
+                .block_with_operations(
+                    "2_0",
+                    (2, 0),
+                    &["1_0", "1_1"],
+                    BlockOperations {
+                        valid: 3,
+                        ..Default::default()
+                    },
+                )
+                .assert_statuses(&[
+                    ("1_0", BlockGraphStatus::ActiveInBlockclique),
+                    ("1_1", BlockGraphStatus::ActiveInBlockclique),
+                    ("2_0", BlockGraphStatus::ActiveInBlockclique),
+                ]);
+
+            let created = create_operations(&tc.creator, operations);
+            assert_eq!(created.len(), 12);
+            assert!(created[..10]
+                .iter()
+                .all(|operation| operation.verify_signature().is_ok()));
+            assert!(created[10..]
+                .iter()
+                .all(|operation| operation.verify_signature().is_err()));
+            let stored_operations = tc.storage.read_operations();
+            for operation in &created {
+                assert!(
+                    stored_operations.get(&operation.id).is_some(),
+                    "operation {} missing from storage",
+                    operation.id
+                );
+            }
+            drop(stored_operations);
+
+            (
+                protocol_controller,
+                tc.consensus_controller,
+                consensus_event_receiver,
+                selector_controller,
+                tc.selector_receiver,
+            )
+        },
+    );
+}
+
+// A block leaving the blockclique without becoming final was reverted by execution:
+// its operations are given back to the pool.
+#[test]
+fn test_tts_operations_of_blocks_leaving_blockclique_given_back_to_pool() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let cfg = ConsensusConfig {
+        t0: MassaTime::from_millis(200),
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now().unwrap(),
+        force_keep_final_periods_without_ops: 128,
+        force_keep_final_periods: 10,
+        delta_f0: 32,
+        ..ConsensusConfig::default()
+    };
+    let storage = Storage::create_root();
+    let staking_address = Address::from_public_key(&staking_key.get_public_key());
+    let (pool_controller, mut pool_receiver) = MockPoolController::new_with_receiver();
+
+    consensus_test_with_pool_and_clock(
+        cfg.clone(),
+        pool_controller,
+        Arc::new(virtual_clock_at_period(&cfg, 5)),
+        move |protocol_controller,
+              consensus_controller,
+              consensus_event_receiver,
+              selector_controller,
+              selector_receiver| {
+            let genesis = consensus_controller
+                .get_block_graph_status(None, None)
+                .expect("could not get block graph status")
+                .genesis_blocks;
+
+            let tc = TestController {
+                creator: staking_key,
+                consensus_controller,
+                selector_receiver,
+                storage,
+                staking_address,
+                timeout_ms: 1000,
+            };
+
+            let operations = BlockOperations {
+                valid: 3,
+                expire_period: 10,
+                ..Default::default()
+            };
+            let operation_ids: PreHashSet<OperationId> = create_operations(&tc.creator, operations)
+                .iter()
+                .map(|operation| operation.id)
+                .collect();
+
+            let other_staker = KeyPair::generate(0).unwrap();
+            let mut dag = BlockDag::new(&tc, genesis);
+            dag.block_with_operations("1_0", (1, 0), &["G0", "G1"], operations)
+                .assert_statuses(&[("1_0", BlockGraphStatus::ActiveInBlockclique)]);
+            // the operations of the blockclique are not given back
+            assert!(operations_given_to_pool(&mut pool_receiver).is_empty());
+
+            // a longer incompatible branch becomes the blockclique
+            dag.block_from("1_0b", (1, 0), &["G0", "G1"], &other_staker)
+                .block_from("1_1b", (1, 1), &["1_0b", "G1"], &other_staker)
+                .assert_statuses(&[
+                    ("1_0", BlockGraphStatus::ActiveInAlternativeCliques),
+                    ("1_0b", BlockGraphStatus::ActiveInBlockclique),
+                    ("1_1b", BlockGraphStatus::ActiveInBlockclique),
+                ]);
+            assert_eq!(operations_given_to_pool(&mut pool_receiver), operation_ids);
+
+            (
+                protocol_controller,
+                tc.consensus_controller,
+                consensus_event_receiver,
+                selector_controller,
+                tc.selector_receiver,
+            )
+        },
+    );
+}
+
+// A block from a creator that was not selected is invalid, and so are its descendants.
+// Consensus reports why, and keeps the reason for the queries.
+#[test]
+fn test_tts_invalid_block_reason() {
+    let staking_key: KeyPair = KeyPair::generate(0).unwrap();
+    let cfg = ConsensusConfig {
+        t0: MassaTime::from_millis(200),
+        thread_count: 2,
+        genesis_timestamp: MassaTime::now().unwrap(),
+        force_keep_final_periods_without_ops: 128,
+        force_keep_final_periods: 10,
+        delta_f0: 4,
+        ..ConsensusConfig::default()
+    };
+    let storage = Storage::create_root();
+    let staking_address = Address::from_public_key(&staking_key.get_public_key());
+
+    consensus_without_pool_test_with_clock(
+        cfg.clone(),
+        Arc::new(virtual_clock_at_period(&cfg, 5)),
+        move |protocol_controller,
+              consensus_controller,
+              consensus_event_receiver,
+              selector_controller,
+              selector_receiver| {
+            let genesis = consensus_controller
+                .get_block_graph_status(None, None)
+                .expect("could not get block graph status")
+                .genesis_blocks;
+
+            let tc = TestController {
+                creator: staking_key,
+                consensus_controller,
+                selector_receiver,
+                storage,
+                staking_address,
+                timeout_ms: 1000,
+            };
+
+            let mut dag = BlockDag::new(&tc, genesis);
+            dag.block("1_0", (1, 0), &["G0", "G1"]);
+            let block_1_0 = dag.id("1_0");
+
+            // created by another staker while the selector draws the staking address
+            let other_staker = KeyPair::generate(0).unwrap();
+            let other_address = Address::from_public_key(&other_staker.get_public_key());
+            let block_1_1 = create_block(
+                Slot::new(1, 1),
+                vec![block_1_0, dag.id("G1")],
+                &other_staker,
+            );
+            let mut block_storage = tc.storage.clone();
+            block_storage.store_block(block_1_1.clone());
+            tc.consensus_controller.register_block(
+                block_1_1.id,
+                Slot::new(1, 1),
+                block_storage,
+                false,
+            );
+            answer_ask_producer_pos(&tc.selector_receiver, &staking_address, tc.timeout_ms);
+            let wrong_creator = InvalidBlockReason::WrongCreator {
+                slot: Slot::new(1, 1),
+                expected: staking_address,
+                creator: other_address,
+            };
+            assert_eq!(
+                next_invalid_block(&consensus_event_receiver),
+                (block_1_1.id, wrong_creator.clone())
+            );
+
+            // its child points to it
+            let block_2_0 =
+                create_block(Slot::new(2, 0), vec![block_1_0, block_1_1.id], &tc.creator);
+            register_block(
+                &tc.consensus_controller,
+                &tc.selector_receiver,
+                block_2_0.clone(),
+                tc.storage.clone(),
+            );
+            assert_eq!(
+                next_invalid_block(&consensus_event_receiver),
+                (
+                    block_2_0.id,
+                    InvalidBlockReason::InvalidAncestor {
+                        ancestor: block_1_1.id
+                    }
+                )
+            );
+
+            // the reasons are kept while the graph knows the blocks
+            assert_eq!(
+                tc.consensus_controller
+                    .get_invalid_block_reasons(&[block_1_1.id, block_1_0]),
+                vec![Some(wrong_creator), None]
+            );
+            assert_eq!(
+                tc.consensus_controller
+                    .get_block_statuses(&[block_1_1.id, block_2_0.id]),
+                vec![BlockGraphStatus::Discarded, BlockGraphStatus::Discarded]
+            );
+
+            (
+                protocol_controller,
+                tc.consensus_controller,
+                consensus_event_receiver,
+                selector_controller,
+                tc.selector_receiver,
+            )
+        },
+    );
+}
@@ -16,15 +16,18 @@ use massa_models::{
     timeslots::{get_block_slot_timestamp, get_latest_block_slot_at_timestamp},
 };
 use massa_storage::Storage;
-use massa_time::MassaTime;
+use massa_time::Clock;
 use parking_lot::RwLock;
 use std::{
     collections::{HashMap, VecDeque},
     sync::Arc,
 };
-use tracing::log::info;
+use tracing::info;
 
-use crate::{commands::ConsensusCommand, state::ConsensusState};
+use crate::{
+    commands::ConsensusCommand,
+    state::{snapshot::SharedGraphSnapshot, ConsensusState},
+};
 
 use super::ConsensusWorker;
 
@@ -74,8 +77,10 @@ impl ConsensusWorker {
     /// * `command_receiver`: channel to receive commands from controller
     /// * `channels`: channels to communicate with other workers
     /// * `shared_state`: shared state with the controller
+    /// * `snapshot`: snapshot of the graph published for the controller
     /// * `init_graph`: Optional graph of blocks to initiate the worker
     /// * `storage`: shared storage
+    /// * `clock`: clock used to read the current time and wait for the slots
     ///
     /// # Returns:
     /// A `ConsensusWorker`, to interact with it use the `ConsensusController`
@@ -83,10 +88,12 @@ impl ConsensusWorker {
         config: ConsensusConfig,
         command_receiver: MassaReceiver<ConsensusCommand>,
         shared_state: Arc<RwLock<ConsensusState>>,
+        snapshot: SharedGraphSnapshot,
         init_graph: Option<BootstrapableGraph>,
         storage: Storage,
+        clock: Arc<dyn Clock>,
     ) -> Result<Self, ConsensusError> {
-        let now = MassaTime::now().expect("Couldn't init timer consensus");
+        let now = clock.now().expect("Couldn't init timer consensus");
         let previous_slot = get_latest_block_slot_at_timestamp(
             config.thread_count,
             config.t0,
@@ -126,13 +133,12 @@ impl ConsensusWorker {
         let next_slot = previous_slot.map_or(Ok(Slot::new(0u64, 0u8)), |s| {
             s.get_next_slot(config.thread_count)
         })?;
-        let next_instant = get_block_slot_timestamp(
+        let next_slot_timestamp = get_block_slot_timestamp(
             config.thread_count,
             config.t0,
             config.genesis_timestamp,
             next_slot,
-        )?
-        .estimate_instant()?;
+        )?;
 
         info!(
             "Started node at time {}, cycle {}, period {}, thread {}",
@@ -190,9 +196,11 @@ impl ConsensusWorker {
             config: config.clone(),
             command_receiver,
             shared_state,
+            snapshot,
             previous_slot,
             next_slot,
-            next_instant,
+            next_slot_timestamp,
+            clock,
         };
 
         // If the node starts after the genesis timestamp then it has to initialize its graph
@@ -285,6 +293,7 @@ impl ConsensusWorker {
                 .channels
                 .execution_controller
                 .update_blockclique_status(notify_finals, Some(notify_blockclique), block_storage);
+            res_consensus.publish_snapshot(write_shared_state);
         }
 
         Ok(res_consensus)
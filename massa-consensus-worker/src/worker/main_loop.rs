@@ -1,14 +1,23 @@
-use std::time::Instant;
+use std::{path::Path, sync::Arc, time::Instant};
 
-use massa_consensus_exports::{error::ConsensusError, events::ConsensusEvent};
+use massa_channel::heartbeat::{Heartbeat, MAX_BEAT_INTERVAL};
+use massa_consensus_exports::{
+    error::{ConsensusError, ProtocolErrorKind},
+    events::ConsensusEvent,
+};
 use massa_models::{
     slot::Slot,
     timeslots::{get_block_slot_timestamp, get_closest_slot_to_timestamp},
 };
 use massa_time::MassaTime;
-use tracing::log::{info, warn};
+use parking_lot::RwLockWriteGuard;
+use tracing::{error, info, info_span, warn};
 
-use crate::commands::ConsensusCommand;
+use crate::{
+    blocks_file::{self, SavedBlock},
+    commands::ConsensusCommand,
+    state::{snapshot::GraphSnapshot, ConsensusState},
+};
 
 use super::ConsensusWorker;
 
@@ -18,6 +27,18 @@ enum WaitingStatus {
     Disconnected,
 }
 
+/// Log an error of the worker, loudly if it is a bug of the node
+fn log_error(err: &ConsensusError, action: &str) {
+    match err.kind() {
+        ProtocolErrorKind::Internal => {
+            error!("internal error in consensus while {}: {}", action, err)
+        }
+        ProtocolErrorKind::PeerFault | ProtocolErrorKind::Transient => {
+            warn!("error in consensus while {}: {}", action, err)
+        }
+    }
+}
+
 impl ConsensusWorker {
     /// Execute a command received from the controller also run an update of the graph after processing the command.
     ///
@@ -28,12 +49,20 @@ impl ConsensusWorker {
     /// An error if the command failed
     fn manage_command(&mut self, command: ConsensusCommand) -> Result<(), ConsensusError> {
         let mut write_shared_state = self.shared_state.write();
-        match command {
+        let res = match command {
             ConsensusCommand::RegisterBlockHeader(block_id, header) => {
+                let _span = info_span!(
+                    "register_block_header",
+                    block_id = %block_id,
+                    slot = %header.content.slot
+                )
+                .entered();
                 write_shared_state.register_block_header(block_id, header, self.previous_slot)?;
                 write_shared_state.block_db_changed()
             }
             ConsensusCommand::RegisterBlock(block_id, slot, block_storage, created) => {
+                let _span =
+                    info_span!("register_block", block_id = %block_id, slot = %slot).entered();
                 write_shared_state.register_block(
                     block_id,
                     slot,
@@ -43,41 +72,124 @@ impl ConsensusWorker {
                 )?;
                 write_shared_state.block_db_changed()
             }
-            ConsensusCommand::MarkInvalidBlock(block_id, header) => {
-                write_shared_state.mark_invalid_block(&block_id, header);
+            ConsensusCommand::MarkInvalidBlock(block_id, header, reason) => {
+                let _span = info_span!(
+                    "mark_invalid_block",
+                    block_id = %block_id,
+                    slot = %header.content.slot
+                )
+                .entered();
+                write_shared_state.mark_invalid_block(&block_id, header, reason);
                 Ok(())
             }
+        };
+        self.publish_snapshot(write_shared_state);
+        res
+    }
+
+    /// Publish a snapshot of `state` for the reads of the controller, if the graph changed
+    /// since the last one. Must be called after each change of the state, with its write lock:
+    /// the lock is downgraded to a read lock to build the snapshot, so that the queries reading
+    /// the state do not wait for the build.
+    pub(crate) fn publish_snapshot(&self, state: RwLockWriteGuard<'_, ConsensusState>) {
+        let state = RwLockWriteGuard::downgrade(state);
+        if self.snapshot.read().is_up_to_date(&state) {
+            return;
+        }
+        let snapshot = Arc::new(GraphSnapshot::new(&state));
+        *self.snapshot.write() = snapshot;
+    }
+
+    /// Save the active blocks that are not final yet, with their operations, to `path`
+    /// to register them again after a restart
+    fn save_blocks(&self, path: &Path) {
+        let blocks: Vec<SavedBlock> = {
+            let read_shared_state = self.shared_state.read();
+            let mut blocks: Vec<SavedBlock> = read_shared_state
+                .blocks_state
+                .active_blocks()
+                .iter()
+                .filter_map(|block_id| {
+                    let (a_block, storage) = read_shared_state.get_full_active_block(block_id)?;
+                    if a_block.is_final {
+                        return None;
+                    }
+                    let block = storage.read_blocks().get(block_id)?.clone();
+                    let stored_operations = storage.read_operations();
+                    let operations = block
+                        .content
+                        .operations
+                        .iter()
+                        .map(|op_id| stored_operations.get(op_id).cloned())
+                        .collect::<Option<Vec<_>>>()?;
+                    Some((block, operations))
+                })
+                .collect();
+            // parents first, so that they are registered first at the next start
+            blocks.sort_unstable_by_key(|(block, _)| (block.content.header.content.slot, block.id));
+            blocks
+        };
+        let buffer = match blocks_file::serialize_blocks(&blocks) {
+            Ok(buffer) => buffer,
+            Err(err) => {
+                warn!("could not serialize the blocks that are not final: {}", err);
+                return;
+            }
+        };
+        match blocks_file::write_blocks(path, &buffer) {
+            Ok(()) => info!(
+                "Saved {} blocks that are not final to {}",
+                blocks.len(),
+                path.display()
+            ),
+            Err(err) => warn!(
+                "could not save the blocks that are not final to {}: {}",
+                path.display(),
+                err
+            ),
         }
     }
 
-    /// Wait and interrupt if we receive a command, a stop signal or we reach the `instant`
+    /// Wait and interrupt if we receive a command, a stop signal or we reach the `timestamp`
     ///
     /// # Return:
-    /// WaitingStatus::Interrupted => if a command has been executed
-    /// WaitingStatus::Ended => if we reached the `instant`
+    /// WaitingStatus::Interrupted => if a command has been executed, or the `timestamp` is not reached yet (the deadline estimated by the clock was too early or the wait was cut short to beat)
+    /// WaitingStatus::Ended => if we reached the `timestamp`
     /// WaitingStatus::Disconnected => if we received a stop signal
-    fn wait_slot_or_command(&mut self, deadline: Instant) -> WaitingStatus {
+    fn wait_slot_or_command(&mut self, timestamp: MassaTime) -> WaitingStatus {
+        let deadline = self
+            .clock
+            .estimate_instant(timestamp)
+            .expect("could not estimate block slot instant");
+        // wake up in time to beat even if the slot is far away, e.g. before genesis
+        let deadline = deadline.min(Instant::now() + MAX_BEAT_INTERVAL);
         match self.command_receiver.recv_deadline(deadline) {
             // message received => manage it
             Ok(command) => {
                 if let Err(err) = self.manage_command(command) {
-                    warn!("Error in consensus: {}", err);
+                    log_error(&err, "managing a command");
                 }
                 WaitingStatus::Interrupted
             }
             // timeout => continue main loop
-            Err(crossbeam::channel::RecvTimeoutError::Timeout) => WaitingStatus::Ended,
+            Err(crossbeam::channel::RecvTimeoutError::Timeout) => {
+                if self.clock.now().expect("could not get current time") < timestamp {
+                    WaitingStatus::Interrupted
+                } else {
+                    WaitingStatus::Ended
+                }
+            }
             // channel disconnected (sender dropped) => quit main loop
             Err(crossbeam::channel::RecvTimeoutError::Disconnected) => WaitingStatus::Disconnected,
         }
     }
 
-    /// Gets the next slot and the instant when it will happen.
+    /// Gets the next slot and the timestamp when it will happen.
     /// Slots can be skipped if we waited too much in-between.
     /// Extra safety against double-production caused by clock adjustments (this is the role of the `previous_slot` parameter).
-    fn get_next_slot(&self, previous_slot: Option<Slot>) -> (Slot, Instant) {
+    fn get_next_slot(&self, previous_slot: Option<Slot>) -> (Slot, MassaTime) {
         // get current absolute time
-        let now = MassaTime::now().expect("could not get current time");
+        let now = self.clock.now().expect("could not get current time");
 
         // get closest slot according to the current absolute time
         let mut next_slot = get_closest_slot_to_timestamp(
@@ -97,30 +209,30 @@ impl ConsensusWorker {
         }
 
         // get the timestamp of the target slot
-        let next_instant = get_block_slot_timestamp(
+        let next_timestamp = get_block_slot_timestamp(
             self.config.thread_count,
             self.config.t0,
             self.config.genesis_timestamp,
             next_slot,
         )
-        .expect("could not get block slot timestamp")
-        .estimate_instant()
-        .expect("could not estimate block slot instant");
+        .expect("could not get block slot timestamp");
 
-        (next_slot, next_instant)
+        (next_slot, next_timestamp)
     }
 
     /// Runs in loop forever. This loop must stop every slot to perform operations on stats and graph
     /// but can be stopped anytime by a command received.
     pub fn run(&mut self) {
         let mut last_prune = Instant::now();
+        let heartbeat = Heartbeat::new("consensus worker");
         loop {
-            match self.wait_slot_or_command(self.next_instant) {
+            heartbeat.beat();
+            match self.wait_slot_or_command(self.next_slot_timestamp) {
                 // When we reached the instant of the next slot
                 WaitingStatus::Ended => {
                     if let Some(end) = self.config.end_timestamp {
                         // The testnet has ended. Will be removed for mainnet.
-                        if self.next_instant > end.estimate_instant().unwrap() {
+                        if self.next_slot_timestamp > end {
                             info!("This episode has come to an end, please get the latest testnet node version to continue");
                             let _ = self
                                 .shared_state
@@ -146,20 +258,21 @@ impl ConsensusWorker {
                     {
                         let mut write_shared_state = self.shared_state.write();
                         if let Err(err) = write_shared_state.slot_tick(self.next_slot) {
-                            warn!("Error while processing block tick: {}", err);
+                            log_error(&err, "processing block tick");
                         }
+                        self.publish_snapshot(write_shared_state);
                     };
                     if last_prune.elapsed().as_millis()
                         > self.config.block_db_prune_interval.to_millis() as u128
                     {
-                        self.shared_state
-                            .write()
-                            .prune()
-                            .expect("Error while pruning");
+                        let mut write_shared_state = self.shared_state.write();
+                        write_shared_state.prune().expect("Error while pruning");
+                        self.publish_snapshot(write_shared_state);
                         last_prune = Instant::now();
                     }
                     self.previous_slot = Some(self.next_slot);
-                    (self.next_slot, self.next_instant) = self.get_next_slot(Some(self.next_slot));
+                    (self.next_slot, self.next_slot_timestamp) =
+                        self.get_next_slot(Some(self.next_slot));
                 }
                 WaitingStatus::Disconnected => {
                     break;
@@ -169,5 +282,8 @@ impl ConsensusWorker {
                 }
             };
         }
+        if let Some(path) = &self.config.blocks_file_path {
+            self.save_blocks(path);
+        }
     }
 }
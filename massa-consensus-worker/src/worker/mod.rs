@@ -4,22 +4,28 @@ use massa_consensus_exports::{
     ConsensusController, ConsensusManager,
 };
 use massa_metrics::MassaMetrics;
+use massa_models::block::BlockDeserializerArgs;
 use massa_models::block_id::BlockId;
 use massa_models::clique::Clique;
-use massa_models::config::CHANNEL_SIZE;
+use massa_models::config::{MAX_DENUNCIATIONS_PER_BLOCK_HEADER, MAX_OPERATIONS_PER_BLOCK};
 use massa_models::prehash::PreHashSet;
 use massa_models::slot::Slot;
 use massa_storage::Storage;
-use massa_time::MassaTime;
+use massa_time::{Clock, MassaTime};
 use parking_lot::RwLock;
 use std::sync::Arc;
 use std::thread;
-use std::time::Instant;
+use tracing::info;
 
+use crate::blocks_file;
 use crate::commands::ConsensusCommand;
 use crate::controller::ConsensusControllerImpl;
 use crate::manager::ConsensusManagerImpl;
-use crate::state::{blocks_state::BlocksState, ConsensusState};
+use crate::state::{
+    blocks_state::BlocksState,
+    snapshot::{GraphSnapshot, SharedGraphSnapshot},
+    ConsensusState,
+};
 
 /// The consensus worker structure that contains all information and tools for the consensus worker thread.
 pub struct ConsensusWorker {
@@ -29,12 +35,16 @@ pub struct ConsensusWorker {
     config: ConsensusConfig,
     /// State shared with the controller
     shared_state: Arc<RwLock<ConsensusState>>,
+    /// Snapshot of the graph published for the controller after each change of the state
+    snapshot: SharedGraphSnapshot,
     /// Previous slot.
     previous_slot: Option<Slot>,
     /// Next slot
     next_slot: Slot,
-    /// Next slot instant
-    next_instant: Instant,
+    /// Next slot timestamp
+    next_slot_timestamp: MassaTime,
+    /// Clock used to read the current time and wait for the next slot
+    clock: Arc<dyn Clock>,
 }
 
 mod init;
@@ -47,6 +57,8 @@ mod main_loop;
 /// * `channels`: Channels to communicate with others modules
 /// * `init_graph`: Optional initial graph to bootstrap the graph. if None, the graph will have only genesis blocks.
 /// * `storage`: Storage to use for the consensus
+/// * `massa_metrics`: Metrics of the node
+/// * `clock`: Clock used to read the current time and wait for the slots
 ///
 /// # Returns:
 /// * The consensus controller to communicate with the consensus worker thread
@@ -57,8 +69,9 @@ pub fn start_consensus_worker(
     init_graph: Option<BootstrapableGraph>,
     storage: Storage,
     massa_metrics: MassaMetrics,
+    clock: Arc<dyn Clock>,
 ) -> (Box<dyn ConsensusController>, Box<dyn ConsensusManager>) {
-    let (tx, rx) = MassaChannel::new("consensus_command".to_string(), Some(CHANNEL_SIZE));
+    let (tx, rx) = MassaChannel::new("consensus_command".to_string(), Some(config.channel_size));
     // desync detection timespan
     let bootstrap_part_size = config.bootstrap_part_size;
     let stats_desync_detection_timespan =
@@ -77,6 +90,7 @@ pub fn start_consensus_worker(
         attack_attempts: Default::default(),
         new_final_blocks: Default::default(),
         new_stale_blocks: Default::default(),
+        reverted_operations: storage.clone_without_refs(),
         active_index_without_ops: Default::default(),
         save_final_periods: Default::default(),
         latest_final_blocks_periods: Default::default(),
@@ -87,20 +101,34 @@ pub fn start_consensus_worker(
         stale_block_stats: Default::default(),
         protocol_blocks: Default::default(),
         wishlist: Default::default(),
-        launch_time: MassaTime::now().unwrap(),
+        launch_time: clock.now().unwrap(),
         stats_desync_detection_timespan,
+        registered_blocks: Default::default(),
+        partition_state: Default::default(),
         stats_history_timespan: std::cmp::max(
-            stats_desync_detection_timespan,
-            config.stats_timespan,
+            std::cmp::max(stats_desync_detection_timespan, config.stats_timespan),
+            config.partition_detection_timespan,
         ),
         prev_blockclique: Default::default(),
         nonfinal_active_blocks_per_slot: Default::default(),
-        massa_metrics,
+        massa_metrics: massa_metrics.clone(),
+        clock: clock.clone(),
     }));
 
+    let snapshot: SharedGraphSnapshot = Arc::new(RwLock::new(Arc::new(GraphSnapshot::default())));
+
     let shared_state_cloned = shared_state.clone();
-    let mut consensus_worker =
-        ConsensusWorker::new(config.clone(), rx, shared_state_cloned, init_graph, storage).unwrap();
+    let blocks_storage = storage.clone_without_refs();
+    let mut consensus_worker = ConsensusWorker::new(
+        config.clone(),
+        rx,
+        shared_state_cloned,
+        snapshot.clone(),
+        init_graph,
+        storage,
+        clock,
+    )
+    .unwrap();
 
     let consensus_thread = thread::Builder::new()
         .name("consensus worker".into())
@@ -115,9 +143,42 @@ pub fn start_consensus_worker(
         tx,
         channels,
         shared_state,
+        snapshot,
         bootstrap_part_size,
         config.broadcast_enabled,
+        config.channel_overflow_policy,
+        config.max_send_wait.to_duration(),
+        massa_metrics,
     );
 
+    if let Some(path) = &config.blocks_file_path {
+        // register again the blocks saved at the last stop, they go through the usual checks
+        let saved_blocks = blocks_file::load_blocks(
+            path,
+            BlockDeserializerArgs {
+                thread_count: config.thread_count,
+                max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
+                endorsement_count: config.endorsement_count,
+                max_denunciations_per_block_header: MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
+                last_start_period: Some(config.last_start_period),
+            },
+        );
+        if !saved_blocks.is_empty() {
+            info!(
+                "Registering again {} blocks saved in {}",
+                saved_blocks.len(),
+                path.display()
+            );
+        }
+        for (block, operations) in saved_blocks {
+            let (block_id, slot) = (block.id, block.content.header.content.slot);
+            let mut block_storage = blocks_storage.clone_without_refs();
+            block_storage.store_endorsements(block.content.header.content.endorsements.clone());
+            block_storage.store_operations(operations);
+            block_storage.store_block(block);
+            controller.register_block(block_id, slot, block_storage, false);
+        }
+    }
+
     (Box::new(controller), Box::new(manager))
 }
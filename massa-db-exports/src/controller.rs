@@ -15,10 +15,21 @@ pub trait MassaDBController: Send + Sync + Debug {
     fn get_change_id(&self) -> Result<Slot, ModelsError>;
 
     /// Set the initial change_id. This function should only be called at startup/reset, as it does not batch this set with other changes.
-    fn set_initial_change_id(&self, change_id: Slot);
+    fn set_initial_change_id(&self, change_id: Slot) -> Result<(), MassaDBError>;
 
-    /// Writes the batch to the DB
-    fn write_batch(&mut self, batch: DBBatch, versioning_batch: DBBatch, change_id: Option<Slot>);
+    /// Writes the batch to the DB.
+    /// If the write fails, the error is kept and the following writes are refused,
+    /// so that the DB is never left with a hole in its changes. The caller must then stop
+    /// applying changes.
+    fn write_batch(
+        &mut self,
+        batch: DBBatch,
+        versioning_batch: DBBatch,
+        change_id: Option<Slot>,
+    ) -> Result<(), MassaDBError>;
+
+    /// Error of the write after which the DB refuses any write, if a write failed
+    fn get_write_error(&self) -> Option<String>;
 
     /// Utility function to put / update a key & value in the batch
     fn put_or_update_entry_value(&self, batch: &mut DBBatch, key: Vec<u8>, value: &[u8]);
@@ -27,10 +38,15 @@ pub trait MassaDBController: Send + Sync + Debug {
     fn delete_key(&self, batch: &mut DBBatch, key: Vec<u8>);
 
     /// Utility function to delete all keys in a prefix
-    fn delete_prefix(&mut self, prefix: &str, handle_str: &str, change_id: Option<Slot>);
+    fn delete_prefix(
+        &mut self,
+        prefix: &str,
+        handle_str: &str,
+        change_id: Option<Slot>,
+    ) -> Result<(), MassaDBError>;
 
     /// Reset the database, and attach it to the given slot.
-    fn reset(&mut self, slot: Slot);
+    fn reset(&mut self, slot: Slot) -> Result<(), MassaDBError>;
 
     /// Exposes RocksDB's "get_cf" function
     fn get_cf(&self, handle_cf: &str, key: Key) -> Result<Option<Value>, MassaDBError>;
@@ -55,6 +71,10 @@ pub trait MassaDBController: Send + Sync + Debug {
     /// Get the current extended state hash of the database
     fn get_xof_db_hash(&self) -> HashXof<HASH_XOF_SIZE_BYTES>;
 
+    /// Get the extended state hash of the database right after the changes of `change_id`,
+    /// if it is the current change_id or one of the last ones written
+    fn get_xof_db_hash_at(&self, change_id: Slot) -> Option<HashXof<HASH_XOF_SIZE_BYTES>>;
+
     /// Flushes the underlying db.
     fn flush(&self) -> Result<(), MassaDBError>;
 
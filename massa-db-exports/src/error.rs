@@ -13,6 +13,8 @@ pub enum MassaDBError {
     InvalidChangeID(String),
     /// time error: {0}
     TimeError(String),
+    /// change id too old: {0}
+    ChangeIdTooOld(String),
     /// rocks db error: {0}
     RocksDBError(String),
     /// hash error: {0}
@@ -1,10 +1,11 @@
 use massa_db_exports::{
     DBBatch, Key, MassaDBConfig, MassaDBController, MassaDBError, MassaDirection,
     MassaIteratorMode, StreamBatch, Value, CF_ERROR, CHANGE_ID_DESER_ERROR, CHANGE_ID_KEY,
-    CHANGE_ID_SER_ERROR, CRUD_ERROR, METADATA_CF, OPEN_ERROR, STATE_CF, STATE_HASH_ERROR,
+    CHANGE_ID_SER_ERROR, METADATA_CF, OPEN_ERROR, STATE_CF, STATE_HASH_ERROR,
     STATE_HASH_INITIAL_BYTES, STATE_HASH_KEY, VERSIONING_CF,
 };
 use massa_hash::{HashXof, HASH_XOF_SIZE_BYTES};
+use massa_logging::massa_journal;
 use massa_models::{
     error::ModelsError,
     slot::{Slot, SlotDeserializer, SlotSerializer},
@@ -22,6 +23,7 @@ use std::{
     ops::Bound::{self, Excluded, Included, Unbounded},
     sync::Arc,
 };
+use tracing::{error, warn};
 
 /// Wrapped RocksDB database
 ///
@@ -53,6 +55,15 @@ pub struct RawMassaDB<
     pub change_id_deserializer: ChangeIDDeserializer,
     /// The current RocksDB batch of the database, in a Mutex to share it with lsmtree
     pub current_batch: Arc<Mutex<WriteBatch>>,
+    /// Error of the first batch that could not be written (disk full, corruption...).
+    /// The database refuses any write after it, so that it stays at the last written change_id.
+    pub write_error: Option<String>,
+    /// The current XOF state hash, as written in the metadata of the database.
+    /// Kept in memory so that reading it can't fail.
+    pub xof_hash: HashXof<HASH_XOF_SIZE_BYTES>,
+    /// XOF state hashes after the last batches written with a change_id, to compare the state
+    /// with other nodes at a given change_id. Bounded like change_history.
+    pub xof_hash_history: BTreeMap<ChangeID, HashXof<HASH_XOF_SIZE_BYTES>>,
 }
 
 impl<ChangeID, ChangeIDSerializer, ChangeIDDeserializer> std::fmt::Debug
@@ -67,6 +78,9 @@ where
             .field("db", &self.db)
             .field("config", &self.config)
             .field("change_history", &self.change_history)
+            .field("write_error", &self.write_error)
+            .field("xof_hash", &self.xof_hash)
+            .field("xof_hash_history", &self.xof_hash_history)
             .finish()
     }
 }
@@ -116,7 +130,7 @@ where
                             .lower_bound(Bound::Excluded(&last_change_id));
 
                         if cursor.peek_prev().is_none() {
-                            return Err(MassaDBError::TimeError(String::from(
+                            return Err(MassaDBError::ChangeIdTooOld(String::from(
                                 "all our changes are strictly after last_change_id, we can't be sure we did not miss any",
                             )));
                         }
@@ -226,7 +240,7 @@ where
                             .lower_bound(Bound::Excluded(&last_change_id));
 
                         if cursor.peek_prev().is_none() {
-                            return Err(MassaDBError::TimeError(String::from(
+                            return Err(MassaDBError::ChangeIdTooOld(String::from(
                                 "all our changes are strictly after last_change_id, we can't be sure we did not miss any",
                             )));
                         }
@@ -379,6 +393,7 @@ where
                 MassaDBError::RocksDBError(format!("Can't write batch to disk: {}", e))
             })?;
         }
+        self.xof_hash = current_xor_hash;
 
         self.change_history
             .entry(self.get_change_id().expect(CHANGE_ID_DESER_ERROR))
@@ -418,7 +433,7 @@ where
     }
 
     /// Set the initial change_id. This function should only be called at startup/reset, as it does not batch this set with other changes.
-    pub fn set_initial_change_id(&self, change_id: ChangeID) {
+    pub fn set_initial_change_id(&self, change_id: ChangeID) -> Result<(), MassaDBError> {
         self.current_batch.lock().clear();
 
         self.set_change_id_to_batch(change_id);
@@ -429,7 +444,9 @@ where
             batch = WriteBatch::from_data(current_batch_guard.data());
             current_batch_guard.clear();
 
-            self.db.write(batch).expect(CRUD_ERROR);
+            self.db.write(batch).map_err(|e| {
+                MassaDBError::RocksDBError(format!("Can't write batch to disk: {}", e))
+            })
         }
     }
 
@@ -528,25 +545,27 @@ where
                 MassaDBError::RocksDBError(format!("Can't write batch to disk: {}", e))
             })?;
         }
+        self.xof_hash = current_xor_hash;
 
         Ok(())
     }
 
     /// Get the current XOF state hash of the database
     pub fn get_xof_db_hash(&self) -> HashXof<HASH_XOF_SIZE_BYTES> {
-        self.get_xof_db_hash_opt()
-            .unwrap_or(HashXof(*STATE_HASH_INITIAL_BYTES))
+        self.xof_hash
     }
 
-    /// Get the current XOF state hash of the database
-    fn get_xof_db_hash_opt(&self) -> Option<HashXof<HASH_XOF_SIZE_BYTES>> {
-        let db = &self.db;
+    /// Read the XOF state hash from the metadata of the database
+    fn read_xof_db_hash(db: &DB) -> Result<HashXof<HASH_XOF_SIZE_BYTES>, MassaDBError> {
         let handle = db.cf_handle(METADATA_CF).expect(CF_ERROR);
 
-        db.get_cf(handle, STATE_HASH_KEY)
-            .expect(CRUD_ERROR)
+        let state_hash_bytes = db
+            .get_cf(handle, STATE_HASH_KEY)
+            .map_err(|e| MassaDBError::RocksDBError(format!("Can't read the state hash: {}", e)))?;
+        Ok(state_hash_bytes
             .as_deref()
             .map(|state_hash_bytes| HashXof(state_hash_bytes.try_into().expect(STATE_HASH_ERROR)))
+            .unwrap_or(HashXof(*STATE_HASH_INITIAL_BYTES)))
     }
 }
 
@@ -568,6 +587,7 @@ impl RawMassaDB<Slot, SlotSerializer, SlotDeserializer> {
         )
         .expect(OPEN_ERROR);
 
+        let xof_hash = Self::read_xof_db_hash(&db).expect(OPEN_ERROR);
         let db = Arc::new(db);
         let current_batch = Arc::new(Mutex::new(WriteBatch::default()));
 
@@ -584,13 +604,18 @@ impl RawMassaDB<Slot, SlotSerializer, SlotDeserializer> {
             change_id_serializer: SlotSerializer::new(),
             change_id_deserializer,
             current_batch,
+            write_error: None,
+            xof_hash,
+            xof_hash_history: BTreeMap::new(),
         };
 
         if massa_db.get_change_id().is_err() {
-            massa_db.set_initial_change_id(Slot {
-                period: 0,
-                thread: 0,
-            });
+            massa_db
+                .set_initial_change_id(Slot {
+                    period: 0,
+                    thread: 0,
+                })
+                .expect(OPEN_ERROR);
         }
 
         massa_db
@@ -604,16 +629,52 @@ impl MassaDBController for RawMassaDB<Slot, SlotSerializer, SlotDeserializer> {
 
         let subpath = format!("backup_{}_{}", slot.period, slot.thread);
 
-        Checkpoint::new(db)
-            .expect("Cannot init checkpoint")
-            .create_checkpoint(db.path().join(subpath))
-            .expect("Failed to create checkpoint");
+        if let Err(err) = Checkpoint::new(db)
+            .and_then(|checkpoint| checkpoint.create_checkpoint(db.path().join(subpath)))
+        {
+            error!("could not backup the database at slot {}: {}", slot, err);
+        }
     }
 
-    /// Writes the batch to the DB
-    fn write_batch(&mut self, batch: DBBatch, versioning_batch: DBBatch, change_id: Option<Slot>) {
-        self.write_changes(batch, versioning_batch, change_id, false)
-            .expect(CRUD_ERROR);
+    /// Writes the batch to the DB.
+    /// If the write fails, the error is kept and the following writes are refused.
+    fn write_batch(
+        &mut self,
+        batch: DBBatch,
+        versioning_batch: DBBatch,
+        change_id: Option<Slot>,
+    ) -> Result<(), MassaDBError> {
+        if let Some(write_error) = &self.write_error {
+            warn!(
+                "the database refuses writes after a write error, dropping the changes of slot {:?}",
+                change_id
+            );
+            return Err(MassaDBError::RocksDBError(format!(
+                "writes refused after a previous write error: {}",
+                write_error
+            )));
+        }
+        if let Err(err) = self.write_changes(batch, versioning_batch, change_id, false) {
+            error!(
+                "could not write the changes of slot {:?} to the database, refusing any further write: {}",
+                change_id, err
+            );
+            massa_journal!("storage_error", { "slot": change_id.map(|slot| slot.to_string()), "error": err.to_string() });
+            self.write_error = Some(err.to_string());
+            return Err(err);
+        }
+        if let Some(change_id) = change_id {
+            self.xof_hash_history.insert(change_id, self.xof_hash);
+            while self.xof_hash_history.len() > self.config.max_history_length {
+                self.xof_hash_history.pop_first();
+            }
+        }
+        Ok(())
+    }
+
+    /// Error of the write after which the database refuses any write
+    fn get_write_error(&self) -> Option<String> {
+        self.write_error.clone()
     }
 
     /// Utility function to put / update a key & value in the batch
@@ -627,7 +688,12 @@ impl MassaDBController for RawMassaDB<Slot, SlotSerializer, SlotDeserializer> {
     }
 
     /// Utility function to delete all keys in a prefix
-    fn delete_prefix(&mut self, prefix: &str, handle_str: &str, change_id: Option<Slot>) {
+    fn delete_prefix(
+        &mut self,
+        prefix: &str,
+        handle_str: &str,
+        change_id: Option<Slot>,
+    ) -> Result<(), MassaDBError> {
         let db = &self.db;
 
         let handle = db.cf_handle(handle_str).expect(CF_ERROR);
@@ -641,20 +707,18 @@ impl MassaDBController for RawMassaDB<Slot, SlotSerializer, SlotDeserializer> {
         }
 
         match handle_str {
-            STATE_CF => {
-                self.write_batch(batch, DBBatch::new(), change_id);
-            }
-            VERSIONING_CF => {
-                self.write_batch(DBBatch::new(), batch, change_id);
-            }
-            _ => {}
+            STATE_CF => self.write_batch(batch, DBBatch::new(), change_id),
+            VERSIONING_CF => self.write_batch(DBBatch::new(), batch, change_id),
+            _ => Ok(()),
         }
     }
 
     /// Reset the database, and attach it to the given slot.
-    fn reset(&mut self, slot: Slot) {
-        self.set_initial_change_id(slot);
+    fn reset(&mut self, slot: Slot) -> Result<(), MassaDBError> {
+        self.set_initial_change_id(slot)?;
         self.change_history.clear();
+        self.xof_hash_history.clear();
+        Ok(())
     }
 
     fn get_cf(&self, handle_cf: &str, key: Key) -> Result<Option<Value>, MassaDBError> {
@@ -728,13 +792,22 @@ impl MassaDBController for RawMassaDB<Slot, SlotSerializer, SlotDeserializer> {
         self.get_xof_db_hash()
     }
 
+    /// Get the extended state hash of the database right after the changes of `change_id`,
+    /// if it is the current change_id or one of the last ones written
+    fn get_xof_db_hash_at(&self, change_id: Slot) -> Option<HashXof<HASH_XOF_SIZE_BYTES>> {
+        if self.get_change_id().ok() == Some(change_id) {
+            return Some(self.xof_hash);
+        }
+        self.xof_hash_history.get(&change_id).copied()
+    }
+
     /// Get the current change_id attached to the database.
     fn get_change_id(&self) -> Result<Slot, ModelsError> {
         self.get_change_id()
     }
 
     /// Set the initial change_id. This function should only be called at startup/reset, as it does not batch this set with other changes.
-    fn set_initial_change_id(&self, change_id: Slot) {
+    fn set_initial_change_id(&self, change_id: Slot) -> Result<(), MassaDBError> {
         self.set_initial_change_id(change_id)
     }
 
@@ -781,3 +854,70 @@ impl MassaDBController for RawMassaDB<Slot, SlotSerializer, SlotDeserializer> {
         self.recompute_db_hash()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_batch_refused_after_error() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut db = MassaDB::new(MassaDBConfig {
+            path: temp_dir.path().to_path_buf(),
+            max_history_length: 10,
+            max_new_elements: 100,
+            thread_count: 2,
+        });
+        let mut batch = DBBatch::new();
+        db.put_or_update_entry_value(&mut batch, b"key".to_vec(), b"value");
+        db.write_batch(batch.clone(), DBBatch::new(), Some(Slot::new(1, 0)))
+            .unwrap();
+        let hash = db.get_xof_db_hash();
+        assert_ne!(hash, HashXof(*STATE_HASH_INITIAL_BYTES));
+        assert_eq!(hash, MassaDB::read_xof_db_hash(&db.db).unwrap());
+
+        // a failed write is reported to the caller
+        assert!(db
+            .write_batch(batch.clone(), DBBatch::new(), Some(Slot::new(0, 1)))
+            .is_err());
+        assert!(db.get_write_error().is_some());
+
+        // and the following writes are refused: the database stays at the last written slot
+        let mut batch = DBBatch::new();
+        db.put_or_update_entry_value(&mut batch, b"key".to_vec(), b"other value");
+        assert!(db
+            .write_batch(batch, DBBatch::new(), Some(Slot::new(2, 0)))
+            .is_err());
+        assert_eq!(db.get_change_id().unwrap(), Slot::new(1, 0));
+        assert_eq!(db.get_xof_db_hash(), hash);
+    }
+
+    #[test]
+    fn test_xof_db_hash_history() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut db = MassaDB::new(MassaDBConfig {
+            path: temp_dir.path().to_path_buf(),
+            max_history_length: 2,
+            max_new_elements: 100,
+            thread_count: 2,
+        });
+        let mut hashes = Vec::new();
+        for period in 1..=3 {
+            let mut batch = DBBatch::new();
+            db.put_or_update_entry_value(&mut batch, b"key".to_vec(), &[period as u8]);
+            db.write_batch(batch, DBBatch::new(), Some(Slot::new(period, 0)))
+                .unwrap();
+            hashes.push(db.get_xof_db_hash());
+        }
+
+        // only the last hashes are kept
+        assert_eq!(db.get_xof_db_hash_at(Slot::new(1, 0)), None);
+        assert_eq!(db.get_xof_db_hash_at(Slot::new(2, 0)), Some(hashes[1]));
+        assert_eq!(db.get_xof_db_hash_at(Slot::new(3, 0)), Some(hashes[2]));
+        assert_eq!(db.get_xof_db_hash_at(Slot::new(4, 0)), None);
+
+        db.reset(Slot::new(0, 1)).unwrap();
+        assert_eq!(db.get_xof_db_hash_at(Slot::new(3, 0)), None);
+    }
+}
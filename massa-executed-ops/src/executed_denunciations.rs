@@ -5,8 +5,9 @@
 
 use crate::{ExecutedDenunciationsChanges, ExecutedDenunciationsConfig};
 use massa_db_exports::{
-    DBBatch, ShareableMassaDBController, CRUD_ERROR, EXECUTED_DENUNCIATIONS_INDEX_DESER_ERROR,
-    EXECUTED_DENUNCIATIONS_INDEX_SER_ERROR, EXECUTED_DENUNCIATIONS_PREFIX, STATE_CF,
+    DBBatch, MassaDBError, ShareableMassaDBController, CRUD_ERROR,
+    EXECUTED_DENUNCIATIONS_INDEX_DESER_ERROR, EXECUTED_DENUNCIATIONS_INDEX_SER_ERROR,
+    EXECUTED_DENUNCIATIONS_PREFIX, STATE_CF,
 };
 use massa_models::denunciation::Denunciation;
 use massa_models::{
@@ -98,13 +99,14 @@ impl ExecutedDenunciations {
     /// Reset the executed denunciations
     ///
     /// USED FOR BOOTSTRAP ONLY
-    pub fn reset(&mut self) {
+    pub fn reset(&mut self) -> Result<(), MassaDBError> {
         {
             let mut db = self.db.write();
-            db.delete_prefix(EXECUTED_DENUNCIATIONS_PREFIX, STATE_CF, None);
+            db.delete_prefix(EXECUTED_DENUNCIATIONS_PREFIX, STATE_CF, None)?;
         }
 
         self.recompute_sorted_denunciations();
+        Ok(())
     }
 
     /// Check if a denunciation (e.g. a denunciation index) was executed
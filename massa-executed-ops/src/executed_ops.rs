@@ -5,7 +5,7 @@
 
 use crate::{ops_changes::ExecutedOpsChanges, ExecutedOpsConfig};
 use massa_db_exports::{
-    DBBatch, ShareableMassaDBController, CRUD_ERROR, EXECUTED_OPS_ID_DESER_ERROR,
+    DBBatch, MassaDBError, ShareableMassaDBController, CRUD_ERROR, EXECUTED_OPS_ID_DESER_ERROR,
     EXECUTED_OPS_ID_SER_ERROR, EXECUTED_OPS_PREFIX, STATE_CF,
 };
 use massa_models::{
@@ -131,12 +131,13 @@ impl ExecutedOps {
     /// Reset the executed operations
     ///
     /// USED FOR BOOTSTRAP ONLY
-    pub fn reset(&mut self) {
+    pub fn reset(&mut self) -> Result<(), MassaDBError> {
         self.db
             .write()
-            .delete_prefix(EXECUTED_OPS_PREFIX, STATE_CF, None);
+            .delete_prefix(EXECUTED_OPS_PREFIX, STATE_CF, None)?;
 
         self.recompute_sorted_ops_and_op_exec_status();
+        Ok(())
     }
 
     /// Apply speculative operations changes to the final executed operations state
@@ -335,15 +336,21 @@ fn test_executed_ops_hash_computing() {
 
     let mut batch_a = DBBatch::new();
     a.apply_changes_to_batch(change_a, apply_slot, &mut batch_a);
-    db_a.write().write_batch(batch_a, Default::default(), None);
+    db_a.write()
+        .write_batch(batch_a, Default::default(), None)
+        .unwrap();
 
     let mut batch_b = DBBatch::new();
     a.apply_changes_to_batch(change_b, apply_slot, &mut batch_b);
-    db_a.write().write_batch(batch_b, Default::default(), None);
+    db_a.write()
+        .write_batch(batch_b, Default::default(), None)
+        .unwrap();
 
     let mut batch_c = DBBatch::new();
     c.apply_changes_to_batch(change_c, apply_slot, &mut batch_c);
-    db_c.write().write_batch(batch_c, Default::default(), None);
+    db_c.write()
+        .write_batch(batch_c, Default::default(), None)
+        .unwrap();
 
     // check that a.hash ^ $(change_b) = c.hash
     assert_ne!(
@@ -363,7 +370,9 @@ fn test_executed_ops_hash_computing() {
     };
     let mut batch_a = DBBatch::new();
     a.prune_to_batch(prune_slot, &mut batch_a);
-    db_a.write().write_batch(batch_a, Default::default(), None);
+    db_a.write()
+        .write_batch(batch_a, Default::default(), None)
+        .unwrap();
 
     // at this point the hash should have been reset to its original value
     assert_eq!(
@@ -73,6 +73,9 @@ pub enum ExecutionError {
 
     /// Factory error: {0}
     FactoryError(#[from] FactoryError),
+
+    /// Final state error: {0}
+    FinalStateError(String),
 }
 
 /// Execution query errors
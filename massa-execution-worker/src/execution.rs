@@ -208,7 +208,13 @@ impl ExecutionState {
     ///
     /// # Arguments
     /// * `exec_out`: execution output to apply
-    pub fn apply_final_execution_output(&mut self, mut exec_out: ExecutionOutput) {
+    ///
+    /// # Returns
+    /// An error if the final state could not be written: the final cursor is then left unchanged
+    pub fn apply_final_execution_output(
+        &mut self,
+        mut exec_out: ExecutionOutput,
+    ) -> Result<(), ExecutionError> {
         if self.final_cursor >= exec_out.slot {
             panic!("attempting to apply a final execution output at or before the current final_cursor");
         }
@@ -233,7 +239,8 @@ impl ExecutionState {
         // apply state changes to the final ledger
         self.final_state
             .write()
-            .finalize(exec_out.slot, exec_out.state_changes);
+            .finalize(exec_out.slot, exec_out.state_changes)
+            .map_err(|err| ExecutionError::FinalStateError(err.to_string()))?;
 
         // update the final ledger's slot
         self.final_cursor = exec_out.slot;
@@ -274,6 +281,7 @@ impl ExecutionState {
                 );
             }
         }
+        Ok(())
     }
 
     /// Applies an execution output to the active (non-final) state
@@ -1313,7 +1321,7 @@ impl ExecutionState {
         slot: &Slot,
         exec_target: Option<&(BlockId, Storage)>,
         selector: Box<dyn SelectorController>,
-    ) {
+    ) -> Result<(), ExecutionError> {
         let target_id = exec_target.as_ref().map(|(b_id, _)| *b_id);
         debug!(
             "execute_final_slot: executing slot={} target={:?}",
@@ -1325,7 +1333,7 @@ impl ExecutionState {
                 "execute_final_slot: final slot already executed (final_cursor = {})",
                 self.final_cursor
             );
-            return;
+            return Ok(());
         }
 
         // check if the final slot execution result is already cached at the front of the speculative execution history
@@ -1336,8 +1344,7 @@ impl ExecutionState {
             {
                 // speculative execution front result matches what we want to compute
                 // apply the cached output and return
-                self.apply_final_execution_output(exec_out);
-                return;
+                return self.apply_final_execution_output(exec_out);
             } else {
                 // speculative cache mismatch
                 warn!(
@@ -1362,11 +1369,12 @@ impl ExecutionState {
         let exec_out = self.execute_slot(slot, exec_target, selector);
 
         // apply execution output to final state
-        self.apply_final_execution_output(exec_out);
+        self.apply_final_execution_output(exec_out)?;
 
         debug!(
             "execute_final_slot: execution finished & result applied & versioning stats updated"
         );
+        Ok(())
     }
 
     /// Runs a read-only execution request.
@@ -8,7 +8,7 @@ use massa_execution_exports::ExecutionConfig;
 use massa_models::{
     block_id::BlockId,
     prehash::PreHashMap,
-    slot::Slot,
+    slot::{Slot, SlotRange},
     timeslots::{get_block_slot_timestamp, get_latest_block_slot_at_timestamp},
 };
 use massa_storage::Storage;
@@ -107,7 +107,7 @@ impl SlotSequencer {
         // Build the slot sequence
 
         // Get the starting slot of the sequence: the earliest CSS-final slot
-        let mut slot = *initial_css_final_blocks
+        let start_slot = *initial_css_final_blocks
             .keys()
             .min()
             .expect("init call should be done with non-empty new_css_final_blocks");
@@ -132,7 +132,7 @@ impl SlotSequencer {
         );
 
         // Iterate from the starting slot to the `max_slot` to build the slot sequence.
-        while slot <= max_slot {
+        for slot in SlotRange::new_inclusive(start_slot, max_slot, self.config.thread_count) {
             // If the slot is rearlier than (or equal to) the latest CSS-final slot in that thread => mark the slot as CSS-final
             let css_final = slot <= self.latest_css_final_slots[slot.thread as usize];
 
@@ -164,11 +164,6 @@ impl SlotSequencer {
                 sce_final,
                 content,
             });
-
-            // Increment slot for the next loop iteration
-            slot = slot
-                .get_next_slot(self.config.thread_count)
-                .expect("overflow in slot iteration");
         }
         // Explicitly consume tainted containers to prevent mistakes caused by using them later.
         if initial_css_final_blocks.into_iter().next().is_some() {
@@ -241,7 +236,7 @@ impl SlotSequencer {
         // and gathering new ones from `new_css_final_blocks` and `new_blockclique`.
 
         // Get earliest useful slot to start the new sequence from (eg. the earliest slot of the previous sequence)
-        let mut slot = self
+        let start_slot = self
             .sequence
             .front()
             .expect("slot sequence should not be empty")
@@ -271,11 +266,8 @@ impl SlotSequencer {
         );
 
         // Preallocate the new sequence of slots
-        let new_seq_len = max_slot
-            .slots_since(&slot, self.config.thread_count)
-            .expect("error computing new sequence length")
-            .saturating_add(1);
-        let mut new_sequence: VecDeque<SlotInfo> = VecDeque::with_capacity(new_seq_len as usize);
+        let slots = SlotRange::new_inclusive(start_slot, max_slot, self.config.thread_count);
+        let mut new_sequence: VecDeque<SlotInfo> = VecDeque::with_capacity(slots.len());
 
         // Mark that we are currently iterating over slots that are SCE-final.
         // The very first slot of the sequence must be SCE-final,
@@ -284,7 +276,7 @@ impl SlotSequencer {
         let mut in_sce_finality = true;
 
         // Loop over the slots to build the new sequence
-        while slot <= max_slot {
+        for slot in slots {
             // The slot is now CSS-final if it is before or at the latest CSS-final slot in its own thread
             let new_css_final = slot <= self.latest_css_final_slots[slot.thread as usize];
 
@@ -337,11 +329,6 @@ impl SlotSequencer {
                     .get_prev_slot(self.config.thread_count)
                     .expect("could not rollback speculative execution cursor");
             }
-
-            // Increment slot for the next loop iteration.
-            slot = slot
-                .get_next_slot(self.config.thread_count)
-                .expect("overflow on slot iteration");
         }
         // Explicitly consume tainted containers to prevent mistakes caused by using them later.
         if !self.sequence.is_empty() {
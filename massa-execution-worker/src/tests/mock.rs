@@ -149,7 +149,8 @@ pub fn get_sample_state(
     final_state
         .db
         .write()
-        .write_batch(batch, Default::default(), None);
+        .write_batch(batch, Default::default(), None)
+        .unwrap();
     final_state.compute_initial_draws().unwrap();
     Ok((Arc::new(RwLock::new(final_state)), tempfile, tempdir))
 }
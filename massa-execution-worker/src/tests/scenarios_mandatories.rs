@@ -1727,7 +1727,8 @@ mod tests {
             .write()
             .db
             .write()
-            .write_batch(batch, Default::default(), None);
+            .write_batch(batch, Default::default(), None)
+            .unwrap();
 
         // create operation 1
         let operation1 = Operation::new_verifiable(
@@ -25,7 +25,7 @@ use massa_wallet::Wallet;
 use parking_lot::{Condvar, Mutex, RwLock};
 use std::sync::Arc;
 use std::thread;
-use tracing::debug;
+use tracing::{debug, error};
 
 /// Structure gathering all elements needed by the execution thread
 pub(crate) struct ExecutionThread {
@@ -200,19 +200,32 @@ impl ExecutionThread {
                             slot,
                             content,
                             self.selector.clone(),
-                        );
+                        )
                     } else {
                         self.execution_state.write().execute_candidate_slot(
                             slot,
                             content,
                             self.selector.clone(),
                         );
+                        Ok(())
                     }
                 },
             );
-            if let Some(_res) = run_result {
-                // A slot was executed: continue.
-                continue;
+            match run_result {
+                Some(Err(err)) => {
+                    // the final state can no longer be written: executing further slots would
+                    // build on changes that were not saved, stop here
+                    error!(
+                        "could not apply a final slot, stopping the execution: {}",
+                        err
+                    );
+                    break;
+                }
+                Some(Ok(())) => {
+                    // A slot was executed: continue.
+                    continue;
+                }
+                None => {}
             }
 
             // low priority: execute a read-only request (note that the queue is of finite length), if there is one ready.
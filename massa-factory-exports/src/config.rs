@@ -2,7 +2,9 @@
 
 //! This file defines the factory settings
 
+use massa_signature::PublicKey;
 use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
 
 /// Structure defining the settings of the factory
 #[derive(Debug, Clone)]
@@ -29,4 +31,33 @@ pub struct FactoryConfig {
     pub denunciation_expire_periods: u64,
     /// choose whether to stop production when zero connections on protocol
     pub stop_production_when_zero_connections: bool,
+    /// choose whether to stop production while consensus suspects a network partition
+    pub stop_production_on_partition: bool,
+    /// external service signing the blocks and endorsements of the keys it holds, if any
+    pub remote_signer: Option<RemoteSignerConfig>,
+}
+
+/// Kind of message submitted to the remote signer, allowing it to restrict what it signs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignedMessageType {
+    /// header of a produced block
+    BlockHeader,
+    /// produced block
+    Block,
+    /// produced endorsement
+    Endorsement,
+    /// handshake with a peer, when the node key is held by the signing service
+    Handshake,
+}
+
+/// Structure defining the settings of the remote signer
+#[derive(Debug, Clone)]
+pub struct RemoteSignerConfig {
+    /// JSON-RPC endpoint of the signing service
+    pub url: String,
+    /// timeout of a signature request
+    pub timeout: MassaTime,
+    /// public keys whose secret keys are held by the signing service
+    pub public_keys: Vec<PublicKey>,
 }
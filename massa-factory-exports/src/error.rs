@@ -10,4 +10,6 @@ pub type FactoryResult<T, E = FactoryError> = core::result::Result<T, E>;
 pub enum FactoryError {
     /// Generic error: {0}
     GenericError(String),
+    /// Remote signer error: {0}
+    RemoteSignerError(String),
 }
@@ -11,7 +11,7 @@ mod controller_traits;
 mod error;
 mod types;
 
-pub use config::FactoryConfig;
+pub use config::{FactoryConfig, RemoteSignerConfig, SignedMessageType};
 pub use controller_traits::FactoryManager;
 pub use error::*;
 pub use types::*;
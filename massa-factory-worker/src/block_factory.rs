@@ -1,7 +1,7 @@
 //! Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use massa_channel::receiver::MassaReceiver;
-use massa_factory_exports::{FactoryChannels, FactoryConfig};
+use massa_factory_exports::{FactoryChannels, FactoryConfig, SignedMessageType};
 use massa_hash::Hash;
 use massa_models::{
     block::{Block, BlockSerializer},
@@ -9,23 +9,26 @@ use massa_models::{
     block_id::BlockId,
     endorsement::SecureShareEndorsement,
     prehash::PreHashSet,
-    secure_share::SecureShareContent,
     slot::Slot,
     timeslots::{get_block_slot_timestamp, get_closest_slot_to_timestamp},
 };
-use massa_time::MassaTime;
+use massa_time::Clock;
 use massa_versioning::versioning::MipStore;
 use massa_wallet::Wallet;
 use parking_lot::RwLock;
 use std::{sync::Arc, thread, time::Instant};
 use tracing::{info, warn};
 
+use crate::signer::FactorySigner;
+
 /// Structure gathering all elements needed by the factory thread
 pub(crate) struct BlockFactoryWorker {
     cfg: FactoryConfig,
-    wallet: Arc<RwLock<Wallet>>,
+    signer: FactorySigner,
     channels: FactoryChannels,
     factory_receiver: MassaReceiver<()>,
+    /// source of time of the slot deadlines
+    clock: Arc<dyn Clock>,
     mip_store: MipStore,
 }
 
@@ -37,16 +40,18 @@ impl BlockFactoryWorker {
         wallet: Arc<RwLock<Wallet>>,
         channels: FactoryChannels,
         factory_receiver: MassaReceiver<()>,
+        clock: Arc<dyn Clock>,
         mip_store: MipStore,
     ) -> thread::JoinHandle<()> {
         thread::Builder::new()
             .name("block-factory".into())
             .spawn(|| {
                 let mut this = Self {
+                    signer: FactorySigner::new(wallet, &cfg.remote_signer),
                     cfg,
-                    wallet,
                     channels,
                     factory_receiver,
+                    clock,
                     mip_store,
                 };
                 this.run();
@@ -59,7 +64,7 @@ impl BlockFactoryWorker {
     /// Extra safety against double-production caused by clock adjustments (this is the role of the `previous_slot` parameter).
     fn get_next_slot(&self, previous_slot: Option<Slot>) -> (Slot, Instant) {
         // get current absolute time
-        let now = MassaTime::now().expect("could not get current time");
+        let now = self.clock.now().expect("could not get current time");
 
         // if it's the first computed slot, add a time shift to prevent double-production on node restart with clock skew
         let base_time = if previous_slot.is_none() {
@@ -97,9 +102,11 @@ impl BlockFactoryWorker {
             self.cfg.genesis_timestamp,
             next_slot,
         )
-        .expect("could not get block slot timestamp")
-        .estimate_instant()
-        .expect("could not estimate block slot instant");
+        .expect("could not get block slot timestamp");
+        let next_instant = self
+            .clock
+            .estimate_instant(next_instant)
+            .expect("could not estimate block slot instant");
 
         (next_slot, next_instant)
     }
@@ -133,17 +140,15 @@ impl BlockFactoryWorker {
             }
         };
 
-        // check if the block producer address is handled by the wallet
-        let block_producer_keypair_ref = self.wallet.read();
-        let block_producer_keypair = if let Some(kp) =
-            block_producer_keypair_ref.find_associated_keypair(&block_producer_addr)
-        {
-            // the selected block producer is managed locally => continue to attempt block production
-            kp
-        } else {
-            // the selected block producer is not managed locally => quit
-            return;
-        };
+        // check if the block producer address is handled by the wallet or the remote signer
+        let block_producer_key =
+            if let Some(key) = self.signer.find_producer_key(&block_producer_addr) {
+                // the selected block producer is managed locally => continue to attempt block production
+                key
+            } else {
+                // the selected block producer is not managed locally => quit
+                return;
+            };
         let mut block_storage = self.channels.storage.clone_without_refs();
         {
             let block_lock = block_storage.read_blocks();
@@ -169,6 +174,16 @@ impl BlockFactoryWorker {
             }
         }
 
+        // do not feed a minority fork
+        if self.cfg.stop_production_on_partition && self.channels.consensus.is_partition_suspected()
+        {
+            warn!(
+                "block factory did not produce block for slot {} because a network partition is suspected",
+                slot
+            );
+            return;
+        }
+
         // get best parents and their periods
         let parents: Vec<(BlockId, u64)> = self.channels.consensus.get_best_parents(); // Vec<(parent_id, parent_period)>
                                                                                        // generate the local storage object
@@ -230,7 +245,9 @@ impl BlockFactoryWorker {
         // create header
         let current_version = self.mip_store.get_network_version_current();
         let announced_version = self.mip_store.get_network_version_to_announce();
-        let header: SecuredHeader = BlockHeader::new_verifiable::<BlockHeaderSerializer, BlockId>(
+        let header: SecuredHeader = match self.signer.sign::<_, _, BlockId>(
+            &block_producer_key,
+            SignedMessageType::BlockHeader,
             BlockHeader {
                 current_version,
                 announced_version,
@@ -241,21 +258,37 @@ impl BlockFactoryWorker {
                 denunciations: self.channels.pool.get_block_denunciations(&slot),
             },
             BlockHeaderSerializer::new(), // TODO reuse self.block_header_serializer
-            block_producer_keypair,
-        )
-        .expect("error while producing block header");
+        ) {
+            Ok(header) => header,
+            Err(err) => {
+                warn!(
+                    "block factory could not sign block header for slot {}: {}",
+                    slot, err
+                );
+                return;
+            }
+        };
         // create block
         let block_ = Block {
             header,
             operations: op_ids.into_iter().collect(),
         };
 
-        let block = Block::new_verifiable(
+        let block = match self.signer.sign(
+            &block_producer_key,
+            SignedMessageType::Block,
             block_,
             BlockSerializer::new(), // TODO reuse self.block_serializer
-            block_producer_keypair,
-        )
-        .expect("error while producing block");
+        ) {
+            Ok(block) => block,
+            Err(err) => {
+                warn!(
+                    "block factory could not sign block for slot {}: {}",
+                    slot, err
+                );
+                return;
+            }
+        };
         let block_id = block.id;
         // store block in storage
         block_storage.store_block(block);
@@ -1,27 +1,29 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use massa_channel::receiver::MassaReceiver;
-use massa_factory_exports::{FactoryChannels, FactoryConfig};
+use massa_factory_exports::{FactoryChannels, FactoryConfig, SignedMessageType};
 use massa_models::{
     block_id::BlockId,
     endorsement::{Endorsement, EndorsementSerializer, SecureShareEndorsement},
-    secure_share::SecureShareContent,
     slot::Slot,
     timeslots::{get_block_slot_timestamp, get_closest_slot_to_timestamp},
 };
-use massa_signature::KeyPair;
-use massa_time::MassaTime;
+use massa_time::{Clock, MassaTime};
 use massa_wallet::Wallet;
 use parking_lot::RwLock;
 use std::{sync::Arc, thread, time::Instant};
 use tracing::{debug, warn};
 
+use crate::signer::{FactorySigner, ProducerKey};
+
 /// Structure gathering all elements needed by the factory thread
 pub(crate) struct EndorsementFactoryWorker {
     cfg: FactoryConfig,
-    wallet: Arc<RwLock<Wallet>>,
+    signer: FactorySigner,
     channels: FactoryChannels,
     factory_receiver: MassaReceiver<()>,
+    /// source of time of the slot deadlines
+    clock: Arc<dyn Clock>,
     half_t0: MassaTime,
     endorsement_serializer: EndorsementSerializer,
 }
@@ -34,6 +36,7 @@ impl EndorsementFactoryWorker {
         wallet: Arc<RwLock<Wallet>>,
         channels: FactoryChannels,
         factory_receiver: MassaReceiver<()>,
+        clock: Arc<dyn Clock>,
     ) -> thread::JoinHandle<()> {
         thread::Builder::new()
             .name("endorsement-factory".into())
@@ -43,10 +46,11 @@ impl EndorsementFactoryWorker {
                         .t0
                         .checked_div_u64(2)
                         .expect("could not compute half_t0"),
+                    signer: FactorySigner::new(wallet, &cfg.remote_signer),
                     cfg,
-                    wallet,
                     channels,
                     factory_receiver,
+                    clock,
                     endorsement_serializer: EndorsementSerializer::new(),
                 };
                 this.run();
@@ -59,7 +63,7 @@ impl EndorsementFactoryWorker {
     /// Extra safety against double-production caused by clock adjustments (this is the role of the `previous_slot` parameter).
     fn get_next_slot(&self, previous_slot: Option<Slot>) -> (Slot, Instant) {
         // get delayed time
-        let now = MassaTime::now().expect("could not get current time");
+        let now = self.clock.now().expect("could not get current time");
 
         // if it's the first computed slot, add a time shift to prevent double-production on node restart with clock skew
         let base_time = if previous_slot.is_none() {
@@ -98,9 +102,11 @@ impl EndorsementFactoryWorker {
             next_slot,
         )
         .expect("could not get block slot timestamp")
-        .saturating_sub(self.half_t0)
-        .estimate_instant()
-        .expect("could not estimate block slot instant");
+        .saturating_sub(self.half_t0);
+        let next_instant = self
+            .clock
+            .estimate_instant(next_instant)
+            .expect("could not estimate block slot instant");
 
         (next_slot, next_instant)
     }
@@ -134,22 +140,18 @@ impl EndorsementFactoryWorker {
             }
         };
 
-        // get creators if they are managed by our wallet
-        let mut producers_indices: Vec<(KeyPair, usize)> = Vec::new();
-        {
-            let wallet = self.wallet.read();
-            for (index, producer_addr) in producer_addrs.into_iter().enumerate() {
-                // check if the block producer address is handled by the wallet
-                let producer_keypair =
-                    if let Some(kp) = wallet.find_associated_keypair(&producer_addr) {
-                        // the selected block producer is managed locally => continue to attempt endorsement production
-                        kp.clone()
-                    } else {
-                        // the selected block producer is not managed locally => continue
-                        continue;
-                    };
-                producers_indices.push((producer_keypair, index));
-            }
+        // get creators if they are managed by our wallet or the remote signer
+        let mut producers_indices: Vec<(ProducerKey, usize)> = Vec::new();
+        for (index, producer_addr) in producer_addrs.into_iter().enumerate() {
+            // check if the block producer address is handled by the wallet or the remote signer
+            let producer_key = if let Some(key) = self.signer.find_producer_key(&producer_addr) {
+                // the selected block producer is managed locally => continue to attempt endorsement production
+                key
+            } else {
+                // the selected block producer is not managed locally => continue
+                continue;
+            };
+            producers_indices.push((producer_key, index));
         }
 
         // quit if there is nothing to produce
@@ -168,6 +170,16 @@ impl EndorsementFactoryWorker {
             }
         }
 
+        // do not feed a minority fork
+        if self.cfg.stop_production_on_partition && self.channels.consensus.is_partition_suspected()
+        {
+            warn!(
+                "endorsement factory did not produce endorsement for slot {} because a network partition is suspected",
+                slot
+            );
+            return;
+        }
+
         // get consensus block ID for that slot
         let endorsed_block: BlockId = self
             .channels
@@ -177,17 +189,26 @@ impl EndorsementFactoryWorker {
         // produce endorsements
         let mut endorsements: Vec<SecureShareEndorsement> =
             Vec::with_capacity(producers_indices.len());
-        for (keypair, index) in producers_indices {
-            let endorsement = Endorsement::new_verifiable(
+        for (key, index) in producers_indices {
+            let endorsement: SecureShareEndorsement = match self.signer.sign(
+                &key,
+                SignedMessageType::Endorsement,
                 Endorsement {
                     slot,
                     index: index as u32,
                     endorsed_block,
                 },
                 self.endorsement_serializer.clone(),
-                &keypair,
-            )
-            .expect("could not create endorsement");
+            ) {
+                Ok(endorsement) => endorsement,
+                Err(err) => {
+                    warn!(
+                        "endorsement factory could not sign endorsement for slot {}: {}",
+                        slot, err
+                    );
+                    continue;
+                }
+            };
 
             // log endorsement creation
             debug!(
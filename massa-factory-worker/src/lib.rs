@@ -6,8 +6,10 @@ mod block_factory;
 mod endorsement_factory;
 mod manager;
 mod run;
+mod signer;
 
 pub use run::start_factory;
+pub use signer::RemoteHandshakeSigner;
 
 #[cfg(test)]
 mod tests;
@@ -10,6 +10,7 @@ use crate::{
     manager::FactoryManagerImpl,
 };
 use massa_factory_exports::{FactoryChannels, FactoryConfig, FactoryManager};
+use massa_time::Clock;
 use massa_wallet::Wallet;
 
 /// Start factory
@@ -18,6 +19,7 @@ use massa_wallet::Wallet;
 /// * `cfg`: factory configuration
 /// * `wallet`: atomic reference to the node wallet
 /// * `channels`: channels to communicate with other modules
+/// * `clock`: source of time of the slot deadlines
 ///
 /// # Return value
 /// Returns a factory manager allowing to stop the workers cleanly.
@@ -26,6 +28,7 @@ pub fn start_factory(
     wallet: Arc<RwLock<Wallet>>,
     channels: FactoryChannels,
     mip_store: MipStore,
+    clock: Arc<dyn Clock>,
 ) -> Box<dyn FactoryManager> {
     // create block factory channel
     let (block_worker_tx, block_worker_rx) =
@@ -41,12 +44,13 @@ pub fn start_factory(
         wallet.clone(),
         channels.clone(),
         block_worker_rx,
+        clock.clone(),
         mip_store,
     );
 
     // start endorsement factory worker
     let endorsement_worker_handle =
-        EndorsementFactoryWorker::spawn(cfg, wallet, channels, endorsement_worker_rx);
+        EndorsementFactoryWorker::spawn(cfg, wallet, channels, endorsement_worker_rx, clock);
 
     // create factory manager
     let manager = FactoryManagerImpl {
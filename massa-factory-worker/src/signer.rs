@@ -0,0 +1,170 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Signature of the produced blocks and endorsements, either with a key of the staking wallet
+//! or by delegating it to a remote signing service holding the secret key.
+//! The handshakes of the node can be delegated to the same service if it holds the node key.
+//!
+//! The remote signer is a JSON-RPC server exposing a `sign` method taking the kind of message,
+//! the public key and the hash to sign, and returning the signature. It is expected to only
+//! sign the message kinds it allows.
+
+use jsonrpsee::core::client::ClientT;
+use jsonrpsee::http_client::{HttpClient, HttpClientBuilder};
+use jsonrpsee::rpc_params;
+use massa_factory_exports::{FactoryError, RemoteSignerConfig, SignedMessageType};
+use massa_hash::Hash;
+use massa_models::address::Address;
+use massa_models::error::ModelsError;
+use massa_models::secure_share::{Id, SecureShare, SecureShareContent};
+use massa_protocol_exports::{HandshakeSigner, ProtocolError};
+use massa_serialization::Serializer;
+use massa_signature::{KeyPair, PublicKey, Signature};
+use massa_wallet::Wallet;
+use parking_lot::RwLock;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+
+/// Key of a producer handled by this node
+#[derive(Clone)]
+pub(crate) enum ProducerKey {
+    /// the keypair is in the staking wallet
+    Local(KeyPair),
+    /// the secret key is held by the remote signer
+    Remote(PublicKey),
+}
+
+/// Client of the remote signing service
+struct RemoteSigner {
+    client: HttpClient,
+    runtime: Runtime,
+    public_keys: Vec<(Address, PublicKey)>,
+}
+
+impl RemoteSigner {
+    fn new(cfg: &RemoteSignerConfig) -> Result<Self, FactoryError> {
+        let client = HttpClientBuilder::default()
+            .request_timeout(cfg.timeout.to_duration())
+            .build(&cfg.url)
+            .map_err(|err| FactoryError::RemoteSignerError(err.to_string()))?;
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|err| FactoryError::RemoteSignerError(err.to_string()))?;
+        Ok(RemoteSigner {
+            client,
+            runtime,
+            public_keys: cfg
+                .public_keys
+                .iter()
+                .map(|public_key| (Address::from_public_key(public_key), *public_key))
+                .collect(),
+        })
+    }
+
+    fn sign(
+        &self,
+        message_type: SignedMessageType,
+        public_key: &PublicKey,
+        hash: &Hash,
+    ) -> Result<Signature, FactoryError> {
+        let signature: Signature = self
+            .runtime
+            .block_on(self.client.request(
+                "sign",
+                rpc_params![message_type, public_key.to_string(), hash.to_bs58_check()],
+            ))
+            .map_err(|err| FactoryError::RemoteSignerError(err.to_string()))?;
+        // never emit a signature that the network would reject
+        public_key
+            .verify_signature(hash, &signature)
+            .map_err(|err| FactoryError::RemoteSignerError(err.to_string()))?;
+        Ok(signature)
+    }
+}
+
+/// Signer of the handshakes delegating to the remote signing service
+pub struct RemoteHandshakeSigner(RemoteSigner);
+
+impl RemoteHandshakeSigner {
+    /// Creates the client of the remote signing service
+    pub fn new(cfg: &RemoteSignerConfig) -> Result<Self, FactoryError> {
+        Ok(RemoteHandshakeSigner(RemoteSigner::new(cfg)?))
+    }
+}
+
+impl HandshakeSigner for RemoteHandshakeSigner {
+    fn holds(&self, public_key: &PublicKey) -> bool {
+        self.0.public_keys.iter().any(|(_, key)| key == public_key)
+    }
+
+    fn sign(&self, public_key: &PublicKey, hash: &Hash) -> Result<Signature, ProtocolError> {
+        self.0
+            .sign(SignedMessageType::Handshake, public_key, hash)
+            .map_err(|err| ProtocolError::GeneralProtocolError(err.to_string()))
+    }
+}
+
+/// Signer of the factory workers
+pub(crate) struct FactorySigner {
+    wallet: Arc<RwLock<Wallet>>,
+    remote: Option<RemoteSigner>,
+}
+
+impl FactorySigner {
+    /// Creates the signer of a factory worker.
+    ///
+    /// # Panics
+    /// if the remote signer client can't be created
+    pub(crate) fn new(wallet: Arc<RwLock<Wallet>>, remote: &Option<RemoteSignerConfig>) -> Self {
+        FactorySigner {
+            wallet,
+            remote: remote.as_ref().map(|cfg| {
+                RemoteSigner::new(cfg).expect("could not create the remote signer client")
+            }),
+        }
+    }
+
+    /// Finds the key of the given producer address if it is handled by this node
+    pub(crate) fn find_producer_key(&self, address: &Address) -> Option<ProducerKey> {
+        if let Some(keypair) = self.wallet.read().find_associated_keypair(address) {
+            return Some(ProducerKey::Local(keypair.clone()));
+        }
+        self.remote.as_ref().and_then(|remote| {
+            remote
+                .public_keys
+                .iter()
+                .find(|(addr, _)| addr == address)
+                .map(|(_, public_key)| ProducerKey::Remote(*public_key))
+        })
+    }
+
+    /// Signs `content` with the given producer key
+    pub(crate) fn sign<T, Ser, ID>(
+        &self,
+        key: &ProducerKey,
+        message_type: SignedMessageType,
+        content: T,
+        content_serializer: Ser,
+    ) -> Result<SecureShare<T, ID>, FactoryError>
+    where
+        T: SecureShareContent,
+        Ser: Serializer<T>,
+        ID: Id,
+    {
+        let result = match key {
+            ProducerKey::Local(keypair) => content.new_verifiable(content_serializer, keypair),
+            ProducerKey::Remote(public_key) => {
+                let remote = self
+                    .remote
+                    .as_ref()
+                    .expect("remote producer key without remote signer");
+                content.new_verifiable_with_signer(content_serializer, *public_key, |hash| {
+                    remote
+                        .sign(message_type, public_key, hash)
+                        .map_err(|err| ModelsError::ErrorRaised(err.to_string()))
+                })
+            }
+        };
+        result.map_err(|err| FactoryError::GenericError(err.to_string()))
+    }
+}
@@ -1,4 +1,5 @@
 mod scenarios;
+mod signer_scenarios;
 mod tools;
 
 pub use tools::*;
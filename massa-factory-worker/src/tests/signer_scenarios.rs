@@ -0,0 +1,177 @@
+use crate::signer::{FactorySigner, ProducerKey, RemoteHandshakeSigner};
+use jsonrpsee::server::{RpcModule, ServerBuilder, ServerHandle};
+use massa_factory_exports::{RemoteSignerConfig, SignedMessageType};
+use massa_hash::Hash;
+use massa_models::{
+    address::Address,
+    block::{Block, BlockSerializer, SecureShareBlock},
+    block_header::{BlockHeader, BlockHeaderSerializer, SecuredHeader},
+    block_id::BlockId,
+    config::THREAD_COUNT,
+    endorsement::{Endorsement, EndorsementSerializer, SecureShareEndorsement},
+    prehash::PreHashMap,
+    slot::Slot,
+};
+use massa_protocol_exports::HandshakeSigner;
+use massa_signature::{KeyPair, Signature};
+use massa_time::MassaTime;
+use massa_wallet::test_exports::create_test_wallet;
+use parking_lot::RwLock;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+
+/// Starts a signing service holding `keypair`, returning its runtime, handle and URL.
+fn start_signing_service(keypair: KeyPair) -> (Runtime, ServerHandle, String) {
+    let runtime = Runtime::new().unwrap();
+    let (handle, url) = runtime.block_on(async {
+        let server = ServerBuilder::default().build("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", server.local_addr().unwrap());
+        let mut module = RpcModule::new(keypair);
+        module
+            .register_method("sign", |params, keypair| {
+                let (_message_type, public_key, hash) =
+                    params.parse::<(SignedMessageType, String, String)>()?;
+                assert_eq!(public_key, keypair.get_public_key().to_string());
+                let hash = Hash::from_bs58_check(&hash).unwrap();
+                Ok::<Signature, jsonrpsee::core::Error>(keypair.sign(&hash).unwrap())
+            })
+            .unwrap();
+        (server.start(module).unwrap(), url)
+    });
+    (runtime, handle, url)
+}
+
+/// Returns a local signer with `keypair` in its wallet and a remote one delegating it to `url`.
+fn local_and_remote_signers(keypair: &KeyPair, url: String) -> (FactorySigner, FactorySigner) {
+    let address = Address::from_public_key(&keypair.get_public_key());
+    let mut accounts = PreHashMap::default();
+    accounts.insert(address, keypair.clone());
+    let local = FactorySigner::new(
+        Arc::new(RwLock::new(create_test_wallet(Some(accounts)))),
+        &None,
+    );
+    let remote = FactorySigner::new(
+        Arc::new(RwLock::new(create_test_wallet(None))),
+        &Some(RemoteSignerConfig {
+            url,
+            timeout: MassaTime::from_millis(5000),
+            public_keys: vec![keypair.get_public_key()],
+        }),
+    );
+    (local, remote)
+}
+
+/// The blocks and endorsements signed by the remote signer are the ones signed locally.
+#[test]
+fn remote_signatures_match_local_ones() {
+    let keypair = KeyPair::generate(0).unwrap();
+    let address = Address::from_public_key(&keypair.get_public_key());
+    let (_runtime, _handle, url) = start_signing_service(keypair.clone());
+    let (local, remote) = local_and_remote_signers(&keypair, url);
+
+    let local_key = local.find_producer_key(&address).unwrap();
+    assert!(matches!(local_key, ProducerKey::Local(_)));
+    let remote_key = remote.find_producer_key(&address).unwrap();
+    assert!(matches!(remote_key, ProducerKey::Remote(_)));
+
+    let endorsement = Endorsement {
+        slot: Slot::new(1, 0),
+        index: 0,
+        endorsed_block: BlockId(Hash::compute_from("blk".as_bytes())),
+    };
+    let local_endorsement: SecureShareEndorsement = local
+        .sign(
+            &local_key,
+            SignedMessageType::Endorsement,
+            endorsement.clone(),
+            EndorsementSerializer::new(),
+        )
+        .unwrap();
+    let remote_endorsement: SecureShareEndorsement = remote
+        .sign(
+            &remote_key,
+            SignedMessageType::Endorsement,
+            endorsement,
+            EndorsementSerializer::new(),
+        )
+        .unwrap();
+    assert_eq!(local_endorsement.id, remote_endorsement.id);
+    assert_eq!(local_endorsement.signature, remote_endorsement.signature);
+
+    let header = BlockHeader {
+        current_version: 0,
+        announced_version: None,
+        slot: Slot::new(1, 0),
+        parents: (0..THREAD_COUNT)
+            .map(|i| BlockId(Hash::compute_from(&[i])))
+            .collect(),
+        operation_merkle_root: Hash::compute_from(&[]),
+        endorsements: vec![local_endorsement],
+        denunciations: vec![],
+    };
+    let local_header: SecuredHeader = local
+        .sign::<_, _, BlockId>(
+            &local_key,
+            SignedMessageType::BlockHeader,
+            header.clone(),
+            BlockHeaderSerializer::new(),
+        )
+        .unwrap();
+    let remote_header: SecuredHeader = remote
+        .sign::<_, _, BlockId>(
+            &remote_key,
+            SignedMessageType::BlockHeader,
+            header,
+            BlockHeaderSerializer::new(),
+        )
+        .unwrap();
+    assert_eq!(local_header.id, remote_header.id);
+    assert_eq!(local_header.signature, remote_header.signature);
+
+    let local_block: SecureShareBlock = local
+        .sign(
+            &local_key,
+            SignedMessageType::Block,
+            Block {
+                header: local_header,
+                operations: vec![],
+            },
+            BlockSerializer::new(),
+        )
+        .unwrap();
+    let remote_block: SecureShareBlock = remote
+        .sign(
+            &remote_key,
+            SignedMessageType::Block,
+            Block {
+                header: remote_header.clone(),
+                operations: vec![],
+            },
+            BlockSerializer::new(),
+        )
+        .unwrap();
+    assert_eq!(local_block.id, remote_block.id);
+    assert_eq!(remote_block.signature, remote_header.signature);
+    assert_eq!(local_block.serialized_data, remote_block.serialized_data);
+}
+
+/// The handshakes are delegated only for the keys held by the remote signer.
+#[test]
+fn remote_handshake_signature() {
+    let keypair = KeyPair::generate(0).unwrap();
+    let (_runtime, _handle, url) = start_signing_service(keypair.clone());
+    let signer = RemoteHandshakeSigner::new(&RemoteSignerConfig {
+        url,
+        timeout: MassaTime::from_millis(5000),
+        public_keys: vec![keypair.get_public_key()],
+    })
+    .unwrap();
+    assert!(signer.holds(&keypair.get_public_key()));
+    assert!(!signer.holds(&KeyPair::generate(0).unwrap().get_public_key()));
+
+    let hash = Hash::compute_from("challenge".as_bytes());
+    let remote_signature = signer.sign(&keypair.get_public_key(), &hash).unwrap();
+    let local_signature =
+        HandshakeSigner::sign(&keypair, &keypair.get_public_key(), &hash).unwrap();
+    assert_eq!(remote_signature, local_signature);
+}
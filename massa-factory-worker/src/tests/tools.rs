@@ -27,7 +27,7 @@ use massa_pos_exports::{
 use massa_protocol_exports::MockProtocolController;
 use massa_signature::KeyPair;
 use massa_storage::Storage;
-use massa_time::MassaTime;
+use massa_time::{MassaTime, SystemClock};
 
 use crate::start_factory;
 use massa_wallet::test_exports::create_test_wallet;
@@ -100,6 +100,7 @@ impl TestFactory {
                 storage: storage.clone_without_refs(),
             },
             mip_store,
+            Arc::new(SystemClock::default()),
         );
 
         TestFactory {
@@ -5,6 +5,7 @@
 use displaydoc::Display;
 use thiserror::Error;
 
+use massa_db_exports::MassaDBError;
 use massa_versioning::versioning::ExtendFromDbError;
 
 /// Final state error
@@ -21,4 +22,6 @@ pub enum FinalStateError {
     SnapshotError(String),
     /// ExtendFromDbError
     MipStoreError(#[from] ExtendFromDbError),
+    /// database error: {0}
+    DatabaseError(#[from] MassaDBError),
 }
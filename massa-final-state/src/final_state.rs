@@ -117,11 +117,11 @@ impl FinalState {
         };
 
         if reset_final_state {
-            final_state.async_pool.reset();
-            final_state.pos_state.reset();
-            final_state.executed_ops.reset();
-            final_state.executed_denunciations.reset();
-            final_state.db.read().set_initial_change_id(slot);
+            final_state.async_pool.reset()?;
+            final_state.pos_state.reset()?;
+            final_state.executed_ops.reset()?;
+            final_state.executed_denunciations.reset()?;
+            final_state.db.read().set_initial_change_id(slot)?;
         }
 
         info!(
@@ -174,7 +174,7 @@ impl FinalState {
             final_state
                 .db
                 .write()
-                .write_batch(batch, Default::default(), Some(recovered_slot));
+                .write_batch(batch, Default::default(), Some(recovered_slot))?;
         }
 
         final_state.last_slot_before_downtime = Some(recovered_slot);
@@ -287,7 +287,7 @@ impl FinalState {
         let cycle = end_slot.get_cycle(self.config.periods_per_cycle);
 
         self.pos_state
-            .feed_cycle_state_hash(cycle, final_state_hash);
+            .feed_cycle_state_hash(cycle, final_state_hash)?;
 
         Ok(())
     }
@@ -323,7 +323,7 @@ impl FinalState {
         self.pos_state
             .db
             .write()
-            .write_batch(batch, Default::default(), Some(end_slot));
+            .write_batch(batch, Default::default(), Some(end_slot))?;
 
         let mut batch = DBBatch::new();
 
@@ -341,7 +341,7 @@ impl FinalState {
         self.pos_state
             .db
             .write()
-            .write_batch(batch, Default::default(), Some(end_slot));
+            .write_batch(batch, Default::default(), Some(end_slot))?;
 
         Ok(())
     }
@@ -379,7 +379,7 @@ impl FinalState {
         self.pos_state
             .db
             .write()
-            .write_batch(batch, Default::default(), Some(end_slot));
+            .write_batch(batch, Default::default(), Some(end_slot))?;
 
         // Firstly, complete the first cycle
         let last_slot = Slot::new_last_of_cycle(
@@ -410,7 +410,7 @@ impl FinalState {
         self.pos_state
             .db
             .write()
-            .write_batch(batch, Default::default(), Some(end_slot));
+            .write_batch(batch, Default::default(), Some(end_slot))?;
 
         // Feed final_state_hash to the completed cycle
         self.feed_cycle_hash_and_selector_for_interpolation(current_slot_cycle)?;
@@ -456,7 +456,7 @@ impl FinalState {
             self.pos_state
                 .db
                 .write()
-                .write_batch(batch, Default::default(), Some(end_slot));
+                .write_batch(batch, Default::default(), Some(end_slot))?;
 
             // Feed final_state_hash to the completed cycle
             self.feed_cycle_hash_and_selector_for_interpolation(cycle)?;
@@ -498,7 +498,7 @@ impl FinalState {
 
         self.db
             .write()
-            .write_batch(batch, Default::default(), Some(end_slot));
+            .write_batch(batch, Default::default(), Some(end_slot))?;
 
         Ok(())
     }
@@ -511,7 +511,7 @@ impl FinalState {
         let final_state_hash = self.db.read().get_xof_db_hash();
 
         self.pos_state
-            .feed_cycle_state_hash(cycle, final_state_hash);
+            .feed_cycle_state_hash(cycle, final_state_hash)?;
 
         self.pos_state
             .feed_selector(cycle.checked_add(2).ok_or_else(|| {
@@ -526,15 +526,18 @@ impl FinalState {
     /// Reset the final state to the initial state.
     ///
     /// USED ONLY FOR BOOTSTRAP
-    pub fn reset(&mut self) {
+    pub fn reset(&mut self) -> Result<(), FinalStateError> {
         let slot = Slot::new(0, self.config.thread_count.saturating_sub(1));
-        self.db.write().reset(slot);
-        self.ledger.reset();
-        self.async_pool.reset();
-        self.pos_state.reset();
-        self.executed_ops.reset();
-        self.executed_denunciations.reset();
-        self.mip_store.reset_db(self.db.clone());
+        self.db.write().reset(slot)?;
+        self.ledger
+            .reset()
+            .map_err(|err| FinalStateError::LedgerError(err.to_string()))?;
+        self.async_pool.reset()?;
+        self.pos_state.reset()?;
+        self.executed_ops.reset()?;
+        self.executed_denunciations.reset()?;
+        self.mip_store.reset_db(self.db.clone())?;
+        Ok(())
     }
 
     /// Performs the initial draws.
@@ -548,7 +551,9 @@ impl FinalState {
     /// Once this is called, the state is attached at the output of the provided slot.
     ///
     /// Panics if the new slot is not the one coming just after the current one.
-    pub fn finalize(&mut self, slot: Slot, changes: StateChanges) {
+    /// Returns an error if the changes could not be written to the database: the state is then
+    /// left at the previous slot and must not be finalized further.
+    pub fn finalize(&mut self, slot: Slot, changes: StateChanges) -> Result<(), FinalStateError> {
         let cur_slot = self.db.read().get_change_id().expect(CHANGE_ID_DESER_ERROR);
         // check slot consistency
         let next_slot = cur_slot
@@ -618,7 +623,7 @@ impl FinalState {
 
         self.db
             .write()
-            .write_batch(db_batch, db_versioning_batch, Some(slot));
+            .write_batch(db_batch, db_versioning_batch, Some(slot))?;
 
         let final_state_hash = self.db.read().get_xof_db_hash();
 
@@ -651,7 +656,8 @@ impl FinalState {
         // feed final_state_hash to the last cycle
         let cycle = slot.get_cycle(self.config.periods_per_cycle);
         self.pos_state
-            .feed_cycle_state_hash(cycle, final_state_hash);
+            .feed_cycle_state_hash(cycle, final_state_hash)?;
+        Ok(())
     }
 
     /// After bootstrap or load from disk, recompute all the caches.
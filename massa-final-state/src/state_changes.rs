@@ -18,10 +18,11 @@ use nom::{
     sequence::tuple,
     IResult, Parser,
 };
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// represents changes that can be applied to the execution state
-#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+#[derive(Default, Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct StateChanges {
     /// ledger changes
     pub ledger_changes: LedgerChanges,
@@ -165,7 +165,8 @@ fn test_final_state() {
         fs.write()
             .db
             .write()
-            .write_batch(batch, versioning_batch, Some(slot));
+            .write_batch(batch, versioning_batch, Some(slot))
+            .unwrap();
 
         let slot = Slot::new(1, 0);
         let mut state_changes = StateChanges::default();
@@ -206,7 +207,7 @@ fn test_final_state() {
         );
         state_changes.ledger_changes = ledger_changes;
 
-        fs.write().finalize(slot, state_changes);
+        fs.write().finalize(slot, state_changes).unwrap();
 
         hash = fs.read().db.read().get_xof_db_hash();
 
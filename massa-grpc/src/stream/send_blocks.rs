@@ -3,8 +3,9 @@
 use crate::error::{match_for_io_error, GrpcError};
 use crate::server::MassaGrpc;
 use futures_util::StreamExt;
-use massa_models::block::{BlockDeserializer, BlockDeserializerArgs, SecureShareBlock};
-use massa_models::error::ModelsError;
+use massa_models::block::{
+    BlockDeserializer, BlockDeserializerArgs, InvalidBlockReason, SecureShareBlock,
+};
 use massa_models::mapping_grpc::secure_share_to_vec;
 use massa_models::secure_share::SecureShareDeserializer;
 use massa_proto_rs::google::rpc::Status;
@@ -96,22 +97,21 @@ pub(crate) async fn send_blocks(
                             if let Err(e) = res_block
                                 .verify_signature()
                                 .and_then(|_| res_block.content.header.verify_signature())
-                                .map(|_| {
+                                .and_then(|_| {
                                     res_block
                                         .content
                                         .header
                                         .content
                                         .endorsements
                                         .iter()
-                                        .map(|endorsement| endorsement.verify_signature())
-                                        .collect::<Vec<Result<(), ModelsError>>>()
+                                        .try_for_each(|endorsement| endorsement.verify_signature())
                                 })
                             {
                                 report_error(
                                     req_content.id.clone(),
                                     tx.clone(),
                                     tonic::Code::InvalidArgument,
-                                    format!("wrong signature: {}", e),
+                                    format!("{}: {}", InvalidBlockReason::BadSignature, e),
                                 )
                                 .await;
                                 continue;
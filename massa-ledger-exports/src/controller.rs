@@ -46,7 +46,7 @@ pub trait LedgerController: Send + Sync + Debug {
     /// Reset the ledger
     ///
     /// USED FOR BOOTSTRAP ONLY
-    fn reset(&mut self);
+    fn reset(&mut self) -> Result<(), LedgerError>;
 
     fn apply_changes_to_batch(&mut self, changes: LedgerChanges, ledger_batch: &mut DBBatch);
 
@@ -15,4 +15,6 @@ pub enum LedgerError {
     MissingEntry(String),
     /// file error: `{0}`
     FileError(String),
+    /// database error: `{0}`
+    DatabaseError(String),
 }
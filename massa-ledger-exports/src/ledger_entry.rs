@@ -11,11 +11,12 @@ use massa_serialization::{Deserializer, SerializeError, Serializer};
 use nom::error::{context, ContextError, ParseError};
 use nom::sequence::tuple;
 use nom::{IResult, Parser};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::ops::Bound::Included;
 
 /// Structure defining an entry associated to an address in the `FinalLedger`
-#[derive(Default, Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize, Eq, PartialEq, JsonSchema)]
 pub struct LedgerEntry {
     /// The balance of that entry.
     pub balance: Amount,
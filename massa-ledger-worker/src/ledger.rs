@@ -73,8 +73,9 @@ impl LedgerController for FinalLedger {
                 err
             ))
         })?;
-        self.sorted_ledger.load_initial_ledger(initial_ledger);
-        Ok(())
+        self.sorted_ledger
+            .load_initial_ledger(initial_ledger)
+            .map_err(|err| LedgerError::DatabaseError(err.to_string()))
     }
 
     /// Gets the balance of a ledger entry
@@ -145,8 +146,10 @@ impl LedgerController for FinalLedger {
     /// Reset the disk ledger.
     ///
     /// USED FOR BOOTSTRAP ONLY
-    fn reset(&mut self) {
-        self.sorted_ledger.reset();
+    fn reset(&mut self) -> Result<(), LedgerError> {
+        self.sorted_ledger
+            .reset()
+            .map_err(|err| LedgerError::DatabaseError(err.to_string()))
     }
 
     /// Allows applying `LedgerChanges` to the final ledger
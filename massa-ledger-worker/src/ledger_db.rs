@@ -3,8 +3,8 @@
 //! Module to interact with the disk ledger
 
 use massa_db_exports::{
-    DBBatch, MassaDirection, MassaIteratorMode, ShareableMassaDBController, CRUD_ERROR,
-    KEY_SER_ERROR, LEDGER_PREFIX, STATE_CF,
+    DBBatch, MassaDBError, MassaDirection, MassaIteratorMode, ShareableMassaDBController,
+    CRUD_ERROR, KEY_SER_ERROR, LEDGER_PREFIX, STATE_CF,
 };
 use massa_ledger_exports::*;
 use massa_models::amount::AmountDeserializer;
@@ -104,7 +104,10 @@ impl LedgerDB {
     /// Loads the initial disk ledger
     ///
     /// # Arguments
-    pub fn load_initial_ledger(&mut self, initial_ledger: HashMap<Address, LedgerEntry>) {
+    pub fn load_initial_ledger(
+        &mut self,
+        initial_ledger: HashMap<Address, LedgerEntry>,
+    ) -> Result<(), MassaDBError> {
         let mut batch = DBBatch::new();
 
         for (address, entry) in initial_ledger {
@@ -115,7 +118,7 @@ impl LedgerDB {
             batch,
             Default::default(),
             Some(Slot::new(0, self.thread_count.saturating_sub(1))),
-        );
+        )
     }
 
     /// Allows applying `LedgerChanges` to the disk ledger
@@ -208,8 +211,8 @@ impl LedgerDB {
         )
     }
 
-    pub fn reset(&self) {
-        self.db.write().delete_prefix(LEDGER_PREFIX, STATE_CF, None);
+    pub fn reset(&self) -> Result<(), MassaDBError> {
+        self.db.write().delete_prefix(LEDGER_PREFIX, STATE_CF, None)
     }
 
     /// Deserializes the key and value, useful after bootstrap
@@ -564,7 +567,8 @@ mod tests {
         ledger_db
             .db
             .write()
-            .write_batch(batch, Default::default(), None);
+            .write_batch(batch, Default::default(), None)
+            .unwrap();
 
         // return db and initial data
         (ledger_db, data)
@@ -610,7 +614,8 @@ mod tests {
         ledger_db
             .db
             .write()
-            .write_batch(batch, Default::default(), None);
+            .write_batch(batch, Default::default(), None)
+            .unwrap();
 
         // check deleted address and ledger hash
         assert_eq!(
@@ -0,0 +1,198 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+//! Massa operation load generator: creates signed transactions at a fixed rate
+//! and submits them to a node through its public API, to stress the operation
+//! pool and the block production of a test network.
+#![warn(missing_docs)]
+#![warn(unused_crate_dependencies)]
+use anyhow::{bail, Result};
+use massa_api_exports::operation::OperationInput;
+use massa_models::{
+    address::Address,
+    amount::Amount,
+    config::CompactConfig,
+    operation::{Operation, OperationSerializer, OperationType},
+    secure_share::SecureShareContent,
+    slot::Slot,
+    timeslots::get_current_latest_block_slot,
+};
+use massa_sdk::{ClientConfig, HttpConfig, RpcClient};
+use massa_signature::KeyPair;
+use massa_time::MassaTime;
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+struct Args {
+    /// Address of the node
+    #[structopt(long, default_value = "127.0.0.1")]
+    ip: IpAddr,
+    /// Port of the public API of the node
+    #[structopt(long, default_value = "33035")]
+    public_port: u16,
+    /// Secret key of an address paying for the operations, can be repeated
+    #[structopt(long = "secret-key", required = true)]
+    secret_keys: Vec<KeyPair>,
+    /// Number of operations sent per second
+    #[structopt(long, default_value = "100")]
+    rate: u64,
+    /// Number of operations sent in a single request
+    #[structopt(long, default_value = "10")]
+    batch_size: u64,
+    /// Fee of each operation
+    #[structopt(long, default_value = "0")]
+    fee: Amount,
+    /// Stop after this number of seconds, run until interrupted if absent
+    #[structopt(long)]
+    duration: Option<u64>,
+    /// Number of seconds between two progress reports
+    #[structopt(long, default_value = "10")]
+    report_interval: u64,
+}
+
+/// Counters reported while the load is generated
+#[derive(Default)]
+struct Stats {
+    sent: u64,
+    accepted: u64,
+    failed_requests: u64,
+}
+
+/// Creates signed transactions, each sender paying a different amount to itself every time
+/// so that no two operations share the same id.
+struct OperationFactory {
+    keypairs: Vec<(KeyPair, Address)>,
+    fee: Amount,
+    counter: u64,
+}
+
+impl OperationFactory {
+    fn new(keypairs: Vec<KeyPair>, fee: Amount) -> Self {
+        OperationFactory {
+            keypairs: keypairs
+                .into_iter()
+                .map(|keypair| {
+                    let address = Address::from_public_key(&keypair.get_public_key());
+                    (keypair, address)
+                })
+                .collect(),
+            fee,
+            counter: 0,
+        }
+    }
+
+    fn create(&mut self, cfg: &CompactConfig, slot: Slot) -> Result<OperationInput> {
+        let (keypair, address) =
+            &self.keypairs[(self.counter % self.keypairs.len() as u64) as usize];
+        self.counter += 1;
+        let mut expire_period = slot.period + cfg.operation_validity_periods;
+        if slot.thread >= address.get_thread(cfg.thread_count) {
+            expire_period += 1;
+        };
+        let op = Operation::new_verifiable(
+            Operation {
+                fee: self.fee,
+                expire_period,
+                op: OperationType::Transaction {
+                    recipient_address: *address,
+                    amount: Amount::from_raw(self.counter),
+                },
+            },
+            OperationSerializer::new(),
+            keypair,
+        )?;
+        Ok(OperationInput {
+            creator_public_key: op.content_creator_pub_key,
+            serialized_content: op.serialized_data,
+            signature: op.signature,
+        })
+    }
+}
+
+#[paw::main]
+fn main(args: Args) -> anyhow::Result<()> {
+    let tokio_rt = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+
+    tokio_rt.block_on(run(args))
+}
+
+async fn run(args: Args) -> Result<()> {
+    if args.rate == 0 || args.batch_size == 0 {
+        bail!("rate and batch size must be strictly positive");
+    }
+    let http_config = HttpConfig {
+        client_config: ClientConfig {
+            max_request_body_size: 52428800,
+            request_timeout: MassaTime::from_millis(60000),
+            max_concurrent_requests: 100,
+            certificate_store: "Native".to_string(),
+            id_kind: "Number".to_string(),
+            max_log_length: 4096,
+            headers: Vec::new(),
+        },
+        enabled: true,
+    };
+    let url = format!("http://{}", SocketAddr::new(args.ip, args.public_port));
+    let client = RpcClient::from_url(&url, &http_config).await;
+    let cfg = match client.get_status().await {
+        Ok(node_status) => node_status.config,
+        Err(e) => bail!("cannot get the status of the node at {}: {}", url, e),
+    };
+
+    let mut factory = OperationFactory::new(args.secret_keys, args.fee);
+    let mut stats = Stats::default();
+    let start = Instant::now();
+    let mut last_report = start;
+    let mut interval = tokio::time::interval(Duration::from_secs_f64(
+        args.batch_size as f64 / args.rate as f64,
+    ));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    println!(
+        "sending {} operations per second to {} in batches of {}",
+        args.rate, url, args.batch_size
+    );
+
+    loop {
+        interval.tick().await;
+        if let Some(duration) = args.duration {
+            if start.elapsed() >= Duration::from_secs(duration) {
+                break;
+            }
+        }
+
+        let slot = get_current_latest_block_slot(cfg.thread_count, cfg.t0, cfg.genesis_timestamp)?
+            .unwrap_or_else(|| Slot::new(0, 0));
+        let operations = (0..args.batch_size)
+            .map(|_| factory.create(&cfg, slot))
+            .collect::<Result<Vec<_>>>()?;
+        stats.sent += args.batch_size;
+        match client.send_operations(operations).await {
+            Ok(operation_ids) => stats.accepted += operation_ids.len() as u64,
+            Err(e) => {
+                stats.failed_requests += 1;
+                eprintln!("failed to send operations: {}", e);
+            }
+        }
+
+        if last_report.elapsed() >= Duration::from_secs(args.report_interval) {
+            last_report = Instant::now();
+            report(&stats, start.elapsed());
+        }
+    }
+    report(&stats, start.elapsed());
+    Ok(())
+}
+
+fn report(stats: &Stats, elapsed: Duration) {
+    println!(
+        "{:.0}s: {} operations sent ({:.1}/s), {} accepted by the pool, {} failed requests",
+        elapsed.as_secs_f64(),
+        stats.sent,
+        stats.sent as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+        stats.accepted,
+        stats.failed_requests
+    );
+}
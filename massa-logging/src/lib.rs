@@ -13,3 +13,15 @@ macro_rules! massa_trace {
         $crate::tracing::trace!("massa:{}:{}", $evt, $crate::serde_json::json!($params));
     };
 }
+
+/// Target of the events recorded in the event journal of the node
+pub const JOURNAL_TARGET: &str = "massa_journal";
+
+#[macro_export]
+/// record a significant event (reorg, desync, ban...) in the event journal of the node,
+/// kept on disk to diagnose incidents after the fact
+macro_rules! massa_journal {
+    ($kind:expr, $params:tt) => {
+        $crate::tracing::info!(target: $crate::JOURNAL_TARGET, kind = $kind, "{}", $crate::serde_json::json!($params));
+    };
+}
@@ -21,6 +21,9 @@ use tracing::warn;
 // #[cfg(not(feature = "testing"))]
 mod server;
 
+#[cfg(feature = "otlp")]
+pub mod otlp;
+
 mod survey;
 
 lazy_static! {
@@ -97,6 +100,9 @@ pub struct MassaMetrics {
     /// counter of operations for final slot
     operations_final_counter: IntCounter,
 
+    /// commands that could not be sent to the consensus worker
+    consensus_dropped_commands: IntCounter,
+
     // block_cache
     block_cache_checked_headers_size: IntGauge,
     block_cache_blocks_known_by_peer: IntGauge,
@@ -271,6 +277,12 @@ impl MassaMetrics {
         let operations_final_counter =
             IntCounter::new("operations_final_counter", "total final operations").unwrap();
 
+        let consensus_dropped_commands = IntCounter::new(
+            "consensus_dropped_commands",
+            "total commands dropped before reaching the consensus worker",
+        )
+        .unwrap();
+
         let mut stopper = MetricsStopper::default();
 
         if enabled {
@@ -306,6 +318,7 @@ impl MassaMetrics {
                 let _ = prometheus::register(Box::new(peernet_total_bytes_receive.clone()));
                 let _ = prometheus::register(Box::new(peernet_total_bytes_sent.clone()));
                 let _ = prometheus::register(Box::new(operations_final_counter.clone()));
+                let _ = prometheus::register(Box::new(consensus_dropped_commands.clone()));
 
                 stopper = server::bind_metrics(addr);
             }
@@ -330,6 +343,7 @@ impl MassaMetrics {
                 active_in_connections,
                 active_out_connections,
                 operations_final_counter,
+                consensus_dropped_commands,
                 block_cache_checked_headers_size,
                 block_cache_blocks_known_by_peer,
                 operation_cache_checked_operations,
@@ -448,6 +462,10 @@ impl MassaMetrics {
         self.operations_final_counter.inc_by(diff);
     }
 
+    pub fn inc_consensus_dropped_commands(&self) {
+        self.consensus_dropped_commands.inc();
+    }
+
     /// Update the bandwidth metrics for all peers
     /// HashMap<peer_id, (tx, rx)>
     pub fn update_peers_tx_rx(&self, data: HashMap<String, (u64, u64)>) {
@@ -0,0 +1,159 @@
+//! Export of the prometheus metrics with OTLP.
+//!
+//! The metrics registered in prometheus are read at regular intervals and reported to an
+//! OpenTelemetry meter: the counters as observable counters, the histograms and summaries as
+//! `_count` and `_sum` counters, the other metrics as observable gauges. The bandwidth metrics of
+//! the peers, registered in prometheus with the peer id in their name, are reported under a single
+//! name with a `peer_id` attribute.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use opentelemetry::{
+    metrics::{CallbackRegistration, Meter},
+    KeyValue,
+};
+use prometheus::proto::MetricType;
+use tracing::warn;
+
+/// prefixes of the names of the metrics registered for each peer, followed by the peer id
+const PEER_METRIC_PREFIXES: [&str; 2] = ["peer_total_bytes_receive_", "peer_total_bytes_sent_"];
+
+/// last values read in prometheus, with their attributes, by OTLP name
+type Samples = HashMap<String, Vec<(f64, Vec<KeyValue>)>>;
+
+/// OTLP name and attributes of the prometheus metric `name`
+fn otlp_name(name: &str) -> (String, Vec<KeyValue>) {
+    for prefix in PEER_METRIC_PREFIXES {
+        if let Some(peer_id) = name.strip_prefix(prefix) {
+            return (
+                prefix.trim_end_matches('_').to_string(),
+                vec![KeyValue::new("peer_id", peer_id.to_string())],
+            );
+        }
+    }
+    (name.to_string(), Vec::new())
+}
+
+/// Reports the prometheus metrics to an OpenTelemetry meter
+struct PrometheusBridge {
+    meter: Meter,
+    samples: Arc<RwLock<Samples>>,
+    /// names of the instruments created in the meter
+    instruments: HashSet<String>,
+    /// callbacks of the instruments, kept registered as long as the bridge runs
+    _registrations: Vec<Box<dyn CallbackRegistration>>,
+}
+
+impl PrometheusBridge {
+    /// Read the prometheus metrics, and create the instruments of the new ones
+    fn refresh(&mut self) {
+        let mut samples = Samples::new();
+        let mut counters = HashSet::new();
+        for family in prometheus::gather() {
+            let (name, attributes) = otlp_name(family.get_name());
+            for metric in family.get_metric() {
+                let mut attributes = attributes.clone();
+                attributes.extend(metric.get_label().iter().map(|label| {
+                    KeyValue::new(label.get_name().to_string(), label.get_value().to_string())
+                }));
+                let values = match family.get_field_type() {
+                    MetricType::COUNTER => {
+                        counters.insert(name.clone());
+                        vec![(name.clone(), metric.get_counter().get_value())]
+                    }
+                    MetricType::GAUGE => vec![(name.clone(), metric.get_gauge().get_value())],
+                    MetricType::UNTYPED => vec![(name.clone(), metric.get_untyped().get_value())],
+                    MetricType::HISTOGRAM => {
+                        let histogram = metric.get_histogram();
+                        counters.insert(format!("{}_count", name));
+                        counters.insert(format!("{}_sum", name));
+                        vec![
+                            (
+                                format!("{}_count", name),
+                                histogram.get_sample_count() as f64,
+                            ),
+                            (format!("{}_sum", name), histogram.get_sample_sum()),
+                        ]
+                    }
+                    MetricType::SUMMARY => {
+                        let summary = metric.get_summary();
+                        counters.insert(format!("{}_count", name));
+                        counters.insert(format!("{}_sum", name));
+                        vec![
+                            (format!("{}_count", name), summary.get_sample_count() as f64),
+                            (format!("{}_sum", name), summary.get_sample_sum()),
+                        ]
+                    }
+                };
+                for (name, value) in values {
+                    samples
+                        .entry(name)
+                        .or_default()
+                        .push((value, attributes.clone()));
+                }
+            }
+        }
+
+        for name in samples.keys() {
+            if !self.instruments.contains(name) {
+                self.register(name, counters.contains(name));
+            }
+        }
+        *self.samples.write().unwrap() = samples;
+    }
+
+    /// Create the instrument `name`, observing the last values read in prometheus
+    fn register(&mut self, name: &str, counter: bool) {
+        let samples = Arc::clone(&self.samples);
+        let key = name.to_string();
+        let registration = if counter {
+            let instrument = self.meter.f64_observable_counter(name.to_string()).init();
+            self.meter
+                .register_callback(&[instrument.as_any()], move |observer| {
+                    for (value, attributes) in
+                        samples.read().unwrap().get(&key).into_iter().flatten()
+                    {
+                        observer.observe_f64(&instrument, *value, attributes);
+                    }
+                })
+        } else {
+            let instrument = self.meter.f64_observable_gauge(name.to_string()).init();
+            self.meter
+                .register_callback(&[instrument.as_any()], move |observer| {
+                    for (value, attributes) in
+                        samples.read().unwrap().get(&key).into_iter().flatten()
+                    {
+                        observer.observe_f64(&instrument, *value, attributes);
+                    }
+                })
+        };
+        match registration {
+            Ok(registration) => {
+                self.instruments.insert(name.to_string());
+                self._registrations.push(registration);
+            }
+            Err(err) => warn!("could not export the metric {} with OTLP: {}", name, err),
+        }
+    }
+}
+
+/// Report the prometheus metrics to `meter`, reading them every `interval`.
+/// Must be called from a tokio runtime.
+pub fn export_prometheus_metrics(meter: Meter, interval: Duration) {
+    let mut bridge = PrometheusBridge {
+        meter,
+        samples: Default::default(),
+        instruments: HashSet::new(),
+        _registrations: Vec::new(),
+    };
+    tokio::spawn(async move {
+        loop {
+            bridge.refresh();
+            tokio::time::sleep(interval).await;
+        }
+    });
+}
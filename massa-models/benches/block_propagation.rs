@@ -0,0 +1,272 @@
+//! Measures the time from the creation of a block on a node to its integration
+//! by all its peers: the creator signs and serializes the header, operation ids and
+//! operations and sends them to each peer over a loopback TCP connection. Every peer runs
+//! in its own thread, deserializes the block, checks the signatures and the operation
+//! merkle root, then acknowledges it. An iteration ends when the last peer has acknowledged.
+//!
+//! Run with `cargo bench -p massa_models --features benchmarking --bench block_propagation`.
+
+#[cfg(feature = "benchmarking")]
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+#[cfg(feature = "benchmarking")]
+fn criterion_benchmark(c: &mut Criterion) {
+    use massa_hash::Hash;
+    use massa_models::{
+        address::Address,
+        amount::Amount,
+        block::{Block, BlockSerializer},
+        block_header::{BlockHeader, BlockHeaderDeserializer, BlockHeaderSerializer},
+        block_id::BlockId,
+        config::{
+            ENDORSEMENT_COUNT, MAX_DATASTORE_VALUE_LENGTH, MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
+            MAX_FUNCTION_NAME_LENGTH, MAX_OPERATIONS_PER_BLOCK,
+            MAX_OPERATION_DATASTORE_ENTRY_COUNT, MAX_OPERATION_DATASTORE_KEY_LENGTH,
+            MAX_OPERATION_DATASTORE_VALUE_LENGTH, MAX_PARAMETERS_SIZE, THREAD_COUNT,
+        },
+        operation::{
+            Operation, OperationIdsDeserializer, OperationIdsSerializer, OperationSerializer,
+            OperationType, OperationsDeserializer, OperationsSerializer, SecureShareOperation,
+        },
+        secure_share::{Id, SecureShareContent, SecureShareDeserializer, SecureShareSerializer},
+        slot::Slot,
+    };
+    use massa_serialization::{DeserializeError, Deserializer, Serializer};
+    use massa_signature::KeyPair;
+    use std::{
+        io::{Read, Write},
+        net::{TcpListener, TcpStream},
+        thread::JoinHandle,
+        time::{Duration, Instant},
+    };
+
+    /// Bytes sent by the creator of a block to each of its peers
+    struct WireBlock {
+        header: Vec<u8>,
+        operation_ids: Vec<u8>,
+        operations: Vec<u8>,
+    }
+
+    impl WireBlock {
+        fn len(&self) -> usize {
+            self.header.len() + self.operation_ids.len() + self.operations.len()
+        }
+
+        /// Each part is framed with its length as a big endian u32, as on the node connections
+        fn write(&self, stream: &mut TcpStream) {
+            for part in [&self.header, &self.operation_ids, &self.operations] {
+                stream
+                    .write_all(&(part.len() as u32).to_be_bytes())
+                    .unwrap();
+                stream.write_all(part).unwrap();
+            }
+        }
+
+        /// Returns `None` when the creator closed the connection
+        fn read(stream: &mut TcpStream) -> Option<WireBlock> {
+            let mut parts = Vec::with_capacity(3);
+            for _ in 0..3 {
+                let mut len = [0u8; 4];
+                stream.read_exact(&mut len).ok()?;
+                let mut part = vec![0u8; u32::from_be_bytes(len) as usize];
+                stream.read_exact(&mut part).ok()?;
+                parts.push(part);
+            }
+            let operations = parts.pop()?;
+            let operation_ids = parts.pop()?;
+            let header = parts.pop()?;
+            Some(WireBlock {
+                header,
+                operation_ids,
+                operations,
+            })
+        }
+    }
+
+    fn create_operations(count: usize) -> Vec<SecureShareOperation> {
+        let keypair = KeyPair::generate(0).unwrap();
+        let recipient_address = Address::from_public_key(&keypair.get_public_key());
+        (0..count)
+            .map(|i| {
+                Operation::new_verifiable(
+                    Operation {
+                        fee: Amount::from_raw(i as u64),
+                        expire_period: 10,
+                        op: OperationType::Transaction {
+                            recipient_address,
+                            amount: Amount::from_raw(1),
+                        },
+                    },
+                    OperationSerializer::new(),
+                    &keypair,
+                )
+                .unwrap()
+            })
+            .collect()
+    }
+
+    /// What the block creator does: build and sign the block, then serialize it for the network
+    fn create_block(keypair: &KeyPair, operations: &[SecureShareOperation]) -> WireBlock {
+        let operation_ids: Vec<_> = operations.iter().map(|op| op.id).collect();
+        let mut total_hash = Vec::new();
+        for op_id in &operation_ids {
+            total_hash.extend(op_id.get_hash().into_bytes());
+        }
+        let header = BlockHeader::new_verifiable(
+            BlockHeader {
+                current_version: 0,
+                announced_version: None,
+                slot: Slot::new(1, 0),
+                parents: (0..THREAD_COUNT)
+                    .map(|i| BlockId(Hash::compute_from(&[i])))
+                    .collect(),
+                operation_merkle_root: Hash::compute_from(&total_hash),
+                endorsements: Vec::new(),
+                denunciations: Vec::new(),
+            },
+            BlockHeaderSerializer::new(),
+            keypair,
+        )
+        .unwrap();
+        let block = Block::new_verifiable(
+            Block {
+                header,
+                operations: operation_ids,
+            },
+            BlockSerializer::new(),
+            keypair,
+        )
+        .unwrap();
+
+        let mut wire_block = WireBlock {
+            header: Vec::new(),
+            operation_ids: Vec::new(),
+            operations: Vec::new(),
+        };
+        SecureShareSerializer::new()
+            .serialize(&block.content.header, &mut wire_block.header)
+            .unwrap();
+        OperationIdsSerializer::new()
+            .serialize(&block.content.operations, &mut wire_block.operation_ids)
+            .unwrap();
+        OperationsSerializer::new()
+            .serialize(&operations.to_vec(), &mut wire_block.operations)
+            .unwrap();
+        wire_block
+    }
+
+    /// What a receiving peer does: deserialize and check the block before handing it to consensus
+    fn integrate_block(wire_block: &WireBlock) {
+        let (_, header) = SecureShareDeserializer::new(BlockHeaderDeserializer::new(
+            THREAD_COUNT,
+            ENDORSEMENT_COUNT,
+            MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
+            Some(0),
+        ))
+        .deserialize::<DeserializeError>(&wire_block.header)
+        .unwrap();
+        header.verify_signature().unwrap();
+
+        let (_, operation_ids) = OperationIdsDeserializer::new(MAX_OPERATIONS_PER_BLOCK)
+            .deserialize::<DeserializeError>(&wire_block.operation_ids)
+            .unwrap();
+        let mut total_hash = Vec::new();
+        for op_id in &operation_ids {
+            total_hash.extend(op_id.get_hash().into_bytes());
+        }
+        assert_eq!(
+            header.content.operation_merkle_root,
+            Hash::compute_from(&total_hash)
+        );
+
+        let (_, operations) = OperationsDeserializer::new(
+            MAX_OPERATIONS_PER_BLOCK,
+            MAX_DATASTORE_VALUE_LENGTH,
+            MAX_FUNCTION_NAME_LENGTH,
+            MAX_PARAMETERS_SIZE,
+            MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+            MAX_OPERATION_DATASTORE_KEY_LENGTH,
+            MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+        )
+        .deserialize::<DeserializeError>(&wire_block.operations)
+        .unwrap();
+        for (op, op_id) in operations.iter().zip(&operation_ids) {
+            op.verify_signature().unwrap();
+            assert_eq!(&op.id, op_id);
+        }
+    }
+
+    /// Start `count` peers, each one in its own thread, and return the connections of the creator to them
+    fn start_peers(count: usize) -> (Vec<TcpStream>, Vec<JoinHandle<()>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let mut connections = Vec::with_capacity(count);
+        let mut handles = Vec::with_capacity(count);
+        for _ in 0..count {
+            let connection = TcpStream::connect(address).unwrap();
+            connection.set_nodelay(true).unwrap();
+            let (mut peer_stream, _) = listener.accept().unwrap();
+            peer_stream.set_nodelay(true).unwrap();
+            handles.push(std::thread::spawn(move || {
+                while let Some(wire_block) = WireBlock::read(&mut peer_stream) {
+                    integrate_block(&wire_block);
+                    peer_stream.write_all(&[1]).unwrap();
+                }
+            }));
+            connections.push(connection);
+        }
+        (connections, handles)
+    }
+
+    let keypair = KeyPair::generate(0).unwrap();
+    for peer_count in [1usize, 8, 32] {
+        let mut group = c.benchmark_group(format!("block_propagation_{}_peers", peer_count));
+        group.sample_size(10);
+        let (mut connections, handles) = start_peers(peer_count);
+        for operation_count in [0usize, 100, 1000, MAX_OPERATIONS_PER_BLOCK as usize] {
+            let operations = create_operations(operation_count);
+            // bytes sent by the creator for each block
+            let wire_size = create_block(&keypair, &operations).len() * peer_count;
+            group.throughput(Throughput::Bytes(wire_size as u64));
+            group.bench_with_input(
+                BenchmarkId::from_parameter(operation_count),
+                &operations,
+                |b, operations| {
+                    b.iter_custom(|iters| {
+                        let mut total = Duration::ZERO;
+                        for _ in 0..iters {
+                            let start = Instant::now();
+                            let wire_block = create_block(&keypair, operations);
+                            for connection in connections.iter_mut() {
+                                wire_block.write(connection);
+                            }
+                            for connection in connections.iter_mut() {
+                                let mut ack = [0u8; 1];
+                                connection.read_exact(&mut ack).unwrap();
+                            }
+                            total += start.elapsed();
+                        }
+                        total
+                    })
+                },
+            );
+        }
+        group.finish();
+        // closing the connections stops the peers
+        connections.clear();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+}
+
+#[cfg(feature = "benchmarking")]
+criterion_group!(benches, criterion_benchmark);
+
+#[cfg(feature = "benchmarking")]
+criterion_main!(benches);
+
+#[cfg(not(feature = "benchmarking"))]
+fn main() {
+    println!("Please use the `--features benchmarking` flag to run this benchmark.");
+}
@@ -10,6 +10,7 @@ use massa_serialization::{
 use massa_signature::{PublicKey, PublicKeyV0, PublicKeyV1};
 use nom::error::{context, ContextError, ErrorKind, ParseError};
 use nom::{IResult, Parser};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::ops::Bound::{Excluded, Included};
 use std::str::FromStr;
@@ -688,7 +689,7 @@ impl Deserializer<SCAddress> for AddressDeserializer {
 }
 
 /// Info for a given address on a given cycle
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ExecutionAddressCycleInfo {
     /// cycle number
     pub cycle: u64,
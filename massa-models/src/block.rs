@@ -7,14 +7,17 @@ use crate::secure_share::{
     Id, SecureShare, SecureShareContent, SecureShareDeserializer, SecureShareSerializer,
 };
 use crate::{
+    address::Address,
     // endorsement::{Endorsement, EndorsementDeserializerLW, SecureShareEndorsement},
     error::ModelsError,
     operation::{
         OperationId, OperationIdsDeserializer, OperationIdsSerializer, SecureShareOperation,
     },
     // slot::{Slot, SlotDeserializer, SlotSerializer},
+    slot::Slot,
 };
 // use massa_hash::{Hash, HashDeserializer};
+use massa_hash::Hash;
 use massa_serialization::{
     // DeserializeError,
     Deserializer,
@@ -23,7 +26,7 @@ use massa_serialization::{
     // U32VarIntDeserializer,
     // U32VarIntSerializer, U64VarIntDeserializer, U64VarIntSerializer,
 };
-use massa_signature::{KeyPair, PublicKey, Signature};
+use massa_signature::{PublicKey, Signature};
 // use nom::branch::alt;
 // use nom::bytes::complete::tag;
 use nom::error::context;
@@ -34,6 +37,7 @@ use nom::{
     error::{ContextError, ParseError},
     IResult,
 };
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 // use serde_with::{DeserializeFromStr, SerializeDisplay};
 // use std::collections::HashSet;
@@ -45,7 +49,7 @@ use crate::block_header::{BlockHeader, BlockHeaderDeserializer, SecuredHeader};
 use crate::block_id::BlockId;
 
 /// block
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Block {
     /// signed header
     pub header: SecuredHeader,
@@ -66,11 +70,19 @@ pub struct FilledBlock {
 pub type SecureShareBlock = SecureShare<Block, BlockId>;
 
 impl SecureShareContent for Block {
-    fn new_verifiable<SC: Serializer<Self>, U: Id>(
+    /// A block is not signed by itself: it carries the signature of its header,
+    /// so the signer is never called.
+    fn new_verifiable_with_signer<SC, U, F>(
         self,
         content_serializer: SC,
-        _keypair: &KeyPair,
-    ) -> Result<SecureShare<Self, U>, ModelsError> {
+        _public_key: PublicKey,
+        _signer: F,
+    ) -> Result<SecureShare<Self, U>, ModelsError>
+    where
+        SC: Serializer<Self>,
+        U: Id,
+        F: FnOnce(&Hash) -> Result<Signature, ModelsError>,
+    {
         let mut content_serialized = Vec::new();
         content_serializer.serialize(&self, &mut content_serialized)?;
         Ok(SecureShare {
@@ -80,6 +92,7 @@ impl SecureShareContent for Block {
             id: U::new(*self.header.id.get_hash()),
             content: self,
             serialized_data: content_serialized,
+            signed_hash: Default::default(),
         })
     }
 
@@ -115,6 +128,7 @@ impl SecureShareContent for Block {
                 id: U::new(*content.header.id.get_hash()),
                 content,
                 serialized_data: buffer[..buffer.len() - rest.len()].to_vec(),
+                signed_hash: Default::default(),
             },
         ))
     }
@@ -394,7 +408,7 @@ impl std::fmt::Display for Block {
 // }
 
 /// Block status within the graph
-#[derive(Eq, PartialEq, Debug, Deserialize, Serialize)]
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Deserialize, Serialize)]
 pub enum BlockGraphStatus {
     /// received but not yet graph-processed
     Incoming,
@@ -414,6 +428,141 @@ pub enum BlockGraphStatus {
     NotFound,
 }
 
+/// Reason why a block is invalid
+#[derive(Eq, PartialEq, Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum InvalidBlockReason {
+    /// the signature of the block is not valid.
+    /// Never recorded in the graph for a block received from the network: the id of a block does
+    /// not commit to its signature, so a peer could get a valid block rejected by sending it with
+    /// a bad signature.
+    BadSignature,
+    /// the creator was not selected to produce a block at the slot of the block
+    WrongCreator {
+        /// slot of the block
+        slot: Slot,
+        /// address selected to produce a block at that slot
+        expected: Address,
+        /// creator of the block
+        creator: Address,
+    },
+    /// an endorser was not selected to endorse at the index of its endorsement
+    WrongEndorser {
+        /// index of the endorsement
+        index: u32,
+        /// address selected to endorse at that index
+        expected: Address,
+        /// creator of the endorsement
+        endorser: Address,
+    },
+    /// a parent is not in the thread it is the parent for
+    WrongParentThread {
+        /// the parent
+        parent: BlockId,
+        /// slot of the parent
+        parent_slot: Slot,
+        /// thread the parent is the parent for
+        thread: u8,
+    },
+    /// a parent is not from a slot earlier than the slot of the block
+    ParentsInFuture {
+        /// the parent
+        parent: BlockId,
+        /// slot of the parent
+        parent_slot: Slot,
+    },
+    /// the parents are not topologically consistent with each other
+    InconsistentParents,
+    /// the block is incompatible with a parent, or its parents with each other
+    IncompatibleParents,
+    /// the block depends on an invalid block
+    InvalidAncestor {
+        /// the first invalid block the block depends on
+        ancestor: BlockId,
+    },
+    /// the operations of the block exceed the max size of a block
+    TooLarge {
+        /// size of the serialized operations, in bytes
+        size: usize,
+        /// max size of the serialized operations of a block, in bytes
+        max_size: usize,
+    },
+    /// an operation of the block is invalid
+    InvalidOperation {
+        /// index of the operation in the block
+        index: usize,
+        /// why the operation is invalid
+        reason: String,
+    },
+}
+
+impl InvalidBlockReason {
+    /// Reason why a block that depends on `block_id`, invalid for the reason `self`, is invalid:
+    /// the first invalid block of the chain is kept
+    pub fn inherited_from(&self, block_id: BlockId) -> InvalidBlockReason {
+        match self {
+            InvalidBlockReason::InvalidAncestor { .. } => self.clone(),
+            _ => InvalidBlockReason::InvalidAncestor { ancestor: block_id },
+        }
+    }
+}
+
+impl std::fmt::Display for InvalidBlockReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvalidBlockReason::BadSignature => write!(f, "bad signature"),
+            InvalidBlockReason::WrongCreator {
+                slot,
+                expected,
+                creator,
+            } => write!(
+                f,
+                "wrong creator {} for the slot {}, {} was selected",
+                creator, slot, expected
+            ),
+            InvalidBlockReason::WrongEndorser {
+                index,
+                expected,
+                endorser,
+            } => write!(
+                f,
+                "wrong endorser {} at index {}, {} was selected",
+                endorser, index, expected
+            ),
+            InvalidBlockReason::WrongParentThread {
+                parent,
+                parent_slot,
+                thread,
+            } => write!(
+                f,
+                "parent {} at slot {} is not in thread {}",
+                parent, parent_slot, thread
+            ),
+            InvalidBlockReason::ParentsInFuture {
+                parent,
+                parent_slot,
+            } => write!(
+                f,
+                "parent {} at slot {} is not earlier than the block",
+                parent, parent_slot
+            ),
+            InvalidBlockReason::InconsistentParents => write!(f, "inconsistent parents"),
+            InvalidBlockReason::IncompatibleParents => write!(f, "incompatible parents"),
+            InvalidBlockReason::InvalidAncestor { ancestor } => {
+                write!(f, "depends on the invalid block {}", ancestor)
+            }
+            InvalidBlockReason::TooLarge { size, max_size } => write!(
+                f,
+                "operations of {} bytes exceed the max size of {} bytes",
+                size, max_size
+            ),
+            InvalidBlockReason::InvalidOperation { index, reason } => {
+                write!(f, "invalid operation at index {}: {}", index, reason)
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -752,6 +901,59 @@ mod test {
         // TODO: assert that the error variant/context/etc. matches the expected failure
         assert!(res.is_err());
     }
+    #[test]
+    #[serial]
+    fn test_block_signed_with_signer() {
+        let keypair = KeyPair::generate(0).unwrap();
+        let header = BlockHeader {
+            current_version: 0,
+            announced_version: None,
+            slot: Slot::new(1, 0),
+            parents: (0..THREAD_COUNT)
+                .map(|i| BlockId(Hash::compute_from(&[i])))
+                .collect(),
+            operation_merkle_root: Hash::compute_from("mno".as_bytes()),
+            endorsements: vec![],
+            denunciations: vec![],
+        };
+
+        // the header signed by an external signer is the one signed with the keypair
+        let local_header: SecuredHeader = header
+            .clone()
+            .new_verifiable(BlockHeaderSerializer::new(), &keypair)
+            .unwrap();
+        let remote_header: SecuredHeader = header
+            .new_verifiable_with_signer(
+                BlockHeaderSerializer::new(),
+                keypair.get_public_key(),
+                |hash| Ok(keypair.sign(hash)?),
+            )
+            .unwrap();
+        assert_eq!(local_header.id, remote_header.id);
+        assert_eq!(local_header.signature, remote_header.signature);
+        assert_eq!(local_header.serialized_data, remote_header.serialized_data);
+        remote_header.verify_signature().unwrap();
+
+        // the block reuses the signature of its header and never calls the signer
+        let block = Block {
+            header: remote_header.clone(),
+            operations: Default::default(),
+        };
+        let local_block: SecureShareBlock = block
+            .clone()
+            .new_verifiable(BlockSerializer::new(), &keypair)
+            .unwrap();
+        let remote_block: SecureShareBlock = block
+            .new_verifiable_with_signer(BlockSerializer::new(), keypair.get_public_key(), |_hash| {
+                panic!("a block must not be signed on its own")
+            })
+            .unwrap();
+        assert_eq!(local_block.id, remote_block.id);
+        assert_eq!(local_block.signature, remote_header.signature);
+        assert_eq!(remote_block.signature, remote_header.signature);
+        assert_eq!(local_block.serialized_data, remote_block.serialized_data);
+    }
+
     #[test]
     #[serial]
     fn test_invalid_block_serialization_no_parents() {
@@ -1222,4 +1424,25 @@ mod test {
         // TODO: assert that the error variant/context/etc. matches the expected failure
         assert!(res.is_err());
     }
+
+    #[test]
+    fn test_invalid_block_reason_inherited() {
+        let block_id = |seed: u8| BlockId(Hash::compute_from(&[seed]));
+        let reason = InvalidBlockReason::TooLarge {
+            size: 2,
+            max_size: 1,
+        };
+
+        // a child of an invalid block points to it
+        let child_reason = reason.inherited_from(block_id(0));
+        assert_eq!(
+            child_reason,
+            InvalidBlockReason::InvalidAncestor {
+                ancestor: block_id(0)
+            }
+        );
+
+        // the descendants point to the first invalid block
+        assert_eq!(child_reason.inherited_from(block_id(1)), child_reason);
+    }
 }
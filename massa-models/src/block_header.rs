@@ -20,13 +20,14 @@ use nom::error::{context, ContextError, ParseError};
 use nom::multi::{count, length_count};
 use nom::sequence::{preceded, tuple};
 use nom::{IResult, Parser};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::Bound::{Excluded, Included};
 use std::collections::HashSet;
 use std::fmt::Formatter;
 
 /// block header
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct BlockHeader {
     /// current network version
     pub current_version: u32,
@@ -444,6 +445,14 @@ impl Deserializer<BlockHeader> for BlockHeaderDeserializer {
         .parse(buffer)?;
 
         if parents.is_empty() {
+            // a header without parents has no endorsements and no denunciations,
+            // the remaining bytes must be their two zero lengths
+            let (rest, _) = context(
+                "Failed empty endorsements and denunciations deserialization",
+                tag(&[0, 0]),
+            )
+            .parse(rest)?;
+
             let res = BlockHeader {
                 current_version,
                 announced_version,
@@ -459,10 +468,7 @@ impl Deserializer<BlockHeader> for BlockHeaderDeserializer {
             res.assert_invariants(self.thread_count, self.endorsement_count)
                 .unwrap();
 
-            return Ok((
-                &rest[2..], // Because there is 0 endorsements & 0 denunciations, we have a remaining [0, 0] in rest and we don't need it
-                res,
-            ));
+            return Ok((rest, res));
         }
 
         // Now deser the endorsements (which were light-weight serialized)
@@ -606,6 +612,7 @@ mod test {
     use massa_serialization::DeserializeError;
 
     use crate::config::{ENDORSEMENT_COUNT, MAX_DENUNCIATIONS_PER_BLOCK_HEADER, THREAD_COUNT};
+    use crate::secure_share::Id;
 
     use crate::test_exports::{
         gen_block_headers_for_denunciation, gen_endorsements_for_denunciation,
@@ -677,6 +684,37 @@ mod test {
         assert_eq!(block_header_1, block_header_der);
     }
 
+    #[test]
+    fn test_truncated_genesis_block_header() {
+        let genesis_header = BlockHeader {
+            current_version: 0,
+            announced_version: None,
+            slot: Slot::new(0, 1),
+            parents: Vec::new(),
+            operation_merkle_root: Hash::compute_from("mno".as_bytes()),
+            endorsements: Vec::new(),
+            denunciations: Vec::new(),
+        };
+        let mut buffer = Vec::new();
+        BlockHeaderSerializer::new()
+            .serialize(&genesis_header, &mut buffer)
+            .unwrap();
+        let der = BlockHeaderDeserializer::new(
+            THREAD_COUNT,
+            ENDORSEMENT_COUNT,
+            MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
+            Some(0),
+        );
+
+        let (rem, header_der) = der.deserialize::<DeserializeError>(&buffer).unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(genesis_header, header_der);
+        // the missing endorsement and denunciation lengths must be an error, not a panic
+        for len in 0..buffer.len() {
+            assert!(der.deserialize::<DeserializeError>(&buffer[..len]).is_err());
+        }
+    }
+
     #[test]
     fn test_verify_sig_batch() {
         let (_slot, _keypair, secured_header_1, secured_header_2, secured_header_3) =
@@ -710,4 +748,26 @@ mod test {
         ];
         verify_signature_batch(&batch_2).unwrap();
     }
+
+    #[test]
+    fn test_signed_hash_cached() {
+        let (_slot, _keypair, secured_header_1, _secured_header_2, _secured_header_3) =
+            gen_block_headers_for_denunciation(None, None);
+        let not_computed = secured_header_1.clone();
+
+        let signed_hash = secured_header_1.compute_signed_hash();
+        assert_eq!(
+            signed_hash,
+            secured_header_1.content.compute_signed_hash(
+                &secured_header_1.content_creator_pub_key,
+                secured_header_1.id.get_hash()
+            )
+        );
+        assert_eq!(secured_header_1.compute_signed_hash(), signed_hash);
+        secured_header_1.verify_signature().unwrap();
+
+        // the cached hash does not take part in comparisons
+        assert_eq!(not_computed, secured_header_1);
+        assert_eq!(not_computed.compute_signed_hash(), signed_hash);
+    }
 }
@@ -4,6 +4,7 @@ use massa_serialization::{
 };
 use nom::error::{ContextError, ParseError};
 use nom::IResult;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::ops::Bound::Included;
 
@@ -11,7 +12,7 @@ use std::ops::Bound::Included;
 pub const BYTECODE_VERSION: u64 = 0;
 
 /// Structure representing executable bytecode
-#[derive(Default, Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize, Eq, PartialEq, JsonSchema)]
 pub struct Bytecode(pub Vec<u8>);
 
 /// Serializer for `Bytecode`
@@ -15,6 +15,7 @@ use nom::error::{ContextError, ParseError};
 use nom::multi::length_count;
 use nom::sequence::tuple;
 use nom::{IResult, Parser};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::block_id::BlockId;
@@ -22,7 +23,7 @@ use crate::prehash::PreHashSet;
 use std::ops::Bound::{Excluded, Included};
 
 /// Mutually compatible blocks in the graph
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
 pub struct Clique {
     /// the block ids of the blocks in that clique
     pub block_ids: PreHashSet<BlockId>,
@@ -1,11 +1,12 @@
 use super::*;
 use crate::amount::Amount;
 use massa_time::MassaTime;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
 /// Compact representation of key values of consensus algorithm used in API
-#[derive(Debug, Deserialize, Serialize, Clone, Copy)]
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, JsonSchema)]
 pub struct CompactConfig {
     /// Time in milliseconds when the blockclique started.
     pub genesis_timestamp: MassaTime,
@@ -47,25 +47,27 @@ pub const HANDSHAKE_RANDOMNESS_SIZE_BYTES: usize = 32;
 pub const CHANNEL_SIZE: usize = 1024;
 
 lazy_static::lazy_static! {
-    /// Time in milliseconds when the blockclique started.
+    /// Genesis timestamp set with the --genesis-timestamp argument in CLI or the `GENESIS_TIMESTAMP`
+    /// environment variable, to start an ephemeral network, see [`parse_genesis_timestamp`]
+    pub static ref GENESIS_TIMESTAMP_OVERRIDE: Option<MassaTime> = get_genesis_timestamp_from_args()
+        .map(|timestamp| parse_genesis_timestamp(&timestamp, MassaTime::now().unwrap()).expect("invalid genesis timestamp"));
+
+    /// Time in milliseconds when the blockclique started, unless overridden by `GENESIS_TIMESTAMP_OVERRIDE`.
     /// In sandbox mode, the value depends on starting time and on the --restart-from-snapshot-at-period argument in CLI,
-    /// so that the network starts or restarts 10 seconds after launch
-    pub static ref GENESIS_TIMESTAMP: MassaTime = if cfg!(feature = "sandbox") {
-        std::env::var("GENESIS_TIMESTAMP").map(|timestamp| MassaTime::from_millis(timestamp.parse::<u64>().unwrap())).unwrap_or_else(|_|
-            MassaTime::now()
-                .unwrap()
-                .saturating_sub(
-                    T0.checked_mul(get_period_from_args()).unwrap()
-                )
-                .saturating_add(MassaTime::from_millis(1000 * 10)
+    /// so that the network starts or restarts 10 seconds after launch.
+    pub static ref GENESIS_TIMESTAMP: MassaTime = GENESIS_TIMESTAMP_OVERRIDE.unwrap_or_else(|| if cfg!(feature = "sandbox") {
+        MassaTime::now()
+            .unwrap()
+            .saturating_sub(
+                T0.checked_mul(get_period_from_args()).unwrap()
             )
-        )
+            .saturating_add(MassaTime::from_millis(1000 * 10))
     } else {
         MassaTime::from_millis(1688490000000) // Tuesday, July 4, 2023 05:00:00 PM UTC
-    };
+    });
 
-    /// TESTNET: time when the blockclique is ended.
-    pub static ref END_TIMESTAMP: Option<MassaTime> = if cfg!(feature = "sandbox") {
+    /// TESTNET: time when the blockclique is ended. A network with an overridden genesis timestamp does not end.
+    pub static ref END_TIMESTAMP: Option<MassaTime> = if cfg!(feature = "sandbox") || GENESIS_TIMESTAMP_OVERRIDE.is_some() {
         None
     } else {
         Some(MassaTime::from_millis(1690808400000))  // Monday, July 31, 2023 03:00:00 PM UTC
@@ -101,6 +103,55 @@ pub fn get_period_from_args() -> u64 {
     last_start_period
 }
 
+/// Helper function to read the genesis timestamp override for lazy_static evaluations:
+/// the --genesis-timestamp argument in CLI, as `--genesis-timestamp <value>` or `--genesis-timestamp=<value>`,
+/// or else the `GENESIS_TIMESTAMP` environment variable
+pub fn get_genesis_timestamp_from_args() -> Option<String> {
+    let mut parse_next = false;
+    for args in std::env::args() {
+        if parse_next {
+            return Some(args);
+        }
+        if let Some(value) = args.strip_prefix("--genesis-timestamp=") {
+            return Some(value.to_string());
+        }
+        parse_next = args == *"--genesis-timestamp";
+    }
+    std::env::var("GENESIS_TIMESTAMP").ok()
+}
+
+/// Parse a genesis timestamp override: either a timestamp in milliseconds, or `+N` to start an
+/// ephemeral network right away. `+N` is the first multiple of `N` seconds since the Unix epoch
+/// that is at least `N` seconds after `now`, so that the nodes of a cluster launched within the
+/// same window of `N` seconds get the same genesis timestamp.
+///
+/// ```
+/// use massa_models::config::constants::parse_genesis_timestamp;
+/// use massa_time::MassaTime;
+///
+/// let now = MassaTime::from_millis(1_000_000);
+/// assert_eq!(parse_genesis_timestamp("1688490000000", now), Some(MassaTime::from_millis(1688490000000)));
+/// assert_eq!(parse_genesis_timestamp("+30", now), Some(MassaTime::from_millis(1_050_000)));
+/// assert_eq!(parse_genesis_timestamp("+30", MassaTime::from_millis(1_020_000)), Some(MassaTime::from_millis(1_050_000)));
+/// assert_eq!(parse_genesis_timestamp("+30", MassaTime::from_millis(1_020_001)), Some(MassaTime::from_millis(1_080_000)));
+/// assert_eq!(parse_genesis_timestamp("+0", now), None);
+/// assert_eq!(parse_genesis_timestamp("soon", now), None);
+/// ```
+pub fn parse_genesis_timestamp(value: &str, now: MassaTime) -> Option<MassaTime> {
+    match value.strip_prefix('+') {
+        Some(delay) => {
+            let window = u64::from_str(delay).ok()?.checked_mul(1000)?;
+            if window == 0 {
+                return None;
+            }
+            let earliest = now.to_millis().checked_add(window)?;
+            let aligned = earliest.checked_add(window - 1)? / window * window;
+            Some(MassaTime::from_millis(aligned))
+        }
+        None => u64::from_str(value).ok().map(MassaTime::from_millis),
+    }
+}
+
 /// Price of a roll in the network
 pub const ROLL_PRICE: Amount = Amount::const_init(100, 0);
 /// Block reward is given for each block creation
@@ -294,6 +345,20 @@ pub const MAX_PEERS_IN_ANNOUNCEMENT_LIST: u64 = 100;
 /// Maximum number of listeners for a peer
 pub const MAX_LISTENERS_PER_PEER: u64 = 100;
 //
+// Estimated sizes in memory, used to report the memory used by the caches and to fit them in the memory budget
+//
+/// Estimated size in memory of a block with its operations
+pub const BLOCK_MEMORY_SIZE_ESTIMATE: u64 = MAX_BLOCK_SIZE as u64;
+/// Estimated size in memory of a block header with its endorsements
+pub const HEADER_MEMORY_SIZE_ESTIMATE: u64 = 8_192;
+/// Estimated size in memory of an operation
+pub const OPERATION_MEMORY_SIZE_ESTIMATE: u64 =
+    MAX_BLOCK_SIZE as u64 / MAX_OPERATIONS_PER_BLOCK as u64;
+/// Estimated size in memory of an endorsement
+pub const ENDORSEMENT_MEMORY_SIZE_ESTIMATE: u64 = 256;
+/// Estimated size in memory of an id in a deduplication cache
+pub const ID_MEMORY_SIZE_ESTIMATE: u64 = 64;
+//
 // Constants used in versioning
 //
 /// Threshold to accept a new versioning
@@ -39,6 +39,7 @@ use nom::{
     IResult, Parser,
 };
 use num_enum::{IntoPrimitive, TryFromPrimitive};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -57,7 +58,7 @@ use massa_signature::{
 
 /// A Variant of Denunciation enum for endorsement
 #[allow(dead_code)]
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct EndorsementDenunciation {
     public_key: PublicKey,
     slot: Slot,
@@ -90,7 +91,7 @@ impl EndorsementDenunciation {
 
 /// A Variant of Denunciation enum for block header
 #[allow(dead_code)]
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct BlockHeaderDenunciation {
     public_key: PublicKey,
     slot: Slot,
@@ -120,7 +121,7 @@ impl BlockHeaderDenunciation {
 }
 
 /// A denunciation enum
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[allow(missing_docs)]
 pub enum Denunciation {
     Endorsement(EndorsementDenunciation),
@@ -745,7 +746,7 @@ impl Deserializer<Denunciation> for DenunciationDeserializer {
 
 // Denunciation Index
 
-#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 /// Index for Denunciations in collections (e.g. like a HashMap...)
 pub enum DenunciationIndex {
     /// Variant for Block header denunciation index
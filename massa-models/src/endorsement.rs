@@ -17,6 +17,7 @@ use nom::{
     error::{ContextError, ParseError},
     IResult,
 };
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_with::{DeserializeFromStr, SerializeDisplay};
 use std::ops::Bound::{Excluded, Included};
@@ -135,7 +136,7 @@ impl Display for Endorsement {
 }
 
 /// an endorsement, as sent in the network
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 pub struct Endorsement {
     /// Slot in which the endorsement can be included
     pub slot: Slot,
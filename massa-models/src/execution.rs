@@ -3,10 +3,11 @@
 use crate::amount::Amount;
 use crate::{address::Address, operation::OperationId, slot::Slot};
 use massa_time::MassaTime;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// filter used when retrieving SC output events
-#[derive(Default, Debug, Deserialize, Clone, Serialize)]
+#[derive(Default, Debug, Deserialize, Clone, Serialize, JsonSchema)]
 pub struct EventFilter {
     /// optional start slot
     pub start: Option<Slot>,
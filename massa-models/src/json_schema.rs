@@ -0,0 +1,52 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use crate::{
+    address::Address, amount::Amount, block_id::BlockId, endorsement::EndorsementId, node::NodeId,
+    operation::OperationId, version::Version,
+};
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{InstanceType, Metadata, Schema, SchemaObject};
+use schemars::JsonSchema;
+
+/// Implements `JsonSchema` for a type whose serde representation is its `Display` string
+macro_rules! string_json_schema {
+    ($type:ty, $name:literal, $description:literal) => {
+        impl JsonSchema for $type {
+            fn schema_name() -> String {
+                $name.to_string()
+            }
+
+            fn json_schema(_: &mut SchemaGenerator) -> Schema {
+                SchemaObject {
+                    instance_type: Some(InstanceType::String.into()),
+                    metadata: Some(Box::new(Metadata {
+                        description: Some($description.to_string()),
+                        ..Default::default()
+                    })),
+                    ..Default::default()
+                }
+                .into()
+            }
+        }
+    };
+}
+
+string_json_schema!(
+    Address,
+    "Address",
+    "Address, prefixed by AU (user) or AS (smart contract)"
+);
+string_json_schema!(Amount, "Amount", "Decimal amount of coins");
+string_json_schema!(BlockId, "BlockId", "Block id, prefixed by B");
+string_json_schema!(
+    EndorsementId,
+    "EndorsementId",
+    "Endorsement id, prefixed by E"
+);
+string_json_schema!(NodeId, "NodeId", "Node id, prefixed by N");
+string_json_schema!(OperationId, "OperationId", "Operation id, prefixed by O");
+string_json_schema!(
+    Version,
+    "Version",
+    "Node version, formatted as INSTANCE.MAJOR.MINOR"
+);
@@ -40,6 +40,8 @@ pub mod endorsement;
 pub mod error;
 /// execution related structures
 pub mod execution;
+/// JSON schema of the models serialized as strings
+pub mod json_schema;
 /// ledger related structures
 pub mod ledger;
 /// mapping grpc
@@ -72,3 +74,6 @@ pub mod version;
 /// Test utils
 #[cfg(feature = "testing")]
 pub mod test_exports;
+
+#[cfg(feature = "benchmarking")]
+use criterion as _;
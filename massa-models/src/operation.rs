@@ -28,6 +28,7 @@ use nom::{
     IResult,
 };
 use num_enum::{IntoPrimitive, TryFromPrimitive};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DeserializeFromStr, SerializeDisplay};
 use std::convert::TryInto;
@@ -243,7 +244,7 @@ enum OperationTypeId {
 }
 
 /// the operation as sent in the network
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 // Only for unit test, otherwise, comparison should be made between OperationId
 #[cfg_attr(test, derive(PartialEq))]
 pub struct Operation {
@@ -423,7 +424,7 @@ impl Deserializer<Operation> for OperationDeserializer {
 
 /// Type specific operation content
 #[serde_as]
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub enum OperationType {
     /// transfer coins from sender to recipient
     Transaction {
@@ -452,6 +453,7 @@ pub enum OperationType {
         max_coins: Amount,
         /// A key-value store associating a hash to arbitrary bytes
         #[serde_as(as = "Vec<(_, _)>")]
+        #[schemars(with = "Vec<(Vec<u8>, Vec<u8>)>")]
         datastore: Datastore,
     },
     /// Calls an exported function from a stored smart contract
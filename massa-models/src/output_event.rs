@@ -1,8 +1,9 @@
 use crate::{address::Address, block_id::BlockId, operation::OperationId, slot::Slot};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::{collections::VecDeque, fmt::Display};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 /// By product of a byte code execution
 pub struct SCOutputEvent {
     /// context generated by the execution context
@@ -19,7 +20,7 @@ impl Display for SCOutputEvent {
 }
 
 /// Context of the event (not generated by the user)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct EventExecutionContext {
     /// when was it generated
     pub slot: Slot,
@@ -1,4 +1,4 @@
-use std::fmt::Display;
+use std::{fmt::Display, sync::OnceLock};
 
 use crate::{address::Address, error::ModelsError};
 use massa_hash::Hash;
@@ -11,13 +11,14 @@ use nom::{
     sequence::tuple,
     IResult,
 };
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Packages type T such that it can be securely sent and received in a trust-free network
 ///
 /// If the internal content is mutated, then it must be re-wrapped, as the assosciated
 /// signature, serialized data, etc. would no longer be in sync
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 pub struct SecureShare<T, ID>
 where
     T: Display + SecureShareContent,
@@ -40,8 +41,31 @@ where
     pub content_creator_address: Address,
     /// A secure hash of the data. See also [massa_hash::Hash]
     pub id: ID,
+    #[serde(skip)]
+    /// Hash signed by the creator, derived from `id` on first use. Leave it empty when building the structure.
+    pub signed_hash: CachedHash,
+}
+
+/// Hash computed on first use and kept afterwards.
+/// It is derived from the other fields of its structure so it is ignored when comparing structures.
+#[derive(Debug, Clone, Default)]
+pub struct CachedHash(OnceLock<Hash>);
+
+impl CachedHash {
+    /// Get the hash, computing it with `compute` if it is not known yet
+    pub fn get_or_compute<F: FnOnce() -> Hash>(&self, compute: F) -> Hash {
+        *self.0.get_or_init(compute)
+    }
 }
 
+impl PartialEq for CachedHash {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for CachedHash {}
+
 /// Used by signed structure
 pub trait Id {
     /// New id from hash
@@ -80,18 +104,36 @@ where
         content_serializer: Ser,
         keypair: &KeyPair,
     ) -> Result<SecureShare<Self, ID>, ModelsError> {
+        self.new_verifiable_with_signer(content_serializer, keypair.get_public_key(), |hash| {
+            Ok(keypair.sign(hash)?)
+        })
+    }
+
+    /// Same as `new_verifiable`, but the signature is produced by `signer` from the hash to sign,
+    /// which allows the secret key of `public_key` to be held outside of the node.
+    fn new_verifiable_with_signer<Ser, ID, F>(
+        self,
+        content_serializer: Ser,
+        public_key: PublicKey,
+        signer: F,
+    ) -> Result<SecureShare<Self, ID>, ModelsError>
+    where
+        Ser: Serializer<Self>,
+        ID: Id,
+        F: FnOnce(&Hash) -> Result<Signature, ModelsError>,
+    {
         let mut content_serialized = Vec::new();
         content_serializer.serialize(&self, &mut content_serialized)?;
-        let public_key = keypair.get_public_key();
         let hash = Self::compute_hash(&self, &content_serialized, &public_key);
         let creator_address = Address::from_public_key(&public_key);
         Ok(SecureShare {
-            signature: self.sign(keypair, &hash)?,
+            signature: signer(&self.compute_signed_hash(&public_key, &hash))?,
             content_creator_pub_key: public_key,
             content_creator_address: creator_address,
             content: self,
             serialized_data: content_serialized,
             id: ID::new(hash),
+            signed_hash: Default::default(),
         })
     }
 
@@ -173,6 +215,7 @@ where
                 content_creator_address: creator_address,
                 serialized_data: content_serialized.to_vec(),
                 id: ID::new(hash),
+                signed_hash: Default::default(),
             },
         ))
     }
@@ -209,17 +252,17 @@ where
 
     /// check if self has been signed by public key
     pub fn verify_signature(&self) -> Result<(), ModelsError> {
-        self.content.verify_signature(
-            &self.content_creator_pub_key,
-            self.id.get_hash(),
-            &self.signature,
-        )
+        Ok(self
+            .content_creator_pub_key
+            .verify_signature(&self.compute_signed_hash(), &self.signature)?)
     }
 
-    /// Compute the signed hash
+    /// Compute the signed hash, only once: the result is kept for the next calls
     pub fn compute_signed_hash(&self) -> Hash {
-        self.content
-            .compute_signed_hash(&self.content_creator_pub_key, self.id.get_hash())
+        self.signed_hash.get_or_compute(|| {
+            self.content
+                .compute_signed_hash(&self.content_creator_pub_key, self.id.get_hash())
+        })
     }
 
     /// get full serialized size
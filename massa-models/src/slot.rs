@@ -6,13 +6,14 @@ use massa_serialization::{
     Deserializer, SerializeError, Serializer, U64VarIntDeserializer, U64VarIntSerializer,
 };
 use nom::error::{context, ContextError, ParseError};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::ops::{Bound, RangeBounds};
 use std::str::FromStr;
 use std::{cmp::Ordering, convert::TryInto};
 
 /// a point in time where a block is expected
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct Slot {
     /// period
     pub period: u64,
@@ -330,8 +331,98 @@ impl Slot {
     }
 }
 
+/// Iterator over a range of consecutive slots, in increasing order (or decreasing order with `rev`)
+///
+/// ## Example
+/// ```rust
+/// # use massa_models::slot::{Slot, SlotRange};
+/// let slots: Vec<Slot> = SlotRange::new(Slot::new(10, 2), Slot::new(11, 1), 3).collect();
+/// assert_eq!(slots, vec![Slot::new(10, 2), Slot::new(11, 0)]);
+/// assert_eq!(SlotRange::new(Slot::new(10, 2), Slot::new(11, 1), 3).len(), 2);
+/// assert_eq!(SlotRange::new(Slot::new(11, 1), Slot::new(10, 2), 3).count(), 0);
+/// let slots: Vec<Slot> = SlotRange::new_inclusive(Slot::new(10, 2), Slot::new(11, 1), 3)
+///     .rev()
+///     .collect();
+/// assert_eq!(slots, vec![Slot::new(11, 1), Slot::new(11, 0), Slot::new(10, 2)]);
+/// assert_eq!(SlotRange::new(Slot::new(0, 0), Slot::new(0, 0), 3).count(), 0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SlotRange {
+    /// first slot not yet returned
+    front: Option<Slot>,
+    /// last slot not yet returned
+    back: Option<Slot>,
+    thread_count: u8,
+}
+
+impl SlotRange {
+    /// Range of the slots from `start` included to `end` excluded
+    pub fn new(start: Slot, end: Slot, thread_count: u8) -> Self {
+        SlotRange {
+            front: Some(start),
+            back: end.get_prev_slot(thread_count).ok(),
+            thread_count,
+        }
+    }
+
+    /// Range of the slots from `start` to `last`, both included
+    pub fn new_inclusive(start: Slot, last: Slot, thread_count: u8) -> Self {
+        SlotRange {
+            front: Some(start),
+            back: Some(last),
+            thread_count,
+        }
+    }
+
+    /// The next slots to return from the front and from the back, if any is left
+    fn bounds(&self) -> Option<(Slot, Slot)> {
+        match (self.front, self.back) {
+            (Some(front), Some(back)) if front <= back => Some((front, back)),
+            _ => None,
+        }
+    }
+}
+
+impl Iterator for SlotRange {
+    type Item = Slot;
+
+    fn next(&mut self) -> Option<Slot> {
+        let (front, back) = self.bounds()?;
+        if front == back {
+            self.front = None;
+        } else {
+            self.front = front.get_next_slot(self.thread_count).ok();
+        }
+        Some(front)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self
+            .bounds()
+            .and_then(|(front, back)| back.slots_since(&front, self.thread_count).ok())
+            .map_or(0, |count| {
+                count.saturating_add(1).try_into().unwrap_or(usize::MAX)
+            });
+        (remaining, Some(remaining))
+    }
+}
+
+impl DoubleEndedIterator for SlotRange {
+    fn next_back(&mut self) -> Option<Slot> {
+        let (front, back) = self.bounds()?;
+        if front == back {
+            self.back = None;
+        } else {
+            self.back = back.get_prev_slot(self.thread_count).ok();
+        }
+        Some(back)
+    }
+}
+
+impl ExactSizeIterator for SlotRange {}
+
 /// When an address is drawn to create an endorsement it is selected for a specific index
-#[derive(Debug, Clone, Deserialize, Serialize, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Deserialize, Serialize, Hash, PartialEq, Eq, JsonSchema)]
 pub struct IndexedSlot {
     /// slot
     pub slot: Slot,
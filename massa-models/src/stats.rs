@@ -1,12 +1,15 @@
 //! Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use crate::slot::Slot;
+use crate::{node::NodeId, slot::Slot};
 use massa_time::MassaTime;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt::Formatter;
+use std::net::SocketAddr;
 
 /// execution statistics
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ExecutionStats {
     /// time window start
     pub time_window_start: MassaTime,
@@ -52,7 +55,7 @@ impl std::fmt::Display for ExecutionStats {
 }
 
 /// stats produced by network module
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct NetworkStats {
     /// in connections count
     pub in_connection_count: u64,
@@ -78,8 +81,121 @@ impl std::fmt::Display for NetworkStats {
     }
 }
 
+/// messages and bytes exchanged with a connected peer
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PeerStats {
+    /// node id of the peer
+    pub node_id: NodeId,
+    /// address of the connection
+    pub address: SocketAddr,
+    /// true if we opened the connection
+    pub is_outgoing: bool,
+    /// category of the peer, none for the default category
+    pub category: Option<String>,
+    /// bytes received from the peer
+    pub bytes_received: u64,
+    /// bytes sent to the peer
+    pub bytes_sent: u64,
+    /// block, header and block ask messages received
+    pub block_messages_received: u64,
+    /// endorsement messages received
+    pub endorsement_messages_received: u64,
+    /// operation messages received
+    pub operation_messages_received: u64,
+    /// peer management messages received
+    pub peer_messages_received: u64,
+    /// messages that could not be read: of an unknown type, or whose content is invalid (these are also counted by type)
+    pub invalid_messages_received: u64,
+    /// messages of a type introduced by a newer version, skipped
+    #[serde(default)]
+    pub unknown_messages_received: u64,
+    /// messages received by kind (block_header, ask_for_blocks, reply_for_blocks, operations, ...), the kinds never received are omitted
+    #[serde(default)]
+    pub messages_received_by_kind: BTreeMap<String, u64>,
+    /// messages sent
+    pub messages_sent: u64,
+    /// messages sent by kind, the kinds never sent are omitted
+    #[serde(default)]
+    pub messages_sent_by_kind: BTreeMap<String, u64>,
+    /// blocks and operations sent in answer to the asks of the peer
+    #[serde(default)]
+    pub asks_served: u64,
+    /// time of the last message received, if any
+    pub last_received: Option<MassaTime>,
+    /// time of the last message sent, if any
+    pub last_sent: Option<MassaTime>,
+    /// the peer announced different network parameters (t0, thread count, genesis timestamp, max block size)
+    #[serde(default)]
+    pub config_mismatch: bool,
+}
+
+fn format_by_kind(counts: &BTreeMap<String, u64>) -> String {
+    counts
+        .iter()
+        .map(|(kind, count)| format!("{}={}", kind, count))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl std::fmt::Display for PeerStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Peer {} ({}, {}{}):",
+            self.node_id,
+            self.address,
+            if self.is_outgoing { "out" } else { "in" },
+            self.category
+                .as_ref()
+                .map(|category| format!(", {}", category))
+                .unwrap_or_default()
+        )?;
+        writeln!(
+            f,
+            "\tBytes: received={}, sent={}",
+            self.bytes_received, self.bytes_sent
+        )?;
+        writeln!(
+            f,
+            "\tMessages received: blocks={}, endorsements={}, operations={}, peers={}, invalid={}, unknown={}",
+            self.block_messages_received,
+            self.endorsement_messages_received,
+            self.operation_messages_received,
+            self.peer_messages_received,
+            self.invalid_messages_received,
+            self.unknown_messages_received
+        )?;
+        if !self.messages_received_by_kind.is_empty() {
+            writeln!(
+                f,
+                "\tMessages received by kind: {}",
+                format_by_kind(&self.messages_received_by_kind)
+            )?;
+        }
+        writeln!(f, "\tMessages sent: {}", self.messages_sent)?;
+        if !self.messages_sent_by_kind.is_empty() {
+            writeln!(
+                f,
+                "\tMessages sent by kind: {}",
+                format_by_kind(&self.messages_sent_by_kind)
+            )?;
+        }
+        writeln!(f, "\tBlocks and operations served: {}", self.asks_served)?;
+        if let Some(last_received) = self.last_received {
+            writeln!(f, "\tLast received: {}", last_received.format_instant())?;
+        }
+        if let Some(last_sent) = self.last_sent {
+            writeln!(f, "\tLast sent: {}", last_sent.format_instant())?;
+        }
+        if self.config_mismatch {
+            writeln!(f, "\tWARNING: runs with different network parameters")?;
+        }
+        Ok(())
+    }
+}
+
 /// stats produced by consensus module
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ConsensusStats {
     /// start of the time span for stats
     pub start_timespan: MassaTime,
@@ -91,6 +207,9 @@ pub struct ConsensusStats {
     pub stale_block_count: u64,
     ///  number of actives cliques
     pub clique_count: u64,
+    /// a network partition is suspected: no block became final recently while most of the new
+    /// blocks were created by this node, it is probably on a minority fork and its data may be stale
+    pub partition_suspected: bool,
 }
 
 impl std::fmt::Display for ConsensusStats {
@@ -109,6 +228,12 @@ impl std::fmt::Display for ConsensusStats {
         writeln!(f, "\tFinal block count: {}", self.final_block_count)?;
         writeln!(f, "\tStale block count: {}", self.stale_block_count)?;
         writeln!(f, "\tClique count: {}", self.clique_count)?;
+        if self.partition_suspected {
+            writeln!(
+                f,
+                "\tNetwork partition suspected: the data of the node may be stale"
+            )?;
+        }
         Ok(())
     }
 }
@@ -130,3 +255,193 @@ impl std::fmt::Display for PoolStats {
         Ok(())
     }
 }
+
+/// memory used by a bounded in-memory cache
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema)]
+pub struct CacheMemoryStats {
+    /// number of entries in the cache
+    pub entry_count: u64,
+    /// number of entries above which the cache evicts its oldest entries, 0 if not bounded
+    pub max_entry_count: u64,
+    /// estimated memory used by the entries, in bytes
+    pub estimated_bytes: u64,
+}
+
+impl CacheMemoryStats {
+    /// Stats of a cache holding `entry_count` entries of about `entry_size` bytes each
+    pub fn new(entry_count: usize, max_entry_count: usize, entry_size: u64) -> Self {
+        CacheMemoryStats {
+            entry_count: entry_count as u64,
+            max_entry_count: max_entry_count as u64,
+            estimated_bytes: (entry_count as u64).saturating_mul(entry_size),
+        }
+    }
+}
+
+impl std::fmt::Display for CacheMemoryStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.max_entry_count == 0 {
+            return write!(
+                f,
+                "{} entries, ~{} KiB",
+                self.entry_count,
+                self.estimated_bytes / 1024
+            );
+        }
+        write!(
+            f,
+            "{}/{} entries, ~{} KiB",
+            self.entry_count,
+            self.max_entry_count,
+            self.estimated_bytes / 1024
+        )
+    }
+}
+
+/// memory used by the block graph and the caches of the consensus module
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ConsensusMemoryStats {
+    /// blocks of the graph, kept until they are pruned
+    pub active_blocks: CacheMemoryStats,
+    /// blocks waiting for their slot
+    pub future_blocks: CacheMemoryStats,
+    /// blocks waiting for their dependencies
+    pub dependency_blocks: CacheMemoryStats,
+    /// discarded blocks kept to ignore them if they are received again
+    pub discarded_blocks: CacheMemoryStats,
+}
+
+impl ConsensusMemoryStats {
+    /// estimated memory used by the graph and all the caches, in bytes
+    pub fn estimated_bytes(&self) -> u64 {
+        self.active_blocks
+            .estimated_bytes
+            .saturating_add(self.future_blocks.estimated_bytes)
+            .saturating_add(self.dependency_blocks.estimated_bytes)
+            .saturating_add(self.discarded_blocks.estimated_bytes)
+    }
+}
+
+impl std::fmt::Display for ConsensusMemoryStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Consensus memory stats:")?;
+        writeln!(f, "\tActive blocks: {}", self.active_blocks)?;
+        writeln!(f, "\tFuture blocks: {}", self.future_blocks)?;
+        writeln!(f, "\tDependency waiting blocks: {}", self.dependency_blocks)?;
+        writeln!(f, "\tDiscarded blocks: {}", self.discarded_blocks)?;
+        Ok(())
+    }
+}
+
+/// memory used by the deduplication caches of the protocol module
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ProtocolMemoryStats {
+    /// headers of the blocks already checked
+    pub known_blocks: CacheMemoryStats,
+    /// ids of the operations already checked
+    pub known_operations: CacheMemoryStats,
+    /// ids of the endorsements already checked
+    pub known_endorsements: CacheMemoryStats,
+}
+
+impl ProtocolMemoryStats {
+    /// estimated memory used by all the caches, in bytes
+    pub fn estimated_bytes(&self) -> u64 {
+        self.known_blocks
+            .estimated_bytes
+            .saturating_add(self.known_operations.estimated_bytes)
+            .saturating_add(self.known_endorsements.estimated_bytes)
+    }
+}
+
+impl std::fmt::Display for ProtocolMemoryStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Protocol memory stats:")?;
+        writeln!(f, "\tKnown blocks: {}", self.known_blocks)?;
+        writeln!(f, "\tKnown operations: {}", self.known_operations)?;
+        writeln!(f, "\tKnown endorsements: {}", self.known_endorsements)?;
+        Ok(())
+    }
+}
+
+/// memory used by the pool module
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct PoolMemoryStats {
+    /// operations of the pool
+    pub operations: CacheMemoryStats,
+    /// endorsements of the pool
+    pub endorsements: CacheMemoryStats,
+}
+
+impl PoolMemoryStats {
+    /// estimated memory used by the pool, in bytes
+    pub fn estimated_bytes(&self) -> u64 {
+        self.operations
+            .estimated_bytes
+            .saturating_add(self.endorsements.estimated_bytes)
+    }
+}
+
+impl std::fmt::Display for PoolMemoryStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Pool memory stats:")?;
+        writeln!(f, "\tOperations: {}", self.operations)?;
+        writeln!(f, "\tEndorsements: {}", self.endorsements)?;
+        Ok(())
+    }
+}
+
+/// memory used by the bounded in-memory caches of the node
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MemoryStats {
+    /// memory budget of the caches in bytes, if any
+    pub budget_bytes: Option<u64>,
+    /// estimated memory used by all the caches, in bytes
+    pub estimated_bytes: u64,
+    /// consensus caches
+    pub consensus: ConsensusMemoryStats,
+    /// protocol caches
+    pub protocol: ProtocolMemoryStats,
+    /// pool
+    pub pool: PoolMemoryStats,
+}
+
+impl MemoryStats {
+    /// Aggregate the stats of the modules
+    pub fn new(
+        budget_bytes: Option<u64>,
+        consensus: ConsensusMemoryStats,
+        protocol: ProtocolMemoryStats,
+        pool: PoolMemoryStats,
+    ) -> Self {
+        MemoryStats {
+            budget_bytes,
+            estimated_bytes: consensus
+                .estimated_bytes()
+                .saturating_add(protocol.estimated_bytes())
+                .saturating_add(pool.estimated_bytes()),
+            consensus,
+            protocol,
+            pool,
+        }
+    }
+}
+
+impl std::fmt::Display for MemoryStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Memory stats:")?;
+        match self.budget_bytes {
+            Some(budget) => writeln!(f, "\tBudget: {} MiB", budget / (1024 * 1024))?,
+            None => writeln!(f, "\tBudget: none")?,
+        }
+        writeln!(
+            f,
+            "\tEstimated usage: {} MiB",
+            self.estimated_bytes / (1024 * 1024)
+        )?;
+        write!(f, "{}", self.consensus)?;
+        write!(f, "{}", self.protocol)?;
+        write!(f, "{}", self.pool)?;
+        Ok(())
+    }
+}
@@ -6,10 +6,11 @@ use nom::{
     error::{context, ContextError, ParseError},
     IResult, Parser,
 };
+use serde::{Deserialize, Serialize};
 use std::{marker::PhantomData, ops::Bound::Included};
 
 /// Streaming step cursor
-#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum StreamingStep<T> {
     /// Started step, only when launching the streaming
     Started,
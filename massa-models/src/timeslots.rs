@@ -92,6 +92,37 @@ pub fn get_current_latest_block_slot(
     get_latest_block_slot_at_timestamp(thread_count, t0, genesis_timestamp, MassaTime::now()?)
 }
 
+/// Returns the first slot happening at or after a given timestamp
+///
+/// # Arguments
+/// * `thread_count`: number of threads.
+/// * `t0`: time in milliseconds between two periods in the same thread.
+/// * `genesis_timestamp`: when the blockclique first started, in milliseconds.
+/// * `timestamp`: target timestamp in milliseconds.
+pub fn get_first_block_slot_from_timestamp(
+    thread_count: u8,
+    t0: MassaTime,
+    genesis_timestamp: MassaTime,
+    timestamp: MassaTime,
+) -> Result<Slot, ModelsError> {
+    let inter_slot = t0.checked_div_u64(thread_count as u64)?;
+    let slot_number: u64 = timestamp
+        .saturating_sub(genesis_timestamp)
+        .checked_add(inter_slot)?
+        .saturating_sub(MassaTime::EPSILON)
+        .checked_div_time(inter_slot)?;
+    Ok(Slot::new(
+        slot_number
+            .checked_div(thread_count as u64)
+            .ok_or(ModelsError::TimeOverflowError)?,
+        slot_number
+            .checked_rem(thread_count as u64)
+            .ok_or(ModelsError::TimeOverflowError)?
+            .try_into()
+            .map_err(|_| ModelsError::ThreadOverflowError)?,
+    ))
+}
+
 /// Turns an `MassaTime` range [start, end) with optional start/end to a `Slot` range [start, end) with optional start/end
 ///
 /// # Arguments
@@ -110,50 +141,12 @@ pub fn time_range_to_slot_range(
     start_time: Option<MassaTime>,
     end_time: Option<MassaTime>,
 ) -> Result<(Option<Slot>, Option<Slot>), ModelsError> {
-    let start_slot = match start_time {
-        None => None,
-        Some(t) => {
-            let inter_slot = t0.checked_div_u64(thread_count as u64)?;
-            let slot_number: u64 = t
-                .saturating_sub(genesis_timestamp)
-                .checked_add(inter_slot)?
-                .saturating_sub(MassaTime::EPSILON)
-                .checked_div_time(inter_slot)?;
-            Some(Slot::new(
-                slot_number
-                    .checked_div(thread_count as u64)
-                    .ok_or(ModelsError::TimeOverflowError)?,
-                slot_number
-                    .checked_rem(thread_count as u64)
-                    .ok_or(ModelsError::TimeOverflowError)?
-                    .try_into()
-                    .map_err(|_| ModelsError::ThreadOverflowError)?,
-            ))
-        }
-    };
-
-    let end_slot = match end_time {
-        None => None,
-        Some(t) => {
-            let inter_slot = t0.checked_div_u64(thread_count as u64)?;
-            let slot_number: u64 = t
-                .saturating_sub(genesis_timestamp)
-                .checked_add(inter_slot)?
-                .saturating_sub(MassaTime::EPSILON)
-                .checked_div_time(inter_slot)?;
-            Some(Slot::new(
-                slot_number
-                    .checked_div(thread_count as u64)
-                    .ok_or(ModelsError::TimeOverflowError)?,
-                slot_number
-                    .checked_rem(thread_count as u64)
-                    .ok_or(ModelsError::TimeOverflowError)?
-                    .try_into()
-                    .map_err(|_| ModelsError::ThreadOverflowError)?,
-            ))
-        }
-    };
-
+    let start_slot = start_time
+        .map(|t| get_first_block_slot_from_timestamp(thread_count, t0, genesis_timestamp, t))
+        .transpose()?;
+    let end_slot = end_time
+        .map(|t| get_first_block_slot_from_timestamp(thread_count, t0, genesis_timestamp, t))
+        .transpose()?;
     Ok((start_slot, end_slot))
 }
 
@@ -203,6 +196,7 @@ pub fn get_closest_slot_to_timestamp(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::slot::SlotRange;
     use serial_test::serial;
 
     #[test]
@@ -283,6 +277,40 @@ mod tests {
         assert_eq!(out_end, Some(Slot::new(2, 0)));
     }
 
+    #[test]
+    #[serial]
+    fn test_slot_timestamp_round_trip() {
+        let thread_count = 3u8;
+        let t0: MassaTime = MassaTime::from_millis(30);
+        let genesis_timestamp: MassaTime = MassaTime::from_millis(100);
+        for slot in SlotRange::new(Slot::new(0, 0), Slot::new(5, 0), thread_count) {
+            let timestamp =
+                get_block_slot_timestamp(thread_count, t0, genesis_timestamp, slot).unwrap();
+            assert_eq!(
+                get_latest_block_slot_at_timestamp(thread_count, t0, genesis_timestamp, timestamp)
+                    .unwrap(),
+                Some(slot)
+            );
+            assert_eq!(
+                get_first_block_slot_from_timestamp(thread_count, t0, genesis_timestamp, timestamp)
+                    .unwrap(),
+                slot
+            );
+            // between two slots, the first slot from the timestamp is the following one
+            let next = slot.get_next_slot(thread_count).unwrap();
+            assert_eq!(
+                get_first_block_slot_from_timestamp(
+                    thread_count,
+                    t0,
+                    genesis_timestamp,
+                    timestamp.saturating_add(MassaTime::from_millis(1))
+                )
+                .unwrap(),
+                next
+            );
+        }
+    }
+
     #[test]
     #[serial]
     fn test_get_closest_slot_to_timestamp() {
@@ -0,0 +1,249 @@
+//! JSON-RPC API servers of the node, kept with the links to the other modules
+//! so that they can be started again on new addresses while the node is running.
+
+use std::net::SocketAddr;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Condvar, Mutex};
+
+use massa_api::{ApiServer, ApiV2, LogFilterSetter, Private, Public, RpcServer, StopHandle, API};
+use massa_api_exports::config::APIConfig;
+use massa_consensus_exports::{ConsensusChannels, ConsensusController};
+use massa_execution_exports::{ExecutionChannels, ExecutionController};
+use massa_models::config::VERSION;
+use massa_models::node::NodeId;
+use massa_pool_exports::{PoolChannels, PoolController};
+use massa_pos_exports::SelectorController;
+use massa_protocol_exports::{ProtocolConfig, ProtocolController};
+use massa_storage::Storage;
+use massa_time::MassaTime;
+use massa_versioning::versioning::MipStore;
+use massa_wallet::Wallet;
+use parking_lot::RwLock;
+use tracing::{error, info};
+
+use crate::settings::APISettings;
+
+/// Links to the other modules used by the API servers
+pub struct ApiComponents {
+    pub consensus_controller: Box<dyn ConsensusController>,
+    pub consensus_channels: ConsensusChannels,
+    pub execution_controller: Box<dyn ExecutionController>,
+    pub execution_channels: ExecutionChannels,
+    pub pool_controller: Box<dyn PoolController>,
+    pub pool_channels: PoolChannels,
+    pub protocol_controller: Box<dyn ProtocolController>,
+    pub protocol_config: ProtocolConfig,
+    pub selector_controller: Box<dyn SelectorController>,
+    pub storage: Storage,
+    pub node_id: NodeId,
+    pub mip_store: MipStore,
+    pub node_wallet: Arc<RwLock<Wallet>>,
+    /// pair shared with the ctrlc handler, used to stop the node
+    pub sig_int_toggled: Arc<(Mutex<bool>, Condvar)>,
+    /// set to request a reload of the configuration
+    pub reload_requested: Arc<AtomicBool>,
+    /// sets the log levels per module
+    pub log_filter: LogFilterSetter,
+    /// time at which the node started
+    pub start_time: MassaTime,
+}
+
+/// Running EXPERIMENTAL, PUBLIC and PRIVATE JsonRPC servers
+pub struct ApiServers {
+    components: ApiComponents,
+    /// configuration of the servers, with their current addresses
+    api_config: APIConfig,
+    api_handle: StopHandle,
+    api_public_handle: StopHandle,
+    api_private_handle: StopHandle,
+}
+
+impl ApiServers {
+    /// Configuration of the servers, with their current addresses
+    pub fn api_config(&self) -> &APIConfig {
+        &self.api_config
+    }
+
+    /// Start the servers on the addresses of `api_config`
+    pub async fn start(components: ApiComponents, api_config: APIConfig) -> Self {
+        let api_handle = start_api(&components, &api_config, &api_config.bind_api)
+            .await
+            .expect("failed to start MASSA API");
+        info!(
+            "API | EXPERIMENTAL JsonRPC | listening on: {}",
+            api_config.bind_api
+        );
+
+        let api_private_handle =
+            start_private_api(&components, &api_config, &api_config.bind_private)
+                .await
+                .expect("failed to start PRIVATE API");
+        info!(
+            "API | PRIVATE JsonRPC | listening on: {}",
+            api_config.bind_private
+        );
+
+        let api_public_handle = start_public_api(&components, &api_config, &api_config.bind_public)
+            .await
+            .expect("failed to start PUBLIC API");
+        info!(
+            "API | PUBLIC JsonRPC | listening on: {}",
+            api_config.bind_public
+        );
+
+        ApiServers {
+            components,
+            api_config,
+            api_handle,
+            api_public_handle,
+            api_private_handle,
+        }
+    }
+
+    /// Move the servers whose address changed in `settings` to their new address.
+    /// The new server is started before the previous one is stopped,
+    /// so a server that can't listen on its new address keeps the previous one.
+    pub async fn rebind(&mut self, settings: &APISettings) {
+        if settings.bind_api != self.api_config.bind_api {
+            match start_api(&self.components, &self.api_config, &settings.bind_api).await {
+                Ok(handle) => {
+                    info!(
+                        "API | EXPERIMENTAL JsonRPC | listening on: {}",
+                        settings.bind_api
+                    );
+                    std::mem::replace(&mut self.api_handle, handle)
+                        .stop()
+                        .await;
+                    self.api_config.bind_api = settings.bind_api;
+                }
+                Err(err) => error!(
+                    "API | EXPERIMENTAL JsonRPC | could not listen on {}, still listening on {}: {}",
+                    settings.bind_api, self.api_config.bind_api, err
+                ),
+            }
+        }
+        if settings.bind_private != self.api_config.bind_private {
+            match start_private_api(&self.components, &self.api_config, &settings.bind_private)
+                .await
+            {
+                Ok(handle) => {
+                    info!(
+                        "API | PRIVATE JsonRPC | listening on: {}",
+                        settings.bind_private
+                    );
+                    std::mem::replace(&mut self.api_private_handle, handle)
+                        .stop()
+                        .await;
+                    self.api_config.bind_private = settings.bind_private;
+                }
+                Err(err) => error!(
+                    "API | PRIVATE JsonRPC | could not listen on {}, still listening on {}: {}",
+                    settings.bind_private, self.api_config.bind_private, err
+                ),
+            }
+        }
+        if settings.bind_public != self.api_config.bind_public {
+            match start_public_api(&self.components, &self.api_config, &settings.bind_public).await
+            {
+                Ok(handle) => {
+                    info!(
+                        "API | PUBLIC JsonRPC | listening on: {}",
+                        settings.bind_public
+                    );
+                    std::mem::replace(&mut self.api_public_handle, handle)
+                        .stop()
+                        .await;
+                    self.api_config.bind_public = settings.bind_public;
+                }
+                Err(err) => error!(
+                    "API | PUBLIC JsonRPC | could not listen on {}, still listening on {}: {}",
+                    settings.bind_public, self.api_config.bind_public, err
+                ),
+            }
+        }
+    }
+
+    /// Stop all the servers
+    pub async fn stop(self) {
+        self.api_handle.stop().await;
+        info!("API | EXPERIMENTAL JsonRPC | stopped");
+
+        self.api_public_handle.stop().await;
+        info!("API | PUBLIC JsonRPC | stopped");
+
+        self.api_private_handle.stop().await;
+        info!("API | PRIVATE JsonRPC | stopped");
+    }
+}
+
+async fn start_api(
+    components: &ApiComponents,
+    api_config: &APIConfig,
+    bind: &SocketAddr,
+) -> Result<StopHandle, String> {
+    API::<ApiV2>::new(
+        components.consensus_controller.clone(),
+        components.consensus_channels.clone(),
+        components.execution_controller.clone(),
+        components.execution_channels.clone(),
+        components.pool_channels.clone(),
+        api_config.clone(),
+        *VERSION,
+    )
+    .serve(bind, api_config)
+    .await
+    .map_err(|err| err.to_string())
+}
+
+async fn start_private_api(
+    components: &ApiComponents,
+    api_config: &APIConfig,
+    bind: &SocketAddr,
+) -> Result<StopHandle, String> {
+    // WebSockets are disabled for the Private and Public API's
+    let mut api_config = api_config.clone();
+    api_config.enable_ws = false;
+    API::<Private>::new(
+        components.consensus_controller.clone(),
+        components.protocol_controller.clone(),
+        components.execution_controller.clone(),
+        components.pool_controller.clone(),
+        components.storage.clone(),
+        api_config.clone(),
+        components.sig_int_toggled.clone(),
+        components.reload_requested.clone(),
+        components.log_filter.clone(),
+        components.node_wallet.clone(),
+        *VERSION,
+        components.start_time,
+    )
+    .serve(bind, &api_config)
+    .await
+    .map_err(|err| err.to_string())
+}
+
+async fn start_public_api(
+    components: &ApiComponents,
+    api_config: &APIConfig,
+    bind: &SocketAddr,
+) -> Result<StopHandle, String> {
+    // WebSockets are disabled for the Private and Public API's
+    let mut api_config = api_config.clone();
+    api_config.enable_ws = false;
+    API::<Public>::new(
+        components.consensus_controller.clone(),
+        components.execution_controller.clone(),
+        api_config.clone(),
+        components.selector_controller.clone(),
+        components.pool_controller.clone(),
+        components.protocol_controller.clone(),
+        components.protocol_config.clone(),
+        *VERSION,
+        components.node_id,
+        components.storage.clone(),
+        components.mip_store.clone(),
+    )
+    .serve(bind, &api_config)
+    .await
+    .map_err(|err| err.to_string())
+}
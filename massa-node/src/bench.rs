@@ -0,0 +1,167 @@
+//! Benchmark of the machine, for `massa-node --bench`.
+//!
+//! Measures the hashing, signing and signature verification throughput with the same code as the node,
+//! and the write speed of the disk holding the ledger, then compares them to the load of a network
+//! producing full blocks in every thread.
+
+use std::{
+    fs,
+    io::Write,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use massa_hash::Hash;
+use massa_models::config::{
+    ENDORSEMENT_COUNT, MAX_BLOCK_SIZE, MAX_OPERATIONS_PER_BLOCK, T0, THREAD_COUNT,
+};
+use massa_signature::{verify_signature_batch, KeyPair};
+
+use crate::settings::SETTINGS;
+
+/// Duration of each measure
+const MEASURE_DURATION: Duration = Duration::from_secs(2);
+/// The machine is recommended for staking if it handles this many times the load of full blocks
+const RECOMMENDED_HEADROOM: f64 = 4.0;
+/// Size of the data hashed at once, of the order of a serialized operation
+const HASHED_DATA_SIZE: usize = 200;
+/// Number of signatures verified at once, as when a block is checked
+const VERIFICATION_BATCH_SIZE: usize = 256;
+/// Size of the file written to measure the disk speed
+const DISK_TEST_SIZE: usize = 64 * 1024 * 1024;
+
+/// Call `run` until `MEASURE_DURATION` elapsed and return the number of items processed per second.
+/// `run` returns the number of items it processed.
+fn measure(mut run: impl FnMut() -> u64) -> f64 {
+    let start = Instant::now();
+    let mut count = 0;
+    while start.elapsed() < MEASURE_DURATION {
+        count += run();
+    }
+    count as f64 / start.elapsed().as_secs_f64()
+}
+
+/// Write `DISK_TEST_SIZE` bytes in `dir` and return the speed in bytes per second
+fn measure_disk_write(dir: &Path) -> std::io::Result<f64> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join("massa-bench.tmp");
+    let data = vec![0x5Au8; 1024 * 1024];
+    let start = Instant::now();
+    let result = (|| {
+        let mut file = fs::File::create(&path)?;
+        for _ in 0..DISK_TEST_SIZE / data.len() {
+            file.write_all(&data)?;
+        }
+        file.sync_all()
+    })();
+    let elapsed = start.elapsed();
+    let _ = fs::remove_file(&path);
+    result.map(|_| DISK_TEST_SIZE as f64 / elapsed.as_secs_f64())
+}
+
+/// Print a measure and whether it meets the recommended value, returns true if it does
+fn report(name: &str, measured: f64, required: f64, unit: &str) -> bool {
+    let recommended = required * RECOMMENDED_HEADROOM;
+    let ok = measured >= recommended;
+    println!(
+        "{:<24} {:>14.0} {}  (recommended: {:.0}) {}",
+        name,
+        measured,
+        unit,
+        recommended,
+        if ok { "ok" } else { "TOO SLOW" }
+    );
+    ok
+}
+
+/// Run the benchmark and print the results.
+/// Returns an error if the machine doesn't meet the recommended staking requirements.
+pub fn run_bench() -> anyhow::Result<()> {
+    let t0_secs = T0.to_duration().as_secs_f64();
+    // load of a network producing full blocks in every thread
+    let operations_per_second = MAX_OPERATIONS_PER_BLOCK as f64 * THREAD_COUNT as f64 / t0_secs;
+    let signatures_per_second =
+        (MAX_OPERATIONS_PER_BLOCK + ENDORSEMENT_COUNT + 1) as f64 * THREAD_COUNT as f64 / t0_secs;
+    let block_bytes_per_second = MAX_BLOCK_SIZE as f64 * THREAD_COUNT as f64 / t0_secs;
+
+    println!(
+        "Measuring the machine for {} threads and a period of {}, each measure takes {} s",
+        THREAD_COUNT,
+        T0,
+        MEASURE_DURATION.as_secs()
+    );
+    let keypair = KeyPair::generate(0)?;
+    let public_key = keypair.get_public_key();
+    let mut all_ok = true;
+
+    let data = vec![0u8; HASHED_DATA_SIZE];
+    let hashes_per_second = measure(|| {
+        std::hint::black_box(Hash::compute_from(&data));
+        1
+    });
+    all_ok &= report(
+        "hashing",
+        hashes_per_second,
+        operations_per_second,
+        "hash/s",
+    );
+
+    let hash = Hash::compute_from(&data);
+    let signs_per_second = measure(|| {
+        std::hint::black_box(keypair.sign(&hash).expect("could not sign"));
+        1
+    });
+    // the node signs its own blocks and endorsements only, this is for information
+    println!("{:<24} {:>14.0} sig/s", "signing", signs_per_second);
+
+    let batch: Vec<_> = (0..VERIFICATION_BATCH_SIZE)
+        .map(|index| {
+            let hash = Hash::compute_from(&(index as u64).to_be_bytes());
+            (
+                hash,
+                keypair.sign(&hash).expect("could not sign"),
+                public_key,
+            )
+        })
+        .collect();
+    let verifications_per_second = measure(|| {
+        verify_signature_batch(&batch).expect("invalid signature in the benchmark");
+        VERIFICATION_BATCH_SIZE as u64
+    });
+    all_ok &= report(
+        "signature verification",
+        verifications_per_second,
+        signatures_per_second,
+        "sig/s",
+    );
+
+    let ledger_dir = SETTINGS
+        .ledger
+        .disk_ledger_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."));
+    match measure_disk_write(ledger_dir) {
+        Ok(bytes_per_second) => {
+            all_ok &= report(
+                &format!("disk write ({})", ledger_dir.display()),
+                bytes_per_second / (1024.0 * 1024.0),
+                block_bytes_per_second / (1024.0 * 1024.0),
+                "MiB/s",
+            );
+        }
+        Err(err) => {
+            println!(
+                "could not measure the disk write speed in {}: {}",
+                ledger_dir.display(),
+                err
+            );
+            all_ok = false;
+        }
+    }
+
+    if !all_ok {
+        anyhow::bail!("this machine does not meet the recommended staking requirements");
+    }
+    println!("this machine meets the recommended staking requirements");
+    Ok(())
+}
@@ -0,0 +1,348 @@
+//! Dry run of the node configuration, for `massa-node --check-config`.
+//!
+//! The configuration files are parsed and cross-checked, the key files are read and decrypted
+//! and the bootstrap servers are contacted, without starting any worker.
+//! All the problems found are reported, not only the first one.
+
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    net::{IpAddr, SocketAddr, TcpStream},
+    path::Path,
+};
+
+use massa_bootstrap::IpType;
+use massa_logging::serde_json;
+use massa_models::config::{try_build_massa_settings, T0};
+use massa_signature::KeyPair;
+use massa_wallet::Wallet;
+
+use crate::settings::Settings;
+
+#[derive(Default)]
+struct Diagnostics {
+    errors: usize,
+    warnings: usize,
+}
+
+impl Diagnostics {
+    fn ok(&mut self, message: impl Display) {
+        println!("ok      {}", message);
+    }
+
+    fn warning(&mut self, message: impl Display) {
+        self.warnings += 1;
+        println!("warning {}", message);
+    }
+
+    fn error(&mut self, message: impl Display) {
+        self.errors += 1;
+        println!("error   {}", message);
+    }
+}
+
+/// Check the configuration of the node and print the diagnostics.
+/// The staking wallet is only decrypted if `password` is given.
+/// Returns an error if the node would not start with this configuration.
+pub fn check_config(password: Option<String>) -> anyhow::Result<()> {
+    let mut diagnostics = Diagnostics::default();
+    match try_build_massa_settings::<Settings>("massa-node", "MASSA_NODE") {
+        Ok(settings) => {
+            diagnostics.ok("configuration files parsed");
+            check_consensus(&settings, &mut diagnostics);
+            check_network(&settings, &mut diagnostics);
+            check_pool(&settings, &mut diagnostics);
+            check_api(&settings, &mut diagnostics);
+            check_keys(&settings, password, &mut diagnostics);
+            check_bootstrap(&settings, &mut diagnostics);
+        }
+        Err(err) => diagnostics.error(format!("could not parse the configuration: {}", err)),
+    }
+    println!(
+        "{} error(s), {} warning(s)",
+        diagnostics.errors, diagnostics.warnings
+    );
+    if diagnostics.errors > 0 {
+        anyhow::bail!("the configuration has {} error(s)", diagnostics.errors);
+    }
+    Ok(())
+}
+
+fn check_consensus(settings: &Settings, diagnostics: &mut Diagnostics) {
+    if settings.consensus.partition_detection_timespan <= T0 {
+        diagnostics.warning(format!(
+            "consensus.partition_detection_timespan ({}) is not longer than a period ({}): partitions will be suspected all the time",
+            settings.consensus.partition_detection_timespan, T0
+        ));
+    }
+}
+
+fn check_network(settings: &Settings, diagnostics: &mut Diagnostics) {
+    let protocol = &settings.protocol;
+    if protocol.max_concurrent_handshakes == 0 {
+        diagnostics
+            .error("protocol.max_concurrent_handshakes is 0: no connection can be established");
+    }
+    if protocol.max_handshakes_per_ip == 0 {
+        diagnostics.error("protocol.max_handshakes_per_ip is 0: no connection can be established");
+    }
+    if protocol.handshake_timeout.to_millis() == 0 {
+        diagnostics.error("protocol.handshake_timeout is 0: no handshake can complete");
+    }
+    let target_out_connections = protocol.default_category_info.target_out_connections
+        + protocol
+            .peers_categories
+            .values()
+            .map(|category| category.target_out_connections)
+            .sum::<usize>();
+    if protocol.isolation_threshold > target_out_connections + protocol.max_in_connections {
+        diagnostics.warning(format!(
+            "protocol.isolation_threshold ({}) is above the number of connections the node can have: it will always be considered isolated",
+            protocol.isolation_threshold
+        ));
+    }
+    if protocol.max_in_connections < protocol.default_category_info.max_in_connections {
+        diagnostics.warning(format!(
+            "protocol.max_in_connections ({}) is below the max in connections of the default category ({})",
+            protocol.max_in_connections, protocol.default_category_info.max_in_connections
+        ));
+    }
+    match std::fs::read_to_string(&protocol.initial_peers_file) {
+        Ok(content) => match serde_json::from_str::<serde_json::Value>(&content) {
+            Ok(_) => diagnostics.ok(format!(
+                "initial peers file {} read",
+                protocol.initial_peers_file.display()
+            )),
+            Err(err) => diagnostics.error(format!(
+                "initial peers file {} is not valid JSON: {}",
+                protocol.initial_peers_file.display(),
+                err
+            )),
+        },
+        Err(err) => diagnostics.error(format!(
+            "could not read the initial peers file {}: {}",
+            protocol.initial_peers_file.display(),
+            err
+        )),
+    }
+
+    // two servers can't listen on the same address
+    let mut binds: Vec<(&str, SocketAddr)> = vec![
+        ("protocol.bind", protocol.bind),
+        ("api.bind_private", settings.api.bind_private),
+        ("api.bind_public", settings.api.bind_public),
+        ("api.bind_api", settings.api.bind_api),
+    ];
+    if let Some(bind) = settings.bootstrap.bind {
+        binds.push(("bootstrap.bind", bind));
+    }
+    if settings.grpc.enabled {
+        binds.push(("grpc.bind", settings.grpc.bind));
+    }
+    if settings.metrics.enabled {
+        binds.push(("metrics.bind", settings.metrics.bind));
+    }
+    for ((name, addr), (other_name, other_addr)) in bind_collisions(&binds) {
+        diagnostics.error(format!(
+            "{} ({}) and {} ({}) listen on the same address",
+            name, addr, other_name, other_addr
+        ));
+    }
+}
+
+/// Pairs of named addresses that can't both be listened on: same port, and same IP or one of
+/// them listening on all the interfaces
+fn bind_collisions<'a>(
+    binds: &[(&'a str, SocketAddr)],
+) -> Vec<((&'a str, SocketAddr), (&'a str, SocketAddr))> {
+    let mut collisions = Vec::new();
+    for (index, (name, addr)) in binds.iter().enumerate() {
+        for (other_name, other_addr) in &binds[index + 1..] {
+            if addr.port() == other_addr.port()
+                && (addr.ip() == other_addr.ip()
+                    || addr.ip().is_unspecified()
+                    || other_addr.ip().is_unspecified())
+            {
+                collisions.push(((*name, *addr), (*other_name, *other_addr)));
+            }
+        }
+    }
+    collisions
+}
+
+fn check_pool(settings: &Settings, diagnostics: &mut Diagnostics) {
+    let pool = &settings.pool;
+    if pool.max_operations_per_sender > pool.max_operation_pool_size {
+        diagnostics.warning(format!(
+            "pool.max_operations_per_sender ({}) is above pool.max_operation_pool_size ({})",
+            pool.max_operations_per_sender, pool.max_operation_pool_size
+        ));
+    }
+    if pool.max_operation_pool_size == 0 {
+        diagnostics
+            .error("pool.max_operation_pool_size is 0: no operation can be included in blocks");
+    }
+}
+
+fn check_api(settings: &Settings, diagnostics: &mut Diagnostics) {
+    let api = &settings.api;
+    if !api.enable_http && !api.enable_ws {
+        diagnostics.error(
+            "api.enable_http and api.enable_ws are both false: the JSON-RPC API can't be reached",
+        );
+    }
+    if !api.openrpc_spec_path.is_file() {
+        diagnostics.warning(format!(
+            "OpenRPC specification {} not found: the rpc.discover method will fail",
+            api.openrpc_spec_path.display()
+        ));
+    }
+}
+
+/// Read a node key file, if it exists
+fn check_keypair_file(path: &Path, name: &str, diagnostics: &mut Diagnostics) {
+    if !path.is_file() {
+        return;
+    }
+    match std::fs::read_to_string(path)
+        .map_err(|err| err.to_string())
+        .and_then(|content| {
+            serde_json::from_str::<KeyPair>(&content).map_err(|err| err.to_string())
+        }) {
+        Ok(_) => diagnostics.ok(format!("{} {} read", name, path.display())),
+        Err(err) => diagnostics.error(format!(
+            "could not read the {} {}: {}",
+            name,
+            path.display(),
+            err
+        )),
+    }
+}
+
+fn check_keys(settings: &Settings, password: Option<String>, diagnostics: &mut Diagnostics) {
+    let protocol = &settings.protocol;
+    if protocol.keypair_seed.is_some() && cfg!(feature = "sandbox") {
+        diagnostics.ok("node key derived from protocol.keypair_seed");
+    } else if protocol.keypair_file.is_file() {
+        check_keypair_file(&protocol.keypair_file, "node key", diagnostics);
+    } else {
+        diagnostics.ok(format!(
+            "node key {} not found: it will be generated at startup",
+            protocol.keypair_file.display()
+        ));
+    }
+    check_keypair_file(
+        &protocol.previous_keypair_file,
+        "previous node key",
+        diagnostics,
+    );
+
+    let wallet_path = &settings.factory.staking_wallet_path;
+    if !wallet_path.is_file() {
+        diagnostics.ok(format!(
+            "staking wallet {} not found: it will be created at startup",
+            wallet_path.display()
+        ));
+    } else if let Some(password) = password {
+        // only read: opening the wallet would migrate the files of older cipher versions
+        match Wallet::read_keys(wallet_path, &password) {
+            Ok((_, keys)) => diagnostics.ok(format!(
+                "staking wallet {} decrypted: {} staking key(s)",
+                wallet_path.display(),
+                keys.len()
+            )),
+            Err(err) => diagnostics.error(format!(
+                "could not decrypt the staking wallet {}: {}",
+                wallet_path.display(),
+                err
+            )),
+        }
+    } else {
+        diagnostics.warning(format!(
+            "staking wallet {} not decrypted: no password given with --pwd",
+            wallet_path.display()
+        ));
+    }
+}
+
+fn check_bootstrap(settings: &Settings, diagnostics: &mut Diagnostics) {
+    let bootstrap = &settings.bootstrap;
+    for (name, path) in [
+        ("whitelist", &bootstrap.bootstrap_whitelist_path),
+        ("blacklist", &bootstrap.bootstrap_blacklist_path),
+    ] {
+        if let Ok(content) = std::fs::read_to_string(path) {
+            if let Err(err) = serde_json::from_str::<HashSet<IpAddr>>(&content) {
+                diagnostics.error(format!(
+                    "bootstrap {} {} is not a JSON list of IPs: {}",
+                    name,
+                    path.display(),
+                    err
+                ));
+            }
+        }
+    }
+
+    let servers: Vec<_> = bootstrap
+        .bootstrap_list
+        .iter()
+        .filter(|(addr, _)| match bootstrap.bootstrap_protocol {
+            IpType::Both => true,
+            IpType::IPv4 => addr.is_ipv4(),
+            IpType::IPv6 => addr.is_ipv6(),
+        })
+        .collect();
+    if servers.is_empty() {
+        diagnostics.warning(
+            "no bootstrap server usable with bootstrap.bootstrap_protocol: the node will start from the initial state",
+        );
+        return;
+    }
+    let mut reachable = 0;
+    for (addr, node_id) in servers {
+        match TcpStream::connect_timeout(addr, bootstrap.connect_timeout.to_duration()) {
+            Ok(_) => {
+                reachable += 1;
+                diagnostics.ok(format!("bootstrap server {} ({}) reachable", addr, node_id));
+            }
+            Err(err) => diagnostics.warning(format!(
+                "bootstrap server {} ({}) unreachable: {}",
+                addr, node_id, err
+            )),
+        }
+    }
+    if reachable == 0 {
+        diagnostics.error("no bootstrap server is reachable: the node can't bootstrap");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bind_collisions() {
+        let addr = |addr: &str| addr.parse::<SocketAddr>().unwrap();
+        let binds = [
+            ("protocol.bind", addr("[::]:31244")),
+            ("api.bind_private", addr("127.0.0.1:33034")),
+            ("api.bind_public", addr("0.0.0.0:33035")),
+            ("api.bind_api", addr("127.0.0.1:33035")),
+            ("bootstrap.bind", addr("[::]:31245")),
+            ("grpc.bind", addr("192.168.0.1:33034")),
+        ];
+        // the same port is only a collision on the same IP or with all the interfaces
+        assert_eq!(bind_collisions(&binds), vec![(binds[2], binds[3])]);
+        let metrics = ("metrics.bind", addr("127.0.0.1:33034"));
+        assert_eq!(
+            bind_collisions(&[binds[1], metrics]),
+            vec![(binds[1], metrics)]
+        );
+        // all the IPv6 interfaces
+        let metrics = ("metrics.bind", addr("10.0.0.1:31244"));
+        assert_eq!(
+            bind_collisions(&[binds[0], metrics]),
+            vec![(binds[0], metrics)]
+        );
+    }
+}
@@ -0,0 +1,851 @@
+//! Publication of the chain events to a message broker, when `event_sink.enabled` is set.
+//!
+//! The finalized blocks, their operations and the reorg notices of the event journal are
+//! published to Kafka (node built with the `kafka` feature) or to NATS JetStream (node built with
+//! the `nats` feature), on one topic each, serialized in JSON or MessagePack.
+//!
+//! Each event is first saved in the outbox directory `event_sink.outbox_path`, and removed from
+//! it once the broker acknowledged it. The events of the outbox are published in order, again
+//! every `event_sink.retry_delay` until acknowledged, and the ones still there when the node
+//! stops are published at the next start, so the delivery is at-least-once. Each event carries
+//! a key (the block id, the operation id, or the time of the reorg) that the consumers can use
+//! to drop the duplicates: Kafka uses it as the message key, and NATS as the `Nats-Msg-Id`
+//! header, which JetStream deduplicates.
+//!
+//! The node does not keep the final blocks to publish them later, so the events of a final slot
+//! that were not saved in the outbox are lost: when the outbox holds `event_sink.queue_size`
+//! events, when the block or its operations left the storage before being read, when the outbox
+//! cannot be written, or when the slot was finalized while the node was stopped. Each loss is
+//! published on `event_sink.gaps_topic` with the range of final slots concerned, which the
+//! consumers can fetch from the API. The last final slot whose events were saved in the outbox
+//! is saved to `event_sink.cursor_path`, so that the slots finalized while the node was stopped
+//! are reported at the next start.
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use massa_api_exports::journal::JournalEvent;
+use massa_execution_exports::SlotExecutionOutput;
+use massa_logging::{massa_journal, serde_json};
+use massa_models::{
+    block::SecureShareBlock, block_id::BlockId, config::THREAD_COUNT,
+    operation::SecureShareOperation, slot::Slot,
+};
+use massa_storage::Storage;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, watch, Notify};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::settings::EventSinkSettings;
+
+/// Message broker to which the events are published
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventSinkBroker {
+    /// Kafka, the topics are Kafka topics
+    Kafka,
+    /// NATS JetStream, the topics are subjects of a stream
+    Nats,
+}
+
+/// Serialization of the published events
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventSinkSerialization {
+    /// JSON
+    Json,
+    /// MessagePack, available with the broker features
+    Msgpack,
+}
+
+/// Minimum interval between two saves of the last final slot saved in the outbox
+const CURSOR_SAVE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Event of the blocks topic
+#[derive(Serialize)]
+struct FinalizedBlockEvent<'a> {
+    slot: Slot,
+    block: &'a SecureShareBlock,
+}
+
+/// Event of the operations topic
+#[derive(Serialize)]
+struct FinalizedOperationEvent<'a> {
+    slot: Slot,
+    block_id: BlockId,
+    operation: &'a SecureShareOperation,
+}
+
+/// Reason why the events of some final slots may not have been published
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum GapReason {
+    /// the slots were skipped because the outbox was full
+    Skipped,
+    /// the block or some of its operations were no longer in storage
+    NotInStorage,
+    /// the events could not be saved in the outbox
+    NotSaved,
+    /// the slots were finalized while the node was stopped
+    NodeStopped,
+}
+
+/// Event of the gaps topic
+#[derive(Serialize)]
+struct GapEvent {
+    from_slot: Slot,
+    to_slot: Slot,
+    reason: GapReason,
+}
+
+/// Event serialized for its topic
+struct SinkMessage {
+    topic: String,
+    key: String,
+    payload: Vec<u8>,
+}
+
+impl SinkMessage {
+    fn new<T: Serialize>(
+        settings: &EventSinkSettings,
+        topic: &str,
+        key: String,
+        event: &T,
+    ) -> Option<Self> {
+        let payload = match settings.serialization {
+            EventSinkSerialization::Json => {
+                serde_json::to_vec(event).map_err(|err| err.to_string())
+            }
+            #[cfg(any(feature = "kafka", feature = "nats"))]
+            EventSinkSerialization::Msgpack => {
+                rmp_serde::to_vec_named(event).map_err(|err| err.to_string())
+            }
+            #[cfg(not(any(feature = "kafka", feature = "nats")))]
+            EventSinkSerialization::Msgpack => {
+                Err("the node is built without a broker feature".to_string())
+            }
+        };
+        match payload {
+            Ok(payload) => Some(SinkMessage {
+                topic: topic.to_string(),
+                key,
+                payload,
+            }),
+            Err(err) => {
+                warn!("event sink: could not serialize the event {}: {}", key, err);
+                None
+            }
+        }
+    }
+}
+
+/// Message reporting that the events of the final slots from `from_slot` to `to_slot` may not
+/// have been published
+fn gap_message(
+    settings: &EventSinkSettings,
+    from_slot: Slot,
+    to_slot: Slot,
+    reason: GapReason,
+) -> Option<SinkMessage> {
+    warn!(
+        "event sink: the events of the final slots {} to {} may not be published ({:?})",
+        from_slot, to_slot, reason
+    );
+    massa_journal!("event_sink_gap", {
+        "from_slot": from_slot.to_string(),
+        "to_slot": to_slot.to_string()
+    });
+    SinkMessage::new(
+        settings,
+        &settings.gaps_topic,
+        format!("gap-{}-{}", from_slot, to_slot),
+        &GapEvent {
+            from_slot,
+            to_slot,
+            reason,
+        },
+    )
+}
+
+/// Final slots missing between the last final slot collected and the newly finalized `slot`
+pub(crate) fn missing_slots(
+    last_slot: Option<Slot>,
+    slot: Slot,
+    thread_count: u8,
+) -> Option<(Slot, Slot)> {
+    let first_missing = last_slot?.get_next_slot(thread_count).ok()?;
+    if slot <= first_missing {
+        return None;
+    }
+    Some((first_missing, slot.get_prev_slot(thread_count).ok()?))
+}
+
+/// Last final slot whose events were saved in the outbox, saved at `path`
+fn load_cursor(path: &Path) -> Option<Slot> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return None,
+        Err(err) => {
+            warn!(
+                "event sink: could not read the cursor {}: {}",
+                path.display(),
+                err
+            );
+            return None;
+        }
+    };
+    match serde_json::from_str(&content) {
+        Ok(slot) => Some(slot),
+        Err(err) => {
+            warn!(
+                "event sink: ignoring the invalid cursor {}: {}",
+                path.display(),
+                err
+            );
+            None
+        }
+    }
+}
+
+/// Save the last final slot whose events were saved in the outbox.
+/// The previous cursor is only replaced once the new one is fully written.
+fn save_cursor(path: &Path, slot: Slot) {
+    let result = (|| -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, serde_json::to_vec(&slot)?)?;
+        fs::rename(tmp_path, path)
+    })();
+    if let Err(err) = result {
+        warn!(
+            "event sink: could not save the cursor {}: {}",
+            path.display(),
+            err
+        );
+    }
+}
+
+/// Path of the event numbered `seq` in the outbox `dir`
+fn outbox_path(dir: &Path, seq: u64) -> std::path::PathBuf {
+    dir.join(format!("{:020}", seq))
+}
+
+/// Sequence numbers of the first event of the outbox `dir` and of the next event to save in it.
+/// The files left by an interrupted save are removed.
+fn scan_outbox(dir: &Path) -> (u64, u64) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return (0, 0),
+        Err(err) => {
+            warn!(
+                "event sink: could not read the outbox {}: {}",
+                dir.display(),
+                err
+            );
+            return (0, 0);
+        }
+    };
+    let mut range: Option<(u64, u64)> = None;
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        match name.parse::<u64>() {
+            Ok(seq) => {
+                range = Some(match range {
+                    Some((first, last)) => (first.min(seq), last.max(seq)),
+                    None => (seq, seq),
+                })
+            }
+            Err(_) if name.ends_with(".tmp") => {
+                let _ = fs::remove_file(entry.path());
+            }
+            Err(_) => {}
+        }
+    }
+    range.map_or((0, 0), |(first, last)| (first, last + 1))
+}
+
+/// Save `messages` in the outbox `dir`, numbered from `next_seq`, which is advanced for each
+/// message saved. A message is only visible in the outbox once fully written to disk.
+fn save_to_outbox(dir: &Path, next_seq: &mut u64, messages: &[SinkMessage]) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    for message in messages {
+        let path = outbox_path(dir, *next_seq);
+        let tmp_path = path.with_extension("tmp");
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(message.topic.as_bytes())?;
+        file.write_all(b"\n")?;
+        file.write_all(message.key.as_bytes())?;
+        file.write_all(b"\n")?;
+        file.write_all(&message.payload)?;
+        file.sync_all()?;
+        fs::rename(tmp_path, path)?;
+        *next_seq += 1;
+    }
+    Ok(())
+}
+
+/// Event numbered `seq` in the outbox `dir`, `None` if it is not saved yet
+fn read_from_outbox(dir: &Path, seq: u64) -> io::Result<Option<SinkMessage>> {
+    let content = match fs::read(outbox_path(dir, seq)) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "invalid event");
+    let mut parts = content.splitn(3, |byte| *byte == b'\n');
+    let topic =
+        String::from_utf8(parts.next().ok_or_else(invalid)?.to_vec()).map_err(|_| invalid())?;
+    let key =
+        String::from_utf8(parts.next().ok_or_else(invalid)?.to_vec()).map_err(|_| invalid())?;
+    let payload = parts.next().ok_or_else(invalid)?.to_vec();
+    Ok(Some(SinkMessage {
+        topic,
+        key,
+        payload,
+    }))
+}
+
+/// Remove the event numbered `seq` from the outbox `dir`
+fn remove_from_outbox(dir: &Path, seq: u64) {
+    if let Err(err) = fs::remove_file(outbox_path(dir, seq)) {
+        if err.kind() != io::ErrorKind::NotFound {
+            warn!(
+                "event sink: could not remove the event {} from the outbox: {}",
+                seq, err
+            );
+        }
+    }
+}
+
+/// Messages of a finalized block and of its operations, read from storage.
+/// Returns `false` if the block or some of its operations are not in storage anymore.
+fn finalized_block_messages(
+    settings: &EventSinkSettings,
+    storage: &Storage,
+    slot: Slot,
+    block_id: BlockId,
+) -> (Vec<SinkMessage>, bool) {
+    let blocks = storage.read_blocks();
+    let Some(block) = blocks.get(&block_id) else {
+        warn!(
+            "event sink: the finalized block {} is not in storage anymore, it is not published",
+            block_id
+        );
+        return (Vec::new(), false);
+    };
+    let mut complete = true;
+    let mut messages: Vec<SinkMessage> = SinkMessage::new(
+        settings,
+        &settings.blocks_topic,
+        block_id.to_string(),
+        &FinalizedBlockEvent { slot, block },
+    )
+    .into_iter()
+    .collect();
+    let operations = storage.read_operations();
+    for op_id in &block.content.operations {
+        match operations.get(op_id) {
+            Some(operation) => messages.extend(SinkMessage::new(
+                settings,
+                &settings.operations_topic,
+                op_id.to_string(),
+                &FinalizedOperationEvent {
+                    slot,
+                    block_id,
+                    operation,
+                },
+            )),
+            None => {
+                warn!(
+                    "event sink: the operation {} of the finalized block {} is not in storage anymore, it is not published",
+                    op_id, block_id
+                );
+                complete = false;
+            }
+        }
+    }
+    (messages, complete)
+}
+
+/// Client of the message broker
+enum Publisher {
+    #[cfg(feature = "kafka")]
+    Kafka(rdkafka::producer::FutureProducer),
+    #[cfg(feature = "nats")]
+    Nats(async_nats::jetstream::Context),
+}
+
+impl Publisher {
+    async fn connect(settings: &EventSinkSettings) -> Result<Self, String> {
+        match settings.broker {
+            #[cfg(feature = "kafka")]
+            EventSinkBroker::Kafka => rdkafka::ClientConfig::new()
+                .set("bootstrap.servers", &settings.servers)
+                .set("enable.idempotence", "true")
+                .set("acks", "all")
+                .create()
+                .map(Publisher::Kafka)
+                .map_err(|err| err.to_string()),
+            #[cfg(not(feature = "kafka"))]
+            EventSinkBroker::Kafka => {
+                Err("the node is built without the `kafka` feature".to_string())
+            }
+            #[cfg(feature = "nats")]
+            EventSinkBroker::Nats => async_nats::connect(settings.servers.as_str())
+                .await
+                .map(|client| Publisher::Nats(async_nats::jetstream::new(client)))
+                .map_err(|err| err.to_string()),
+            #[cfg(not(feature = "nats"))]
+            EventSinkBroker::Nats => {
+                Err("the node is built without the `nats` feature".to_string())
+            }
+        }
+    }
+
+    /// Publish a message and wait for the broker to acknowledge it
+    #[cfg_attr(not(any(feature = "kafka", feature = "nats")), allow(unused_variables))]
+    async fn publish(
+        &self,
+        message: &SinkMessage,
+        timeout: std::time::Duration,
+    ) -> Result<(), String> {
+        match *self {
+            #[cfg(feature = "kafka")]
+            Publisher::Kafka(ref producer) => producer
+                .send(
+                    rdkafka::producer::FutureRecord::to(&message.topic)
+                        .key(&message.key)
+                        .payload(&message.payload),
+                    rdkafka::util::Timeout::After(timeout),
+                )
+                .await
+                .map(|_| ())
+                .map_err(|(err, _)| err.to_string()),
+            #[cfg(feature = "nats")]
+            Publisher::Nats(ref jetstream) => {
+                let mut headers = async_nats::HeaderMap::new();
+                headers.insert("Nats-Msg-Id", message.key.as_str());
+                let ack = jetstream
+                    .publish_with_headers(
+                        message.topic.clone(),
+                        headers,
+                        message.payload.clone().into(),
+                    )
+                    .await
+                    .map_err(|err| err.to_string())?;
+                match tokio::time::timeout(timeout, ack).await {
+                    Ok(ack) => ack.map(|_| ()).map_err(|err| err.to_string()),
+                    Err(_) => Err("timed out waiting for the acknowledgement".to_string()),
+                }
+            }
+        }
+    }
+}
+
+/// Publication of the chain events, running until stopped
+pub struct EventSink {
+    stop_tx: watch::Sender<bool>,
+    collector: JoinHandle<()>,
+    publisher: JoinHandle<()>,
+}
+
+impl EventSink {
+    /// Start publishing the finalized slots of `slot_outputs`, whose blocks and operations are
+    /// read from `storage`, and the reorgs of `journal_events`
+    pub fn start(
+        settings: EventSinkSettings,
+        mut slot_outputs: broadcast::Receiver<SlotExecutionOutput>,
+        mut journal_events: broadcast::Receiver<JournalEvent>,
+        storage: Storage,
+    ) -> Self {
+        let (stop_tx, mut stop_rx) = watch::channel(false);
+        let (first_seq, mut next_seq) = scan_outbox(&settings.outbox_path);
+        if next_seq > first_seq {
+            info!(
+                "event sink: {} events of the outbox were not acknowledged before the node stopped, publishing them again",
+                next_seq - first_seq
+            );
+        }
+        // sequence number of the next event of the outbox to publish
+        let published_seq = Arc::new(AtomicU64::new(first_seq));
+        let saved = Arc::new(Notify::new());
+
+        let collector_settings = settings.clone();
+        let collector_published_seq = published_seq.clone();
+        let collector_saved = saved.clone();
+        let mut collector_stop_rx = stop_rx.clone();
+        let collector = tokio::spawn(async move {
+            let settings = collector_settings;
+            // the slots following the saved cursor, up to the first slot finalized after the
+            // start, were finalized while the node was stopped
+            let mut last_slot = load_cursor(&settings.cursor_path);
+            let mut gap_reason = GapReason::NodeStopped;
+            let mut last_save = Instant::now();
+            loop {
+                let (messages, slot): (Vec<SinkMessage>, Option<Slot>) = tokio::select! {
+                    _ = collector_stop_rx.changed() => break,
+                    output = slot_outputs.recv() => match output {
+                        Ok(SlotExecutionOutput::FinalizedSlot(output)) => {
+                            // the slots skipped while the outbox is full are reported as a gap
+                            // at the next final slot saved
+                            let pending = next_seq
+                                .saturating_sub(collector_published_seq.load(Ordering::Acquire));
+                            if pending >= settings.queue_size as u64 {
+                                warn!(
+                                    "event sink: the outbox is full, the events of the final slot {} are not published",
+                                    output.slot
+                                );
+                                continue;
+                            }
+                            let mut messages = Vec::new();
+                            if let Some((from_slot, to_slot)) =
+                                missing_slots(last_slot, output.slot, THREAD_COUNT)
+                            {
+                                messages.extend(gap_message(
+                                    &settings, from_slot, to_slot, gap_reason,
+                                ));
+                            }
+                            if let Some(block_info) = output.block_info {
+                                let (block_messages, complete) = finalized_block_messages(
+                                    &settings,
+                                    &storage,
+                                    output.slot,
+                                    block_info.block_id,
+                                );
+                                messages.extend(block_messages);
+                                if !complete {
+                                    messages.extend(gap_message(
+                                        &settings,
+                                        output.slot,
+                                        output.slot,
+                                        GapReason::NotInStorage,
+                                    ));
+                                }
+                            }
+                            (messages, Some(output.slot))
+                        }
+                        Ok(SlotExecutionOutput::ExecutedSlot(_)) => continue,
+                        // the skipped final slots are reported as a gap at the next final slot
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            warn!("event sink: {} slot outputs were skipped because the sink is late", skipped);
+                            continue;
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                    event = journal_events.recv() => match event {
+                        Ok(event) if event.kind == "reorg" => (
+                            SinkMessage::new(
+                                &settings,
+                                &settings.reorgs_topic,
+                                event.time.to_millis().to_string(),
+                                &event,
+                            )
+                            .into_iter()
+                            .collect(),
+                            None,
+                        ),
+                        Ok(_) => continue,
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            warn!("event sink: {} journal events were skipped because the sink is late", skipped);
+                            continue;
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                };
+                let result = save_to_outbox(&settings.outbox_path, &mut next_seq, &messages);
+                collector_saved.notify_one();
+                match (result, slot) {
+                    (Ok(()), Some(slot)) => {
+                        last_slot = Some(slot);
+                        gap_reason = GapReason::Skipped;
+                        if last_save.elapsed() >= CURSOR_SAVE_INTERVAL {
+                            save_cursor(&settings.cursor_path, slot);
+                            last_save = Instant::now();
+                        }
+                    }
+                    (Ok(()), None) => {}
+                    // the slot is reported as a gap at the next final slot saved
+                    (Err(err), slot) => {
+                        warn!(
+                            "event sink: could not save the events in the outbox {}: {}",
+                            settings.outbox_path.display(),
+                            err
+                        );
+                        if slot.is_some() {
+                            gap_reason = GapReason::NotSaved;
+                        }
+                    }
+                }
+            }
+            if let Some(slot) = last_slot {
+                save_cursor(&settings.cursor_path, slot);
+            }
+        });
+
+        let publisher = tokio::spawn(async move {
+            let retry_delay = settings.retry_delay.to_duration();
+            let timeout = settings.publish_timeout.to_duration();
+            let publisher = loop {
+                match Publisher::connect(&settings).await {
+                    Ok(publisher) => break publisher,
+                    Err(err) => warn!("event sink: could not connect to the broker: {}", err),
+                }
+                tokio::select! {
+                    _ = stop_rx.changed() => return,
+                    _ = tokio::time::sleep(retry_delay) => {}
+                }
+            };
+            info!(
+                "event sink: publishing the chain events to {}",
+                settings.servers
+            );
+            let mut seq = first_seq;
+            loop {
+                let message = match read_from_outbox(&settings.outbox_path, seq) {
+                    Ok(Some(message)) => message,
+                    // every saved event is acknowledged
+                    Ok(None) => {
+                        if *stop_rx.borrow() {
+                            return;
+                        }
+                        tokio::select! {
+                            _ = stop_rx.changed() => {}
+                            _ = saved.notified() => {}
+                        }
+                        continue;
+                    }
+                    Err(err) => {
+                        warn!(
+                            "event sink: dropping the event {} of the outbox, which cannot be read: {}",
+                            seq, err
+                        );
+                        remove_from_outbox(&settings.outbox_path, seq);
+                        seq += 1;
+                        published_seq.store(seq, Ordering::Release);
+                        continue;
+                    }
+                };
+                loop {
+                    match publisher.publish(&message, timeout).await {
+                        Ok(()) => break,
+                        // once stopping, the publication stops at the first failure:
+                        // the events still in the outbox are published at the next start
+                        Err(err) if *stop_rx.borrow() => {
+                            warn!(
+                                "event sink: the events still in the outbox are published at the next start: {}",
+                                err
+                            );
+                            return;
+                        }
+                        Err(err) => {
+                            warn!(
+                                "event sink: could not publish the event {}, retrying: {}",
+                                message.key, err
+                            );
+                            tokio::select! {
+                                _ = stop_rx.changed() => {}
+                                _ = tokio::time::sleep(retry_delay) => {}
+                            }
+                        }
+                    }
+                }
+                remove_from_outbox(&settings.outbox_path, seq);
+                seq += 1;
+                published_seq.store(seq, Ordering::Release);
+            }
+        });
+
+        EventSink {
+            stop_tx,
+            collector,
+            publisher,
+        }
+    }
+
+    /// Stop collecting the events, and publish the events still in the outbox if the broker is reachable
+    pub async fn stop(self) {
+        let _ = self.stop_tx.send(true);
+        let _ = self.collector.await;
+        let _ = self.publisher.await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_time::MassaTime;
+
+    fn settings(serialization: EventSinkSerialization) -> EventSinkSettings {
+        EventSinkSettings {
+            enabled: true,
+            broker: EventSinkBroker::Nats,
+            servers: "nats://localhost:4222".to_string(),
+            blocks_topic: "massa.blocks".to_string(),
+            operations_topic: "massa.operations".to_string(),
+            reorgs_topic: "massa.reorgs".to_string(),
+            gaps_topic: "massa.gaps".to_string(),
+            serialization,
+            queue_size: 10,
+            retry_delay: MassaTime::from_millis(1000),
+            publish_timeout: MassaTime::from_millis(10000),
+            cursor_path: std::env::temp_dir().join("massa_event_sink_cursor.json"),
+            outbox_path: std::env::temp_dir().join("massa_event_sink_outbox"),
+        }
+    }
+
+    #[test]
+    fn test_sink_message_serialization() {
+        let event = JournalEvent {
+            time: MassaTime::from_millis(42),
+            kind: "reorg".to_string(),
+            details: serde_json::json!({ "blockclique_size": 3 }),
+        };
+
+        let json = SinkMessage::new(
+            &settings(EventSinkSerialization::Json),
+            "massa.reorgs",
+            "42".to_string(),
+            &event,
+        )
+        .unwrap();
+        assert_eq!(json.topic, "massa.reorgs");
+        assert_eq!(json.key, "42");
+        let decoded: serde_json::Value = serde_json::from_slice(&json.payload).unwrap();
+        assert_eq!(decoded["kind"], "reorg");
+        assert_eq!(decoded["details"]["blockclique_size"], 3);
+    }
+
+    #[cfg(any(feature = "kafka", feature = "nats"))]
+    #[test]
+    fn test_sink_message_msgpack_serialization() {
+        let event = JournalEvent {
+            time: MassaTime::from_millis(42),
+            kind: "reorg".to_string(),
+            details: serde_json::json!({ "blockclique_size": 3 }),
+        };
+        let msgpack = SinkMessage::new(
+            &settings(EventSinkSerialization::Msgpack),
+            "massa.reorgs",
+            "42".to_string(),
+            &event,
+        )
+        .unwrap();
+        let decoded: serde_json::Value = rmp_serde::from_slice(&msgpack.payload).unwrap();
+        assert_eq!(decoded["kind"], "reorg");
+        assert_eq!(decoded["details"]["blockclique_size"], 3);
+    }
+
+    #[test]
+    fn test_missing_slots() {
+        let thread_count = 2;
+        // nothing collected yet
+        assert_eq!(missing_slots(None, Slot::new(4, 1), thread_count), None);
+        // next slot, or a slot already collected
+        assert_eq!(
+            missing_slots(Some(Slot::new(4, 0)), Slot::new(4, 1), thread_count),
+            None
+        );
+        assert_eq!(
+            missing_slots(Some(Slot::new(4, 1)), Slot::new(4, 0), thread_count),
+            None
+        );
+        // skipped slots
+        assert_eq!(
+            missing_slots(Some(Slot::new(4, 0)), Slot::new(5, 1), thread_count),
+            Some((Slot::new(4, 1), Slot::new(5, 0)))
+        );
+        assert_eq!(
+            missing_slots(Some(Slot::new(4, 1)), Slot::new(5, 1), thread_count),
+            Some((Slot::new(5, 0), Slot::new(5, 0)))
+        );
+    }
+
+    #[test]
+    fn test_cursor() {
+        let dir =
+            std::env::temp_dir().join(format!("massa_event_sink_test_{}", std::process::id()));
+        let path = dir.join("cursor.json");
+        assert_eq!(load_cursor(&path), None);
+
+        save_cursor(&path, Slot::new(12, 3));
+        assert_eq!(load_cursor(&path), Some(Slot::new(12, 3)));
+        save_cursor(&path, Slot::new(13, 0));
+        assert_eq!(load_cursor(&path), Some(Slot::new(13, 0)));
+
+        fs::write(&path, "not a slot").unwrap();
+        assert_eq!(load_cursor(&path), None);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_gap_message() {
+        let settings = settings(EventSinkSerialization::Json);
+        let message = gap_message(
+            &settings,
+            Slot::new(4, 1),
+            Slot::new(5, 0),
+            GapReason::Skipped,
+        )
+        .unwrap();
+        assert_eq!(message.topic, "massa.gaps");
+        let decoded: serde_json::Value = serde_json::from_slice(&message.payload).unwrap();
+        assert_eq!(decoded["from_slot"]["period"], 4);
+        assert_eq!(decoded["to_slot"]["thread"], 0);
+        assert_eq!(decoded["reason"], "skipped");
+    }
+
+    #[test]
+    fn test_outbox() {
+        let dir =
+            std::env::temp_dir().join(format!("massa_event_sink_outbox_{}", std::process::id()));
+        assert_eq!(scan_outbox(&dir), (0, 0));
+        assert!(read_from_outbox(&dir, 0).unwrap().is_none());
+
+        let messages = vec![
+            SinkMessage {
+                topic: "massa.blocks".to_string(),
+                key: "block".to_string(),
+                payload: b"{\n}".to_vec(),
+            },
+            SinkMessage {
+                topic: "massa.operations".to_string(),
+                key: "operation".to_string(),
+                payload: Vec::new(),
+            },
+        ];
+        let mut next_seq = 0;
+        save_to_outbox(&dir, &mut next_seq, &messages).unwrap();
+        assert_eq!(next_seq, 2);
+        assert_eq!(scan_outbox(&dir), (0, 2));
+
+        let message = read_from_outbox(&dir, 0).unwrap().unwrap();
+        assert_eq!(message.topic, "massa.blocks");
+        assert_eq!(message.key, "block");
+        assert_eq!(message.payload, b"{\n}");
+        let message = read_from_outbox(&dir, 1).unwrap().unwrap();
+        assert_eq!(message.topic, "massa.operations");
+        assert!(message.payload.is_empty());
+
+        // acknowledged events are removed, and interrupted saves are ignored
+        remove_from_outbox(&dir, 0);
+        fs::write(outbox_path(&dir, 2).with_extension("tmp"), "partial").unwrap();
+        assert_eq!(scan_outbox(&dir), (1, 2));
+        assert!(!outbox_path(&dir, 2).with_extension("tmp").exists());
+
+        fs::write(outbox_path(&dir, 2), "no topic").unwrap();
+        assert!(read_from_outbox(&dir, 2).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,857 @@
+//! Indexing of the final chain data in PostgreSQL, when the node is built with the `postgres`
+//! feature and `indexer.enabled` is set.
+//!
+//! The finalized blocks, their operations and the balances changed at each final slot are written
+//! to the database at `indexer.database_url`, in the tables created by [`SCHEMA`]. Each slot is
+//! written in a single transaction of upserts, along with the last indexed slot: a slot written
+//! again, after a write failure or a restart of the node, leaves the tables unchanged. A slot
+//! finalized at or before the last indexed slot means that the node rewound its final state (it
+//! bootstrapped again after a desync): the rows of the following slots, which may belong to
+//! another chain, are deleted before the slot is written.
+//!
+//! The slots finalized while the database is unreachable wait in a queue of `indexer.queue_size`
+//! slots, and their transaction is retried every `indexer.retry_delay`.
+//!
+//! The node does not keep the old final blocks, so the index cannot be backfilled: a final slot
+//! that was not received (finalized while the queue was full or while the node was stopped), or
+//! whose block or operations are no longer in storage, is a gap in the index. By default the
+//! indexing stops at the first gap, and the last indexed slot stays the one before it. With
+//! `indexer.allow_gaps`, the gap is recorded in the `indexer_gaps` table and the indexing goes on.
+
+use massa_execution_exports::SlotExecutionOutput;
+use massa_ledger_exports::{LedgerEntryUpdate, SetOrKeep, SetUpdateOrDelete};
+use massa_logging::{massa_journal, serde_json};
+use massa_models::{
+    address::Address, amount::Amount, block_id::BlockId, config::THREAD_COUNT,
+    operation::OperationId, operation::OperationType, slot::Slot,
+};
+use massa_storage::Storage;
+use std::future::Future;
+use tokio::sync::{broadcast, mpsc, watch};
+use tokio::task::JoinHandle;
+use tokio_postgres::{Client, NoTls};
+use tokio_postgres_rustls::MakeRustlsConnect;
+use tracing::{error, info, warn};
+
+use crate::event_sink::missing_slots;
+use crate::settings::IndexerSettings;
+
+/// Tables of the index
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS blocks (
+    block_id TEXT PRIMARY KEY,
+    period BIGINT NOT NULL,
+    thread SMALLINT NOT NULL,
+    creator TEXT NOT NULL,
+    parents TEXT[] NOT NULL,
+    operation_count INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS blocks_slot ON blocks (period, thread);
+CREATE INDEX IF NOT EXISTS blocks_creator ON blocks (creator);
+CREATE TABLE IF NOT EXISTS operations (
+    operation_id TEXT PRIMARY KEY,
+    block_id TEXT NOT NULL REFERENCES blocks (block_id) ON DELETE CASCADE,
+    period BIGINT NOT NULL,
+    thread SMALLINT NOT NULL,
+    creator TEXT NOT NULL,
+    fee NUMERIC NOT NULL,
+    expire_period BIGINT NOT NULL,
+    operation_type TEXT NOT NULL,
+    content JSONB NOT NULL
+);
+CREATE INDEX IF NOT EXISTS operations_block ON operations (block_id);
+CREATE INDEX IF NOT EXISTS operations_creator ON operations (creator);
+CREATE TABLE IF NOT EXISTS balance_changes (
+    address TEXT NOT NULL,
+    period BIGINT NOT NULL,
+    thread SMALLINT NOT NULL,
+    balance NUMERIC NOT NULL,
+    PRIMARY KEY (address, period, thread)
+);
+CREATE INDEX IF NOT EXISTS balance_changes_slot ON balance_changes (period, thread);
+CREATE TABLE IF NOT EXISTS indexer_state (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    period BIGINT NOT NULL,
+    thread SMALLINT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS indexer_gaps (
+    from_period BIGINT NOT NULL,
+    from_thread SMALLINT NOT NULL,
+    to_period BIGINT NOT NULL,
+    to_thread SMALLINT NOT NULL,
+    reason TEXT NOT NULL,
+    PRIMARY KEY (from_period, from_thread)
+);
+";
+
+/// Operation of an indexed block
+struct IndexedOperation {
+    id: OperationId,
+    creator: Address,
+    fee: Amount,
+    expire_period: u64,
+    operation_type: &'static str,
+    /// JSON content of the operation
+    content: String,
+}
+
+/// Finalized block, with the operations found in storage
+struct IndexedBlock {
+    id: BlockId,
+    creator: Address,
+    parents: Vec<BlockId>,
+    /// number of operations of the block, including the ones missing from storage
+    operation_count: usize,
+    operations: Vec<IndexedOperation>,
+}
+
+/// Data of a final slot to write to the index
+struct IndexedSlot {
+    slot: Slot,
+    block: Option<IndexedBlock>,
+    /// false if the block or some of its operations were no longer in storage
+    complete: bool,
+    /// new balances of the addresses whose balance changed at this slot
+    balances: Vec<(Address, Amount)>,
+}
+
+/// Reason why some final slots are missing from the index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GapReason {
+    /// the slots were finalized while the queue was full or while the node was stopped
+    NotReceived,
+    /// the block of the slot or some of its operations were no longer in storage
+    NotInStorage,
+}
+
+impl GapReason {
+    /// Value of the `reason` column of the `indexer_gaps` table
+    fn as_str(&self) -> &'static str {
+        match self {
+            GapReason::NotReceived => "not_received",
+            GapReason::NotInStorage => "not_in_storage",
+        }
+    }
+}
+
+/// Final slots from the first to the second one, missing from the index
+type Gap = (Slot, Slot, GapReason);
+
+/// Gaps that writing `indexed` after the last indexed slot `head` would leave in the index
+fn slot_gaps(head: Option<Slot>, indexed: &IndexedSlot) -> Vec<Gap> {
+    let mut gaps: Vec<Gap> = missing_slots(head, indexed.slot, THREAD_COUNT)
+        .map(|(from_slot, to_slot)| (from_slot, to_slot, GapReason::NotReceived))
+        .into_iter()
+        .collect();
+    if !indexed.complete {
+        gaps.push((indexed.slot, indexed.slot, GapReason::NotInStorage));
+    }
+    gaps
+}
+
+/// Name of the type of an operation in the index
+fn operation_type_name(op: &OperationType) -> &'static str {
+    match op {
+        OperationType::Transaction { .. } => "Transaction",
+        OperationType::RollBuy { .. } => "RollBuy",
+        OperationType::RollSell { .. } => "RollSell",
+        OperationType::ExecuteSC { .. } => "ExecuteSC",
+        OperationType::CallSC { .. } => "CallSC",
+    }
+}
+
+impl IndexedSlot {
+    /// Collect the data of a final slot: the block and its operations are read from storage
+    fn new(
+        slot: Slot,
+        block_id: Option<BlockId>,
+        ledger_changes: &massa_ledger_exports::LedgerChanges,
+        storage: &Storage,
+    ) -> Self {
+        let mut complete = true;
+        let block = block_id.and_then(|block_id| {
+            let blocks = storage.read_blocks();
+            let Some(block) = blocks.get(&block_id) else {
+                warn!(
+                    "indexer: the finalized block {} is not in storage anymore, it is not indexed",
+                    block_id
+                );
+                complete = false;
+                return None;
+            };
+            let operations = storage.read_operations();
+            Some(IndexedBlock {
+                id: block_id,
+                creator: block.content_creator_address,
+                parents: block.content.header.content.parents.clone(),
+                operation_count: block.content.operations.len(),
+                operations: block
+                    .content
+                    .operations
+                    .iter()
+                    .filter_map(|op_id| {
+                        let Some(op) = operations.get(op_id) else {
+                            warn!(
+                                "indexer: the operation {} of the finalized block {} is not in storage anymore",
+                                op_id, block_id
+                            );
+                            complete = false;
+                            return None;
+                        };
+                        Some(IndexedOperation {
+                            id: *op_id,
+                            creator: op.content_creator_address,
+                            fee: op.content.fee,
+                            expire_period: op.content.expire_period,
+                            operation_type: operation_type_name(&op.content.op),
+                            content: serde_json::to_string(&op.content.op).unwrap_or_default(),
+                        })
+                    })
+                    .collect(),
+            })
+        });
+
+        let balances = ledger_changes
+            .0
+            .iter()
+            .filter_map(|(address, change)| match change {
+                SetUpdateOrDelete::Set(entry) => Some((*address, entry.balance)),
+                SetUpdateOrDelete::Update(LedgerEntryUpdate {
+                    balance: SetOrKeep::Set(balance),
+                    ..
+                }) => Some((*address, *balance)),
+                SetUpdateOrDelete::Update(_) => None,
+                SetUpdateOrDelete::Delete => Some((*address, Amount::zero())),
+            })
+            .collect();
+
+        IndexedSlot {
+            slot,
+            block,
+            complete,
+            balances,
+        }
+    }
+}
+
+/// TLS connector trusting the system root certificates and the one of `indexer.tls_root_certificate`
+fn tls_connector(settings: &IndexerSettings) -> Result<MakeRustlsConnect, String> {
+    let mut roots = rustls::RootCertStore::empty();
+    let system_certificates = rustls_native_certs::load_native_certs()
+        .map_err(|err| format!("could not load the system root certificates: {}", err))?;
+    roots.add_parsable_certificates(
+        &system_certificates
+            .into_iter()
+            .map(|certificate| certificate.0)
+            .collect::<Vec<_>>(),
+    );
+    if let Some(path) = &settings.tls_root_certificate {
+        let pem = std::fs::read(path)
+            .map_err(|err| format!("could not read {}: {}", path.display(), err))?;
+        let certificates = rustls_pemfile::certs(&mut pem.as_slice())
+            .map_err(|err| format!("could not parse {}: {}", path.display(), err))?;
+        let (added, _) = roots.add_parsable_certificates(&certificates);
+        if added == 0 {
+            return Err(format!("no valid certificate in {}", path.display()));
+        }
+    }
+    let config = rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(MakeRustlsConnect::new(config))
+}
+
+/// Run the connection to the database until it is closed
+fn spawn_connection<F>(connection: F)
+where
+    F: Future<Output = Result<(), tokio_postgres::Error>> + Send + 'static,
+{
+    tokio::spawn(async move {
+        if let Err(err) = connection.await {
+            warn!(
+                "indexer: the connection to the database was closed: {}",
+                err
+            );
+        }
+    });
+}
+
+/// Connect to the database, with TLS if `tls` is set, and create the tables.
+/// Returns the client and the last indexed slot.
+async fn connect(
+    settings: &IndexerSettings,
+    tls: Option<&MakeRustlsConnect>,
+) -> Result<(Client, Option<Slot>), tokio_postgres::Error> {
+    let client = match tls {
+        Some(tls) => {
+            let (client, connection) =
+                tokio_postgres::connect(&settings.database_url, tls.clone()).await?;
+            spawn_connection(connection);
+            client
+        }
+        None => {
+            let (client, connection) =
+                tokio_postgres::connect(&settings.database_url, NoTls).await?;
+            spawn_connection(connection);
+            client
+        }
+    };
+    client.batch_execute(SCHEMA).await?;
+    let head = client
+        .query_opt("SELECT period, thread FROM indexer_state", &[])
+        .await?
+        .map(|row| Slot::new(row.get::<_, i64>(0) as u64, row.get::<_, i16>(1) as u8));
+    Ok((client, head))
+}
+
+/// Write a final slot and the gaps it leaves in a single transaction.
+/// The rows of the slots following `slot` are deleted first if the index is ahead of it.
+async fn write_slot(
+    client: &mut Client,
+    head: Option<Slot>,
+    indexed: &IndexedSlot,
+    gaps: &[Gap],
+) -> Result<(), tokio_postgres::Error> {
+    let period = indexed.slot.period as i64;
+    let thread = indexed.slot.thread as i16;
+    let tx = client.transaction().await?;
+
+    if head.map_or(false, |head| indexed.slot <= head) {
+        tx.execute(
+            "DELETE FROM balance_changes WHERE (period, thread) >= ($1, $2)",
+            &[&period, &thread],
+        )
+        .await?;
+        tx.execute(
+            "DELETE FROM blocks WHERE (period, thread) >= ($1, $2)",
+            &[&period, &thread],
+        )
+        .await?;
+        tx.execute(
+            "DELETE FROM indexer_gaps WHERE (from_period, from_thread) >= ($1, $2)",
+            &[&period, &thread],
+        )
+        .await?;
+        // a gap ending after the rewind point now ends at the previous slot
+        if let Ok(prev_slot) = indexed.slot.get_prev_slot(THREAD_COUNT) {
+            tx.execute(
+                "UPDATE indexer_gaps SET to_period = $3, to_thread = $4 WHERE (to_period, to_thread) >= ($1, $2)",
+                &[
+                    &period,
+                    &thread,
+                    &(prev_slot.period as i64),
+                    &(prev_slot.thread as i16),
+                ],
+            )
+            .await?;
+        }
+    }
+
+    for (from_slot, to_slot, reason) in gaps {
+        tx.execute(
+            "INSERT INTO indexer_gaps (from_period, from_thread, to_period, to_thread, reason)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (from_period, from_thread) DO UPDATE SET to_period = EXCLUDED.to_period,
+                to_thread = EXCLUDED.to_thread, reason = EXCLUDED.reason",
+            &[
+                &(from_slot.period as i64),
+                &(from_slot.thread as i16),
+                &(to_slot.period as i64),
+                &(to_slot.thread as i16),
+                &reason.as_str(),
+            ],
+        )
+        .await?;
+    }
+
+    if let Some(block) = &indexed.block {
+        let parents: Vec<String> = block.parents.iter().map(|id| id.to_string()).collect();
+        tx.execute(
+            "INSERT INTO blocks (block_id, period, thread, creator, parents, operation_count)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             ON CONFLICT (block_id) DO UPDATE SET period = EXCLUDED.period, thread = EXCLUDED.thread,
+                creator = EXCLUDED.creator, parents = EXCLUDED.parents, operation_count = EXCLUDED.operation_count",
+            &[
+                &block.id.to_string(),
+                &period,
+                &thread,
+                &block.creator.to_string(),
+                &parents,
+                &(block.operation_count as i32),
+            ],
+        )
+        .await?;
+        for op in &block.operations {
+            tx.execute(
+                "INSERT INTO operations (operation_id, block_id, period, thread, creator, fee, expire_period, operation_type, content)
+                 VALUES ($1, $2, $3, $4, $5, $6::TEXT::NUMERIC, $7, $8, $9::TEXT::JSONB)
+                 ON CONFLICT (operation_id) DO UPDATE SET block_id = EXCLUDED.block_id, period = EXCLUDED.period,
+                    thread = EXCLUDED.thread, creator = EXCLUDED.creator, fee = EXCLUDED.fee,
+                    expire_period = EXCLUDED.expire_period, operation_type = EXCLUDED.operation_type,
+                    content = EXCLUDED.content",
+                &[
+                    &op.id.to_string(),
+                    &block.id.to_string(),
+                    &period,
+                    &thread,
+                    &op.creator.to_string(),
+                    &op.fee.to_string(),
+                    &(op.expire_period as i64),
+                    &op.operation_type,
+                    &op.content,
+                ],
+            )
+            .await?;
+        }
+    }
+
+    for (address, balance) in &indexed.balances {
+        tx.execute(
+            "INSERT INTO balance_changes (address, period, thread, balance)
+             VALUES ($1, $2, $3, $4::TEXT::NUMERIC)
+             ON CONFLICT (address, period, thread) DO UPDATE SET balance = EXCLUDED.balance",
+            &[&address.to_string(), &period, &thread, &balance.to_string()],
+        )
+        .await?;
+    }
+
+    tx.execute(
+        "INSERT INTO indexer_state (id, period, thread) VALUES (TRUE, $1, $2)
+         ON CONFLICT (id) DO UPDATE SET period = EXCLUDED.period, thread = EXCLUDED.thread",
+        &[&period, &thread],
+    )
+    .await?;
+    tx.commit().await
+}
+
+/// Indexing of the final slots, running until stopped
+pub struct Indexer {
+    stop_tx: watch::Sender<bool>,
+    collector: JoinHandle<()>,
+    writer: JoinHandle<()>,
+}
+
+impl Indexer {
+    /// Start indexing the final slots of `slot_outputs`, whose blocks and operations are read from `storage`
+    pub fn start(
+        settings: IndexerSettings,
+        mut slot_outputs: broadcast::Receiver<SlotExecutionOutput>,
+        storage: Storage,
+    ) -> Self {
+        let (stop_tx, mut stop_rx) = watch::channel(false);
+        let (queue_tx, mut queue_rx) = mpsc::channel::<IndexedSlot>(settings.queue_size);
+
+        let mut collector_stop_rx = stop_rx.clone();
+        let collector = tokio::spawn(async move {
+            loop {
+                let indexed = tokio::select! {
+                    _ = collector_stop_rx.changed() => break,
+                    output = slot_outputs.recv() => match output {
+                        Ok(SlotExecutionOutput::FinalizedSlot(output)) => IndexedSlot::new(
+                            output.slot,
+                            output.block_info.map(|info| info.block_id),
+                            &output.state_changes.ledger_changes,
+                            &storage,
+                        ),
+                        Ok(SlotExecutionOutput::ExecutedSlot(_)) => continue,
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            warn!("indexer: {} slot outputs were skipped because the queue is full", skipped);
+                            massa_journal!("indexer_lagged", { "skipped_slot_outputs": skipped });
+                            continue;
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                };
+                if queue_tx.send(indexed).await.is_err() {
+                    return;
+                }
+            }
+        });
+
+        let writer = tokio::spawn(async move {
+            let retry_delay = settings.retry_delay.to_duration();
+            let tls = if settings.tls {
+                match tls_connector(&settings) {
+                    Ok(tls) => Some(tls),
+                    Err(err) => {
+                        error!("indexer: TLS cannot be set up, nothing is indexed: {}", err);
+                        return;
+                    }
+                }
+            } else {
+                None
+            };
+            let mut connection: Option<(Client, Option<Slot>)> = None;
+            while let Some(indexed) = queue_rx.recv().await {
+                loop {
+                    let result = async {
+                        if connection.is_none() {
+                            let (client, head) = connect(&settings, tls.as_ref()).await?;
+                            info!(
+                                "indexer: connected to the database, last indexed slot: {:?}",
+                                head
+                            );
+                            connection = Some((client, head));
+                        }
+                        let (client, head) = connection.as_mut().expect("connected above");
+                        let gaps = slot_gaps(*head, &indexed);
+                        if !gaps.is_empty() && !settings.allow_gaps {
+                            return Ok(Err((*head, gaps)));
+                        }
+                        write_slot(client, *head, &indexed, &gaps).await?;
+                        *head = Some(indexed.slot);
+                        Ok::<_, tokio_postgres::Error>(Ok(gaps))
+                    }
+                    .await;
+                    match result {
+                        Ok(Ok(gaps)) => {
+                            for (from_slot, to_slot, reason) in gaps {
+                                warn!(
+                                    "indexer: the final slots {} to {} are missing from the index ({:?})",
+                                    from_slot, to_slot, reason
+                                );
+                                massa_journal!("indexer_gap", {
+                                    "from_slot": from_slot.to_string(),
+                                    "to_slot": to_slot.to_string(),
+                                    "stopped": false
+                                });
+                            }
+                            break;
+                        }
+                        // the index cannot be backfilled: it stays complete up to the last indexed slot
+                        Ok(Err((head, gaps))) => {
+                            for (from_slot, to_slot, reason) in gaps {
+                                error!(
+                                    "indexer: the final slots {} to {} cannot be indexed ({:?}), indexing stopped after the slot {:?}. \
+                                    Rebuild the index, or set indexer.allow_gaps to record the gap and index the following slots",
+                                    from_slot, to_slot, reason, head
+                                );
+                                massa_journal!("indexer_gap", {
+                                    "from_slot": from_slot.to_string(),
+                                    "to_slot": to_slot.to_string(),
+                                    "stopped": true
+                                });
+                            }
+                            return;
+                        }
+                        // once stopping, the queue is dropped at the first failure
+                        Err(err) if *stop_rx.borrow() => {
+                            warn!("indexer: the slots still in the queue are not indexed at shutdown: {}", err);
+                            return;
+                        }
+                        Err(err) => {
+                            warn!(
+                                "indexer: could not index the slot {}, retrying: {}",
+                                indexed.slot, err
+                            );
+                            if connection
+                                .as_ref()
+                                .map_or(false, |(client, _)| client.is_closed())
+                            {
+                                connection = None;
+                            }
+                            tokio::select! {
+                                _ = stop_rx.changed() => {}
+                                _ = tokio::time::sleep(retry_delay) => {}
+                            }
+                        }
+                    }
+                }
+            }
+        });
+
+        Indexer {
+            stop_tx,
+            collector,
+            writer,
+        }
+    }
+
+    /// Stop collecting the final slots, and index the slots still in the queue if the database is reachable
+    pub async fn stop(self) {
+        let _ = self.stop_tx.send(true);
+        let _ = self.collector.await;
+        let _ = self.writer.await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_hash::Hash;
+    use massa_ledger_exports::{LedgerChanges, LedgerEntry};
+    use massa_models::{
+        block::{Block, BlockSerializer, SecureShareBlock},
+        block_header::{BlockHeader, BlockHeaderSerializer},
+        operation::{Operation, OperationSerializer, SecureShareOperation},
+        secure_share::SecureShareContent,
+    };
+    use massa_signature::KeyPair;
+    use std::str::FromStr;
+
+    fn operation(keypair: &KeyPair, expire_period: u64) -> SecureShareOperation {
+        Operation::new_verifiable(
+            Operation {
+                fee: Amount::from_str("0.01").unwrap(),
+                expire_period,
+                op: OperationType::Transaction {
+                    recipient_address: Address::from_public_key(&keypair.get_public_key()),
+                    amount: Amount::from_str("1").unwrap(),
+                },
+            },
+            OperationSerializer::new(),
+            keypair,
+        )
+        .unwrap()
+    }
+
+    fn block(
+        keypair: &KeyPair,
+        slot: Slot,
+        operations: &[SecureShareOperation],
+    ) -> SecureShareBlock {
+        let header = BlockHeader::new_verifiable(
+            BlockHeader {
+                current_version: 0,
+                announced_version: None,
+                slot,
+                parents: (0..THREAD_COUNT)
+                    .map(|i| BlockId(Hash::compute_from(&[i])))
+                    .collect(),
+                operation_merkle_root: Hash::compute_from(&Vec::new()),
+                endorsements: Vec::new(),
+                denunciations: Vec::new(),
+            },
+            BlockHeaderSerializer::new(),
+            keypair,
+        )
+        .unwrap();
+        Block::new_verifiable(
+            Block {
+                header,
+                operations: operations.iter().map(|op| op.id).collect(),
+            },
+            BlockSerializer::new(),
+            keypair,
+        )
+        .unwrap()
+    }
+
+    fn indexed_slot(slot: Slot, complete: bool) -> IndexedSlot {
+        IndexedSlot {
+            slot,
+            block: None,
+            complete,
+            balances: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_indexed_slot_new() {
+        let keypair = KeyPair::generate(0).unwrap();
+        let slot = Slot::new(4, 1);
+        let operations = vec![operation(&keypair, 10), operation(&keypair, 11)];
+        let block = block(&keypair, slot, &operations);
+        let block_id = block.id;
+        let mut storage = Storage::create_root();
+        storage.store_operations(operations[..1].to_vec());
+        storage.store_block(block);
+
+        // an operation missing from storage makes the slot incomplete
+        let indexed = IndexedSlot::new(slot, Some(block_id), &LedgerChanges::default(), &storage);
+        assert!(!indexed.complete);
+        let indexed_block = indexed.block.unwrap();
+        assert_eq!(indexed_block.id, block_id);
+        assert_eq!(
+            indexed_block.creator,
+            Address::from_public_key(&keypair.get_public_key())
+        );
+        assert_eq!(indexed_block.parents.len(), THREAD_COUNT as usize);
+        assert_eq!(indexed_block.operation_count, 2);
+        assert_eq!(indexed_block.operations.len(), 1);
+        assert_eq!(indexed_block.operations[0].id, operations[0].id);
+        assert_eq!(indexed_block.operations[0].expire_period, 10);
+        assert_eq!(indexed_block.operations[0].operation_type, "Transaction");
+
+        storage.store_operations(operations[1..].to_vec());
+        let indexed = IndexedSlot::new(slot, Some(block_id), &LedgerChanges::default(), &storage);
+        assert!(indexed.complete);
+        assert_eq!(indexed.block.unwrap().operations.len(), 2);
+
+        // a block missing from storage makes the slot incomplete, a slot without block does not
+        let missing_block = BlockId(Hash::compute_from(b"missing"));
+        let indexed = IndexedSlot::new(
+            slot,
+            Some(missing_block),
+            &LedgerChanges::default(),
+            &storage,
+        );
+        assert!(indexed.block.is_none());
+        assert!(!indexed.complete);
+        let indexed = IndexedSlot::new(slot, None, &LedgerChanges::default(), &storage);
+        assert!(indexed.block.is_none());
+        assert!(indexed.complete);
+    }
+
+    #[test]
+    fn test_indexed_slot_balances() {
+        let addresses: Vec<Address> = (0..4)
+            .map(|_| Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key()))
+            .collect();
+        let mut ledger_changes = LedgerChanges::default();
+        ledger_changes.0.insert(
+            addresses[0],
+            SetUpdateOrDelete::Set(LedgerEntry {
+                balance: Amount::from_str("5").unwrap(),
+                ..Default::default()
+            }),
+        );
+        ledger_changes.0.insert(
+            addresses[1],
+            SetUpdateOrDelete::Update(LedgerEntryUpdate {
+                balance: SetOrKeep::Set(Amount::from_str("7").unwrap()),
+                ..Default::default()
+            }),
+        );
+        // only the bytecode or the datastore changed
+        ledger_changes.0.insert(
+            addresses[2],
+            SetUpdateOrDelete::Update(LedgerEntryUpdate::default()),
+        );
+        ledger_changes
+            .0
+            .insert(addresses[3], SetUpdateOrDelete::Delete);
+
+        let mut balances = IndexedSlot::new(
+            Slot::new(4, 1),
+            None,
+            &ledger_changes,
+            &Storage::create_root(),
+        )
+        .balances;
+        balances.sort_by_key(|(address, _)| addresses.iter().position(|a| a == address));
+        assert_eq!(
+            balances,
+            vec![
+                (addresses[0], Amount::from_str("5").unwrap()),
+                (addresses[1], Amount::from_str("7").unwrap()),
+                (addresses[3], Amount::zero()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_slot_gaps() {
+        let slot = Slot::new(4, 1);
+        // empty index, next slot, or rewind
+        assert!(slot_gaps(None, &indexed_slot(slot, true)).is_empty());
+        assert!(slot_gaps(Some(Slot::new(4, 0)), &indexed_slot(slot, true)).is_empty());
+        assert!(slot_gaps(Some(Slot::new(5, 0)), &indexed_slot(slot, true)).is_empty());
+        // slots not received
+        assert_eq!(
+            slot_gaps(Some(Slot::new(3, 5)), &indexed_slot(slot, true)),
+            vec![(Slot::new(3, 6), Slot::new(4, 0), GapReason::NotReceived)]
+        );
+        // block or operations not in storage
+        assert_eq!(
+            slot_gaps(Some(Slot::new(3, 5)), &indexed_slot(slot, false)),
+            vec![
+                (Slot::new(3, 6), Slot::new(4, 0), GapReason::NotReceived),
+                (slot, slot, GapReason::NotInStorage)
+            ]
+        );
+        assert_eq!(
+            slot_gaps(None, &indexed_slot(slot, false)),
+            vec![(slot, slot, GapReason::NotInStorage)]
+        );
+    }
+
+    async fn count(client: &Client, table: &str) -> i64 {
+        client
+            .query_one(&format!("SELECT COUNT(*) FROM {}", table), &[])
+            .await
+            .unwrap()
+            .get(0)
+    }
+
+    /// Requires a PostgreSQL database whose tables can be dropped, at the URL of the
+    /// `MASSA_INDEXER_TEST_DATABASE_URL` environment variable:
+    /// `cargo test -p massa-node --features postgres -- --ignored test_write_slot_rewind`
+    #[tokio::test]
+    #[ignore]
+    async fn test_write_slot_rewind() {
+        let settings = IndexerSettings {
+            enabled: true,
+            database_url: std::env::var("MASSA_INDEXER_TEST_DATABASE_URL").unwrap(),
+            queue_size: 10,
+            retry_delay: massa_time::MassaTime::from_millis(100),
+            allow_gaps: true,
+            tls: false,
+            tls_root_certificate: None,
+        };
+        let (mut client, _) = connect(&settings, None).await.unwrap();
+        client
+            .batch_execute(
+                "DROP TABLE IF EXISTS operations, blocks, balance_changes, indexer_state, indexer_gaps",
+            )
+            .await
+            .unwrap();
+        let (mut client, head) = connect(&settings, None).await.unwrap();
+        assert_eq!(head, None);
+
+        let keypair = KeyPair::generate(0).unwrap();
+        let address = Address::from_public_key(&keypair.get_public_key());
+        let mut storage = Storage::create_root();
+        let mut head = None;
+        for period in 1..=3 {
+            let slot = Slot::new(period, 0);
+            let operations = vec![operation(&keypair, period + 10)];
+            let block = block(&keypair, slot, &operations);
+            let block_id = block.id;
+            storage.store_operations(operations);
+            storage.store_block(block);
+            let mut ledger_changes = LedgerChanges::default();
+            ledger_changes.0.insert(
+                address,
+                SetUpdateOrDelete::Update(LedgerEntryUpdate {
+                    balance: SetOrKeep::Set(Amount::from_str(&period.to_string()).unwrap()),
+                    ..Default::default()
+                }),
+            );
+            let indexed = IndexedSlot::new(slot, Some(block_id), &ledger_changes, &storage);
+            let gaps = slot_gaps(head, &indexed);
+            write_slot(&mut client, head, &indexed, &gaps)
+                .await
+                .unwrap();
+            head = Some(slot);
+        }
+        assert_eq!(count(&client, "blocks").await, 3);
+        assert_eq!(count(&client, "operations").await, 3);
+        assert_eq!(count(&client, "balance_changes").await, 3);
+        // the slots were not consecutive
+        assert_eq!(count(&client, "indexer_gaps").await, 2);
+
+        // the node rewound to the period 2: the rows of the periods 2 and 3 are replaced
+        let indexed = IndexedSlot::new(Slot::new(2, 0), None, &LedgerChanges::default(), &storage);
+        write_slot(&mut client, head, &indexed, &[]).await.unwrap();
+        assert_eq!(count(&client, "blocks").await, 1);
+        assert_eq!(count(&client, "operations").await, 1);
+        assert_eq!(count(&client, "balance_changes").await, 1);
+        let gaps = client
+            .query(
+                "SELECT to_period, to_thread FROM indexer_gaps ORDER BY from_period",
+                &[],
+            )
+            .await
+            .unwrap();
+        assert_eq!(gaps.len(), 1);
+        assert_eq!(gaps[0].get::<_, i64>(0), 1);
+        assert_eq!(gaps[0].get::<_, i16>(1), THREAD_COUNT as i16 - 1);
+        let state = client
+            .query_one("SELECT period, thread FROM indexer_state", &[])
+            .await
+            .unwrap();
+        assert_eq!(state.get::<_, i64>(0), 2);
+    }
+}
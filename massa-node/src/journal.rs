@@ -0,0 +1,177 @@
+//! Event journal of the node: the significant events recorded with `massa_journal!` (reorgs,
+//! desyncs, bans, invalid blocks, bootstrap attempts) are appended as JSON lines to
+//! `logging.journal_path`, whatever the log level.
+//!
+//! When the journal gets larger than `logging.journal_max_size`, it is moved to a file with a `.1`
+//! suffix, replacing the previous one, and a new journal is started. The events are read back by
+//! the `get_journal_events` method of the private API, and broadcast to the running modules
+//! interested in them, such as the event sink.
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use massa_api_exports::journal::{previous_journal_path, JournalEvent};
+use massa_logging::{serde_json, JOURNAL_TARGET};
+use massa_time::MassaTime;
+use parking_lot::Mutex;
+use tokio::sync::broadcast;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Capacity of the broadcast of the events of the journal
+pub const JOURNAL_BROADCAST_CAPACITY: usize = 100;
+
+/// Layer writing the events of the journal to disk
+pub struct JournalLayer {
+    writer: Mutex<JournalWriter>,
+    events: broadcast::Sender<JournalEvent>,
+}
+
+impl JournalLayer {
+    /// Create a layer appending to the journal at `path`, rotated above `max_size` bytes,
+    /// and broadcasting the events to the subscribers of `events`
+    pub fn new(path: PathBuf, max_size: u64, events: broadcast::Sender<JournalEvent>) -> Self {
+        JournalLayer {
+            writer: Mutex::new(JournalWriter {
+                path,
+                max_size,
+                file: None,
+                size: 0,
+            }),
+            events,
+        }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for JournalLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if event.metadata().target() != JOURNAL_TARGET {
+            return;
+        }
+        let mut visitor = JournalVisitor::default();
+        event.record(&mut visitor);
+        let Ok(time) = MassaTime::now() else {
+            return;
+        };
+        let details = serde_json::from_str(&visitor.message)
+            .unwrap_or(serde_json::Value::String(visitor.message));
+        let journal_event = JournalEvent {
+            time,
+            kind: visitor.kind,
+            details,
+        };
+        if let Err(err) = self.writer.lock().append(&journal_event) {
+            // logging the error from here would record it again in the journal
+            eprintln!("could not write to the event journal: {}", err);
+        }
+        // there may be no subscriber
+        let _ = self.events.send(journal_event);
+    }
+}
+
+/// Collects the fields of an event of the journal
+#[derive(Default)]
+struct JournalVisitor {
+    kind: String,
+    message: String,
+}
+
+impl Visit for JournalVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "kind" => self.kind = value.to_string(),
+            "message" => self.message = value.to_string(),
+            _ => {}
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        match field.name() {
+            "kind" => self.kind = format!("{:?}", value),
+            "message" => self.message = format!("{:?}", value),
+            _ => {}
+        }
+    }
+}
+
+struct JournalWriter {
+    path: PathBuf,
+    max_size: u64,
+    /// journal file, opened at the first event
+    file: Option<File>,
+    /// size of the journal file
+    size: u64,
+}
+
+impl JournalWriter {
+    fn append(&mut self, event: &JournalEvent) -> std::io::Result<()> {
+        let mut line = serde_json::to_vec(event)?;
+        line.push(b'\n');
+        if self.file.is_none() {
+            if let Some(dir) = self.path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+            self.size = file.metadata()?.len();
+            self.file = Some(file);
+        }
+        if self.size > 0 && self.size + line.len() as u64 > self.max_size {
+            self.file = None;
+            std::fs::rename(&self.path, previous_journal_path(&self.path))?;
+            self.file = Some(File::create(&self.path)?);
+            self.size = 0;
+        }
+        let file = self.file.as_mut().expect("journal file opened above");
+        file.write_all(&line)?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_api_exports::journal::{read_journal, JournalFilter};
+
+    #[test]
+    fn test_journal_rotation() {
+        let dir = std::env::temp_dir().join(format!("massa_journal_test_{}", std::process::id()));
+        let path = dir.join("journal.jsonl");
+        let mut writer = JournalWriter {
+            path: path.clone(),
+            max_size: 120,
+            file: None,
+            size: 0,
+        };
+        for i in 0..6 {
+            writer
+                .append(&JournalEvent {
+                    time: MassaTime::from_millis(i),
+                    kind: if i % 2 == 0 { "reorg" } else { "peer_banned" }.to_string(),
+                    details: serde_json::json!({ "index": i }),
+                })
+                .unwrap();
+        }
+        assert!(previous_journal_path(&path).exists());
+
+        // the oldest events were dropped with the rotations, the others are read in order
+        let events = read_journal(&path, &JournalFilter::default()).unwrap();
+        assert!(!events.is_empty() && events.len() < 6);
+        assert_eq!(events.last().unwrap().time, MassaTime::from_millis(5));
+        assert!(events.windows(2).all(|pair| pair[0].time < pair[1].time));
+
+        let filter = JournalFilter {
+            kind: Some("reorg".to_string()),
+            ..Default::default()
+        };
+        let reorgs = read_journal(&path, &filter).unwrap();
+        assert!(reorgs.iter().all(|event| event.kind == "reorg"));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,132 @@
+//! Log filter of the node: a default level, set by `logging.level`, and levels per module that
+//! can be changed while the node is running with the `node_set_log_filter` private API method.
+//!
+//! Module levels are given as a comma-separated list of `module=level` directives, for example
+//! `massa_protocol_worker::handlers::block_handler=debug,massa_consensus_worker=trace`.
+//! A directive applies to the module and to its sub-modules. Only the logs of the massa modules
+//! are printed, whatever the filter.
+
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use tracing::info;
+use tracing_subscriber::{
+    filter::{LevelFilter, Targets},
+    reload, Registry,
+};
+
+/// Log level filter of a `logging.level` setting
+fn log_level_filter(level: usize) -> LevelFilter {
+    match level {
+        4 => LevelFilter::TRACE,
+        3 => LevelFilter::DEBUG,
+        2 => LevelFilter::INFO,
+        1 => LevelFilter::WARN,
+        _ => LevelFilter::ERROR,
+    }
+}
+
+/// Filter to add to the log layer, changed through a `LogFilter`
+pub type LogFilterLayer = reload::Layer<Targets, Registry>;
+
+/// Current log filter of the node
+struct LogFilterState {
+    /// default level, as in `logging.level`
+    level: usize,
+    /// levels per module
+    directives: String,
+}
+
+/// Handle used to change the log filter of the node
+#[derive(Clone)]
+pub struct LogFilter {
+    handle: reload::Handle<Targets, Registry>,
+    state: Arc<Mutex<LogFilterState>>,
+}
+
+impl LogFilter {
+    /// Create a filter with a default `level` and no module levels,
+    /// and the layer applying it
+    pub fn new(level: usize) -> (Self, LogFilterLayer) {
+        let (layer, handle) =
+            reload::Layer::new(Targets::new().with_default(log_level_filter(level)));
+        let log_filter = LogFilter {
+            handle,
+            state: Arc::new(Mutex::new(LogFilterState {
+                level,
+                directives: String::new(),
+            })),
+        };
+        (log_filter, layer)
+    }
+
+    /// Change the default level, keeping the module levels
+    pub fn set_level(&self, level: usize) -> Result<(), String> {
+        let mut state = self.state.lock();
+        self.apply(level, &state.directives)?;
+        state.level = level;
+        info!("log level set to {}", level);
+        Ok(())
+    }
+
+    /// Replace the module levels by `directives`. An empty string removes them.
+    pub fn set_directives(&self, directives: &str) -> Result<(), String> {
+        let mut state = self.state.lock();
+        self.apply(state.level, directives)?;
+        state.directives = directives.to_string();
+        info!("log levels per module set to \"{}\"", directives);
+        Ok(())
+    }
+
+    fn apply(&self, level: usize, directives: &str) -> Result<(), String> {
+        let targets = build_targets(level, directives)
+            .map_err(|err| format!("invalid log filter \"{}\": {}", directives, err))?;
+        self.handle
+            .reload(targets)
+            .map_err(|err| format!("could not change the log filter: {}", err))
+    }
+}
+
+/// Filter with a default `level` and the module levels of `directives`
+fn build_targets(
+    level: usize,
+    directives: &str,
+) -> Result<Targets, tracing_subscriber::filter::ParseError> {
+    let targets = if directives.trim().is_empty() {
+        Targets::new()
+    } else {
+        directives.parse::<Targets>()?
+    };
+    Ok(targets.with_default(log_level_filter(level)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing::Level;
+
+    #[test]
+    fn test_build_targets() {
+        let targets = build_targets(2, "").unwrap();
+        assert!(targets.would_enable("massa_consensus_worker::state", &Level::INFO));
+        assert!(!targets.would_enable("massa_consensus_worker::state", &Level::DEBUG));
+
+        let targets = build_targets(
+            2,
+            "massa_protocol_worker::handlers::block_handler=debug,massa_consensus_worker=trace",
+        )
+        .unwrap();
+        assert!(targets.would_enable(
+            "massa_protocol_worker::handlers::block_handler::retrieval",
+            &Level::DEBUG
+        ));
+        assert!(!targets.would_enable(
+            "massa_protocol_worker::handlers::operation_handler",
+            &Level::DEBUG
+        ));
+        assert!(targets.would_enable("massa_consensus_worker::state", &Level::TRACE));
+        assert!(!targets.would_enable("massa_execution_worker", &Level::DEBUG));
+
+        assert!(build_targets(2, "massa_consensus_worker=verbose").is_err());
+    }
+}
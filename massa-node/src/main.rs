@@ -6,60 +6,67 @@
 #![feature(ip)]
 extern crate massa_logging;
 
+use crate::api_servers::{ApiComponents, ApiServers};
+use crate::event_sink::EventSink;
+use crate::journal::{JournalLayer, JOURNAL_BROADCAST_CAPACITY};
+use crate::log_filter::LogFilter;
+use crate::memory_budget::CacheLimits;
 #[cfg(feature = "op_spammer")]
 use crate::operation_injector::start_operation_injector;
-use crate::settings::SETTINGS;
+use crate::reload::ConfigReloader;
+use crate::settings::{APISettings, SETTINGS};
+use crate::shutdown::{Managers, ShutdownController};
+use crate::watchdog::{ExitGuard, Watchdog, WatchdogAction};
 
 use crossbeam_channel::TryRecvError;
 use dialoguer::Password;
-use massa_api::{ApiServer, ApiV2, Private, Public, RpcServer, StopHandle, API};
 use massa_api_exports::config::APIConfig;
+use massa_api_exports::journal::JournalEvent;
 use massa_async_pool::AsyncPoolConfig;
 use massa_bootstrap::BootstrapError;
 use massa_bootstrap::{
-    get_state, start_bootstrap_server, BootstrapConfig, BootstrapManager, BootstrapTcpListener,
-    DefaultConnector,
+    get_state, start_bootstrap_server, BootstrapConfig, BootstrapTcpListener, DefaultConnector,
 };
 use massa_channel::receiver::MassaReceiver;
 use massa_channel::MassaChannel;
 use massa_consensus_exports::events::ConsensusEvent;
-use massa_consensus_exports::{ConsensusChannels, ConsensusConfig, ConsensusManager};
+use massa_consensus_exports::{ConsensusChannels, ConsensusConfig};
 use massa_consensus_worker::start_consensus_worker;
 use massa_db_exports::{MassaDBConfig, MassaDBController};
 use massa_db_worker::MassaDB;
 use massa_executed_ops::{ExecutedDenunciationsConfig, ExecutedOpsConfig};
 use massa_execution_exports::{
-    ExecutionChannels, ExecutionConfig, ExecutionManager, GasCosts, StorageCostsConstants,
+    ExecutionChannels, ExecutionConfig, GasCosts, StorageCostsConstants,
 };
 use massa_execution_worker::start_execution_worker;
-use massa_factory_exports::{FactoryChannels, FactoryConfig, FactoryManager};
-use massa_factory_worker::start_factory;
+use massa_factory_exports::{FactoryChannels, FactoryConfig, RemoteSignerConfig};
+use massa_factory_worker::{start_factory, RemoteHandshakeSigner};
 use massa_final_state::{FinalState, FinalStateConfig};
 use massa_grpc::config::GrpcConfig;
 use massa_grpc::server::MassaGrpc;
 use massa_ledger_exports::LedgerConfig;
 use massa_ledger_worker::FinalLedger;
-use massa_logging::massa_trace;
-use massa_metrics::{MassaMetrics, MetricsStopper};
+use massa_logging::{massa_trace, JOURNAL_TARGET};
+use massa_metrics::MassaMetrics;
 use massa_models::address::Address;
 use massa_models::config::constants::{
     BLOCK_REWARD, BOOTSTRAP_RANDOMNESS_SIZE_BYTES, CHANNEL_SIZE, CONSENSUS_BOOTSTRAP_PART_SIZE,
     DELTA_F0, DENUNCIATION_EXPIRE_PERIODS, ENDORSEMENT_COUNT, END_TIMESTAMP, GENESIS_KEY,
-    GENESIS_TIMESTAMP, INITIAL_DRAW_SEED, LEDGER_COST_PER_BYTE, LEDGER_ENTRY_BASE_COST,
-    LEDGER_ENTRY_DATASTORE_BASE_SIZE, MAX_ADVERTISE_LENGTH, MAX_ASK_BLOCKS_PER_MESSAGE,
-    MAX_ASYNC_GAS, MAX_ASYNC_MESSAGE_DATA, MAX_ASYNC_POOL_LENGTH, MAX_BLOCK_SIZE,
-    MAX_BOOTSTRAP_ASYNC_POOL_CHANGES, MAX_BOOTSTRAP_BLOCKS, MAX_BOOTSTRAP_ERROR_LENGTH,
-    MAX_BYTECODE_LENGTH, MAX_CONSENSUS_BLOCKS_IDS, MAX_DATASTORE_ENTRY_COUNT,
-    MAX_DATASTORE_KEY_LENGTH, MAX_DATASTORE_VALUE_LENGTH, MAX_DEFERRED_CREDITS_LENGTH,
-    MAX_DENUNCIATIONS_PER_BLOCK_HEADER, MAX_DENUNCIATION_CHANGES_LENGTH,
-    MAX_ENDORSEMENTS_PER_MESSAGE, MAX_EXECUTED_OPS_CHANGES_LENGTH, MAX_EXECUTED_OPS_LENGTH,
-    MAX_FUNCTION_NAME_LENGTH, MAX_GAS_PER_BLOCK, MAX_LEDGER_CHANGES_COUNT, MAX_LISTENERS_PER_PEER,
-    MAX_OPERATIONS_PER_BLOCK, MAX_OPERATIONS_PER_MESSAGE, MAX_OPERATION_DATASTORE_ENTRY_COUNT,
-    MAX_OPERATION_DATASTORE_KEY_LENGTH, MAX_OPERATION_DATASTORE_VALUE_LENGTH,
-    MAX_OPERATION_STORAGE_TIME, MAX_PARAMETERS_SIZE, MAX_PEERS_IN_ANNOUNCEMENT_LIST,
-    MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH, MAX_SIZE_CHANNEL_COMMANDS_CONNECTIVITY,
-    MAX_SIZE_CHANNEL_COMMANDS_PEERS, MAX_SIZE_CHANNEL_COMMANDS_PEER_TESTERS,
-    MAX_SIZE_CHANNEL_COMMANDS_PROPAGATION_BLOCKS,
+    GENESIS_TIMESTAMP, GENESIS_TIMESTAMP_OVERRIDE, INITIAL_DRAW_SEED, LEDGER_COST_PER_BYTE,
+    LEDGER_ENTRY_BASE_COST, LEDGER_ENTRY_DATASTORE_BASE_SIZE, MAX_ADVERTISE_LENGTH,
+    MAX_ASK_BLOCKS_PER_MESSAGE, MAX_ASYNC_GAS, MAX_ASYNC_MESSAGE_DATA, MAX_ASYNC_POOL_LENGTH,
+    MAX_BLOCK_SIZE, MAX_BOOTSTRAP_ASYNC_POOL_CHANGES, MAX_BOOTSTRAP_BLOCKS,
+    MAX_BOOTSTRAP_ERROR_LENGTH, MAX_BYTECODE_LENGTH, MAX_CONSENSUS_BLOCKS_IDS,
+    MAX_DATASTORE_ENTRY_COUNT, MAX_DATASTORE_KEY_LENGTH, MAX_DATASTORE_VALUE_LENGTH,
+    MAX_DEFERRED_CREDITS_LENGTH, MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
+    MAX_DENUNCIATION_CHANGES_LENGTH, MAX_ENDORSEMENTS_PER_MESSAGE, MAX_EXECUTED_OPS_CHANGES_LENGTH,
+    MAX_EXECUTED_OPS_LENGTH, MAX_FUNCTION_NAME_LENGTH, MAX_GAS_PER_BLOCK, MAX_LEDGER_CHANGES_COUNT,
+    MAX_LISTENERS_PER_PEER, MAX_OPERATIONS_PER_BLOCK, MAX_OPERATIONS_PER_MESSAGE,
+    MAX_OPERATION_DATASTORE_ENTRY_COUNT, MAX_OPERATION_DATASTORE_KEY_LENGTH,
+    MAX_OPERATION_DATASTORE_VALUE_LENGTH, MAX_OPERATION_STORAGE_TIME, MAX_PARAMETERS_SIZE,
+    MAX_PEERS_IN_ANNOUNCEMENT_LIST, MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH,
+    MAX_SIZE_CHANNEL_COMMANDS_CONNECTIVITY, MAX_SIZE_CHANNEL_COMMANDS_PEERS,
+    MAX_SIZE_CHANNEL_COMMANDS_PEER_TESTERS, MAX_SIZE_CHANNEL_COMMANDS_PROPAGATION_BLOCKS,
     MAX_SIZE_CHANNEL_COMMANDS_PROPAGATION_ENDORSEMENTS,
     MAX_SIZE_CHANNEL_COMMANDS_PROPAGATION_OPERATIONS, MAX_SIZE_CHANNEL_COMMANDS_RETRIEVAL_BLOCKS,
     MAX_SIZE_CHANNEL_COMMANDS_RETRIEVAL_ENDORSEMENTS,
@@ -76,14 +83,14 @@ use massa_models::config::{
     POOL_CONTROLLER_ENDORSEMENTS_CHANNEL_SIZE, POOL_CONTROLLER_OPERATIONS_CHANNEL_SIZE,
 };
 use massa_models::slot::Slot;
-use massa_pool_exports::{PoolChannels, PoolConfig, PoolManager};
+use massa_pool_exports::{PoolChannels, PoolConfig};
 use massa_pool_worker::start_pool_controller;
-use massa_pos_exports::{PoSConfig, SelectorConfig, SelectorManager};
+use massa_pos_exports::{PoSConfig, SelectorConfig};
 use massa_pos_worker::start_selector_worker;
-use massa_protocol_exports::{ProtocolConfig, ProtocolManager, TransportType};
+use massa_protocol_exports::{HandshakeSigner, ProtocolConfig, ProtocolController, TransportType};
 use massa_protocol_worker::{create_protocol_controller, start_protocol_controller};
 use massa_storage::Storage;
-use massa_time::MassaTime;
+use massa_time::{Clock, MassaTime, MonotonicClock, SystemClock};
 use massa_versioning::mips::get_mip_list;
 use massa_versioning::versioning::{MipStatsConfig, MipStore};
 use massa_wallet::Wallet;
@@ -91,45 +98,70 @@ use num::rational::Ratio;
 use parking_lot::RwLock;
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Condvar, Mutex};
 use std::time::Duration;
 use std::{path::Path, process, sync::Arc};
 use structopt::StructOpt;
 use tokio::sync::broadcast;
 use tracing::{debug, error, info, warn};
-use tracing_subscriber::filter::{filter_fn, LevelFilter};
-
+use tracing_subscriber::filter::filter_fn;
+
+mod api_servers;
+mod bench;
+mod check_config;
+mod event_sink;
+#[cfg(feature = "postgres")]
+mod indexer;
+mod journal;
+mod log_filter;
+mod memory_budget;
 #[cfg(feature = "op_spammer")]
 mod operation_injector;
+mod reload;
 mod settings;
+mod shutdown;
+#[cfg(feature = "otlp")]
+mod telemetry;
+mod watchdog;
 
 async fn launch(
     args: &Args,
     node_wallet: Arc<RwLock<Wallet>>,
     sig_int_toggled: Arc<(Mutex<bool>, Condvar)>,
+    reload_requested: Arc<AtomicBool>,
+    log_filter: LogFilter,
+    journal_events: broadcast::Sender<JournalEvent>,
+    api_settings: &APISettings,
 ) -> (
     MassaReceiver<ConsensusEvent>,
-    Option<BootstrapManager>,
-    Box<dyn ConsensusManager>,
-    Box<dyn ExecutionManager>,
-    Box<dyn SelectorManager>,
-    Box<dyn PoolManager>,
-    Box<dyn ProtocolManager>,
-    Box<dyn FactoryManager>,
-    StopHandle,
-    StopHandle,
-    StopHandle,
-    Option<massa_grpc::server::StopHandle>,
-    MetricsStopper,
+    Box<dyn ProtocolController>,
+    ApiServers,
+    ShutdownController,
 ) {
     info!("Node version : {}", *VERSION);
+    if let Some(genesis_timestamp) = *GENESIS_TIMESTAMP_OVERRIDE {
+        warn!(
+            "the genesis timestamp is overridden by {}: the network starts at {} ({} ms)",
+            if args.genesis_timestamp.is_some() {
+                "--genesis-timestamp"
+            } else {
+                "the GENESIS_TIMESTAMP environment variable"
+            },
+            genesis_timestamp.format_instant(),
+            genesis_timestamp.to_millis()
+        );
+    }
+    if SETTINGS.protocol.keypair_seed.is_some() && !cfg!(feature = "sandbox") {
+        warn!("protocol.keypair_seed is only used in sandbox mode, it is ignored");
+    }
     let now = MassaTime::now().expect("could not get now time");
     // Do not start if genesis is in the future. This is meant to prevent nodes
     // from desync if the bootstrap nodes keep a previous ledger
+    // (an ephemeral network whose genesis timestamp is overridden starts once every node is launched)
     #[cfg(all(not(feature = "sandbox"), not(feature = "bootstrap_server")))]
     {
-        if *GENESIS_TIMESTAMP > now {
+        if *GENESIS_TIMESTAMP > now && GENESIS_TIMESTAMP_OVERRIDE.is_none() {
             let (days, hours, mins, secs) = GENESIS_TIMESTAMP
                 .saturating_sub(now)
                 .days_hours_mins_secs()
@@ -236,9 +268,15 @@ async fn launch(
         SETTINGS.metrics.tick_delay.to_duration(),
     );
 
+    // An interrupted bootstrap resumes on the ledger it was writing
+    let resume_bootstrap =
+        args.restart_from_snapshot_at_period.is_none() && SETTINGS.bootstrap.manifest_path.exists();
+
     // Remove current disk ledger if there is one and we don't want to restart from snapshot
     // NOTE: this is temporary, since we cannot currently handle bootstrap from remaining ledger
-    if args.keep_ledger || args.restart_from_snapshot_at_period.is_some() {
+    if resume_bootstrap {
+        info!("Keeping the ledger of the interrupted bootstrap");
+    } else if args.keep_ledger || args.restart_from_snapshot_at_period.is_some() {
         info!("Loading old ledger for next episode");
     } else {
         if SETTINGS.ledger.disk_ledger_path.exists() {
@@ -308,7 +346,7 @@ async fn launch(
                 Box::new(ledger),
                 selector_controller.clone(),
                 mip_store.clone(),
-                true,
+                !resume_bootstrap,
             )
             .expect("could not init final state"),
         },
@@ -331,6 +369,8 @@ async fn launch(
         max_clock_delta: SETTINGS.bootstrap.max_clock_delta,
         cache_duration: SETTINGS.bootstrap.cache_duration,
         keep_ledger: args.keep_ledger,
+        manifest_path: SETTINGS.bootstrap.manifest_path.clone(),
+        state_hash_quorum: SETTINGS.bootstrap.state_hash_quorum,
         max_listeners_per_peer: MAX_LISTENERS_PER_PEER as u32,
         max_simultaneous_bootstraps: SETTINGS.bootstrap.max_simultaneous_bootstraps,
         per_ip_min_interval: SETTINGS.bootstrap.per_ip_min_interval,
@@ -474,7 +514,10 @@ async fn launch(
         snip_amount: SETTINGS.execution.snip_amount,
         roll_count_to_slash_on_denunciation: ROLL_COUNT_TO_SLASH_ON_DENUNCIATION,
         denunciation_expire_periods: DENUNCIATION_EXPIRE_PERIODS,
-        broadcast_enabled: SETTINGS.api.enable_broadcast,
+        // the event sink and the indexer read the finalized slots
+        broadcast_enabled: SETTINGS.api.enable_broadcast
+            || SETTINGS.event_sink.enabled
+            || SETTINGS.indexer.enabled,
         broadcast_slot_execution_output_channel_capacity: SETTINGS
             .execution
             .broadcast_slot_execution_output_channel_capacity,
@@ -497,6 +540,39 @@ async fn launch(
         massa_metrics.clone(),
     );
 
+    // publication of the chain events to a message broker
+    let event_sink = SETTINGS.event_sink.enabled.then(|| {
+        EventSink::start(
+            SETTINGS.event_sink.clone(),
+            execution_channels.slot_execution_output_sender.subscribe(),
+            journal_events.subscribe(),
+            shared_storage.clone_without_refs(),
+        )
+    });
+    #[cfg(feature = "postgres")]
+    let indexer = SETTINGS.indexer.enabled.then(|| {
+        indexer::Indexer::start(
+            SETTINGS.indexer.clone(),
+            execution_channels.slot_execution_output_sender.subscribe(),
+            shared_storage.clone_without_refs(),
+        )
+    });
+    #[cfg(not(feature = "postgres"))]
+    if SETTINGS.indexer.enabled {
+        warn!(
+            "the node is built without the `postgres` feature, the final chain data is not indexed"
+        );
+    }
+
+    // limits of the in-memory caches, fitted in the memory budget if one is set
+    let cache_limits = CacheLimits::from_settings(&SETTINGS);
+    if let Some(budget) = SETTINGS.memory.budget_mb {
+        info!(
+            "Cache limits fitted in a memory budget of {} MB: {:?}",
+            budget, cache_limits
+        );
+    }
+
     // launch pool controller
     let pool_config = PoolConfig {
         thread_count: THREAD_COUNT,
@@ -506,11 +582,14 @@ async fn launch(
         max_block_endorsement_count: ENDORSEMENT_COUNT,
         operation_validity_periods: OPERATION_VALIDITY_PERIODS,
         max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
-        max_operation_pool_size: SETTINGS.pool.max_operation_pool_size,
+        max_operation_pool_size: cache_limits.max_operation_pool_size,
         max_operation_pool_excess_items: SETTINGS.pool.max_operation_pool_excess_items,
+        max_operations_per_sender: SETTINGS.pool.max_operations_per_sender,
         operation_pool_refresh_interval: SETTINGS.pool.operation_pool_refresh_interval,
+        operations_file_path: SETTINGS.pool.operations_file_path.clone(),
+        operations_save_interval: SETTINGS.pool.operations_save_interval,
         operation_max_future_start_delay: SETTINGS.pool.operation_max_future_start_delay,
-        max_endorsements_pool_size_per_thread: SETTINGS.pool.max_endorsements_pool_size_per_thread,
+        max_endorsements_pool_size_per_thread: cache_limits.max_endorsements_pool_size_per_thread,
         operations_channel_size: POOL_CONTROLLER_OPERATIONS_CHANNEL_SIZE,
         endorsements_channel_size: POOL_CONTROLLER_ENDORSEMENTS_CHANNEL_SIZE,
         denunciations_channel_size: POOL_CONTROLLER_DENUNCIATIONS_CHANNEL_SIZE,
@@ -548,12 +627,12 @@ async fn launch(
     let protocol_config = ProtocolConfig {
         thread_count: THREAD_COUNT,
         ask_block_timeout: SETTINGS.protocol.ask_block_timeout,
-        max_known_blocks_size: SETTINGS.protocol.max_known_blocks_size,
+        max_known_blocks_size: cache_limits.max_known_blocks_size,
         max_node_known_blocks_size: SETTINGS.protocol.max_node_known_blocks_size,
         max_node_wanted_blocks_size: SETTINGS.protocol.max_node_wanted_blocks_size,
-        max_known_ops_size: SETTINGS.protocol.max_known_ops_size,
+        max_known_ops_size: cache_limits.max_known_ops_size,
         max_node_known_ops_size: SETTINGS.protocol.max_node_known_ops_size,
-        max_known_endorsements_size: SETTINGS.protocol.max_known_endorsements_size,
+        max_known_endorsements_size: cache_limits.max_known_endorsements_size,
         max_node_known_endorsements_size: SETTINGS.protocol.max_node_known_endorsements_size,
         max_simultaneous_ask_blocks_per_node: SETTINGS
             .protocol
@@ -583,7 +662,17 @@ async fn launch(
         initial_peers: SETTINGS.protocol.initial_peers_file.clone(),
         listeners,
         keypair_file: SETTINGS.protocol.keypair_file.clone(),
-        max_known_blocks_saved_size: SETTINGS.protocol.max_known_blocks_size,
+        previous_keypair_file: Some(SETTINGS.protocol.previous_keypair_file.clone()),
+        keypair_seed: if cfg!(feature = "sandbox") {
+            SETTINGS.protocol.keypair_seed.clone()
+        } else {
+            None
+        },
+        message_record_file: SETTINGS.protocol.message_record_file.clone(),
+        message_record_max_file_size: SETTINGS.protocol.message_record_max_file_size,
+        message_record_rotated_files: SETTINGS.protocol.message_record_rotated_files,
+        chaos: SETTINGS.protocol.chaos,
+        max_known_blocks_saved_size: cache_limits.max_known_blocks_size,
         asked_operations_buffer_capacity: SETTINGS.protocol.asked_operations_buffer_capacity,
         thread_tester_count: SETTINGS.protocol.thread_tester_count,
         max_operation_storage_time: MAX_OPERATION_STORAGE_TIME,
@@ -618,7 +707,12 @@ async fn launch(
         read_write_limit_bytes_per_second: SETTINGS.protocol.read_write_limit_bytes_per_second
             as u128,
         try_connection_timer: SETTINGS.protocol.try_connection_timer,
+        isolation_threshold: SETTINGS.protocol.isolation_threshold,
         max_in_connections: SETTINGS.protocol.max_in_connections,
+        max_concurrent_handshakes: SETTINGS.protocol.max_concurrent_handshakes,
+        max_handshakes_per_ip: SETTINGS.protocol.max_handshakes_per_ip,
+        handshake_timeout: SETTINGS.protocol.handshake_timeout,
+        message_rate_classes: SETTINGS.protocol.message_rate_classes,
         timeout_connection: SETTINGS.protocol.timeout_connection,
         message_timeout: SETTINGS.protocol.message_timeout,
         routable_ip: SETTINGS
@@ -640,18 +734,21 @@ async fn launch(
         thread_count: THREAD_COUNT,
         t0: T0,
         genesis_key: GENESIS_KEY.clone(),
-        max_discarded_blocks: SETTINGS.consensus.max_discarded_blocks,
-        max_future_processing_blocks: SETTINGS.consensus.max_future_processing_blocks,
-        max_dependency_blocks: SETTINGS.consensus.max_dependency_blocks,
+        max_discarded_blocks: cache_limits.max_discarded_blocks,
+        max_future_processing_blocks: cache_limits.max_future_processing_blocks,
+        max_dependency_blocks: cache_limits.max_dependency_blocks,
         delta_f0: DELTA_F0,
         operation_validity_periods: OPERATION_VALIDITY_PERIODS,
         periods_per_cycle: PERIODS_PER_CYCLE,
         stats_timespan: SETTINGS.consensus.stats_timespan,
+        partition_detection_timespan: SETTINGS.consensus.partition_detection_timespan,
         force_keep_final_periods: SETTINGS.consensus.force_keep_final_periods,
         endorsement_count: ENDORSEMENT_COUNT,
         block_db_prune_interval: SETTINGS.consensus.block_db_prune_interval,
         max_gas_per_block: MAX_GAS_PER_BLOCK,
         channel_size: CHANNEL_SIZE,
+        channel_overflow_policy: SETTINGS.consensus.channel_overflow_policy,
+        max_send_wait: SETTINGS.consensus.max_send_wait,
         bootstrap_part_size: CONSENSUS_BOOTSTRAP_PART_SIZE,
         broadcast_enabled: SETTINGS.api.enable_broadcast,
         broadcast_blocks_headers_channel_capacity: SETTINGS
@@ -665,6 +762,7 @@ async fn launch(
         force_keep_final_periods_without_ops: SETTINGS
             .consensus
             .force_keep_final_periods_without_ops,
+        blocks_file_path: SETTINGS.consensus.blocks_file_path.clone(),
     };
 
     let (consensus_event_sender, consensus_event_receiver) =
@@ -686,14 +784,37 @@ async fn launch(
         .0,
     };
 
+    // the slot deadlines of consensus and of the factory follow the system time progressively
+    let slot_clock: Arc<dyn Clock> = Arc::new(
+        MonotonicClock::new(Arc::new(SystemClock::new(
+            SETTINGS.consensus.clock_compensation_millis,
+        )))
+        .expect("could not read the system time"),
+    );
     let (consensus_controller, consensus_manager) = start_consensus_worker(
         consensus_config,
         consensus_channels.clone(),
         bootstrap_state.graph,
         shared_storage.clone(),
         massa_metrics.clone(),
+        slot_clock.clone(),
     );
 
+    let remote_signer_config = SETTINGS
+        .factory
+        .remote_signer
+        .as_ref()
+        .map(|remote_signer| RemoteSignerConfig {
+            url: remote_signer.url.clone(),
+            timeout: remote_signer.timeout,
+            public_keys: remote_signer.public_keys.clone(),
+        });
+    let handshake_signer = remote_signer_config.as_ref().map(|cfg| {
+        Arc::new(
+            RemoteHandshakeSigner::new(cfg).expect("could not create the remote signer client"),
+        ) as Arc<dyn HandshakeSigner>
+    });
+
     let (protocol_manager, keypair, node_id) = start_protocol_controller(
         protocol_config.clone(),
         selector_controller.clone(),
@@ -704,6 +825,7 @@ async fn launch(
         protocol_channels,
         mip_store.clone(),
         massa_metrics.clone(),
+        handshake_signer,
     )
     .expect("could not start protocol controller");
 
@@ -722,6 +844,8 @@ async fn launch(
         stop_production_when_zero_connections: SETTINGS
             .factory
             .stop_production_when_zero_connections,
+        stop_production_on_partition: SETTINGS.factory.stop_production_on_partition,
+        remote_signer: remote_signer_config,
     };
     let factory_channels = FactoryChannels {
         selector: selector_controller.clone(),
@@ -735,6 +859,7 @@ async fn launch(
         node_wallet.clone(),
         factory_channels,
         mip_store.clone(),
+        slot_clock,
     );
 
     let bootstrap_manager = bootstrap_config.listen_addr.map(|addr| {
@@ -759,9 +884,10 @@ async fn launch(
     });
 
     let api_config: APIConfig = APIConfig {
-        bind_private: SETTINGS.api.bind_private,
-        bind_public: SETTINGS.api.bind_public,
-        bind_api: SETTINGS.api.bind_api,
+        // the addresses may have been reloaded since the start of the node
+        bind_private: api_settings.bind_private,
+        bind_public: api_settings.bind_public,
+        bind_api: api_settings.bind_api,
         draw_lookahead_period_count: SETTINGS.api.draw_lookahead_period_count,
         max_arguments: SETTINGS.api.max_arguments,
         openrpc_spec_path: SETTINGS.api.openrpc_spec_path.clone(),
@@ -770,9 +896,14 @@ async fn launch(
         max_request_body_size: SETTINGS.api.max_request_body_size,
         max_response_body_size: SETTINGS.api.max_response_body_size,
         max_connections: SETTINGS.api.max_connections,
+        max_block_graph_page_size: SETTINGS.api.max_block_graph_page_size,
         max_subscriptions_per_connection: SETTINGS.api.max_subscriptions_per_connection,
         max_log_length: SETTINGS.api.max_log_length,
         allow_hosts: SETTINGS.api.allow_hosts.clone(),
+        cors_allowed_origins: SETTINGS.api.cors_allowed_origins.clone(),
+        max_requests_per_second: SETTINGS.api.max_requests_per_second,
+        trust_forwarded_for: SETTINGS.api.trust_forwarded_for,
+        max_concurrent_requests: SETTINGS.api.max_concurrent_requests,
         batch_request_limit: SETTINGS.api.batch_request_limit,
         ping_interval: SETTINGS.api.ping_interval,
         enable_http: SETTINGS.api.enable_http,
@@ -788,32 +919,12 @@ async fn launch(
         genesis_timestamp: *GENESIS_TIMESTAMP,
         t0: T0,
         periods_per_cycle: PERIODS_PER_CYCLE,
+        operation_validity_periods: OPERATION_VALIDITY_PERIODS,
         last_start_period: final_state.read().last_start_period,
+        memory_budget_bytes: SETTINGS.memory.budget_bytes(),
+        journal_path: SETTINGS.logging.journal_path.clone(),
     };
 
-    // spawn Massa API
-    let api = API::<ApiV2>::new(
-        consensus_controller.clone(),
-        consensus_channels.clone(),
-        execution_controller.clone(),
-        pool_channels.clone(),
-        api_config.clone(),
-        *VERSION,
-    );
-    let api_handle = api
-        .serve(&SETTINGS.api.bind_api, &api_config)
-        .await
-        .expect("failed to start MASSA API");
-
-    info!(
-        "API | EXPERIMENTAL JsonRPC | listening on: {}",
-        &SETTINGS.api.bind_api
-    );
-
-    // Disable WebSockets for Private and Public API's
-    let mut api_config = api_config.clone();
-    api_config.enable_ws = false;
-
     // Whether to spawn gRPC API
     let grpc_handle = if SETTINGS.grpc.enabled {
         let grpc_config = GrpcConfig {
@@ -877,8 +988,8 @@ async fn launch(
             consensus_controller: consensus_controller.clone(),
             consensus_channels: consensus_channels.clone(),
             execution_controller: execution_controller.clone(),
-            execution_channels,
-            pool_channels,
+            execution_channels: execution_channels.clone(),
+            pool_channels: pool_channels.clone(),
             pool_command_sender: pool_controller.clone(),
             protocol_command_sender: protocol_controller.clone(),
             selector_controller: selector_controller.clone(),
@@ -920,45 +1031,30 @@ async fn launch(
         args.nb_op,
     );
 
-    // spawn private API
-    let api_private = API::<Private>::new(
-        protocol_controller.clone(),
-        execution_controller.clone(),
-        api_config.clone(),
-        sig_int_toggled,
-        node_wallet,
-    );
-    let api_private_handle = api_private
-        .serve(&SETTINGS.api.bind_private, &api_config)
-        .await
-        .expect("failed to start PRIVATE API");
-    info!(
-        "API | PRIVATE JsonRPC | listening on: {}",
-        api_config.bind_private
-    );
-
-    // spawn public API
-    let api_public = API::<Public>::new(
-        consensus_controller.clone(),
-        execution_controller.clone(),
-        api_config.clone(),
-        selector_controller.clone(),
-        pool_controller.clone(),
-        protocol_controller.clone(),
-        protocol_config.clone(),
-        *VERSION,
-        node_id,
-        shared_storage.clone(),
-        mip_store.clone(),
-    );
-    let api_public_handle = api_public
-        .serve(&SETTINGS.api.bind_public, &api_config)
-        .await
-        .expect("failed to start PUBLIC API");
-    info!(
-        "API | PUBLIC JsonRPC | listening on: {}",
-        api_config.bind_public
-    );
+    // spawn EXPERIMENTAL, PRIVATE and PUBLIC API's
+    let api_servers = ApiServers::start(
+        ApiComponents {
+            consensus_controller: consensus_controller.clone(),
+            consensus_channels: consensus_channels.clone(),
+            execution_controller: execution_controller.clone(),
+            execution_channels,
+            pool_controller: pool_controller.clone(),
+            pool_channels,
+            protocol_controller: protocol_controller.clone(),
+            protocol_config: protocol_config.clone(),
+            selector_controller: selector_controller.clone(),
+            storage: shared_storage.clone(),
+            node_id,
+            mip_store: mip_store.clone(),
+            node_wallet,
+            sig_int_toggled,
+            reload_requested,
+            log_filter: Arc::new(move |directives: &str| log_filter.set_directives(directives)),
+            start_time: now,
+        },
+        api_config,
+    )
+    .await;
 
     #[cfg(feature = "deadlock_detection")]
     {
@@ -989,116 +1085,65 @@ async fn launch(
             })
             .expect("failed to spawn thread : deadlock-detection");
     }
-    (
-        consensus_event_receiver,
-        bootstrap_manager,
-        consensus_manager,
-        execution_manager,
-        selector_manager,
-        pool_manager,
-        protocol_manager,
-        factory_manager,
-        api_private_handle,
-        api_public_handle,
-        api_handle,
+    let shutdown_controller = ShutdownController::new(
+        Managers {
+            bootstrap_manager,
+            consensus_manager,
+            execution_manager,
+            selector_manager,
+            pool_manager,
+            protocol_manager,
+            factory_manager,
+        },
         grpc_handle,
         metrics_stopper,
+        event_sink,
+        db,
+    );
+    #[cfg(feature = "postgres")]
+    let shutdown_controller = shutdown_controller.with_indexer(indexer);
+    (
+        consensus_event_receiver,
+        protocol_controller,
+        api_servers,
+        shutdown_controller,
     )
 }
 
-struct Managers {
-    bootstrap_manager: Option<BootstrapManager>,
-    consensus_manager: Box<dyn ConsensusManager>,
-    execution_manager: Box<dyn ExecutionManager>,
-    selector_manager: Box<dyn SelectorManager>,
-    pool_manager: Box<dyn PoolManager>,
-    protocol_manager: Box<dyn ProtocolManager>,
-    factory_manager: Box<dyn FactoryManager>,
-}
-
-async fn stop(
-    _consensus_event_receiver: MassaReceiver<ConsensusEvent>,
-    Managers {
-        bootstrap_manager,
-        mut execution_manager,
-        mut consensus_manager,
-        mut selector_manager,
-        mut pool_manager,
-        mut protocol_manager,
-        mut factory_manager,
-    }: Managers,
-    api_private_handle: StopHandle,
-    api_public_handle: StopHandle,
-    api_handle: StopHandle,
-    grpc_handle: Option<massa_grpc::server::StopHandle>,
-    mut metrics_stopper: MetricsStopper,
-) {
-    // stop bootstrap
-    if let Some(bootstrap_manager) = bootstrap_manager {
-        bootstrap_manager
-            .stop()
-            .expect("bootstrap server shutdown failed")
-    }
-
-    info!("Start stopping API's: gRPC, EXPERIMENTAL, PUBLIC, PRIVATE");
-
-    // stop Massa gRPC API
-    if let Some(handle) = grpc_handle {
-        handle.stop();
-    }
-
-    // stop Massa API
-    api_handle.stop().await;
-    info!("API | EXPERIMENTAL JsonRPC | stopped");
-
-    // stop public API
-    api_public_handle.stop().await;
-    info!("API | PUBLIC JsonRPC | stopped");
-
-    // stop private API
-    api_private_handle.stop().await;
-    info!("API | PRIVATE JsonRPC | stopped");
-
-    // stop metrics
-    metrics_stopper.stop();
-
-    // stop factory
-    factory_manager.stop();
-
-    // stop protocol controller
-    protocol_manager.stop();
-
-    // stop consensus
-    consensus_manager.stop();
-
-    // stop pool
-    pool_manager.stop();
-
-    // stop execution controller
-    execution_manager.stop();
-
-    // stop selector controller
-    selector_manager.stop();
-
-    // stop pool controller
-    // TODO
-    //let protocol_pool_event_receiver = pool_manager.stop().await.expect("pool shutdown failed");
-
-    // note that FinalLedger gets destroyed as soon as its Arc count goes to zero
-}
-
 #[derive(StructOpt)]
 struct Args {
     #[structopt(long = "keep-ledger")]
     keep_ledger: bool,
     /// Wallet password
-    #[structopt(short = "p", long = "pwd")]
+    #[structopt(
+        short = "p",
+        long = "pwd",
+        env = "MASSA_NODE_WALLET_PASSWORD",
+        hide_env_values = true
+    )]
     password: Option<String>,
 
+    /// Store the wallet password in the OS keyring once entered, to read it from there afterwards
+    #[structopt(long = "save-pwd-in-keyring")]
+    save_password_in_keyring: bool,
+
     /// restart_from_snapshot_at_period
     #[structopt(long = "restart-from-snapshot-at-period")]
     restart_from_snapshot_at_period: Option<u64>,
 
+    /// genesis timestamp in milliseconds, or `+N` to start an ephemeral network at the first multiple of N seconds
+    /// at least N seconds after launch. Overrides the `GENESIS_TIMESTAMP` environment variable
+    #[structopt(long = "genesis-timestamp")]
+    genesis_timestamp: Option<String>,
+
+    /// Check the configuration and the key files and contact the bootstrap servers, then exit without starting the node
+    #[structopt(long = "check-config")]
+    check_config: bool,
+
+    /// Measure the hashing, signature and disk speed of the machine against the staking requirements, then exit
+    #[structopt(long = "bench")]
+    bench: bool,
+
     #[cfg(feature = "op_spammer")]
     /// number of operations
     #[structopt(
@@ -1121,8 +1166,36 @@ struct Args {
     dl_interval: u64,
 }
 
-/// Load wallet, asking for passwords if necessary
-fn load_wallet(password: Option<String>, path: &Path) -> anyhow::Result<Arc<RwLock<Wallet>>> {
+/// Keyring entry of the password of the wallet at `path`
+#[cfg(feature = "keyring")]
+fn keyring_entry(path: &Path) -> Option<keyring::Entry> {
+    keyring::Entry::new("massa-node", &path.to_string_lossy())
+        .map_err(|err| warn!("could not access the OS keyring: {}", err))
+        .ok()
+}
+
+/// Load wallet, asking for passwords if necessary.
+/// The password is taken from the command line or the environment, then from the OS keyring
+/// if the `keyring` feature is enabled, and is prompted for otherwise.
+fn load_wallet(
+    password: Option<String>,
+    save_password_in_keyring: bool,
+    path: &Path,
+) -> anyhow::Result<Arc<RwLock<Wallet>>> {
+    #[cfg(feature = "keyring")]
+    let password = password.or_else(|| {
+        keyring_entry(path).and_then(|entry| match entry.get_password() {
+            Ok(password) => Some(password),
+            Err(keyring::Error::NoEntry) => None,
+            Err(err) => {
+                warn!(
+                    "could not read the wallet password from the OS keyring: {}",
+                    err
+                );
+                None
+            }
+        })
+    });
     let password = if path.is_file() {
         password.unwrap_or_else(|| {
             Password::new()
@@ -1139,14 +1212,27 @@ fn load_wallet(password: Option<String>, path: &Path) -> anyhow::Result<Arc<RwLo
                 .expect("IO error: Password reading failed, staking keys file couldn't be created")
         })
     };
-    Ok(Arc::new(RwLock::new(Wallet::new(
-        PathBuf::from(path),
-        password,
-    )?)))
+    let wallet = Wallet::new(PathBuf::from(path), password.clone())?;
+    if save_password_in_keyring {
+        #[cfg(feature = "keyring")]
+        if let Some(entry) = keyring_entry(path) {
+            entry.set_password(&password)?;
+        }
+        #[cfg(not(feature = "keyring"))]
+        warn!("the node is built without the `keyring` feature, the wallet password is not saved");
+    }
+    Ok(Arc::new(RwLock::new(wallet)))
 }
 
 #[paw::main]
 fn main(args: Args) -> anyhow::Result<()> {
+    if args.check_config {
+        return check_config::check_config(args.password);
+    }
+    if args.bench {
+        return bench::run_bench();
+    }
+
     let tokio_rt = tokio::runtime::Builder::new_multi_thread()
         .thread_name_fn(|| {
             static ATOMIC_ID: AtomicUsize = AtomicUsize::new(0);
@@ -1163,23 +1249,58 @@ fn main(args: Args) -> anyhow::Result<()> {
 async fn run(args: Args) -> anyhow::Result<()> {
     let mut cur_args = args;
     use tracing_subscriber::prelude::*;
+    // the log levels can be changed while the node is running
+    let (log_filter, log_filter_layer) = LogFilter::new(SETTINGS.logging.level);
     // spawn the console server in the background, returning a `Layer`:
     let tracing_layer = tracing_subscriber::fmt::layer()
-        .with_filter(match SETTINGS.logging.level {
-            4 => LevelFilter::TRACE,
-            3 => LevelFilter::DEBUG,
-            2 => LevelFilter::INFO,
-            1 => LevelFilter::WARN,
-            _ => LevelFilter::ERROR,
-        })
+        .with_filter(log_filter_layer)
         .with_filter(filter_fn(|metadata| {
-            metadata.target().starts_with("massa") // ignore non-massa logs
+            // ignore non-massa logs, and the journal events that are written to their own file
+            metadata.target().starts_with("massa") && metadata.target() != JOURNAL_TARGET
         }));
+    let (journal_events, _) = broadcast::channel(JOURNAL_BROADCAST_CAPACITY);
+    let journal_layer = JournalLayer::new(
+        SETTINGS.logging.journal_path.clone(),
+        SETTINGS.logging.journal_max_size,
+        journal_events.clone(),
+    )
+    .with_filter(filter_fn(|metadata| metadata.target() == JOURNAL_TARGET));
     // build a `Subscriber` by combining layers with a `tracing_subscriber::Registry`:
-    tracing_subscriber::registry()
+    let subscriber = tracing_subscriber::registry()
         // add the console layer to the subscriber or default layers...
         .with(tracing_layer)
-        .init();
+        .with(journal_layer);
+    #[cfg(feature = "otlp")]
+    let subscriber = subscriber.with(
+        SETTINGS
+            .otlp
+            .enabled
+            .then(|| telemetry::otlp_tracer(&SETTINGS.otlp))
+            .flatten()
+            .map(|tracer| {
+                tracing_opentelemetry::layer()
+                    .with_tracer(tracer)
+                    .with_filter(filter_fn(|metadata| {
+                        metadata.target().starts_with("massa")
+                            && metadata.target() != JOURNAL_TARGET
+                            && *metadata.level() <= tracing::Level::INFO
+                    }))
+            }),
+    );
+    subscriber.init();
+
+    #[cfg(feature = "otlp")]
+    let otlp_meter_provider = SETTINGS
+        .otlp
+        .enabled
+        .then(|| telemetry::start_metrics_export(&SETTINGS.otlp))
+        .flatten();
+    #[cfg(not(feature = "otlp"))]
+    if SETTINGS.otlp.enabled {
+        warn!(
+            "the node is built without the `otlp` feature, the spans and metrics are not exported"
+        );
+    }
 
     // Setup panic handlers,
     // and when a panic occurs,
@@ -1194,6 +1315,7 @@ async fn run(args: Args) -> anyhow::Result<()> {
     // load or create wallet, asking for password if necessary
     let node_wallet = load_wallet(
         cur_args.password.clone(),
+        cur_args.save_password_in_keyring,
         &SETTINGS.factory.staking_wallet_path,
     )?;
 
@@ -1210,45 +1332,111 @@ async fn run(args: Args) -> anyhow::Result<()> {
     })
     .expect("Error setting Ctrl-C handler");
 
+    // SIGTERM, sent by service managers, stops the node the same way
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut sigterm = signal(SignalKind::terminate()).expect("Error setting SIGTERM handler");
+        let sig_int_toggled = Arc::clone(&sig_int_toggled);
+        tokio::spawn(async move {
+            if sigterm.recv().await.is_some() {
+                *sig_int_toggled
+                    .0
+                    .lock()
+                    .expect("double-lock on interupt bool in SIGTERM handler") = true;
+                sig_int_toggled.1.notify_all();
+            }
+        });
+    }
+
+    // reload of the configuration, requested on SIGHUP or by the private API
+    let mut config_reloader = ConfigReloader::new(SETTINGS.clone(), log_filter.clone());
+    let reload_requested = Arc::new(AtomicBool::new(false));
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut sighup = signal(SignalKind::hangup()).expect("Error setting SIGHUP handler");
+        let reload_requested = Arc::clone(&reload_requested);
+        tokio::spawn(async move {
+            while sighup.recv().await.is_some() {
+                reload_requested.store(true, Ordering::Relaxed);
+            }
+        });
+    }
+
     #[cfg(feature = "resync_check")]
     let mut resync_check = Some(std::time::Instant::now() + std::time::Duration::from_secs(10));
 
+    let mut result = Ok(());
     loop {
-        let (
-            consensus_event_receiver,
-            bootstrap_manager,
-            consensus_manager,
-            execution_manager,
-            selector_manager,
-            pool_manager,
-            protocol_manager,
-            factory_manager,
-            api_private_handle,
-            api_public_handle,
-            api_handle,
-            grpc_handle,
-            metrics_stopper,
-        ) = launch(&cur_args, node_wallet.clone(), Arc::clone(&sig_int_toggled)).await;
+        let (consensus_event_receiver, protocol_controller, mut api_servers, shutdown_controller) =
+            launch(
+                &cur_args,
+                node_wallet.clone(),
+                Arc::clone(&sig_int_toggled),
+                Arc::clone(&reload_requested),
+                log_filter.clone(),
+                journal_events.clone(),
+                &config_reloader.applied_settings().api,
+            )
+            .await;
+
+        let mut watchdog = SETTINGS
+            .watchdog
+            .enabled
+            .then(|| Watchdog::new(SETTINGS.watchdog.timeout.to_duration()));
+        let mut watchdog_restart = false;
+        let mut storage_error = None;
 
         // loop over messages
         let restart = loop {
             massa_trace!("massa-node.main.run.select", {});
-            match consensus_event_receiver.try_recv() {
-                Ok(evt) => match evt {
-                    ConsensusEvent::NeedSync => {
+            // invalid blocks come in bursts: handle all the pending events before waiting
+            let mut consensus_stop = None;
+            loop {
+                match consensus_event_receiver.try_recv() {
+                    Ok(ConsensusEvent::NeedSync) => {
                         warn!("in response to a desynchronization, the node is going to bootstrap again");
-                        break true;
+                        consensus_stop = Some(true);
+                        break;
+                    }
+                    Ok(ConsensusEvent::Stop) => {
+                        consensus_stop = Some(false);
+                        break;
                     }
-                    ConsensusEvent::Stop => {
-                        break false;
+                    Ok(ConsensusEvent::InvalidBlock {
+                        block_id,
+                        slot,
+                        creator,
+                        reason,
+                    }) => {
+                        if node_wallet
+                            .read()
+                            .get_wallet_address_list()
+                            .contains(&creator)
+                        {
+                            warn!(
+                                "block {} produced by our address {} at slot {} is invalid: {}",
+                                block_id, creator, slot, reason
+                            );
+                        } else {
+                            debug!(
+                                "block {} of {} at slot {} is invalid: {}",
+                                block_id, creator, slot, reason
+                            );
+                        }
                     }
-                },
-                Err(TryRecvError::Disconnected) => {
-                    error!("consensus_event_receiver.wait_event disconnected");
-                    break false;
+                    Err(TryRecvError::Disconnected) => {
+                        error!("consensus_event_receiver.wait_event disconnected");
+                        consensus_stop = Some(false);
+                        break;
+                    }
+                    Err(TryRecvError::Empty) => break,
                 }
-                _ => {}
-            };
+            }
+            if let Some(restart) = consensus_stop {
+                break restart;
+            }
 
             // every 100ms/or when alerted, check if sigint toggled
             // if toggled, break loop
@@ -1264,6 +1452,46 @@ async fn run(args: Args) -> anyhow::Result<()> {
                 info!("interrupt signal received");
                 break false;
             }
+            drop(wake);
+
+            // the final state can no longer be written to disk (disk full, corruption...):
+            // stop the node cleanly, the database being left at the last slot written
+            if let Some(err) = shutdown_controller.storage_error() {
+                error!(
+                    "the final state could not be written to disk, stopping the node: {}",
+                    err
+                );
+                storage_error = Some(err);
+                break false;
+            }
+
+            if reload_requested.swap(false, Ordering::Relaxed) {
+                info!("reloading the configuration");
+                if let Some(settings) = config_reloader.reload() {
+                    api_servers.rebind(&settings.api).await;
+                    config_reloader.note_api_binds(api_servers.api_config());
+                    if let Err(err) = protocol_controller.reload_initial_peers() {
+                        warn!("could not reload the initial peers: {}", err);
+                    }
+                }
+            }
+
+            if let Some(watchdog) = watchdog.as_mut() {
+                if watchdog.check() {
+                    match SETTINGS.watchdog.action {
+                        WatchdogAction::Log => {}
+                        WatchdogAction::Restart => {
+                            warn!("watchdog: restarting the node modules");
+                            watchdog_restart = true;
+                            break true;
+                        }
+                        WatchdogAction::Exit => {
+                            error!("watchdog: exiting");
+                            std::process::exit(1);
+                        }
+                    }
+                }
+            }
 
             // Elements of the system that involve stopping and restarting should be checked by forcing a relaunch.
             // This check allows the system to start up as normal, wait 10s, then force a relaunch. If Things take too long
@@ -1277,30 +1505,25 @@ async fn run(args: Args) -> anyhow::Result<()> {
                 }
             }
         };
-        stop(
-            consensus_event_receiver,
-            Managers {
-                bootstrap_manager,
-                consensus_manager,
-                execution_manager,
-                selector_manager,
-                pool_manager,
-                protocol_manager,
-                factory_manager,
-            },
-            api_private_handle,
-            api_public_handle,
-            api_handle,
-            grpc_handle,
-            metrics_stopper,
-        )
-        .await;
-
+        // a stuck worker can prevent the modules from stopping
+        let exit_guard =
+            watchdog_restart.then(|| ExitGuard::new(SETTINGS.watchdog.timeout.to_duration()));
+        shutdown_controller
+            .shutdown(consensus_event_receiver, api_servers)
+            .await;
+        drop(exit_guard);
+
+        if let Some(err) = storage_error {
+            result = Err(anyhow::anyhow!("storage error: {}", err));
+            break;
+        }
         if !restart {
             break;
         }
         // If we restart because of a desync, then we do not want to restart from a snapshot
         cur_args.restart_from_snapshot_at_period = None;
     }
-    Ok(())
+    #[cfg(feature = "otlp")]
+    telemetry::shutdown(otlp_meter_provider);
+    result
 }
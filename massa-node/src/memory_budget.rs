@@ -0,0 +1,214 @@
+//! Limits of the bounded in-memory caches of the node, lowered to fit the memory budget if one is set.
+//!
+//! Each cache already evicts its oldest or least interesting entries when it reaches its limit,
+//! so fitting the caches in the budget only means lowering those limits: each cache gets a fixed
+//! share of the budget, converted into a number of entries with the estimated size of an entry.
+//! A cache never goes below the minimum the node needs to follow the network (see
+//! `CacheLimits::minimum`), so a budget that is too small is exceeded rather than obeyed.
+
+use massa_models::config::{
+    BLOCK_MEMORY_SIZE_ESTIMATE, ENDORSEMENT_COUNT, ENDORSEMENT_MEMORY_SIZE_ESTIMATE,
+    HEADER_MEMORY_SIZE_ESTIMATE, ID_MEMORY_SIZE_ESTIMATE, MAX_OPERATIONS_PER_BLOCK,
+    OPERATION_MEMORY_SIZE_ESTIMATE, THREAD_COUNT,
+};
+use tracing::warn;
+
+use crate::settings::Settings;
+
+/// Maximum number of entries of each cache
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheLimits {
+    /// blocks waiting for their slot
+    pub max_future_processing_blocks: usize,
+    /// blocks waiting for their dependencies
+    pub max_dependency_blocks: usize,
+    /// discarded blocks
+    pub max_discarded_blocks: usize,
+    /// headers of the blocks already checked by protocol
+    pub max_known_blocks_size: usize,
+    /// ids of the operations already checked by protocol
+    pub max_known_ops_size: usize,
+    /// ids of the endorsements already checked by protocol
+    pub max_known_endorsements_size: usize,
+    /// operations in the pool
+    pub max_operation_pool_size: usize,
+    /// endorsements in the pool, per thread
+    pub max_endorsements_pool_size_per_thread: usize,
+}
+
+impl CacheLimits {
+    /// Limits read from the configuration, fitted in the memory budget if one is set
+    pub fn from_settings(settings: &Settings) -> Self {
+        let limits = CacheLimits {
+            max_future_processing_blocks: settings.consensus.max_future_processing_blocks,
+            max_dependency_blocks: settings.consensus.max_dependency_blocks,
+            max_discarded_blocks: settings.consensus.max_discarded_blocks,
+            max_known_blocks_size: settings.protocol.max_known_blocks_size,
+            max_known_ops_size: settings.protocol.max_known_ops_size,
+            max_known_endorsements_size: settings.protocol.max_known_endorsements_size,
+            max_operation_pool_size: settings.pool.max_operation_pool_size,
+            max_endorsements_pool_size_per_thread: settings
+                .pool
+                .max_endorsements_pool_size_per_thread,
+        };
+        match settings.memory.budget_bytes() {
+            Some(budget) => {
+                let minimum_bytes = CacheLimits::minimum().estimated_bytes();
+                if budget < minimum_bytes {
+                    warn!(
+                        "memory budget of {} bytes is below the {} bytes the caches need at least, \
+                        the caches are kept at their minimum size",
+                        budget, minimum_bytes
+                    );
+                }
+                limits.fit_to_budget(budget)
+            }
+            None => limits,
+        }
+    }
+
+    /// Smallest limits that still let the node follow the network: one or a few periods of
+    /// blocks and headers, and enough operations and endorsements to fill a block.
+    pub fn minimum() -> Self {
+        let thread_count = THREAD_COUNT as usize;
+        CacheLimits {
+            max_future_processing_blocks: thread_count,
+            max_dependency_blocks: 2 * thread_count,
+            max_discarded_blocks: thread_count,
+            max_known_blocks_size: 4 * thread_count,
+            max_known_ops_size: MAX_OPERATIONS_PER_BLOCK as usize,
+            max_known_endorsements_size: 2 * thread_count * ENDORSEMENT_COUNT as usize,
+            max_operation_pool_size: MAX_OPERATIONS_PER_BLOCK as usize,
+            max_endorsements_pool_size_per_thread: 2 * ENDORSEMENT_COUNT as usize,
+        }
+    }
+
+    /// Estimated memory used by the caches when they are full, in bytes
+    pub fn estimated_bytes(&self) -> u64 {
+        self.max_future_processing_blocks as u64 * BLOCK_MEMORY_SIZE_ESTIMATE
+            + self.max_dependency_blocks as u64 * BLOCK_MEMORY_SIZE_ESTIMATE
+            + self.max_discarded_blocks as u64 * HEADER_MEMORY_SIZE_ESTIMATE
+            + self.max_known_blocks_size as u64 * HEADER_MEMORY_SIZE_ESTIMATE
+            + self.max_known_ops_size as u64 * ID_MEMORY_SIZE_ESTIMATE
+            + self.max_known_endorsements_size as u64 * ID_MEMORY_SIZE_ESTIMATE
+            + self.max_operation_pool_size as u64 * OPERATION_MEMORY_SIZE_ESTIMATE
+            + self.max_endorsements_pool_size_per_thread as u64
+                * THREAD_COUNT as u64
+                * ENDORSEMENT_MEMORY_SIZE_ESTIMATE
+    }
+
+    /// Lower the limits so that each cache stays within its share of `budget` bytes.
+    /// Limits already below their share are kept, and no limit is lowered below its minimum
+    /// (see `CacheLimits::minimum`): configured limits below their minimum are kept as well.
+    pub fn fit_to_budget(self, budget: u64) -> Self {
+        let minimum = CacheLimits::minimum();
+        // share of the budget of each cache, in percent
+        let fit = |limit: usize, minimum: usize, percent: u64, entry_size: u64| -> usize {
+            let max_entries = (budget / 100).saturating_mul(percent) / entry_size;
+            limit.min(
+                usize::try_from(max_entries)
+                    .unwrap_or(usize::MAX)
+                    .max(minimum),
+            )
+        };
+        CacheLimits {
+            max_future_processing_blocks: fit(
+                self.max_future_processing_blocks,
+                minimum.max_future_processing_blocks,
+                20,
+                BLOCK_MEMORY_SIZE_ESTIMATE,
+            ),
+            max_dependency_blocks: fit(
+                self.max_dependency_blocks,
+                minimum.max_dependency_blocks,
+                30,
+                BLOCK_MEMORY_SIZE_ESTIMATE,
+            ),
+            max_discarded_blocks: fit(
+                self.max_discarded_blocks,
+                minimum.max_discarded_blocks,
+                2,
+                HEADER_MEMORY_SIZE_ESTIMATE,
+            ),
+            max_known_blocks_size: fit(
+                self.max_known_blocks_size,
+                minimum.max_known_blocks_size,
+                5,
+                HEADER_MEMORY_SIZE_ESTIMATE,
+            ),
+            max_known_ops_size: fit(
+                self.max_known_ops_size,
+                minimum.max_known_ops_size,
+                8,
+                ID_MEMORY_SIZE_ESTIMATE,
+            ),
+            max_known_endorsements_size: fit(
+                self.max_known_endorsements_size,
+                minimum.max_known_endorsements_size,
+                1,
+                ID_MEMORY_SIZE_ESTIMATE,
+            ),
+            max_operation_pool_size: fit(
+                self.max_operation_pool_size,
+                minimum.max_operation_pool_size,
+                30,
+                OPERATION_MEMORY_SIZE_ESTIMATE,
+            ),
+            max_endorsements_pool_size_per_thread: fit(
+                self.max_endorsements_pool_size_per_thread,
+                minimum.max_endorsements_pool_size_per_thread,
+                4,
+                ENDORSEMENT_MEMORY_SIZE_ESTIMATE * THREAD_COUNT as u64,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn default_limits() -> CacheLimits {
+        CacheLimits {
+            max_future_processing_blocks: 400,
+            max_dependency_blocks: 2048,
+            max_discarded_blocks: 100,
+            max_known_blocks_size: 1024,
+            max_known_ops_size: 1_000_000,
+            max_known_endorsements_size: 2048,
+            max_operation_pool_size: 500_000,
+            max_endorsements_pool_size_per_thread: 25_000,
+        }
+    }
+
+    #[test]
+    fn test_fit_to_budget() {
+        // a large budget keeps the configured limits
+        assert_eq!(default_limits().fit_to_budget(1 << 40), default_limits());
+
+        let budget = 256 * 1024 * 1024;
+        let limits = default_limits().fit_to_budget(budget);
+        assert!(limits.max_future_processing_blocks < 400);
+        assert!(limits.max_dependency_blocks < 2048);
+        assert!(limits.max_operation_pool_size < 500_000);
+        assert!(limits.estimated_bytes() <= budget);
+
+        // a budget too small for the caches keeps them at their minimum
+        assert_eq!(default_limits().fit_to_budget(0), CacheLimits::minimum());
+        assert!(CacheLimits::minimum().estimated_bytes() > 0);
+    }
+
+    #[test]
+    fn test_fit_to_budget_keeps_limits_below_minimum() {
+        let limits = CacheLimits {
+            max_future_processing_blocks: 10,
+            ..default_limits()
+        }
+        .fit_to_budget(0);
+        assert_eq!(limits.max_future_processing_blocks, 10);
+        assert_eq!(
+            limits.max_dependency_blocks,
+            CacheLimits::minimum().max_dependency_blocks
+        );
+    }
+}
@@ -0,0 +1,129 @@
+//! Reload of the settings that can change while the node is running.
+//!
+//! When the node receives SIGHUP, or when the `node_reload_config` method of the private API is
+//! called, the configuration files are read again and the following changes are applied without
+//! stopping consensus or the other modules:
+//! - `logging.level`
+//! - `api.bind_private`, `api.bind_public` and `api.bind_api`: the JSON-RPC servers are moved to
+//!   their new address
+//! - the content of the initial peers file: its peers are tested again and added to the known peers
+//!
+//! The reloaded settings that were applied are kept by the `ConfigReloader`, and the modules
+//! relaunched after a desynchronization or by the watchdog use them instead of the settings the
+//! node was started with.
+//!
+//! The bootstrap white and black lists don't need a reload: the bootstrap server already reads
+//! their files again periodically. The other settings, including the bandwidth limits that are
+//! given to the network layer when it starts, are only applied at the next start of the node, and
+//! their changes are logged as a warning.
+
+use massa_api_exports::config::APIConfig;
+use massa_models::config::try_build_massa_settings;
+use tracing::warn;
+
+use crate::log_filter::LogFilter;
+use crate::settings::Settings;
+
+/// Reads the configuration files again and applies the new log level
+pub struct ConfigReloader {
+    /// settings the node was started with, updated with the reloaded settings once applied
+    applied_settings: Settings,
+    log_filter: LogFilter,
+}
+
+impl ConfigReloader {
+    /// Create a reloader for a node started with `startup_settings`
+    pub fn new(startup_settings: Settings, log_filter: LogFilter) -> Self {
+        ConfigReloader {
+            applied_settings: startup_settings,
+            log_filter,
+        }
+    }
+
+    /// Settings currently applied: the settings the node was started with,
+    /// with the reloaded ones that were applied since then
+    pub fn applied_settings(&self) -> &Settings {
+        &self.applied_settings
+    }
+
+    /// Note the addresses the API servers listen on after a reload,
+    /// which can differ from the reloaded ones if a server could not be moved
+    pub fn note_api_binds(&mut self, api_config: &APIConfig) {
+        self.applied_settings.api.bind_api = api_config.bind_api;
+        self.applied_settings.api.bind_private = api_config.bind_private;
+        self.applied_settings.api.bind_public = api_config.bind_public;
+    }
+
+    /// Read the configuration files again and apply the new log level.
+    /// Returns the new settings, or `None` if the configuration can't be read,
+    /// in which case the current settings are kept.
+    pub fn reload(&mut self) -> Option<Settings> {
+        let settings: Settings = match try_build_massa_settings("massa-node", "MASSA_NODE") {
+            Ok(settings) => settings,
+            Err(err) => {
+                warn!(
+                    "could not read the configuration, keeping the current one: {}",
+                    err
+                );
+                return None;
+            }
+        };
+
+        if settings.logging.level != self.applied_settings.logging.level {
+            match self.log_filter.set_level(settings.logging.level) {
+                Ok(()) => self.applied_settings.logging.level = settings.logging.level,
+                Err(err) => warn!("{}", err),
+            }
+        }
+
+        let sections = restart_required_sections(&self.applied_settings, &settings);
+        if !sections.is_empty() {
+            warn!(
+                "the changes of the [{}] settings will only be applied at the next start of the node",
+                sections.join("], [")
+            );
+        }
+        Some(settings)
+    }
+}
+
+/// Sections of the settings that changed between `current` and `new`,
+/// ignoring the changes of the settings that are reloaded
+fn restart_required_sections(current: &Settings, new: &Settings) -> Vec<&'static str> {
+    let mut new = new.clone();
+    new.logging.level = current.logging.level;
+    new.api.bind_private = current.api.bind_private;
+    new.api.bind_public = current.api.bind_public;
+    new.api.bind_api = current.api.bind_api;
+    // the entries of a `HashMap` are not formatted in a deterministic order
+    let same_categories = current.protocol.peers_categories.len()
+        == new.protocol.peers_categories.len()
+        && current
+            .protocol
+            .peers_categories
+            .iter()
+            .all(|(name, info)| {
+                new.protocol
+                    .peers_categories
+                    .get(name)
+                    .map_or(false, |new_info| {
+                        format!("{:?}", new_info) == format!("{:?}", info)
+                    })
+            });
+    if same_categories {
+        new.protocol.peers_categories = current.protocol.peers_categories.clone();
+    }
+
+    macro_rules! changed_sections {
+        ($($section:ident),*) => {
+            [$((stringify!($section), format!("{:?}", current.$section) != format!("{:?}", new.$section))),*]
+                .into_iter()
+                .filter_map(|(section, changed)| changed.then_some(section))
+                .collect()
+        };
+    }
+    changed_sections!(
+        logging, protocol, consensus, api, network, bootstrap, pool, execution, ledger, selector,
+        factory, grpc, metrics, otlp, event_sink, indexer, versioning, memory, watchdog
+    )
+}
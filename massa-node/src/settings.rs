@@ -4,12 +4,17 @@
 use std::{collections::HashMap, path::PathBuf};
 
 use massa_bootstrap::IpType;
+use massa_consensus_exports::ChannelOverflowPolicy;
 use massa_models::{config::build_massa_settings, node::NodeId};
-use massa_protocol_exports::PeerCategoryInfo;
+use massa_protocol_exports::{ChaosConfig, MessageRateClasses, PeerCategoryInfo};
+use massa_signature::PublicKey;
 use massa_time::MassaTime;
 use serde::Deserialize;
 use std::net::{IpAddr, SocketAddr};
 
+use crate::event_sink::{EventSinkBroker, EventSinkSerialization};
+use crate::watchdog::WatchdogAction;
+
 lazy_static::lazy_static! {
     pub static ref SETTINGS: Settings = build_massa_settings("massa-node", "MASSA_NODE");
 }
@@ -17,6 +22,10 @@ lazy_static::lazy_static! {
 #[derive(Debug, Deserialize, Clone)]
 pub struct LoggingSettings {
     pub level: usize,
+    /// file to which the events of the journal are appended
+    pub journal_path: PathBuf,
+    /// size in bytes above which the journal is rotated
+    pub journal_max_size: u64,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -70,6 +79,10 @@ pub struct BootstrapSettings {
     pub per_ip_min_interval: MassaTime,
     pub ip_list_max_size: usize,
     pub max_bytes_read_write: u64,
+    /// Manifest of the state chunks received during an ongoing bootstrap
+    pub manifest_path: PathBuf,
+    /// Number of bootstrap servers, other than the one we bootstrapped from, that must confirm the hash of the bootstrapped state
+    pub state_hash_quorum: usize,
     /// Allocated time with which to manage the bootstrap process
     pub bootstrap_timeout: MassaTime,
 }
@@ -83,6 +96,21 @@ pub struct FactorySettings {
     pub staking_wallet_path: PathBuf,
     /// stop the production in case we are not connected to anyone
     pub stop_production_when_zero_connections: bool,
+    /// stop the production while a network partition is suspected
+    pub stop_production_on_partition: bool,
+    /// remote signing service, if any
+    pub remote_signer: Option<RemoteSignerSettings>,
+}
+
+/// Remote signer configuration, read from a file configuration
+#[derive(Debug, Deserialize, Clone)]
+pub struct RemoteSignerSettings {
+    /// JSON-RPC endpoint of the signing service
+    pub url: String,
+    /// timeout of a signature request
+    pub timeout: MassaTime,
+    /// public keys whose secret keys are held by the signing service
+    pub public_keys: Vec<PublicKey>,
 }
 
 /// Pool configuration, read from a file configuration
@@ -90,8 +118,14 @@ pub struct FactorySettings {
 pub struct PoolSettings {
     pub max_operation_pool_size: usize,
     pub max_operation_pool_excess_items: usize,
+    /// max number of operations kept in the pool per sender
+    pub max_operations_per_sender: usize,
     pub operation_max_future_start_delay: MassaTime,
     pub operation_pool_refresh_interval: MassaTime,
+    /// file where the pending operations are saved to be reloaded at startup, if any
+    pub operations_file_path: Option<PathBuf>,
+    /// interval between two saves of the pending operations
+    pub operations_save_interval: MassaTime,
     pub max_endorsements_pool_size_per_thread: usize,
     pub max_item_return_count: usize,
     /// endorsements channel capacity
@@ -112,9 +146,14 @@ pub struct APISettings {
     pub max_request_body_size: u32,
     pub max_response_body_size: u32,
     pub max_connections: u32,
+    pub max_block_graph_page_size: usize,
     pub max_subscriptions_per_connection: u32,
     pub max_log_length: u32,
     pub allow_hosts: Vec<String>,
+    pub cors_allowed_origins: Vec<String>,
+    pub max_requests_per_second: u64,
+    pub trust_forwarded_for: bool,
+    pub max_concurrent_requests: usize,
     pub batch_request_limit: u32,
     pub ping_interval: MassaTime,
     pub enable_http: bool,
@@ -138,7 +177,37 @@ pub struct Settings {
     pub factory: FactorySettings,
     pub grpc: GrpcSettings,
     pub metrics: MetricsSettings,
+    pub otlp: OtlpSettings,
+    pub event_sink: EventSinkSettings,
+    pub indexer: IndexerSettings,
     pub versioning: VersioningSettings,
+    pub memory: MemorySettings,
+    pub watchdog: WatchdogSettings,
+}
+
+/// Watchdog of the worker threads
+#[derive(Debug, Deserialize, Clone)]
+pub struct WatchdogSettings {
+    /// enable the watchdog
+    pub enabled: bool,
+    /// time without heartbeat after which a worker is considered stuck
+    pub timeout: MassaTime,
+    /// what to do when a worker is stuck
+    pub action: WatchdogAction,
+}
+
+/// Memory budget of the in-memory caches
+#[derive(Debug, Deserialize, Clone)]
+pub struct MemorySettings {
+    /// memory budget of the caches in megabytes, the configured cache limits are used if absent
+    pub budget_mb: Option<u64>,
+}
+
+impl MemorySettings {
+    /// memory budget of the caches in bytes, if any
+    pub fn budget_bytes(&self) -> Option<u64> {
+        self.budget_mb.map(|mb| mb.saturating_mul(1024 * 1024))
+    }
 }
 
 /// Consensus configuration
@@ -153,10 +222,14 @@ pub struct ConsensusSettings {
     pub max_dependency_blocks: usize,
     /// stats time span
     pub stats_timespan: MassaTime,
+    /// time without new final block, while most of the new blocks are created by this node, after which a network partition is suspected
+    pub partition_detection_timespan: MassaTime,
     /// force keep at least this number of final periods in RAM for each thread
     pub force_keep_final_periods: u64,
     /// force keep at least this number of final periods without operations in RAM for each thread
     pub force_keep_final_periods_without_ops: u64,
+    /// milliseconds added to the system time to compensate a known clock drift
+    pub clock_compensation_millis: i64,
     /// old blocks are pruned every `block_db_prune_interval`
     pub block_db_prune_interval: MassaTime,
     /// blocks headers channel capacity
@@ -165,6 +238,12 @@ pub struct ConsensusSettings {
     pub broadcast_blocks_channel_capacity: usize,
     /// filled blocks channel capacity
     pub broadcast_filled_blocks_channel_capacity: usize,
+    /// what to do with incoming blocks when the consensus command channel is full
+    pub channel_overflow_policy: ChannelOverflowPolicy,
+    /// maximum time to wait for room in the consensus command channel before dropping a command
+    pub max_send_wait: MassaTime,
+    /// file where the blocks that are not final yet are saved when the node stops, if any
+    pub blocks_file_path: Option<PathBuf>,
 }
 
 // TODO: Remove one date. Kept for retro compatibility.
@@ -184,6 +263,71 @@ pub struct MetricsSettings {
     pub tick_delay: MassaTime,
 }
 
+/// Export of the tracing spans and the metrics with OTLP,
+/// used if the node is built with the `otlp` feature
+#[derive(Debug, Deserialize, Clone)]
+pub struct OtlpSettings {
+    /// enable the export
+    pub enabled: bool,
+    /// gRPC endpoint of the OpenTelemetry collector
+    pub endpoint: String,
+    /// interval at which the metrics are exported
+    pub metrics_interval: MassaTime,
+}
+
+/// Publication of the chain events to a message broker,
+/// used if the node is built with the feature of the broker (`kafka` or `nats`)
+#[derive(Debug, Deserialize, Clone)]
+pub struct EventSinkSettings {
+    /// enable the publication
+    pub enabled: bool,
+    /// message broker
+    pub broker: EventSinkBroker,
+    /// addresses of the brokers, comma separated
+    pub servers: String,
+    /// topic of the finalized blocks
+    pub blocks_topic: String,
+    /// topic of the operations of the finalized blocks
+    pub operations_topic: String,
+    /// topic of the reorg notices
+    pub reorgs_topic: String,
+    /// topic of the notices of the final slots whose events may not have been published
+    pub gaps_topic: String,
+    /// serialization of the events
+    pub serialization: EventSinkSerialization,
+    /// maximum number of events waiting in the outbox to be published
+    pub queue_size: usize,
+    /// delay before publishing again an event that was not acknowledged
+    pub retry_delay: MassaTime,
+    /// maximum time to wait for the broker to acknowledge an event
+    pub publish_timeout: MassaTime,
+    /// file in which the last final slot whose events were saved in the outbox is saved
+    pub cursor_path: PathBuf,
+    /// directory in which the events are saved until the broker acknowledges them
+    pub outbox_path: PathBuf,
+}
+
+/// Indexing of the final chain data in PostgreSQL,
+/// used if the node is built with the `postgres` feature
+#[derive(Debug, Deserialize, Clone)]
+pub struct IndexerSettings {
+    /// enable the indexing
+    pub enabled: bool,
+    /// connection string of the database
+    pub database_url: String,
+    /// maximum number of final slots waiting to be indexed
+    pub queue_size: usize,
+    /// delay before writing again a slot that could not be written
+    pub retry_delay: MassaTime,
+    /// record the final slots missing from the index in the `indexer_gaps` table and go on,
+    /// instead of stopping the indexing at the first missing slot
+    pub allow_gaps: bool,
+    /// connect to the database with TLS
+    pub tls: bool,
+    /// PEM file of a root certificate trusted in addition to the system ones
+    pub tls_root_certificate: Option<PathBuf>,
+}
+
 /// Protocol Configuration, read from toml user configuration file
 #[derive(Debug, Deserialize, Clone)]
 pub struct ProtocolSettings {
@@ -231,6 +375,18 @@ pub struct ProtocolSettings {
     pub initial_peers_file: PathBuf,
     /// Keypair
     pub keypair_file: PathBuf,
+    /// Keypair replaced by the one of `keypair_file`
+    pub previous_keypair_file: PathBuf,
+    /// Seed from which the keypair is derived instead of `keypair_file`, sandbox mode only
+    pub keypair_seed: Option<String>,
+    /// File where all the received messages are recorded, recording is disabled if absent
+    pub message_record_file: Option<PathBuf>,
+    /// Size in bytes after which the recording file is rotated
+    pub message_record_max_file_size: u64,
+    /// Number of rotated recording files kept
+    pub message_record_rotated_files: u32,
+    /// Faults injected in the network layer, disabled if absent. For test networks only
+    pub chaos: Option<ChaosConfig>,
     /// Ip we are bind to listen to
     pub bind: SocketAddr,
     /// Ip seen by others. If none the bind ip is used
@@ -243,12 +399,22 @@ pub struct ProtocolSettings {
     pub read_write_limit_bytes_per_second: u64,
     /// try connection timer
     pub try_connection_timer: MassaTime,
+    /// the node is isolated when it has fewer connected peers than this
+    pub isolation_threshold: usize,
     /// Timeout connection
     pub timeout_connection: MassaTime,
     /// Message timeout
     pub message_timeout: MassaTime,
     /// Nb in connections
     pub max_in_connections: usize,
+    /// Max number of handshakes in progress at the same time
+    pub max_concurrent_handshakes: usize,
+    /// Max number of handshakes in progress at the same time with the same IP
+    pub max_handshakes_per_ip: usize,
+    /// Max duration of a handshake
+    pub handshake_timeout: MassaTime,
+    /// Rate limits and priorities of each type of message
+    pub message_rate_classes: MessageRateClasses,
     /// Peers limits per category
     pub peers_categories: HashMap<String, PeerCategoryInfo>,
     /// Limits for default category
@@ -0,0 +1,163 @@
+//! Shutdown of the node.
+//!
+//! The modules are stopped one after the other, in an order that lets each of them finish its
+//! work while the modules it depends on are still running:
+//! 1. block production: the factories stop, so that no block or endorsement is produced while
+//!    the rest of the node is going down
+//! 2. incoming connections: the bootstrap server, the API servers and the metrics server stop
+//!    accepting clients
+//! 3. network: protocol closes the connections to the peers, which drop us from their active
+//!    connections
+//! 4. consensus, which saves the blocks that are not final yet to `consensus.blocks_file_path`
+//! 5. pool, which saves its pending operations to `pool.operations_file_path`
+//! 6. execution, which finishes writing the final slots to the final state
+//! 7. selector
+//! 8. the event sink and the indexer publish the last finalized slots
+//! 9. the final state database is flushed to disk
+//!
+//! The saved blocks and operations are reloaded at the next start and checked again, as if they
+//! came from the network. The endorsements of the pool are not saved: they are only useful for a
+//! few slots.
+
+use massa_bootstrap::BootstrapManager;
+use massa_channel::receiver::MassaReceiver;
+use massa_consensus_exports::{events::ConsensusEvent, ConsensusManager};
+use massa_db_exports::ShareableMassaDBController;
+use massa_execution_exports::ExecutionManager;
+use massa_factory_exports::FactoryManager;
+use massa_metrics::MetricsStopper;
+use massa_pool_exports::PoolManager;
+use massa_pos_exports::SelectorManager;
+use massa_protocol_exports::ProtocolManager;
+use tracing::{error, info};
+
+use crate::api_servers::ApiServers;
+use crate::event_sink::EventSink;
+#[cfg(feature = "postgres")]
+use crate::indexer::Indexer;
+
+/// Managers of the modules of the node
+pub struct Managers {
+    pub bootstrap_manager: Option<BootstrapManager>,
+    pub consensus_manager: Box<dyn ConsensusManager>,
+    pub execution_manager: Box<dyn ExecutionManager>,
+    pub selector_manager: Box<dyn SelectorManager>,
+    pub pool_manager: Box<dyn PoolManager>,
+    pub protocol_manager: Box<dyn ProtocolManager>,
+    pub factory_manager: Box<dyn FactoryManager>,
+}
+
+/// Stops the modules of a running node in order
+pub struct ShutdownController {
+    managers: Managers,
+    grpc_handle: Option<massa_grpc::server::StopHandle>,
+    metrics_stopper: MetricsStopper,
+    event_sink: Option<EventSink>,
+    #[cfg(feature = "postgres")]
+    indexer: Option<Indexer>,
+    db: ShareableMassaDBController,
+}
+
+impl ShutdownController {
+    /// Create the controller of a node running the modules of `managers`
+    pub fn new(
+        managers: Managers,
+        grpc_handle: Option<massa_grpc::server::StopHandle>,
+        metrics_stopper: MetricsStopper,
+        event_sink: Option<EventSink>,
+        db: ShareableMassaDBController,
+    ) -> Self {
+        ShutdownController {
+            managers,
+            grpc_handle,
+            metrics_stopper,
+            event_sink,
+            #[cfg(feature = "postgres")]
+            indexer: None,
+            db,
+        }
+    }
+
+    /// Stop the indexer of the final chain data with the node
+    #[cfg(feature = "postgres")]
+    pub fn with_indexer(mut self, indexer: Option<Indexer>) -> Self {
+        self.indexer = indexer;
+        self
+    }
+
+    /// Error of the write after which the final state database refuses any write.
+    /// The node cannot make progress anymore and must be stopped.
+    pub fn storage_error(&self) -> Option<String> {
+        self.db.read().get_write_error()
+    }
+
+    /// Stop the node.
+    /// The consensus event receiver is kept until consensus is stopped,
+    /// so that consensus can still send its events while it stops.
+    pub async fn shutdown(
+        self,
+        _consensus_event_receiver: MassaReceiver<ConsensusEvent>,
+        api_servers: ApiServers,
+    ) {
+        let ShutdownController {
+            managers:
+                Managers {
+                    bootstrap_manager,
+                    mut consensus_manager,
+                    mut execution_manager,
+                    mut selector_manager,
+                    mut pool_manager,
+                    mut protocol_manager,
+                    mut factory_manager,
+                },
+            grpc_handle,
+            mut metrics_stopper,
+            event_sink,
+            #[cfg(feature = "postgres")]
+            indexer,
+            db,
+        } = self;
+
+        info!("shutdown: stopping block production");
+        factory_manager.stop();
+
+        info!("shutdown: stopping bootstrap server, API's and metrics");
+        if let Some(bootstrap_manager) = bootstrap_manager {
+            bootstrap_manager
+                .stop()
+                .expect("bootstrap server shutdown failed")
+        }
+        if let Some(handle) = grpc_handle {
+            handle.stop();
+        }
+        api_servers.stop().await;
+        metrics_stopper.stop();
+
+        info!("shutdown: disconnecting from peers");
+        protocol_manager.stop();
+
+        info!("shutdown: stopping consensus and saving the blocks that are not final");
+        consensus_manager.stop();
+        info!("shutdown: stopping pool and saving the pending operations");
+        pool_manager.stop();
+        info!("shutdown: stopping execution");
+        execution_manager.stop();
+        selector_manager.stop();
+
+        if let Some(event_sink) = event_sink {
+            info!("shutdown: publishing the last chain events");
+            event_sink.stop().await;
+        }
+        #[cfg(feature = "postgres")]
+        if let Some(indexer) = indexer {
+            info!("shutdown: indexing the last final slots");
+            indexer.stop().await;
+        }
+
+        info!("shutdown: flushing the final state to disk");
+        if let Err(err) = db.read().flush() {
+            error!("could not flush the final state to disk: {}", err);
+        }
+        info!("shutdown: done");
+    }
+}
@@ -0,0 +1,74 @@
+//! Export of the tracing spans and the metrics of the node with OTLP, when the node is built with
+//! the `otlp` feature and `otlp.enabled` is set.
+//!
+//! The spans of the node (handshakes, blocks received from peers, blocks processed by consensus,
+//! slot ticks) are sent with their `peer_id`, `block_id` and `slot` fields, and the prometheus
+//! metrics are sent every `otlp.metrics_interval`, to the OpenTelemetry collector at
+//! `otlp.endpoint`. The resource of the node is `service.name=massa-node` with its version; the
+//! other resource attributes, such as `service.instance.id`, are read from the standard
+//! `OTEL_RESOURCE_ATTRIBUTES` environment variable.
+
+use massa_models::config::VERSION;
+use opentelemetry::metrics::{MeterProvider as _, MetricsError};
+use opentelemetry::sdk::{metrics::MeterProvider, trace::Tracer, Resource};
+use opentelemetry::trace::TraceError;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use tracing::warn;
+
+use crate::settings::OtlpSettings;
+
+/// Resource of the node, completed by the attributes of the environment
+fn resource() -> Resource {
+    Resource::default().merge(&Resource::new([
+        KeyValue::new("service.name", "massa-node"),
+        KeyValue::new("service.version", VERSION.to_string()),
+    ]))
+}
+
+/// Tracer exporting the spans to the collector.
+/// Called before the logs are set up: the errors are printed on stderr.
+pub fn otlp_tracer(settings: &OtlpSettings) -> Option<Tracer> {
+    opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(settings.endpoint.clone()),
+        )
+        .with_trace_config(opentelemetry::sdk::trace::config().with_resource(resource()))
+        .install_batch(opentelemetry::runtime::Tokio)
+        .map_err(|err: TraceError| eprintln!("could not export the spans with OTLP: {}", err))
+        .ok()
+}
+
+/// Start exporting the prometheus metrics to the collector
+pub fn start_metrics_export(settings: &OtlpSettings) -> Option<MeterProvider> {
+    let meter_provider = opentelemetry_otlp::new_pipeline()
+        .metrics(opentelemetry::runtime::Tokio)
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(settings.endpoint.clone()),
+        )
+        .with_period(settings.metrics_interval.to_duration())
+        .with_resource(resource())
+        .build()
+        .map_err(|err: MetricsError| warn!("could not export the metrics with OTLP: {}", err))
+        .ok()?;
+    massa_metrics::otlp::export_prometheus_metrics(
+        meter_provider.meter("massa-node"),
+        settings.metrics_interval.to_duration(),
+    );
+    Some(meter_provider)
+}
+
+/// Send the spans and the metrics that are not exported yet
+pub fn shutdown(meter_provider: Option<MeterProvider>) {
+    if let Some(meter_provider) = meter_provider {
+        if let Err(err) = meter_provider.shutdown() {
+            warn!("could not export the last metrics with OTLP: {}", err);
+        }
+    }
+    opentelemetry::global::shutdown_tracer_provider();
+}
@@ -0,0 +1,152 @@
+//! Watchdog of the worker threads of the node.
+//!
+//! The consensus worker and the protocol threads (connectivity, peer management, peer testers, and
+//! the retrieval and propagation threads of the block, operation and endorsement handlers) beat at
+//! each iteration of their loop. When they wait for a message or a deadline, they wake up at least
+//! every `MAX_BEAT_INTERVAL` to beat, even before genesis. A worker that did not beat for longer
+//! than `watchdog.timeout` is considered stuck, for example waiting on a full channel or a lock that
+//! is never released. The watchdog then logs the time since the last beat of each
+//! worker and the deadlocks found by `parking_lot`, and the node applies `watchdog.action`.
+
+use std::collections::BTreeSet;
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::time::Duration;
+
+use massa_channel::heartbeat::{heartbeat_ages, MAX_BEAT_INTERVAL};
+use massa_logging::massa_journal;
+use serde::Deserialize;
+use tracing::{error, info, warn};
+
+/// What the node does when a worker is stuck
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchdogAction {
+    /// only log the diagnostics
+    Log,
+    /// stop the modules of the node and start them again.
+    /// The process exits if the modules do not stop within the watchdog timeout.
+    Restart,
+    /// exit the process with a non-zero code, to be restarted by a service manager
+    Exit,
+}
+
+/// Finds the workers that stopped beating
+pub struct Watchdog {
+    timeout: Duration,
+    /// workers already reported as stuck
+    stuck: BTreeSet<String>,
+}
+
+impl Watchdog {
+    /// Create a watchdog considering a worker stuck after `timeout` without beating
+    pub fn new(timeout: Duration) -> Self {
+        if timeout <= MAX_BEAT_INTERVAL {
+            warn!(
+                "watchdog: the timeout {:?} is not larger than the heartbeat interval {:?}, workers waiting for messages will be reported as stuck",
+                timeout, MAX_BEAT_INTERVAL
+            );
+        }
+        Watchdog {
+            timeout,
+            stuck: BTreeSet::new(),
+        }
+    }
+
+    /// Check the heartbeats of the workers, and log diagnostics if a worker is newly stuck.
+    /// Returns true if a worker is newly stuck.
+    pub fn check(&mut self) -> bool {
+        let ages = heartbeat_ages();
+        let stuck: BTreeSet<String> = ages
+            .iter()
+            .filter(|(_, age)| **age > self.timeout)
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in self.stuck.difference(&stuck) {
+            info!("watchdog: worker \"{}\" is beating again", name);
+        }
+        let newly_stuck = stuck.difference(&self.stuck).next().is_some();
+        self.stuck = stuck;
+        if !newly_stuck {
+            return false;
+        }
+
+        error!(
+            "watchdog: no heartbeat for more than {:?} from: {}",
+            self.timeout,
+            self.stuck.iter().cloned().collect::<Vec<_>>().join(", ")
+        );
+        massa_journal!("worker_stuck", { "workers": self.stuck });
+        for (name, age) in &ages {
+            error!("watchdog: last heartbeat of \"{}\": {:?} ago", name, age);
+        }
+        let deadlocks = parking_lot::deadlock::check_deadlock();
+        for (i, threads) in deadlocks.iter().enumerate() {
+            error!("watchdog: deadlock #{}", i);
+            for thread in threads {
+                error!(
+                    "watchdog: thread {:?}\n{:?}",
+                    thread.thread_id(),
+                    thread.backtrace()
+                );
+            }
+        }
+        true
+    }
+}
+
+/// Exits the process if it is not dropped within a timeout:
+/// stopping a stuck worker can block forever.
+pub struct ExitGuard {
+    /// dropping the sender wakes the guard thread up
+    _done: Sender<()>,
+}
+
+impl ExitGuard {
+    /// Exit the process if the guard is still alive after `timeout`
+    pub fn new(timeout: Duration) -> Self {
+        let (done, done_receiver) = channel::<()>();
+        std::thread::Builder::new()
+            .name("watchdog-exit-guard".into())
+            .spawn(move || {
+                if let Err(RecvTimeoutError::Timeout) = done_receiver.recv_timeout(timeout) {
+                    error!("watchdog: the node modules did not stop in time, exiting");
+                    std::process::exit(1);
+                }
+            })
+            .expect("failed to spawn thread : watchdog-exit-guard");
+        ExitGuard { _done: done }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_channel::heartbeat::Heartbeat;
+
+    #[test]
+    fn test_watchdog_check() {
+        let name = "watchdog_test_worker";
+        let heartbeat = Heartbeat::new(name);
+        let mut watchdog = Watchdog::new(Duration::from_millis(50));
+        watchdog.check();
+        assert!(!watchdog.stuck.contains(name));
+
+        // no beat for longer than the timeout: reported once
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(watchdog.check());
+        assert!(watchdog.stuck.contains(name));
+        assert!(!watchdog.check());
+        assert!(watchdog.stuck.contains(name));
+
+        // beating again
+        heartbeat.beat();
+        watchdog.check();
+        assert!(!watchdog.stuck.contains(name));
+
+        // a stopped worker is not watched anymore
+        drop(heartbeat);
+        std::thread::sleep(Duration::from_millis(100));
+        watchdog.check();
+        assert!(!watchdog.stuck.contains(name));
+    }
+}
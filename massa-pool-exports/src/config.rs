@@ -3,9 +3,10 @@
 use massa_models::amount::Amount;
 use massa_time::MassaTime;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 /// Pool configuration
-#[derive(Debug, Deserialize, Serialize, Clone, Copy)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct PoolConfig {
     /// thread count
     pub thread_count: u8,
@@ -19,6 +20,10 @@ pub struct PoolConfig {
     pub operation_validity_periods: u64,
     /// operation pool refresh interval
     pub operation_pool_refresh_interval: MassaTime,
+    /// file where the pending operations are saved to be reloaded at startup, if any
+    pub operations_file_path: Option<PathBuf>,
+    /// interval between two saves of the pending operations
+    pub operations_save_interval: MassaTime,
     /// max delay in the future for operation validity start
     pub operation_max_future_start_delay: MassaTime,
     /// max operations per block
@@ -27,6 +32,8 @@ pub struct PoolConfig {
     pub max_operation_pool_size: usize,
     /// max excess on pool size (in-between refreshes)
     pub max_operation_pool_excess_items: usize,
+    /// max number of operations kept in the pool per sender
+    pub max_operations_per_sender: usize,
     /// max endorsement pool size per thread (in number of endorsements)
     pub max_endorsements_pool_size_per_thread: usize,
     /// max number of endorsements per block
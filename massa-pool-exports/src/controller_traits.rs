@@ -1,11 +1,13 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use massa_models::{
+    address::Address,
     block_id::BlockId,
     denunciation::{Denunciation, DenunciationPrecursor},
     endorsement::EndorsementId,
     operation::OperationId,
     slot::Slot,
+    stats::PoolMemoryStats,
 };
 use massa_storage::Storage;
 
@@ -42,12 +44,19 @@ pub trait PoolController: Send + Sync {
     /// Get the number of operations in the pool
     fn get_operation_count(&self) -> usize;
 
+    /// Get the memory used by the operations and endorsements of the pool
+    fn get_memory_stats(&self) -> PoolMemoryStats;
+
     /// Check if the pool contains a list of endorsements. Returns one boolean per item.
     fn contains_endorsements(&self, endorsements: &[EndorsementId]) -> Vec<bool>;
 
     /// Check if the pool contains a list of operations. Returns one boolean per item.
     fn contains_operations(&self, operations: &[OperationId]) -> Vec<bool>;
 
+    /// Get the pending operations sent by or to an address, from the best to the worst score.
+    /// Returns at most `limit` operation ids.
+    fn get_address_operations(&self, address: &Address, limit: usize) -> Vec<OperationId>;
+
     /// Check if the pool contains a denunciation. Returns a boolean
     #[cfg(feature = "testing")]
     fn contains_denunciation(&self, denunciation: &Denunciation) -> bool;
@@ -19,6 +19,7 @@ impl Default for PoolConfig {
             max_block_size: MAX_BLOCK_SIZE,
             max_operation_pool_size: 32000,
             max_operation_pool_excess_items: 10000,
+            max_operations_per_sender: 1000,
             max_endorsements_pool_size_per_thread: 1000,
             max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
             max_block_endorsement_count: ENDORSEMENT_COUNT,
@@ -35,6 +36,8 @@ impl Default for PoolConfig {
             max_denunciations_per_block_header: MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
             last_start_period: 0,
             operation_pool_refresh_interval: MassaTime::from_millis(2000),
+            operations_file_path: None,
+            operations_save_interval: MassaTime::from_millis(60000),
             operation_max_future_start_delay: T0.saturating_mul(5),
         }
     }
@@ -12,7 +12,8 @@ use std::sync::{
 use massa_models::config::THREAD_COUNT;
 use massa_models::denunciation::{Denunciation, DenunciationPrecursor};
 use massa_models::{
-    block_id::BlockId, endorsement::EndorsementId, operation::OperationId, slot::Slot,
+    address::Address, block_id::BlockId, endorsement::EndorsementId, operation::OperationId,
+    slot::Slot, stats::PoolMemoryStats,
 };
 use massa_storage::Storage;
 use massa_time::MassaTime;
@@ -76,6 +77,11 @@ pub enum MockPoolControllerMessage {
         /// Response channel
         response_tx: mpsc::Sender<usize>,
     },
+    /// Get the memory used by the pool
+    GetMemoryStats {
+        /// Response channel
+        response_tx: mpsc::Sender<PoolMemoryStats>,
+    },
     /// Get denunciation count
     GetDenunciationCount {
         /// Response channel
@@ -95,6 +101,15 @@ pub enum MockPoolControllerMessage {
         /// Response channel
         response_tx: mpsc::Sender<Vec<bool>>,
     },
+    /// Get the pending operations of an address
+    GetAddressOperations {
+        /// address sending or receiving the operations
+        address: Address,
+        /// max number of returned operations
+        limit: usize,
+        /// Response channel
+        response_tx: mpsc::Sender<Vec<OperationId>>,
+    },
     /// Get stats of the pool
     GetStats {
         /// Response channel
@@ -221,6 +236,16 @@ impl PoolController for MockPoolController {
         response_rx.recv().unwrap()
     }
 
+    fn get_memory_stats(&self) -> PoolMemoryStats {
+        let (response_tx, response_rx) = mpsc::channel();
+        self.q
+            .lock()
+            .unwrap()
+            .send(MockPoolControllerMessage::GetMemoryStats { response_tx })
+            .unwrap();
+        response_rx.recv().unwrap()
+    }
+
     fn contains_endorsements(&self, endorsements: &[EndorsementId]) -> Vec<bool> {
         let (response_tx, response_rx) = mpsc::channel();
         self.q
@@ -247,6 +272,20 @@ impl PoolController for MockPoolController {
         response_rx.recv().unwrap()
     }
 
+    fn get_address_operations(&self, address: &Address, limit: usize) -> Vec<OperationId> {
+        let (response_tx, response_rx) = mpsc::channel();
+        self.q
+            .lock()
+            .unwrap()
+            .send(MockPoolControllerMessage::GetAddressOperations {
+                address: *address,
+                limit,
+                response_tx,
+            })
+            .unwrap();
+        response_rx.recv().unwrap()
+    }
+
     fn notify_final_cs_periods(&mut self, final_cs_periods: &[u64]) {
         self.last_final_cs_periods = final_cs_periods.to_vec();
         self.q
@@ -3,8 +3,15 @@
 //! Pool controller implementation
 
 use massa_models::{
-    block_id::BlockId, denunciation::Denunciation, denunciation::DenunciationPrecursor,
-    endorsement::EndorsementId, operation::OperationId, slot::Slot,
+    address::Address,
+    block_id::BlockId,
+    config::{ENDORSEMENT_MEMORY_SIZE_ESTIMATE, OPERATION_MEMORY_SIZE_ESTIMATE},
+    denunciation::Denunciation,
+    denunciation::DenunciationPrecursor,
+    endorsement::EndorsementId,
+    operation::OperationId,
+    slot::Slot,
+    stats::{CacheMemoryStats, PoolMemoryStats},
 };
 use massa_pool_exports::{PoolConfig, PoolController, PoolManager};
 use massa_storage::Storage;
@@ -35,7 +42,7 @@ pub enum Command {
 #[derive(Clone)]
 pub struct PoolControllerImpl {
     /// Config
-    pub(crate) _config: PoolConfig,
+    pub(crate) config: PoolConfig,
     /// Shared reference to the operation pool
     pub(crate) operation_pool: Arc<RwLock<OperationPool>>,
     /// Shared reference to the endorsement pool
@@ -204,6 +211,24 @@ impl PoolController for PoolControllerImpl {
         self.operation_pool.read().len()
     }
 
+    /// Get the memory used by the operations and endorsements of the pool
+    fn get_memory_stats(&self) -> PoolMemoryStats {
+        PoolMemoryStats {
+            operations: CacheMemoryStats::new(
+                self.operation_pool.read().len(),
+                self.config.max_operation_pool_size,
+                OPERATION_MEMORY_SIZE_ESTIMATE,
+            ),
+            endorsements: CacheMemoryStats::new(
+                self.endorsement_pool.read().len(),
+                self.config
+                    .max_endorsements_pool_size_per_thread
+                    .saturating_mul(self.config.thread_count as usize),
+                ENDORSEMENT_MEMORY_SIZE_ESTIMATE,
+            ),
+        }
+    }
+
     /// Check if the pool contains a list of endorsements. Returns one boolean per item.
     fn contains_endorsements(&self, endorsements: &[EndorsementId]) -> Vec<bool> {
         let lck = self.endorsement_pool.read();
@@ -216,6 +241,13 @@ impl PoolController for PoolControllerImpl {
         operations.iter().map(|id| lck.contains(id)).collect()
     }
 
+    /// Get the pending operations sent by or to an address, from the best to the worst score
+    fn get_address_operations(&self, address: &Address, limit: usize) -> Vec<OperationId> {
+        self.operation_pool
+            .read()
+            .get_address_operations(address, limit)
+    }
+
     /// Check if the pool contains a denunciation. Returns a boolean
     #[cfg(feature = "testing")]
     fn contains_denunciation(&self, denunciation: &Denunciation) -> bool {
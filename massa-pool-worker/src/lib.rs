@@ -13,6 +13,7 @@ mod controller_impl;
 mod denunciation_pool;
 mod endorsement_pool;
 mod operation_pool;
+mod operations_file;
 mod types;
 mod worker;
 
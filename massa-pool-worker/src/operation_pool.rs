@@ -1,21 +1,30 @@
 //! Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use massa_hash::Hash;
 use massa_models::{
     address::Address,
     amount::Amount,
-    operation::OperationId,
+    operation::{OperationId, OperationType},
     prehash::{CapacityAllocator, PreHashMap, PreHashSet},
     slot::Slot,
     timeslots::get_latest_block_slot_at_timestamp,
 };
 use massa_pool_exports::{PoolChannels, PoolConfig};
+use massa_serialization::SerializeError;
 use massa_storage::Storage;
 use massa_time::MassaTime;
 use massa_wallet::Wallet;
 use parking_lot::RwLock;
-use std::{cmp::max, cmp::Ordering, cmp::PartialOrd, collections::BTreeSet, sync::Arc};
+use std::{
+    cmp::max,
+    cmp::Ordering,
+    cmp::PartialOrd,
+    collections::{hash_map::Entry, BTreeSet, HashMap},
+    sync::Arc,
+};
 use tracing::{debug, warn};
 
+use crate::operations_file;
 use crate::types::OperationInfo;
 
 pub struct OperationPool {
@@ -182,6 +191,68 @@ impl OperationPool {
         self.storage.drop_operation_refs(&removed);
     }
 
+    /// Replace by fee: among the operations of a sender with the same content and expire period,
+    /// which only differ by their fee, only keep the one with the highest fee.
+    /// The operations with a different content are distinct operations, all kept.
+    /// On equal fees, the operation that arrived first is kept.
+    /// Assumes that the ops are in arrival order, which is the case before the first sort of a refresh
+    /// as new ops are appended at the end of the container.
+    fn eliminate_replaced_operations(&mut self) {
+        let mut best_ops: HashMap<(Address, u64, Hash), usize> = HashMap::new();
+        for (index, op_info) in self.sorted_ops.iter().enumerate() {
+            match best_ops.entry((
+                op_info.creator_address,
+                op_info.expire_period(),
+                op_info.content_hash,
+            )) {
+                Entry::Vacant(entry) => {
+                    entry.insert(index);
+                }
+                Entry::Occupied(mut entry) => {
+                    if op_info.fee > self.sorted_ops[*entry.get()].fee {
+                        entry.insert(index);
+                    }
+                }
+            }
+        }
+        if best_ops.len() == self.sorted_ops.len() {
+            return;
+        }
+        let kept: PreHashSet<OperationId> = best_ops
+            .into_values()
+            .map(|index| self.sorted_ops[index].id)
+            .collect();
+        let mut removed = PreHashSet::default();
+        self.sorted_ops.retain(|op_info| {
+            if kept.contains(&op_info.id) {
+                return true;
+            }
+            removed.insert(op_info.id);
+            false
+        });
+        // drop from storage
+        self.storage.drop_operation_refs(&removed);
+    }
+
+    /// Only keep the `max_operations_per_sender` best operations of each sender,
+    /// so that a single address cannot fill the pool.
+    /// Assumes that the ops are sorted from the best to the worst score.
+    fn eliminate_sender_excess(&mut self) {
+        let mut sender_counts: PreHashMap<Address, usize> = PreHashMap::default();
+        let mut removed = PreHashSet::default();
+        self.sorted_ops.retain(|op_info| {
+            let count = sender_counts.entry(op_info.creator_address).or_default();
+            if *count >= self.config.max_operations_per_sender {
+                removed.insert(op_info.id);
+                return false;
+            }
+            *count += 1;
+            true
+        });
+        // drop from storage
+        self.storage.drop_operation_refs(&removed);
+    }
+
     /// Eliminate all operations that would cause a sender balance overflow.
     /// Assumes that the ops are sorted by ascending score.
     fn eliminate_balance_overflows(&mut self, sender_balances: &PreHashMap<Address, Amount>) {
@@ -332,6 +403,9 @@ impl OperationPool {
         // pre-filter to eliminate obviously uninteresting ops
         self.prefilter_ops(&exec_statuses, &pos_draws, &sender_balances);
 
+        // keep the highest fee among the ops of a sender with the same content and expire period
+        self.eliminate_replaced_operations();
+
         // score operations
         let scores = self.score_operations(&exec_statuses, &pos_draws);
 
@@ -348,10 +422,23 @@ impl OperationPool {
         // eliminate balance overflows in sorted ops
         self.eliminate_balance_overflows(&sender_balances);
 
+        // eliminate the ops exceeding the per-sender limit
+        self.eliminate_sender_excess();
+
         // eliminate container size overflows
         self.truncate_container();
     }
 
+    /// Serialize the pending operations, to save them to disk
+    pub(crate) fn serialize_operations(&self) -> Result<Vec<u8>, SerializeError> {
+        let ops = self.storage.read_operations();
+        operations_file::serialize_operations(
+            self.sorted_ops
+                .iter()
+                .filter_map(|op_info| ops.get(&op_info.id)),
+        )
+    }
+
     /// Get the number of stored elements
     pub fn len(&self) -> usize {
         self.sorted_ops.len()
@@ -362,6 +449,29 @@ impl OperationPool {
         self.storage.get_op_refs().contains(id)
     }
 
+    /// Get at most `limit` operations sent by `address`, or transferring coins or calling a smart contract at `address`.
+    /// The operations are listed from the best to the worst score, and the ones added since the last refresh come last.
+    pub fn get_address_operations(&self, address: &Address, limit: usize) -> Vec<OperationId> {
+        let ops = self.storage.read_operations();
+        self.sorted_ops
+            .iter()
+            .filter(|op_info| {
+                if &op_info.creator_address == address {
+                    return true;
+                }
+                match ops.get(&op_info.id).map(|op| &op.content.op) {
+                    Some(OperationType::Transaction {
+                        recipient_address, ..
+                    }) => recipient_address == address,
+                    Some(OperationType::CallSC { target_addr, .. }) => target_addr == address,
+                    _ => false,
+                }
+            })
+            .take(limit)
+            .map(|op_info| op_info.id)
+            .collect()
+    }
+
     /// notify of new final slot
     pub(crate) fn notify_final_cs_periods(&mut self, final_cs_periods: &[u64]) {
         // update internal final slot counter
@@ -381,20 +491,31 @@ impl OperationPool {
         // If there are too many extra operations,
         // we don't want the container to fill up too much in-between refreshes so we drop any excess.
         // This is because refreshing the container is very heavy and is only called periodically.
+        // The new operations paying the lowest fees are dropped first, so that a low-fee flood
+        // does not push out the other incoming operations.
         let dropped_items = self
             .sorted_ops
             .len()
             .saturating_add(new_op_ids.len())
             .saturating_sub(self.config.max_operation_pool_size)
             .saturating_sub(self.config.max_operation_pool_excess_items);
-        for _ in 0..dropped_items {
-            if let Some(id) = new_op_ids.iter().next().copied() {
+        if dropped_items > 0 {
+            let mut new_op_fees: Vec<(Amount, OperationId)> = {
+                let ops = ops_storage.read_operations();
+                new_op_ids
+                    .iter()
+                    .map(|id| {
+                        let op = ops
+                            .get(id)
+                            .expect("operation not found in storage but listed as owned");
+                        (op.content.fee, *id)
+                    })
+                    .collect()
+            };
+            new_op_fees.sort_unstable_by_key(|(fee, _)| *fee);
+            for (_, id) in new_op_fees.into_iter().take(dropped_items) {
                 new_op_ids.remove(&id);
-            } else {
-                break;
             }
-        }
-        if dropped_items > 0 {
             warn!(
                 "Operation pool excess limit reached. Dropping {} non-scored operations.",
                 dropped_items
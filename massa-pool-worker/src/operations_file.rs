@@ -0,0 +1,90 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+//! File in which the operation pool saves its pending operations.
+//!
+//! The operations are saved periodically and when the pool stops, then reloaded at startup
+//! so that a restart of the node does not drop the operations submitted by the users.
+//! The reloaded operations go through the usual checks of the pool on its first refresh:
+//! the expired, executed or unaffordable ones are dropped there.
+
+use std::{fs, io, path::Path};
+
+use massa_models::{
+    config::{
+        MAX_DATASTORE_VALUE_LENGTH, MAX_FUNCTION_NAME_LENGTH, MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+        MAX_OPERATION_DATASTORE_KEY_LENGTH, MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+        MAX_PARAMETERS_SIZE,
+    },
+    operation::{OperationDeserializer, SecureShareOperation},
+    secure_share::{SecureShareDeserializer, SecureShareSerializer},
+};
+use massa_serialization::{DeserializeError, Deserializer, SerializeError, Serializer};
+use tracing::warn;
+
+/// Serialize `operations` one after the other, in the format of the file
+pub(crate) fn serialize_operations<'a>(
+    operations: impl Iterator<Item = &'a SecureShareOperation>,
+) -> Result<Vec<u8>, SerializeError> {
+    let serializer = SecureShareSerializer::new();
+    let mut buffer = Vec::new();
+    for op in operations {
+        serializer.serialize(op, &mut buffer)?;
+    }
+    Ok(buffer)
+}
+
+/// Write the serialized operations to the file at `path`.
+/// The previous file is only replaced once the new one is fully written.
+pub(crate) fn write_operations(path: &Path, buffer: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, buffer)?;
+    fs::rename(tmp_path, path)
+}
+
+/// Read the operations saved at `path`, if any.
+/// Stops at the first invalid operation, and drops the ones whose signature is invalid.
+pub(crate) fn load_operations(path: &Path) -> Vec<SecureShareOperation> {
+    let buffer = match fs::read(path) {
+        Ok(buffer) => buffer,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Vec::new(),
+        Err(err) => {
+            warn!(
+                "could not read the saved operations {}: {}",
+                path.display(),
+                err
+            );
+            return Vec::new();
+        }
+    };
+    let deserializer = SecureShareDeserializer::new(OperationDeserializer::new(
+        MAX_DATASTORE_VALUE_LENGTH,
+        MAX_FUNCTION_NAME_LENGTH,
+        MAX_PARAMETERS_SIZE,
+        MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+        MAX_OPERATION_DATASTORE_KEY_LENGTH,
+        MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+    ));
+    let mut operations = Vec::new();
+    let mut rest = buffer.as_slice();
+    while !rest.is_empty() {
+        match deserializer.deserialize::<DeserializeError>(rest) {
+            Ok((new_rest, op)) => {
+                if op.verify_signature().is_ok() {
+                    operations.push(op);
+                }
+                rest = new_rest;
+            }
+            Err(err) => {
+                warn!(
+                    "ignoring the end of the saved operations {}: {}",
+                    path.display(),
+                    err
+                );
+                break;
+            }
+        }
+    }
+    operations
+}
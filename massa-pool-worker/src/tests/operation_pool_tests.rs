@@ -12,18 +12,37 @@
 //! Same as classic but we try to add irrelevant operation. (See the definition
 //! chapter below)
 //!
+//! # Operation eviction
+//! Function: [`test_operation_eviction`]
+//! Operations of a sender with the same content and expire period are replaced by the one
+//! with the highest fee, and only the best operations of a sender are kept.
+//!
+//! # Distinct operations with the same expire period
+//! Function: [`test_distinct_operations_not_replaced`]
+//! The operations of a sender with the same expire period but different contents are all kept.
+//!
+//! # Operations of an address
+//! Function: [`test_get_address_operations`]
+//! The pending operations sent by an address or transferring coins to it are listed.
+//!
+//! # Operations file
+//! Function: [`test_operations_file`]
+//! The pending operations saved to disk are reloaded with their signature checked.
+//!
 //! # Definition
 //! Relevant operation: Operation with a validity range corresponding to the
 //! latest period given his own thread. All operation which doesn't fit these
 //! requirements are "irrelevant"
 //!
+use crate::operations_file::{load_operations, serialize_operations, write_operations};
 use crate::tests::tools::OpGenerator;
 
 use super::tools::{create_some_operations, operation_pool_test, PoolTestBoilerPlate};
 use massa_execution_exports::MockExecutionController;
-use massa_models::{amount::Amount, operation::OperationId, slot::Slot};
+use massa_models::{address::Address, amount::Amount, operation::OperationId, slot::Slot};
 use massa_pool_exports::PoolConfig;
 use massa_pos_exports::MockSelectorController;
+use massa_signature::KeyPair;
 use std::time::Duration;
 
 #[test]
@@ -149,6 +168,282 @@ fn test_add_irrelevant_operation() {
     );
 }
 
+/// Test the replacement by fee and the per-sender limit applied on refresh.
+/// # Initialization
+/// Add 4 operations of a single sender, with at most 2 operations kept per sender:
+/// * `replaced` and `replacing` share their content and expire period, `replacing` paying a higher fee
+/// * `best` pays the highest fee and `worst` the lowest one
+#[test]
+fn test_operation_eviction() {
+    let mut pool_config = PoolConfig::default();
+    pool_config.max_operations_per_sender = 2;
+    let execution_controller = {
+        let mut res = Box::new(MockExecutionController::new());
+        res.expect_clone_box().returning(|| {
+            let mut story = MockExecutionController::new();
+            story
+                .expect_get_ops_exec_status()
+                .returning(|ops| vec![(None, None); ops.len()]);
+            story
+                .expect_get_final_and_candidate_balance()
+                .returning(|addrs| {
+                    vec![
+                        (
+                            // Operations need to be paid for
+                            Some(Amount::const_init(1_000_000_000, 0)),
+                            Some(Amount::const_init(1_000_000_000, 0)),
+                        );
+                        addrs.len()
+                    ]
+                });
+
+            Box::new(story)
+        });
+        res
+    };
+    let selector_controller = {
+        let mut res = Box::new(MockSelectorController::new());
+        res.expect_clone_box().times(2).returning(|| {
+            //TODO: Add sequence
+            let mut story = MockSelectorController::new();
+            story.expect_get_address_selections().returning(|_, _, _| {
+                let mut all_slots = Vec::new();
+                for i in 0..15 {
+                    for j in 0..32 {
+                        all_slots.push(Slot::new(i, j));
+                    }
+                }
+                Ok((all_slots.clone(), vec![]))
+            });
+            Box::new(story)
+        });
+        res
+    };
+    operation_pool_test(
+        pool_config,
+        execution_controller,
+        selector_controller,
+        |mut operation_pool, mut storage| {
+            let op_gen = OpGenerator::default()
+                .creator(KeyPair::generate(0).unwrap())
+                .receiver(KeyPair::generate(0).unwrap());
+            let replaced = op_gen
+                .clone()
+                .expirery(5)
+                .fee(Amount::from_raw(10))
+                .generate();
+            let replacing = op_gen
+                .clone()
+                .expirery(5)
+                .fee(Amount::from_raw(20))
+                .generate();
+            let best = op_gen
+                .clone()
+                .expirery(6)
+                .fee(Amount::from_raw(30))
+                .generate();
+            let worst = op_gen
+                .clone()
+                .expirery(7)
+                .fee(Amount::from_raw(5))
+                .generate();
+            let ids = [replaced.id, replacing.id, best.id, worst.id];
+            storage.store_operations(vec![replaced, replacing, best, worst]);
+            operation_pool.add_operations(storage);
+            // Allow some time for the pool to add and refresh the operations
+            std::thread::sleep(Duration::from_secs(3));
+            assert_eq!(operation_pool.get_operation_count(), 2);
+            assert_eq!(
+                operation_pool.contains_operations(&ids),
+                vec![false, true, true, false]
+            );
+        },
+    );
+}
+
+/// Test that the operations of a sender are only replaced by the same operation at a higher fee.
+/// # Initialization
+/// Add transfers of a single sender with the same expire period, as sent within a period:
+/// each one to a different recipient or of a different amount.
+#[test]
+fn test_distinct_operations_not_replaced() {
+    let execution_controller = {
+        let mut res = Box::new(MockExecutionController::new());
+        res.expect_clone_box().returning(|| {
+            let mut story = MockExecutionController::new();
+            story
+                .expect_get_ops_exec_status()
+                .returning(|ops| vec![(None, None); ops.len()]);
+            story
+                .expect_get_final_and_candidate_balance()
+                .returning(|addrs| {
+                    vec![
+                        (
+                            // Operations need to be paid for
+                            Some(Amount::const_init(1_000_000_000, 0)),
+                            Some(Amount::const_init(1_000_000_000, 0)),
+                        );
+                        addrs.len()
+                    ]
+                });
+
+            Box::new(story)
+        });
+        res
+    };
+    let selector_controller = {
+        let mut res = Box::new(MockSelectorController::new());
+        res.expect_clone_box().times(2).returning(|| {
+            let mut story = MockSelectorController::new();
+            story.expect_get_address_selections().returning(|_, _, _| {
+                let mut all_slots = Vec::new();
+                for i in 0..15 {
+                    for j in 0..32 {
+                        all_slots.push(Slot::new(i, j));
+                    }
+                }
+                Ok((all_slots.clone(), vec![]))
+            });
+            Box::new(story)
+        });
+        res
+    };
+    operation_pool_test(
+        PoolConfig::default(),
+        execution_controller,
+        selector_controller,
+        |mut operation_pool, mut storage| {
+            let op_gen = OpGenerator::default()
+                .creator(KeyPair::generate(0).unwrap())
+                .expirery(5);
+            let recipient = KeyPair::generate(0).unwrap();
+            let mut ops: Vec<_> = (1..=3)
+                .map(|fee| op_gen.clone().fee(Amount::from_raw(fee)).generate())
+                .collect();
+            ops.extend((1..=3).map(|amount| {
+                op_gen
+                    .clone()
+                    .receiver(recipient.clone())
+                    .amount(Amount::from_raw(amount))
+                    .fee(Amount::from_raw(10))
+                    .generate()
+            }));
+            let ids: Vec<OperationId> = ops.iter().map(|op| op.id).collect();
+            storage.store_operations(ops);
+            operation_pool.add_operations(storage);
+            // Allow some time for the pool to add and refresh the operations
+            std::thread::sleep(Duration::from_secs(3));
+            assert_eq!(operation_pool.get_operation_count(), ids.len());
+            assert_eq!(
+                operation_pool.contains_operations(&ids),
+                vec![true; ids.len()]
+            );
+        },
+    );
+}
+
+/// Test the listing of the pending operations involving an address.
+/// # Initialization
+/// Add an operation sent by the address, a transfer to the address and an unrelated operation.
+#[test]
+fn test_get_address_operations() {
+    let execution_controller = {
+        let mut res = Box::new(MockExecutionController::new());
+        res.expect_clone_box().returning(|| {
+            let mut story = MockExecutionController::new();
+            story
+                .expect_get_ops_exec_status()
+                .returning(|ops| vec![(None, None); ops.len()]);
+            story
+                .expect_get_final_and_candidate_balance()
+                .returning(|addrs| {
+                    vec![
+                        (
+                            // Operations need to be paid for
+                            Some(Amount::const_init(1_000_000_000, 0)),
+                            Some(Amount::const_init(1_000_000_000, 0)),
+                        );
+                        addrs.len()
+                    ]
+                });
+
+            Box::new(story)
+        });
+        res
+    };
+    let selector_controller = {
+        let mut res = Box::new(MockSelectorController::new());
+        res.expect_clone_box().times(2).returning(|| {
+            //TODO: Add sequence
+            let mut story = MockSelectorController::new();
+            story.expect_get_address_selections().returning(|_, _, _| {
+                let mut all_slots = Vec::new();
+                for i in 0..15 {
+                    for j in 0..32 {
+                        all_slots.push(Slot::new(i, j));
+                    }
+                }
+                Ok((all_slots.clone(), vec![]))
+            });
+            Box::new(story)
+        });
+        res
+    };
+    operation_pool_test(
+        PoolConfig::default(),
+        execution_controller,
+        selector_controller,
+        |mut operation_pool, mut storage| {
+            let keypair = KeyPair::generate(0).unwrap();
+            let address = Address::from_public_key(&keypair.get_public_key());
+            let sent = OpGenerator::default()
+                .creator(keypair.clone())
+                .expirery(2)
+                .generate();
+            let received = OpGenerator::default()
+                .receiver(keypair)
+                .expirery(2)
+                .generate();
+            let unrelated = OpGenerator::default().expirery(2).generate();
+            let mut expected = vec![sent.id, received.id];
+            expected.sort();
+            storage.store_operations(vec![sent, received, unrelated]);
+            operation_pool.add_operations(storage);
+            // Allow some time for the pool to add the operations
+            std::thread::sleep(Duration::from_millis(100));
+
+            let mut ops = operation_pool.get_address_operations(&address, 10);
+            ops.sort();
+            assert_eq!(ops, expected);
+            assert_eq!(operation_pool.get_address_operations(&address, 1).len(), 1);
+        },
+    );
+}
+
+/// Test that the saved operations are reloaded, and that the end of a truncated file is ignored.
+#[test]
+fn test_operations_file() {
+    let path = std::env::temp_dir().join(format!(
+        "massa_pool_operations_{}.bin",
+        KeyPair::generate(0).unwrap().get_public_key()
+    ));
+    let op_gen = OpGenerator::default().expirery(2);
+    let ops = create_some_operations(3, &op_gen);
+    let ids: Vec<OperationId> = ops.iter().map(|op| op.id).collect();
+
+    let buffer = serialize_operations(ops.iter()).unwrap();
+    write_operations(&path, &buffer).unwrap();
+    let loaded: Vec<OperationId> = load_operations(&path).iter().map(|op| op.id).collect();
+    assert_eq!(loaded, ids);
+
+    write_operations(&path, &buffer[..buffer.len() - 1]).unwrap();
+    let loaded: Vec<OperationId> = load_operations(&path).iter().map(|op| op.id).collect();
+    assert_eq!(loaded, ids[..2]);
+
+    std::fs::remove_file(&path).unwrap();
+    assert!(load_operations(&path).is_empty());
+}
+
 /// TODO refactor old tests
 #[test]
 fn test_pool() {
@@ -202,7 +497,11 @@ fn test_pool() {
         mut pool_manager,
         mut pool_controller,
         storage: storage_base,
-    } = PoolTestBoilerPlate::pool_test(pool_config, execution_controller, selector_controller);
+    } = PoolTestBoilerPlate::pool_test(
+        pool_config.clone(),
+        execution_controller,
+        selector_controller,
+    );
 
     // // generate (id, transactions, range of validity) by threads
     let mut thread_tx_lists = vec![Vec::new(); pool_config.thread_count as usize];
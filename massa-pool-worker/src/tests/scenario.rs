@@ -92,7 +92,7 @@ fn test_simple_get_operations() {
         mut pool_manager,
         mut pool_controller,
         mut storage,
-    } = PoolTestBoilerPlate::pool_test(config, execution_controller, selector_controller);
+    } = PoolTestBoilerPlate::pool_test(config.clone(), execution_controller, selector_controller);
 
     // setup storage
     storage.store_operations(ops);
@@ -24,7 +24,7 @@ use massa_wallet::test_exports::create_test_wallet;
 use parking_lot::RwLock;
 use tokio::sync::broadcast;
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub(crate) struct OpGenerator {
     creator: Option<KeyPair>,
     receiver: Option<KeyPair>,
@@ -39,7 +39,6 @@ impl OpGenerator {
         self
     }
 
-    #[allow(dead_code)]
     pub(crate) fn amount(mut self, amount: Amount) -> Self {
         self.amount = Some(amount);
         self
@@ -50,7 +49,6 @@ impl OpGenerator {
         self
     }
 
-    #[allow(dead_code)]
     pub(crate) fn receiver(mut self, receiver: KeyPair) -> Self {
         self.receiver = Some(receiver);
         self
@@ -1,8 +1,10 @@
+use massa_hash::Hash;
 use massa_models::{
     address::Address,
     amount::Amount,
-    operation::{OperationId, SecureShareOperation},
+    operation::{OperationId, OperationTypeSerializer, SecureShareOperation},
 };
+use massa_serialization::Serializer;
 use std::ops::RangeInclusive;
 
 #[derive(Debug, Clone)]
@@ -16,9 +18,17 @@ pub struct OperationInfo {
     /// max amount that the op might spend from the sender's balance
     pub max_spending: Amount,
     pub validity_period_range: RangeInclusive<u64>,
+    /// hash of the content of the op without its fee and expire period:
+    /// the ops of a sender with the same content and expire period only differ by their fee
+    pub content_hash: Hash,
 }
 
 impl OperationInfo {
+    /// last period at which the op can be included in a block
+    pub fn expire_period(&self) -> u64 {
+        *self.validity_period_range.end()
+    }
+
     pub fn from_op(
         op: &SecureShareOperation,
         operation_validity_periods: u64,
@@ -34,6 +44,16 @@ impl OperationInfo {
             thread: op.content_creator_address.get_thread(thread_count),
             validity_period_range: op.get_validity_range(operation_validity_periods),
             max_spending: op.get_max_spending(roll_price),
+            content_hash: content_hash(op),
         }
     }
 }
+
+fn content_hash(op: &SecureShareOperation) -> Hash {
+    let mut buffer = Vec::new();
+    match OperationTypeSerializer::new().serialize(&op.content.op, &mut buffer) {
+        Ok(()) => Hash::compute_from(&buffer),
+        // unique to the op: it never replaces another one
+        Err(_) => Hash::compute_from(op.id.to_bytes()),
+    }
+}
@@ -5,12 +5,14 @@
 use crate::controller_impl::{Command, PoolManagerImpl};
 use crate::denunciation_pool::DenunciationPool;
 use crate::operation_pool::OperationPool;
+use crate::operations_file;
 use crate::{controller_impl::PoolControllerImpl, endorsement_pool::EndorsementPool};
 use massa_pool_exports::PoolConfig;
 use massa_pool_exports::{PoolChannels, PoolController, PoolManager};
 use massa_storage::Storage;
 use massa_wallet::Wallet;
 use parking_lot::RwLock;
+use std::path::Path;
 use std::time::Instant;
 use std::{
     sync::mpsc::{sync_channel, Receiver, RecvError, RecvTimeoutError},
@@ -18,7 +20,7 @@ use std::{
     thread,
     thread::JoinHandle,
 };
-use tracing::warn;
+use tracing::{info, warn};
 
 /// Endorsement pool write thread instance
 pub(crate) struct EndorsementPoolThread {
@@ -97,11 +99,36 @@ impl OperationPoolThread {
             .expect("failed to spawn thread: operation-pool")
     }
 
+    /// Save the pending operations to `path`, to reload them after a restart
+    fn save_operations(&self, path: &Path) {
+        let buffer = match self.operation_pool.read().serialize_operations() {
+            Ok(buffer) => buffer,
+            Err(err) => {
+                warn!("could not serialize the pending operations: {}", err);
+                return;
+            }
+        };
+        if let Err(err) = operations_file::write_operations(path, &buffer) {
+            warn!(
+                "could not save the pending operations to {}: {}",
+                path.display(),
+                err
+            );
+        }
+    }
+
     /// Run the thread.
     fn run(self, config: PoolConfig) {
         let mut next_refresh = Instant::now();
+        let mut next_save = Instant::now()
+            .checked_add(config.operations_save_interval.to_duration())
+            .expect("could not compute time of next op pool save");
         loop {
-            match self.receiver.recv_deadline(next_refresh) {
+            let deadline = match config.operations_file_path {
+                Some(_) => next_refresh.min(next_save),
+                None => next_refresh,
+            };
+            match self.receiver.recv_deadline(deadline) {
                 Err(RecvTimeoutError::Disconnected) | Ok(Command::Stop) => break,
                 Ok(Command::AddItems(operations)) => {
                     self.operation_pool.write().add_operations(operations)
@@ -122,6 +149,15 @@ impl OperationPoolThread {
                     .checked_add(config.operation_pool_refresh_interval.to_duration())
                     .expect("could not compute time of next op pool refresh")
             }
+            if let Some(path) = &config.operations_file_path && next_save <= Instant::now() {
+                self.save_operations(path);
+                next_save = Instant::now()
+                    .checked_add(config.operations_save_interval.to_duration())
+                    .expect("could not compute time of next op pool save")
+            }
+        }
+        if let Some(path) = &config.operations_file_path {
+            self.save_operations(path);
         }
     }
 }
@@ -194,20 +230,37 @@ pub fn start_pool_controller(
     let (denunciations_input_sender, denunciations_input_receiver) =
         sync_channel(config.denunciations_channel_size);
     let operation_pool = Arc::new(RwLock::new(OperationPool::init(
-        config,
+        config.clone(),
         storage,
         channels.clone(),
         wallet.clone(),
     )));
+    if let Some(path) = &config.operations_file_path {
+        // reload the operations saved before the last stop, they are checked again on the first refresh
+        let saved_operations = operations_file::load_operations(path);
+        if !saved_operations.is_empty() {
+            info!(
+                "Reloading {} operations saved in {}",
+                saved_operations.len(),
+                path.display()
+            );
+            let mut ops_storage = storage.clone_without_refs();
+            ops_storage.store_operations(saved_operations);
+            operation_pool.write().add_operations(ops_storage);
+        }
+    }
     let endorsement_pool = Arc::new(RwLock::new(EndorsementPool::init(
-        config,
+        config.clone(),
         storage,
         channels.clone(),
         wallet,
     )));
-    let denunciation_pool = Arc::new(RwLock::new(DenunciationPool::init(config, channels)));
+    let denunciation_pool = Arc::new(RwLock::new(DenunciationPool::init(
+        config.clone(),
+        channels,
+    )));
     let controller = PoolControllerImpl {
-        _config: config,
+        config: config.clone(),
         operation_pool: operation_pool.clone(),
         endorsement_pool: endorsement_pool.clone(),
         denunciation_pool: denunciation_pool.clone(),
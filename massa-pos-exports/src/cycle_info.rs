@@ -19,6 +19,7 @@ use nom::{
     IResult, Parser,
 };
 use num::rational::Ratio;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, VecDeque};
 use std::ops::Bound::Included;
@@ -293,7 +294,7 @@ impl Deserializer<CycleInfo> for CycleInfoDeserializer {
 }
 
 /// Block production statistics
-#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
 pub struct ProductionStats {
     /// Number of successfully created blocks
     pub block_success_count: u64,
@@ -14,6 +14,7 @@ use nom::{
     sequence::tuple,
     IResult, Parser,
 };
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::{collections::BTreeMap, ops::RangeBounds};
 use std::{
@@ -23,7 +24,7 @@ use std::{
 
 const DEFERRED_CREDITS_HASH_INITIAL_BYTES: &[u8; HASH_XOF_SIZE_BYTES] = &[0; HASH_XOF_SIZE_BYTES];
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
 /// Structure containing all the PoS deferred credits information
 pub struct DeferredCredits {
     /// Deferred credits
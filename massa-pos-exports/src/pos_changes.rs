@@ -14,12 +14,40 @@ use nom::{
     sequence::tuple,
     IResult, Parser,
 };
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+/// Serde representation of a `BitVec<u8>`, described for the JSON schema only
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+#[schemars(rename = "BitSeq")]
+struct BitVecSchema {
+    /// bit ordering of the elements
+    order: String,
+    /// index of the first bit in the first element
+    head: BitIdxSchema,
+    /// number of bits
+    bits: u64,
+    /// elements holding the bits
+    data: Vec<u8>,
+}
+
+/// Serde representation of a bit index, described for the JSON schema only
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+#[schemars(rename = "BitIdx")]
+struct BitIdxSchema {
+    /// width of the element in bits
+    width: u8,
+    /// index of the bit in the element
+    index: u8,
+}
+
 /// Recap of all PoS changes
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct PoSChanges {
     /// extra block seed bits added
+    #[schemars(with = "BitVecSchema")]
     pub seed_bits: BitVec<u8>,
 
     /// new roll counts for addresses (can be 0 to remove the address from the registry)
@@ -6,7 +6,7 @@ use crate::{
 use crate::{DeferredCredits, PoSConfig};
 use bitvec::vec::BitVec;
 use massa_db_exports::{
-    DBBatch, MassaDirection, MassaIteratorMode, ShareableMassaDBController,
+    DBBatch, MassaDBError, MassaDirection, MassaIteratorMode, ShareableMassaDBController,
     CYCLE_HISTORY_DESER_ERROR, CYCLE_HISTORY_PREFIX, CYCLE_HISTORY_SER_ERROR,
     DEFERRED_CREDITS_DESER_ERROR, DEFERRED_CREDITS_PREFIX, DEFERRED_CREDITS_SER_ERROR, STATE_CF,
 };
@@ -221,12 +221,13 @@ impl PoSFinalState {
     /// Reset the state of the PoS final state
     ///
     /// USED ONLY FOR BOOTSTRAP
-    pub fn reset(&mut self) {
+    pub fn reset(&mut self) -> Result<(), MassaDBError> {
         let mut db = self.db.write();
-        db.delete_prefix(CYCLE_HISTORY_PREFIX, STATE_CF, None);
-        db.delete_prefix(DEFERRED_CREDITS_PREFIX, STATE_CF, None);
+        db.delete_prefix(CYCLE_HISTORY_PREFIX, STATE_CF, None)?;
+        db.delete_prefix(DEFERRED_CREDITS_PREFIX, STATE_CF, None)?;
         self.cycle_history_cache = Default::default();
         self.rng_seed_cache = None;
+        Ok(())
     }
 
     /// Create the initial cycle based off the initial rolls.
@@ -567,7 +568,7 @@ impl PoSFinalState {
         &self,
         cycle: u64,
         final_state_hash: HashXof<HASH_XOF_SIZE_BYTES>,
-    ) {
+    ) -> Result<(), MassaDBError> {
         if self.get_cycle_index(cycle).is_some() {
             let mut batch = DBBatch::new();
             self.put_cycle_history_final_state_hash_snapshot(
@@ -576,7 +577,7 @@ impl PoSFinalState {
                 &mut batch,
             );
 
-            self.db.write().write_batch(batch, Default::default(), None);
+            self.db.write().write_batch(batch, Default::default(), None)
         } else {
             panic!("cycle {} should be contained here", cycle);
         }
@@ -1098,6 +1099,18 @@ impl PoSFinalState {
         }
     }
 
+    /// Final state hash snapshot of a complete cycle, taken at its last slot.
+    /// Returns `None` if the cycle is not in the history or not complete.
+    pub fn get_complete_cycle_state_hash(
+        &self,
+        cycle: u64,
+    ) -> Option<HashXof<HASH_XOF_SIZE_BYTES>> {
+        if !self.is_cycle_complete(cycle)? {
+            return None;
+        }
+        self.get_cycle_history_final_state_hash_snapshot(cycle)
+    }
+
     /// Check if a cycle is complete (all slots finalized)
     pub fn is_cycle_complete(&self, cycle: u64) -> Option<bool> {
         let key = complete_key!(self.cycle_history_cycle_prefix(cycle));
@@ -1634,7 +1647,8 @@ mod tests {
         pos_state
             .db
             .write()
-            .write_batch(batch, DBBatch::new(), None);
+            .write_batch(batch, DBBatch::new(), None)
+            .unwrap();
 
         // Recompute the cache, and assert we do not miss any data
         // We .clear() the cache explicitly, even though we do not need to, to make sure the recomputation works
@@ -1730,7 +1744,8 @@ mod tests {
         let mut batch = DBBatch::new();
         pos_state.create_initial_cycle(&mut batch);
         db.write()
-            .write_batch(batch, Default::default(), Some(Slot::new(0, 0)));
+            .write_batch(batch, Default::default(), Some(Slot::new(0, 0)))
+            .unwrap();
 
         // add changes
         let addr = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
@@ -1756,7 +1771,8 @@ mod tests {
             .apply_changes_to_batch(changes, Slot::new(0, 0), false, &mut batch)
             .unwrap();
         db.write()
-            .write_batch(batch, Default::default(), Some(Slot::new(0, 0)));
+            .write_batch(batch, Default::default(), Some(Slot::new(0, 0)))
+            .unwrap();
 
         // update changes once
         roll_changes.clear();
@@ -1781,7 +1797,8 @@ mod tests {
             .apply_changes_to_batch(changes, Slot::new(0, 1), false, &mut batch)
             .unwrap();
         db.write()
-            .write_batch(batch, Default::default(), Some(Slot::new(0, 1)));
+            .write_batch(batch, Default::default(), Some(Slot::new(0, 1)))
+            .unwrap();
 
         // update changes twice
         roll_changes.clear();
@@ -1807,7 +1824,8 @@ mod tests {
             .apply_changes_to_batch(changes, Slot::new(1, 0), false, &mut batch)
             .unwrap();
         db.write()
-            .write_batch(batch, Default::default(), Some(Slot::new(1, 0)));
+            .write_batch(batch, Default::default(), Some(Slot::new(1, 0)))
+            .unwrap();
 
         let cycles = pos_state.get_cycle_history_cycles();
         assert_eq!(cycles.len(), 1, "wrong number of cycles");
@@ -1,6 +1,9 @@
 use crate::CycleDraws;
 use massa_hash::Hash;
-use massa_models::{address::Address, slot::Slot};
+use massa_models::{
+    address::Address,
+    slot::{Slot, SlotRange},
+};
 use massa_pos_exports::{PosError, PosResult, Selection, SelectorConfig};
 use rand::{distributions::Distribution, SeedableRng};
 use rand_distr::WeightedAliasIndex;
@@ -45,7 +48,7 @@ pub(crate) fn perform_draws(
     })?;
 
     // perform cycle draws
-    let mut cur_slot = Slot::new_first_of_cycle(cycle, cfg.periods_per_cycle).map_err(|err| {
+    let first_slot = Slot::new_first_of_cycle(cycle, cfg.periods_per_cycle).map_err(|err| {
         PosError::OverflowError(format!("start slot overflow in perform_draws: {}", err))
     })?;
     let last_slot = Slot::new_last_of_cycle(cycle, cfg.periods_per_cycle, cfg.thread_count)
@@ -61,7 +64,7 @@ pub(crate) fn perform_draws(
 
     let mut five_first_slots: Vec<(Slot, Selection)> = Vec::new();
     let mut count = 0;
-    loop {
+    for cur_slot in SlotRange::new_inclusive(first_slot, last_slot, cfg.thread_count) {
         // draw block creator
         let producer = if cur_slot.period > 0 {
             addresses[dist.sample(&mut rng)]
@@ -85,13 +88,6 @@ pub(crate) fn perform_draws(
         }
         // add to draws
         cycle_draws.draws.insert(cur_slot, selection);
-
-        if cur_slot == last_slot {
-            break;
-        }
-        cur_slot = cur_slot.get_next_slot(cfg.thread_count).map_err(|err| {
-            PosError::OverflowError(format!("iteration slot overflow in perform_draws: {}", err))
-        })?;
     }
 
     debug!(
@@ -23,6 +23,17 @@ pub struct PeerData {
     pub category: String,
 }
 
+/// Peer of the peer database, as exported to seed the peer database of another node
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KnownPeer {
+    /// addresses the peer listens on
+    pub listeners: HashMap<SocketAddr, TransportType>,
+    /// timestamp of the last announcement received from the peer, in milliseconds
+    pub last_announce: u64,
+    /// true if the peer was tested successfully
+    pub trusted: bool,
+}
+
 /// Peers that are transmitted during bootstrap
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct BootstrapPeers(pub Vec<(PeerId, HashMap<SocketAddr, TransportType>)>);
@@ -4,11 +4,11 @@ use std::collections::HashMap;
 use std::net::SocketAddr;
 
 use crate::error::ProtocolError;
-use crate::BootstrapPeers;
+use crate::{BootstrapPeers, KnownPeer, TransportType};
 
 use crate::PeerId;
 use massa_models::prehash::{PreHashMap, PreHashSet};
-use massa_models::stats::NetworkStats;
+use massa_models::stats::{NetworkStats, PeerStats, ProtocolMemoryStats};
 use massa_models::{block_header::SecuredHeader, block_id::BlockId};
 use massa_storage::Storage;
 use peernet::peer::PeerConnectionType;
@@ -69,6 +69,12 @@ pub trait ProtocolController: Send + Sync {
         ProtocolError,
     >;
 
+    /// Get the memory used by the caches of already checked blocks, operations and endorsements
+    fn get_memory_stats(&self) -> Result<ProtocolMemoryStats, ProtocolError>;
+
+    /// Get the bandwidth and message counters of each connected peer
+    fn get_peers_detail(&self) -> Result<Vec<PeerStats>, ProtocolError>;
+
     /// Get a list of peers to be sent to someone that bootstrap to us
     fn get_bootstrap_peers(&self) -> Result<BootstrapPeers, ProtocolError>;
 
@@ -78,6 +84,18 @@ pub trait ProtocolController: Send + Sync {
     /// Unban a list of Peer Id
     fn unban_peers(&self, peer_ids: Vec<PeerId>) -> Result<(), ProtocolError>;
 
+    /// Read the initial peers file again, and test its peers to add them to the known peers
+    fn reload_initial_peers(&self) -> Result<(), ProtocolError>;
+
+    /// Get the peers of the peer database that are not banned
+    fn get_known_peers(&self) -> Result<HashMap<PeerId, KnownPeer>, ProtocolError>;
+
+    /// Test the given peers to add them to the known peers, as the ones of the initial peers file
+    fn add_known_peers(
+        &self,
+        peers: HashMap<PeerId, HashMap<SocketAddr, TransportType>>,
+    ) -> Result<(), ProtocolError>;
+
     /// Returns a boxed clone of self.
     /// Useful to allow cloning `Box<dyn ProtocolController>`.
     fn clone_box(&self) -> Box<dyn ProtocolController>;
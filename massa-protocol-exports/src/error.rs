@@ -1,7 +1,7 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use displaydoc::Display;
-use massa_models::error::ModelsError;
+use massa_models::{error::ModelsError, slot::Slot};
 use massa_pos_exports::PosError;
 use massa_versioning::versioning_factory::FactoryError;
 use std::net::IpAddr;
@@ -41,6 +41,14 @@ pub enum ProtocolError {
     ContainerInconsistencyError(String),
     /// Invalid operation error: {0}
     InvalidOperationError(String),
+    /// Invalid endorsement error: {0}
+    InvalidEndorsementError(String),
+    /// No draw for slot {0}: its cycle is not drawn yet or was pruned
+    SlotNotDrawn(Slot),
+    /// Invalid key rotation: {0}
+    InvalidKeyRotationError(String),
+    /// Connection error: {0}
+    ConnectionError(String),
     /// Listener error: {0}
     ListenerError(String),
     /// Incompatible network version: local current is {local} received is {received}
@@ -63,6 +71,65 @@ pub enum ProtocolError {
     PosError(#[from] PosError),
 }
 
+/// Cause of a protocol error, deciding how the node reacts to it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtocolErrorKind {
+    /// A peer sent invalid data: it is penalized
+    PeerFault,
+    /// A resource was temporarily unavailable (full channel, closed connection...): the data is dropped
+    /// or the action is retried later, and no peer is penalized
+    Transient,
+    /// A bug or a broken invariant of the node: it is reported loudly, and no peer is penalized
+    Internal,
+}
+
+impl ProtocolError {
+    /// Error of the lookup of the draws of `slot`, a slot received from a peer.
+    /// The slots with no draw are too far from the current cycle: the peer is at fault.
+    pub fn from_selection_error(slot: Slot, err: PosError) -> Self {
+        match err {
+            PosError::CycleUnavailable(_) => ProtocolError::SlotNotDrawn(slot),
+            err => ProtocolError::PosError(err),
+        }
+    }
+
+    /// Cause of the error
+    pub fn kind(&self) -> ProtocolErrorKind {
+        match self {
+            ProtocolError::WrongSignature
+            | ProtocolError::InvalidIpError(_)
+            | ProtocolError::ModelsError(_)
+            | ProtocolError::InvalidOperationError(_)
+            | ProtocolError::InvalidEndorsementError(_)
+            | ProtocolError::SlotNotDrawn(_)
+            | ProtocolError::InvalidKeyRotationError(_)
+            | ProtocolError::IncompatibleNetworkVersion { .. }
+            | ProtocolError::OutdatedAnnouncedNetworkVersion { .. } => ProtocolErrorKind::PeerFault,
+            ProtocolError::ChannelError(_)
+            | ProtocolError::PeerConnectionError(_)
+            | ProtocolError::IOError(_)
+            | ProtocolError::MissingPeersError
+            | ProtocolError::SendError(_)
+            | ProtocolError::ConnectionError(_) => ProtocolErrorKind::Transient,
+            ProtocolError::PosError(err) => match err {
+                PosError::ChannelDown(_)
+                | PosError::CycleUnavailable(_)
+                | PosError::CycleUnfinished(_) => ProtocolErrorKind::Transient,
+                _ => ProtocolErrorKind::Internal,
+            },
+            // the catch-all: the peer faults have their own variants
+            ProtocolError::GeneralProtocolError(_)
+            | ProtocolError::SerdeError(_)
+            | ProtocolError::UnexpectedNodeCommandChannelClosure
+            | ProtocolError::UnexpectedWriterClosure
+            | ProtocolError::TimeError(_)
+            | ProtocolError::ContainerInconsistencyError(_)
+            | ProtocolError::ListenerError(_)
+            | ProtocolError::FactoryError(_) => ProtocolErrorKind::Internal,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum NetworkConnectionErrorType {
     CloseConnectionWithNoConnectionToClose(IpAddr),
@@ -70,3 +137,67 @@ pub enum NetworkConnectionErrorType {
     ToManyConnectionAttempt(IpAddr),
     ToManyConnectionFailure(IpAddr),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_kinds() {
+        let slot = Slot::new(12, 3);
+        for (err, kind) in [
+            (ProtocolError::WrongSignature, ProtocolErrorKind::PeerFault),
+            (
+                ProtocolError::SlotNotDrawn(slot),
+                ProtocolErrorKind::PeerFault,
+            ),
+            (
+                ProtocolError::InvalidEndorsementError("wrong creator".to_string()),
+                ProtocolErrorKind::PeerFault,
+            ),
+            (
+                ProtocolError::InvalidKeyRotationError("same key".to_string()),
+                ProtocolErrorKind::PeerFault,
+            ),
+            (
+                ProtocolError::SendError("channel full".to_string()),
+                ProtocolErrorKind::Transient,
+            ),
+            (
+                ProtocolError::ConnectionError("max connections reached".to_string()),
+                ProtocolErrorKind::Transient,
+            ),
+            (
+                ProtocolError::PosError(PosError::ChannelDown("stopped".to_string())),
+                ProtocolErrorKind::Transient,
+            ),
+            (
+                ProtocolError::PosError(PosError::OverflowError("rolls".to_string())),
+                ProtocolErrorKind::Internal,
+            ),
+            (
+                ProtocolError::GeneralProtocolError("unexpected".to_string()),
+                ProtocolErrorKind::Internal,
+            ),
+            (
+                ProtocolError::ContainerInconsistencyError("missing".to_string()),
+                ProtocolErrorKind::Internal,
+            ),
+        ] {
+            assert_eq!(err.kind(), kind, "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_selection_errors() {
+        let slot = Slot::new(12, 3);
+        // a slot with no draw is a fault of the peer that sent it
+        let err = ProtocolError::from_selection_error(slot, PosError::CycleUnavailable(1));
+        assert!(matches!(err, ProtocolError::SlotNotDrawn(s) if s == slot));
+        assert_eq!(err.kind(), ProtocolErrorKind::PeerFault);
+        // not the other errors of the selector
+        let err =
+            ProtocolError::from_selection_error(slot, PosError::ChannelDown("stopped".to_string()));
+        assert_eq!(err.kind(), ProtocolErrorKind::Transient);
+    }
+}
@@ -3,16 +3,20 @@ mod controller_trait;
 mod error;
 mod peer_id;
 mod settings;
+mod signer;
 
 pub use bootstrap_peers::{
-    BootstrapPeers, BootstrapPeersDeserializer, BootstrapPeersSerializer, PeerData,
+    BootstrapPeers, BootstrapPeersDeserializer, BootstrapPeersSerializer, KnownPeer, PeerData,
 };
 pub use controller_trait::{ProtocolController, ProtocolManager};
-pub use error::ProtocolError;
+pub use error::{ProtocolError, ProtocolErrorKind};
 pub use peer_id::{PeerId, PeerIdDeserializer, PeerIdSerializer};
 pub use peernet::peer::PeerConnectionType;
 pub use peernet::transports::TransportType;
-pub use settings::{PeerCategoryInfo, ProtocolConfig};
+pub use settings::{
+    ChaosConfig, MessageRateClass, MessageRateClasses, PeerCategoryInfo, ProtocolConfig,
+};
+pub use signer::HandshakeSigner;
 
 #[cfg(feature = "testing")]
 pub mod test_exports;
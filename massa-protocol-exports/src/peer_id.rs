@@ -28,7 +28,7 @@ impl PeerId {
     ) -> Result<(), ProtocolError> {
         self.public_key
             .verify_signature(hash, signature)
-            .map_err(|err| ProtocolError::GeneralProtocolError(err.to_string()))
+            .map_err(|_| ProtocolError::WrongSignature)
     }
 }
 
@@ -11,12 +11,60 @@ use massa_time::MassaTime;
 use peernet::transports::TransportType;
 use serde::Deserialize;
 
+/// Faults injected in the network layer to soak-test the node. Rates are probabilities between 0 and 1.
+#[derive(Debug, Deserialize, Clone, Copy)]
+pub struct ChaosConfig {
+    /// probability for a message sent to a peer to close the connection instead
+    pub kill_connection_rate: f64,
+    /// probability for a message sent to a peer to be dropped
+    pub drop_message_rate: f64,
+    /// probability for the sending of a message to be stalled for `stall_duration`
+    pub stall_rate: f64,
+    /// duration of a stalled sending
+    pub stall_duration: MassaTime,
+    /// probability for a received message to have one of its bytes flipped
+    pub corrupt_message_rate: f64,
+}
+
 #[derive(Debug, Deserialize, Clone, Copy)]
 pub struct PeerCategoryInfo {
     pub allow_local_peers: bool,
     pub target_out_connections: usize,
     pub max_in_connections: usize,
     pub max_in_connections_per_ip: usize,
+    /// peers of this category are not banned when they misbehave, only on request of the node operator
+    #[serde(default)]
+    pub ban_immune: bool,
+    /// bandwidth allowance: max bytes received per second from each peer of this category,
+    /// the next messages are dropped. 0 for no limit
+    #[serde(default)]
+    pub max_bytes_received_per_second: u64,
+}
+
+/// Limits applied to one type of message
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+pub struct MessageRateClass {
+    /// max number of messages of this type received from a peer per second, the next ones are dropped. 0 for no limit
+    #[serde(default)]
+    pub max_per_second: u64,
+    /// priority of the messages of this type sent to peers, None to let the handlers choose
+    #[serde(default)]
+    pub high_priority: Option<bool>,
+}
+
+/// Limits of each class of messages
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+pub struct MessageRateClasses {
+    /// block headers
+    pub block_header: MessageRateClass,
+    /// asks for block infos
+    pub ask_for_blocks: MessageRateClass,
+    /// block infos sent in answer to our asks
+    pub reply_for_blocks: MessageRateClass,
+    pub endorsement: MessageRateClass,
+    pub operation: MessageRateClass,
+    /// peer announcements and lists
+    pub peer_management: MessageRateClass,
 }
 
 /// Dynamic protocol configuration mix in static settings and constants configurations.
@@ -24,6 +72,18 @@ pub struct PeerCategoryInfo {
 pub struct ProtocolConfig {
     /// self keypair
     pub keypair_file: PathBuf,
+    /// keypair replaced by the one of `keypair_file`, if the node key was rotated
+    pub previous_keypair_file: Option<PathBuf>,
+    /// seed from which the keypair is derived instead of `keypair_file`, for test networks only
+    pub keypair_seed: Option<String>,
+    /// file where all the received messages are recorded, to be replayed later
+    pub message_record_file: Option<PathBuf>,
+    /// size in bytes after which the recording file is rotated
+    pub message_record_max_file_size: u64,
+    /// number of rotated recording files kept
+    pub message_record_rotated_files: u32,
+    /// faults injected in the network layer, for testing purposes only
+    pub chaos: Option<ChaosConfig>,
     /// listeners from where we can receive messages
     pub listeners: HashMap<SocketAddr, TransportType>,
     /// initial peers path
@@ -144,8 +204,18 @@ pub struct ProtocolConfig {
     pub last_start_period: u64,
     /// try connection timer
     pub try_connection_timer: MassaTime,
+    /// the node is isolated when it has fewer connected peers than this
+    pub isolation_threshold: usize,
     /// Max in connections
     pub max_in_connections: usize,
+    /// Max number of handshakes in progress at the same time, the connections beyond it are refused
+    pub max_concurrent_handshakes: usize,
+    /// Max number of handshakes in progress at the same time with the same IP
+    pub max_handshakes_per_ip: usize,
+    /// Max duration of a handshake, the connection is closed beyond it
+    pub handshake_timeout: MassaTime,
+    /// rate limits and priorities of each type of message
+    pub message_rate_classes: MessageRateClasses,
     /// Timeout connection
     pub timeout_connection: MassaTime,
     /// Timeout message
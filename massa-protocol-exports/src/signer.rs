@@ -0,0 +1,28 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use crate::ProtocolError;
+use massa_hash::Hash;
+use massa_models::error::ModelsError;
+use massa_signature::{KeyPair, PublicKey, Signature};
+
+/// Signer of the handshakes (listeners announcement and challenge) with the node key
+pub trait HandshakeSigner: Send + Sync {
+    /// Returns true if the secret key of `public_key` is held by this signer
+    fn holds(&self, public_key: &PublicKey) -> bool;
+
+    /// Signs `hash` with the secret key of `public_key`
+    fn sign(&self, public_key: &PublicKey, hash: &Hash) -> Result<Signature, ProtocolError>;
+}
+
+impl HandshakeSigner for KeyPair {
+    fn holds(&self, public_key: &PublicKey) -> bool {
+        self.get_public_key() == *public_key
+    }
+
+    fn sign(&self, public_key: &PublicKey, hash: &Hash) -> Result<Signature, ProtocolError> {
+        if !self.holds(public_key) {
+            return Err(ProtocolError::WrongSignature);
+        }
+        Ok(KeyPair::sign(self, hash).map_err(ModelsError::from)?)
+    }
+}
@@ -12,6 +12,12 @@ impl Default for ProtocolConfig {
                 .expect("cannot create temp file")
                 .path()
                 .to_path_buf(),
+            previous_keypair_file: None,
+            keypair_seed: None,
+            message_record_file: None,
+            message_record_max_file_size: 100_000_000,
+            message_record_rotated_files: 2,
+            chaos: None,
             ask_block_timeout: MassaTime::from_millis(500),
             max_known_blocks_saved_size: 300,
             max_known_blocks_size: 100,
@@ -77,8 +83,13 @@ impl Default for ProtocolConfig {
             read_write_limit_bytes_per_second: 1024 * 1000,
             timeout_connection: MassaTime::from_millis(1000),
             try_connection_timer: MassaTime::from_millis(5000),
+            isolation_threshold: 1,
             routable_ip: None,
             max_in_connections: 10,
+            max_concurrent_handshakes: 10,
+            max_handshakes_per_ip: 10,
+            handshake_timeout: MassaTime::from_millis(10000),
+            message_rate_classes: Default::default(),
             debug: true,
             peers_categories: HashMap::default(),
             default_category_info: PeerCategoryInfo {
@@ -86,6 +97,8 @@ impl Default for ProtocolConfig {
                 max_in_connections: 10,
                 target_out_connections: 10,
                 max_in_connections_per_ip: 0,
+                ban_immune: false,
+                max_bytes_received_per_second: 0,
             },
             version: "TEST.23.2".parse().unwrap(),
         }
@@ -0,0 +1,66 @@
+//! Run the byzantine peer against a running node, for example a devnet node:
+//!
+//! ```sh
+//! cargo run -p massa_protocol_worker --features byzantine --example byzantine_peer -- \
+//!     <node protocol address> <node version> [thread count]
+//! ```
+//!
+//! Each attack is reported with whether the node reacted as expected.
+//! Check in the logs and the peer stats of the node that it kept working meanwhile.
+
+use std::{net::SocketAddr, str::FromStr, time::Duration};
+
+use massa_models::{
+    block_header::SecuredHeader,
+    config::{MAX_ASK_BLOCKS_PER_MESSAGE, THREAD_COUNT},
+    slot::Slot,
+    version::Version,
+};
+use massa_protocol_exports::ProtocolError;
+use massa_protocol_worker::byzantine::{ByzantinePeer, LiveNodeLink, MalformedHandshake};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn main() -> Result<(), ProtocolError> {
+    let mut args = std::env::args().skip(1);
+    let usage = "usage: byzantine_peer <node protocol address> <node version> [thread count]";
+    let addr = SocketAddr::from_str(&args.next().expect(usage)).expect("invalid node address");
+    let version = Version::from_str(&args.next().expect(usage)).expect("invalid node version");
+    let thread_count = args
+        .next()
+        .map(|arg| arg.parse().expect("invalid thread count"))
+        .unwrap_or(THREAD_COUNT);
+
+    for malformed in MalformedHandshake::ALL {
+        let closed = LiveNodeLink::send_malformed_handshake(
+            addr,
+            &ByzantinePeer::new(),
+            version,
+            malformed,
+            TIMEOUT,
+        )?;
+        println!(
+            "{:?} handshake: connection closed by the node: {}",
+            malformed, closed
+        );
+    }
+
+    let peer = ByzantinePeer::new();
+    let mut link = LiveNodeLink::connect(addr, &peer, version, TIMEOUT)?;
+    println!("connected to {} as {}", link.node_peer_id, peer.peer_id);
+
+    let header: SecuredHeader =
+        peer.send_header_with_unknown_parents(&mut link, Slot::new(1, 0), thread_count)?;
+    println!("sent header {} with unknown parents", header.id);
+    peer.send_oversized_ask(&mut link, MAX_ASK_BLOCKS_PER_MESSAGE as usize + 1)?;
+    println!("sent an ask of {} blocks", MAX_ASK_BLOCKS_PER_MESSAGE + 1);
+    peer.flood_asks(&mut link, 10_000)?;
+    println!("sent 10000 asks for unknown blocks");
+    peer.send_header_with_wrong_signature(&mut link, header)?;
+    println!("sent a header with a wrong signature: the node should ban us");
+    match link.receive() {
+        Ok(_) => println!("the node is still sending to us"),
+        Err(err) => println!("the node stopped sending to us: {}", err),
+    }
+    Ok(())
+}
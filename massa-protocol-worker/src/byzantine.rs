@@ -0,0 +1,407 @@
+//! Adversarial peer sending invalid or abusive messages, to check how a node resists them.
+//!
+//! The messages go through a `ByzantineLink`: the mock network of the protocol tests,
+//! or a `LiveNodeLink` connected to a running node, for example on a devnet
+//! (see `examples/byzantine_peer.rs`, built with the `byzantine` feature).
+
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+    net::{Shutdown, SocketAddr, TcpStream},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+
+use massa_hash::Hash;
+use massa_models::{
+    block_header::{BlockHeader, BlockHeaderSerializer, SecuredHeader},
+    block_id::BlockId,
+    secure_share::SecureShareContent,
+    slot::Slot,
+    version::{Version, VersionSerializer},
+};
+use massa_protocol_exports::{PeerId, PeerIdDeserializer, PeerIdSerializer, ProtocolError};
+use massa_serialization::{DeserializeError, Deserializer, Serializer};
+use massa_signature::{KeyPair, Signature};
+use peernet::messages::MessagesSerializer as PeerNetMessagesSerializer;
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+use crate::{
+    context::Context,
+    handlers::{
+        block_handler::{AskForBlocksInfo, BlockMessage, BlockMessageSerializer},
+        endorsement_handler::EndorsementMessageSerializer,
+        operation_handler::OperationMessageSerializer,
+        peer_handler::{Announcement, AnnouncementSerializer, PeerManagementMessageSerializer},
+    },
+    messages::{Message, MessagesSerializer},
+};
+
+/// Maximal length of a frame read from a live node
+const MAX_FRAME_LENGTH: u32 = 1_048_576_000;
+
+/// Channel through which a `ByzantinePeer` sends its messages
+pub trait ByzantineLink {
+    /// Send `data` as a message of `peer_id`
+    fn send_raw(&mut self, peer_id: &PeerId, data: &[u8]) -> Result<(), ProtocolError>;
+}
+
+/// Ways to corrupt a handshake
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MalformedHandshake {
+    /// handshake cut in the middle of the peer id
+    Truncated,
+    /// version of another network
+    IncompatibleVersion,
+    /// announcement signed by another key than the one of the peer id
+    WrongAnnouncementSignature,
+    /// handshake id that is neither an announcement nor a message
+    UnknownId,
+    /// frame announcing a length beyond the limit of the receiver
+    OversizedLength,
+}
+
+impl MalformedHandshake {
+    /// All the ways to corrupt a handshake
+    pub const ALL: [MalformedHandshake; 5] = [
+        MalformedHandshake::Truncated,
+        MalformedHandshake::IncompatibleVersion,
+        MalformedHandshake::WrongAnnouncementSignature,
+        MalformedHandshake::UnknownId,
+        MalformedHandshake::OversizedLength,
+    ];
+}
+
+/// Peer misbehaving on purpose
+pub struct ByzantinePeer {
+    pub keypair: KeyPair,
+    pub peer_id: PeerId,
+    message_serializer: MessagesSerializer,
+}
+
+impl ByzantinePeer {
+    /// Create a new peer with a random key
+    pub fn new() -> Self {
+        let keypair = KeyPair::generate(0).expect("could not generate a keypair");
+        ByzantinePeer {
+            peer_id: PeerId::from_public_key(keypair.get_public_key()),
+            keypair,
+            message_serializer: MessagesSerializer::new()
+                .with_block_message_serializer(BlockMessageSerializer::new())
+                .with_endorsement_message_serializer(EndorsementMessageSerializer::new())
+                .with_operation_message_serializer(OperationMessageSerializer::new())
+                .with_peer_management_message_serializer(PeerManagementMessageSerializer::new()),
+        }
+    }
+
+    /// Send arbitrary bytes as a message
+    pub fn send_raw(
+        &self,
+        link: &mut impl ByzantineLink,
+        data: &[u8],
+    ) -> Result<(), ProtocolError> {
+        link.send_raw(&self.peer_id, data)
+    }
+
+    /// Send a valid message
+    pub fn send_message(
+        &self,
+        link: &mut impl ByzantineLink,
+        message: &Message,
+    ) -> Result<(), ProtocolError> {
+        self.send_with_trailing_bytes(link, message, &[])
+    }
+
+    /// Send a valid message followed by `trailing` garbage bytes
+    pub fn send_with_trailing_bytes(
+        &self,
+        link: &mut impl ByzantineLink,
+        message: &Message,
+        trailing: &[u8],
+    ) -> Result<(), ProtocolError> {
+        let mut data = Vec::new();
+        self.message_serializer
+            .serialize(message, &mut data)
+            .map_err(|err| ProtocolError::GeneralProtocolError(err.to_string()))?;
+        data.extend_from_slice(trailing);
+        self.send_raw(link, &data)
+    }
+
+    /// Send `header` after replacing its signature by a signature of another content
+    pub fn send_header_with_wrong_signature(
+        &self,
+        link: &mut impl ByzantineLink,
+        mut header: SecuredHeader,
+    ) -> Result<(), ProtocolError> {
+        header.signature = self
+            .keypair
+            .sign(&Hash::compute_from(b"not the header"))
+            .map_err(|err| ProtocolError::GeneralProtocolError(err.to_string()))?;
+        self.send_message(
+            link,
+            &Message::Block(Box::new(BlockMessage::BlockHeader(header))),
+        )
+    }
+
+    /// Send a correctly signed header at `slot` whose parents do not exist, returns it
+    pub fn send_header_with_unknown_parents(
+        &self,
+        link: &mut impl ByzantineLink,
+        slot: Slot,
+        thread_count: u8,
+    ) -> Result<SecuredHeader, ProtocolError> {
+        let header = BlockHeader::new_verifiable(
+            BlockHeader {
+                current_version: 0,
+                announced_version: None,
+                slot,
+                parents: (0..thread_count)
+                    .map(|thread| {
+                        BlockId(Hash::compute_from(
+                            format!("unknown parent {}", thread).as_bytes(),
+                        ))
+                    })
+                    .collect(),
+                operation_merkle_root: Hash::compute_from(&Vec::new()),
+                endorsements: Vec::new(),
+                denunciations: Vec::new(),
+            },
+            BlockHeaderSerializer::new(),
+            &self.keypair,
+        )?;
+        self.send_message(
+            link,
+            &Message::Block(Box::new(BlockMessage::BlockHeader(header.clone()))),
+        )?;
+        Ok(header)
+    }
+
+    /// Send `count` asks for the headers of blocks that do not exist
+    pub fn flood_asks(
+        &self,
+        link: &mut impl ByzantineLink,
+        count: usize,
+    ) -> Result<(), ProtocolError> {
+        for index in 0..count {
+            self.send_message(link, &unknown_blocks_ask(index, 1))?;
+        }
+        Ok(())
+    }
+
+    /// Send a single ask for `count` unknown blocks.
+    /// Beyond `max_size_block_infos` of the receiver, it can't be read.
+    pub fn send_oversized_ask(
+        &self,
+        link: &mut impl ByzantineLink,
+        count: usize,
+    ) -> Result<(), ProtocolError> {
+        self.send_message(link, &unknown_blocks_ask(0, count))
+    }
+
+    /// Handshake of this peer, announcing no listener, corrupted as requested.
+    /// `MalformedHandshake::OversizedLength` corrupts the frame, not its content: see `write_frame`.
+    pub fn handshake(
+        &self,
+        version: Version,
+        malformed: Option<MalformedHandshake>,
+    ) -> Result<Vec<u8>, ProtocolError> {
+        let version = match malformed {
+            Some(MalformedHandshake::IncompatibleVersion) => {
+                Version::from_str("BYZN.0.0").expect("invalid byzantine version")
+            }
+            _ => version,
+        };
+        let context = Context {
+            our_public_key: self.keypair.get_public_key(),
+            signer: Arc::new(self.keypair.clone()),
+        };
+        let mut announcement = Announcement::new(HashMap::new(), None, None, &context)
+            .map_err(|err| ProtocolError::GeneralProtocolError(err.to_string()))?;
+        if malformed == Some(MalformedHandshake::WrongAnnouncementSignature) {
+            announcement.signature = KeyPair::generate(0)
+                .and_then(|other_keypair| other_keypair.sign(&announcement.hash))
+                .map_err(|err| ProtocolError::GeneralProtocolError(err.to_string()))?;
+        }
+
+        let mut bytes = Vec::new();
+        PeerIdSerializer::new()
+            .serialize(&self.peer_id, &mut bytes)
+            .and_then(|_| VersionSerializer::new().serialize(&version, &mut bytes))
+            .map_err(|err| ProtocolError::GeneralProtocolError(err.to_string()))?;
+        bytes.push(match malformed {
+            Some(MalformedHandshake::UnknownId) => 42,
+            _ => 0,
+        });
+        AnnouncementSerializer::new()
+            .serialize(&announcement, &mut bytes)
+            .map_err(|err| ProtocolError::GeneralProtocolError(err.to_string()))?;
+        if malformed == Some(MalformedHandshake::Truncated) {
+            bytes.truncate(16);
+        }
+        Ok(bytes)
+    }
+
+    /// Sign the random bytes sent by the other side of a handshake
+    fn sign_challenge(&self, challenge: &[u8]) -> Result<Signature, ProtocolError> {
+        self.keypair
+            .sign(&Hash::compute_from(challenge))
+            .map_err(|err| ProtocolError::GeneralProtocolError(err.to_string()))
+    }
+}
+
+impl Default for ByzantinePeer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Ask for the headers of `count` blocks that do not exist, starting at `first_index`
+fn unknown_blocks_ask(first_index: usize, count: usize) -> Message {
+    Message::Block(Box::new(BlockMessage::AskForBlocks(
+        (first_index..first_index + count)
+            .map(|index| {
+                (
+                    BlockId(Hash::compute_from(
+                        format!("unknown block {}", index).as_bytes(),
+                    )),
+                    AskForBlocksInfo::Header,
+                )
+            })
+            .collect(),
+    )))
+}
+
+/// Write `data` in a frame, as the TCP transport does: length on 4 bytes (big endian) then data.
+/// With `oversized`, the frame announces a length far beyond the limits of the receivers.
+pub fn write_frame(stream: &mut TcpStream, data: &[u8], oversized: bool) -> std::io::Result<()> {
+    let length = if oversized {
+        u32::MAX
+    } else {
+        data.len() as u32
+    };
+    stream.write_all(&length.to_be_bytes())?;
+    stream.write_all(data)?;
+    stream.flush()
+}
+
+/// Read a frame written by `write_frame`
+pub fn read_frame(stream: &mut TcpStream) -> std::io::Result<Vec<u8>> {
+    let mut length = [0u8; 4];
+    stream.read_exact(&mut length)?;
+    let length = u32::from_be_bytes(length);
+    if length > MAX_FRAME_LENGTH {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("frame too long: {}", length),
+        ));
+    }
+    let mut data = vec![0u8; length as usize];
+    stream.read_exact(&mut data)?;
+    Ok(data)
+}
+
+/// Connection of a `ByzantinePeer` to a running node, after a valid handshake
+pub struct LiveNodeLink {
+    stream: TcpStream,
+    /// peer id of the node
+    pub node_peer_id: PeerId,
+}
+
+impl LiveNodeLink {
+    /// Connect to the node listening on `addr` and perform the handshake as `peer`.
+    /// `version` must be compatible with the version of the node.
+    pub fn connect(
+        addr: SocketAddr,
+        peer: &ByzantinePeer,
+        version: Version,
+        timeout: Duration,
+    ) -> Result<Self, ProtocolError> {
+        let mut stream = connect(addr, timeout)?;
+
+        // announcements
+        write_frame(&mut stream, &peer.handshake(version, None)?, false)?;
+        let received = read_frame(&mut stream)?;
+        let (_, node_peer_id) = PeerIdDeserializer::new()
+            .deserialize::<DeserializeError>(&received)
+            .map_err(|err| ProtocolError::GeneralProtocolError(err.to_string()))?;
+
+        // each side signs random bytes of the other side
+        let mut challenge = [0u8; 32];
+        StdRng::from_entropy().fill_bytes(&mut challenge);
+        write_frame(&mut stream, &challenge, false)?;
+        let node_challenge = read_frame(&mut stream)?;
+        write_frame(
+            &mut stream,
+            &peer.sign_challenge(&node_challenge)?.to_bytes(),
+            false,
+        )?;
+        let node_signature = Signature::from_bytes(&read_frame(&mut stream)?)
+            .map_err(|err| ProtocolError::GeneralProtocolError(err.to_string()))?;
+        node_peer_id.verify_signature(&Hash::compute_from(&challenge), &node_signature)?;
+
+        Ok(LiveNodeLink {
+            stream,
+            node_peer_id,
+        })
+    }
+
+    /// Connect to the node listening on `addr` and send it a handshake corrupted as `malformed`.
+    /// Returns whether the node closed the connection within `timeout`, as it should.
+    pub fn send_malformed_handshake(
+        addr: SocketAddr,
+        peer: &ByzantinePeer,
+        version: Version,
+        malformed: MalformedHandshake,
+        timeout: Duration,
+    ) -> Result<bool, ProtocolError> {
+        let mut stream = connect(addr, timeout)?;
+        write_frame(
+            &mut stream,
+            &peer.handshake(version, Some(malformed))?,
+            malformed == MalformedHandshake::OversizedLength,
+        )?;
+        // the node sends its own handshake first, then must close the connection
+        let mut buffer = [0u8; 4096];
+        loop {
+            match stream.read(&mut buffer) {
+                Ok(0) => return Ok(true),
+                Ok(_) => continue,
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    return Ok(false)
+                }
+                // reset by the node
+                Err(_) => return Ok(true),
+            }
+        }
+    }
+
+    /// Read the next message sent by the node
+    pub fn receive(&mut self) -> Result<Vec<u8>, ProtocolError> {
+        Ok(read_frame(&mut self.stream)?)
+    }
+}
+
+impl ByzantineLink for LiveNodeLink {
+    fn send_raw(&mut self, _peer_id: &PeerId, data: &[u8]) -> Result<(), ProtocolError> {
+        Ok(write_frame(&mut self.stream, data, false)?)
+    }
+}
+
+impl Drop for LiveNodeLink {
+    fn drop(&mut self) {
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+fn connect(addr: SocketAddr, timeout: Duration) -> Result<TcpStream, ProtocolError> {
+    let stream = TcpStream::connect_timeout(&addr, timeout)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    Ok(stream)
+}
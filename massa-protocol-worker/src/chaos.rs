@@ -0,0 +1,390 @@
+//! Fault injection in the network layer, used to soak-test the node against
+//! unreliable connections. Compiled with the `chaos` feature and enabled only when a `ChaosConfig` is set.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    net::SocketAddr,
+    sync::Arc,
+    time::Instant,
+};
+
+use crossbeam::channel::{unbounded, Receiver, RecvTimeoutError, Sender};
+use massa_protocol_exports::{ChaosConfig, PeerId, ProtocolError};
+use parking_lot::Mutex;
+use peernet::peer::PeerConnectionType;
+use rand::Rng;
+use tracing::debug;
+
+use crate::{
+    handlers::{
+        block_handler::BlockMessageSerializer, endorsement_handler::EndorsementMessageSerializer,
+        operation_handler::OperationMessageSerializer,
+        peer_handler::PeerManagementMessageSerializer,
+    },
+    messages::{Message, MessagesSerializer},
+    wrap_network::ActiveConnectionsTrait,
+};
+
+/// Check that all the rates of `config` are probabilities
+pub fn check_chaos_config(config: &ChaosConfig) -> Result<(), ProtocolError> {
+    for (name, rate) in [
+        ("kill_connection_rate", config.kill_connection_rate),
+        ("drop_message_rate", config.drop_message_rate),
+        ("stall_rate", config.stall_rate),
+        ("corrupt_message_rate", config.corrupt_message_rate),
+    ] {
+        if !(0.0..=1.0).contains(&rate) {
+            return Err(ProtocolError::GeneralProtocolError(format!(
+                "chaos {} must be between 0 and 1, got {}",
+                name, rate
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Stall of the writes to a peer
+struct PeerStall {
+    /// time at which the messages sent during the stall are delivered
+    until: Instant,
+    /// messages waiting for the end of the stall
+    queued: usize,
+}
+
+/// Message whose sending is delayed until the end of the stall of its peer
+struct StalledMessage {
+    send_at: Instant,
+    peer_id: PeerId,
+    message: Message,
+    high_priority: bool,
+}
+
+/// Active connections randomly killing connections, dropping messages and stalling writes.
+/// Stalled messages are sent by a background thread, so that the caller is never blocked:
+/// while a peer is stalled, all the messages sent to it are queued and delivered in order at the end of the stall.
+pub struct ChaosActiveConnections {
+    inner: Box<dyn ActiveConnectionsTrait>,
+    config: ChaosConfig,
+    stalls: Arc<Mutex<HashMap<PeerId, PeerStall>>>,
+    stalled_sender: Sender<StalledMessage>,
+}
+
+impl ChaosActiveConnections {
+    pub fn new(inner: Box<dyn ActiveConnectionsTrait>, config: ChaosConfig) -> Self {
+        let stalls = Arc::new(Mutex::new(HashMap::new()));
+        let (stalled_sender, stalled_receiver) = unbounded();
+        let thread_inner = inner.clone_box();
+        let thread_stalls = stalls.clone();
+        std::thread::Builder::new()
+            .name("protocol-chaos-stalls".to_string())
+            .spawn(move || send_stalled_messages(thread_inner, thread_stalls, stalled_receiver))
+            .expect("could not spawn the chaos stall thread");
+        ChaosActiveConnections {
+            inner,
+            config,
+            stalls,
+            stalled_sender,
+        }
+    }
+
+    /// Queue `message` if `peer_id` is stalled, possibly starting a new stall.
+    /// Returns the message back if it can be sent right away.
+    fn stall(
+        &self,
+        peer_id: &PeerId,
+        message: Message,
+        high_priority: bool,
+    ) -> Option<(Message, bool)> {
+        let now = Instant::now();
+        let mut stalls = self.stalls.lock();
+        if stalls
+            .get(peer_id)
+            .map_or(false, |stall| stall.queued == 0 && stall.until <= now)
+        {
+            stalls.remove(peer_id);
+        }
+        if !stalls.contains_key(peer_id) {
+            if !rand::thread_rng().gen_bool(self.config.stall_rate) {
+                return Some((message, high_priority));
+            }
+            debug!("chaos: stalling the messages to {}", peer_id);
+            stalls.insert(
+                peer_id.clone(),
+                PeerStall {
+                    until: now + self.config.stall_duration.to_duration(),
+                    queued: 0,
+                },
+            );
+        }
+        let stall = stalls.get_mut(peer_id).expect("stall inserted above");
+        let stalled = StalledMessage {
+            send_at: stall.until,
+            peer_id: peer_id.clone(),
+            message,
+            high_priority,
+        };
+        if self.stalled_sender.send(stalled).is_ok() {
+            stall.queued += 1;
+        }
+        None
+    }
+}
+
+/// Send the stalled messages at the end of the stall of their peer, until all the `ChaosActiveConnections` are dropped
+fn send_stalled_messages(
+    active_connections: Box<dyn ActiveConnectionsTrait>,
+    stalls: Arc<Mutex<HashMap<PeerId, PeerStall>>>,
+    receiver: Receiver<StalledMessage>,
+) {
+    let message_serializer = MessagesSerializer::new()
+        .with_block_message_serializer(BlockMessageSerializer::new())
+        .with_endorsement_message_serializer(EndorsementMessageSerializer::new())
+        .with_operation_message_serializer(OperationMessageSerializer::new())
+        .with_peer_management_message_serializer(PeerManagementMessageSerializer::new());
+    // ordered by sending time then by arrival, which keeps the order of the messages of each peer
+    let mut queue: BTreeMap<(Instant, u64), StalledMessage> = BTreeMap::new();
+    let mut sequence: u64 = 0;
+    loop {
+        let received = match queue.keys().next() {
+            Some((send_at, _)) => {
+                receiver.recv_timeout(send_at.saturating_duration_since(Instant::now()))
+            }
+            None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match received {
+            Ok(stalled) => {
+                queue.insert((stalled.send_at, sequence), stalled);
+                sequence += 1;
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        let now = Instant::now();
+        while queue
+            .keys()
+            .next()
+            .map_or(false, |(send_at, _)| *send_at <= now)
+        {
+            let (_, stalled) = queue.pop_first().expect("queue checked above");
+            if let Err(err) = active_connections.send_to_peer(
+                &stalled.peer_id,
+                &message_serializer,
+                stalled.message,
+                stalled.high_priority,
+            ) {
+                debug!(
+                    "chaos: could not send a stalled message to {}: {}",
+                    stalled.peer_id, err
+                );
+            }
+            let mut stalls = stalls.lock();
+            if let Some(stall) = stalls.get_mut(&stalled.peer_id) {
+                stall.queued = stall.queued.saturating_sub(1);
+            }
+        }
+        // forget the stalls that are over
+        stalls
+            .lock()
+            .retain(|_, stall| stall.queued > 0 || stall.until > now);
+    }
+}
+
+impl ActiveConnectionsTrait for ChaosActiveConnections {
+    fn send_to_peer(
+        &self,
+        peer_id: &PeerId,
+        message_serializer: &MessagesSerializer,
+        message: Message,
+        high_priority: bool,
+    ) -> Result<(), ProtocolError> {
+        let mut rng = rand::thread_rng();
+        if rng.gen_bool(self.config.kill_connection_rate) {
+            debug!("chaos: killing the connection to {}", peer_id);
+            self.inner.clone_box().shutdown_connection(peer_id);
+            return Err(ProtocolError::SendError(
+                "connection killed by chaos injection".to_string(),
+            ));
+        }
+        if rng.gen_bool(self.config.drop_message_rate) {
+            debug!("chaos: dropping a message to {}", peer_id);
+            return Ok(());
+        }
+        match self.stall(peer_id, message, high_priority) {
+            Some((message, high_priority)) => {
+                self.inner
+                    .send_to_peer(peer_id, message_serializer, message, high_priority)
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn ActiveConnectionsTrait> {
+        Box::new(ChaosActiveConnections {
+            inner: self.inner.clone_box(),
+            config: self.config,
+            stalls: self.stalls.clone(),
+            stalled_sender: self.stalled_sender.clone(),
+        })
+    }
+
+    fn get_peer_ids_connected(&self) -> HashSet<PeerId> {
+        self.inner.get_peer_ids_connected()
+    }
+
+    fn get_peers_connected(
+        &self,
+    ) -> HashMap<PeerId, (SocketAddr, PeerConnectionType, Option<String>)> {
+        self.inner.get_peers_connected()
+    }
+
+    fn get_nb_out_connections(&self) -> usize {
+        self.inner.get_nb_out_connections()
+    }
+
+    fn get_nb_in_connections(&self) -> usize {
+        self.inner.get_nb_in_connections()
+    }
+
+    fn shutdown_connection(&mut self, peer_id: &PeerId) {
+        self.inner.shutdown_connection(peer_id)
+    }
+
+    fn get_peers_connections_bandwidth(&self) -> HashMap<String, (u64, u64)> {
+        self.inner.get_peers_connections_bandwidth()
+    }
+}
+
+/// Returns a copy of `data` with one byte flipped, with a probability of `corrupt_message_rate`
+pub fn corrupt_message(config: &ChaosConfig, data: &[u8]) -> Option<Vec<u8>> {
+    let mut rng = rand::thread_rng();
+    if data.is_empty() || !rng.gen_bool(config.corrupt_message_rate) {
+        return None;
+    }
+    let mut corrupted = data.to_vec();
+    let index = rng.gen_range(0..corrupted.len());
+    corrupted[index] ^= rng.gen_range(1..=u8::MAX);
+    Some(corrupted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::peer_handler::PeerManagementMessage;
+    use massa_signature::KeyPair;
+    use massa_time::MassaTime;
+    use std::time::Duration;
+
+    /// Active connections keeping the number of peers of the `ListPeers` messages sent, with the sending time
+    #[derive(Clone, Default)]
+    struct SentMessages(Arc<Mutex<Vec<(usize, Instant)>>>);
+
+    impl ActiveConnectionsTrait for SentMessages {
+        fn send_to_peer(
+            &self,
+            _peer_id: &PeerId,
+            _message_serializer: &MessagesSerializer,
+            message: Message,
+            _high_priority: bool,
+        ) -> Result<(), ProtocolError> {
+            if let Message::PeerManagement(message) = message {
+                if let PeerManagementMessage::ListPeers(peers) = *message {
+                    self.0.lock().push((peers.len(), Instant::now()));
+                }
+            }
+            Ok(())
+        }
+
+        fn clone_box(&self) -> Box<dyn ActiveConnectionsTrait> {
+            Box::new(self.clone())
+        }
+
+        fn get_peer_ids_connected(&self) -> HashSet<PeerId> {
+            HashSet::new()
+        }
+
+        fn get_peers_connected(
+            &self,
+        ) -> HashMap<PeerId, (SocketAddr, PeerConnectionType, Option<String>)> {
+            HashMap::new()
+        }
+
+        fn get_nb_out_connections(&self) -> usize {
+            0
+        }
+
+        fn get_nb_in_connections(&self) -> usize {
+            0
+        }
+
+        fn shutdown_connection(&mut self, _peer_id: &PeerId) {}
+
+        fn get_peers_connections_bandwidth(&self) -> HashMap<String, (u64, u64)> {
+            HashMap::new()
+        }
+    }
+
+    fn chaos_config(corrupt_message_rate: f64) -> ChaosConfig {
+        ChaosConfig {
+            kill_connection_rate: 0.0,
+            drop_message_rate: 0.0,
+            stall_rate: 0.0,
+            stall_duration: MassaTime::from_millis(0),
+            corrupt_message_rate,
+        }
+    }
+
+    #[test]
+    fn test_corrupt_message() {
+        let data = vec![7u8; 32];
+        assert!(corrupt_message(&chaos_config(0.0), &data).is_none());
+
+        let always = chaos_config(1.0);
+        let corrupted = corrupt_message(&always, &data).unwrap();
+        assert_eq!(corrupted.len(), data.len());
+        assert_eq!(
+            corrupted.iter().zip(&data).filter(|(a, b)| a != b).count(),
+            1
+        );
+        assert!(corrupt_message(&always, &[]).is_none());
+        assert!(check_chaos_config(&chaos_config(1.5)).is_err());
+    }
+
+    #[test]
+    fn test_stalled_messages_do_not_block_the_sender() {
+        let sent = SentMessages::default();
+        let mut config = chaos_config(0.0);
+        config.stall_rate = 1.0;
+        config.stall_duration = MassaTime::from_millis(200);
+        let chaos = ChaosActiveConnections::new(Box::new(sent.clone()), config);
+        let peer_id = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+        let message_serializer = MessagesSerializer::new();
+
+        let start = Instant::now();
+        for peer_count in 0..3 {
+            let peers = vec![(peer_id.clone(), HashMap::new()); peer_count];
+            chaos
+                .send_to_peer(
+                    &peer_id,
+                    &message_serializer,
+                    Message::PeerManagement(Box::new(PeerManagementMessage::ListPeers(peers))),
+                    false,
+                )
+                .unwrap();
+        }
+        assert!(start.elapsed() < Duration::from_millis(200));
+        assert!(sent.0.lock().is_empty());
+
+        // the messages are delivered in order at the end of the stall
+        std::thread::sleep(Duration::from_millis(600));
+        let sent = sent.0.lock();
+        assert_eq!(
+            sent.iter()
+                .map(|(peer_count, _)| *peer_count)
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+        assert!(sent
+            .iter()
+            .all(|(_, sent_at)| sent_at.duration_since(start) >= Duration::from_millis(200)));
+    }
+}
@@ -1,13 +1,17 @@
 use crossbeam::channel::tick;
 use crossbeam::select;
-use massa_channel::{receiver::MassaReceiver, sender::MassaSender};
+use massa_channel::{heartbeat::Heartbeat, receiver::MassaReceiver, sender::MassaSender};
 use massa_consensus_exports::ConsensusController;
+use massa_logging::massa_journal;
 use massa_metrics::MassaMetrics;
-use massa_models::stats::NetworkStats;
+use massa_models::config::{HEADER_MEMORY_SIZE_ESTIMATE, ID_MEMORY_SIZE_ESTIMATE};
+use massa_models::node::NodeId;
+use massa_models::stats::{CacheMemoryStats, NetworkStats, PeerStats, ProtocolMemoryStats};
 use massa_pool_exports::PoolController;
 use massa_pos_exports::SelectorController;
 use massa_protocol_exports::{PeerCategoryInfo, PeerId, ProtocolConfig, ProtocolError};
 use massa_storage::Storage;
+use massa_time::MassaTime;
 use massa_versioning::versioning::MipStore;
 use parking_lot::RwLock;
 use peernet::peer::PeerConnectionType;
@@ -18,14 +22,19 @@ use std::{thread::JoinHandle, time::Duration};
 use tracing::{info, warn};
 
 use crate::{
-    handlers::peer_handler::models::{InitialPeers, PeerState, SharedPeerDB},
+    handlers::peer_handler::models::{InitialPeers, PeerManagementCmd, PeerState, SharedPeerDB},
     worker::ProtocolChannels,
 };
-use crate::{handlers::peer_handler::PeerManagementHandler, messages::MessagesHandler};
+use crate::{
+    handlers::peer_handler::PeerManagementHandler,
+    messages::{MessageTypeId, MessagesHandler},
+    peer_stats,
+};
 use crate::{
     handlers::{
         block_handler::{cache::BlockCache, BlockHandler},
         endorsement_handler::{cache::EndorsementCache, EndorsementHandler},
+        log_error,
         operation_handler::{cache::OperationCache, OperationHandler},
         peer_handler::models::PeerMessageTuple,
     },
@@ -42,6 +51,12 @@ pub enum ConnectivityCommand {
             HashMap<PeerId, (SocketAddr, PeerConnectionType)>,
         )>,
     },
+    GetMemoryStats {
+        responder: MassaSender<ProtocolMemoryStats>,
+    },
+    GetPeersDetail {
+        responder: MassaSender<Vec<PeerStats>>,
+    },
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -117,6 +132,9 @@ pub(crate) fn start_connectivity_thread(
                 config.max_node_known_blocks_size.try_into().unwrap(),
             )));
 
+            let peer_stats = messages_handler.peer_stats.clone();
+            let rate_limiter = messages_handler.rate_limiter.clone();
+
             // Start handlers
             let mut peer_management_handler = PeerManagementHandler::new(
                 initial_peers,
@@ -143,6 +161,7 @@ pub(crate) fn start_connectivity_thread(
                 sender_operations_propagation_ext.clone(),
                 protocol_channels.operation_handler_propagation.1.clone(),
                 peer_management_handler.sender.command_sender.clone(),
+                peer_stats.clone(),
                 massa_metrics.clone(),
             );
             let mut endorsement_handler = EndorsementHandler::new(
@@ -158,6 +177,7 @@ pub(crate) fn start_connectivity_thread(
                 sender_endorsements_propagation_ext.clone(),
                 protocol_channels.endorsement_handler_propagation.1.clone(),
                 peer_management_handler.sender.command_sender.clone(),
+                peer_stats.clone(),
                 massa_metrics.clone(),
             );
             let mut block_handler = BlockHandler::new(
@@ -174,19 +194,26 @@ pub(crate) fn start_connectivity_thread(
                 sender_endorsements_propagation_ext,
                 peer_management_handler.sender.command_sender.clone(),
                 config.clone(),
-                endorsement_cache,
-                operation_cache,
-                block_cache,
+                endorsement_cache.clone(),
+                operation_cache.clone(),
+                block_cache.clone(),
                 storage.clone_without_refs(),
                 mip_store,
+                peer_stats.clone(),
                 massa_metrics.clone(),
             );
 
             let tick_metrics = tick(massa_metrics.tick_delay);
             let tick_try_connect = tick(config.try_connection_timer.to_duration());
 
+            // the node is isolated when its connected peers drop below `isolation_threshold`
+            let mut connected_once = false;
+            let mut isolated = false;
+
+            let heartbeat = Heartbeat::new("protocol-connectivity");
             //Try to connect to peers
             loop {
+                heartbeat.beat();
                 select! {
                     recv(protocol_channels.connectivity_thread.1) -> msg => {
                         // update channel metrics
@@ -226,6 +253,58 @@ pub(crate) fn start_connectivity_thread(
                                 }).collect();
                                 responder.try_send((stats, peers)).unwrap_or_else(|_| warn!("Failed to send stats to responder"));
                             }
+                            Ok(ConnectivityCommand::GetMemoryStats { responder }) => {
+                                let stats = ProtocolMemoryStats {
+                                    known_blocks: CacheMemoryStats::new(
+                                        block_cache.read().checked_headers.len(),
+                                        config.max_known_blocks_size,
+                                        HEADER_MEMORY_SIZE_ESTIMATE,
+                                    ),
+                                    known_operations: CacheMemoryStats::new(
+                                        operation_cache.read().checked_operations.len(),
+                                        config.max_known_ops_size,
+                                        ID_MEMORY_SIZE_ESTIMATE,
+                                    ),
+                                    known_endorsements: CacheMemoryStats::new(
+                                        endorsement_cache.read().checked_endorsements.len(),
+                                        config.max_known_endorsements_size,
+                                        ID_MEMORY_SIZE_ESTIMATE,
+                                    ),
+                                };
+                                responder.try_send(stats).unwrap_or_else(|_| warn!("Failed to send memory stats to responder"));
+                            }
+                            Ok(ConnectivityCommand::GetPeersDetail { responder }) => {
+                                let active_connections = network_controller.get_active_connections();
+                                let bandwidth = active_connections.get_peers_connections_bandwidth();
+                                let counters = peer_stats.read();
+                                let details = active_connections.get_peers_connected().into_iter().map(|(peer_id, (address, connection_type, category))| {
+                                    let (bytes_sent, bytes_received) = bandwidth.get(&peer_id.to_string()).copied().unwrap_or_default();
+                                    let peer_counters = counters.get(&peer_id).cloned().unwrap_or_default();
+                                    let received = |message_type| peer_counters.received_of_type(message_type);
+                                    PeerStats {
+                                        node_id: NodeId::new(peer_id.get_public_key()),
+                                        address,
+                                        is_outgoing: connection_type == PeerConnectionType::OUT,
+                                        category,
+                                        bytes_received,
+                                        bytes_sent,
+                                        block_messages_received: received(MessageTypeId::Block),
+                                        endorsement_messages_received: received(MessageTypeId::Endorsement),
+                                        operation_messages_received: received(MessageTypeId::Operation),
+                                        peer_messages_received: received(MessageTypeId::PeerManagement),
+                                        invalid_messages_received: peer_counters.invalid_received(),
+                                        unknown_messages_received: peer_counters.unknown_received(),
+                                        messages_received_by_kind: peer_counters.received_by_kind(),
+                                        messages_sent: peer_counters.sent_total(),
+                                        messages_sent_by_kind: peer_counters.sent_by_kind(),
+                                        asks_served: peer_counters.asks_served(),
+                                        last_received: peer_counters.last_received(),
+                                        last_sent: peer_counters.last_sent(),
+                                        config_mismatch: peer_counters.config_mismatch(),
+                                    }
+                                }).collect();
+                                responder.try_send(details).unwrap_or_else(|_| warn!("Failed to send peers detail to responder"));
+                            }
                             Err(_) => {
                                 warn!("Channel to connectivity thread is closed. Stopping the protocol");
                                 break;
@@ -245,6 +324,24 @@ pub(crate) fn start_connectivity_thread(
                     recv(tick_try_connect) -> _ => {
                         let active_conn = network_controller.get_active_connections();
                         let peers_connected = active_conn.get_peers_connected();
+                        if peers_connected.len() >= config.isolation_threshold {
+                            connected_once = true;
+                            if isolated {
+                                isolated = false;
+                                info!("Not isolated anymore: connected to {} peers", peers_connected.len());
+                                massa_journal!("reconnected", { "connected_peers": peers_connected.len() });
+                            }
+                        } else if connected_once {
+                            if !isolated {
+                                isolated = true;
+                                warn!("Isolated: connected to {} peers, testing the initial and known peers again", peers_connected.len());
+                                massa_journal!("isolated", { "connected_peers": peers_connected.len() });
+                            }
+                            // test them again at each try until the node is reconnected
+                            if let Err(err) = peer_management_handler.sender.command_sender.try_send(PeerManagementCmd::Reconnect) {
+                                warn!("Failed to ask the peer handler to reconnect: {}", err);
+                            }
+                        }
                         let mut slots_per_category: Vec<(String, usize)> = peer_categories.iter().map(|(category, category_infos)| {
                             (category.clone(), category_infos.1.target_out_connections.saturating_sub(peers_connected.iter().filter(|(_, peer)| {
                                 if peer.1 == PeerConnectionType::OUT && let Some(peer_category) = &peer.2 {
@@ -257,13 +354,26 @@ pub(crate) fn start_connectivity_thread(
                         let mut slot_default_category = config.default_category_info.target_out_connections.saturating_sub(peers_connected.iter().filter(|(_, peer)| {
                             peer.1 == PeerConnectionType::OUT && peer.2.is_none()
                         }).count());
-                        let mut addresses_to_connect: Vec<SocketAddr> = Vec::new();
+                        let mut addresses_to_connect: Vec<(PeerId, SocketAddr)> = Vec::new();
+                        let now = MassaTime::now().expect("could not get the current time");
+                        {
+                            let mut peer_db_write = peer_db.write();
+                            peer_db_write.clear_dial_attempts(peers_connected.keys());
+                            peer_db_write.prune_dial_attempts(now);
+                        }
+                        let peer_ids_connected = peers_connected.keys().cloned().collect();
+                        peer_stats::retain_connected(&peer_stats, &peer_ids_connected);
+                        rate_limiter.update_connected(&peers_connected);
                         {
                             let peer_db_read = peer_db.read();
                             for (_, peer_id) in &peer_db_read.index_by_newest {
                                 if peers_connected.contains_key(peer_id) {
                                     continue;
                                 }
+                                // spread the attempts over the other peers while this one is cooling down
+                                if peer_db_read.is_dial_cooling_down(peer_id, config.try_connection_timer, now) {
+                                    continue;
+                                }
                                 if let Some(peer_info) = peer_db_read.peers.get(peer_id).and_then(|peer| {
                                     if peer.state == PeerState::Trusted {
                                         Some(peer.clone())
@@ -293,12 +403,12 @@ pub(crate) fn start_connectivity_thread(
                                     if let Some(category) = category_found {
                                         for (name, category_infos) in &mut slots_per_category {
                                             if name == category && category_infos > &mut 0 {
-                                                addresses_to_connect.push(*addr);
+                                                addresses_to_connect.push((peer_id.clone(), *addr));
                                                 *category_infos -= 1;
                                             }
                                         }
                                     } else if slot_default_category > 0 {
-                                        addresses_to_connect.push(*addr);
+                                        addresses_to_connect.push((peer_id.clone(), *addr));
                                         slot_default_category -= 1;
                                     }
 
@@ -310,11 +420,13 @@ pub(crate) fn start_connectivity_thread(
                                 }
                             }
                         }
-                        for addr in addresses_to_connect {
+                        for (peer_id, addr) in addresses_to_connect {
+                            peer_db.write().note_dial_attempt(&peer_id, now);
                             info!("Trying to connect to addr {}", addr);
                             // We only manage TCP for now
+                            // a failed dial is retried once its cooldown is over
                             if let Err(err) = network_controller.try_connect(addr, config.timeout_connection.to_duration()) {
-                                warn!("Failed to connect to peer {:?}: {:?}", addr, err);
+                                log_error(&err, &format!("connecting to peer {}", addr));
                             }
                         }
                     }
@@ -1,14 +1,25 @@
-use massa_protocol_exports::PeerId;
-use massa_signature::KeyPair;
+use std::sync::Arc;
+
+use massa_hash::Hash;
+use massa_protocol_exports::{HandshakeSigner, PeerId, ProtocolError};
+use massa_signature::{PublicKey, Signature};
 use peernet::context::Context as PeernetContext;
 
 #[derive(Clone)]
 pub struct Context {
-    pub our_keypair: KeyPair,
+    pub our_public_key: PublicKey,
+    /// signs with the secret key of `our_public_key`, either local or held by a remote signer
+    pub signer: Arc<dyn HandshakeSigner>,
+}
+
+impl Context {
+    pub fn sign(&self, hash: &Hash) -> Result<Signature, ProtocolError> {
+        self.signer.sign(&self.our_public_key, hash)
+    }
 }
 
 impl PeernetContext<PeerId> for Context {
     fn get_peer_id(&self) -> PeerId {
-        PeerId::from_public_key(self.our_keypair.get_public_key())
+        PeerId::from_public_key(self.our_public_key)
     }
 }
@@ -5,9 +5,11 @@ use massa_models::{
     block_header::SecuredHeader,
     block_id::BlockId,
     prehash::{PreHashMap, PreHashSet},
-    stats::NetworkStats,
+    stats::{NetworkStats, PeerStats, ProtocolMemoryStats},
+};
+use massa_protocol_exports::{
+    BootstrapPeers, KnownPeer, PeerId, ProtocolController, ProtocolError, TransportType,
 };
-use massa_protocol_exports::{BootstrapPeers, PeerId, ProtocolController, ProtocolError};
 use massa_storage::Storage;
 use peernet::peer::PeerConnectionType;
 
@@ -156,11 +158,39 @@ impl ProtocolController for ProtocolControllerImpl {
             .map_err(|_| ProtocolError::ChannelError("get_stats command receive error".into()))
     }
 
+    fn get_memory_stats(&self) -> Result<ProtocolMemoryStats, ProtocolError> {
+        let (sender, receiver) = MassaChannel::new("get_memory_stats".to_string(), Some(1));
+        self.sender_connectivity_thread
+            .as_ref()
+            .unwrap()
+            .try_send(ConnectivityCommand::GetMemoryStats { responder: sender })
+            .map_err(|_| {
+                ProtocolError::ChannelError("get_memory_stats command send error".into())
+            })?;
+        receiver.recv_timeout(Duration::from_secs(10)).map_err(|_| {
+            ProtocolError::ChannelError("get_memory_stats command receive error".into())
+        })
+    }
+
+    fn get_peers_detail(&self) -> Result<Vec<PeerStats>, ProtocolError> {
+        let (sender, receiver) = MassaChannel::new("get_peers_detail".to_string(), Some(1));
+        self.sender_connectivity_thread
+            .as_ref()
+            .unwrap()
+            .try_send(ConnectivityCommand::GetPeersDetail { responder: sender })
+            .map_err(|_| {
+                ProtocolError::ChannelError("get_peers_detail command send error".into())
+            })?;
+        receiver.recv_timeout(Duration::from_secs(10)).map_err(|_| {
+            ProtocolError::ChannelError("get_peers_detail command receive error".into())
+        })
+    }
+
     fn ban_peers(&self, peer_ids: Vec<PeerId>) -> Result<(), ProtocolError> {
         self.sender_peer_management_thread
             .as_ref()
             .unwrap()
-            .try_send(PeerManagementCmd::Ban(peer_ids))
+            .try_send(PeerManagementCmd::ForceBan(peer_ids))
             .map_err(|_| ProtocolError::ChannelError("ban_peers command send error".into()))
     }
 
@@ -172,6 +202,41 @@ impl ProtocolController for ProtocolControllerImpl {
             .map_err(|_| ProtocolError::ChannelError("unban_peers command send error".into()))
     }
 
+    fn reload_initial_peers(&self) -> Result<(), ProtocolError> {
+        self.sender_peer_management_thread
+            .as_ref()
+            .unwrap()
+            .try_send(PeerManagementCmd::ReloadInitialPeers)
+            .map_err(|_| {
+                ProtocolError::ChannelError("reload_initial_peers command send error".into())
+            })
+    }
+
+    fn get_known_peers(&self) -> Result<HashMap<PeerId, KnownPeer>, ProtocolError> {
+        let (sender, receiver) = MassaChannel::new("get_known_peers".to_string(), Some(1));
+        self.sender_peer_management_thread
+            .as_ref()
+            .unwrap()
+            .try_send(PeerManagementCmd::GetKnownPeers { responder: sender })
+            .map_err(|_| {
+                ProtocolError::ChannelError("get_known_peers command send error".into())
+            })?;
+        receiver.recv_timeout(Duration::from_secs(10)).map_err(|_| {
+            ProtocolError::ChannelError("get_known_peers command receive error".into())
+        })
+    }
+
+    fn add_known_peers(
+        &self,
+        peers: HashMap<PeerId, HashMap<SocketAddr, TransportType>>,
+    ) -> Result<(), ProtocolError> {
+        self.sender_peer_management_thread
+            .as_ref()
+            .unwrap()
+            .try_send(PeerManagementCmd::TestPeers(peers))
+            .map_err(|_| ProtocolError::ChannelError("add_known_peers command send error".into()))
+    }
+
     fn get_bootstrap_peers(&self) -> Result<BootstrapPeers, ProtocolError> {
         let (sender, receiver) = MassaChannel::new("get_bootstrap_peers".to_string(), Some(1));
         self.sender_peer_management_thread
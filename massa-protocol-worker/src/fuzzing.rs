@@ -0,0 +1,238 @@
+//! Entry points of the fuzz targets of `fuzz/`: decoding of the raw messages received from
+//! the network, and generation of a corpus of valid messages to start fuzzing from.
+
+use std::collections::HashMap;
+
+use massa_hash::Hash;
+use massa_models::{
+    address::Address,
+    amount::Amount,
+    block_header::{BlockHeader, BlockHeaderSerializer},
+    block_id::BlockId,
+    endorsement::{Endorsement, EndorsementSerializer},
+    operation::{Operation, OperationPrefixIds, OperationSerializer, OperationType},
+    secure_share::SecureShareContent,
+    slot::Slot,
+};
+use massa_protocol_exports::{PeerId, ProtocolConfig};
+use massa_serialization::{DeserializeError, Deserializer, U64VarIntDeserializer};
+use massa_signature::KeyPair;
+use peernet::{messages::MessagesSerializer as _, transports::TransportType};
+use std::ops::Bound::Included;
+
+use crate::{
+    handlers::{
+        block_handler::{
+            AskForBlocksInfo, BlockInfoReply, BlockMessage, BlockMessageDeserializer,
+            BlockMessageDeserializerArgs, BlockMessageSerializer,
+        },
+        endorsement_handler::{
+            EndorsementMessage, EndorsementMessageDeserializer, EndorsementMessageDeserializerArgs,
+            EndorsementMessageSerializer,
+        },
+        operation_handler::{
+            OperationMessage, OperationMessageDeserializer, OperationMessageDeserializerArgs,
+            OperationMessageSerializer,
+        },
+        peer_handler::{
+            PeerManagementMessage, PeerManagementMessageDeserializer,
+            PeerManagementMessageDeserializerArgs, PeerManagementMessageSerializer,
+        },
+    },
+    messages::{
+        Message, MessageEnvelopeDeserializer, MessageTypeId, MessagesSerializer,
+        MESSAGE_ENVELOPE_ID,
+    },
+};
+
+/// Decode a raw message as the protocol handlers do, with the limits of `config`.
+/// Returns whether the message is valid.
+pub fn deserialize_message(config: &ProtocolConfig, data: &[u8]) -> bool {
+    let Ok((data, raw_id)) = U64VarIntDeserializer::new(Included(0), Included(u64::MAX))
+        .deserialize::<DeserializeError>(data)
+    else {
+        return false;
+    };
+    let enveloped = raw_id == MESSAGE_ENVELOPE_ID;
+    let envelope;
+    let (raw_id, data) = if enveloped {
+        match MessageEnvelopeDeserializer::new(data.len() as u64)
+            .deserialize::<DeserializeError>(data)
+        {
+            Ok((rest, parsed)) if rest.is_empty() => {
+                envelope = parsed;
+                (envelope.message_type, envelope.payload.as_slice())
+            }
+            _ => return false,
+        }
+    } else {
+        (raw_id, data)
+    };
+    let Ok(id) = MessageTypeId::try_from(raw_id) else {
+        // the handlers skip the messages of unknown type sent in an envelope
+        return enveloped;
+    };
+    match id {
+        MessageTypeId::Block => BlockMessageDeserializer::new(BlockMessageDeserializerArgs {
+            thread_count: config.thread_count,
+            endorsement_count: config.endorsement_count,
+            block_infos_length_max: config.max_size_block_infos,
+            max_operations_per_block: config.max_operations_per_block,
+            max_datastore_value_length: config.max_size_value_datastore,
+            max_function_name_length: config.max_size_function_name,
+            max_parameters_size: config.max_size_call_sc_parameter,
+            max_op_datastore_entry_count: config.max_op_datastore_entry_count,
+            max_op_datastore_key_length: config.max_op_datastore_key_length,
+            max_op_datastore_value_length: config.max_op_datastore_value_length,
+            max_denunciations_in_block_header: config.max_denunciations_in_block_header,
+            last_start_period: Some(config.last_start_period),
+        })
+        .deserialize::<DeserializeError>(data)
+        .is_ok(),
+        MessageTypeId::Endorsement => {
+            EndorsementMessageDeserializer::new(EndorsementMessageDeserializerArgs {
+                thread_count: config.thread_count,
+                max_length_endorsements: config.max_endorsements_per_message,
+                endorsement_count: config.endorsement_count,
+            })
+            .deserialize::<DeserializeError>(data)
+            .is_ok()
+        }
+        MessageTypeId::Operation => {
+            OperationMessageDeserializer::new(OperationMessageDeserializerArgs {
+                max_operations_prefix_ids: config.max_operations_per_message as u32,
+                max_operations: config.max_operations_per_message as u32,
+                max_datastore_value_length: config.max_op_datastore_value_length,
+                max_function_name_length: config.max_size_function_name,
+                max_parameters_size: config.max_size_call_sc_parameter,
+                max_op_datastore_entry_count: config.max_op_datastore_entry_count,
+                max_op_datastore_key_length: config.max_op_datastore_key_length,
+                max_op_datastore_value_length: config.max_op_datastore_value_length,
+            })
+            .deserialize::<DeserializeError>(data)
+            .is_ok()
+        }
+        MessageTypeId::PeerManagement => {
+            PeerManagementMessageDeserializer::new(PeerManagementMessageDeserializerArgs {
+                max_peers_per_announcement: config.max_size_peers_announcement,
+                max_listeners_per_peer: config.max_size_listeners_per_peer,
+            })
+            .deserialize::<DeserializeError>(data)
+            .is_ok()
+        }
+    }
+}
+
+/// Valid raw messages of every type, valid for the limits of `config`
+pub fn message_corpus(config: &ProtocolConfig) -> Vec<Vec<u8>> {
+    let keypair = KeyPair::generate(0).unwrap();
+    let peer_id = PeerId::from_public_key(keypair.get_public_key());
+    let slot = Slot::new(config.last_start_period + 1, 0);
+    let parents: Vec<BlockId> = (0..config.thread_count)
+        .map(|i| BlockId(Hash::compute_from(&[i])))
+        .collect();
+    let endorsement = Endorsement::new_verifiable(
+        Endorsement {
+            slot,
+            index: 0,
+            endorsed_block: parents[slot.thread as usize],
+        },
+        EndorsementSerializer::new(),
+        &keypair,
+    )
+    .unwrap();
+    let header = BlockHeader::new_verifiable(
+        BlockHeader {
+            current_version: 0,
+            announced_version: None,
+            slot,
+            parents,
+            operation_merkle_root: Hash::compute_from(&[]),
+            endorsements: vec![endorsement.clone()],
+            denunciations: Vec::new(),
+        },
+        BlockHeaderSerializer::new(),
+        &keypair,
+    )
+    .unwrap();
+    let operation = Operation::new_verifiable(
+        Operation {
+            fee: Amount::from_raw(1),
+            expire_period: slot.period + 10,
+            op: OperationType::Transaction {
+                recipient_address: Address::from_public_key(&keypair.get_public_key()),
+                amount: Amount::from_raw(1),
+            },
+        },
+        OperationSerializer::new(),
+        &keypair,
+    )
+    .unwrap();
+    let prefix_ids: OperationPrefixIds = [operation.id.prefix()].into_iter().collect();
+    let mut listeners = HashMap::new();
+    listeners.insert("127.0.0.1:31244".parse().unwrap(), TransportType::Tcp);
+
+    let messages: Vec<Message> = vec![
+        BlockMessage::BlockHeader(header.clone()).into(),
+        BlockMessage::AskForBlocks(vec![
+            (header.id, AskForBlocksInfo::Header),
+            (header.id, AskForBlocksInfo::Info),
+            (header.id, AskForBlocksInfo::Operations(vec![operation.id])),
+        ])
+        .into(),
+        BlockMessage::ReplyForBlocks(vec![
+            (header.id, BlockInfoReply::Header(header.clone())),
+            (header.id, BlockInfoReply::Info(vec![operation.id])),
+            (
+                header.id,
+                BlockInfoReply::Operations(vec![operation.clone()]),
+            ),
+            (header.id, BlockInfoReply::NotFound),
+        ])
+        .into(),
+        EndorsementMessage::Endorsements(vec![endorsement]).into(),
+        OperationMessage::OperationsAnnouncement(prefix_ids.clone()).into(),
+        OperationMessage::AskForOperations(prefix_ids).into(),
+        OperationMessage::Operations(vec![operation]).into(),
+        PeerManagementMessage::NewPeerConnected((peer_id.clone(), listeners.clone())).into(),
+        PeerManagementMessage::ListPeers(vec![(peer_id, listeners)]).into(),
+    ];
+
+    let serializer = MessagesSerializer::new()
+        .with_block_message_serializer(BlockMessageSerializer::new())
+        .with_endorsement_message_serializer(EndorsementMessageSerializer::new())
+        .with_operation_message_serializer(OperationMessageSerializer::new())
+        .with_peer_management_message_serializer(PeerManagementMessageSerializer::new());
+    // each message is also sent in an envelope, with an extension field
+    messages
+        .iter()
+        .flat_map(|message| {
+            let mut buffer = Vec::new();
+            serializer.serialize(message, &mut buffer).unwrap();
+            let mut enveloped = Vec::new();
+            serializer
+                .serialize_in_envelope(message, vec![(1, vec![0])], &mut enveloped)
+                .unwrap();
+            [buffer, enveloped]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_corpus_is_valid() {
+        let config = ProtocolConfig::default();
+        let corpus = message_corpus(&config);
+        assert_eq!(corpus.len(), 18);
+        for message in &corpus {
+            assert!(deserialize_message(&config, message));
+            // truncated messages must be rejected without panicking
+            for len in 0..message.len() {
+                deserialize_message(&config, &message[..len]);
+            }
+        }
+    }
+}
@@ -0,0 +1,112 @@
+//! Statistics about the answers of the peers to our block requests.
+//!
+//! They are used to ask the blocks of the wishlist to the peers that are the most likely to answer quickly:
+//! the ones that answer fast, that rarely let our requests time out and that are not already busy with our other requests.
+
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use massa_protocol_exports::PeerId;
+
+/// Weight of the last measured round-trip time in the moving average
+const RTT_SMOOTHING: f64 = 0.2;
+
+#[derive(Debug, Clone, Default)]
+struct PeerAskStats {
+    /// moving average of the time the peer takes to answer, in milliseconds. None if it never answered
+    rtt_ms: Option<f64>,
+    /// number of requests answered
+    answered: u64,
+    /// number of requests that timed out
+    timed_out: u64,
+}
+
+/// Answer statistics of the connected peers
+#[derive(Debug, Default)]
+pub(crate) struct AskStats {
+    peers: HashMap<PeerId, PeerAskStats>,
+}
+
+impl AskStats {
+    /// Note that `peer_id` answered one of our requests after `rtt`
+    pub(crate) fn note_answer(&mut self, peer_id: &PeerId, rtt: Duration) {
+        let stats = self.peers.entry(peer_id.clone()).or_default();
+        let rtt_ms = rtt.as_secs_f64() * 1000.0;
+        stats.rtt_ms = Some(match stats.rtt_ms {
+            Some(average) => average + RTT_SMOOTHING * (rtt_ms - average),
+            None => rtt_ms,
+        });
+        stats.answered = stats.answered.saturating_add(1);
+    }
+
+    /// Note that one of our requests to `peer_id` timed out
+    pub(crate) fn note_timeout(&mut self, peer_id: &PeerId) {
+        let stats = self.peers.entry(peer_id.clone()).or_default();
+        stats.timed_out = stats.timed_out.saturating_add(1);
+    }
+
+    /// Forget the peers we are not connected to anymore
+    pub(crate) fn retain_connected(&mut self, peers_connected: &HashSet<PeerId>) {
+        self.peers
+            .retain(|peer_id, _| peers_connected.contains(peer_id));
+    }
+
+    /// Expected time for `peer_id` to answer a new request, in milliseconds,
+    /// given the `in_flight` requests we already sent to it.
+    /// The peers that never answered are expected to answer in `default_rtt`.
+    pub(crate) fn expected_answer_ms(
+        &self,
+        peer_id: &PeerId,
+        in_flight: usize,
+        default_rtt: Duration,
+    ) -> u64 {
+        let default_rtt_ms = default_rtt.as_secs_f64() * 1000.0;
+        let (rtt_ms, answer_rate) = match self.peers.get(peer_id) {
+            Some(stats) => (
+                stats.rtt_ms.unwrap_or(default_rtt_ms),
+                (stats.answered + 1) as f64 / (stats.answered + stats.timed_out + 1) as f64,
+            ),
+            None => (default_rtt_ms, 1.0),
+        };
+        (rtt_ms * (in_flight + 1) as f64 / answer_rate) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_signature::KeyPair;
+
+    fn peer() -> PeerId {
+        PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key())
+    }
+
+    #[test]
+    fn test_expected_answer_time() {
+        let mut stats = AskStats::default();
+        let default_rtt = Duration::from_millis(500);
+        let (fast, slow, unreliable, unknown) = (peer(), peer(), peer(), peer());
+        stats.note_answer(&fast, Duration::from_millis(50));
+        stats.note_answer(&slow, Duration::from_millis(300));
+        stats.note_answer(&unreliable, Duration::from_millis(50));
+        stats.note_timeout(&unreliable);
+        stats.note_timeout(&unreliable);
+
+        assert_eq!(stats.expected_answer_ms(&fast, 0, default_rtt), 50);
+        assert_eq!(stats.expected_answer_ms(&unreliable, 0, default_rtt), 100);
+        assert_eq!(stats.expected_answer_ms(&unknown, 0, default_rtt), 500);
+        // a busy fast peer is expected to answer later than an idle slow one
+        assert!(
+            stats.expected_answer_ms(&fast, 9, default_rtt)
+                > stats.expected_answer_ms(&slow, 0, default_rtt)
+        );
+
+        // the average follows the new measures
+        stats.note_answer(&fast, Duration::from_millis(100));
+        assert_eq!(stats.expected_answer_ms(&fast, 0, default_rtt), 60);
+
+        stats.retain_connected(&HashSet::from([slow.clone()]));
+        assert_eq!(stats.expected_answer_ms(&fast, 0, default_rtt), 500);
+        assert_eq!(stats.expected_answer_ms(&slow, 0, default_rtt), 300);
+    }
+}
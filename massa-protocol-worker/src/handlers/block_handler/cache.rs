@@ -4,7 +4,7 @@ use massa_models::{block_header::SecuredHeader, block_id::BlockId};
 use massa_protocol_exports::PeerId;
 use parking_lot::RwLock;
 use schnellru::{ByLength, LruMap};
-use tracing::log::warn;
+use tracing::warn;
 
 pub struct BlockCache {
     pub checked_headers: LruMap<BlockId, SecuredHeader>,
@@ -9,6 +9,7 @@ use massa_protocol_exports::ProtocolConfig;
 use massa_storage::Storage;
 use massa_versioning::versioning::MipStore;
 
+use crate::peer_stats::SharedPeerStats;
 use crate::wrap_network::ActiveConnectionsTrait;
 
 use self::{
@@ -17,20 +18,22 @@ use self::{
     retrieval::start_retrieval_thread,
 };
 
+mod ask_stats;
 pub mod cache;
 pub mod commands_propagation;
 pub mod commands_retrieval;
 mod messages;
+mod pipeline;
 mod propagation;
 mod retrieval;
 
-pub(crate) use messages::{BlockMessage, BlockMessageSerializer};
-
-#[cfg(feature = "testing")]
-pub use messages::{
-    AskForBlocksInfo, BlockInfoReply, BlockMessageDeserializer, BlockMessageDeserializerArgs,
+pub(crate) use messages::{
+    BlockInfoReply, BlockMessage, BlockMessageSerializer, MessageTypeId as BlockMessageTypeId,
 };
 
+#[cfg(any(test, feature = "testing", feature = "fuzzing"))]
+pub use messages::{AskForBlocksInfo, BlockMessageDeserializer, BlockMessageDeserializerArgs};
+
 use super::{
     endorsement_handler::{
         cache::SharedEndorsementCache, commands_propagation::EndorsementHandlerPropagationCommand,
@@ -68,6 +71,7 @@ impl BlockHandler {
         cache: SharedBlockCache,
         storage: Storage,
         mip_store: MipStore,
+        peer_stats: SharedPeerStats,
         massa_metrics: MassaMetrics,
     ) -> Self {
         let block_retrieval_thread = start_retrieval_thread(
@@ -87,6 +91,7 @@ impl BlockHandler {
             cache.clone(),
             storage.clone_without_refs(),
             mip_store,
+            peer_stats,
             massa_metrics,
         );
         let block_propagation_thread = start_propagation_thread(
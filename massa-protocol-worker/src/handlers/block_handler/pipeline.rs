@@ -0,0 +1,335 @@
+//! Stages of the processing of incoming block messages before the retrieval thread.
+//!
+//! Incoming blocks go through the following stages, each one running on its own thread and
+//! connected to the next one by a bounded channel, so that they work on different blocks at
+//! the same time and a slow stage slows down the previous ones instead of buffering:
+//! - decoding of the raw messages, including the hash of the headers
+//! - header checks that need no signature: genesis, network version and endorsements of the header
+//! - verification of the signatures of the header and of its endorsements, in one batch
+//! - update of the caches and of the wishlist, in the retrieval thread
+//! - graph insertion, in the consensus worker
+//!
+//! All the messages go through all the stages, in order. Only the headers of `BlockHeader`
+//! messages are checked by the stages, the other messages are forwarded untouched.
+
+use std::{collections::HashSet, thread::JoinHandle};
+
+use crossbeam::{channel::Receiver, select};
+use massa_channel::{receiver::MassaReceiver, sender::MassaSender, MassaChannel};
+use massa_models::{block_header::SecuredHeader, timeslots::get_block_slot_timestamp};
+use massa_protocol_exports::{PeerId, ProtocolConfig, ProtocolError};
+use massa_serialization::{DeserializeError, Deserializer};
+use massa_versioning::versioning::MipStore;
+use tracing::{info, warn};
+
+use crate::{
+    handlers::{
+        endorsement_handler::cache::SharedEndorsementCache, peer_handler::models::PeerMessageTuple,
+    },
+    peer_stats::{note_invalid_message, SharedPeerStats},
+    sig_verifier::verify_sigs_batch,
+};
+
+use super::{
+    cache::SharedBlockCache,
+    messages::{BlockMessage, BlockMessageDeserializer, BlockMessageDeserializerArgs},
+};
+
+/// What the stages found about the header of a `BlockHeader` message
+#[derive(Debug)]
+pub(crate) enum HeaderCheck {
+    /// not a `BlockHeader` message
+    NotChecked,
+    /// the header passed the checks that need no signature, its signatures are not verified yet
+    Checked,
+    /// the header and the signatures of the header and its endorsements are valid
+    Verified,
+    /// the header was already checked before: `checked_headers` holds it
+    AlreadyChecked,
+    /// the header is critically incorrect
+    Invalid,
+    /// the header could not be accepted, the error tells whether the peer is at fault
+    Error(ProtocolError),
+}
+
+/// A block message, the peer that sent it and the result of the checks of its header
+pub(crate) type CheckedBlockMessage = (PeerId, BlockMessage, HeaderCheck);
+
+struct DecodingThread {
+    receiver_network: MassaReceiver<PeerMessageTuple>,
+    sender_decoded: MassaSender<CheckedBlockMessage>,
+    /// disconnected when the retrieval thread stops
+    receiver_stop: Receiver<()>,
+    deserializer: BlockMessageDeserializer,
+    peer_stats: SharedPeerStats,
+}
+
+impl DecodingThread {
+    fn run(&mut self) {
+        loop {
+            select! {
+                recv(self.receiver_network) -> msg => {
+                    self.receiver_network.update_metrics();
+                    let Ok((peer_id, message)) = msg else {
+                        info!("Stop block decoding thread");
+                        return;
+                    };
+                    let message = match self.deserializer.deserialize::<DeserializeError>(&message) {
+                        Ok((rest, _)) if !rest.is_empty() => {
+                            warn!("Message not fully consumed from {}", peer_id);
+                            note_invalid_message(&self.peer_stats, &peer_id);
+                            continue;
+                        }
+                        Ok((_, message)) => message,
+                        Err(err) => {
+                            warn!("Error in deserializing block message: {:?}", err);
+                            note_invalid_message(&self.peer_stats, &peer_id);
+                            continue;
+                        }
+                    };
+                    // blocks until the retrieval thread has room for the message
+                    if self.sender_decoded.send((peer_id, message, HeaderCheck::NotChecked)).is_err() {
+                        info!("Stop block decoding thread: retrieval thread stopped");
+                        return;
+                    }
+                },
+                recv(self.receiver_stop) -> _ => {
+                    info!("Stop block decoding thread: retrieval thread stopped");
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Start the decoding stage, reading raw messages from `receiver_network` and sending
+/// the decoded ones to `sender_decoded`. It stops when `receiver_stop` is disconnected.
+fn start_decoding_thread(
+    receiver_network: MassaReceiver<PeerMessageTuple>,
+    sender_decoded: MassaSender<CheckedBlockMessage>,
+    receiver_stop: Receiver<()>,
+    config: &ProtocolConfig,
+    peer_stats: SharedPeerStats,
+) -> JoinHandle<()> {
+    let deserializer = BlockMessageDeserializer::new(BlockMessageDeserializerArgs {
+        thread_count: config.thread_count,
+        endorsement_count: config.endorsement_count,
+        block_infos_length_max: config.max_size_block_infos,
+        max_operations_per_block: config.max_operations_per_block,
+        max_datastore_value_length: config.max_size_value_datastore,
+        max_function_name_length: config.max_size_function_name,
+        max_parameters_size: config.max_size_call_sc_parameter,
+        max_op_datastore_entry_count: config.max_op_datastore_entry_count,
+        max_op_datastore_key_length: config.max_op_datastore_key_length,
+        max_op_datastore_value_length: config.max_op_datastore_value_length,
+        max_denunciations_in_block_header: config.max_denunciations_in_block_header,
+        last_start_period: Some(config.last_start_period),
+    });
+    std::thread::Builder::new()
+        .name("protocol-block-handler-decoding".to_string())
+        .spawn(move || {
+            let mut decoding_thread = DecodingThread {
+                receiver_network,
+                sender_decoded,
+                receiver_stop,
+                deserializer,
+                peer_stats,
+            };
+            decoding_thread.run();
+        })
+        .expect("OS failed to start block decoding thread")
+}
+
+/// Checks of a header that need no signature verification, see `HeaderCheck`
+pub(crate) fn check_header(
+    header: &SecuredHeader,
+    config: &ProtocolConfig,
+    mip_store: &MipStore,
+    cache: &SharedBlockCache,
+) -> HeaderCheck {
+    // refuse genesis blocks
+    if header.content.slot.period == 0 || header.content.parents.is_empty() {
+        return HeaderCheck::Invalid;
+    }
+    if let Err(err) = check_network_version_compatibility(header, config, mip_store) {
+        return HeaderCheck::Error(err);
+    }
+    if cache.read().checked_headers.peek(&header.id).is_some() {
+        return HeaderCheck::AlreadyChecked;
+    }
+    // check endorsement in header integrity
+    let mut used_endorsement_indices: HashSet<u32> =
+        HashSet::with_capacity(header.content.endorsements.len());
+    for endorsement in header.content.endorsements.iter() {
+        // check index reuse
+        if !used_endorsement_indices.insert(endorsement.content.index) {
+            return HeaderCheck::Invalid;
+        }
+        // check slot
+        if endorsement.content.slot != header.content.slot {
+            return HeaderCheck::Invalid;
+        }
+        // check endorsed block
+        if endorsement.content.endorsed_block
+            != header.content.parents[header.content.slot.thread as usize]
+        {
+            return HeaderCheck::Invalid;
+        }
+    }
+    HeaderCheck::Checked
+}
+
+/// Check if the incoming header network version is compatible with the current node
+fn check_network_version_compatibility(
+    header: &SecuredHeader,
+    config: &ProtocolConfig,
+    mip_store: &MipStore,
+) -> Result<(), ProtocolError> {
+    let slot = header.content.slot;
+    let ts = get_block_slot_timestamp(
+        config.thread_count,
+        config.t0,
+        config.genesis_timestamp,
+        slot,
+    )?;
+    let current_version = mip_store.get_network_version_active_at(ts);
+    if header.content.current_version != current_version {
+        // Received a current version different from current version (given by mip store)
+        Err(ProtocolError::IncompatibleNetworkVersion {
+            local: current_version,
+            received: header.content.current_version,
+        })
+    } else {
+        if let Some(announced_version) = header.content.announced_version {
+            if announced_version <= current_version {
+                // Received an announced network version that is already known
+                return Err(ProtocolError::OutdatedAnnouncedNetworkVersion {
+                    local: current_version,
+                    announced_received: announced_version,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Verify in one batch the signature of a header and the ones of its endorsements that are not
+/// in the `checked_endorsements` cache. Returns `HeaderCheck::Verified` or `HeaderCheck::Invalid`.
+pub(crate) fn verify_header_signatures(
+    header: &SecuredHeader,
+    endorsement_cache: &SharedEndorsementCache,
+) -> HeaderCheck {
+    let mut signatures = vec![(
+        header.compute_signed_hash(),
+        header.signature,
+        header.content_creator_pub_key,
+    )];
+    {
+        let cache_read = endorsement_cache.read();
+        signatures.extend(
+            header
+                .content
+                .endorsements
+                .iter()
+                .filter(|endorsement| {
+                    cache_read
+                        .checked_endorsements
+                        .peek(&endorsement.id)
+                        .is_none()
+                })
+                .map(|endorsement| {
+                    (
+                        endorsement.compute_signed_hash(),
+                        endorsement.signature,
+                        endorsement.content_creator_pub_key,
+                    )
+                }),
+        );
+    }
+    match verify_sigs_batch(&signatures) {
+        Ok(()) => HeaderCheck::Verified,
+        Err(_) => HeaderCheck::Invalid,
+    }
+}
+
+/// Run `check` on the header of the `BlockHeader` messages received from `receiver`
+/// and send all the messages to `sender`, until one of them is disconnected
+fn run_header_stage(
+    name: &str,
+    receiver: MassaReceiver<CheckedBlockMessage>,
+    sender: MassaSender<CheckedBlockMessage>,
+    check: impl Fn(&SecuredHeader, HeaderCheck) -> HeaderCheck,
+) {
+    while let Ok((peer_id, message, header_check)) = receiver.recv() {
+        receiver.update_metrics();
+        let header_check = match &message {
+            BlockMessage::BlockHeader(header) => check(header, header_check),
+            _ => header_check,
+        };
+        // blocks until the next stage has room for the message
+        if sender.send((peer_id, message, header_check)).is_err() {
+            break;
+        }
+    }
+    info!("Stop block {} thread", name);
+}
+
+/// Start the stages before the retrieval thread, see the module documentation.
+/// Returns the receiver of the checked messages and the handles of the stage threads.
+/// The stages stop when `receiver_stop` or the returned receiver is disconnected.
+pub(crate) fn start_pipeline(
+    receiver_network: MassaReceiver<PeerMessageTuple>,
+    receiver_stop: Receiver<()>,
+    config: &ProtocolConfig,
+    mip_store: MipStore,
+    cache: SharedBlockCache,
+    endorsement_cache: SharedEndorsementCache,
+    peer_stats: SharedPeerStats,
+) -> (MassaReceiver<CheckedBlockMessage>, Vec<JoinHandle<()>>) {
+    let capacity = Some(config.max_size_channel_network_to_block_handler);
+    let (sender_decoded, receiver_decoded) =
+        MassaChannel::new("block_handler_decoded_messages".to_string(), capacity);
+    let (sender_checked, receiver_checked) =
+        MassaChannel::new("block_handler_checked_headers".to_string(), capacity);
+    let (sender_verified, receiver_verified) =
+        MassaChannel::new("block_handler_verified_headers".to_string(), capacity);
+
+    let decoding = start_decoding_thread(
+        receiver_network,
+        sender_decoded,
+        receiver_stop,
+        config,
+        peer_stats,
+    );
+    let header_config = config.clone();
+    let header_checks = std::thread::Builder::new()
+        .name("protocol-block-handler-header-checks".to_string())
+        .spawn(move || {
+            run_header_stage(
+                "header checks",
+                receiver_decoded,
+                sender_checked,
+                |header, _| check_header(header, &header_config, &mip_store, &cache),
+            )
+        })
+        .expect("OS failed to start block header checks thread");
+    let signature_checks = std::thread::Builder::new()
+        .name("protocol-block-handler-signature-checks".to_string())
+        .spawn(move || {
+            run_header_stage(
+                "signature checks",
+                receiver_checked,
+                sender_verified,
+                |header, header_check| match header_check {
+                    HeaderCheck::Checked => verify_header_signatures(header, &endorsement_cache),
+                    other => other,
+                },
+            )
+        })
+        .expect("OS failed to start block signature checks thread");
+    (
+        receiver_verified,
+        vec![decoding, header_checks, signature_checks],
+    )
+}
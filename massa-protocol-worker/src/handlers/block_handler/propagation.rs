@@ -1,6 +1,11 @@
 use std::{collections::VecDeque, thread::JoinHandle};
 
-use massa_channel::{receiver::MassaReceiver, sender::MassaSender};
+use crossbeam::channel::RecvTimeoutError;
+use massa_channel::{
+    heartbeat::{Heartbeat, MAX_BEAT_INTERVAL},
+    receiver::MassaReceiver,
+    sender::MassaSender,
+};
 use massa_logging::massa_trace;
 use massa_models::{block_id::BlockId, prehash::PreHashSet};
 use massa_protocol_exports::PeerId;
@@ -32,8 +37,10 @@ pub struct PropagationThread {
 
 impl PropagationThread {
     fn run(&mut self) {
+        let heartbeat = Heartbeat::new("protocol-block-handler-propagation");
         loop {
-            match self.receiver.recv() {
+            heartbeat.beat();
+            match self.receiver.recv_timeout(MAX_BEAT_INTERVAL) {
                 Ok(command) => {
                     match command {
                         BlockHandlerPropagationCommand::IntegratedBlock { block_id, storage } => {
@@ -125,7 +132,8 @@ impl PropagationThread {
                         }
                     }
                 }
-                Err(_) => {
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => {
                     info!("Stop block propagation thread");
                     return;
                 }
@@ -1,7 +1,7 @@
 use std::{
-    collections::{hash_map::Entry, HashMap, HashSet},
+    collections::{hash_map::Entry, HashMap},
     thread::JoinHandle,
-    time::Instant,
+    time::{Duration, Instant},
 };
 
 use crate::{
@@ -10,12 +10,14 @@ use crate::{
             cache::SharedEndorsementCache,
             commands_propagation::EndorsementHandlerPropagationCommand,
         },
+        is_peer_fault,
         operation_handler::{
             cache::SharedOperationCache, commands_propagation::OperationHandlerPropagationCommand,
         },
         peer_handler::models::{PeerManagementCmd, PeerMessageTuple},
     },
     messages::MessagesSerializer,
+    peer_stats::SharedPeerStats,
     sig_verifier::verify_sigs_batch,
     wrap_network::ActiveConnectionsTrait,
 };
@@ -23,13 +25,13 @@ use crossbeam::{
     channel::{at, tick},
     select,
 };
-use massa_channel::{receiver::MassaReceiver, sender::MassaSender};
+use massa_channel::{heartbeat::Heartbeat, receiver::MassaReceiver, sender::MassaSender};
 use massa_consensus_exports::ConsensusController;
 use massa_hash::{Hash, HASH_SIZE_BYTES};
 use massa_logging::massa_trace;
 use massa_metrics::MassaMetrics;
 use massa_models::{
-    block::{Block, BlockSerializer},
+    block::{Block, BlockSerializer, InvalidBlockReason},
     block_header::SecuredHeader,
     block_id::BlockId,
     endorsement::SecureShareEndorsement,
@@ -43,20 +45,21 @@ use massa_pool_exports::PoolController;
 use massa_pos_exports::SelectorController;
 use massa_protocol_exports::PeerId;
 use massa_protocol_exports::{ProtocolConfig, ProtocolError};
-use massa_serialization::{DeserializeError, Deserializer, Serializer};
+use massa_serialization::Serializer;
 use massa_storage::Storage;
 use massa_time::{MassaTime, TimeError};
 use massa_versioning::versioning::MipStore;
 use schnellru::{ByLength, LruMap};
-use tracing::{debug, info, warn};
+use tracing::{debug, info, info_span, warn};
 
 use super::{
+    ask_stats::AskStats,
     cache::SharedBlockCache,
     commands_propagation::BlockHandlerPropagationCommand,
     commands_retrieval::BlockHandlerRetrievalCommand,
-    messages::{
-        AskForBlocksInfo, BlockInfoReply, BlockMessage, BlockMessageDeserializer,
-        BlockMessageDeserializerArgs,
+    messages::{AskForBlocksInfo, BlockInfoReply, BlockMessage},
+    pipeline::{
+        check_header, start_pipeline, verify_header_signatures, CheckedBlockMessage, HeaderCheck,
     },
     BlockMessageSerializer,
 };
@@ -93,12 +96,16 @@ pub struct RetrievalThread {
     selector_controller: Box<dyn SelectorController>,
     consensus_controller: Box<dyn ConsensusController>,
     pool_controller: Box<dyn PoolController>,
-    receiver_network: MassaReceiver<PeerMessageTuple>,
+    receiver_network: MassaReceiver<CheckedBlockMessage>,
     _internal_sender: MassaSender<BlockHandlerPropagationCommand>,
     receiver: MassaReceiver<BlockHandlerRetrievalCommand>,
     block_message_serializer: MessagesSerializer,
     block_wishlist: PreHashMap<BlockId, BlockInfo>,
     asked_blocks: HashMap<PeerId, PreHashMap<BlockId, Instant>>,
+    /// answer statistics of the peers, to choose whom to ask the blocks
+    ask_stats: AskStats,
+    /// last time the block requests were updated, to count each timed out request once
+    last_ask_update: Instant,
     peer_cmd_sender: MassaSender<PeerManagementCmd>,
     sender_propagation_ops: MassaSender<OperationHandlerPropagationCommand>,
     sender_propagation_endorsements: MassaSender<EndorsementHandlerPropagationCommand>,
@@ -114,41 +121,15 @@ pub struct RetrievalThread {
 
 impl RetrievalThread {
     fn run(&mut self) {
-        let block_message_deserializer =
-            BlockMessageDeserializer::new(BlockMessageDeserializerArgs {
-                thread_count: self.config.thread_count,
-                endorsement_count: self.config.endorsement_count,
-                block_infos_length_max: self.config.max_size_block_infos,
-                max_operations_per_block: self.config.max_operations_per_block,
-                max_datastore_value_length: self.config.max_size_value_datastore,
-                max_function_name_length: self.config.max_size_function_name,
-                max_parameters_size: self.config.max_size_call_sc_parameter,
-                max_op_datastore_entry_count: self.config.max_op_datastore_entry_count,
-                max_op_datastore_key_length: self.config.max_op_datastore_key_length,
-                max_op_datastore_value_length: self.config.max_op_datastore_value_length,
-                max_denunciations_in_block_header: self.config.max_denunciations_in_block_header,
-                last_start_period: Some(self.config.last_start_period),
-            });
-
         let tick_update_metrics = tick(self.massa_metrics.tick_delay);
+        let heartbeat = Heartbeat::new("protocol-block-handler-retrieval");
         loop {
+            heartbeat.beat();
             select! {
                 recv(self.receiver_network) -> msg => {
                     self.receiver_network.update_metrics();
                     match msg {
-                        Ok((peer_id, message)) => {
-                            let (rest, message) = match block_message_deserializer
-                                .deserialize::<DeserializeError>(&message) {
-                                Ok((rest, message)) => (rest, message),
-                                Err(err) => {
-                                    warn!("Error in deserializing block message: {:?}", err);
-                                    continue;
-                                }
-                            };
-                            if !rest.is_empty() {
-                                println!("Error: message not fully consumed");
-                                return;
-                            }
+                        Ok((peer_id, message, header_check)) => {
                             match message {
                                 BlockMessage::AskForBlocks(block_infos) => {
                                     if let Err(err) = self.on_asked_for_blocks_received(peer_id.clone(), block_infos) {
@@ -156,7 +137,9 @@ impl RetrievalThread {
                                     }
                                 }
                                 BlockMessage::ReplyForBlocks(block_infos) => {
+                                    self.note_block_answers(&peer_id, &block_infos);
                                     for (block_id, block_info) in block_infos.into_iter() {
+                                        let _span = info_span!("block", peer_id = %peer_id, block_id = %block_id).entered();
                                         if let Err(err) = self.on_block_info_received(peer_id.clone(), block_id, block_info) {
                                             warn!("Error in on_block_info_received: {:?}", err);
                                         }
@@ -166,26 +149,35 @@ impl RetrievalThread {
                                     }
                                 }
                                 BlockMessage::BlockHeader(header) => {
+                                    let _span = info_span!("block", peer_id = %peer_id, block_id = %header.id, slot = %header.content.slot).entered();
                                     massa_trace!(BLOCK_HEADER, { "peer_id": peer_id, "header": header});
-                                    if let Ok(Some((block_id, is_new))) =
-                                        self.note_header_from_peer(&header, &peer_id)
-                                    {
-                                        if is_new {
-                                            self.consensus_controller
-                                                .register_block_header(block_id, header);
+                                    match self.note_checked_header_from_peer(&header, &peer_id, header_check) {
+                                        Ok(Some((block_id, is_new))) => {
+                                            if is_new {
+                                                self.consensus_controller
+                                                    .register_block_header(block_id, header);
+                                            }
+                                            if let Err(err) = self.update_ask_block() {
+                                                warn!("Error in update_ask_blocks: {:?}", err);
+                                            }
                                         }
-                                        if let Err(err) = self.update_ask_block() {
-                                            warn!("Error in update_ask_blocks: {:?}", err);
+                                        Ok(None) => {
+                                            warn!(
+                                                "peer {} sent us critically incorrect header, \
+                                                which may be an attack attempt by the remote peer \
+                                                or a loss of sync between us and the remote peer",
+                                                peer_id,
+                                            );
+                                            if let Err(err) = self.ban_node(&peer_id) {
+                                                warn!("Error while banning peer {} err: {:?}", peer_id, err);
+                                            }
                                         }
-                                    } else {
-                                        warn!(
-                                            "peer {} sent us critically incorrect header, \
-                                            which may be an attack attempt by the remote peer \
-                                            or a loss of sync between us and the remote peer",
-                                            peer_id,
-                                        );
-                                        if let Err(err) = self.ban_node(&peer_id) {
-                                            warn!("Error while banning peer {} err: {:?}", peer_id, err);
+                                        Err(err) => {
+                                            if is_peer_fault(&err, &peer_id, "header") {
+                                                if let Err(err) = self.ban_node(&peer_id) {
+                                                    warn!("Error while banning peer {} err: {:?}", peer_id, err);
+                                                }
+                                            }
                                         }
                                     }
                                 }
@@ -420,14 +412,10 @@ impl RetrievalThread {
             }
         }
         if let Err(err) = self.note_header_from_peer(&header, &from_peer_id) {
-            warn!(
-                "peer {} sent us critically incorrect header through protocol, \
-                which may be an attack attempt by the remote node \
-                or a loss of sync between us and the remote node. Err = {}",
-                from_peer_id, err
-            );
-            if let Err(err) = self.ban_node(&from_peer_id) {
-                warn!("Error while banning peer {} err: {:?}", from_peer_id, err);
+            if is_peer_fault(&err, &from_peer_id, "header") {
+                if let Err(err) = self.ban_node(&from_peer_id) {
+                    warn!("Error while banning peer {} err: {:?}", from_peer_id, err);
+                }
             }
             return Ok(());
         };
@@ -444,40 +432,6 @@ impl RetrievalThread {
         Ok(())
     }
 
-    /// Check if the incoming header network version is compatible with the current node
-    fn check_network_version_compatibility(
-        &self,
-        header: &SecuredHeader,
-    ) -> Result<(), ProtocolError> {
-        let slot = header.content.slot;
-        let ts = get_block_slot_timestamp(
-            self.config.thread_count,
-            self.config.t0,
-            self.config.genesis_timestamp,
-            slot,
-        )?;
-        let current_version = self.mip_store.get_network_version_active_at(ts);
-        if header.content.current_version != current_version {
-            // Received a current version different from current version (given by mip store)
-            Err(ProtocolError::IncompatibleNetworkVersion {
-                local: current_version,
-                received: header.content.current_version,
-            })
-        } else {
-            if let Some(announced_version) = header.content.announced_version {
-                if announced_version <= current_version {
-                    // Received an announced network version that is already known
-                    return Err(ProtocolError::OutdatedAnnouncedNetworkVersion {
-                        local: current_version,
-                        announced_received: announced_version,
-                    });
-                }
-            }
-
-            Ok(())
-        }
-    }
-
     /// Perform checks on a header,
     /// and if valid update the node's view of the world.
     ///
@@ -500,18 +454,37 @@ impl RetrievalThread {
         header: &SecuredHeader,
         from_peer_id: &PeerId,
     ) -> Result<Option<(BlockId, bool)>, ProtocolError> {
-        // TODO: Check if the error is used here ?
-        // refuse genesis blocks
-        if header.content.slot.period == 0 || header.content.parents.is_empty() {
-            return Ok(None);
-        }
+        let header_check = match check_header(header, &self.config, &self.mip_store, &self.cache) {
+            HeaderCheck::Checked => verify_header_signatures(header, &self.endorsement_cache),
+            header_check => header_check,
+        };
+        self.note_checked_header_from_peer(header, from_peer_id, header_check)
+    }
 
-        self.check_network_version_compatibility(header)?;
+    /// Same as `note_header_from_peer` for a header that went through the stages of the
+    /// pipeline, which already ran the checks that do not need the state of this thread.
+    fn note_checked_header_from_peer(
+        &mut self,
+        header: &SecuredHeader,
+        from_peer_id: &PeerId,
+        header_check: HeaderCheck,
+    ) -> Result<Option<(BlockId, bool)>, ProtocolError> {
+        let signatures_verified = match header_check {
+            HeaderCheck::NotChecked => return self.note_header_from_peer(header, from_peer_id),
+            HeaderCheck::Invalid => {
+                massa_trace!("protocol.protocol_worker.check_header.err_invalid", { "header": header });
+                return Ok(None);
+            }
+            HeaderCheck::Error(err) => return Err(err),
+            HeaderCheck::Verified => true,
+            // a header already checked may have left the cache since then
+            HeaderCheck::Checked | HeaderCheck::AlreadyChecked => false,
+        };
 
         // compute ID
         let block_id = header.id;
 
-        // check if this header was already verified
+        // check if this header was already verified, possibly since the checks of the pipeline
         {
             let mut cache_write = self.cache.write();
             if let Some(block_header) = cache_write.checked_headers.get(&block_id).cloned() {
@@ -546,9 +519,11 @@ impl RetrievalThread {
             }
         }
 
-        if let Err(err) =
-            self.note_endorsements_from_peer(header.content.endorsements.clone(), from_peer_id)
-        {
+        if let Err(err) = self.note_endorsements_from_peer(
+            header.content.endorsements.clone(),
+            from_peer_id,
+            signatures_verified,
+        ) {
             warn!(
                 "node {} sent us a header containing critically incorrect endorsements: {}",
                 from_peer_id, err
@@ -557,33 +532,13 @@ impl RetrievalThread {
         };
 
         // check header signature
-        if let Err(err) = header.verify_signature() {
-            massa_trace!("protocol.protocol_worker.check_header.err_signature", { "header": header, "err": format!("{}", err)});
-            return Ok(None);
-        };
-
-        // check endorsement in header integrity
-        let mut used_endorsement_indices: HashSet<u32> =
-            HashSet::with_capacity(header.content.endorsements.len());
-        for endorsement in header.content.endorsements.iter() {
-            // check index reuse
-            if !used_endorsement_indices.insert(endorsement.content.index) {
-                massa_trace!("protocol.protocol_worker.check_header.err_endorsement_index_reused", { "header": header, "endorsement": endorsement});
-                return Ok(None);
-            }
-            // check slot
-            if endorsement.content.slot != header.content.slot {
-                massa_trace!("protocol.protocol_worker.check_header.err_endorsement_invalid_slot", { "header": header, "endorsement": endorsement});
+        if !signatures_verified {
+            if let Err(err) = header.verify_signature() {
+                massa_trace!("protocol.protocol_worker.check_header.err_signature", { "header": header, "err": format!("{}", err)});
                 return Ok(None);
-            }
-            // check endorsed block
-            if endorsement.content.endorsed_block
-                != header.content.parents[header.content.slot.thread as usize]
-            {
-                massa_trace!("protocol.protocol_worker.check_header.err_endorsement_invalid_endorsed_block", { "header": header, "endorsement": endorsement});
-                return Ok(None);
-            }
+            };
         }
+
         {
             let mut cache_write = self.cache.write();
             cache_write.checked_headers.insert(block_id, header.clone());
@@ -627,6 +582,21 @@ impl RetrievalThread {
             .map_err(|err| ProtocolError::SendError(err.to_string()))
     }
 
+    /// Measure the time `peer_id` took to answer the blocks we asked it
+    fn note_block_answers(&mut self, peer_id: &PeerId, block_infos: &[(BlockId, BlockInfoReply)]) {
+        let Some(asked_blocks) = self.asked_blocks.get(peer_id) else {
+            return;
+        };
+        for (block_id, reply) in block_infos {
+            if matches!(reply, BlockInfoReply::NotFound) {
+                continue;
+            }
+            if let Some(ask_time) = asked_blocks.get(block_id) {
+                self.ask_stats.note_answer(peer_id, ask_time.elapsed());
+            }
+        }
+    }
+
     /// Remove the given blocks from the local wishlist
     pub(crate) fn remove_asked_blocks_of_node(&mut self, remove_hashes: &PreHashSet<BlockId>) {
         massa_trace!("protocol.protocol_worker.remove_asked_blocks_of_node", {
@@ -645,11 +615,12 @@ impl RetrievalThread {
     /// Does not ban if the endorsement is invalid
     ///
     /// Checks performed:
-    /// - Valid signature.
+    /// - Valid signature, unless `signatures_verified` tells that the pipeline already verified it.
     pub(crate) fn note_endorsements_from_peer(
         &mut self,
         endorsements: Vec<SecureShareEndorsement>,
         from_peer_id: &PeerId,
+        signatures_verified: bool,
     ) -> Result<(), ProtocolError> {
         massa_trace!("protocol.protocol_worker.note_endorsements_from_node", { "node": from_peer_id, "endorsements": endorsements});
         let length = endorsements.len();
@@ -673,26 +644,31 @@ impl RetrievalThread {
 
         // Batch signature verification
         // optimized signature verification
-        verify_sigs_batch(
-            &new_endorsements
-                .values()
-                .map(|endorsement| {
-                    (
-                        endorsement.compute_signed_hash(),
-                        endorsement.signature,
-                        endorsement.content_creator_pub_key,
-                    )
-                })
-                .collect::<Vec<_>>(),
-        )?;
+        if !signatures_verified {
+            verify_sigs_batch(
+                &new_endorsements
+                    .values()
+                    .map(|endorsement| {
+                        (
+                            endorsement.compute_signed_hash(),
+                            endorsement.signature,
+                            endorsement.content_creator_pub_key,
+                        )
+                    })
+                    .collect::<Vec<_>>(),
+            )?;
+        }
 
         // Check PoS draws
         for endorsement in new_endorsements.values() {
             let selection = self
                 .selector_controller
-                .get_selection(endorsement.content.slot)?;
+                .get_selection(endorsement.content.slot)
+                .map_err(|err| {
+                    ProtocolError::from_selection_error(endorsement.content.slot, err)
+                })?;
             let Some(address) = selection.endorsements.get(endorsement.content.index as usize) else {
-                return Err(ProtocolError::GeneralProtocolError(
+                return Err(ProtocolError::InvalidEndorsementError(
                     format!(
                         "No selection on slot {} for index {}",
                         endorsement.content.slot, endorsement.content.index
@@ -700,7 +676,7 @@ impl RetrievalThread {
                 ))
             };
             if address != &endorsement.content_creator_address {
-                return Err(ProtocolError::GeneralProtocolError(format!(
+                return Err(ProtocolError::InvalidEndorsementError(format!(
                     "Invalid endorsement: expected address {}, got {}",
                     address, endorsement.content_creator_address
                 )));
@@ -910,9 +886,18 @@ impl RetrievalThread {
 
             if info.operations_size > self.config.max_serialized_operations_size_per_block {
                 warn!("Peer id {} sent us a operation list for block id {} but the operations we already have in our records exceed max size.", from_peer_id, block_id);
+                // the list matches the header, so the block is too large whatever the peer
+                let reason = InvalidBlockReason::TooLarge {
+                    size: info.operations_size,
+                    max_size: self.config.max_serialized_operations_size_per_block,
+                };
+                let header = header.clone();
                 if let Err(err) = self.ban_node(&from_peer_id) {
                     warn!("Error while banning peer {} err: {:?}", from_peer_id, err);
                 }
+                self.block_wishlist.remove(&block_id);
+                self.consensus_controller
+                    .mark_invalid_block(block_id, header, reason);
                 return Ok(());
             }
 
@@ -972,12 +957,40 @@ impl RetrievalThread {
         mut operations: Vec<SecureShareOperation>,
     ) -> Result<(), ProtocolError> {
         if let Err(err) = self.note_operations_from_peer(operations.clone(), &from_peer_id) {
-            warn!(
-                "Peer id {} sent us operations for block id {} but they failed at verifications. Err = {}",
-                from_peer_id, block_id, err
-            );
-            if let Err(err) = self.ban_node(&from_peer_id) {
-                warn!("Error while banning peer {} err: {:?}", from_peer_id, err);
+            if is_peer_fault(
+                &err,
+                &from_peer_id,
+                &format!("operations of block {}", block_id),
+            ) {
+                if let Err(err) = self.ban_node(&from_peer_id) {
+                    warn!("Error while banning peer {} err: {:?}", from_peer_id, err);
+                }
+            }
+            // an operation listed by the block that exceeds the max size of a block makes the
+            // block invalid: the id of an operation commits to its content, hence to its size
+            let max_size = self.config.max_serialized_operations_size_per_block;
+            if let Some(info) = self.block_wishlist.get(&block_id)
+                && let (Some(header), Some(operation_ids)) = (&info.header, &info.operation_ids)
+                && let Some((index, operation)) = operation_ids.iter().enumerate().find_map(|(index, id)| {
+                    operations
+                        .iter()
+                        .find(|op| &op.id == id && op.serialized_size() > max_size)
+                        .map(|op| (index, op))
+                })
+            {
+                let reason = InvalidBlockReason::InvalidOperation {
+                    index,
+                    reason: format!(
+                        "operation {} of {} bytes exceeds the max block size of {} bytes",
+                        operation.id,
+                        operation.serialized_size(),
+                        max_size
+                    ),
+                };
+                let header = header.clone();
+                self.block_wishlist.remove(&block_id);
+                self.consensus_controller
+                    .mark_invalid_block(block_id, header, reason);
             }
             return Ok(());
         }
@@ -1032,8 +1045,14 @@ impl RetrievalThread {
                         warn!("Error while banning peer {} err: {:?}", from_peer_id, err);
                     }
                     self.block_wishlist.remove(&block_id);
-                    self.consensus_controller
-                        .mark_invalid_block(block_id, header);
+                    self.consensus_controller.mark_invalid_block(
+                        block_id,
+                        header,
+                        InvalidBlockReason::TooLarge {
+                            size: full_op_size,
+                            max_size: self.config.max_serialized_operations_size_per_block,
+                        },
+                    );
                 } else {
                     if known_operations != &block_ids_set {
                         warn!(
@@ -1070,6 +1089,7 @@ impl RetrievalThread {
                         id: block_id,
                         content: block,
                         serialized_data: content_serialized,
+                        signed_hash: Default::default(),
                     };
 
                     // create block storage (without parents)
@@ -1145,8 +1165,8 @@ impl RetrievalThread {
                 return Err(ProtocolError::InvalidOperationError(format!(
                     "Operation {} exceeds max block size,  maximum authorized {} bytes but found {} bytes",
                     operation.id,
-                    operation.serialized_size(),
-                    self.config.max_serialized_operations_size_per_block
+                    self.config.max_serialized_operations_size_per_block,
+                    operation.serialized_size()
                 )));
             };
 
@@ -1262,6 +1282,7 @@ impl RetrievalThread {
                         .try_into()
                         .expect("max_node_known_blocks_size is too big"),
                 );
+                self.ask_stats.retain_connected(&peers_connected);
                 let peers_in_asked_blocks: Vec<PeerId> =
                     self.asked_blocks.keys().cloned().collect();
                 for peer_id in peers_in_asked_blocks {
@@ -1298,6 +1319,10 @@ impl RetrievalThread {
                     } else {
                         (None, false)
                     };
+                    // count the timeouts that happened since the last update
+                    if let Some(timeout_at) = timeout_at_opt && timed_out && timeout_at > self.last_ask_update {
+                        self.ask_stats.note_timeout(peer_id);
+                    }
                     let knows_block = blocks_known.get(hash);
 
                     // check if the peer recently told us it doesn't have the block
@@ -1383,6 +1408,8 @@ impl RetrievalThread {
                 )
             })
             .collect();
+        // peers that never answered are expected to answer within half of the timeout
+        let default_rtt: Duration = self.config.ask_block_timeout.to_duration() / 2;
         {
             let cache_read = self.cache.read();
             for (hash, criteria) in candidate_nodes.into_iter() {
@@ -1404,10 +1431,15 @@ impl RetrievalThread {
                     })
                     .min_by_key(|(knowledge, peer_id, _, instant)| {
                         (
-                            *knowledge,                                         // block knowledge
-                            *active_block_req_count.get(peer_id).unwrap_or(&0), // active requests
-                            *instant,                                           // node age
-                            peer_id.clone(),                                    // node ID
+                            *knowledge, // block knowledge
+                            // expected answer time given the answer history and the active requests
+                            self.ask_stats.expected_answer_ms(
+                                peer_id,
+                                *active_block_req_count.get(peer_id).unwrap_or(&0),
+                                default_rtt,
+                            ),
+                            *instant,        // node age
+                            peer_id.clone(), // node ID
                         )
                     })
                 {
@@ -1451,6 +1483,7 @@ impl RetrievalThread {
         }
 
         self.next_timer_ask_block = next_tick;
+        self.last_ask_update = now;
         Ok(())
     }
 }
@@ -1474,10 +1507,22 @@ pub fn start_retrieval_thread(
     cache: SharedBlockCache,
     storage: Storage,
     mip_store: MipStore,
+    peer_stats: SharedPeerStats,
     massa_metrics: MassaMetrics,
 ) -> JoinHandle<()> {
     let block_message_serializer =
         MessagesSerializer::new().with_block_message_serializer(BlockMessageSerializer::new());
+    // blocks are decoded and their headers checked by separate threads, see `pipeline`
+    let (sender_stop_pipeline, receiver_stop_pipeline) = crossbeam::channel::bounded(0);
+    let (receiver_checked, pipeline_threads) = start_pipeline(
+        receiver_network,
+        receiver_stop_pipeline,
+        &config,
+        mip_store.clone(),
+        cache.clone(),
+        endorsement_cache.clone(),
+        peer_stats,
+    );
     std::thread::Builder::new()
         .name("protocol-block-handler-retrieval".to_string())
         .spawn(move || {
@@ -1489,10 +1534,12 @@ pub fn start_retrieval_thread(
                 next_timer_ask_block: Instant::now() + config.ask_block_timeout.to_duration(),
                 block_wishlist: PreHashMap::default(),
                 asked_blocks: HashMap::default(),
+                ask_stats: AskStats::default(),
+                last_ask_update: Instant::now(),
                 peer_cmd_sender,
                 sender_propagation_ops,
                 sender_propagation_endorsements,
-                receiver_network,
+                receiver_network: receiver_checked,
                 block_message_serializer,
                 receiver,
                 _internal_sender,
@@ -1505,6 +1552,13 @@ pub fn start_retrieval_thread(
                 massa_metrics,
             };
             retrieval_thread.run();
+            drop(retrieval_thread);
+            drop(sender_stop_pipeline);
+            for thread in pipeline_threads {
+                if thread.join().is_err() {
+                    warn!("Block pipeline thread panicked");
+                }
+            }
         })
         .expect("OS failed to start block retrieval thread")
 }
@@ -7,6 +7,7 @@ use massa_pos_exports::SelectorController;
 use massa_protocol_exports::ProtocolConfig;
 use massa_storage::Storage;
 
+use crate::peer_stats::SharedPeerStats;
 use crate::wrap_network::ActiveConnectionsTrait;
 
 use self::{
@@ -22,7 +23,11 @@ mod messages;
 mod propagation;
 mod retrieval;
 
-pub(crate) use messages::{EndorsementMessage, EndorsementMessageSerializer};
+pub(crate) use messages::{
+    EndorsementMessage, EndorsementMessageSerializer, MessageTypeId as EndorsementMessageTypeId,
+};
+#[cfg(any(test, feature = "fuzzing"))]
+pub(crate) use messages::{EndorsementMessageDeserializer, EndorsementMessageDeserializerArgs};
 
 use super::peer_handler::models::{PeerManagementCmd, PeerMessageTuple};
 
@@ -52,6 +57,7 @@ impl EndorsementHandler {
         local_sender: MassaSender<EndorsementHandlerPropagationCommand>,
         local_receiver: MassaReceiver<EndorsementHandlerPropagationCommand>,
         sender_peer_cmd: MassaSender<PeerManagementCmd>,
+        peer_stats: SharedPeerStats,
         massa_metrics: MassaMetrics,
     ) -> Self {
         let endorsement_retrieval_thread = start_retrieval_thread(
@@ -64,6 +70,7 @@ impl EndorsementHandler {
             pool_controller,
             config.clone(),
             storage.clone_without_refs(),
+            peer_stats,
             massa_metrics,
         );
 
@@ -1,13 +1,17 @@
 use std::thread::JoinHandle;
 
-use massa_channel::receiver::MassaReceiver;
+use crossbeam::channel::RecvTimeoutError;
+use massa_channel::{
+    heartbeat::{Heartbeat, MAX_BEAT_INTERVAL},
+    receiver::MassaReceiver,
+};
 use massa_models::{
     endorsement::{EndorsementId, SecureShareEndorsement},
     prehash::{PreHashMap, PreHashSet},
 };
 use massa_protocol_exports::PeerId;
 use massa_protocol_exports::ProtocolConfig;
-use tracing::{debug, info, log::warn};
+use tracing::{debug, info, warn};
 
 use crate::{messages::MessagesSerializer, wrap_network::ActiveConnectionsTrait};
 
@@ -26,8 +30,10 @@ struct PropagationThread {
 
 impl PropagationThread {
     fn run(&mut self) {
+        let heartbeat = Heartbeat::new("protocol-endorsement-handler-propagation");
         loop {
-            match self.receiver.recv() {
+            heartbeat.beat();
+            match self.receiver.recv_timeout(MAX_BEAT_INTERVAL) {
                 Ok(msg) => {
                     match msg {
                         EndorsementHandlerPropagationCommand::PropagateEndorsements(
@@ -135,7 +141,8 @@ impl PropagationThread {
                         }
                     }
                 }
-                Err(_) => {
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => {
                     info!("Stop endorsement propagation thread");
                     return;
                 }
@@ -1,7 +1,11 @@
 use std::thread::JoinHandle;
 
 use crossbeam::{channel::tick, select};
-use massa_channel::{receiver::MassaReceiver, sender::MassaSender};
+use massa_channel::{
+    heartbeat::{Heartbeat, MAX_BEAT_INTERVAL},
+    receiver::MassaReceiver,
+    sender::MassaSender,
+};
 use massa_logging::massa_trace;
 use massa_metrics::MassaMetrics;
 use massa_models::{
@@ -22,8 +26,10 @@ use tracing::{debug, info, warn};
 use crate::{
     handlers::{
         endorsement_handler::messages::EndorsementMessage,
+        is_peer_fault,
         peer_handler::models::{PeerManagementCmd, PeerMessageTuple},
     },
+    peer_stats::{note_invalid_message, SharedPeerStats},
     sig_verifier::verify_sigs_batch,
 };
 
@@ -44,6 +50,7 @@ pub struct RetrievalThread {
     config: ProtocolConfig,
     storage: Storage,
     peer_cmd_sender: MassaSender<PeerManagementCmd>,
+    peer_stats: SharedPeerStats,
     metrics: MassaMetrics,
 }
 
@@ -56,8 +63,10 @@ impl RetrievalThread {
                 endorsement_count: self.config.endorsement_count,
             });
         let tick_metrics = tick(self.metrics.tick_delay);
+        let heartbeat = Heartbeat::new("protocol-endorsement-handler-retrieval");
 
         loop {
+            heartbeat.beat();
             select! {
                 recv(self.receiver) -> msg => {
                     self.receiver.update_metrics();
@@ -68,12 +77,14 @@ impl RetrievalThread {
                                 Ok((rest, message)) => (rest, message),
                                 Err(err) => {
                                     warn!("Error while deserializing message from peer {} err: {:?}", peer_id, err);
+                                    note_invalid_message(&self.peer_stats, &peer_id);
                                     continue;
                                 }
                             };
                             if !rest.is_empty() {
-                                println!("Error: message not fully consumed");
-                                return;
+                                warn!("Message not fully consumed from {}", peer_id);
+                                note_invalid_message(&self.peer_stats, &peer_id);
+                                continue;
                             }
                             match message {
                                 EndorsementMessage::Endorsements(endorsements) => {
@@ -81,14 +92,10 @@ impl RetrievalThread {
                                     if let Err(err) =
                                         self.note_endorsements_from_peer(endorsements, &peer_id)
                                     {
-                                        warn!(
-                                            "peer {} sent us critically incorrect endorsements, \
-                                            which may be an attack attempt by the remote node or a \
-                                            loss of sync between us and the remote node. Err = {}",
-                                            peer_id, err
-                                        );
-                                        if let Err(err) = self.ban_node(&peer_id) {
-                                            warn!("Error while banning peer {} err: {:?}", peer_id, err);
+                                        if is_peer_fault(&err, &peer_id, "endorsements") {
+                                            if let Err(err) = self.ban_node(&peer_id) {
+                                                warn!("Error while banning peer {} err: {:?}", peer_id, err);
+                                            }
                                         }
                                     }
                                 }
@@ -128,6 +135,7 @@ impl RetrievalThread {
                     self.metrics
                         .set_endorsements_cache_metrics(read.checked_endorsements.len(), count);
                 }
+                default(MAX_BEAT_INTERVAL) => {}
             }
         }
     }
@@ -185,9 +193,12 @@ impl RetrievalThread {
         for endorsement in new_endorsements.values() {
             let selection = self
                 .selector_controller
-                .get_selection(endorsement.content.slot)?;
+                .get_selection(endorsement.content.slot)
+                .map_err(|err| {
+                    ProtocolError::from_selection_error(endorsement.content.slot, err)
+                })?;
             let Some(address) = selection.endorsements.get(endorsement.content.index as usize) else {
-                        return Err(ProtocolError::GeneralProtocolError(
+                        return Err(ProtocolError::InvalidEndorsementError(
                             format!(
                                 "No selection on slot {} for index {}",
                                 endorsement.content.slot, endorsement.content.index
@@ -195,7 +206,7 @@ impl RetrievalThread {
                         ))
                     };
             if address != &endorsement.content_creator_address {
-                return Err(ProtocolError::GeneralProtocolError(format!(
+                return Err(ProtocolError::InvalidEndorsementError(format!(
                     "Invalid endorsement: expected address {}, got {}",
                     address, endorsement.content_creator_address
                 )));
@@ -301,6 +312,7 @@ pub fn start_retrieval_thread(
     pool_controller: Box<dyn PoolController>,
     config: ProtocolConfig,
     storage: Storage,
+    peer_stats: SharedPeerStats,
     metrics: MassaMetrics,
 ) -> JoinHandle<()> {
     std::thread::Builder::new()
@@ -316,6 +328,7 @@ pub fn start_retrieval_thread(
                 pool_controller,
                 config,
                 storage,
+                peer_stats,
                 metrics,
             };
             retrieval_thread.run();
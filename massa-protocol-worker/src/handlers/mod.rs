@@ -2,3 +2,45 @@ pub mod block_handler;
 pub mod endorsement_handler;
 pub mod operation_handler;
 pub mod peer_handler;
+
+use massa_protocol_exports::{PeerId, ProtocolError, ProtocolErrorKind};
+use tracing::{error, warn};
+
+/// Log an error raised while processing the data received from `peer_id`.
+/// Returns true if the peer is at fault, in which case it must be banned.
+pub(crate) fn is_peer_fault(err: &ProtocolError, peer_id: &PeerId, data: &str) -> bool {
+    match err.kind() {
+        ProtocolErrorKind::PeerFault => {
+            warn!(
+                "peer {} sent us critically incorrect {}, which may be an attack attempt by the remote peer \
+                or a loss of sync between us and the remote peer. Err = {}",
+                peer_id, data, err
+            );
+            true
+        }
+        ProtocolErrorKind::Transient => {
+            warn!(
+                "could not process the {} received from peer {}, dropping them: {}",
+                data, peer_id, err
+            );
+            false
+        }
+        ProtocolErrorKind::Internal => {
+            error!(
+                "internal error while processing the {} received from peer {}: {}",
+                data, peer_id, err
+            );
+            false
+        }
+    }
+}
+
+/// Log an error raised by an action of the node, loudly if it is a bug of the node
+pub(crate) fn log_error(err: &ProtocolError, action: &str) {
+    match err.kind() {
+        ProtocolErrorKind::Internal => error!("internal error while {}: {}", action, err),
+        ProtocolErrorKind::PeerFault | ProtocolErrorKind::Transient => {
+            warn!("error while {}: {}", action, err)
+        }
+    }
+}
@@ -6,6 +6,7 @@ use massa_pool_exports::PoolController;
 use massa_protocol_exports::ProtocolConfig;
 use massa_storage::Storage;
 
+use crate::peer_stats::SharedPeerStats;
 use crate::wrap_network::ActiveConnectionsTrait;
 
 use self::{
@@ -21,7 +22,11 @@ mod messages;
 mod propagation;
 mod retrieval;
 
-pub(crate) use messages::{OperationMessage, OperationMessageSerializer};
+pub(crate) use messages::{
+    MessageTypeId as OperationMessageTypeId, OperationMessage, OperationMessageSerializer,
+};
+#[cfg(any(test, feature = "fuzzing"))]
+pub(crate) use messages::{OperationMessageDeserializer, OperationMessageDeserializerArgs};
 
 use super::peer_handler::models::{PeerManagementCmd, PeerMessageTuple};
 
@@ -50,6 +55,7 @@ impl OperationHandler {
         local_sender: MassaSender<OperationHandlerPropagationCommand>,
         local_receiver: MassaReceiver<OperationHandlerPropagationCommand>,
         peer_cmd_sender: MassaSender<PeerManagementCmd>,
+        peer_stats: SharedPeerStats,
         massa_metrics: MassaMetrics,
     ) -> Self {
         let operation_retrieval_thread = start_retrieval_thread(
@@ -62,6 +68,7 @@ impl OperationHandler {
             receiver_retrieval_ext,
             local_sender.clone(),
             peer_cmd_sender,
+            peer_stats,
             massa_metrics.clone(),
         );
 
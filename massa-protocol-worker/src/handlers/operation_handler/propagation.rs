@@ -2,7 +2,10 @@ use std::collections::VecDeque;
 use std::{mem, thread::JoinHandle};
 
 use crossbeam::channel::RecvTimeoutError;
-use massa_channel::receiver::MassaReceiver;
+use massa_channel::{
+    heartbeat::{Heartbeat, MAX_BEAT_INTERVAL},
+    receiver::MassaReceiver,
+};
 use massa_logging::massa_trace;
 use massa_metrics::MassaMetrics;
 use massa_models::operation::OperationId;
@@ -11,7 +14,7 @@ use massa_models::prehash::PreHashSet;
 use massa_protocol_exports::PeerId;
 use massa_protocol_exports::ProtocolConfig;
 use massa_storage::Storage;
-use tracing::{debug, info, log::warn};
+use tracing::{debug, info, warn};
 
 use crate::{
     handlers::operation_handler::OperationMessage, messages::MessagesSerializer,
@@ -41,8 +44,11 @@ impl PropagationThread {
         let mut batch_deadline = std::time::Instant::now()
             .checked_add(self.config.operation_announcement_interval.to_duration())
             .expect("Can't init interval op propagation");
+        let heartbeat = Heartbeat::new("protocol-operation-handler-propagation");
         loop {
-            match self.internal_receiver.recv_deadline(batch_deadline) {
+            heartbeat.beat();
+            let deadline = batch_deadline.min(std::time::Instant::now() + MAX_BEAT_INTERVAL);
+            match self.internal_receiver.recv_deadline(deadline) {
                 Ok(internal_message) => {
                     match internal_message {
                         OperationHandlerPropagationCommand::PropagateOperations(operations) => {
@@ -84,6 +90,10 @@ impl PropagationThread {
                     }
                 }
                 Err(RecvTimeoutError::Timeout) => {
+                    if std::time::Instant::now() < batch_deadline {
+                        // woken up only to beat
+                        continue;
+                    }
                     self.announce_ops();
                     batch_deadline = std::time::Instant::now()
                         .checked_add(self.config.operation_announcement_interval.to_duration())
@@ -1,7 +1,11 @@
 use std::{collections::VecDeque, thread::JoinHandle, time::Instant};
 
 use crossbeam::{channel::tick, select};
-use massa_channel::{receiver::MassaReceiver, sender::MassaSender};
+use massa_channel::{
+    heartbeat::{Heartbeat, MAX_BEAT_INTERVAL},
+    receiver::MassaReceiver,
+    sender::MassaSender,
+};
 use massa_logging::massa_trace;
 use massa_metrics::MassaMetrics;
 use massa_models::{
@@ -20,8 +24,12 @@ use massa_time::{MassaTime, TimeError};
 use schnellru::{ByLength, LruMap};
 
 use crate::{
-    handlers::peer_handler::models::{PeerManagementCmd, PeerMessageTuple},
+    handlers::{
+        is_peer_fault,
+        peer_handler::models::{PeerManagementCmd, PeerMessageTuple},
+    },
     messages::MessagesSerializer,
+    peer_stats::{note_invalid_message, SharedPeerStats},
     sig_verifier::verify_sigs_batch,
     wrap_network::ActiveConnectionsTrait,
 };
@@ -60,6 +68,7 @@ pub struct RetrievalThread {
     receiver_ext: MassaReceiver<OperationHandlerRetrievalCommand>,
     operation_message_serializer: MessagesSerializer,
     peer_cmd_sender: MassaSender<PeerManagementCmd>,
+    peer_stats: SharedPeerStats,
     _massa_metrics: MassaMetrics,
 }
 
@@ -77,8 +86,10 @@ impl RetrievalThread {
                 max_op_datastore_value_length: self.config.max_op_datastore_value_length,
             });
         let tick_ask_operations = tick(self.config.operation_batch_proc_period.to_duration());
+        let heartbeat = Heartbeat::new("protocol-operation-handler-retrieval");
 
         loop {
+            heartbeat.beat();
             select! {
                 recv(self.receiver) -> msg => {
                     self.receiver.update_metrics();
@@ -89,21 +100,23 @@ impl RetrievalThread {
                                     Ok((rest, message)) => (rest, message),
                                     Err(err) => {
                                         warn!("Error when deserializing message from peer {}: Err = {}", peer_id, err);
+                                        note_invalid_message(&self.peer_stats, &peer_id);
                                         continue;
                                     }
                                 };
                             if !rest.is_empty() {
-                                println!("Error: message not fully consumed");
-                                return;
+                                warn!("Message not fully consumed from {}", peer_id);
+                                note_invalid_message(&self.peer_stats, &peer_id);
+                                continue;
                             }
                             match message {
                                 OperationMessage::Operations(ops) => {
                                     debug!("Received operation message: Operations from {}", peer_id);
                                     if let Err(err) = self.note_operations_from_peer(ops, &peer_id) {
-                                        warn!("peer {} sent us critically incorrect operation, which may be an attack attempt by the remote peer or a loss of sync between us and the remote peer. Err = {}", peer_id, err);
-
-                                        if let Err(e) = self.ban_node(&peer_id) {
-                                            warn!("Error when banning node: {}", e);
+                                        if is_peer_fault(&err, &peer_id, "operations") {
+                                            if let Err(e) = self.ban_node(&peer_id) {
+                                                warn!("Error when banning node: {}", e);
+                                            }
                                         }
                                     }
                                 }
@@ -149,6 +162,7 @@ impl RetrievalThread {
                         warn!("Error in update_ask_operation: {}", err);
                     };
                 }
+                default(MAX_BEAT_INTERVAL) => {}
             }
         }
     }
@@ -481,6 +495,7 @@ pub fn start_retrieval_thread(
     receiver_ext: MassaReceiver<OperationHandlerRetrievalCommand>,
     internal_sender: MassaSender<OperationHandlerPropagationCommand>,
     peer_cmd_sender: MassaSender<PeerManagementCmd>,
+    peer_stats: SharedPeerStats,
     massa_metrics: MassaMetrics,
 ) -> JoinHandle<()> {
     std::thread::Builder::new()
@@ -505,6 +520,7 @@ pub fn start_retrieval_thread(
                     .with_operation_message_serializer(OperationMessageSerializer::new()),
                 op_batch_buffer: VecDeque::new(),
                 peer_cmd_sender,
+                peer_stats,
                 _massa_metrics: massa_metrics,
             };
             retrieval_thread.run();
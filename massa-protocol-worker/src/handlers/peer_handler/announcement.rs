@@ -6,7 +6,7 @@ use std::{
 
 use massa_hash::Hash;
 use massa_models::serialization::IpAddrDeserializer;
-use massa_signature::{KeyPair, Signature, SignatureDeserializer};
+use massa_signature::{Signature, SignatureDeserializer};
 use massa_time::MassaTime;
 use nom::{
     error::{context, ContextError, ParseError},
@@ -19,17 +19,25 @@ use peernet::{
     transports::TransportType,
 };
 
+use super::rotation::{KeyRotation, KeyRotationDeserializer, KeyRotationSerializer};
+use crate::context::Context;
 use massa_serialization::{
     DeserializeError, Deserializer, SerializeError, Serializer, U64VarIntDeserializer,
     U64VarIntSerializer,
 };
 
+/// Handshake id of an announcement carrying a key rotation.
+/// Peers not supporting key rotations refuse it as an unknown id.
+pub const ANNOUNCEMENT_WITH_KEY_ROTATION_ID: u8 = 2;
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Announcement {
     /// Listeners
     pub listeners: HashMap<SocketAddr, TransportType>,
     /// Timestamp
     pub timestamp: u64,
+    /// Rotation of the key of the peer, covered by the signature of the announcement
+    pub key_rotation: Option<KeyRotation>,
     /// Hash
     pub hash: Hash,
     /// serialized version
@@ -59,6 +67,7 @@ impl Serializer<Announcement> for AnnouncementSerializer {
 pub struct AnnouncementDeserializer {
     length_listeners_deserializer: U64VarIntDeserializer,
     ip_addr_deserializer: IpAddrDeserializer,
+    key_rotation_deserializer: KeyRotationDeserializer,
 }
 
 pub struct AnnouncementDeserializerArgs {
@@ -73,14 +82,23 @@ impl AnnouncementDeserializer {
                 Included(args.max_listeners),
             ),
             ip_addr_deserializer: IpAddrDeserializer::new(),
+            key_rotation_deserializer: KeyRotationDeserializer::new(),
         }
     }
-}
 
-impl Deserializer<Announcement> for AnnouncementDeserializer {
-    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+    /// Deserializes an announcement carrying a key rotation after its timestamp,
+    /// sent with the `ANNOUNCEMENT_WITH_KEY_ROTATION_ID` handshake id
+    pub fn deserialize_with_key_rotation<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], Announcement, E> {
+        self.deserialize_announcement(buffer, true)
+    }
+
+    fn deserialize_announcement<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
         &self,
         buffer: &'a [u8],
+        with_key_rotation: bool,
     ) -> IResult<&'a [u8], Announcement, E> {
         let (rest, (listeners, timestamp)) = context(
             "Failed announcement deserialization",
@@ -145,6 +163,12 @@ impl Deserializer<Announcement> for AnnouncementDeserializer {
         )
         .map(|info| info)
         .parse(buffer)?;
+        let (rest, key_rotation) = if with_key_rotation {
+            let (rest, key_rotation) = self.key_rotation_deserializer.deserialize(rest)?;
+            (rest, Some(key_rotation))
+        } else {
+            (rest, None)
+        };
         let serialized = buffer[..buffer.len() - rest.len()].to_vec();
         let hash = Hash::compute_from(&serialized);
         let signature_deserializer = SignatureDeserializer::new();
@@ -162,6 +186,7 @@ impl Deserializer<Announcement> for AnnouncementDeserializer {
                 listeners: listeners.into_iter().collect(),
                 hash,
                 timestamp,
+                key_rotation,
                 serialized,
                 signature,
             },
@@ -169,11 +194,21 @@ impl Deserializer<Announcement> for AnnouncementDeserializer {
     }
 }
 
+impl Deserializer<Announcement> for AnnouncementDeserializer {
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], Announcement, E> {
+        self.deserialize_announcement(buffer, false)
+    }
+}
+
 impl Announcement {
     pub fn new(
         mut listeners: HashMap<SocketAddr, TransportType>,
         routable_ip: Option<IpAddr>,
-        keypair: &KeyPair,
+        key_rotation: Option<KeyRotation>,
+        context: &Context,
     ) -> PeerNetResult<Self> {
         let mut buf: Vec<u8> = vec![];
         let length_serializer = U64VarIntSerializer::new();
@@ -208,12 +243,21 @@ impl Announcement {
             .expect("Unable to get MassaTime::now")
             .to_millis();
         buf.extend(timestamp.to_be_bytes());
+        if let Some(key_rotation) = &key_rotation {
+            KeyRotationSerializer::new()
+                .serialize(key_rotation, &mut buf)
+                .map_err(|err| {
+                    PeerNetError::HandlerError
+                        .error("Announcement serialization", Some(err.to_string()))
+                })?;
+        }
         let hash = Hash::compute_from(&buf);
         Ok(Self {
             listeners,
             timestamp,
+            key_rotation,
             hash,
-            signature: keypair.sign(&hash).map_err(|err| {
+            signature: context.sign(&hash).map_err(|err| {
                 PeerNetError::SignError.error("Announcement serialization", Some(err.to_string()))
             })?,
             serialized: buf,
@@ -223,13 +267,18 @@ impl Announcement {
 
 #[cfg(test)]
 mod tests {
+    use crate::context::Context;
     use crate::handlers::peer_handler::announcement::{
         Announcement, AnnouncementDeserializer, AnnouncementDeserializerArgs,
     };
+    use crate::handlers::peer_handler::rotation::{KeyRotation, KeyRotationSerializer};
+    use massa_models::config::SIGNATURE_DESER_SIZE;
+    use massa_protocol_exports::PeerId;
     use massa_serialization::{DeserializeError, Deserializer, Serializer};
     use massa_signature::KeyPair;
     use peernet::transports::TransportType;
     use std::collections::HashMap;
+    use std::sync::Arc;
 
     use super::AnnouncementSerializer;
 
@@ -238,8 +287,12 @@ mod tests {
         let mut listeners = HashMap::new();
         listeners.insert("127.0.0.1:8081".parse().unwrap(), TransportType::Tcp);
         listeners.insert("127.0.0.1:8082".parse().unwrap(), TransportType::Quic);
-        let announcement =
-            Announcement::new(listeners, None, &KeyPair::generate(0).unwrap()).unwrap();
+        let keypair = KeyPair::generate(0).unwrap();
+        let context = Context {
+            our_public_key: keypair.get_public_key(),
+            signer: Arc::new(keypair),
+        };
+        let announcement = Announcement::new(listeners, None, None, &context).unwrap();
         let announcement_serializer = AnnouncementSerializer::new();
         let announcement_deserializer =
             AnnouncementDeserializer::new(AnnouncementDeserializerArgs { max_listeners: 100 });
@@ -252,4 +305,52 @@ mod tests {
             .unwrap();
         assert_eq!(announcement, announcement_deserialized);
     }
+
+    #[test]
+    fn test_ser_deser_with_key_rotation() {
+        let previous_keypair = KeyPair::generate(0).unwrap();
+        let keypair = KeyPair::generate(0).unwrap();
+        let peer_id = PeerId::from_public_key(keypair.get_public_key());
+        let key_rotation = KeyRotation::new(&previous_keypair, &peer_id).unwrap();
+        let context = Context {
+            our_public_key: keypair.get_public_key(),
+            signer: Arc::new(keypair),
+        };
+        let announcement =
+            Announcement::new(HashMap::new(), None, Some(key_rotation.clone()), &context).unwrap();
+        let mut buf: Vec<u8> = vec![];
+        AnnouncementSerializer::new()
+            .serialize(&announcement, &mut buf)
+            .unwrap();
+        let announcement_deserializer =
+            AnnouncementDeserializer::new(AnnouncementDeserializerArgs { max_listeners: 100 });
+        let (rest, announcement_deserialized) = announcement_deserializer
+            .deserialize_with_key_rotation::<DeserializeError>(&buf)
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(announcement, announcement_deserialized);
+        assert_eq!(announcement_deserialized.key_rotation, Some(key_rotation));
+        peer_id
+            .verify_signature(
+                &announcement_deserialized.hash,
+                &announcement_deserialized.signature,
+            )
+            .unwrap();
+
+        // the rotation is covered by the signature of the announcement
+        let other_rotation = KeyRotation::new(&KeyPair::generate(0).unwrap(), &peer_id).unwrap();
+        let mut tampered = Vec::new();
+        tampered.extend(&buf[..9]);
+        KeyRotationSerializer::new()
+            .serialize(&other_rotation, &mut tampered)
+            .unwrap();
+        tampered.extend(&buf[buf.len() - SIGNATURE_DESER_SIZE..]);
+        let (_, tampered) = announcement_deserializer
+            .deserialize_with_key_rotation::<DeserializeError>(&tampered)
+            .unwrap();
+        assert_eq!(tampered.key_rotation, Some(other_rotation));
+        assert!(peer_id
+            .verify_signature(&tampered.hash, &tampered.signature)
+            .is_err());
+    }
 }
@@ -0,0 +1,181 @@
+//! Limits on the handshakes in progress.
+//!
+//! Each handshake holds a thread and buffers until the peer answers or times out, so they are
+//! limited in number, in total and per IP, so that a few addresses can't take all the slots.
+//! The read timeout of the connections only bounds each read: a peer sending its bytes slowly
+//! could keep a handshake running for long, so the handshakes lasting longer than a timeout
+//! are ended by shutting down their connection.
+
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Weak,
+    },
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+use peernet::transports::endpoint::Endpoint;
+use tracing::debug;
+
+/// Interval between two checks of the handshakes running for too long
+const WATCHDOG_TICK: Duration = Duration::from_millis(200);
+
+/// Handshakes in progress, shared by the clones of the handshake handler
+#[derive(Debug, Default)]
+pub(crate) struct RunningHandshakes {
+    total: usize,
+    per_ip: HashMap<IpAddr, usize>,
+}
+
+pub(crate) type SharedRunningHandshakes = Arc<Mutex<RunningHandshakes>>;
+
+/// Slot taken by a running handshake, released when it ends
+pub(crate) struct HandshakeSlot {
+    running: SharedRunningHandshakes,
+    ip: IpAddr,
+}
+
+impl HandshakeSlot {
+    /// Take a slot for a handshake with `ip` if fewer than `max` handshakes are running,
+    /// and fewer than `max_per_ip` with `ip`. Returns the reason of the refusal otherwise.
+    pub(crate) fn acquire(
+        running: &SharedRunningHandshakes,
+        ip: IpAddr,
+        max: usize,
+        max_per_ip: usize,
+    ) -> Result<Self, &'static str> {
+        let ip = ip.to_canonical();
+        let mut running_write = running.lock();
+        if running_write.total >= max {
+            return Err("Too many handshakes in progress");
+        }
+        let with_ip = running_write.per_ip.entry(ip).or_default();
+        if *with_ip >= max_per_ip {
+            return Err("Too many handshakes in progress with this IP");
+        }
+        *with_ip += 1;
+        running_write.total += 1;
+        Ok(HandshakeSlot {
+            running: running.clone(),
+            ip,
+        })
+    }
+}
+
+impl Drop for HandshakeSlot {
+    fn drop(&mut self) {
+        let mut running_write = self.running.lock();
+        running_write.total = running_write.total.saturating_sub(1);
+        if let Some(with_ip) = running_write.per_ip.get_mut(&self.ip) {
+            *with_ip = with_ip.saturating_sub(1);
+            if *with_ip == 0 {
+                running_write.per_ip.remove(&self.ip);
+            }
+        }
+    }
+}
+
+/// A handshake watched by the watchdog: its deadline, whether it ended, and its connection
+type WatchedHandshake = (Instant, Arc<AtomicBool>, Endpoint);
+
+/// Shuts down the connections of the handshakes running for longer than a timeout.
+/// Its thread stops once all the clones are dropped.
+#[derive(Clone)]
+pub(crate) struct HandshakeWatchdog {
+    timeout: Duration,
+    handshakes: Arc<Mutex<Vec<WatchedHandshake>>>,
+}
+
+/// Marks the end of a watched handshake when dropped
+pub(crate) struct HandshakeWatch(Arc<AtomicBool>);
+
+impl Drop for HandshakeWatch {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Release);
+    }
+}
+
+impl HandshakeWatchdog {
+    pub(crate) fn start(timeout: Duration) -> Self {
+        let handshakes: Arc<Mutex<Vec<WatchedHandshake>>> = Default::default();
+        let weak_handshakes = Arc::downgrade(&handshakes);
+        std::thread::Builder::new()
+            .name("protocol-handshake-watchdog".to_string())
+            .spawn(move || run_watchdog(weak_handshakes))
+            .expect("OS failed to start handshake watchdog thread");
+        HandshakeWatchdog {
+            timeout,
+            handshakes,
+        }
+    }
+
+    /// Watch the handshake running on `endpoint` until the returned watch is dropped
+    pub(crate) fn watch(&self, endpoint: Endpoint) -> HandshakeWatch {
+        let ended = Arc::new(AtomicBool::new(false));
+        self.handshakes
+            .lock()
+            .push((Instant::now() + self.timeout, ended.clone(), endpoint));
+        HandshakeWatch(ended)
+    }
+}
+
+fn run_watchdog(handshakes: Weak<Mutex<Vec<WatchedHandshake>>>) {
+    loop {
+        std::thread::sleep(WATCHDOG_TICK);
+        let Some(handshakes) = handshakes.upgrade() else {
+            return;
+        };
+        let now = Instant::now();
+        handshakes.lock().retain_mut(|(deadline, ended, endpoint)| {
+            if ended.load(Ordering::Acquire) {
+                return false;
+            }
+            if *deadline <= now {
+                debug!(
+                    "handshake with {} timed out, closing the connection",
+                    endpoint.get_target_addr()
+                );
+                endpoint.shutdown();
+                return false;
+            }
+            true
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn test_handshake_slots() {
+        let running: SharedRunningHandshakes = Default::default();
+        let ip = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let other_ip = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+
+        // limited per IP
+        let first = HandshakeSlot::acquire(&running, ip, 3, 2).unwrap();
+        let _second = HandshakeSlot::acquire(&running, ip, 3, 2).unwrap();
+        assert!(HandshakeSlot::acquire(&running, ip, 3, 2).is_err());
+
+        // and in total
+        let third = HandshakeSlot::acquire(&running, other_ip, 3, 2).unwrap();
+        assert!(HandshakeSlot::acquire(&running, other_ip, 3, 2).is_err());
+
+        // the slots are released when the handshakes end
+        drop(first);
+        assert!(HandshakeSlot::acquire(&running, ip, 3, 2).is_ok());
+        assert_eq!(running.lock().total, 2);
+        assert_eq!(running.lock().per_ip[&ip], 1);
+
+        // an IPv4 address mapped in IPv6 is the same IP
+        let mapped = IpAddr::V6(Ipv4Addr::new(10, 0, 0, 2).to_ipv6_mapped());
+        drop(third);
+        let _mapped = HandshakeSlot::acquire(&running, mapped, 3, 1).unwrap();
+        assert!(HandshakeSlot::acquire(&running, other_ip, 3, 1).is_err());
+    }
+}
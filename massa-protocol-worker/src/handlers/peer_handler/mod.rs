@@ -1,18 +1,26 @@
 use std::cmp::Reverse;
 use std::net::IpAddr;
-use std::{collections::HashMap, net::SocketAddr, thread::JoinHandle, time::Duration};
+use std::sync::Arc;
+use std::{
+    collections::{HashMap, HashSet},
+    net::SocketAddr,
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
 
 use crossbeam::channel::tick;
 use crossbeam::select;
-use massa_channel::{receiver::MassaReceiver, sender::MassaSender};
-use massa_hash::Hash;
-use massa_models::config::SIGNATURE_DESER_SIZE;
+use massa_channel::{heartbeat::Heartbeat, receiver::MassaReceiver, sender::MassaSender};
+use massa_hash::{Hash, HASH_SIZE_BYTES};
+use massa_logging::massa_journal;
+use massa_models::config::{MAX_BLOCK_SIZE, SIGNATURE_DESER_SIZE};
 use massa_models::version::{VersionDeserializer, VersionSerializer};
 use massa_protocol_exports::{
     BootstrapPeers, PeerId, PeerIdDeserializer, PeerIdSerializer, ProtocolConfig,
 };
 use massa_serialization::{DeserializeError, Deserializer, Serializer};
 use massa_signature::Signature;
+use massa_time::MassaTime;
 use peernet::context::Context as _;
 use peernet::messages::MessagesSerializer as _;
 use rand::{rngs::StdRng, RngCore, SeedableRng};
@@ -23,38 +31,49 @@ use peernet::{
     peer::InitConnectionHandler,
     transports::{endpoint::Endpoint, TransportType},
 };
-use tracing::log::{debug, error, info, warn};
+use tracing::{debug, error, field, info, info_span, warn};
 
 use crate::context::Context;
+use crate::handlers::log_error;
 use crate::handlers::peer_handler::models::PeerState;
 use crate::messages::{Message, MessagesHandler, MessagesSerializer};
+use crate::peer_stats::{note_config_mismatch, note_invalid_message, rotate_peer_stats};
 use crate::wrap_network::ActiveConnectionsTrait;
 
 use self::models::PeerInfo;
 use self::{
     models::{
-        InitialPeers, PeerManagementChannel, PeerManagementCmd, PeerMessageTuple, SharedPeerDB,
+        ban_immune_peers, read_initial_peers, reload_initial_peers, InitialPeers,
+        PeerManagementChannel, PeerManagementCmd, PeerMessageTuple, SharedPeerDB,
+        RECONNECT_INTERVAL,
     },
     tester::Tester,
 };
 
 use self::{
     announcement::{
-        Announcement, AnnouncementDeserializer, AnnouncementDeserializerArgs,
-        AnnouncementSerializer,
+        AnnouncementDeserializer, AnnouncementDeserializerArgs, ANNOUNCEMENT_WITH_KEY_ROTATION_ID,
     },
-    messages::{PeerManagementMessageDeserializer, PeerManagementMessageDeserializerArgs},
+    handshake_limits::{HandshakeSlot, HandshakeWatchdog, SharedRunningHandshakes},
+    rotation::KeyRotation,
 };
 
 /// This file contains the definition of the peer management handler
 /// This handler is here to check that announcements we receive are valid and
 /// that all the endpoints we received are active.
 mod announcement;
+mod handshake_limits;
 mod messages;
 pub mod models;
+pub mod rotation;
 mod tester;
 
-pub(crate) use messages::{PeerManagementMessage, PeerManagementMessageSerializer};
+pub(crate) use announcement::{Announcement, AnnouncementSerializer};
+pub(crate) use messages::{
+    MessageTypeId as PeerManagementMessageTypeId, PeerManagementMessage,
+    PeerManagementMessageDeserializer, PeerManagementMessageDeserializerArgs,
+    PeerManagementMessageSerializer,
+};
 
 pub struct PeerManagementHandler {
     pub peer_db: SharedPeerDB,
@@ -84,6 +103,7 @@ impl PeerManagementHandler {
         config: &ProtocolConfig,
     ) -> Self {
         let message_serializer = PeerManagementMessageSerializer::new();
+        let peer_stats = messages_handler.peer_stats.clone();
 
         let ((test_sender, test_receiver), testers) = Tester::run(
             config,
@@ -98,6 +118,16 @@ impl PeerManagementHandler {
         .name("protocol-peer-handler".to_string())
         .spawn({
             let peer_db = peer_db.clone();
+            let mut initial_peers = initial_peers.clone();
+            // peers of the initial peers file, replaced when it is reloaded
+            let (mut file_peers, mut immune_file_peers) = match read_initial_peers(&config.initial_peers) {
+                Ok(peers) => (
+                    peers.keys().cloned().collect::<HashSet<PeerId>>(),
+                    ban_immune_peers(&config.peers_categories, &peers),
+                ),
+                Err(_) => Default::default(),
+            };
+            let mut last_reconnect: Option<Instant> = None;
             let ticker = tick(Duration::from_secs(10));
             let config = config.clone();
             let message_serializer = MessagesSerializer::new()
@@ -108,7 +138,9 @@ impl PeerManagementHandler {
                     max_listeners_per_peer: config.max_size_listeners_per_peer,
                 });
             move || {
+                let heartbeat = Heartbeat::new("protocol-peer-handler");
                 loop {
+                    heartbeat.beat();
                     select! {
                         recv(ticker) -> _ => {
                             let peers_to_send = peer_db.read().get_rand_peers_to_send(100);
@@ -119,9 +151,9 @@ impl PeerManagementHandler {
                             let msg = PeerManagementMessage::ListPeers(peers_to_send);
 
                             for peer_id in &active_connections.get_peer_ids_connected() {
-                                if let Err(e) = active_connections
+                                if let Err(err) = active_connections
                                     .send_to_peer(peer_id, &message_serializer, msg.clone().into(), false) {
-                                    error!("error sending ListPeers message to peer: {:?}", e);
+                                    log_error(&err, &format!("sending ListPeers message to peer {}", peer_id));
                                }
                             }
                         }
@@ -130,13 +162,32 @@ impl PeerManagementHandler {
                             // internal command
                            match cmd {
                              Ok(PeerManagementCmd::Ban(peer_ids)) => {
+                                let peers_connected = active_connections.get_peers_connected();
                                 // remove running handshake ?
                                 for peer_id in peer_ids {
+                                    let ban_immune = match peers_connected.get(&peer_id) {
+                                        Some((_, _, category)) => category
+                                            .as_ref()
+                                            .and_then(|category| config.peers_categories.get(category))
+                                            .map_or(false, |category_info| category_info.ban_immune),
+                                        // not connected: only trust the categories of the initial peers file
+                                        None => peer_db.read().is_one_of(&peer_id, &immune_file_peers),
+                                    };
+                                    if ban_immune {
+                                        info!("Not banning peer {:?}: its category is ban-immune", peer_id);
+                                        continue;
+                                    }
                                     active_connections.shutdown_connection(&peer_id);
 
                                     // update peer_db
                                     peer_db.write().ban_peer(&peer_id);
                                 }
+                            },
+                             Ok(PeerManagementCmd::ForceBan(peer_ids)) => {
+                                for peer_id in peer_ids {
+                                    active_connections.shutdown_connection(&peer_id);
+                                    peer_db.write().ban_peer(&peer_id);
+                                }
                             },
                              Ok(PeerManagementCmd::Unban(peer_ids)) => {
                                 for peer_id in peer_ids {
@@ -156,6 +207,60 @@ impl PeerManagementHandler {
                                     warn!("error sending bootstrap peers: {:?}", err);
                                 }
                              },
+                             Ok(PeerManagementCmd::ReloadInitialPeers) => {
+                                match read_initial_peers(&config.initial_peers) {
+                                    Ok(reloaded) => {
+                                        info!("Testing the {} peers of the initial peers file", reloaded.len());
+                                        // tested again when the node is isolated
+                                        reload_initial_peers(&mut initial_peers, &mut file_peers, &reloaded);
+                                        immune_file_peers = ban_immune_peers(&config.peers_categories, &reloaded);
+                                        for (peer_id, data) in reloaded {
+                                            // an initial peer may have rotated its key since the file was written
+                                            let peer_id = peer_db.read().current_peer_id(&peer_id);
+                                            if let Err(e) = test_sender.try_send((peer_id, data.listeners)) {
+                                                debug!("error when sending msg to peer tester : {}", e);
+                                            }
+                                        }
+                                    }
+                                    Err(err) => warn!("could not read the initial peers file: {}", err),
+                                }
+                             },
+                             Ok(PeerManagementCmd::GetKnownPeers { responder }) => {
+                                let peers = peer_db.read().get_known_peers();
+                                if let Err(err) = responder.try_send(peers) {
+                                    warn!("error sending known peers: {:?}", err);
+                                }
+                             },
+                             Ok(PeerManagementCmd::TestPeers(peers)) => {
+                                info!("Testing {} imported peers", peers.len());
+                                for (peer_id, listeners) in peers {
+                                    if let Err(e) = test_sender.try_send((peer_id, listeners)) {
+                                        debug!("error when sending msg to peer tester : {}", e);
+                                    }
+                                }
+                             },
+                             Ok(PeerManagementCmd::Reconnect) => {
+                                // the connectivity thread asks at each connection try while the node is isolated
+                                if last_reconnect.map_or(false, |last| last.elapsed() < RECONNECT_INTERVAL) {
+                                    continue;
+                                }
+                                last_reconnect = Some(Instant::now());
+                                let peers_to_test = {
+                                    let mut peer_db_write = peer_db.write();
+                                    let peers_to_test = peer_db_write.peers_to_reconnect(
+                                        &initial_peers,
+                                        config.max_size_channel_commands_peer_testers,
+                                    );
+                                    peer_db_write.reset_cooldowns(&peers_to_test);
+                                    peers_to_test
+                                };
+                                debug!("Isolated: testing {} peers again", peers_to_test.len());
+                                for (peer_id, listeners) in peers_to_test {
+                                    if let Err(e) = test_sender.try_send((peer_id, listeners)) {
+                                        debug!("error when sending msg to peer tester : {}", e);
+                                    }
+                                }
+                             },
                              Ok(PeerManagementCmd::Stop) => {
                                 while let Ok(_msg) = test_receiver.try_recv() {
                                     // nothing to do just clean the channel
@@ -187,11 +292,13 @@ impl PeerManagementHandler {
                                 Ok((rest, message)) => (rest, message),
                                 Err(e) => {
                                     warn!("error when deserializing message: {:?}", e);
+                                    note_invalid_message(&peer_stats, &peer_id);
                                     continue;
                                 }
                             };
                             if !rest.is_empty() {
                                 warn!("message not fully deserialized");
+                                note_invalid_message(&peer_stats, &peer_id);
                                 continue;
                             }
                             match message {
@@ -265,6 +372,28 @@ pub struct MassaHandshake {
     peer_id_serializer: PeerIdSerializer,
     peer_id_deserializer: PeerIdDeserializer,
     message_handlers: MessagesHandler,
+    /// announced to peers if our key replaced a previous one
+    key_rotation: Option<KeyRotation>,
+    /// handshakes in progress, shared by the clones of the handshake handler
+    running_handshakes: SharedRunningHandshakes,
+    /// ends the handshakes running for too long
+    watchdog: HandshakeWatchdog,
+    /// hash of our network parameters, compared to the one of the peers
+    config_hash: Hash,
+}
+
+/// Marks the hash of the network parameters at the end of the handshake
+const CONFIG_HASH_TAG: u8 = 0xFF;
+
+/// Hash of the parameters that must be the same on all the nodes of a network.
+/// Peers announcing a different one run a misconfigured build.
+fn compute_config_hash(config: &ProtocolConfig) -> Hash {
+    let mut data = Vec::new();
+    data.push(config.thread_count);
+    data.extend(config.t0.as_millis().to_be_bytes());
+    data.extend(config.genesis_timestamp.as_millis().to_be_bytes());
+    data.extend(MAX_BLOCK_SIZE.to_be_bytes());
+    Hash::compute_from(&data)
 }
 
 impl MassaHandshake {
@@ -272,7 +401,9 @@ impl MassaHandshake {
         peer_db: SharedPeerDB,
         config: ProtocolConfig,
         message_handlers: MessagesHandler,
+        key_rotation: Option<KeyRotation>,
     ) -> Self {
+        let config_hash = compute_config_hash(&config);
         Self {
             peer_db,
             announcement_serializer: AnnouncementSerializer::new(),
@@ -289,6 +420,10 @@ impl MassaHandshake {
             peer_mngt_msg_serializer: MessagesSerializer::new()
                 .with_peer_management_message_serializer(PeerManagementMessageSerializer::new()),
             message_handlers,
+            key_rotation,
+            running_handshakes: Default::default(),
+            watchdog: HandshakeWatchdog::start(config.handshake_timeout.to_duration()),
+            config_hash,
         }
     }
 }
@@ -301,6 +436,31 @@ impl InitConnectionHandler<PeerId, Context, MessagesHandler> for MassaHandshake
         listeners: &HashMap<SocketAddr, TransportType>,
         messages_handler: MessagesHandler,
     ) -> PeerNetResult<PeerId> {
+        let span = info_span!(
+            "handshake",
+            remote_addr = %endpoint.get_target_addr(),
+            peer_id = field::Empty
+        );
+        let _enter = span.enter();
+        // refuse the connections beyond the limits instead of letting stalled peers pile them up,
+        // see `handshake_limits`
+        let _slot = HandshakeSlot::acquire(
+            &self.running_handshakes,
+            endpoint.get_target_addr().ip(),
+            self.config.max_concurrent_handshakes,
+            self.config.max_handshakes_per_ip,
+        )
+        .map_err(|reason| {
+            debug!("refusing the connection: {}", reason);
+            PeerNetError::HandshakeError.error("Massa Handshake", Some(reason.to_string()))
+        })?;
+        let _watch = match endpoint.try_clone() {
+            Ok(watched_endpoint) => Some(self.watchdog.watch(watched_endpoint)),
+            Err(err) => {
+                debug!("the handshake can't be timed out: {}", err);
+                None
+            }
+        };
         let mut bytes = vec![];
         self.peer_id_serializer
             .serialize(&context.get_peer_id(), &mut bytes)
@@ -318,11 +478,17 @@ impl InitConnectionHandler<PeerId, Context, MessagesHandler> for MassaHandshake
                     Some(format!("Failed to serialize version: {}", err)),
                 )
             })?;
-        bytes.push(0);
+        // a key rotation is announced with another id, so that it is refused by the peers not supporting it
+        bytes.push(if self.key_rotation.is_some() {
+            ANNOUNCEMENT_WITH_KEY_ROTATION_ID
+        } else {
+            0
+        });
         let listeners_announcement = Announcement::new(
             listeners.clone(),
             self.config.routable_ip,
-            &context.our_keypair,
+            self.key_rotation.clone(),
+            context,
         )
         .unwrap();
         self.announcement_serializer
@@ -333,6 +499,9 @@ impl InitConnectionHandler<PeerId, Context, MessagesHandler> for MassaHandshake
                     Some(format!("Failed to serialize announcement: {}", err)),
                 )
             })?;
+        // appended after the announcement, so that it is ignored by the peers not supporting it
+        bytes.push(CONFIG_HASH_TAG);
+        bytes.extend(self.config_hash.to_bytes());
         endpoint.send::<PeerId>(&bytes)?;
         let received = endpoint.receive::<PeerId>()?;
         if received.len() < 32 {
@@ -350,6 +519,7 @@ impl InitConnectionHandler<PeerId, Context, MessagesHandler> for MassaHandshake
                     Some(format!("Failed to deserialize peer id: {}", err)),
                 )
             })?;
+        span.record("peer_id", field::display(&peer_id));
         {
             let peer_db_read = self.peer_db.read();
             if let Some(info) = peer_db_read.peers.get(&peer_id) {
@@ -389,22 +559,25 @@ impl InitConnectionHandler<PeerId, Context, MessagesHandler> for MassaHandshake
                 PeerNetError::HandshakeError
                     .error("Massa Handshake", Some("Failed to get id".to_string())),
             )?;
-            match id {
-                0 => {
-                    let (_, announcement) = self
-                        .announcement_deserializer
-                        .deserialize::<DeserializeError>(
-                            received.get(1..).ok_or(PeerNetError::HandshakeError.error(
-                                "Massa Handshake",
-                                Some("Failed to get data".to_string()),
-                            ))?,
+            match *id {
+                0 | ANNOUNCEMENT_WITH_KEY_ROTATION_ID => {
+                    let data = received.get(1..).ok_or(
+                        PeerNetError::HandshakeError
+                            .error("Massa Handshake", Some("Failed to get data".to_string())),
+                    )?;
+                    let (rest, announcement) = if *id == ANNOUNCEMENT_WITH_KEY_ROTATION_ID {
+                        self.announcement_deserializer
+                            .deserialize_with_key_rotation::<DeserializeError>(data)
+                    } else {
+                        self.announcement_deserializer
+                            .deserialize::<DeserializeError>(data)
+                    }
+                    .map_err(|err| {
+                        PeerNetError::HandshakeError.error(
+                            "Massa Handshake",
+                            Some(format!("Failed to deserialize announcement: {}", err)),
                         )
-                        .map_err(|err| {
-                            PeerNetError::HandshakeError.error(
-                                "Massa Handshake",
-                                Some(format!("Failed to deserialize announcement: {}", err)),
-                            )
-                        })?;
+                    })?;
                     if peer_id
                         .verify_signature(&announcement.hash, &announcement.signature)
                         .is_err()
@@ -412,6 +585,47 @@ impl InitConnectionHandler<PeerId, Context, MessagesHandler> for MassaHandshake
                         return Err(PeerNetError::HandshakeError
                             .error("Massa Handshake", Some("Invalid signature".to_string())));
                     }
+                    // the rotation is covered by the signature of the announcement checked above
+                    if let Some(key_rotation) = &announcement.key_rotation {
+                        key_rotation.verify(&peer_id).map_err(|err| {
+                            PeerNetError::HandshakeError.error(
+                                "Massa Handshake",
+                                Some(format!("Invalid key rotation: {}", err)),
+                            )
+                        })?;
+                        let rotated = self.peer_db.write().rotate_peer(
+                            &key_rotation.previous_peer_id,
+                            &peer_id,
+                            &announcement,
+                        );
+                        rotate_peer_stats(
+                            &self.message_handlers.peer_stats,
+                            &key_rotation.previous_peer_id,
+                            &peer_id,
+                        );
+                        if !rotated {
+                            return Err(PeerNetError::HandshakeError.error(
+                                "Massa Handshake",
+                                Some("Rotated key of a banned peer".to_string()),
+                            ));
+                        }
+                    }
+                    // older peers do not announce the hash of their parameters
+                    if let Some(config_hash) = rest
+                        .strip_prefix(&[CONFIG_HASH_TAG])
+                        .and_then(|rest| rest.get(..HASH_SIZE_BYTES))
+                        .and_then(|bytes| bytes.try_into().ok())
+                    {
+                        let config_hash = Hash::from_bytes(config_hash);
+                        if config_hash != self.config_hash {
+                            warn!(
+                                "peer {} runs with different network parameters (t0, thread count, genesis timestamp or max block size)",
+                                peer_id
+                            );
+                            massa_journal!("config_mismatch", { "peer_id": peer_id.to_string() });
+                            note_config_mismatch(&self.message_handlers.peer_stats, &peer_id);
+                        }
+                    }
                     let message = PeerManagementMessage::NewPeerConnected((
                         peer_id.clone(),
                         announcement.clone().listeners,
@@ -448,13 +662,12 @@ impl InitConnectionHandler<PeerId, Context, MessagesHandler> for MassaHandshake
 
                     // sign their random bytes
                     let other_random_hash = Hash::compute_from(other_random_bytes);
-                    let self_signature =
-                        context.our_keypair.sign(&other_random_hash).map_err(|_| {
-                            PeerNetError::HandshakeError.error(
-                                "Massa Handshake",
-                                Some("Failed to sign random bytes".to_string()),
-                            )
-                        })?;
+                    let self_signature = context.sign(&other_random_hash).map_err(|err| {
+                        PeerNetError::HandshakeError.error(
+                            "Massa Handshake",
+                            Some(format!("Failed to sign random bytes: {}", err)),
+                        )
+                    })?;
 
                     let mut bytes = [0u8; SIGNATURE_DESER_SIZE];
                     bytes.copy_from_slice(&self_signature.to_bytes());
@@ -494,6 +707,7 @@ impl InitConnectionHandler<PeerId, Context, MessagesHandler> for MassaHandshake
             }
         };
         {
+            let now = MassaTime::now().expect("could not get the current time");
             let mut peer_db_write = self.peer_db.write();
             // if handshake failed, we set the peer state to HandshakeFailed
             match &res {
@@ -521,6 +735,7 @@ impl InitConnectionHandler<PeerId, Context, MessagesHandler> for MassaHandshake
                         });
                 }
                 Ok((_peer_id, None)) => {
+                    peer_db_write.note_handshake_failure(&peer_id, now);
                     peer_db_write.peers.entry(peer_id).and_modify(|info| {
                         //TODO: Add the peerdb but for now impossible as we don't have announcement and we need one to place in peerdb
                         info.state = PeerState::HandshakeFailed;
@@ -531,9 +746,13 @@ impl InitConnectionHandler<PeerId, Context, MessagesHandler> for MassaHandshake
                     ));
                 }
                 Err(_) => {
+                    peer_db_write.note_handshake_failure(&peer_id, now);
                     peer_db_write.peers.entry(peer_id).and_modify(|info| {
                         //TODO: Add the peerdb but for now impossible as we don't have announcement and we need one to place in peerdb
-                        info.state = PeerState::HandshakeFailed;
+                        // keep the bans, including the ones inherited through a key rotation
+                        if info.state != PeerState::Banned {
+                            info.state = PeerState::HandshakeFailed;
+                        }
                     });
                 }
             }
@@ -606,3 +825,89 @@ impl InitConnectionHandler<PeerId, Context, MessagesHandler> for MassaHandshake
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::byzantine::{write_frame, ByzantinePeer, MalformedHandshake};
+    use massa_channel::MassaChannel;
+    use massa_serialization::U64VarIntDeserializer;
+    use std::net::TcpListener;
+    use std::ops::Bound::Included;
+
+    /// Run the handshake of a tester against a peer answering `handshake`
+    fn test_handshake(
+        peer: &ByzantinePeer,
+        malformed: Option<MalformedHandshake>,
+        peer_db: &SharedPeerDB,
+    ) -> PeerNetResult<PeerId> {
+        let config = ProtocolConfig::default();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handshake = peer.handshake(config.version, malformed).unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let _ = write_frame(
+                &mut stream,
+                &handshake,
+                malformed == Some(MalformedHandshake::OversizedLength),
+            );
+        });
+        let (sender_blocks, _) = MassaChannel::new("blocks".to_string(), None);
+        let (sender_endorsements, _) = MassaChannel::new("endorsements".to_string(), None);
+        let (sender_operations, _) = MassaChannel::new("operations".to_string(), None);
+        let (sender_peers, _) = MassaChannel::new("peers".to_string(), None);
+        let result = Tester::tcp_handshake(
+            MessagesHandler {
+                id_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
+                sender_blocks,
+                sender_endorsements,
+                sender_operations,
+                sender_peers,
+                recorder: None,
+                chaos: None,
+                peer_stats: Default::default(),
+                rate_limiter: Default::default(),
+            },
+            peer_db.clone(),
+            AnnouncementDeserializer::new(AnnouncementDeserializerArgs {
+                max_listeners: config.max_size_listeners_per_peer,
+            }),
+            VersionDeserializer::new(),
+            PeerIdDeserializer::new(),
+            addr,
+            config.version,
+        );
+        server.join().unwrap();
+        result
+    }
+
+    #[test]
+    fn test_tester_handshake() {
+        let peer = ByzantinePeer::new();
+        let peer_db: SharedPeerDB = Default::default();
+        assert_eq!(test_handshake(&peer, None, &peer_db).unwrap(), peer.peer_id);
+        assert_eq!(
+            peer_db.read().peers[&peer.peer_id].state,
+            PeerState::Trusted
+        );
+    }
+
+    #[test]
+    fn test_tester_refuses_malformed_handshakes() {
+        for malformed in MalformedHandshake::ALL {
+            let peer = ByzantinePeer::new();
+            let peer_db: SharedPeerDB = Default::default();
+            assert!(
+                test_handshake(&peer, Some(malformed), &peer_db).is_err(),
+                "{:?} handshake accepted",
+                malformed
+            );
+            assert!(
+                !peer_db.read().peers.contains_key(&peer.peer_id),
+                "{:?} handshake added the peer",
+                malformed
+            );
+        }
+    }
+}
@@ -1,21 +1,73 @@
 use massa_channel::sender::MassaSender;
-use massa_protocol_exports::{BootstrapPeers, PeerId, ProtocolError};
+use massa_logging::massa_journal;
+use massa_protocol_exports::{
+    BootstrapPeers, KnownPeer, PeerCategoryInfo, PeerData, PeerId, ProtocolError,
+};
 use massa_time::MassaTime;
 use parking_lot::RwLock;
 use peernet::transports::TransportType;
 use rand::seq::SliceRandom;
 use std::cmp::Reverse;
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashSet};
+use std::path::Path;
 use std::time::Duration;
 use std::{collections::HashMap, net::SocketAddr, sync::Arc};
-use tracing::log::info;
+use tracing::info;
 
 use super::announcement::Announcement;
 
 const THREE_DAYS_MS: u64 = 3 * 24 * 60 * 60 * 1_000_000;
+/// Longest wait before dialing again a peer we failed to connect to
+const MAX_DIAL_COOLDOWN_MS: u64 = 10 * 60 * 1_000;
+/// The dial attempts of a peer that was not dialed for this long are forgotten:
+/// it is no longer one of the peers we try to connect to
+const DIAL_ATTEMPTS_TTL_MS: u64 = 6 * MAX_DIAL_COOLDOWN_MS;
+/// Shortest time between two rounds of tests of the initial and known peers while the node is isolated
+pub(crate) const RECONNECT_INTERVAL: Duration = Duration::from_secs(60);
 
 pub type InitialPeers = HashMap<PeerId, HashMap<SocketAddr, TransportType>>;
 
+/// Read the initial peers file
+pub(crate) fn read_initial_peers(path: &Path) -> Result<HashMap<PeerId, PeerData>, ProtocolError> {
+    Ok(serde_json::from_str::<HashMap<PeerId, PeerData>>(
+        &std::fs::read_to_string(path)?,
+    )?)
+}
+
+/// Replace the peers of the initial peers file in `initial_peers` by the `reloaded` ones,
+/// keeping the other initial peers (the bootstrap peers).
+/// `file_peers` holds the peer ids of the file, and is updated to the reloaded ones.
+pub(crate) fn reload_initial_peers(
+    initial_peers: &mut InitialPeers,
+    file_peers: &mut HashSet<PeerId>,
+    reloaded: &HashMap<PeerId, PeerData>,
+) {
+    initial_peers.retain(|peer_id, _| !file_peers.contains(peer_id));
+    file_peers.clear();
+    for (peer_id, data) in reloaded {
+        initial_peers.insert(peer_id.clone(), data.listeners.clone());
+        file_peers.insert(peer_id.clone());
+    }
+}
+
+/// Peers of the initial peers file whose category is ban-immune.
+/// The addresses announced by the other peers are not trusted for this, as any peer can announce
+/// the address of a ban-immune category.
+pub(crate) fn ban_immune_peers(
+    categories: &HashMap<String, PeerCategoryInfo>,
+    file_peers: &HashMap<PeerId, PeerData>,
+) -> HashSet<PeerId> {
+    file_peers
+        .iter()
+        .filter(|(_, data)| {
+            categories
+                .get(&data.category)
+                .map_or(false, |category_info| category_info.ban_immune)
+        })
+        .map(|(peer_id, _)| peer_id.clone())
+        .collect()
+}
+
 #[derive(Default)]
 pub struct PeerDB {
     pub peers: HashMap<PeerId, PeerInfo>,
@@ -23,6 +75,11 @@ pub struct PeerDB {
     pub index_by_newest: BTreeSet<(Reverse<u64>, PeerId)>,
     /// Tested addresses used to avoid testing the same address too often. //TODO: Need to be pruned
     pub tested_addresses: HashMap<SocketAddr, MassaTime>,
+    /// Peers we dialed, or that failed a handshake with us, without getting connected since:
+    /// number of attempts and time of the last one
+    pub dial_attempts: HashMap<PeerId, (u32, MassaTime)>,
+    /// Key rotations announced by the peers: new peer id of each previous one
+    pub rotated_peers: HashMap<PeerId, PeerId>,
 }
 
 pub type SharedPeerDB = Arc<RwLock<PeerDB>>;
@@ -46,11 +103,23 @@ pub enum PeerState {
 
 #[derive(Clone)]
 pub enum PeerManagementCmd {
+    /// Ban peers that misbehaved, except the ones of a ban-immune category
     Ban(Vec<PeerId>),
+    /// Ban peers on request of the node operator, whatever their category
+    ForceBan(Vec<PeerId>),
     Unban(Vec<PeerId>),
     GetBootstrapPeers {
         responder: MassaSender<BootstrapPeers>,
     },
+    ReloadInitialPeers,
+    GetKnownPeers {
+        responder: MassaSender<HashMap<PeerId, KnownPeer>>,
+    },
+    /// Test the given peers to add them to the known peers
+    TestPeers(InitialPeers),
+    /// The node is isolated: test the initial peers and the known peers again, without cooldown.
+    /// Done at most once every `RECONNECT_INTERVAL`.
+    Reconnect,
     Stop,
 }
 
@@ -64,21 +133,178 @@ impl PeerDB {
         println!("peers: {:?}", self.peers);
         if let Some(peer) = self.peers.get_mut(peer_id) {
             peer.state = PeerState::Banned;
+            self.dial_attempts.remove(peer_id);
             info!("Banned peer: {:?}", peer_id);
+            massa_journal!("peer_banned", { "peer_id": peer_id.to_string() });
         } else {
             info!("Tried to ban unknown peer: {:?}", peer_id);
         };
     }
 
+    /// Moves what we know about `previous_peer_id` to `new_peer_id` after a key rotation:
+    /// its state, including a ban, and its dial attempts.
+    /// The categories of the peers are given by their IP, so they are kept as long as the peer keeps its IP.
+    /// Returns false if the previous or the new peer id is banned: the ban is then kept on the new one.
+    pub fn rotate_peer(
+        &mut self,
+        previous_peer_id: &PeerId,
+        new_peer_id: &PeerId,
+        announcement: &Announcement,
+    ) -> bool {
+        if let Some(new_info) = self.peers.get(new_peer_id) {
+            if new_info.state == PeerState::Banned {
+                return false;
+            }
+        }
+        self.rotated_peers
+            .insert(previous_peer_id.clone(), new_peer_id.clone());
+        self.index_by_newest
+            .retain(|(_, peer_id)| peer_id != previous_peer_id);
+        if let Some(attempts) = self.dial_attempts.remove(previous_peer_id) {
+            self.dial_attempts.insert(new_peer_id.clone(), attempts);
+        }
+        let Some(previous_info) = self.peers.remove(previous_peer_id) else {
+            return true;
+        };
+        info!(
+            "Peer {:?} rotated its key to {:?}",
+            previous_peer_id, new_peer_id
+        );
+        let banned = previous_info.state == PeerState::Banned;
+        self.peers.insert(
+            new_peer_id.clone(),
+            PeerInfo {
+                last_announce: announcement.clone(),
+                state: previous_info.state,
+            },
+        );
+        !banned
+    }
+
+    /// Current peer id of `peer_id`, following the key rotations it announced
+    pub fn current_peer_id(&self, peer_id: &PeerId) -> PeerId {
+        let mut current = peer_id;
+        // bounded in case of a rotation cycle
+        for _ in 0..self.rotated_peers.len() {
+            match self.rotated_peers.get(current) {
+                Some(next) => current = next,
+                None => break,
+            }
+        }
+        current.clone()
+    }
+
+    /// Whether `peer_id` is one of `peers`, or the peer id one of them rotated its key to
+    pub fn is_one_of(&self, peer_id: &PeerId, peers: &HashSet<PeerId>) -> bool {
+        peers.contains(peer_id)
+            || peers
+                .iter()
+                .any(|peer| &self.current_peer_id(peer) == peer_id)
+    }
+
     pub fn unban_peer(&mut self, peer_id: &PeerId) {
         if self.peers.contains_key(peer_id) {
             self.peers.remove(peer_id);
+            self.dial_attempts.remove(peer_id);
             info!("Unbanned peer: {:?}", peer_id);
         } else {
             info!("Tried to unban unknown peer: {:?}", peer_id);
         };
     }
 
+    /// Note that we dialed `peer_id` at `now`
+    pub fn note_dial_attempt(&mut self, peer_id: &PeerId, now: MassaTime) {
+        let attempts = self
+            .dial_attempts
+            .entry(peer_id.clone())
+            .or_insert((0, now));
+        *attempts = (attempts.0.saturating_add(1), now);
+    }
+
+    /// Note that the handshake with `peer_id` failed at `now`, whoever dialed.
+    /// After a dial of ours it counts as a second attempt: the peer is reachable but refuses us,
+    /// or runs an incompatible version, so it is dialed again even later.
+    pub fn note_handshake_failure(&mut self, peer_id: &PeerId, now: MassaTime) {
+        self.note_dial_attempt(peer_id, now);
+    }
+
+    /// Forget the dial attempts of the peers we are connected to
+    pub fn clear_dial_attempts<'a>(&mut self, connected: impl Iterator<Item = &'a PeerId>) {
+        for peer_id in connected {
+            self.dial_attempts.remove(peer_id);
+        }
+    }
+
+    /// Forget the dial attempts of the peers that are unknown, banned,
+    /// or that were not dialed for `DIAL_ATTEMPTS_TTL_MS`
+    pub fn prune_dial_attempts(&mut self, now: MassaTime) {
+        let peers = &self.peers;
+        self.dial_attempts.retain(|peer_id, (_, last_attempt)| {
+            peers
+                .get(peer_id)
+                .map_or(false, |peer| peer.state != PeerState::Banned)
+                && now.saturating_sub(*last_attempt).to_millis() < DIAL_ATTEMPTS_TTL_MS
+        });
+    }
+
+    /// Returns true if `peer_id` was dialed too recently to be dialed again at `now`.
+    /// The wait starts at `base_cooldown` and doubles with each failed attempt, up to `MAX_DIAL_COOLDOWN_MS`.
+    pub fn is_dial_cooling_down(
+        &self,
+        peer_id: &PeerId,
+        base_cooldown: MassaTime,
+        now: MassaTime,
+    ) -> bool {
+        let Some((attempts, last_attempt)) = self.dial_attempts.get(peer_id) else {
+            return false;
+        };
+        let factor = 1u64 << attempts.saturating_sub(1).min(16);
+        let cooldown = base_cooldown
+            .to_millis()
+            .saturating_mul(factor)
+            .min(MAX_DIAL_COOLDOWN_MS);
+        now.saturating_sub(*last_attempt).to_millis() < cooldown
+    }
+
+    /// Peers to test again when the node is isolated: the `initial_peers`, following their key
+    /// rotations, then at most `max_known` known peers that are not banned, the most recently
+    /// announced first.
+    pub fn peers_to_reconnect(
+        &self,
+        initial_peers: &InitialPeers,
+        max_known: usize,
+    ) -> InitialPeers {
+        let mut peers: InitialPeers = initial_peers
+            .iter()
+            .map(|(peer_id, listeners)| (self.current_peer_id(peer_id), listeners.clone()))
+            .collect();
+        let mut known: Vec<(&PeerId, &PeerInfo)> = self
+            .peers
+            .iter()
+            .filter(|(peer_id, peer)| {
+                peer.state != PeerState::Banned
+                    && !peer.last_announce.listeners.is_empty()
+                    && !peers.contains_key(*peer_id)
+            })
+            .collect();
+        known.sort_unstable_by_key(|(_, peer)| Reverse(peer.last_announce.timestamp));
+        for (peer_id, peer) in known.into_iter().take(max_known) {
+            peers.insert(peer_id.clone(), peer.last_announce.listeners.clone());
+        }
+        peers
+    }
+
+    /// Forget when `peers` were last tested, and all the dial attempts,
+    /// so that the peers are tested and dialed again without waiting for their cooldown
+    pub fn reset_cooldowns(&mut self, peers: &InitialPeers) {
+        for listeners in peers.values() {
+            for addr in listeners.keys() {
+                self.tested_addresses.remove(addr);
+            }
+        }
+        self.dial_attempts.clear();
+    }
+
     /// Retrieve the peer with the oldest test date.
     pub fn get_oldest_peer(&self, cooldown: Duration) -> Option<SocketAddr> {
         match self
@@ -137,6 +363,26 @@ impl PeerDB {
         result
     }
 
+    /// Export the peers that are not banned and that we can dial
+    pub fn get_known_peers(&self) -> HashMap<PeerId, KnownPeer> {
+        self.peers
+            .iter()
+            .filter(|(_, peer)| {
+                peer.state != PeerState::Banned && !peer.last_announce.listeners.is_empty()
+            })
+            .map(|(peer_id, peer)| {
+                (
+                    peer_id.clone(),
+                    KnownPeer {
+                        listeners: peer.last_announce.listeners.clone(),
+                        last_announce: peer.last_announce.timestamp,
+                        trusted: peer.state == PeerState::Trusted,
+                    },
+                )
+            })
+            .collect()
+    }
+
     pub fn get_banned_peer_count(&self) -> u64 {
         self.peers
             .values()
@@ -149,3 +395,297 @@ impl PeerDB {
         unimplemented!()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::Context;
+    use massa_signature::KeyPair;
+
+    #[test]
+    fn test_dial_cooldown() {
+        let mut db = PeerDB::default();
+        let peer_id = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+        let base = MassaTime::from_millis(1_000);
+        let start = MassaTime::from_millis(1_000_000);
+        assert!(!db.is_dial_cooling_down(&peer_id, base, start));
+
+        // the wait doubles with each failed attempt
+        db.note_dial_attempt(&peer_id, start);
+        assert!(db.is_dial_cooling_down(
+            &peer_id,
+            base,
+            start.saturating_add(MassaTime::from_millis(999))
+        ));
+        assert!(!db.is_dial_cooling_down(&peer_id, base, start.saturating_add(base)));
+        db.note_dial_attempt(&peer_id, start);
+        assert!(db.is_dial_cooling_down(&peer_id, base, start.saturating_add(base)));
+        assert!(!db.is_dial_cooling_down(
+            &peer_id,
+            base,
+            start.saturating_add(MassaTime::from_millis(2_000))
+        ));
+
+        // up to the maximum wait
+        for _ in 0..40 {
+            db.note_dial_attempt(&peer_id, start);
+        }
+        let max_wait = MassaTime::from_millis(MAX_DIAL_COOLDOWN_MS);
+        assert!(!db.is_dial_cooling_down(&peer_id, base, start.saturating_add(max_wait)));
+
+        // connecting resets the attempts
+        db.clear_dial_attempts([peer_id.clone()].iter());
+        assert!(!db.is_dial_cooling_down(&peer_id, base, start));
+
+        // a dial ending in a failed handshake counts twice
+        db.note_dial_attempt(&peer_id, start);
+        db.note_handshake_failure(&peer_id, start);
+        assert_eq!(db.dial_attempts[&peer_id].0, 2);
+        assert!(db.is_dial_cooling_down(&peer_id, base, start.saturating_add(base)));
+    }
+
+    #[test]
+    fn test_prune_dial_attempts() {
+        let mut db = PeerDB::default();
+        let keypair = KeyPair::generate(0).unwrap();
+        let known = PeerId::from_public_key(keypair.get_public_key());
+        let banned_keypair = KeyPair::generate(0).unwrap();
+        let banned = PeerId::from_public_key(banned_keypair.get_public_key());
+        let unknown = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+        for (peer_id, keypair) in [(&known, &keypair), (&banned, &banned_keypair)] {
+            db.peers.insert(
+                peer_id.clone(),
+                PeerInfo {
+                    last_announce: announcement_of(keypair),
+                    state: PeerState::Trusted,
+                },
+            );
+        }
+        let start = MassaTime::from_millis(1_000_000);
+        for peer_id in [&known, &banned, &unknown] {
+            db.note_dial_attempt(peer_id, start);
+        }
+
+        // banning forgets the attempts, pruning forgets the ones of the unknown peers
+        db.ban_peer(&banned);
+        assert!(!db.dial_attempts.contains_key(&banned));
+        db.prune_dial_attempts(start);
+        assert_eq!(db.dial_attempts.len(), 1);
+        assert!(db.dial_attempts.contains_key(&known));
+
+        // and the ones of the peers not dialed for a while
+        db.prune_dial_attempts(
+            start.saturating_add(MassaTime::from_millis(DIAL_ATTEMPTS_TTL_MS - 1)),
+        );
+        assert!(db.dial_attempts.contains_key(&known));
+        db.prune_dial_attempts(start.saturating_add(MassaTime::from_millis(DIAL_ATTEMPTS_TTL_MS)));
+        assert!(db.dial_attempts.is_empty());
+    }
+
+    fn announcement_of(keypair: &KeyPair) -> Announcement {
+        let context = Context {
+            our_public_key: keypair.get_public_key(),
+            signer: Arc::new(keypair.clone()),
+        };
+        Announcement::new(HashMap::new(), None, None, &context).unwrap()
+    }
+
+    #[test]
+    fn test_rotate_peer() {
+        let mut db = PeerDB::default();
+        let previous_keypair = KeyPair::generate(0).unwrap();
+        let previous_peer_id = PeerId::from_public_key(previous_keypair.get_public_key());
+        let keypair = KeyPair::generate(0).unwrap();
+        let peer_id = PeerId::from_public_key(keypair.get_public_key());
+        db.peers.insert(
+            previous_peer_id.clone(),
+            PeerInfo {
+                last_announce: announcement_of(&previous_keypair),
+                state: PeerState::Trusted,
+            },
+        );
+        db.note_dial_attempt(&previous_peer_id, MassaTime::from_millis(1_000));
+
+        // the state and the dial attempts follow the key
+        assert!(db.rotate_peer(&previous_peer_id, &peer_id, &announcement_of(&keypair)));
+        assert!(!db.peers.contains_key(&previous_peer_id));
+        assert_eq!(db.peers[&peer_id].state, PeerState::Trusted);
+        assert_eq!(db.dial_attempts[&peer_id].0, 1);
+        assert!(!db.dial_attempts.contains_key(&previous_peer_id));
+        assert_eq!(db.current_peer_id(&previous_peer_id), peer_id);
+
+        // announcing the rotation again changes nothing
+        assert!(db.rotate_peer(&previous_peer_id, &peer_id, &announcement_of(&keypair)));
+        assert_eq!(db.peers[&peer_id].state, PeerState::Trusted);
+    }
+
+    #[test]
+    fn test_ban_immune_peers() {
+        let category = |ban_immune| PeerCategoryInfo {
+            allow_local_peers: false,
+            target_out_connections: 1,
+            max_in_connections: 1,
+            max_in_connections_per_ip: 1,
+            ban_immune,
+            max_bytes_received_per_second: 0,
+        };
+        let categories = HashMap::from([
+            ("Whitelisted".to_string(), category(true)),
+            ("Bootstrap".to_string(), category(false)),
+        ]);
+        let peer_data = |category: &str| PeerData {
+            listeners: HashMap::new(),
+            category: category.to_string(),
+        };
+        let keypair = KeyPair::generate(0).unwrap();
+        let whitelisted = PeerId::from_public_key(keypair.get_public_key());
+        let bootstrap = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+        let unknown_category =
+            PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+        let immune = ban_immune_peers(
+            &categories,
+            &HashMap::from([
+                (whitelisted.clone(), peer_data("Whitelisted")),
+                (bootstrap.clone(), peer_data("Bootstrap")),
+                (unknown_category.clone(), peer_data("Unknown")),
+            ]),
+        );
+        assert_eq!(immune, HashSet::from([whitelisted.clone()]));
+
+        // the immunity follows the key rotations of the peer
+        let mut db = PeerDB::default();
+        let rotated_keypair = KeyPair::generate(0).unwrap();
+        let rotated = PeerId::from_public_key(rotated_keypair.get_public_key());
+        assert!(db.is_one_of(&whitelisted, &immune));
+        assert!(!db.is_one_of(&rotated, &immune));
+        db.peers.insert(
+            whitelisted.clone(),
+            PeerInfo {
+                last_announce: announcement_of(&keypair),
+                state: PeerState::Trusted,
+            },
+        );
+        assert!(db.rotate_peer(&whitelisted, &rotated, &announcement_of(&rotated_keypair)));
+        assert!(db.is_one_of(&rotated, &immune));
+        assert!(!db.is_one_of(&bootstrap, &immune));
+    }
+
+    #[test]
+    fn test_rotate_banned_peer() {
+        let mut db = PeerDB::default();
+        let previous_keypair = KeyPair::generate(0).unwrap();
+        let previous_peer_id = PeerId::from_public_key(previous_keypair.get_public_key());
+        let keypair = KeyPair::generate(0).unwrap();
+        let peer_id = PeerId::from_public_key(keypair.get_public_key());
+        db.peers.insert(
+            previous_peer_id.clone(),
+            PeerInfo {
+                last_announce: announcement_of(&previous_keypair),
+                state: PeerState::Trusted,
+            },
+        );
+        db.ban_peer(&previous_peer_id);
+
+        // a banned peer can't escape its ban by rotating its key
+        assert!(!db.rotate_peer(&previous_peer_id, &peer_id, &announcement_of(&keypair)));
+        assert_eq!(db.peers[&peer_id].state, PeerState::Banned);
+        assert!(!db.peers.contains_key(&previous_peer_id));
+
+        // nor by rotating the key of a peer that is not banned to a banned one
+        let other_keypair = KeyPair::generate(0).unwrap();
+        let other_peer_id = PeerId::from_public_key(other_keypair.get_public_key());
+        db.peers.insert(
+            other_peer_id.clone(),
+            PeerInfo {
+                last_announce: announcement_of(&other_keypair),
+                state: PeerState::Trusted,
+            },
+        );
+        assert!(!db.rotate_peer(&other_peer_id, &peer_id, &announcement_of(&keypair)));
+        assert_eq!(db.peers[&peer_id].state, PeerState::Banned);
+        assert_eq!(db.peers[&other_peer_id].state, PeerState::Trusted);
+    }
+
+    fn listeners_of(port: u16) -> HashMap<SocketAddr, TransportType> {
+        HashMap::from([(SocketAddr::from(([127, 0, 0, 1], port)), TransportType::Tcp)])
+    }
+
+    fn known_peer(listeners: HashMap<SocketAddr, TransportType>, timestamp: u64) -> PeerInfo {
+        let mut last_announce = announcement_of(&KeyPair::generate(0).unwrap());
+        last_announce.listeners = listeners;
+        last_announce.timestamp = timestamp;
+        PeerInfo {
+            last_announce,
+            state: PeerState::Trusted,
+        }
+    }
+
+    #[test]
+    fn test_reload_initial_peers() {
+        let bootstrap_peer =
+            PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+        let removed_peer = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+        let added_peer = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+        let mut initial_peers = InitialPeers::from([
+            (bootstrap_peer.clone(), listeners_of(1)),
+            (removed_peer.clone(), listeners_of(2)),
+        ]);
+        let mut file_peers = HashSet::from([removed_peer.clone()]);
+        let reloaded = HashMap::from([(
+            added_peer.clone(),
+            PeerData {
+                listeners: listeners_of(3),
+                category: "Bootstrap".to_string(),
+            },
+        )]);
+
+        // the peers of the file are replaced, the bootstrap peers are kept
+        reload_initial_peers(&mut initial_peers, &mut file_peers, &reloaded);
+        assert_eq!(
+            initial_peers,
+            InitialPeers::from([
+                (bootstrap_peer, listeners_of(1)),
+                (added_peer.clone(), listeners_of(3)),
+            ])
+        );
+        assert_eq!(file_peers, HashSet::from([added_peer]));
+    }
+
+    #[test]
+    fn test_peers_to_reconnect() {
+        let mut db = PeerDB::default();
+        let initial_peer = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+        let initial_peers = InitialPeers::from([(initial_peer.clone(), listeners_of(1))]);
+        let mut known_peers = Vec::new();
+        for port in 2..6 {
+            let peer_id = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+            db.peers.insert(
+                peer_id.clone(),
+                known_peer(listeners_of(port), u64::from(port)),
+            );
+            known_peers.push(peer_id);
+        }
+        db.ban_peer(&known_peers[3]);
+
+        // the initial peers, then the most recently announced known peers that are not banned
+        let peers = db.peers_to_reconnect(&initial_peers, 2);
+        assert_eq!(peers.len(), 3);
+        assert!(peers.contains_key(&initial_peer));
+        assert!(peers.contains_key(&known_peers[2]));
+        assert!(peers.contains_key(&known_peers[1]));
+
+        // their test cooldown and the dial backoff of all the peers are reset
+        let now = MassaTime::from_millis(1_000_000);
+        for (peer_id, listeners) in &peers {
+            db.note_dial_attempt(peer_id, now);
+            for addr in listeners.keys() {
+                db.tested_addresses.insert(*addr, now);
+            }
+        }
+        db.note_dial_attempt(&known_peers[0], now);
+        db.reset_cooldowns(&peers);
+        assert!(db.tested_addresses.is_empty());
+        assert!(db.dial_attempts.is_empty());
+        assert!(!db.is_dial_cooling_down(&initial_peer, MassaTime::from_millis(1_000), now));
+    }
+}
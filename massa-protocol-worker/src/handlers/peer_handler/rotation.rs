@@ -0,0 +1,140 @@
+use massa_hash::Hash;
+use massa_protocol_exports::{PeerId, PeerIdDeserializer, PeerIdSerializer, ProtocolError};
+use massa_serialization::{Deserializer, SerializeError, Serializer};
+use massa_signature::{KeyPair, Signature, SignatureDeserializer};
+use nom::{
+    error::{context, ContextError, ParseError},
+    sequence::tuple,
+    IResult, Parser,
+};
+
+/// Domain separator of the hash signed by a key rotation
+const KEY_ROTATION_DOMAIN: &[u8] = b"massa-node-key-rotation";
+
+/// Record announcing that a node replaced its key: the previous key signs the new one,
+/// so that peers can move what they know about the previous peer id to the new one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyRotation {
+    /// Peer id of the replaced key
+    pub previous_peer_id: PeerId,
+    /// Signature of the new peer id by the previous key
+    pub signature: Signature,
+}
+
+impl KeyRotation {
+    /// Creates the record of the rotation from `previous_keypair` to `new_peer_id`
+    pub fn new(previous_keypair: &KeyPair, new_peer_id: &PeerId) -> Result<Self, ProtocolError> {
+        Ok(KeyRotation {
+            previous_peer_id: PeerId::from_public_key(previous_keypair.get_public_key()),
+            signature: previous_keypair
+                .sign(&Self::compute_hash(new_peer_id))
+                .map_err(|err| ProtocolError::GeneralProtocolError(err.to_string()))?,
+        })
+    }
+
+    /// Checks that the previous key signed the rotation to `new_peer_id`
+    pub fn verify(&self, new_peer_id: &PeerId) -> Result<(), ProtocolError> {
+        if &self.previous_peer_id == new_peer_id {
+            return Err(ProtocolError::InvalidKeyRotationError(
+                "key rotation to the same key".to_string(),
+            ));
+        }
+        self.previous_peer_id
+            .verify_signature(&Self::compute_hash(new_peer_id), &self.signature)
+    }
+
+    fn compute_hash(new_peer_id: &PeerId) -> Hash {
+        let mut data = KEY_ROTATION_DOMAIN.to_vec();
+        data.extend(new_peer_id.get_public_key().to_bytes());
+        Hash::compute_from(&data)
+    }
+}
+
+#[derive(Clone)]
+pub struct KeyRotationSerializer {
+    peer_id_serializer: PeerIdSerializer,
+}
+
+impl KeyRotationSerializer {
+    pub fn new() -> Self {
+        Self {
+            peer_id_serializer: PeerIdSerializer::new(),
+        }
+    }
+}
+
+impl Serializer<KeyRotation> for KeyRotationSerializer {
+    fn serialize(&self, value: &KeyRotation, buffer: &mut Vec<u8>) -> Result<(), SerializeError> {
+        self.peer_id_serializer
+            .serialize(&value.previous_peer_id, buffer)?;
+        buffer.extend(value.signature.to_bytes());
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct KeyRotationDeserializer {
+    peer_id_deserializer: PeerIdDeserializer,
+    signature_deserializer: SignatureDeserializer,
+}
+
+impl KeyRotationDeserializer {
+    pub fn new() -> Self {
+        Self {
+            peer_id_deserializer: PeerIdDeserializer::new(),
+            signature_deserializer: SignatureDeserializer::new(),
+        }
+    }
+}
+
+impl Deserializer<KeyRotation> for KeyRotationDeserializer {
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], KeyRotation, E> {
+        context(
+            "Failed key rotation deserialization",
+            tuple((
+                context("Failed previous peer id deserialization", |buffer| {
+                    self.peer_id_deserializer.deserialize(buffer)
+                }),
+                context("Failed signature deserialization", |buffer| {
+                    self.signature_deserializer.deserialize(buffer)
+                }),
+            )),
+        )
+        .map(|(previous_peer_id, signature)| KeyRotation {
+            previous_peer_id,
+            signature,
+        })
+        .parse(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_serialization::DeserializeError;
+
+    #[test]
+    fn test_key_rotation_roundtrip() {
+        let previous_keypair = KeyPair::generate(0).unwrap();
+        let new_peer_id = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+        let rotation = KeyRotation::new(&previous_keypair, &new_peer_id).unwrap();
+        rotation.verify(&new_peer_id).unwrap();
+
+        let mut buffer = Vec::new();
+        KeyRotationSerializer::new()
+            .serialize(&rotation, &mut buffer)
+            .unwrap();
+        let (rest, deserialized) = KeyRotationDeserializer::new()
+            .deserialize::<DeserializeError>(&buffer)
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(deserialized, rotation);
+
+        // the rotation can't be replayed for another key
+        let other_peer_id = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+        assert!(rotation.verify(&other_peer_id).is_err());
+    }
+}
@@ -7,7 +7,9 @@ use std::{
 };
 
 use crate::messages::MessagesHandler;
-use massa_channel::{receiver::MassaReceiver, sender::MassaSender, MassaChannel};
+use massa_channel::{
+    heartbeat::Heartbeat, receiver::MassaReceiver, sender::MassaSender, MassaChannel,
+};
 use massa_models::version::{Version, VersionDeserializer};
 use massa_protocol_exports::{PeerConnectionType, PeerId, PeerIdDeserializer, ProtocolConfig};
 use massa_serialization::{DeserializeError, Deserializer};
@@ -18,10 +20,12 @@ use peernet::{
     transports::TransportType,
 };
 use std::cmp::Reverse;
-use tracing::info;
+use tracing::{field, info, info_span};
 
 use super::{
-    announcement::{AnnouncementDeserializer, AnnouncementDeserializerArgs},
+    announcement::{
+        AnnouncementDeserializer, AnnouncementDeserializerArgs, ANNOUNCEMENT_WITH_KEY_ROTATION_ID,
+    },
     models::PeerInfo,
     SharedPeerDB,
 };
@@ -54,8 +58,9 @@ impl Tester {
             Some(config.max_size_channel_commands_peer_testers),
         );
 
-        for _ in 0..config.thread_tester_count {
+        for index in 0..config.thread_tester_count {
             testers.push(Tester::new(
+                index,
                 peer_db.clone(),
                 active_connections.clone(),
                 config.clone(),
@@ -78,6 +83,8 @@ impl Tester {
         addr: SocketAddr,
         our_version: Version,
     ) -> PeerNetResult<PeerId> {
+        let span = info_span!("tester_handshake", remote_addr = %addr, peer_id = field::Empty);
+        let _enter = span.enter();
         let result = {
             let mut socket =
                 std::net::TcpStream::connect_timeout(&addr, Duration::from_millis(500))
@@ -116,6 +123,7 @@ impl Tester {
                         Some("Failed to deserialize PeerId".to_string()),
                     )
                 })?;
+            span.record("peer_id", field::display(&peer_id));
             let res = {
                 {
                     // check if peer is banned
@@ -146,21 +154,24 @@ impl Tester {
                     PeerNetError::HandshakeError
                         .error("Massa Handshake", Some("Failed to get id".to_string())),
                 )?;
-                match id {
-                    0 => {
-                        let (_, announcement) = announcement_deserializer
-                            .deserialize::<DeserializeError>(data.get(1..).ok_or(
-                                PeerNetError::HandshakeError.error(
-                                    "Massa Handshake",
-                                    Some("Failed to get buffer".to_string()),
-                                ),
-                            )?)
-                            .map_err(|err| {
-                                PeerNetError::HandshakeError.error(
-                                    "Tester Handshake",
-                                    Some(format!("Failed to deserialize announcement: {}", err)),
-                                )
-                            })?;
+                match *id {
+                    0 | ANNOUNCEMENT_WITH_KEY_ROTATION_ID => {
+                        let data = data.get(1..).ok_or(
+                            PeerNetError::HandshakeError
+                                .error("Massa Handshake", Some("Failed to get buffer".to_string())),
+                        )?;
+                        let (_, announcement) = if *id == ANNOUNCEMENT_WITH_KEY_ROTATION_ID {
+                            announcement_deserializer
+                                .deserialize_with_key_rotation::<DeserializeError>(data)
+                        } else {
+                            announcement_deserializer.deserialize::<DeserializeError>(data)
+                        }
+                        .map_err(|err| {
+                            PeerNetError::HandshakeError.error(
+                                "Tester Handshake",
+                                Some(format!("Failed to deserialize announcement: {}", err)),
+                            )
+                        })?;
 
                         if peer_id
                             .verify_signature(&announcement.hash, &announcement.signature)
@@ -171,6 +182,24 @@ impl Tester {
                                 Some(String::from("Invalid signature")),
                             ));
                         }
+                        if let Some(key_rotation) = &announcement.key_rotation {
+                            key_rotation.verify(&peer_id).map_err(|err| {
+                                PeerNetError::HandshakeError.error(
+                                    "Tester Handshake",
+                                    Some(format!("Invalid key rotation: {}", err)),
+                                )
+                            })?;
+                            if !peer_db.write().rotate_peer(
+                                &key_rotation.previous_peer_id,
+                                &peer_id,
+                                &announcement,
+                            ) {
+                                return Err(PeerNetError::HandshakeError.error(
+                                    "Tester Handshake",
+                                    Some(String::from("Rotated key of a banned peer")),
+                                ));
+                            }
+                        }
                         //TODO: Check ip we are connected match one of the announced ips
                         {
                             let mut peer_db_write = peer_db.write();
@@ -226,7 +255,7 @@ impl Tester {
                 });
             }
             if let Err(e) = socket.shutdown(std::net::Shutdown::Both) {
-                tracing::log::error!("Failed to shutdown socket: {}", e);
+                tracing::error!("Failed to shutdown socket: {}", e);
             }
             res
         };
@@ -236,6 +265,7 @@ impl Tester {
 
     /// Create a new tester (spawn a thread)
     pub fn new(
+        index: u8,
         peer_db: SharedPeerDB,
         active_connections: Box<dyn ActiveConnectionsTrait>,
         protocol_config: ProtocolConfig,
@@ -244,7 +274,7 @@ impl Tester {
         target_out_connections: HashMap<String, (Vec<IpAddr>, usize)>,
         default_target_out_connections: usize,
     ) -> Self {
-        tracing::log::debug!("running new tester");
+        tracing::debug!("running new tester");
 
         let handle = std::thread::Builder::new()
         .name("protocol-peer-handler-tester".to_string())
@@ -261,7 +291,10 @@ impl Tester {
 
             //let mut network_manager = PeerNetManager::new(config);
             let protocol_config = protocol_config.clone();
+            // each tester beats with its own name, so that one stuck tester is not hidden by the others
+            let heartbeat = Heartbeat::new(&format!("protocol-peer-handler-tester-{}", index));
             loop {
+                heartbeat.beat();
                 crossbeam::select! {
                     recv(receiver) -> res => {
                         receiver.update_metrics();
@@ -366,7 +399,7 @@ impl Tester {
                                             //     &OutConnectionConfig::Tcp(Box::new(TcpOutConnectionConfig::new(protocol_config.read_write_limit_bytes_per_second / 10, Duration::from_millis(100)))),
                                             // );
 
-                                            tracing::log::debug!("{:?}", res);
+                                            tracing::debug!("{:?}", res);
                                         }
                                     };
                                 }
@@ -420,7 +453,7 @@ impl Tester {
                         //     protocol_config.timeout_connection.to_duration(),
                         //     &OutConnectionConfig::Tcp(Box::new(TcpOutConnectionConfig::new(protocol_config.read_write_limit_bytes_per_second / 10, Duration::from_millis(100)))),
                         // );
-                        tracing::log::debug!("{:?}", res);
+                        tracing::debug!("{:?}", res);
                     }
                 }
             }
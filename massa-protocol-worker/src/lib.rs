@@ -2,12 +2,21 @@
 #![feature(let_chains)]
 #![feature(ip)]
 
+#[cfg(any(test, feature = "byzantine"))]
+pub mod byzantine;
+#[cfg(any(test, feature = "chaos"))]
+mod chaos;
 mod connectivity;
 mod context;
 mod controller;
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod fuzzing;
 mod handlers;
 mod manager;
 mod messages;
+mod peer_stats;
+mod rate_classes;
+pub mod recorder;
 mod sig_verifier;
 mod worker;
 mod wrap_network;
@@ -1,7 +1,14 @@
 use massa_channel::sender::MassaSender;
-use massa_protocol_exports::PeerId;
+use massa_protocol_exports::{ChaosConfig, PeerId};
 use massa_serialization::{
-    DeserializeError, Deserializer, Serializer, U64VarIntDeserializer, U64VarIntSerializer,
+    DeserializeError, Deserializer, SerializeError, Serializer, U64VarIntDeserializer,
+    U64VarIntSerializer,
+};
+use nom::{
+    error::{context, ContextError, ParseError},
+    multi::length_count,
+    sequence::tuple,
+    IResult, Parser,
 };
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use peernet::{
@@ -10,15 +17,27 @@ use peernet::{
         MessagesHandler as PeerNetMessagesHandler, MessagesSerializer as PeerNetMessagesSerializer,
     },
 };
+use std::ops::Bound::Included;
+use tracing::debug;
 
+#[cfg(feature = "chaos")]
+use crate::chaos::corrupt_message;
 use crate::handlers::{
-    block_handler::{BlockMessage, BlockMessageSerializer},
-    endorsement_handler::{EndorsementMessage, EndorsementMessageSerializer},
-    operation_handler::{OperationMessage, OperationMessageSerializer},
+    block_handler::{BlockMessage, BlockMessageSerializer, BlockMessageTypeId},
+    endorsement_handler::{
+        EndorsementMessage, EndorsementMessageSerializer, EndorsementMessageTypeId,
+    },
+    operation_handler::{OperationMessage, OperationMessageSerializer, OperationMessageTypeId},
     peer_handler::{
         models::PeerMessageTuple, PeerManagementMessage, PeerManagementMessageSerializer,
+        PeerManagementMessageTypeId,
     },
 };
+use crate::peer_stats::{
+    note_invalid_message, note_message_received, note_unknown_message, SharedPeerStats,
+};
+use crate::rate_classes::MessageRateLimiter;
+use crate::recorder::MessageRecorder;
 
 #[derive(Debug)]
 pub enum Message {
@@ -28,7 +47,7 @@ pub enum Message {
     PeerManagement(Box<PeerManagementMessage>),
 }
 
-#[derive(IntoPrimitive, Debug, Eq, PartialEq, TryFromPrimitive)]
+#[derive(IntoPrimitive, Debug, Clone, Copy, Eq, PartialEq, Hash, TryFromPrimitive)]
 #[repr(u64)]
 pub enum MessageTypeId {
     Block = 0,
@@ -37,6 +56,252 @@ pub enum MessageTypeId {
     PeerManagement = 3,
 }
 
+/// Type of a message down to the message of its handler,
+/// e.g. the headers, asks and replies of the block handler
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum MessageKind {
+    BlockHeader,
+    AskForBlocks,
+    ReplyForBlocks,
+    Endorsements,
+    OperationsAnnouncement,
+    AskForOperations,
+    Operations,
+    NewPeerConnected,
+    ListPeers,
+}
+
+impl MessageKind {
+    pub const COUNT: usize = 9;
+
+    pub const ALL: [MessageKind; MessageKind::COUNT] = [
+        MessageKind::BlockHeader,
+        MessageKind::AskForBlocks,
+        MessageKind::ReplyForBlocks,
+        MessageKind::Endorsements,
+        MessageKind::OperationsAnnouncement,
+        MessageKind::AskForOperations,
+        MessageKind::Operations,
+        MessageKind::NewPeerConnected,
+        MessageKind::ListPeers,
+    ];
+
+    /// Kind of a message of type `message_type` whose content is `data`, read from the id
+    /// the message of the handler starts with. `None` if the id can't be read or is unknown.
+    pub fn read(message_type: MessageTypeId, data: &[u8]) -> Option<MessageKind> {
+        let (_, id) = U64VarIntDeserializer::new(Included(0), Included(u64::MAX))
+            .deserialize::<DeserializeError>(data)
+            .ok()?;
+        let kind = match message_type {
+            MessageTypeId::Block => match BlockMessageTypeId::try_from(id).ok()? {
+                BlockMessageTypeId::BlockHeader => MessageKind::BlockHeader,
+                BlockMessageTypeId::AskForBlocks => MessageKind::AskForBlocks,
+                BlockMessageTypeId::ReplyForBlocks => MessageKind::ReplyForBlocks,
+            },
+            MessageTypeId::Endorsement => match EndorsementMessageTypeId::try_from(id).ok()? {
+                EndorsementMessageTypeId::Endorsements => MessageKind::Endorsements,
+            },
+            MessageTypeId::Operation => match OperationMessageTypeId::try_from(id).ok()? {
+                OperationMessageTypeId::OperationsAnnouncement => {
+                    MessageKind::OperationsAnnouncement
+                }
+                OperationMessageTypeId::AskForOperations => MessageKind::AskForOperations,
+                OperationMessageTypeId::Operations => MessageKind::Operations,
+            },
+            MessageTypeId::PeerManagement => {
+                match PeerManagementMessageTypeId::try_from(id).ok()? {
+                    PeerManagementMessageTypeId::NewPeerConnected => MessageKind::NewPeerConnected,
+                    PeerManagementMessageTypeId::ListPeers => MessageKind::ListPeers,
+                }
+            }
+        };
+        Some(kind)
+    }
+
+    /// Type of the messages of this kind
+    pub fn message_type(self) -> MessageTypeId {
+        match self {
+            MessageKind::BlockHeader | MessageKind::AskForBlocks | MessageKind::ReplyForBlocks => {
+                MessageTypeId::Block
+            }
+            MessageKind::Endorsements => MessageTypeId::Endorsement,
+            MessageKind::OperationsAnnouncement
+            | MessageKind::AskForOperations
+            | MessageKind::Operations => MessageTypeId::Operation,
+            MessageKind::NewPeerConnected | MessageKind::ListPeers => MessageTypeId::PeerManagement,
+        }
+    }
+
+    /// Name of the kind in the stats of the peers
+    pub fn name(self) -> &'static str {
+        match self {
+            MessageKind::BlockHeader => "block_header",
+            MessageKind::AskForBlocks => "ask_for_blocks",
+            MessageKind::ReplyForBlocks => "reply_for_blocks",
+            MessageKind::Endorsements => "endorsements",
+            MessageKind::OperationsAnnouncement => "operations_announcement",
+            MessageKind::AskForOperations => "ask_for_operations",
+            MessageKind::Operations => "operations",
+            MessageKind::NewPeerConnected => "new_peer_connected",
+            MessageKind::ListPeers => "list_peers",
+        }
+    }
+}
+
+impl From<&Message> for MessageKind {
+    fn from(message: &Message) -> Self {
+        match message {
+            Message::Block(message) => match **message {
+                BlockMessage::BlockHeader(_) => MessageKind::BlockHeader,
+                BlockMessage::AskForBlocks(_) => MessageKind::AskForBlocks,
+                BlockMessage::ReplyForBlocks(_) => MessageKind::ReplyForBlocks,
+            },
+            Message::Endorsement(EndorsementMessage::Endorsements(_)) => MessageKind::Endorsements,
+            Message::Operation(message) => match message {
+                OperationMessage::OperationsAnnouncement(_) => MessageKind::OperationsAnnouncement,
+                OperationMessage::AskForOperations(_) => MessageKind::AskForOperations,
+                OperationMessage::Operations(_) => MessageKind::Operations,
+            },
+            Message::PeerManagement(message) => match **message {
+                PeerManagementMessage::NewPeerConnected(_) => MessageKind::NewPeerConnected,
+                PeerManagementMessage::ListPeers(_) => MessageKind::ListPeers,
+            },
+        }
+    }
+}
+
+/// Type id announcing a `MessageEnvelope` instead of a message of one of the types above
+pub const MESSAGE_ENVELOPE_ID: u64 = 127;
+
+/// Maximum number of extension fields of an envelope
+const MAX_ENVELOPE_EXTENSIONS: u64 = 64;
+
+/// Self-describing wrapper of a message: its type, its length and optional extension fields
+/// (tag, length, value). Receivers skip the message types and the extension tags they don't know,
+/// so that new messages and fields can be introduced without disconnecting older peers.
+///
+/// The messages of the types of `MessageTypeId` are still sent without envelope,
+/// for the peers that don't support it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageEnvelope {
+    /// type of the wrapped message
+    pub message_type: u64,
+    /// serialized message, without its type
+    pub payload: Vec<u8>,
+    /// optional fields: tag and value
+    pub extensions: Vec<(u64, Vec<u8>)>,
+}
+
+#[derive(Clone, Default)]
+pub struct MessageEnvelopeSerializer {
+    u64_serializer: U64VarIntSerializer,
+}
+
+impl MessageEnvelopeSerializer {
+    pub fn new() -> Self {
+        Self {
+            u64_serializer: U64VarIntSerializer::new(),
+        }
+    }
+
+    fn serialize_bytes(&self, bytes: &[u8], buffer: &mut Vec<u8>) -> Result<(), SerializeError> {
+        self.u64_serializer
+            .serialize(&(bytes.len() as u64), buffer)?;
+        buffer.extend_from_slice(bytes);
+        Ok(())
+    }
+}
+
+impl Serializer<MessageEnvelope> for MessageEnvelopeSerializer {
+    fn serialize(
+        &self,
+        value: &MessageEnvelope,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), SerializeError> {
+        self.u64_serializer.serialize(&value.message_type, buffer)?;
+        self.serialize_bytes(&value.payload, buffer)?;
+        self.u64_serializer
+            .serialize(&(value.extensions.len() as u64), buffer)?;
+        for (tag, extension) in &value.extensions {
+            self.u64_serializer.serialize(tag, buffer)?;
+            self.serialize_bytes(extension, buffer)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct MessageEnvelopeDeserializer {
+    u64_deserializer: U64VarIntDeserializer,
+    length_deserializer: U64VarIntDeserializer,
+    extension_count_deserializer: U64VarIntDeserializer,
+}
+
+impl MessageEnvelopeDeserializer {
+    /// Creates a deserializer of the envelopes of messages of at most `max_length` bytes
+    pub fn new(max_length: u64) -> Self {
+        Self {
+            u64_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
+            length_deserializer: U64VarIntDeserializer::new(Included(0), Included(max_length)),
+            extension_count_deserializer: U64VarIntDeserializer::new(
+                Included(0),
+                Included(MAX_ENVELOPE_EXTENSIONS),
+            ),
+        }
+    }
+
+    fn deserialize_bytes<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], Vec<u8>, E> {
+        let (rest, length) = self.length_deserializer.deserialize(buffer)?;
+        let bytes = rest.get(..length as usize).ok_or_else(|| {
+            nom::Err::Error(ParseError::from_error_kind(
+                rest,
+                nom::error::ErrorKind::Eof,
+            ))
+        })?;
+        Ok((&rest[bytes.len()..], bytes.to_vec()))
+    }
+}
+
+impl Deserializer<MessageEnvelope> for MessageEnvelopeDeserializer {
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], MessageEnvelope, E> {
+        context(
+            "Failed message envelope deserialization",
+            tuple((
+                context("Failed message type deserialization", |input| {
+                    self.u64_deserializer.deserialize(input)
+                }),
+                context("Failed payload deserialization", |input| {
+                    self.deserialize_bytes(input)
+                }),
+                length_count(
+                    context("Failed extension count deserialization", |input| {
+                        self.extension_count_deserializer.deserialize(input)
+                    }),
+                    context(
+                        "Failed extension deserialization",
+                        tuple((
+                            |input| self.u64_deserializer.deserialize(input),
+                            |input| self.deserialize_bytes(input),
+                        )),
+                    ),
+                ),
+            )),
+        )
+        .map(|(message_type, payload, extensions)| MessageEnvelope {
+            message_type,
+            payload,
+            extensions,
+        })
+        .parse(buffer)
+    }
+}
+
 impl From<&Message> for MessageTypeId {
     fn from(value: &Message) -> Self {
         match value {
@@ -130,6 +395,40 @@ impl MessagesSerializer {
         self.peer_management_message_serializer = Some(peer_management_message_serializer);
         self
     }
+
+    /// Serializes `message` in a `MessageEnvelope` with the given extension fields.
+    /// Only peers supporting envelopes can read it.
+    pub fn serialize_in_envelope(
+        &self,
+        message: &Message,
+        extensions: Vec<(u64, Vec<u8>)>,
+        buffer: &mut Vec<u8>,
+    ) -> PeerNetResult<()> {
+        let mut serialized = Vec::new();
+        self.serialize(message, &mut serialized)?;
+        let (payload, message_type) = U64VarIntDeserializer::new(Included(0), Included(u64::MAX))
+            .deserialize::<DeserializeError>(&serialized)
+            .map_err(|err| {
+                PeerNetError::HandlerError.error(
+                    "MessagesSerializer",
+                    Some(format!("Failed to read message type: {}", err)),
+                )
+            })?;
+        let envelope = MessageEnvelope {
+            message_type,
+            payload: payload.to_vec(),
+            extensions,
+        };
+        self.id_serializer
+            .serialize(&MESSAGE_ENVELOPE_ID, buffer)
+            .and_then(|_| MessageEnvelopeSerializer::new().serialize(&envelope, buffer))
+            .map_err(|err| {
+                PeerNetError::HandlerError.error(
+                    "MessagesSerializer",
+                    Some(format!("Failed to serialize message envelope: {}", err)),
+                )
+            })
+    }
 }
 
 impl PeerNetMessagesSerializer<Message> for MessagesSerializer {
@@ -223,25 +522,102 @@ pub struct MessagesHandler {
     pub sender_endorsements: MassaSender<PeerMessageTuple>,
     pub sender_operations: MassaSender<PeerMessageTuple>,
     pub sender_peers: MassaSender<PeerMessageTuple>,
+    /// records the received messages when the node is started with a record file
+    pub recorder: Option<MessageRecorder>,
+    /// faults injected in the received messages, for testing purposes only
+    pub chaos: Option<ChaosConfig>,
+    /// counters of the messages received from each peer
+    pub peer_stats: SharedPeerStats,
+    /// drops the messages received beyond the rate configured for their type
+    pub rate_limiter: MessageRateLimiter,
 }
 
 impl PeerNetMessagesHandler<PeerId> for MessagesHandler {
     fn handle(&self, data: &[u8], peer_id: &PeerId) -> PeerNetResult<()> {
+        if let Some(recorder) = &self.recorder {
+            recorder.record(peer_id, data);
+        }
+        if !self.rate_limiter.allow_bytes(peer_id, data.len()) {
+            debug!(
+                "dropping a message of {} bytes from {}: bandwidth allowance exceeded",
+                data.len(),
+                peer_id
+            );
+            return Ok(());
+        }
+        #[cfg(feature = "chaos")]
+        let corrupted = self
+            .chaos
+            .as_ref()
+            .and_then(|chaos| corrupt_message(chaos, data));
+        #[cfg(feature = "chaos")]
+        let data = corrupted.as_deref().unwrap_or(data);
         let (data, raw_id) = self
             .id_deserializer
             .deserialize::<DeserializeError>(data)
             .map_err(|err| {
+                note_invalid_message(&self.peer_stats, peer_id);
                 PeerNetError::HandlerError.error(
                     "MessagesHandler",
                     Some(format!("Failed to deserialize id: {}", err)),
                 )
             })?;
-        let id = MessageTypeId::try_from(raw_id).map_err(|_| {
-            PeerNetError::HandlerError.error(
+        // an envelope gives the type and the bounds of the message it wraps
+        let envelope = if raw_id == MESSAGE_ENVELOPE_ID {
+            let (rest, envelope) = MessageEnvelopeDeserializer::new(data.len() as u64)
+                .deserialize::<DeserializeError>(data)
+                .map_err(|err| {
+                    note_invalid_message(&self.peer_stats, peer_id);
+                    PeerNetError::HandlerError.error(
+                        "MessagesHandler",
+                        Some(format!("Failed to deserialize message envelope: {}", err)),
+                    )
+                })?;
+            if !rest.is_empty() {
+                note_invalid_message(&self.peer_stats, peer_id);
+                return Err(PeerNetError::HandlerError.error(
+                    "MessagesHandler",
+                    Some(String::from("Trailing data after message envelope")),
+                ));
+            }
+            // no extension is defined yet: all of them are skipped
+            Some(envelope)
+        } else {
+            None
+        };
+        let (raw_id, data) = match &envelope {
+            Some(envelope) => (envelope.message_type, envelope.payload.as_slice()),
+            None => (raw_id, data),
+        };
+        let Ok(id) = MessageTypeId::try_from(raw_id) else {
+            if envelope.is_some() {
+                // message types introduced by newer versions are sent in an envelope,
+                // so that older peers can skip them without failing
+                note_unknown_message(&self.peer_stats, peer_id);
+                debug!("skipping message of unknown type {} from {}", raw_id, peer_id);
+                return Ok(());
+            }
+            note_invalid_message(&self.peer_stats, peer_id);
+            return Err(PeerNetError::HandlerError.error(
                 "MessagesHandler",
-                Some(String::from("Failed to deserialize id")),
-            )
-        })?;
+                Some(format!("Unknown message type {}", raw_id)),
+            ));
+        };
+        let Some(kind) = MessageKind::read(id, data) else {
+            // the handler would fail to read it
+            note_invalid_message(&self.peer_stats, peer_id);
+            debug!("dropping {:?} message of unknown kind from {}", id, peer_id);
+            return Ok(());
+        };
+        note_message_received(&self.peer_stats, peer_id, kind);
+        if !self.rate_limiter.allow(peer_id, kind) {
+            debug!(
+                "dropping {} message from {}: rate limit exceeded",
+                kind.name(),
+                peer_id
+            );
+            return Ok(());
+        }
         match id {
             MessageTypeId::Block => self
                 .sender_blocks
@@ -282,3 +658,124 @@ impl PeerNetMessagesHandler<PeerId> for MessagesHandler {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_channel::{receiver::MassaReceiver, MassaChannel};
+    use massa_signature::KeyPair;
+
+    fn handler() -> (MessagesHandler, MassaReceiver<PeerMessageTuple>) {
+        let (sender_blocks, _) = MassaChannel::new("blocks".to_string(), Some(10));
+        let (sender_endorsements, _) = MassaChannel::new("endorsements".to_string(), Some(10));
+        let (sender_operations, receiver_operations) =
+            MassaChannel::new("operations".to_string(), Some(10));
+        let (sender_peers, _) = MassaChannel::new("peers".to_string(), Some(10));
+        let handler = MessagesHandler {
+            id_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
+            sender_blocks,
+            sender_endorsements,
+            sender_operations,
+            sender_peers,
+            recorder: None,
+            chaos: None,
+            peer_stats: Default::default(),
+            rate_limiter: Default::default(),
+        };
+        (handler, receiver_operations)
+    }
+
+    fn enveloped(envelope: &MessageEnvelope) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        U64VarIntSerializer::new()
+            .serialize(&MESSAGE_ENVELOPE_ID, &mut buffer)
+            .unwrap();
+        MessageEnvelopeSerializer::new()
+            .serialize(envelope, &mut buffer)
+            .unwrap();
+        buffer
+    }
+
+    #[test]
+    fn test_envelope_ser_deser() {
+        let envelope = MessageEnvelope {
+            message_type: 200,
+            payload: vec![1, 2, 3],
+            extensions: vec![(1, vec![4, 5]), (300, Vec::new())],
+        };
+        let mut buffer = Vec::new();
+        MessageEnvelopeSerializer::new()
+            .serialize(&envelope, &mut buffer)
+            .unwrap();
+        let (rest, deserialized) = MessageEnvelopeDeserializer::new(buffer.len() as u64)
+            .deserialize::<DeserializeError>(&buffer)
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(deserialized, envelope);
+
+        // a field longer than the message is rejected
+        assert!(MessageEnvelopeDeserializer::new(2)
+            .deserialize::<DeserializeError>(&buffer)
+            .is_err());
+        // so is a truncated envelope
+        assert!(MessageEnvelopeDeserializer::new(buffer.len() as u64)
+            .deserialize::<DeserializeError>(&buffer[..buffer.len() - 3])
+            .is_err());
+    }
+
+    #[test]
+    fn test_handle_enveloped_message() {
+        let (handler, receiver_operations) = handler();
+        let peer_id = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+        let data = enveloped(&MessageEnvelope {
+            message_type: MessageTypeId::Operation.into(),
+            payload: vec![1, 2, 3],
+            extensions: vec![(42, vec![0])],
+        });
+        handler.handle(&data, &peer_id).unwrap();
+        // the payload is dispatched as if it had been sent without envelope
+        let (sender, payload) = receiver_operations.try_recv().unwrap();
+        assert_eq!(sender, peer_id);
+        assert_eq!(payload, vec![1, 2, 3]);
+        let peer_stats = handler.peer_stats.read();
+        assert_eq!(
+            peer_stats[&peer_id].received(MessageKind::AskForOperations),
+            1
+        );
+    }
+
+    #[test]
+    fn test_handle_unknown_message_type() {
+        let (handler, receiver_operations) = handler();
+        let peer_id = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+
+        // an unknown type in an envelope is skipped
+        let data = enveloped(&MessageEnvelope {
+            message_type: 100,
+            payload: vec![1, 2, 3],
+            extensions: Vec::new(),
+        });
+        handler.handle(&data, &peer_id).unwrap();
+        assert!(receiver_operations.try_recv().is_err());
+        assert_eq!(handler.peer_stats.read()[&peer_id].unknown_received(), 1);
+
+        // an unknown type without envelope can't be delimited: the message is invalid
+        let mut data = Vec::new();
+        U64VarIntSerializer::new()
+            .serialize(&100, &mut data)
+            .unwrap();
+        data.extend_from_slice(&[1, 2, 3]);
+        assert!(handler.handle(&data, &peer_id).is_err());
+        // so is an envelope followed by trailing data
+        let mut data = enveloped(&MessageEnvelope {
+            message_type: 100,
+            payload: Vec::new(),
+            extensions: Vec::new(),
+        });
+        data.push(0);
+        assert!(handler.handle(&data, &peer_id).is_err());
+        let peer_stats = handler.peer_stats.read();
+        assert_eq!(peer_stats[&peer_id].invalid_received(), 2);
+        assert_eq!(peer_stats[&peer_id].unknown_received(), 1);
+    }
+}
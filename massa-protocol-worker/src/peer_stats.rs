@@ -0,0 +1,410 @@
+//! Counters of the messages exchanged with each connected peer.
+//!
+//! The received messages are counted by the messages handler of peernet, by kind, before they are sent to the handlers.
+//! The handlers count the ones whose content is invalid.
+//! The sent messages are counted by wrapping the active connections used by the handlers.
+//!
+//! The counters of each peer are atomic: counting a message only takes the read lock of the map of the peers,
+//! the write lock is only taken for a new peer.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use massa_protocol_exports::{PeerId, ProtocolError};
+use massa_time::MassaTime;
+use parking_lot::RwLock;
+use peernet::peer::PeerConnectionType;
+
+use crate::{
+    handlers::{
+        block_handler::{BlockInfoReply, BlockMessage},
+        operation_handler::OperationMessage,
+    },
+    messages::{Message, MessageKind, MessageTypeId, MessagesSerializer},
+    wrap_network::ActiveConnectionsTrait,
+};
+
+/// Messages exchanged with a peer
+#[derive(Debug, Default)]
+pub struct PeerMessageCounters {
+    /// messages received by kind, indexed as `MessageKind::ALL`
+    received: [AtomicU64; MessageKind::COUNT],
+    /// messages sent by kind, indexed as `MessageKind::ALL`
+    sent: [AtomicU64; MessageKind::COUNT],
+    /// messages that could not be read: of an unknown type, or whose content is invalid
+    invalid_received: AtomicU64,
+    /// messages in an envelope of a type we don't know, sent by newer peers
+    unknown_received: AtomicU64,
+    /// blocks and operations sent in answer to the asks of the peer
+    asks_served: AtomicU64,
+    /// time of the last message received, in milliseconds, 0 if none
+    last_received: AtomicU64,
+    /// time of the last message sent, in milliseconds, 0 if none
+    last_sent: AtomicU64,
+    /// the peer announced different network parameters at handshake
+    config_mismatch: AtomicBool,
+}
+
+fn index_of(kind: MessageKind) -> usize {
+    MessageKind::ALL
+        .iter()
+        .position(|other| *other == kind)
+        .expect("every kind is in MessageKind::ALL")
+}
+
+fn add(counter: &AtomicU64, count: u64) {
+    // saturating, as the other counters of the node
+    let _ = counter.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |value| {
+        Some(value.saturating_add(count))
+    });
+}
+
+fn note_time(time: &AtomicU64) {
+    if let Ok(now) = MassaTime::now() {
+        time.store(now.to_millis(), Ordering::Relaxed);
+    }
+}
+
+fn read_time(time: &AtomicU64) -> Option<MassaTime> {
+    match time.load(Ordering::Relaxed) {
+        0 => None,
+        millis => Some(MassaTime::from_millis(millis)),
+    }
+}
+
+impl PeerMessageCounters {
+    /// Messages of `kind` received
+    pub fn received(&self, kind: MessageKind) -> u64 {
+        self.received[index_of(kind)].load(Ordering::Relaxed)
+    }
+
+    /// Messages of `kind` sent
+    pub fn sent(&self, kind: MessageKind) -> u64 {
+        self.sent[index_of(kind)].load(Ordering::Relaxed)
+    }
+
+    /// Messages of a type received, whatever their kind
+    pub fn received_of_type(&self, message_type: MessageTypeId) -> u64 {
+        MessageKind::ALL
+            .iter()
+            .filter(|kind| kind.message_type() == message_type)
+            .map(|kind| self.received(*kind))
+            .fold(0, u64::saturating_add)
+    }
+
+    /// Messages sent, whatever their kind
+    pub fn sent_total(&self) -> u64 {
+        MessageKind::ALL
+            .iter()
+            .map(|kind| self.sent(*kind))
+            .fold(0, u64::saturating_add)
+    }
+
+    /// Non-zero counts of the messages received by kind, by name of the kind
+    pub fn received_by_kind(&self) -> BTreeMap<String, u64> {
+        self.by_kind(|kind| self.received(kind))
+    }
+
+    /// Non-zero counts of the messages sent by kind, by name of the kind
+    pub fn sent_by_kind(&self) -> BTreeMap<String, u64> {
+        self.by_kind(|kind| self.sent(kind))
+    }
+
+    fn by_kind(&self, count: impl Fn(MessageKind) -> u64) -> BTreeMap<String, u64> {
+        MessageKind::ALL
+            .iter()
+            .map(|kind| (kind.name().to_string(), count(*kind)))
+            .filter(|(_, count)| *count > 0)
+            .collect()
+    }
+
+    pub fn invalid_received(&self) -> u64 {
+        self.invalid_received.load(Ordering::Relaxed)
+    }
+
+    pub fn unknown_received(&self) -> u64 {
+        self.unknown_received.load(Ordering::Relaxed)
+    }
+
+    pub fn asks_served(&self) -> u64 {
+        self.asks_served.load(Ordering::Relaxed)
+    }
+
+    pub fn last_received(&self) -> Option<MassaTime> {
+        read_time(&self.last_received)
+    }
+
+    pub fn last_sent(&self) -> Option<MassaTime> {
+        read_time(&self.last_sent)
+    }
+
+    pub fn config_mismatch(&self) -> bool {
+        self.config_mismatch.load(Ordering::Relaxed)
+    }
+}
+
+pub type SharedPeerStats = Arc<RwLock<HashMap<PeerId, Arc<PeerMessageCounters>>>>;
+
+/// Apply `update` to the counters of `peer_id`, under the read lock if the peer is already known
+fn update_counters(
+    peer_stats: &SharedPeerStats,
+    peer_id: &PeerId,
+    update: impl Fn(&PeerMessageCounters),
+) {
+    if let Some(counters) = peer_stats.read().get(peer_id) {
+        update(counters);
+        return;
+    }
+    update(peer_stats.write().entry(peer_id.clone()).or_default());
+}
+
+/// Count a message of `kind` received from `peer_id`
+pub fn note_message_received(peer_stats: &SharedPeerStats, peer_id: &PeerId, kind: MessageKind) {
+    update_counters(peer_stats, peer_id, |counters| {
+        add(&counters.received[index_of(kind)], 1);
+        note_time(&counters.last_received);
+    });
+}
+
+/// Count a message received from `peer_id` that could not be read.
+/// The messages whose content is found invalid by the handlers were already counted by kind.
+pub fn note_invalid_message(peer_stats: &SharedPeerStats, peer_id: &PeerId) {
+    update_counters(peer_stats, peer_id, |counters| {
+        add(&counters.invalid_received, 1);
+        note_time(&counters.last_received);
+    });
+}
+
+/// Count a message received from `peer_id` in an envelope of a type we don't know
+pub fn note_unknown_message(peer_stats: &SharedPeerStats, peer_id: &PeerId) {
+    update_counters(peer_stats, peer_id, |counters| {
+        add(&counters.unknown_received, 1);
+        note_time(&counters.last_received);
+    });
+}
+
+/// Blocks and operations served by `message`.
+/// The operations are only sent in full in answer to an ask, the blocks in a reply.
+fn served_by(message: &Message) -> u64 {
+    let served = match message {
+        Message::Block(message) => match message.as_ref() {
+            BlockMessage::ReplyForBlocks(infos) => infos
+                .iter()
+                .filter(|(_, info)| !matches!(info, BlockInfoReply::NotFound))
+                .count(),
+            _ => 0,
+        },
+        Message::Operation(OperationMessage::Operations(operations)) => operations.len(),
+        _ => 0,
+    };
+    served as u64
+}
+
+/// Count a message of `kind` sent to `peer_id`, serving `served` blocks and operations
+pub fn note_message_sent(
+    peer_stats: &SharedPeerStats,
+    peer_id: &PeerId,
+    kind: MessageKind,
+    served: u64,
+) {
+    update_counters(peer_stats, peer_id, |counters| {
+        add(&counters.sent[index_of(kind)], 1);
+        add(&counters.asks_served, served);
+        note_time(&counters.last_sent);
+    });
+}
+
+/// Note that `peer_id` announced different network parameters at handshake
+pub fn note_config_mismatch(peer_stats: &SharedPeerStats, peer_id: &PeerId) {
+    update_counters(peer_stats, peer_id, |counters| {
+        counters.config_mismatch.store(true, Ordering::Relaxed);
+    });
+}
+
+/// Move the counters of `previous_peer_id` to `new_peer_id` after a key rotation
+pub fn rotate_peer_stats(
+    peer_stats: &SharedPeerStats,
+    previous_peer_id: &PeerId,
+    new_peer_id: &PeerId,
+) {
+    let mut peer_stats = peer_stats.write();
+    if let Some(counters) = peer_stats.remove(previous_peer_id) {
+        peer_stats.insert(new_peer_id.clone(), counters);
+    }
+}
+
+/// Forget the counters of the peers we are not connected to anymore
+pub fn retain_connected(peer_stats: &SharedPeerStats, peers_connected: &HashSet<PeerId>) {
+    peer_stats
+        .write()
+        .retain(|peer_id, _| peers_connected.contains(peer_id));
+}
+
+/// Active connections counting the messages sent to each peer
+pub struct CountingActiveConnections {
+    inner: Box<dyn ActiveConnectionsTrait>,
+    peer_stats: SharedPeerStats,
+}
+
+impl CountingActiveConnections {
+    pub fn new(inner: Box<dyn ActiveConnectionsTrait>, peer_stats: SharedPeerStats) -> Self {
+        CountingActiveConnections { inner, peer_stats }
+    }
+}
+
+impl ActiveConnectionsTrait for CountingActiveConnections {
+    fn send_to_peer(
+        &self,
+        peer_id: &PeerId,
+        message_serializer: &MessagesSerializer,
+        message: Message,
+        high_priority: bool,
+    ) -> Result<(), ProtocolError> {
+        let kind = MessageKind::from(&message);
+        let served = served_by(&message);
+        self.inner
+            .send_to_peer(peer_id, message_serializer, message, high_priority)?;
+        note_message_sent(&self.peer_stats, peer_id, kind, served);
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn ActiveConnectionsTrait> {
+        Box::new(CountingActiveConnections {
+            inner: self.inner.clone_box(),
+            peer_stats: self.peer_stats.clone(),
+        })
+    }
+
+    fn get_peer_ids_connected(&self) -> HashSet<PeerId> {
+        self.inner.get_peer_ids_connected()
+    }
+
+    fn get_peers_connected(
+        &self,
+    ) -> HashMap<PeerId, (SocketAddr, PeerConnectionType, Option<String>)> {
+        self.inner.get_peers_connected()
+    }
+
+    fn get_nb_out_connections(&self) -> usize {
+        self.inner.get_nb_out_connections()
+    }
+
+    fn get_nb_in_connections(&self) -> usize {
+        self.inner.get_nb_in_connections()
+    }
+
+    fn shutdown_connection(&mut self, peer_id: &PeerId) {
+        self.inner.shutdown_connection(peer_id)
+    }
+
+    fn get_peers_connections_bandwidth(&self) -> HashMap<String, (u64, u64)> {
+        self.inner.get_peers_connections_bandwidth()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_hash::Hash;
+    use massa_models::block_id::BlockId;
+    use massa_signature::KeyPair;
+
+    fn peer() -> PeerId {
+        PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key())
+    }
+
+    #[test]
+    fn test_peer_stats_received() {
+        let peer_stats = SharedPeerStats::default();
+        let peer_id = peer();
+        note_message_received(&peer_stats, &peer_id, MessageKind::AskForBlocks);
+        note_message_received(&peer_stats, &peer_id, MessageKind::AskForBlocks);
+        note_message_received(&peer_stats, &peer_id, MessageKind::BlockHeader);
+        note_message_received(&peer_stats, &peer_id, MessageKind::Operations);
+        note_invalid_message(&peer_stats, &peer_id);
+        note_unknown_message(&peer_stats, &peer_id);
+
+        let counters = peer_stats.read()[&peer_id].clone();
+        assert_eq!(counters.received(MessageKind::AskForBlocks), 2);
+        assert_eq!(counters.received_of_type(MessageTypeId::Block), 3);
+        assert_eq!(counters.received_of_type(MessageTypeId::Operation), 1);
+        assert_eq!(counters.received_of_type(MessageTypeId::Endorsement), 0);
+        assert_eq!(
+            counters.received_by_kind(),
+            BTreeMap::from([
+                ("ask_for_blocks".to_string(), 2),
+                ("block_header".to_string(), 1),
+                ("operations".to_string(), 1),
+            ])
+        );
+        assert_eq!(counters.invalid_received(), 1);
+        assert_eq!(counters.unknown_received(), 1);
+        assert!(counters.last_received().is_some());
+        assert!(counters.last_sent().is_none());
+    }
+
+    #[test]
+    fn test_peer_stats_sent() {
+        let peer_stats = SharedPeerStats::default();
+        let peer_id = peer();
+        let block_id = |seed: &[u8]| BlockId(Hash::compute_from(seed));
+        let reply = Message::Block(Box::new(BlockMessage::ReplyForBlocks(vec![
+            (block_id(b"found"), BlockInfoReply::Info(Vec::new())),
+            (block_id(b"missing"), BlockInfoReply::NotFound),
+        ])));
+        let ask = Message::Block(Box::new(BlockMessage::AskForBlocks(Vec::new())));
+        for message in [&reply, &ask] {
+            note_message_sent(
+                &peer_stats,
+                &peer_id,
+                MessageKind::from(message),
+                served_by(message),
+            );
+        }
+        let operations = Message::Operation(OperationMessage::Operations(Vec::new()));
+        note_message_sent(
+            &peer_stats,
+            &peer_id,
+            MessageKind::from(&operations),
+            served_by(&operations),
+        );
+
+        let counters = peer_stats.read()[&peer_id].clone();
+        assert_eq!(counters.sent(MessageKind::ReplyForBlocks), 1);
+        assert_eq!(counters.sent(MessageKind::AskForBlocks), 1);
+        assert_eq!(counters.sent(MessageKind::Operations), 1);
+        assert_eq!(counters.sent_total(), 3);
+        // the blocks not found are not served
+        assert_eq!(counters.asks_served(), 1);
+        assert!(counters.last_sent().is_some());
+    }
+
+    #[test]
+    fn test_peer_stats_rotate_and_retain() {
+        let peer_stats = SharedPeerStats::default();
+        let previous_peer_id = peer();
+        let peer_id = peer();
+        let other = peer();
+        note_message_received(&peer_stats, &previous_peer_id, MessageKind::ListPeers);
+        note_config_mismatch(&peer_stats, &previous_peer_id);
+        note_message_received(&peer_stats, &other, MessageKind::ListPeers);
+
+        // the counters follow the key rotation
+        rotate_peer_stats(&peer_stats, &previous_peer_id, &peer_id);
+        assert!(!peer_stats.read().contains_key(&previous_peer_id));
+        let counters = peer_stats.read()[&peer_id].clone();
+        assert_eq!(counters.received(MessageKind::ListPeers), 1);
+        assert!(counters.config_mismatch());
+
+        // and are forgotten when the peer disconnects
+        retain_connected(&peer_stats, &HashSet::from([peer_id.clone()]));
+        assert!(peer_stats.read().contains_key(&peer_id));
+        assert!(!peer_stats.read().contains_key(&other));
+    }
+}
@@ -0,0 +1,343 @@
+//! Rate limits and priorities configured for each class of messages, and bandwidth allowances
+//! configured for each category of peers.
+//!
+//! The received messages beyond the rate of their class, or beyond the bandwidth allowance of the
+//! category of their peer, are dropped before reaching the handlers.
+//! The priority of the sent messages is applied by wrapping the active connections used by the handlers.
+
+use std::{
+    collections::{HashMap, HashSet},
+    net::SocketAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use massa_protocol_exports::{
+    MessageRateClass, MessageRateClasses, PeerCategoryInfo, PeerId, ProtocolError,
+};
+use parking_lot::{Mutex, RwLock};
+use peernet::peer::PeerConnectionType;
+
+use crate::{
+    messages::{Message, MessageKind, MessagesSerializer},
+    wrap_network::ActiveConnectionsTrait,
+};
+
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Number of rate classes, the fields of `MessageRateClasses`
+const RATE_CLASS_COUNT: usize = 6;
+
+/// Index of the rate class of the messages of `kind`, and its limits
+fn rate_class(classes: &MessageRateClasses, kind: MessageKind) -> (usize, MessageRateClass) {
+    match kind {
+        MessageKind::BlockHeader => (0, classes.block_header),
+        MessageKind::AskForBlocks => (1, classes.ask_for_blocks),
+        MessageKind::ReplyForBlocks => (2, classes.reply_for_blocks),
+        MessageKind::Endorsements => (3, classes.endorsement),
+        MessageKind::OperationsAnnouncement
+        | MessageKind::AskForOperations
+        | MessageKind::Operations => (4, classes.operation),
+        MessageKind::NewPeerConnected | MessageKind::ListPeers => (5, classes.peer_management),
+    }
+}
+
+/// Messages, or bytes, received from a peer in the current window
+#[derive(Debug, Clone, Copy)]
+struct RateWindow {
+    start: Instant,
+    count: u64,
+}
+
+impl RateWindow {
+    fn new(start: Instant) -> Self {
+        RateWindow { start, count: 0 }
+    }
+
+    /// Count `count` more at `now`, in a new window if the current one is over.
+    /// Returns false if more than `max` were counted in the window.
+    fn add(&mut self, count: u64, max: u64, now: Instant) -> bool {
+        if now.saturating_duration_since(self.start) >= RATE_WINDOW {
+            *self = RateWindow::new(now);
+        }
+        self.count = self.count.saturating_add(count);
+        self.count <= max
+    }
+}
+
+/// Windows of a peer: the messages received by rate class, and the bytes received
+#[derive(Debug)]
+struct PeerRates {
+    messages: [RateWindow; RATE_CLASS_COUNT],
+    bytes: RateWindow,
+    /// bandwidth allowance of the category of the peer
+    max_bytes_per_second: u64,
+}
+
+/// Counts the messages received from each peer by rate class, and the bytes received from each
+/// peer, over windows of one second.
+///
+/// Each peer has its own lock: counting a message only takes the read lock of the map of the
+/// peers, the write lock is only taken for a new peer.
+#[derive(Debug, Clone, Default)]
+pub struct MessageRateLimiter {
+    classes: MessageRateClasses,
+    /// bandwidth allowance of each category of peers
+    category_allowances: HashMap<String, u64>,
+    /// bandwidth allowance of the peers of no category, and of the peers not classified yet
+    default_allowance: u64,
+    peers: Arc<RwLock<HashMap<PeerId, Arc<Mutex<PeerRates>>>>>,
+}
+
+impl MessageRateLimiter {
+    pub fn new(classes: MessageRateClasses) -> Self {
+        MessageRateLimiter {
+            classes,
+            ..Default::default()
+        }
+    }
+
+    /// Apply the bandwidth allowances of the categories of peers
+    pub fn with_bandwidth_allowances(
+        mut self,
+        categories: &HashMap<String, PeerCategoryInfo>,
+        default_category: &PeerCategoryInfo,
+    ) -> Self {
+        self.category_allowances = categories
+            .iter()
+            .map(|(name, info)| (name.clone(), info.max_bytes_received_per_second))
+            .collect();
+        self.default_allowance = default_category.max_bytes_received_per_second;
+        self
+    }
+
+    fn peer_rates(&self, peer_id: &PeerId, now: Instant) -> Arc<Mutex<PeerRates>> {
+        if let Some(rates) = self.peers.read().get(peer_id) {
+            return rates.clone();
+        }
+        self.peers
+            .write()
+            .entry(peer_id.clone())
+            .or_insert_with(|| {
+                Arc::new(Mutex::new(PeerRates {
+                    messages: [RateWindow::new(now); RATE_CLASS_COUNT],
+                    bytes: RateWindow::new(now),
+                    max_bytes_per_second: self.default_allowance,
+                }))
+            })
+            .clone()
+    }
+
+    /// Count `bytes` received from `peer_id`.
+    /// Returns false if the peer already sent more than the allowance of its category in the current window.
+    pub fn allow_bytes(&self, peer_id: &PeerId, bytes: usize) -> bool {
+        self.allow_bytes_at(peer_id, bytes, Instant::now())
+    }
+
+    fn allow_bytes_at(&self, peer_id: &PeerId, bytes: usize, now: Instant) -> bool {
+        let rates = self.peer_rates(peer_id, now);
+        let mut rates = rates.lock();
+        let max_bytes_per_second = rates.max_bytes_per_second;
+        max_bytes_per_second == 0 || rates.bytes.add(bytes as u64, max_bytes_per_second, now)
+    }
+
+    /// Count a message of `kind` received from `peer_id`.
+    /// Returns false if the peer already sent too many messages of its rate class in the current window.
+    pub fn allow(&self, peer_id: &PeerId, kind: MessageKind) -> bool {
+        self.allow_at(peer_id, kind, Instant::now())
+    }
+
+    fn allow_at(&self, peer_id: &PeerId, kind: MessageKind, now: Instant) -> bool {
+        let (index, class) = rate_class(&self.classes, kind);
+        if class.max_per_second == 0 {
+            return true;
+        }
+        self.peer_rates(peer_id, now).lock().messages[index].add(1, class.max_per_second, now)
+    }
+
+    /// Forget the peers we are not connected to anymore, and apply to the connected ones the
+    /// bandwidth allowance of the category of their connection
+    pub fn update_connected(
+        &self,
+        peers_connected: &HashMap<PeerId, (SocketAddr, PeerConnectionType, Option<String>)>,
+    ) {
+        let mut peers = self.peers.write();
+        peers.retain(|peer_id, _| peers_connected.contains_key(peer_id));
+        for (peer_id, rates) in peers.iter() {
+            rates.lock().max_bytes_per_second = peers_connected[peer_id]
+                .2
+                .as_ref()
+                .and_then(|category| self.category_allowances.get(category))
+                .copied()
+                .unwrap_or(self.default_allowance);
+        }
+    }
+}
+
+/// Active connections sending the messages with the priority configured for their type
+pub struct PrioritizedActiveConnections {
+    inner: Box<dyn ActiveConnectionsTrait>,
+    classes: MessageRateClasses,
+}
+
+impl PrioritizedActiveConnections {
+    pub fn new(inner: Box<dyn ActiveConnectionsTrait>, classes: MessageRateClasses) -> Self {
+        PrioritizedActiveConnections { inner, classes }
+    }
+}
+
+impl ActiveConnectionsTrait for PrioritizedActiveConnections {
+    fn send_to_peer(
+        &self,
+        peer_id: &PeerId,
+        message_serializer: &MessagesSerializer,
+        message: Message,
+        high_priority: bool,
+    ) -> Result<(), ProtocolError> {
+        let high_priority = rate_class(&self.classes, MessageKind::from(&message))
+            .1
+            .high_priority
+            .unwrap_or(high_priority);
+        self.inner
+            .send_to_peer(peer_id, message_serializer, message, high_priority)
+    }
+
+    fn clone_box(&self) -> Box<dyn ActiveConnectionsTrait> {
+        Box::new(PrioritizedActiveConnections {
+            inner: self.inner.clone_box(),
+            classes: self.classes,
+        })
+    }
+
+    fn get_peer_ids_connected(&self) -> HashSet<PeerId> {
+        self.inner.get_peer_ids_connected()
+    }
+
+    fn get_peers_connected(
+        &self,
+    ) -> HashMap<PeerId, (SocketAddr, PeerConnectionType, Option<String>)> {
+        self.inner.get_peers_connected()
+    }
+
+    fn get_nb_out_connections(&self) -> usize {
+        self.inner.get_nb_out_connections()
+    }
+
+    fn get_nb_in_connections(&self) -> usize {
+        self.inner.get_nb_in_connections()
+    }
+
+    fn shutdown_connection(&mut self, peer_id: &PeerId) {
+        self.inner.shutdown_connection(peer_id)
+    }
+
+    fn get_peers_connections_bandwidth(&self) -> HashMap<String, (u64, u64)> {
+        self.inner.get_peers_connections_bandwidth()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_signature::KeyPair;
+
+    #[test]
+    fn test_rate_limiter() {
+        let limiter = MessageRateLimiter::new(MessageRateClasses {
+            operation: MessageRateClass {
+                max_per_second: 2,
+                high_priority: None,
+            },
+            ..Default::default()
+        });
+        let peer = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+        let start = Instant::now();
+        // the kinds of operation messages share the class
+        assert!(limiter.allow_at(&peer, MessageKind::OperationsAnnouncement, start));
+        assert!(limiter.allow_at(&peer, MessageKind::Operations, start));
+        assert!(!limiter.allow_at(&peer, MessageKind::AskForOperations, start));
+        // the other classes are not limited
+        for _ in 0..10 {
+            assert!(limiter.allow_at(&peer, MessageKind::BlockHeader, start));
+        }
+        // a new window starts after one second
+        assert!(limiter.allow_at(&peer, MessageKind::Operations, start + RATE_WINDOW));
+    }
+
+    #[test]
+    fn test_block_rate_classes() {
+        let class = |max_per_second| MessageRateClass {
+            max_per_second,
+            high_priority: None,
+        };
+        let limiter = MessageRateLimiter::new(MessageRateClasses {
+            block_header: class(1),
+            ask_for_blocks: class(2),
+            reply_for_blocks: class(3),
+            ..Default::default()
+        });
+        let peer = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+        let start = Instant::now();
+        // a peer flooding asks can still send its headers and replies
+        for _ in 0..2 {
+            assert!(limiter.allow_at(&peer, MessageKind::AskForBlocks, start));
+        }
+        assert!(!limiter.allow_at(&peer, MessageKind::AskForBlocks, start));
+        assert!(limiter.allow_at(&peer, MessageKind::BlockHeader, start));
+        assert!(!limiter.allow_at(&peer, MessageKind::BlockHeader, start));
+        for _ in 0..3 {
+            assert!(limiter.allow_at(&peer, MessageKind::ReplyForBlocks, start));
+        }
+        assert!(!limiter.allow_at(&peer, MessageKind::ReplyForBlocks, start));
+    }
+
+    #[test]
+    fn test_bandwidth_allowances() {
+        let category = |max_bytes_received_per_second| PeerCategoryInfo {
+            allow_local_peers: false,
+            target_out_connections: 1,
+            max_in_connections: 1,
+            max_in_connections_per_ip: 1,
+            ban_immune: false,
+            max_bytes_received_per_second,
+        };
+        let limiter = MessageRateLimiter::new(Default::default()).with_bandwidth_allowances(
+            &HashMap::from([("Whitelisted".to_string(), category(0))]),
+            &category(100),
+        );
+        let whitelisted = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+        let other = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+        let start = Instant::now();
+
+        // the peers not classified yet get the allowance of the default category
+        assert!(limiter.allow_bytes_at(&whitelisted, 60, start));
+        assert!(!limiter.allow_bytes_at(&whitelisted, 60, start));
+        assert!(limiter.allow_bytes_at(&other, 100, start));
+        assert!(!limiter.allow_bytes_at(&other, 1, start));
+
+        // then the allowance of the category of their connection
+        let addr = SocketAddr::from(([127, 0, 0, 1], 31244));
+        limiter.update_connected(&HashMap::from([
+            (
+                whitelisted.clone(),
+                (
+                    addr,
+                    PeerConnectionType::IN,
+                    Some("Whitelisted".to_string()),
+                ),
+            ),
+            (other.clone(), (addr, PeerConnectionType::IN, None)),
+        ]));
+        for _ in 0..10 {
+            assert!(limiter.allow_bytes_at(&whitelisted, 1_000, start));
+        }
+        assert!(!limiter.allow_bytes_at(&other, 1, start));
+
+        // a new window starts after one second
+        assert!(limiter.allow_bytes_at(&other, 100, start + RATE_WINDOW));
+
+        // the disconnected peers are forgotten
+        limiter.update_connected(&HashMap::new());
+        assert!(limiter.peers.read().is_empty());
+    }
+}
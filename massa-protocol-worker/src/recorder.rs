@@ -0,0 +1,349 @@
+//! Recording of the messages received from the network, and reading of the recordings
+//! to replay them against the protocol handlers.
+//!
+//! A recording is a sequence of entries made of the reception timestamp (varint milliseconds),
+//! the peer id, the length of the message (varint) and the raw message as passed to `MessagesHandler`.
+//! The recording file is rotated when it reaches its maximum size.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    ops::Bound::Included,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use crossbeam::channel::{bounded, Receiver, Sender, TrySendError};
+use massa_protocol_exports::{PeerId, PeerIdDeserializer, PeerIdSerializer, ProtocolError};
+use massa_serialization::{
+    DeserializeError, Deserializer, Serializer, U64VarIntDeserializer, U64VarIntSerializer,
+};
+use massa_time::MassaTime;
+use peernet::messages::MessagesHandler as PeerNetMessagesHandler;
+use tracing::warn;
+
+/// Number of entries waiting to be written before new messages are no longer recorded
+const RECORDER_QUEUE_CAPACITY: usize = 10_000;
+
+enum RecorderCommand {
+    /// serialized entry to append to the recording
+    Entry(Vec<u8>),
+    /// write all the previous entries to the file then answer
+    Flush(Sender<()>),
+}
+
+/// Appends the received messages to a recording file.
+/// Entries are written by a background thread so that message handling never waits for the disk:
+/// when the thread lags behind, messages are not recorded and their number is logged.
+#[derive(Clone)]
+pub struct MessageRecorder {
+    sender: Sender<RecorderCommand>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl MessageRecorder {
+    /// Create a recorder writing to `path`, truncating the file and removing its rotated files if they exist.
+    /// When the file exceeds `max_file_size` bytes it is renamed `<path>.1`, the previous `<path>.1` becomes
+    /// `<path>.2` and so on, keeping at most `rotated_files` of them.
+    pub fn create(
+        path: &Path,
+        max_file_size: u64,
+        rotated_files: u32,
+    ) -> Result<Self, ProtocolError> {
+        let mut index = 1;
+        while rotated_path(path, index).exists() {
+            std::fs::remove_file(rotated_path(path, index))?;
+            index += 1;
+        }
+        let writer = RecordWriter {
+            path: path.to_path_buf(),
+            max_file_size,
+            rotated_files,
+            writer: Some(BufWriter::new(File::create(path)?)),
+            written: 0,
+        };
+        let (sender, receiver) = bounded(RECORDER_QUEUE_CAPACITY);
+        let dropped = Arc::new(AtomicU64::new(0));
+        let thread_dropped = dropped.clone();
+        std::thread::Builder::new()
+            .name("protocol-message-recorder".to_string())
+            .spawn(move || writer.run(receiver, thread_dropped))?;
+        Ok(MessageRecorder { sender, dropped })
+    }
+
+    /// Record a message received from `peer_id`. Failures are logged and do not affect message handling.
+    pub fn record(&self, peer_id: &PeerId, data: &[u8]) {
+        let mut entry = Vec::with_capacity(data.len() + 64);
+        let u64_serializer = U64VarIntSerializer::new();
+        let res = MassaTime::now()
+            .map_err(|err| err.to_string())
+            .and_then(|now| {
+                u64_serializer
+                    .serialize(&now.to_millis(), &mut entry)
+                    .and_then(|_| PeerIdSerializer::new().serialize(peer_id, &mut entry))
+                    .and_then(|_| u64_serializer.serialize(&(data.len() as u64), &mut entry))
+                    .map_err(|err| err.to_string())
+            });
+        if let Err(err) = res {
+            warn!("could not record message from {}: {}", peer_id, err);
+            return;
+        }
+        entry.extend_from_slice(data);
+        if let Err(TrySendError::Full(_)) = self.sender.try_send(RecorderCommand::Entry(entry)) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Wait until all the messages recorded so far are written to the file
+    pub fn flush(&self) {
+        let (ack_sender, ack_receiver) = bounded(1);
+        if self.sender.send(RecorderCommand::Flush(ack_sender)).is_ok() {
+            let _ = ack_receiver.recv();
+        }
+    }
+}
+
+/// Path of the `index`-th rotated file of the recording at `path`
+fn rotated_path(path: &Path, index: u32) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", index));
+    PathBuf::from(name)
+}
+
+/// Writing side of a `MessageRecorder`, run by its background thread
+struct RecordWriter {
+    path: PathBuf,
+    max_file_size: u64,
+    rotated_files: u32,
+    /// only `None` while the files are rotated
+    writer: Option<BufWriter<File>>,
+    /// bytes written to the current file
+    written: u64,
+}
+
+impl RecordWriter {
+    fn run(mut self, receiver: Receiver<RecorderCommand>, dropped: Arc<AtomicU64>) {
+        // the loop ends when all the recorders are dropped
+        while let Ok(command) = receiver.recv() {
+            let mut next = Some(command);
+            // write all the queued entries before flushing the file
+            while let Some(command) = next.take() {
+                match command {
+                    RecorderCommand::Entry(entry) => {
+                        if let Err(err) = self.write(&entry) {
+                            warn!("could not record message: {}", err);
+                        }
+                    }
+                    RecorderCommand::Flush(ack) => {
+                        if let Err(err) = self.flush() {
+                            warn!("could not flush the message recording: {}", err);
+                        }
+                        let _ = ack.send(());
+                    }
+                }
+                next = receiver.try_recv().ok();
+            }
+            if let Err(err) = self.flush() {
+                warn!("could not flush the message recording: {}", err);
+            }
+            let dropped = dropped.swap(0, Ordering::Relaxed);
+            if dropped > 0 {
+                warn!(
+                    "{} received messages were not recorded because the recorder lagged behind",
+                    dropped
+                );
+            }
+        }
+    }
+
+    fn write(&mut self, entry: &[u8]) -> std::io::Result<()> {
+        if self.written > 0 && self.written + entry.len() as u64 > self.max_file_size {
+            self.rotate()?;
+        }
+        let writer = self.writer.as_mut().ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::Other, "recording file is closed")
+        })?;
+        writer.write_all(entry)?;
+        self.written += entry.len() as u64;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self.writer.as_mut() {
+            Some(writer) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            writer.flush()?;
+        }
+        for index in (1..self.rotated_files).rev() {
+            let from = rotated_path(&self.path, index);
+            if from.exists() {
+                std::fs::rename(from, rotated_path(&self.path, index + 1))?;
+            }
+        }
+        if self.rotated_files > 0 {
+            std::fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        }
+        self.writer = Some(BufWriter::new(File::create(&self.path)?));
+        self.written = 0;
+        Ok(())
+    }
+}
+
+/// Message read from a recording
+#[derive(Debug, Clone)]
+pub struct RecordedMessage {
+    /// time of reception
+    pub timestamp: MassaTime,
+    /// peer that sent the message
+    pub peer_id: PeerId,
+    /// raw message
+    pub data: Vec<u8>,
+}
+
+/// Read all the messages of a recording, starting with its oldest rotated file
+pub fn read_recording(path: &Path) -> Result<Vec<RecordedMessage>, ProtocolError> {
+    let mut files = vec![path.to_path_buf()];
+    let mut index = 1;
+    while rotated_path(path, index).exists() {
+        files.push(rotated_path(path, index));
+        index += 1;
+    }
+    let mut messages = Vec::new();
+    for file in files.iter().rev() {
+        messages.extend(read_recording_file(file)?);
+    }
+    Ok(messages)
+}
+
+/// Read the messages of a single recording file
+fn read_recording_file(path: &Path) -> Result<Vec<RecordedMessage>, ProtocolError> {
+    let content = std::fs::read(path)?;
+    let u64_deserializer = U64VarIntDeserializer::new(Included(0), Included(u64::MAX));
+    let peer_id_deserializer = PeerIdDeserializer::new();
+    let mut buffer = content.as_slice();
+    let mut messages = Vec::new();
+    while !buffer.is_empty() {
+        let (rest, timestamp) = u64_deserializer
+            .deserialize::<DeserializeError>(buffer)
+            .map_err(|err| ProtocolError::GeneralProtocolError(err.to_string()))?;
+        let (rest, peer_id) = peer_id_deserializer
+            .deserialize::<DeserializeError>(rest)
+            .map_err(|err| ProtocolError::GeneralProtocolError(err.to_string()))?;
+        let (rest, len) = u64_deserializer
+            .deserialize::<DeserializeError>(rest)
+            .map_err(|err| ProtocolError::GeneralProtocolError(err.to_string()))?;
+        let len = usize::try_from(len)
+            .ok()
+            .filter(|len| *len <= rest.len())
+            .ok_or_else(|| {
+                ProtocolError::GeneralProtocolError("truncated recording".to_string())
+            })?;
+        messages.push(RecordedMessage {
+            timestamp: MassaTime::from_millis(timestamp),
+            peer_id,
+            data: rest[..len].to_vec(),
+        });
+        buffer = &rest[len..];
+    }
+    Ok(messages)
+}
+
+/// Feed recorded messages to `messages_handler` in their recording order
+pub fn replay_recording<H: PeerNetMessagesHandler<PeerId>>(
+    messages: &[RecordedMessage],
+    messages_handler: &H,
+) -> Result<(), ProtocolError> {
+    for message in messages {
+        messages_handler
+            .handle(&message.data, &message.peer_id)
+            .map_err(|err| ProtocolError::GeneralProtocolError(err.to_string()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_signature::KeyPair;
+    use parking_lot::Mutex;
+    use peernet::error::PeerNetResult;
+    use tempfile::NamedTempFile;
+
+    #[derive(Clone)]
+    struct CollectingHandler(Arc<Mutex<Vec<(PeerId, Vec<u8>)>>>);
+
+    impl PeerNetMessagesHandler<PeerId> for CollectingHandler {
+        fn handle(&self, data: &[u8], peer_id: &PeerId) -> PeerNetResult<()> {
+            self.0.lock().push((peer_id.clone(), data.to_vec()));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let file = NamedTempFile::new().unwrap();
+        let peer_a = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+        let peer_b = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+        let recorder = MessageRecorder::create(file.path(), 1_000_000, 1).unwrap();
+        recorder.record(&peer_a, &[0, 1, 2]);
+        recorder.record(&peer_b, &[]);
+        recorder.record(&peer_a, &[3; 300]);
+        recorder.flush();
+
+        let messages = read_recording(file.path()).unwrap();
+        assert_eq!(messages.len(), 3);
+        assert!(messages[0].timestamp <= messages[2].timestamp);
+
+        let handler = CollectingHandler(Arc::new(Mutex::new(Vec::new())));
+        replay_recording(&messages, &handler).unwrap();
+        assert_eq!(
+            *handler.0.lock(),
+            vec![
+                (peer_a.clone(), vec![0, 1, 2]),
+                (peer_b, vec![]),
+                (peer_a, vec![3; 300])
+            ]
+        );
+    }
+
+    #[test]
+    fn test_record_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("messages.rec");
+        let peer_id = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+        // each entry is larger than half the maximum size so every file holds a single one
+        let recorder = MessageRecorder::create(&path, 100, 2).unwrap();
+        for index in 0..5 {
+            recorder.record(&peer_id, &[index; 60]);
+        }
+        recorder.flush();
+
+        assert!(rotated_path(&path, 2).exists());
+        assert!(!rotated_path(&path, 3).exists());
+        let data: Vec<Vec<u8>> = read_recording(&path)
+            .unwrap()
+            .into_iter()
+            .map(|message| message.data)
+            .collect();
+        assert_eq!(data, vec![vec![2; 60], vec![3; 60], vec![4; 60]]);
+
+        // a new recording does not mix with the rotated files of the previous one
+        let recorder = MessageRecorder::create(&path, 100, 2).unwrap();
+        recorder.record(&peer_id, &[5; 60]);
+        recorder.flush();
+        let data: Vec<Vec<u8>> = read_recording(&path)
+            .unwrap()
+            .into_iter()
+            .map(|message| message.data)
+            .collect();
+        assert_eq!(data, vec![vec![5; 60]]);
+    }
+}
@@ -0,0 +1,27 @@
+//! Adversarial fake peers of the protocol tests, sending through the mock network
+
+use massa_channel::receiver::MassaReceiver;
+use massa_protocol_exports::{PeerId, ProtocolError};
+
+use crate::{
+    byzantine::{ByzantineLink, ByzantinePeer},
+    messages::Message,
+};
+
+use super::mock_network::MockNetworkController;
+
+impl ByzantineLink for MockNetworkController {
+    fn send_raw(&mut self, peer_id: &PeerId, data: &[u8]) -> Result<(), ProtocolError> {
+        self.send_raw_from_peer(peer_id, data)
+    }
+}
+
+/// Create a new byzantine peer connected to the protocol under test.
+/// Returns the receiver of the messages sent to it.
+pub fn connect_byzantine_peer(
+    network_controller: &mut MockNetworkController,
+) -> (ByzantinePeer, MassaReceiver<Message>) {
+    let peer = ByzantinePeer::new();
+    let (_, receiver) = network_controller.create_fake_connection(peer.peer_id.clone());
+    (peer, receiver)
+}
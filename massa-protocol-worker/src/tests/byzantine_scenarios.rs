@@ -0,0 +1,346 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use std::time::Duration;
+
+use massa_consensus_exports::test_exports::{
+    ConsensusEventReceiver, MockConsensusControllerMessage,
+};
+use massa_models::{block_id::BlockId, prehash::PreHashSet, slot::Slot};
+use massa_protocol_exports::PeerId;
+use massa_protocol_exports::{test_exports::tools, ProtocolConfig};
+use massa_signature::KeyPair;
+use massa_time::MassaTime;
+use serial_test::serial;
+
+use crate::{
+    handlers::block_handler::{BlockInfoReply, BlockMessage},
+    messages::Message,
+    wrap_network::ActiveConnectionsTrait,
+};
+
+use super::{byzantine::connect_byzantine_peer, context::protocol_test};
+
+/// Wait for `block_id` to be registered in consensus
+fn header_registered_in_consensus(
+    consensus_event_receiver: &mut ConsensusEventReceiver,
+    block_id: BlockId,
+) -> bool {
+    consensus_event_receiver
+        .wait_command(MassaTime::from_millis(1000), |evt| match evt {
+            MockConsensusControllerMessage::RegisterBlockHeader {
+                block_id: registered_id,
+                header: _,
+            } if registered_id == block_id => Some(()),
+            _ => None,
+        })
+        .is_some()
+}
+
+#[test]
+#[serial]
+fn test_protocol_keeps_handling_blocks_after_message_with_trailing_bytes() {
+    let default_panic = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_panic(info);
+        std::process::exit(1);
+    }));
+
+    let mut protocol_config = ProtocolConfig::default();
+    protocol_config.thread_count = 2;
+    protocol_config.initial_peers = "./src/tests/empty_initial_peers.json".to_string().into();
+    protocol_test(
+        &protocol_config,
+        move |mut network_controller,
+              protocol_controller,
+              protocol_manager,
+              mut consensus_event_receiver,
+              pool_event_receiver,
+              selector_event_receiver| {
+            //1. Create a byzantine node and an honest one
+            let (byzantine, _byzantine_receiver) = connect_byzantine_peer(&mut network_controller);
+            let node_a_keypair = KeyPair::generate(0).unwrap();
+            let (node_a_peer_id, _node_a) = network_controller
+                .create_fake_connection(PeerId::from_public_key(node_a_keypair.get_public_key()));
+
+            //2. The byzantine node sends a valid header followed by garbage
+            let byzantine_block = tools::create_block(&byzantine.keypair);
+            byzantine
+                .send_with_trailing_bytes(
+                    &mut network_controller,
+                    &Message::Block(Box::new(BlockMessage::BlockHeader(
+                        byzantine_block.content.header.clone(),
+                    ))),
+                    &[0xFF; 16],
+                )
+                .unwrap();
+            assert!(!header_registered_in_consensus(
+                &mut consensus_event_receiver,
+                byzantine_block.id
+            ));
+
+            //3. The header of the honest node is still handled
+            let block = tools::create_block(&node_a_keypair);
+            network_controller
+                .send_from_peer(
+                    &node_a_peer_id,
+                    Message::Block(Box::new(BlockMessage::BlockHeader(
+                        block.content.header.clone(),
+                    ))),
+                )
+                .unwrap();
+            assert!(header_registered_in_consensus(
+                &mut consensus_event_receiver,
+                block.id
+            ));
+            (
+                network_controller,
+                protocol_controller,
+                protocol_manager,
+                consensus_event_receiver,
+                pool_event_receiver,
+                selector_event_receiver,
+            )
+        },
+    )
+}
+
+#[test]
+#[serial]
+fn test_protocol_bans_node_sending_header_with_wrong_signature() {
+    let default_panic = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_panic(info);
+        std::process::exit(1);
+    }));
+
+    let mut protocol_config = ProtocolConfig::default();
+    protocol_config.thread_count = 2;
+    protocol_config.initial_peers = "./src/tests/empty_initial_peers.json".to_string().into();
+    protocol_test(
+        &protocol_config,
+        move |mut network_controller,
+              protocol_controller,
+              protocol_manager,
+              mut consensus_event_receiver,
+              pool_event_receiver,
+              selector_event_receiver| {
+            //1. Create a byzantine node
+            let (byzantine, _byzantine_receiver) = connect_byzantine_peer(&mut network_controller);
+
+            //2. Send a header signed over another content
+            let block = tools::create_block(&byzantine.keypair);
+            byzantine
+                .send_header_with_wrong_signature(
+                    &mut network_controller,
+                    block.content.header.clone(),
+                )
+                .unwrap();
+
+            //3. Check that the node is banned and the header is not sent to consensus
+            assert!(!header_registered_in_consensus(
+                &mut consensus_event_receiver,
+                block.id
+            ));
+            assert!(!network_controller
+                .get_connections()
+                .get_peer_ids_connected()
+                .contains(&byzantine.peer_id));
+            (
+                network_controller,
+                protocol_controller,
+                protocol_manager,
+                consensus_event_receiver,
+                pool_event_receiver,
+                selector_event_receiver,
+            )
+        },
+    )
+}
+
+#[test]
+#[serial]
+fn test_protocol_keeps_handling_blocks_during_ask_flood() {
+    let default_panic = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_panic(info);
+        std::process::exit(1);
+    }));
+
+    let mut protocol_config = ProtocolConfig::default();
+    protocol_config.thread_count = 2;
+    protocol_config.initial_peers = "./src/tests/empty_initial_peers.json".to_string().into();
+    protocol_test(
+        &protocol_config,
+        move |mut network_controller,
+              protocol_controller,
+              protocol_manager,
+              mut consensus_event_receiver,
+              pool_event_receiver,
+              selector_event_receiver| {
+            //1. Create a byzantine node and an honest one
+            let (byzantine, byzantine_receiver) = connect_byzantine_peer(&mut network_controller);
+            let node_a_keypair = KeyPair::generate(0).unwrap();
+            let (node_a_peer_id, _node_a) = network_controller
+                .create_fake_connection(PeerId::from_public_key(node_a_keypair.get_public_key()));
+
+            //2. The byzantine node floods asks for unknown blocks
+            byzantine.flood_asks(&mut network_controller, 500).unwrap();
+
+            //3. The header of the honest node is still handled
+            let block = tools::create_block(&node_a_keypair);
+            network_controller
+                .send_from_peer(
+                    &node_a_peer_id,
+                    Message::Block(Box::new(BlockMessage::BlockHeader(
+                        block.content.header.clone(),
+                    ))),
+                )
+                .unwrap();
+            assert!(header_registered_in_consensus(
+                &mut consensus_event_receiver,
+                block.id
+            ));
+
+            //4. The asks of the byzantine node were answered
+            match byzantine_receiver.recv_timeout(Duration::from_millis(1500)) {
+                Ok(Message::Block(message)) => match *message {
+                    BlockMessage::ReplyForBlocks(replies) => {
+                        assert!(matches!(replies[0].1, BlockInfoReply::NotFound))
+                    }
+                    _ => panic!("Unexpected block message sent to the byzantine node"),
+                },
+                _ => panic!("The byzantine node didn't receive a reply to its asks"),
+            }
+            (
+                network_controller,
+                protocol_controller,
+                protocol_manager,
+                consensus_event_receiver,
+                pool_event_receiver,
+                selector_event_receiver,
+            )
+        },
+    )
+}
+
+#[test]
+#[serial]
+fn test_protocol_asks_unknown_parents_to_the_sender_of_a_header() {
+    let default_panic = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_panic(info);
+        std::process::exit(1);
+    }));
+
+    let mut protocol_config = ProtocolConfig::default();
+    protocol_config.thread_count = 2;
+    protocol_config.initial_peers = "./src/tests/empty_initial_peers.json".to_string().into();
+    protocol_test(
+        &protocol_config,
+        move |mut network_controller,
+              protocol_controller,
+              protocol_manager,
+              mut consensus_event_receiver,
+              pool_event_receiver,
+              selector_event_receiver| {
+            //1. Create a byzantine node
+            let (byzantine, byzantine_receiver) = connect_byzantine_peer(&mut network_controller);
+
+            //2. It sends a correctly signed header whose parents do not exist
+            let header = byzantine
+                .send_header_with_unknown_parents(&mut network_controller, Slot::new(1, 1), 2)
+                .unwrap();
+
+            //3. The header is valid on its own: protocol leaves the check of its parents to consensus
+            assert!(header_registered_in_consensus(
+                &mut consensus_event_receiver,
+                header.id
+            ));
+
+            //4. When consensus asks for a parent, it is asked to the byzantine node, which claimed to know it
+            let parent = header.content.parents[0];
+            protocol_controller
+                .send_wishlist_delta(
+                    vec![(parent, None)].into_iter().collect(),
+                    PreHashSet::<BlockId>::default(),
+                )
+                .unwrap();
+            match byzantine_receiver.recv_timeout(Duration::from_millis(1500)) {
+                Ok(Message::Block(message)) => match *message {
+                    BlockMessage::AskForBlocks(asks) => {
+                        assert!(asks.iter().any(|(block_id, _)| *block_id == parent))
+                    }
+                    _ => panic!("Unexpected block message sent to the byzantine node"),
+                },
+                _ => panic!("The parent was not asked to the byzantine node"),
+            }
+
+            //5. The byzantine node is not banned: unknown parents are not a fault on their own
+            assert!(network_controller
+                .get_connections()
+                .get_peer_ids_connected()
+                .contains(&byzantine.peer_id));
+            (
+                network_controller,
+                protocol_controller,
+                protocol_manager,
+                consensus_event_receiver,
+                pool_event_receiver,
+                selector_event_receiver,
+            )
+        },
+    )
+}
+
+#[test]
+#[serial]
+fn test_protocol_drops_oversized_asks() {
+    let default_panic = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_panic(info);
+        std::process::exit(1);
+    }));
+
+    let mut protocol_config = ProtocolConfig::default();
+    protocol_config.thread_count = 2;
+    protocol_config.max_size_block_infos = 10;
+    protocol_config.initial_peers = "./src/tests/empty_initial_peers.json".to_string().into();
+    protocol_test(
+        &protocol_config,
+        move |mut network_controller,
+              protocol_controller,
+              protocol_manager,
+              consensus_event_receiver,
+              pool_event_receiver,
+              selector_event_receiver| {
+            //1. Create a byzantine node
+            let (byzantine, byzantine_receiver) = connect_byzantine_peer(&mut network_controller);
+
+            //2. It asks for more blocks than allowed in a message, then for a single one
+            byzantine
+                .send_oversized_ask(&mut network_controller, 11)
+                .unwrap();
+            byzantine.flood_asks(&mut network_controller, 1).unwrap();
+
+            //3. Only the valid ask is answered
+            match byzantine_receiver.recv_timeout(Duration::from_millis(1500)) {
+                Ok(Message::Block(message)) => match *message {
+                    BlockMessage::ReplyForBlocks(replies) => assert_eq!(replies.len(), 1),
+                    _ => panic!("Unexpected block message sent to the byzantine node"),
+                },
+                _ => panic!("The byzantine node didn't receive a reply to its valid ask"),
+            }
+            assert!(byzantine_receiver
+                .recv_timeout(Duration::from_millis(500))
+                .is_err());
+            (
+                network_controller,
+                protocol_controller,
+                protocol_manager,
+                consensus_event_receiver,
+                pool_event_receiver,
+                selector_event_receiver,
+            )
+        },
+    )
+}
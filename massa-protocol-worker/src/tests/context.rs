@@ -32,7 +32,7 @@ use massa_versioning::versioning::{MipStatsConfig, MipStore};
 use num::rational::Ratio;
 use parking_lot::RwLock;
 use std::ops::Bound::Included;
-use tracing::{debug, log::warn};
+use tracing::{debug, warn};
 
 /// start a new `ProtocolController` from a `ProtocolConfig`
 ///
@@ -96,6 +96,10 @@ pub fn start_protocol_controller_with_mock_network(
         sender_operations: sender_operations.clone(),
         sender_peers: sender_peers.clone(),
         id_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
+        recorder: None,
+        chaos: None,
+        peer_stats: Default::default(),
+        rate_limiter: Default::default(),
     };
 
     let (controller, channels) = create_protocol_controller(config.clone());
@@ -129,6 +133,8 @@ pub fn start_protocol_controller_with_mock_network(
             max_in_connections: 10,
             target_out_connections: 10,
             max_in_connections_per_ip: 10,
+            ban_immune: false,
+            max_bytes_received_per_second: 0,
         },
         config,
         mip_store,
@@ -1,17 +1,19 @@
 use std::{
     collections::{HashMap, HashSet},
     sync::Arc,
+    time::Duration,
 };
 
 use massa_channel::{receiver::MassaReceiver, sender::MassaSender, MassaChannel};
 use massa_protocol_exports::{PeerId, ProtocolError};
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use peernet::{
     messages::{
         MessagesHandler as PeerNetMessagesHandler, MessagesSerializer as PeerNetMessagesSerializer,
     },
     peer::PeerConnectionType,
 };
+use rand::Rng;
 
 use crate::{
     handlers::{
@@ -23,14 +25,69 @@ use crate::{
     wrap_network::{ActiveConnectionsTrait, NetworkController},
 };
 
+/// Conditions applied to the messages exchanged with a fake peer, in both directions
+#[derive(Clone, Debug, Default)]
+pub struct LinkConditions {
+    /// minimal delay before a message is delivered
+    pub latency: Duration,
+    /// maximal random delay added to the latency, messages can be reordered when it is not zero
+    pub jitter: Duration,
+    /// probability for a message to be dropped
+    pub loss_rate: f64,
+    /// probability for a message received from the peer to be handled twice
+    /// (messages sent to the peer are not `Clone` and are never duplicated)
+    pub duplicate_rate: f64,
+}
+
+impl LinkConditions {
+    /// Delays after which each copy of a message must be delivered, empty if the message is lost
+    fn draw_deliveries(&self) -> Vec<Duration> {
+        let mut rng = rand::thread_rng();
+        if rng.gen_bool(self.loss_rate.clamp(0.0, 1.0)) {
+            return Vec::new();
+        }
+        let copies = if rng.gen_bool(self.duplicate_rate.clamp(0.0, 1.0)) {
+            2
+        } else {
+            1
+        };
+        (0..copies)
+            .map(|_| {
+                let jitter_millis = self.jitter.as_millis() as u64;
+                self.latency + Duration::from_millis(rng.gen_range(0..=jitter_millis))
+            })
+            .collect()
+    }
+
+    /// Run `deliver` once per copy of the message, after its delay
+    fn deliver<F: Fn() + Send + Sync + 'static>(&self, deliver: F) {
+        let deliveries = self.draw_deliveries();
+        if deliveries == [Duration::ZERO] {
+            deliver();
+            return;
+        }
+        let deliver = Arc::new(deliver);
+        for delay in deliveries {
+            let deliver = deliver.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(delay);
+                deliver();
+            });
+        }
+    }
+}
+
 pub struct MockActiveConnections {
     pub connections: HashMap<PeerId, MassaSender<Message>>,
+    /// conditions of the links to the fake peers, perfect links if absent
+    pub link_conditions: HashMap<PeerId, LinkConditions>,
 }
 
 impl MockActiveConnections {
     pub fn new() -> Self {
         Self {
             connections: HashMap::new(),
+            link_conditions: HashMap::new(),
         }
     }
 }
@@ -82,12 +139,19 @@ impl ActiveConnectionsTrait for SharedMockActiveConnections {
         message: Message,
         _high_priority: bool,
     ) -> Result<(), massa_protocol_exports::ProtocolError> {
-        let _ = self
-            .read()
-            .connections
+        let connections = self.read();
+        let sender = connections.connections.get(peer_id).unwrap().clone();
+        let message = Mutex::new(Some(message));
+        connections
+            .link_conditions
             .get(peer_id)
-            .unwrap()
-            .try_send(message);
+            .cloned()
+            .unwrap_or_default()
+            .deliver(move || {
+                if let Some(message) = message.lock().take() {
+                    let _ = sender.try_send(message);
+                }
+            });
         Ok(())
     }
 
@@ -148,11 +212,34 @@ impl MockNetworkController {
         self.connections.write().connections.remove(peer_id);
     }
 
-    /// Simulate a peer that send a message to us
+    /// Apply latency, jitter, loss and duplication to the messages exchanged with a fake peer
+    pub fn set_link_conditions(&mut self, peer_id: &PeerId, conditions: LinkConditions) {
+        self.connections
+            .write()
+            .link_conditions
+            .insert(peer_id.clone(), conditions);
+    }
+
+    /// Simulate a peer that send a message to us.
+    /// When conditions are set on the link, the message is handled asynchronously
+    /// and handling errors are not reported.
     pub fn send_from_peer(
         &mut self,
         peer_id: &PeerId,
         message: Message,
+    ) -> Result<(), ProtocolError> {
+        let mut data = Vec::new();
+        self.message_serializer
+            .serialize(&message, &mut data)
+            .map_err(|err| ProtocolError::GeneralProtocolError(err.to_string()))?;
+        self.send_raw_from_peer(peer_id, &data)
+    }
+
+    /// Simulate a peer that send raw bytes to us, which may not be a valid message
+    pub fn send_raw_from_peer(
+        &mut self,
+        peer_id: &PeerId,
+        data: &[u8],
     ) -> Result<(), ProtocolError> {
         let peers_connected: HashSet<PeerId> = self
             .connections
@@ -166,13 +253,27 @@ impl MockNetworkController {
                 "Peer not connected".to_string(),
             ));
         }
-        let mut data = Vec::new();
-        self.message_serializer
-            .serialize(&message, &mut data)
-            .map_err(|err| ProtocolError::GeneralProtocolError(err.to_string()))?;
-        self.messages_handler
-            .handle(&data, peer_id)
-            .map_err(|err| ProtocolError::GeneralProtocolError(err.to_string()))?;
+        let conditions = self
+            .connections
+            .read()
+            .link_conditions
+            .get(peer_id)
+            .cloned();
+        match conditions {
+            Some(conditions) => {
+                let messages_handler = self.messages_handler.clone();
+                let peer_id = peer_id.clone();
+                let data = data.to_vec();
+                conditions.deliver(move || {
+                    let _ = messages_handler.handle(&data, &peer_id);
+                });
+            }
+            None => {
+                self.messages_handler
+                    .handle(data, peer_id)
+                    .map_err(|err| ProtocolError::GeneralProtocolError(err.to_string()))?;
+            }
+        }
         Ok(())
     }
 
@@ -17,6 +17,8 @@ use crate::{create_protocol_controller, start_protocol_controller};
 
 mod ban_nodes_scenarios;
 mod block_scenarios;
+mod byzantine;
+mod byzantine_scenarios;
 mod cache_scenarios;
 mod context;
 mod endorsements_scenarios;
@@ -107,6 +109,8 @@ fn basic() {
             max_in_connections: 1,
             target_out_connections: 1,
             max_in_connections_per_ip: 1,
+            ban_immune: false,
+            max_bytes_received_per_second: 0,
         },
     );
     config1.peers_categories = categories;
@@ -119,6 +123,8 @@ fn basic() {
             max_in_connections: 5,
             target_out_connections: 1,
             max_in_connections_per_ip: 1,
+            ban_immune: false,
+            max_bytes_received_per_second: 0,
         },
     );
     config2.peers_categories = categories2;
@@ -157,6 +163,7 @@ fn basic() {
         channels1,
         mip_store.clone(),
         metrics.clone(),
+        None,
     )
     .expect("Failed to start protocol 1");
     let (mut manager2, _, _) = start_protocol_controller(
@@ -169,6 +176,7 @@ fn basic() {
         channels2,
         mip_store,
         metrics,
+        None,
     )
     .expect("Failed to start protocol 2");
 
@@ -258,6 +266,8 @@ fn stop_with_controller_still_exists() {
             max_in_connections: 1,
             target_out_connections: 1,
             max_in_connections_per_ip: 1,
+            ban_immune: false,
+            max_bytes_received_per_second: 0,
         },
     );
     config1.peers_categories = categories;
@@ -270,6 +280,8 @@ fn stop_with_controller_still_exists() {
             max_in_connections: 5,
             target_out_connections: 1,
             max_in_connections_per_ip: 1,
+            ban_immune: false,
+            max_bytes_received_per_second: 0,
         },
     );
     config2.peers_categories = categories2;
@@ -306,6 +318,7 @@ fn stop_with_controller_still_exists() {
         channels1,
         mip_store.clone(),
         metrics.clone(),
+        None,
     )
     .expect("Failed to start protocol 1");
     let (mut manager2, _, _) = start_protocol_controller(
@@ -318,6 +331,7 @@ fn stop_with_controller_still_exists() {
         channels2,
         mip_store,
         metrics,
+        None,
     )
     .expect("Failed to start protocol 2");
 
@@ -21,6 +21,7 @@ use crate::{
 
 use super::{
     context::{protocol_test, protocol_test_with_storage},
+    mock_network::LinkConditions,
     tools::assert_hash_asked_to_node,
 };
 
@@ -96,6 +97,81 @@ fn test_protocol_sends_valid_operations_it_receives_to_pool() {
     )
 }
 
+#[test]
+#[serial]
+fn test_protocol_sends_operations_received_over_a_slow_link_to_pool() {
+    let default_panic = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_panic(info);
+        std::process::exit(1);
+    }));
+
+    let mut protocol_config = ProtocolConfig::default();
+    protocol_config.thread_count = 2;
+    protocol_config.initial_peers = "./src/tests/empty_initial_peers.json".to_string().into();
+    protocol_test(
+        &protocol_config,
+        move |mut network_controller,
+              protocol_controller,
+              protocol_manager,
+              consensus_event_receiver,
+              mut pool_event_receiver,
+              selector_event_receiver| {
+            //1. Create 1 node behind a slow link that duplicates every message
+            let node_a_keypair = KeyPair::generate(0).unwrap();
+            let (node_a_peer_id, _node_a) = network_controller
+                .create_fake_connection(PeerId::from_public_key(node_a_keypair.get_public_key()));
+            network_controller.set_link_conditions(
+                &node_a_peer_id,
+                LinkConditions {
+                    latency: Duration::from_millis(300),
+                    jitter: Duration::from_millis(100),
+                    loss_rate: 0.0,
+                    duplicate_rate: 1.0,
+                },
+            );
+
+            //2. Node A send an op
+            let operation = tools::create_operation_with_expire_period(&node_a_keypair, 1);
+            network_controller
+                .send_from_peer(
+                    &node_a_peer_id,
+                    Message::Operation(OperationMessage::Operations(vec![operation.clone()])),
+                )
+                .unwrap();
+
+            //3. Check protocol sends the operation to pool once the link delay elapsed.
+            let received_operations = match pool_event_receiver.wait_command(
+                MassaTime::from_millis(2000),
+                |evt| match evt {
+                    evt @ MockPoolControllerMessage::AddOperations { .. } => Some(evt),
+                    _ => None,
+                },
+            ) {
+                Some(MockPoolControllerMessage::AddOperations { operations, .. }) => operations,
+                _ => panic!("Unexpected or no protocol pool event."),
+            };
+            assert!(received_operations.get_op_refs().contains(&operation.id));
+
+            //4. The duplicate is already known and is not sent to pool again.
+            assert!(pool_event_receiver
+                .wait_command(MassaTime::from_millis(1000), |evt| match evt {
+                    evt @ MockPoolControllerMessage::AddOperations { .. } => Some(evt),
+                    _ => None,
+                })
+                .is_none());
+            (
+                network_controller,
+                protocol_controller,
+                protocol_manager,
+                consensus_event_receiver,
+                pool_event_receiver,
+                selector_event_receiver,
+            )
+        },
+    )
+}
+
 #[test]
 #[serial]
 fn test_protocol_does_not_send_invalid_operations_it_receives_to_pool() {
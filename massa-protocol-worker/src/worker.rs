@@ -1,11 +1,12 @@
 use massa_channel::{receiver::MassaReceiver, sender::MassaSender, MassaChannel};
 use massa_consensus_exports::ConsensusController;
+use massa_hash::Hash;
 use massa_metrics::MassaMetrics;
 use massa_models::node::NodeId;
 use massa_pool_exports::PoolController;
 use massa_pos_exports::SelectorController;
 use massa_protocol_exports::{
-    BootstrapPeers, PeerData, PeerId, ProtocolConfig, ProtocolController, ProtocolError,
+    BootstrapPeers, HandshakeSigner, PeerId, ProtocolConfig, ProtocolController, ProtocolError,
     ProtocolManager,
 };
 use massa_serialization::U64VarIntDeserializer;
@@ -22,9 +23,11 @@ use peernet::{
     config::{PeerNetCategoryInfo, PeerNetConfiguration},
     network_manager::PeerNetManager,
 };
-use std::{collections::HashMap, fs::read_to_string, ops::Bound::Included, sync::Arc};
-use tracing::{debug, log::warn};
+use std::{fs::read_to_string, ops::Bound::Included, sync::Arc};
+use tracing::{debug, info, warn};
 
+#[cfg(feature = "chaos")]
+use crate::chaos::check_chaos_config;
 use crate::{
     connectivity::{start_connectivity_thread, ConnectivityCommand},
     context::Context,
@@ -43,12 +46,16 @@ use crate::{
             commands_retrieval::OperationHandlerRetrievalCommand,
         },
         peer_handler::{
-            models::{PeerDB, PeerManagementCmd},
+            models::{read_initial_peers, PeerDB, PeerManagementCmd},
+            rotation::KeyRotation,
             MassaHandshake,
         },
     },
     manager::ProtocolManagerImpl,
     messages::MessagesHandler,
+    peer_stats::SharedPeerStats,
+    rate_classes::MessageRateLimiter,
+    recorder::MessageRecorder,
     wrap_network::NetworkControllerImpl,
 };
 
@@ -167,6 +174,16 @@ pub fn create_protocol_controller(
     )
 }
 
+/// Derive the node keypair from `seed`, so that the nodes of a test network keep the same ids across resets
+fn keypair_from_seed(seed: &str) -> Result<KeyPair, ProtocolError> {
+    // version 0 followed by the secret key
+    let mut bytes = vec![0u8];
+    bytes.extend(Hash::compute_from(seed.as_bytes()).to_bytes());
+    KeyPair::from_bytes(&bytes).map_err(|err| {
+        ProtocolError::GeneralProtocolError(format!("could not derive node key from seed: {}", err))
+    })
+}
+
 /// start a new `ProtocolController` from a `ProtocolConfig`
 ///
 /// # Arguments
@@ -174,6 +191,7 @@ pub fn create_protocol_controller(
 /// * `consensus_controller`: interact with consensus module
 /// * `bootstrap_peers`: list of peers to connect to retrieved from the bootstrap
 /// * `storage`: Shared storage to fetch data that are fetch across all modules
+/// * `remote_signer`: remote signing service, signing the handshakes if it holds the node key
 #[allow(clippy::too_many_arguments)]
 pub fn start_protocol_controller(
     config: ProtocolConfig,
@@ -185,6 +203,7 @@ pub fn start_protocol_controller(
     protocol_channels: ProtocolChannels,
     mip_store: MipStore,
     massa_metrics: MassaMetrics,
+    remote_signer: Option<Arc<dyn HandshakeSigner>>,
 ) -> Result<(Box<dyn ProtocolManager>, KeyPair, NodeId), ProtocolError> {
     debug!("starting protocol controller");
     let peer_db = Arc::new(RwLock::new(PeerDB::default()));
@@ -206,6 +225,8 @@ pub fn start_protocol_controller(
         Some(config.max_size_channel_network_to_peer_handler),
     );
 
+    let peer_stats = SharedPeerStats::default();
+
     // Register channels for handlers
     let message_handlers: MessagesHandler = MessagesHandler {
         sender_blocks: sender_blocks.clone(),
@@ -213,10 +234,29 @@ pub fn start_protocol_controller(
         sender_operations: sender_operations.clone(),
         sender_peers: sender_peers.clone(),
         id_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
+        recorder: config
+            .message_record_file
+            .as_ref()
+            .map(|path| {
+                MessageRecorder::create(
+                    path,
+                    config.message_record_max_file_size,
+                    config.message_record_rotated_files,
+                )
+            })
+            .transpose()?,
+        chaos: config.chaos,
+        peer_stats: peer_stats.clone(),
+        rate_limiter: MessageRateLimiter::new(config.message_rate_classes)
+            .with_bandwidth_allowances(&config.peers_categories, &config.default_category_info),
     };
 
-    // try to read node keypair from file, otherwise generate it & write to file. Then derive nodeId
-    let keypair = if std::path::Path::is_file(&config.keypair_file) {
+    // derive the node keypair from the seed if any, otherwise try to read it from file,
+    // otherwise generate it & write to file. Then derive nodeId
+    let keypair = if let Some(seed) = &config.keypair_seed {
+        warn!("the node key is derived from a seed: for test networks only");
+        keypair_from_seed(seed)?
+    } else if std::path::Path::is_file(&config.keypair_file) {
         // file exists: try to load it
         let keypair_bs58_check_encoded = read_to_string(&config.keypair_file).map_err(|err| {
             std::io::Error::new(err.kind(), format!("could not load node key file: {}", err))
@@ -238,19 +278,55 @@ pub fn start_protocol_controller(
         keypair
     };
 
+    // if the node key was rotated, peers are told about it with a record signed by the previous key
+    let key_rotation = match &config.previous_keypair_file {
+        Some(previous_keypair_file) if previous_keypair_file.is_file() => {
+            let previous_keypair_bs58_check_encoded = read_to_string(previous_keypair_file)
+                .map_err(|err| {
+                    std::io::Error::new(
+                        err.kind(),
+                        format!("could not load previous node key file: {}", err),
+                    )
+                })?;
+            let previous_keypair =
+                serde_json::from_slice::<KeyPair>(previous_keypair_bs58_check_encoded.as_bytes())?;
+            if previous_keypair.get_public_key() != keypair.get_public_key() {
+                Some(KeyRotation::new(
+                    &previous_keypair,
+                    &PeerId::from_public_key(keypair.get_public_key()),
+                )?)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    };
+
+    let handshake_signer: Arc<dyn HandshakeSigner> = match remote_signer {
+        Some(remote_signer) if remote_signer.holds(&keypair.get_public_key()) => {
+            info!("the handshakes are signed by the remote signer");
+            remote_signer
+        }
+        _ => Arc::new(keypair.clone()),
+    };
+
     let mut peernet_config = PeerNetConfiguration::default(
-        MassaHandshake::new(peer_db.clone(), config.clone(), message_handlers.clone()),
+        MassaHandshake::new(
+            peer_db.clone(),
+            config.clone(),
+            message_handlers.clone(),
+            key_rotation,
+        ),
         message_handlers.clone(),
         Context {
-            our_keypair: keypair.clone(),
+            our_public_key: keypair.get_public_key(),
+            signer: handshake_signer,
         },
     );
     peernet_config.write_timeout = config.message_timeout.to_duration();
     peernet_config.read_timeout = config.message_timeout.to_duration();
 
-    let initial_peers_infos = serde_json::from_str::<HashMap<PeerId, PeerData>>(
-        &std::fs::read_to_string(&config.initial_peers)?,
-    )?;
+    let initial_peers_infos = read_initial_peers(&config.initial_peers)?;
 
     let initial_peers = if let Some(bootstrap_peers) = bootstrap_peers {
         //TODO: Remove when we will be able to test the bootstrap peer even if someone else found them full
@@ -312,9 +388,24 @@ pub fn start_protocol_controller(
     };
     peernet_config.max_in_connections = config.max_in_connections;
 
-    let network_controller = Box::new(NetworkControllerImpl::new(PeerNetManager::new(
-        peernet_config,
-    )));
+    #[cfg(feature = "chaos")]
+    if let Some(chaos) = &config.chaos {
+        check_chaos_config(chaos)?;
+        warn!("chaos injection is enabled: connections and messages will be disturbed on purpose");
+    }
+    #[cfg(not(feature = "chaos"))]
+    if config.chaos.is_some() {
+        return Err(ProtocolError::GeneralProtocolError(
+            "chaos injection is configured but the node was built without the `chaos` feature"
+                .to_string(),
+        ));
+    }
+    let network_controller = Box::new(NetworkControllerImpl::new(
+        PeerNetManager::new(peernet_config),
+        config.chaos,
+        peer_stats,
+        config.message_rate_classes,
+    ));
 
     let connectivity_thread_handle = start_connectivity_thread(
         PeerId::from_public_key(keypair.get_public_key()),
@@ -3,7 +3,7 @@ use std::{
     net::SocketAddr,
 };
 
-use massa_protocol_exports::{PeerId, ProtocolError};
+use massa_protocol_exports::{ChaosConfig, MessageRateClasses, PeerId, ProtocolError};
 use peernet::{
     network_manager::{PeerNetManager, SharedActiveConnections},
     peer::PeerConnectionType,
@@ -14,8 +14,13 @@ use crate::{
     context::Context,
     handlers::peer_handler::MassaHandshake,
     messages::{Message, MessagesHandler, MessagesSerializer},
+    peer_stats::{CountingActiveConnections, SharedPeerStats},
+    rate_classes::PrioritizedActiveConnections,
 };
 
+#[cfg(feature = "chaos")]
+use crate::chaos::ChaosActiveConnections;
+
 pub trait ActiveConnectionsTrait: Send + Sync {
     fn send_to_peer(
         &self,
@@ -134,19 +139,44 @@ pub trait NetworkController: Send + Sync {
 
 pub struct NetworkControllerImpl {
     peernet_manager: PeerNetManager<PeerId, Context, MassaHandshake, MessagesHandler>,
+    #[cfg_attr(not(feature = "chaos"), allow(dead_code))]
+    chaos: Option<ChaosConfig>,
+    peer_stats: SharedPeerStats,
+    message_rate_classes: MessageRateClasses,
 }
 
 impl NetworkControllerImpl {
     pub fn new(
         peernet_manager: PeerNetManager<PeerId, Context, MassaHandshake, MessagesHandler>,
+        chaos: Option<ChaosConfig>,
+        peer_stats: SharedPeerStats,
+        message_rate_classes: MessageRateClasses,
     ) -> Self {
-        Self { peernet_manager }
+        Self {
+            peernet_manager,
+            chaos,
+            peer_stats,
+            message_rate_classes,
+        }
     }
 }
 
 impl NetworkController for NetworkControllerImpl {
     fn get_active_connections(&self) -> Box<dyn ActiveConnectionsTrait> {
-        Box::new(self.peernet_manager.active_connections.clone())
+        let active_connections = Box::new(self.peernet_manager.active_connections.clone());
+        #[cfg(feature = "chaos")]
+        let active_connections: Box<dyn ActiveConnectionsTrait> = match self.chaos {
+            Some(chaos) => Box::new(ChaosActiveConnections::new(active_connections, chaos)),
+            None => active_connections,
+        };
+        let active_connections = Box::new(PrioritizedActiveConnections::new(
+            active_connections,
+            self.message_rate_classes,
+        ));
+        Box::new(CountingActiveConnections::new(
+            active_connections,
+            self.peer_stats.clone(),
+        ))
     }
 
     fn start_listener(
@@ -177,7 +207,7 @@ impl NetworkController for NetworkControllerImpl {
         //TODO: Change when we support multiple transports
         self.peernet_manager
             .try_connect(TransportType::Tcp, addr, timeout)
-            .map_err(|err| ProtocolError::GeneralProtocolError(err.to_string()))?;
+            .map_err(|err| ProtocolError::ConnectionError(err.to_string()))?;
         Ok(())
     }
 
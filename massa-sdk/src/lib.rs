@@ -18,13 +18,14 @@ use massa_api_exports::page::PagedVecV2;
 use massa_api_exports::ApiRequest;
 use massa_api_exports::{
     address::AddressInfo,
-    block::{BlockInfo, BlockSummary},
+    block::{BlockGraphPage, BlockInfo, BlockSummary},
     datastore::{DatastoreEntryInput, DatastoreEntryOutput},
     endorsement::EndorsementInfo,
     execution::{ExecuteReadOnlyResponse, ReadOnlyBytecodeExecution, ReadOnlyCall},
-    node::NodeStatus,
-    operation::{OperationInfo, OperationInput},
-    TimeInterval,
+    journal::{JournalEvent, JournalFilter},
+    node::{KnownPeer, NodeStatus, NodeStatusSummary},
+    operation::{OperationInfo, OperationInput, PoolOperationsRequest, TransactionInput},
+    GraphIntervalRequest, TimeInterval,
 };
 use massa_models::secure_share::SecureShare;
 use massa_models::{
@@ -40,6 +41,7 @@ use massa_models::{
     operation::{Operation, OperationId},
     output_event::SCOutputEvent,
     prehash::{PreHashMap, PreHashSet},
+    stats::{MemoryStats, PeerStats},
     version::Version,
 };
 use massa_proto_rs::massa::api::v1::massa_service_client::MassaServiceClient;
@@ -130,6 +132,22 @@ impl RpcClient {
             .map_err(|e| to_error_obj(e.to_string()))
     }
 
+    /// Reload the part of the configuration that can change while the node is running
+    pub async fn node_reload_config(&self) -> RpcResult<()> {
+        self.http_client
+            .request("node_reload_config", rpc_params![])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
+    /// Set the log levels per module, from a comma-separated list of `module=level` directives
+    pub async fn node_set_log_filter(&self, directives: String) -> RpcResult<()> {
+        self.http_client
+            .request("node_set_log_filter", rpc_params![directives])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
     /// Sign message with node's key.
     /// Returns the public key that signed the message and the signature.
     pub async fn node_sign_message(&self, message: Vec<u8>) -> RpcResult<PubkeySig> {
@@ -157,6 +175,15 @@ impl RpcClient {
             .map_err(|e| to_error_obj(e.to_string()))
     }
 
+    /// Build a transaction signed with a key of the node wallet, add it to the node pool and propagate it.
+    /// Returns the id of the created operation.
+    pub async fn send_transaction(&self, transaction: TransactionInput) -> RpcResult<OperationId> {
+        self.http_client
+            .request("send_transaction", rpc_params![transaction])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
     /// Return hash-set of staking addresses.
     pub async fn get_staking_addresses(&self) -> RpcResult<PreHashSet<Address>> {
         self.http_client
@@ -201,6 +228,54 @@ impl RpcClient {
             .map_err(|e| to_error_obj(e.to_string()))
     }
 
+    /// Returns the estimated memory used by the caches of the node.
+    pub async fn get_memory_stats(&self) -> RpcResult<MemoryStats> {
+        self.http_client
+            .request("get_memory_stats", rpc_params![])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
+    /// Returns a summary of the node: version, uptime, sync progress, pool size and staking keys count.
+    pub async fn get_node_status(&self) -> RpcResult<NodeStatusSummary> {
+        self.http_client
+            .request("get_node_status", rpc_params![])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
+    /// Returns the bandwidth and message counters of each connected peer.
+    pub async fn get_peers_detail(&self) -> RpcResult<Vec<PeerStats>> {
+        self.http_client
+            .request("get_peers_detail", rpc_params![])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
+    /// Returns the peers known by the node that are not banned.
+    pub async fn node_get_known_peers(&self) -> RpcResult<Vec<KnownPeer>> {
+        self.http_client
+            .request("node_get_known_peers", rpc_params![])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
+    /// Asks the node to test the given peers to add them to its peer database.
+    pub async fn node_add_known_peers(&self, peers: Vec<KnownPeer>) -> RpcResult<()> {
+        self.http_client
+            .request("node_add_known_peers", rpc_params![peers])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
+    /// Returns the events of the event journal of the node that pass the filter.
+    pub async fn get_journal_events(&self, filter: JournalFilter) -> RpcResult<Vec<JournalEvent>> {
+        self.http_client
+            .request("get_journal_events", rpc_params![filter])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
     /// Returns node peers whitelist IP address(es).
     pub async fn node_peers_whitelist(&self) -> RpcResult<Vec<IpAddr>> {
         self.http_client
@@ -324,6 +399,18 @@ impl RpcClient {
             .map_err(|e| to_error_obj(e.to_string()))
     }
 
+    /// Returns the operations waiting in the pool that are sent by an address,
+    /// or that transfer coins or call a smart contract at this address.
+    pub async fn get_pool_operations(
+        &self,
+        request: PoolOperationsRequest,
+    ) -> RpcResult<Vec<OperationInfo>> {
+        self.http_client
+            .request("get_pool_operations", rpc_params![request])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
     /// Returns endorsement(s) information associated to a given list of endorsement(s) ID(s)
     pub async fn get_endorsements(
         &self,
@@ -366,6 +453,18 @@ impl RpcClient {
             .map_err(|e| to_error_obj(e.to_string()))
     }
 
+    /// Get a page of the block graph within the specified slot interval.
+    /// The returned `next_cursor` is to be provided in the next request to get the following page.
+    pub async fn get_graph_interval_paged(
+        &self,
+        request: GraphIntervalRequest,
+    ) -> RpcResult<BlockGraphPage> {
+        self.http_client
+            .request("get_graph_interval_paged", rpc_params![request])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
     /// Get info by addresses
     pub async fn get_addresses(&self, addresses: Vec<Address>) -> RpcResult<Vec<AddressInfo>> {
         self.http_client
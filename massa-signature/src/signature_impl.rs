@@ -1323,6 +1323,39 @@ pub fn verify_signature_batch(
     )
 }
 
+/// Schema of a type whose serde representation is a bs58-check string with a version prefix
+fn string_json_schema(description: &str) -> schemars::schema::Schema {
+    schemars::schema::SchemaObject {
+        instance_type: Some(schemars::schema::InstanceType::String.into()),
+        metadata: Some(Box::new(schemars::schema::Metadata {
+            description: Some(description.to_string()),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+    .into()
+}
+
+impl schemars::JsonSchema for PublicKey {
+    fn schema_name() -> String {
+        "PublicKey".to_string()
+    }
+
+    fn json_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        string_json_schema("Public key, prefixed by P")
+    }
+}
+
+impl schemars::JsonSchema for Signature {
+    fn schema_name() -> String {
+        "Signature".to_string()
+    }
+
+    fn json_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        string_json_schema("Signature, bs58-check encoded")
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
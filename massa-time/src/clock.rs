@@ -0,0 +1,262 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+//! Clocks used by the workers to read the current time and wait for deadlines
+
+use crate::{MassaTime, TimeError};
+use std::convert::TryFrom;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// Maximal real time a waiter of a `VirtualClock` sleeps before checking the virtual time again
+const VIRTUAL_CLOCK_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Source of time of the workers
+pub trait Clock: Send + Sync {
+    /// Current timestamp
+    fn now(&self) -> Result<MassaTime, TimeError>;
+
+    /// Instant at which `time` is (or was) reached, used as deadline when waiting on a channel.
+    /// Callers must check `now()` again when the deadline is reached, as it is only an estimation.
+    fn estimate_instant(&self, time: MassaTime) -> Result<Instant, TimeError>;
+
+    /// Block the current thread until `time` is reached
+    fn sleep_until(&self, time: MassaTime) -> Result<(), TimeError>;
+}
+
+/// Clock reading the system time, shifted by a compensation to correct a known drift
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock {
+    /// milliseconds added to the system time
+    compensation_millis: i64,
+}
+
+impl SystemClock {
+    /// Create a new system clock, `compensation_millis` being added to the system time
+    pub fn new(compensation_millis: i64) -> Self {
+        SystemClock {
+            compensation_millis,
+        }
+    }
+}
+
+impl Clock for SystemClock {
+    /// ```
+    /// # use massa_time::*;
+    /// let clock = SystemClock::new(60_000);
+    /// let drift = clock.now().unwrap().saturating_sub(MassaTime::now().unwrap());
+    /// assert!(drift > MassaTime::from_millis(59_000));
+    /// ```
+    fn now(&self) -> Result<MassaTime, TimeError> {
+        let now = MassaTime::now()?;
+        let compensation = MassaTime::from_millis(self.compensation_millis.unsigned_abs());
+        if self.compensation_millis >= 0 {
+            now.checked_add(compensation)
+        } else {
+            now.checked_sub(compensation)
+        }
+    }
+
+    fn estimate_instant(&self, time: MassaTime) -> Result<Instant, TimeError> {
+        let (cur_timestamp, cur_instant) = (self.now()?, Instant::now());
+        if time >= cur_timestamp {
+            cur_instant.checked_add(time.saturating_sub(cur_timestamp).to_duration())
+        } else {
+            cur_instant.checked_sub(cur_timestamp.saturating_sub(time).to_duration())
+        }
+        .ok_or(TimeError::TimeOverflowError)
+    }
+
+    fn sleep_until(&self, time: MassaTime) -> Result<(), TimeError> {
+        std::thread::sleep(time.saturating_sub(self.now()?).to_duration());
+        Ok(())
+    }
+}
+
+/// A `MonotonicClock` follows the changes of its reference clock at most 1/`SLEW_RATE_DIVISOR`
+/// faster or slower than the monotonic clock: 50ms per second
+const SLEW_RATE_DIVISOR: u32 = 20;
+
+/// Last time read from a `MonotonicClock`
+#[derive(Debug, Clone, Copy)]
+struct MonotonicAnchor {
+    /// time returned at `instant`
+    timestamp: MassaTime,
+    /// instant of the last correction
+    instant: Instant,
+    /// latest time returned, so that the clock never goes back
+    latest: MassaTime,
+}
+
+/// Clock following the monotonic clock, and slewed towards a reference clock (usually the system time).
+/// A step adjustment of the reference time (NTP, manual change) is caught up progressively
+/// instead of shifting the deadlines at once, and the time returned never goes back.
+/// Clones share the same time.
+#[derive(Clone)]
+pub struct MonotonicClock {
+    /// clock whose time is followed progressively
+    reference: Arc<dyn Clock>,
+    /// time at the last correction
+    anchor: Arc<Mutex<MonotonicAnchor>>,
+}
+
+impl MonotonicClock {
+    /// Create a new monotonic clock anchored on the current time of `reference`
+    ///
+    /// ```
+    /// # use massa_time::*;
+    /// # use std::{sync::Arc, time::Duration};
+    /// let clock = MonotonicClock::new(Arc::new(SystemClock::default())).unwrap();
+    /// let deadline = clock.now().unwrap().saturating_add(MassaTime::from_millis(20));
+    /// clock.sleep_until(deadline).unwrap();
+    /// assert!(clock.now().unwrap() >= deadline);
+    ///
+    /// // a step of the reference time is caught up progressively
+    /// let reference = VirtualClock::new(MassaTime::from_millis(1_000_000));
+    /// let clock = MonotonicClock::new(Arc::new(reference.clone())).unwrap();
+    /// let start = clock.now().unwrap();
+    /// reference.advance(MassaTime::from_millis(60_000));
+    /// assert!(clock.now().unwrap() < start.saturating_add(MassaTime::from_millis(1_000)));
+    /// std::thread::sleep(Duration::from_millis(200));
+    /// assert!(clock.now().unwrap() >= start.saturating_add(MassaTime::from_millis(210)));
+    /// ```
+    pub fn new(reference: Arc<dyn Clock>) -> Result<Self, TimeError> {
+        let timestamp = reference.now()?;
+        Ok(MonotonicClock {
+            reference,
+            anchor: Arc::new(Mutex::new(MonotonicAnchor {
+                timestamp,
+                instant: Instant::now(),
+                latest: timestamp,
+            })),
+        })
+    }
+}
+
+impl std::fmt::Debug for MonotonicClock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MonotonicClock")
+            .field("anchor", &self.anchor)
+            .finish()
+    }
+}
+
+impl Clock for MonotonicClock {
+    fn now(&self) -> Result<MassaTime, TimeError> {
+        let reference_now = self.reference.now()?;
+        let mut anchor = self.anchor.lock().expect("monotonic clock lock poisoned");
+        let instant = Instant::now();
+        let elapsed = instant.saturating_duration_since(anchor.instant);
+        let monotonic_now = anchor
+            .timestamp
+            .checked_add(MassaTime::try_from(elapsed)?)?;
+
+        // move towards the reference time, by at most a fraction of the elapsed time
+        let max_correction = MassaTime::try_from(elapsed / SLEW_RATE_DIVISOR)?;
+        let now = if reference_now >= monotonic_now {
+            monotonic_now.saturating_add(std::cmp::min(
+                reference_now.saturating_sub(monotonic_now),
+                max_correction,
+            ))
+        } else {
+            monotonic_now.saturating_sub(std::cmp::min(
+                monotonic_now.saturating_sub(reference_now),
+                max_correction,
+            ))
+        };
+        // the elapsed time keeps accumulating until a correction of at least 1ms is possible
+        if now != monotonic_now {
+            anchor.timestamp = now;
+            anchor.instant = instant;
+        }
+        anchor.latest = std::cmp::max(anchor.latest, now);
+        Ok(anchor.latest)
+    }
+
+    fn estimate_instant(&self, time: MassaTime) -> Result<Instant, TimeError> {
+        let (cur_timestamp, cur_instant) = (self.now()?, Instant::now());
+        if time >= cur_timestamp {
+            cur_instant.checked_add(time.saturating_sub(cur_timestamp).to_duration())
+        } else {
+            cur_instant.checked_sub(cur_timestamp.saturating_sub(time).to_duration())
+        }
+        .ok_or(TimeError::TimeOverflowError)
+    }
+
+    /// The time can be slewed while sleeping: sleep again until `time` is reached
+    fn sleep_until(&self, time: MassaTime) -> Result<(), TimeError> {
+        loop {
+            let now = self.now()?;
+            if now >= time {
+                return Ok(());
+            }
+            std::thread::sleep(time.saturating_sub(now).to_duration());
+        }
+    }
+}
+
+/// Clock that only moves when told to, so that tests do not depend on the real time.
+/// Clones share the same time.
+///
+/// ```
+/// # use massa_time::*;
+/// let clock = VirtualClock::new(MassaTime::from_millis(1_000));
+/// let waiter = clock.clone();
+/// let handle = std::thread::spawn(move || waiter.sleep_until(MassaTime::from_millis(3_000)));
+/// clock.advance(MassaTime::from_millis(2_000));
+/// handle.join().unwrap().unwrap();
+/// assert_eq!(clock.now().unwrap(), MassaTime::from_millis(3_000));
+/// ```
+#[derive(Debug, Clone)]
+pub struct VirtualClock {
+    /// current virtual time and condition notified when it changes
+    time: Arc<(Mutex<MassaTime>, Condvar)>,
+}
+
+impl VirtualClock {
+    /// Create a new virtual clock starting at `start`
+    pub fn new(start: MassaTime) -> Self {
+        VirtualClock {
+            time: Arc::new((Mutex::new(start), Condvar::new())),
+        }
+    }
+
+    /// Move the clock forward by `duration`
+    pub fn advance(&self, duration: MassaTime) {
+        let (time, condvar) = &*self.time;
+        let mut time = time.lock().expect("virtual clock lock poisoned");
+        *time = time.saturating_add(duration);
+        condvar.notify_all();
+    }
+
+    /// Set the clock to `new_time`, which must not be in the past of the clock
+    pub fn set(&self, new_time: MassaTime) {
+        let (time, condvar) = &*self.time;
+        let mut time = time.lock().expect("virtual clock lock poisoned");
+        *time = std::cmp::max(*time, new_time);
+        condvar.notify_all();
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> Result<MassaTime, TimeError> {
+        Ok(*self.time.0.lock().expect("virtual clock lock poisoned"))
+    }
+
+    /// Virtual time can jump at any moment: a deadline that is not reached yet
+    /// is estimated a short poll interval away so that waiters check it again soon.
+    fn estimate_instant(&self, time: MassaTime) -> Result<Instant, TimeError> {
+        if time <= self.now()? {
+            Ok(Instant::now())
+        } else {
+            Ok(Instant::now() + VIRTUAL_CLOCK_POLL_INTERVAL)
+        }
+    }
+
+    fn sleep_until(&self, target: MassaTime) -> Result<(), TimeError> {
+        let (time, condvar) = &*self.time;
+        let mut time = time.lock().expect("virtual clock lock poisoned");
+        while *time < target {
+            time = condvar.wait(time).expect("virtual clock lock poisoned");
+        }
+        Ok(())
+    }
+}
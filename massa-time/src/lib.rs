@@ -4,11 +4,14 @@
 #![warn(unused_crate_dependencies)]
 #![feature(bound_map)]
 
+mod clock;
 mod error;
+pub use clock::{Clock, MonotonicClock, SystemClock, VirtualClock};
 pub use error::TimeError;
 use massa_serialization::{Deserializer, Serializer, U64VarIntDeserializer, U64VarIntSerializer};
 use nom::error::{context, ContextError, ParseError};
 use nom::IResult;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::ops::Bound;
@@ -22,7 +25,7 @@ use time::{Date, OffsetDateTime};
 
 /// Time structure used everywhere.
 /// milliseconds since 01/01/1970.
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, JsonSchema)]
 pub struct MassaTime(u64);
 
 /// Serializer for `MassaTime`
@@ -159,7 +162,8 @@ impl From<MassaTime> for Duration {
 impl FromStr for MassaTime {
     type Err = crate::TimeError;
 
-    /// Conversion from `&str`.
+    /// Conversion from `&str`, either a number of milliseconds or a duration made of
+    /// numbers followed by a unit among `d`, `h`, `m`, `s` and `ms`.
     ///
     /// ```
     /// # use massa_time::*;
@@ -168,11 +172,97 @@ impl FromStr for MassaTime {
     /// let time : MassaTime = MassaTime::from_millis(42);
     ///
     /// assert_eq!(time, MassaTime::from_str(duration).unwrap());
+    /// assert_eq!(MassaTime::from_str("500ms").unwrap(), MassaTime::from_millis(500));
+    /// assert_eq!(MassaTime::from_str("2s").unwrap(), MassaTime::from_millis(2_000));
+    /// assert_eq!(MassaTime::from_str("1h30m").unwrap(), MassaTime::from_millis(5_400_000));
+    /// assert!(MassaTime::from_str("1x").is_err());
+    /// assert!(MassaTime::from_str("s").is_err());
     /// ```
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(MassaTime(
-            u64::from_str(s).map_err(|_| Self::Err::ConversionError)?,
-        ))
+        let s = s.trim();
+        if let Ok(millis) = u64::from_str(s) {
+            return Ok(MassaTime(millis));
+        }
+        if s.is_empty() {
+            return Err(Self::Err::ConversionError);
+        }
+        let mut millis: u64 = 0;
+        let mut rest = s;
+        while !rest.is_empty() {
+            let digits_len = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .ok_or(Self::Err::ConversionError)?;
+            let value =
+                u64::from_str(&rest[..digits_len]).map_err(|_| Self::Err::ConversionError)?;
+            rest = &rest[digits_len..];
+            let unit_len = rest
+                .find(|c: char| c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let unit_millis: u64 = match &rest[..unit_len] {
+                "ms" => 1,
+                "s" => 1_000,
+                "m" => 60_000,
+                "h" => 3_600_000,
+                "d" => 86_400_000,
+                _ => return Err(Self::Err::ConversionError),
+            };
+            rest = &rest[unit_len..];
+            millis = value
+                .checked_mul(unit_millis)
+                .and_then(|value| millis.checked_add(value))
+                .ok_or(Self::Err::TimeOverflowError)?;
+        }
+        Ok(MassaTime(millis))
+    }
+}
+
+impl<'de> Deserialize<'de> for MassaTime {
+    /// Deserializes a number of milliseconds, or a string parsed by `MassaTime::from_str`
+    /// so that durations can be written as `"500ms"` or `"1h30m"` in the configuration files.
+    ///
+    /// ```
+    /// # use massa_time::*;
+    /// # use serde::de::{value::Error, IntoDeserializer};
+    /// # use serde::Deserialize;
+    /// let time = MassaTime::deserialize("2s".into_deserializer() as serde::de::value::StrDeserializer<Error>);
+    /// assert_eq!(time.unwrap(), MassaTime::from_millis(2_000));
+    /// let time = MassaTime::deserialize(42u64.into_deserializer() as serde::de::value::U64Deserializer<Error>);
+    /// assert_eq!(time.unwrap(), MassaTime::from_millis(42));
+    /// ```
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct MassaTimeVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for MassaTimeVisitor {
+            type Value = MassaTime;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a number of milliseconds or a duration like \"1h30m\"")
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, value: u64) -> Result<MassaTime, E> {
+                Ok(MassaTime(value))
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, value: i64) -> Result<MassaTime, E> {
+                u64::try_from(value)
+                    .map(MassaTime)
+                    .map_err(|_| E::custom("negative time"))
+            }
+
+            fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<MassaTime, E> {
+                MassaTime::from_str(value)
+                    .map_err(|_| E::custom(format!("invalid duration: {}", value)))
+            }
+
+            fn visit_newtype_struct<D: serde::Deserializer<'de>>(
+                self,
+                deserializer: D,
+            ) -> Result<MassaTime, D::Error> {
+                deserializer.deserialize_any(self)
+            }
+        }
+
+        deserializer.deserialize_any(MassaTimeVisitor)
     }
 }
 
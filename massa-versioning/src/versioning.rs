@@ -13,8 +13,8 @@ use thiserror::Error;
 use tracing::{debug, warn};
 
 use massa_db_exports::{
-    DBBatch, ShareableMassaDBController, MIP_STORE_PREFIX, MIP_STORE_STATS_PREFIX, STATE_CF,
-    VERSIONING_CF,
+    DBBatch, MassaDBError, ShareableMassaDBController, MIP_STORE_PREFIX, MIP_STORE_STATS_PREFIX,
+    STATE_CF, VERSIONING_CF,
 };
 use massa_models::config::MIP_STORE_STATS_BLOCK_CONSIDERED;
 #[allow(unused_imports)]
@@ -708,13 +708,11 @@ impl MipStore {
         guard.extend_from_db(db)
     }
 
-    pub fn reset_db(&self, db: ShareableMassaDBController) {
-        {
-            let mut guard = db.write();
-            guard.delete_prefix(MIP_STORE_PREFIX, STATE_CF, None);
-            guard.delete_prefix(MIP_STORE_PREFIX, VERSIONING_CF, None);
-            guard.delete_prefix(MIP_STORE_STATS_PREFIX, VERSIONING_CF, None);
-        }
+    pub fn reset_db(&self, db: ShareableMassaDBController) -> Result<(), MassaDBError> {
+        let mut guard = db.write();
+        guard.delete_prefix(MIP_STORE_PREFIX, STATE_CF, None)?;
+        guard.delete_prefix(MIP_STORE_PREFIX, VERSIONING_CF, None)?;
+        guard.delete_prefix(MIP_STORE_STATS_PREFIX, VERSIONING_CF, None)
     }
 }
 
@@ -2478,7 +2476,9 @@ mod test {
 
         let mut guard_db = db.write();
         // FIXME / TODO: no slot hardcoding?
-        guard_db.write_batch(db_batch, db_versioning_batch, Some(Slot::new(3, 0)));
+        guard_db
+            .write_batch(db_batch, db_versioning_batch, Some(Slot::new(3, 0)))
+            .unwrap();
         drop(guard_db);
 
         // Step 4
@@ -22,4 +22,6 @@ pub enum WalletError {
     MissingKeyError(Address),
     /// `MassaCipher` error: {0}
     MassaCipherError(#[from] massa_cipher::CipherError),
+    /// Mnemonic error: {0}
+    MnemonicError(String),
 }
@@ -6,8 +6,9 @@
 #![feature(map_try_insert)]
 
 pub use error::WalletError;
+pub use mnemonic::{derive_keypair, generate_mnemonic, MNEMONIC_WORD_COUNT};
 
-use massa_cipher::{decrypt, encrypt};
+use massa_cipher::{decrypt, encrypt, VERSION as CIPHER_VERSION};
 use massa_hash::Hash;
 use massa_models::address::Address;
 use massa_models::composite::PubkeySig;
@@ -16,9 +17,11 @@ use massa_models::prehash::{PreHashMap, PreHashSet};
 use massa_models::secure_share::SecureShareContent;
 use massa_signature::{KeyPair, PublicKey};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 mod error;
+mod mnemonic;
 
 /// Contains the keypairs created in the wallet.
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -35,15 +38,19 @@ impl Wallet {
     /// Generates a new wallet initialized with the provided file content
     pub fn new(path: PathBuf, password: String) -> Result<Wallet, WalletError> {
         if path.is_file() {
-            let content = &std::fs::read(&path)?[..];
-            let (_version, decrypted_content) = decrypt(&password, content)?;
-            let keys =
-                serde_json::from_slice::<PreHashMap<Address, KeyPair>>(&decrypted_content[..])?;
-            Ok(Wallet {
+            let (version, keys) = Wallet::read_keys(&path, &password)?;
+            let wallet = Wallet {
                 keys,
                 wallet_path: path,
                 password,
-            })
+            };
+            // re-encrypt the files of older cipher versions with the current key derivation,
+            // keeping the original file until the new one is written
+            if version < CIPHER_VERSION {
+                std::fs::copy(&wallet.wallet_path, wallet.backup_path())?;
+                wallet.save()?;
+            }
+            Ok(wallet)
         } else {
             let wallet = Wallet {
                 keys: PreHashMap::default(),
@@ -55,6 +62,23 @@ impl Wallet {
         }
     }
 
+    /// Decrypts the keys of a wallet file without modifying it.
+    /// Returns the cipher version of the file and the keys.
+    pub fn read_keys(
+        path: &Path,
+        password: &str,
+    ) -> Result<(u32, PreHashMap<Address, KeyPair>), WalletError> {
+        let content = std::fs::read(path)?;
+        let (version, decrypted_content) = decrypt(password, &content)?;
+        let keys = serde_json::from_slice::<PreHashMap<Address, KeyPair>>(&decrypted_content)?;
+        Ok((version, keys))
+    }
+
+    /// Path of the copy of the wallet file made before migrating it to the current cipher version
+    pub fn backup_path(&self) -> PathBuf {
+        path_with_suffix(&self.wallet_path, "bak")
+    }
+
     /// Sign arbitrary message with the associated keypair
     /// returns none if the address isn't in the wallet or if an error occurred during the signature
     /// else returns the public key that signed the message and the signature
@@ -91,6 +115,23 @@ impl Wallet {
         Ok(addrs)
     }
 
+    /// Derives the keypairs of indexes `first_index..first_index + count` from a mnemonic phrase
+    /// and adds them to the wallet, returns their addresses.
+    /// The wallet file is updated.
+    pub fn add_keypairs_from_mnemonic(
+        &mut self,
+        phrase: &str,
+        passphrase: &str,
+        first_index: u32,
+        count: u32,
+        keypair_version: u64,
+    ) -> Result<Vec<Address>, WalletError> {
+        let keys = (first_index..first_index.saturating_add(count))
+            .map(|index| derive_keypair(phrase, passphrase, index, keypair_version))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.add_keypairs(keys)
+    }
+
     /// Removes wallet entries given a list of addresses. Missing entries are ignored.
     /// The wallet file is updated.
     pub fn remove_addresses(&mut self, addresses: &Vec<Address>) -> Result<(), WalletError> {
@@ -125,10 +166,17 @@ impl Wallet {
 
     /// Save the wallet in json format in a file
     /// Only the keypair is dumped
+    /// The file is replaced atomically: the content is written to a temporary file which is then renamed
     fn save(&self) -> Result<(), WalletError> {
         let ser_keys = serde_json::to_string(&self.keys)?;
         let encrypted_content = encrypt(&self.password, ser_keys.as_bytes())?;
-        std::fs::write(&self.wallet_path, encrypted_content)?;
+        let tmp_path = path_with_suffix(&self.wallet_path, "tmp");
+        {
+            let mut file = std::fs::File::create(&tmp_path)?;
+            file.write_all(&encrypted_content)?;
+            file.sync_all()?;
+        }
+        std::fs::rename(&tmp_path, &self.wallet_path)?;
         Ok(())
     }
 
@@ -162,6 +210,82 @@ impl std::fmt::Display for Wallet {
     }
 }
 
+/// `path` with `.suffix` appended to its file name
+fn path_with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".");
+    file_name.push(suffix);
+    path.with_file_name(file_name)
+}
+
 /// Test utils
 #[cfg(feature = "testing")]
 pub mod test_exports;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_cipher::{encrypt_legacy, PBKDF2_VERSION};
+
+    const PASSWORD: &str = "wallet password";
+
+    /// Writes a wallet file with the legacy `PBKDF2` key derivation, returns its keys
+    fn write_legacy_wallet(path: &Path) -> PreHashMap<Address, KeyPair> {
+        let keys: PreHashMap<Address, KeyPair> = (0..3)
+            .map(|_| {
+                let keypair = KeyPair::generate(0).unwrap();
+                (Address::from_public_key(&keypair.get_public_key()), keypair)
+            })
+            .collect();
+        let content = serde_json::to_vec(&keys).unwrap();
+        std::fs::write(
+            path,
+            encrypt_legacy(PBKDF2_VERSION, PASSWORD, &content).unwrap(),
+        )
+        .unwrap();
+        keys
+    }
+
+    fn addresses(keys: &PreHashMap<Address, KeyPair>) -> PreHashSet<Address> {
+        keys.keys().copied().collect()
+    }
+
+    #[test]
+    fn test_read_keys_does_not_migrate() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wallet.dat");
+        let keys = write_legacy_wallet(&path);
+        let content = std::fs::read(&path).unwrap();
+
+        let (version, read) = Wallet::read_keys(&path, PASSWORD).unwrap();
+        assert_eq!(version, PBKDF2_VERSION);
+        assert_eq!(addresses(&read), addresses(&keys));
+        assert_eq!(std::fs::read(&path).unwrap(), content);
+        assert!(Wallet::read_keys(&path, "wrong password").is_err());
+    }
+
+    #[test]
+    fn test_migrate_legacy_wallet() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wallet.dat");
+        let keys = write_legacy_wallet(&path);
+        let legacy_content = std::fs::read(&path).unwrap();
+
+        let wallet = Wallet::new(path.clone(), PASSWORD.to_string()).unwrap();
+        assert_eq!(addresses(&wallet.keys), addresses(&keys));
+
+        // the file is re-encrypted with the current cipher version
+        let (version, migrated) = Wallet::read_keys(&path, PASSWORD).unwrap();
+        assert_eq!(version, CIPHER_VERSION);
+        assert_eq!(addresses(&migrated), addresses(&keys));
+
+        // the legacy file is kept as a backup, and no temporary file is left
+        assert_eq!(std::fs::read(wallet.backup_path()).unwrap(), legacy_content);
+        assert!(!path_with_suffix(&path, "tmp").exists());
+
+        // an up-to-date file is not migrated again
+        std::fs::remove_file(wallet.backup_path()).unwrap();
+        Wallet::new(path, PASSWORD.to_string()).unwrap();
+        assert!(!wallet.backup_path().exists());
+    }
+}
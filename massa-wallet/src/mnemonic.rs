@@ -0,0 +1,141 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+//! BIP39 mnemonic phrases and SLIP-0010 hierarchical derivation of the wallet keypairs
+//!
+//! Keys derived from a phrase follow the hardened path `m/44'/632'/0'/0'/index'`,
+//! so that the whole wallet can be restored from the phrase alone.
+
+use crate::WalletError;
+use bip39::{Language, Mnemonic};
+use hmac::{Hmac, Mac};
+use massa_serialization::{Serializer, U64VarIntSerializer};
+use massa_signature::KeyPair;
+use sha2::Sha512;
+
+/// Number of words of the generated mnemonic phrases
+pub const MNEMONIC_WORD_COUNT: usize = 24;
+
+/// SLIP-0044 coin type of Massa
+const MASSA_COIN_TYPE: u32 = 632;
+
+/// Offset of the hardened child indexes
+const HARDENED_OFFSET: u32 = 0x8000_0000;
+
+/// Key of the HMAC producing the SLIP-0010 ed25519 master key
+const ED25519_SEED_KEY: &[u8] = b"ed25519 seed";
+
+type HmacSha512 = Hmac<Sha512>;
+
+/// Extended private key: secret key and chain code
+struct ExtendedKey {
+    secret: [u8; 32],
+    chain_code: [u8; 32],
+}
+
+impl ExtendedKey {
+    /// Split a HMAC-SHA512 output into secret key and chain code
+    fn from_hmac(mac: HmacSha512) -> Self {
+        let output = mac.finalize().into_bytes();
+        let mut secret = [0u8; 32];
+        let mut chain_code = [0u8; 32];
+        secret.copy_from_slice(&output[..32]);
+        chain_code.copy_from_slice(&output[32..]);
+        ExtendedKey { secret, chain_code }
+    }
+
+    /// Master key of a seed
+    fn master(seed: &[u8]) -> Self {
+        let mut mac =
+            HmacSha512::new_from_slice(ED25519_SEED_KEY).expect("HMAC accepts keys of any size");
+        mac.update(seed);
+        Self::from_hmac(mac)
+    }
+
+    /// Hardened child of the key, ed25519 only supporting hardened derivation
+    fn derive_hardened(&self, index: u32) -> Self {
+        let mut mac =
+            HmacSha512::new_from_slice(&self.chain_code).expect("HMAC accepts keys of any size");
+        mac.update(&[0u8]);
+        mac.update(&self.secret);
+        mac.update(&(index | HARDENED_OFFSET).to_be_bytes());
+        Self::from_hmac(mac)
+    }
+}
+
+/// Generate a new random mnemonic phrase of `MNEMONIC_WORD_COUNT` english words
+pub fn generate_mnemonic() -> Result<String, WalletError> {
+    let mnemonic = Mnemonic::generate_in(Language::English, MNEMONIC_WORD_COUNT)
+        .map_err(|err| WalletError::MnemonicError(err.to_string()))?;
+    Ok(mnemonic.to_string())
+}
+
+/// Derive the keypair of index `index` from a mnemonic phrase and an optional passphrase
+///
+/// The phrase checksum is verified, and the keypair is created with the given keypair version.
+pub fn derive_keypair(
+    phrase: &str,
+    passphrase: &str,
+    index: u32,
+    keypair_version: u64,
+) -> Result<KeyPair, WalletError> {
+    let mnemonic = Mnemonic::parse_in_normalized(Language::English, phrase)
+        .map_err(|err| WalletError::MnemonicError(err.to_string()))?;
+    let seed = mnemonic.to_seed_normalized(passphrase);
+    let key = derive_path(&seed, &[44, MASSA_COIN_TYPE, 0, 0, index]);
+
+    let mut bytes = Vec::with_capacity(key.secret.len() + 1);
+    U64VarIntSerializer::new()
+        .serialize(&keypair_version, &mut bytes)
+        .map_err(|err| WalletError::MnemonicError(err.to_string()))?;
+    bytes.extend_from_slice(&key.secret);
+    KeyPair::from_bytes(&bytes).map_err(|err| WalletError::MnemonicError(err.to_string()))
+}
+
+/// Derive the key at the given hardened path from a seed
+fn derive_path(seed: &[u8], path: &[u32]) -> ExtendedKey {
+    path.iter().fold(ExtendedKey::master(seed), |key, index| {
+        key.derive_hardened(*index)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slip10_ed25519_vector() {
+        // test vector 1 of SLIP-0010 for ed25519
+        let seed = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
+            0x0e, 0x0f,
+        ];
+        let master = derive_path(&seed, &[]);
+        assert_eq!(
+            master.secret.to_vec(),
+            hex_bytes("2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7")
+        );
+        let child = derive_path(&seed, &[0]);
+        assert_eq!(
+            child.secret.to_vec(),
+            hex_bytes("68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3")
+        );
+    }
+
+    #[test]
+    fn test_mnemonic_restore() {
+        let phrase = generate_mnemonic().unwrap();
+        assert_eq!(phrase.split_whitespace().count(), MNEMONIC_WORD_COUNT);
+        let first = derive_keypair(&phrase, "", 0, 0).unwrap();
+        let restored = derive_keypair(&phrase, "", 0, 0).unwrap();
+        let second = derive_keypair(&phrase, "", 1, 0).unwrap();
+        assert_eq!(first.to_string(), restored.to_string());
+        assert_ne!(first.to_string(), second.to_string());
+        assert!(derive_keypair("not a valid phrase", "", 0, 0).is_err());
+    }
+
+    fn hex_bytes(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+}